@@ -0,0 +1,107 @@
+//! Baseline timings of the core kernels at several sizes.
+//!
+//! - `dense_linear`: const-generic dense forward pass, square layers.
+//! - `activation_layers`: element-wise activations over arrays.
+//! - `loss`: forward value and derivative of the vector losses.
+//! - `train_step`: one SGD step (forward, backward and update) of a `Sequential` model.
+//! - `dense_backend`: runtime-shaped `Dense` forward pass on each compute backend, one
+//!   sample at a time and as a batched matrix product.
+
+use std::hint::black_box;
+use criterion::measurement::WallTime;
+use criterion::{criterion_group, criterion_main, BenchmarkGroup, BenchmarkId, Criterion, Throughput};
+use neuralnet::activation_fn::{relu_layer, sigmoid_layer, softplus_layer, tanh_layer};
+use neuralnet::forward_propagation::dense_linear;
+use neuralnet::backend::{Naive, Unrolled};
+use neuralnet::loss_fn::Loss;
+use neuralnet::model::{Dense, ModelBuilder};
+use neuralnet_benches::{one_hot, probabilities, uniform_array, uniform_layer, uniform_vec};
+
+fn bench_dense_size<const N: usize>(group: &mut BenchmarkGroup<'_, WallTime>) {
+    let layer = uniform_layer::<N, N>(1);
+    let inputs = uniform_array::<N>(2);
+    group.throughput(Throughput::Elements((N * N) as u64));
+    group.bench_function(BenchmarkId::from_parameter(N), |b| b.iter(|| dense_linear(black_box(&inputs), black_box(&layer))));
+}
+
+fn dense_linear_benches(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dense_linear");
+    bench_dense_size::<16>(&mut group);
+    bench_dense_size::<64>(&mut group);
+    bench_dense_size::<256>(&mut group);
+    group.finish();
+}
+
+fn bench_activations_size<const N: usize>(group: &mut BenchmarkGroup<'_, WallTime>) {
+    let inputs = uniform_array::<N>(3);
+    group.throughput(Throughput::Elements(N as u64));
+    group.bench_with_input(BenchmarkId::new("relu", N), &inputs, |b, x| b.iter(|| relu_layer(black_box(x))));
+    group.bench_with_input(BenchmarkId::new("sigmoid", N), &inputs, |b, x| b.iter(|| sigmoid_layer(black_box(x))));
+    group.bench_with_input(BenchmarkId::new("tanh", N), &inputs, |b, x| b.iter(|| tanh_layer(black_box(x))));
+    group.bench_with_input(BenchmarkId::new("softplus", N), &inputs, |b, x| b.iter(|| softplus_layer(black_box(x))));
+}
+
+fn activation_benches(c: &mut Criterion) {
+    let mut group = c.benchmark_group("activation_layers");
+    bench_activations_size::<64>(&mut group);
+    bench_activations_size::<1024>(&mut group);
+    group.finish();
+}
+
+fn loss_benches(c: &mut Criterion) {
+    let mut group = c.benchmark_group("loss");
+    for n in [10, 100, 1000] {
+        let predictions = uniform_vec(n, 4);
+        let targets = uniform_vec(n, 5);
+        let distribution = probabilities(n, 6);
+        let label = one_hot(n, n / 2);
+        group.throughput(Throughput::Elements(n as u64));
+        for (name, loss, p, t) in [
+            ("mse", Loss::MeanSquaredError, &predictions, &targets),
+            ("cross_entropy", Loss::CrossEntropy, &distribution, &label),
+        ] {
+            group.bench_with_input(BenchmarkId::new(format!("{}_forward", name), n), &(p, t), |b, (p, t)| {
+                b.iter(|| loss.forward(black_box(p), black_box(t)))
+            });
+            group.bench_with_input(BenchmarkId::new(format!("{}_derivative", name), n), &(p, t), |b, (p, t)| {
+                b.iter(|| loss.derivative(black_box(p), black_box(t)))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn train_step_benches(c: &mut Criterion) {
+    const INPUTS: usize = 32;
+    const CLASSES: usize = 10;
+    let mut group = c.benchmark_group("train_step");
+    let inputs = uniform_vec(INPUTS, 7);
+    let target = one_hot(CLASSES, 3);
+    for hidden in [16, 64, 256] {
+        let mut model = ModelBuilder::new(INPUTS).seed(8).dense(hidden).relu().dense(CLASSES).softmax().build::<f64>().unwrap();
+        group.throughput(Throughput::Elements(model.n_parameters() as u64));
+        group.bench_function(BenchmarkId::from_parameter(hidden), |b| {
+            b.iter(|| model.train_step(black_box(&inputs), black_box(&target), Loss::CrossEntropy, 1e-3))
+        });
+    }
+    group.finish();
+}
+
+fn dense_backend_benches(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dense_backend");
+    for n in [64, 256, 1024] {
+        let layer = Dense::new((0..n as u64).map(|seed| uniform_vec(n, seed)).collect(), uniform_vec(n, 9));
+        let inputs = uniform_vec(n, 10);
+        let mut out = Vec::with_capacity(n);
+        group.throughput(Throughput::Elements((n * n) as u64));
+        group.bench_function(BenchmarkId::new("naive", n), |b| b.iter(|| layer.forward_on(&Naive, black_box(&inputs), &mut out)));
+        group.bench_function(BenchmarkId::new("unrolled", n), |b| b.iter(|| layer.forward_on(&Unrolled, black_box(&inputs), &mut out)));
+        let batch: Vec<Vec<f64>> = (0..32).map(|seed| uniform_vec(n, 100 + seed)).collect();
+        group.throughput(Throughput::Elements((32 * n * n) as u64));
+        group.bench_function(BenchmarkId::new("unrolled_batch_32", n), |b| b.iter(|| layer.forward_batch_on(&Unrolled, black_box(&batch))));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, dense_linear_benches, activation_benches, loss_benches, train_step_benches, dense_backend_benches);
+criterion_main!(benches);
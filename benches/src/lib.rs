@@ -0,0 +1,38 @@
+//! Seeded inputs shared by the benchmarks, so every run measures the same data.
+
+use neuralnet::layers::Layer1D;
+use neuralnet::random::Rng;
+
+/// `n` values drawn uniformly from `[-1, 1)`.
+pub fn uniform_vec(n: usize, seed: u64) -> Vec<f64> {
+    let mut rng = Rng::new(seed);
+    (0..n).map(|_| rng.next_f64() * 2.0 - 1.0).collect()
+}
+
+/// `N` values drawn uniformly from `[-1, 1)`.
+pub fn uniform_array<const N: usize>(seed: u64) -> [f32; N] {
+    let mut rng = Rng::new(seed);
+    std::array::from_fn(|_| (rng.next_f64() * 2.0 - 1.0) as f32)
+}
+
+/// A const-generic dense layer with uniform weights and biases in `[-1, 1)`.
+pub fn uniform_layer<const OUT: usize, const IN: usize>(seed: u64) -> Layer1D<f32, OUT, IN> {
+    let mut rng = Rng::new(seed);
+    let mut next = || (rng.next_f64() * 2.0 - 1.0) as f32;
+    let weights = std::array::from_fn(|_| std::array::from_fn(|_| next()));
+    let biases = std::array::from_fn(|_| next());
+    Layer1D::new(weights, biases)
+}
+
+/// A random probability distribution over `n` classes.
+pub fn probabilities(n: usize, seed: u64) -> Vec<f64> {
+    let mut rng = Rng::new(seed);
+    let weights: Vec<f64> = (0..n).map(|_| rng.next_f64() + 1e-3).collect();
+    let total: f64 = weights.iter().sum();
+    weights.into_iter().map(|w| w / total).collect()
+}
+
+/// One-hot vector of length `n` with a one at `class`.
+pub fn one_hot(n: usize, class: usize) -> Vec<f64> {
+    (0..n).map(|k| if k == class { 1.0 } else { 0.0 }).collect()
+}
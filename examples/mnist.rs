@@ -0,0 +1,98 @@
+//! Handwritten digit classification on MNIST with mini-batch training.
+//!
+//! Pass the directory holding the four IDX files from the MNIST site
+//! (`train-images-idx3-ubyte.gz`, `train-labels-idx1-ubyte.gz`, `t10k-images-idx3-ubyte.gz`,
+//! `t10k-labels-idx1-ubyte.gz`, compressed or not). Without it the example falls back to
+//! a small synthetic set of 28x28 stroke "digits", so it also runs offline.
+//!
+//! Run with `cargo run --release --example mnist -- <mnist-dir>`.
+
+use std::path::Path;
+use neuralnet::data_handling::{read_idx_images, read_idx_labels};
+use neuralnet::loss_fn::Loss;
+use neuralnet::metrics::{accuracy, argmax, confusion_matrix};
+use neuralnet::model::ModelBuilder;
+use neuralnet::random::Rng;
+use neuralnet::training::Trainer;
+
+const SIDE: usize = 28;
+const CLASSES: usize = 10;
+
+type Split = (Vec<Vec<f64>>, Vec<usize>);
+
+fn read_split(dir: &Path, prefix: &str) -> Result<Split, Box<dyn std::error::Error>> {
+    let find = |name: String| {
+        let gz = dir.join(format!("{}.gz", name));
+        if gz.exists() { gz } else { dir.join(name) }
+    };
+    let images = read_idx_images::<f64, _>(find(format!("{}-images-idx3-ubyte", prefix)))?;
+    let labels = read_idx_labels(find(format!("{}-labels-idx1-ubyte", prefix)))?;
+    if (images.rows, images.cols) != (SIDE, SIDE) || images.images.len() != labels.len() {
+        return Err(format!("{}: unexpected image size or label count", prefix).into());
+    }
+    Ok((images.images, labels))
+}
+
+/// Ten kinds of strokes (rows, columns, diagonals and boxes) with random offsets and noise.
+fn synthetic(n: usize, rng: &mut Rng) -> Split {
+    let labels: Vec<usize> = (0..n).map(|i| i % CLASSES).collect();
+    let images = labels.iter().map(|&class| {
+        let mut image = vec![0.0; SIDE * SIDE];
+        let offset = 4 + rng.gen_index(SIDE - 8);
+        for i in 4..SIDE - 4 {
+            let points = match class {
+                0 => vec![(offset, i)],
+                1 => vec![(i, offset)],
+                2 => vec![(i, i)],
+                3 => vec![(i, SIDE - 1 - i)],
+                4 => vec![(offset, i), (i, offset)],
+                5 => vec![(i, i), (i, SIDE - 1 - i)],
+                6 => vec![(4, i), (SIDE - 5, i)],
+                7 => vec![(i, 4), (i, SIDE - 5)],
+                8 => vec![(4, i), (SIDE - 5, i), (i, 4), (i, SIDE - 5)],
+                _ => vec![(offset, i), (i, i)],
+            };
+            for (r, c) in points {
+                image[r * SIDE + c] = 1.0;
+            }
+        }
+        for pixel in image.iter_mut() {
+            *pixel = (*pixel + 0.2 * rng.next_normal()).clamp(0.0, 1.0);
+        }
+        image
+    }).collect();
+    (images, labels)
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let ((train_x, train_y), (test_x, test_y), epochs) = match std::env::args().nth(1) {
+        Some(dir) => (read_split(Path::new(&dir), "train")?, read_split(Path::new(&dir), "t10k")?, 5),
+        None => {
+            println!("no MNIST directory given, using synthetic digits");
+            let mut rng = Rng::new(42);
+            (synthetic(2000, &mut rng), synthetic(500, &mut rng), 10)
+        }
+    };
+    println!("{} training and {} test images", train_x.len(), test_x.len());
+    let targets: Vec<Vec<f64>> = train_y.iter()
+        .map(|&y| (0..CLASSES).map(|k| if k == y { 1.0 } else { 0.0 }).collect())
+        .collect();
+
+    let mut model = ModelBuilder::new(SIDE * SIDE).dense(128).relu().dense(CLASSES).softmax().seed(1).build::<f64>()?;
+    let trainer = Trainer::new(Loss::CrossEntropy, 0.1, epochs).batch_size(32).shuffle(0);
+    let history = trainer.fit_with(&mut model, &train_x, &targets, |m| {
+        let predictions: Vec<usize> = m.predict(&test_x).iter().map(|p| argmax(p)).collect();
+        vec![("test_accuracy".to_string(), accuracy(&predictions, &test_y))]
+    });
+    for record in &history.records {
+        println!("epoch {:>2}: loss {:.4}, test accuracy {:.3}", record.epoch, record.train_loss, record.metrics["test_accuracy"]);
+    }
+
+    let predictions: Vec<usize> = model.predict(&test_x).iter().map(|p| argmax(p)).collect();
+    println!("confusion matrix (rows: true digit, columns: predicted):");
+    for (digit, row) in confusion_matrix(&predictions, &test_y, CLASSES).iter().enumerate() {
+        let counts: Vec<String> = row.iter().map(|n| format!("{:>5}", n)).collect();
+        println!("{}: {}", digit, counts.join(""));
+    }
+    Ok(())
+}
@@ -0,0 +1,211 @@
+//! Handwritten digit classification on MNIST with a convolutional feature extractor.
+//!
+//! A bank of 3x3 filters (`Layer2D`) slides over every image: each 3x3 patch is run
+//! through `dense_conv2d`, followed by ReLU and 2x2 max pooling. The pooled feature maps
+//! feed a `Sequential` softmax head. Both parts are trained together with mini-batch
+//! SGD: the head through `backward_pass_with`, the filters through `Layer::backward` on
+//! every patch that received a gradient.
+//!
+//! Takes the same arguments as the `mnist` example (a directory with the four IDX files,
+//! compressed or not) and likewise falls back to synthetic stroke "digits" without one.
+//!
+//! Run with `cargo run --release --example mnist_conv -- <mnist-dir>`.
+
+use std::path::Path;
+use neuralnet::back_propagation::backward_pass_with;
+use neuralnet::data_handling::{read_idx_images, read_idx_labels};
+use neuralnet::forward_propagation::dense_conv2d;
+use neuralnet::layers::{Layer, Layer2D};
+use neuralnet::loss_fn::Loss;
+use neuralnet::metrics::{accuracy, argmax};
+use neuralnet::model::{ModelBuilder, Sequential};
+use neuralnet::random::Rng;
+
+const SIDE: usize = 28;
+const CLASSES: usize = 10;
+const FILTERS: usize = 8;
+const KERNEL: usize = 3;
+/// Pixels in one 3x3 patch, the `FILTER_SIZE` of the filter bank.
+const PATCH: usize = KERNEL * KERNEL;
+/// Side of a feature map after the valid 3x3 convolution.
+const CONV_SIDE: usize = SIDE - KERNEL + 1;
+/// Side of a feature map after 2x2 max pooling.
+const POOL_SIDE: usize = CONV_SIDE / 2;
+const FEATURES: usize = FILTERS * POOL_SIDE * POOL_SIDE;
+
+type Split = (Vec<Vec<f64>>, Vec<usize>);
+
+fn read_split(dir: &Path, prefix: &str, limit: usize) -> Result<Split, Box<dyn std::error::Error>> {
+    let find = |name: String| {
+        let gz = dir.join(format!("{}.gz", name));
+        if gz.exists() { gz } else { dir.join(name) }
+    };
+    let mut images = read_idx_images::<f64, _>(find(format!("{}-images-idx3-ubyte", prefix)))?;
+    let mut labels = read_idx_labels(find(format!("{}-labels-idx1-ubyte", prefix)))?;
+    if (images.rows, images.cols) != (SIDE, SIDE) || images.images.len() != labels.len() {
+        return Err(format!("{}: unexpected image size or label count", prefix).into());
+    }
+    images.images.truncate(limit);
+    labels.truncate(limit);
+    Ok((images.images, labels))
+}
+
+/// Ten kinds of strokes (rows, columns, diagonals and boxes) with random offsets and noise.
+fn synthetic(n: usize, rng: &mut Rng) -> Split {
+    let labels: Vec<usize> = (0..n).map(|i| i % CLASSES).collect();
+    let images = labels.iter().map(|&class| {
+        let mut image = vec![0.0; SIDE * SIDE];
+        let offset = 4 + rng.gen_index(SIDE - 8);
+        for i in 4..SIDE - 4 {
+            let points = match class {
+                0 => vec![(offset, i)],
+                1 => vec![(i, offset)],
+                2 => vec![(i, i)],
+                3 => vec![(i, SIDE - 1 - i)],
+                4 => vec![(offset, i), (i, offset)],
+                5 => vec![(i, i), (i, SIDE - 1 - i)],
+                6 => vec![(4, i), (SIDE - 5, i)],
+                7 => vec![(i, 4), (i, SIDE - 5)],
+                8 => vec![(4, i), (SIDE - 5, i), (i, 4), (i, SIDE - 5)],
+                _ => vec![(offset, i), (i, i)],
+            };
+            for (r, c) in points {
+                image[r * SIDE + c] = 1.0;
+            }
+        }
+        for pixel in image.iter_mut() {
+            *pixel = (*pixel + 0.2 * rng.next_normal()).clamp(0.0, 1.0);
+        }
+        image
+    }).collect();
+    (images, labels)
+}
+
+/// The 3x3 patch of `image` whose top-left pixel is `(r, c)`.
+fn patch(image: &[f64], r: usize, c: usize) -> [f64; PATCH] {
+    std::array::from_fn(|k| image[(r + k / KERNEL) * SIDE + c + k % KERNEL])
+}
+
+/// Pooled feature maps of one image, filter-major, together with the conv position
+/// `(r, c)` each pooled value came from (for routing gradients back).
+struct Features {
+    values: Vec<f64>,
+    sources: Vec<(usize, usize)>,
+}
+
+/// Convolution, ReLU and 2x2 max pooling.
+fn extract(conv: &Layer2D<f64, FILTERS, PATCH>, image: &[f64]) -> Features {
+    let mut maps = vec![[0.0; FILTERS]; CONV_SIDE * CONV_SIDE];
+    for r in 0..CONV_SIDE {
+        for c in 0..CONV_SIDE {
+            maps[r * CONV_SIDE + c] = dense_conv2d(&patch(image, r, c), conv).map(|v| v.max(0.0));
+        }
+    }
+    let mut values = vec![0.0; FEATURES];
+    let mut sources = vec![(0, 0); FEATURES];
+    for pr in 0..POOL_SIDE {
+        for pc in 0..POOL_SIDE {
+            for (r, c) in [(2 * pr, 2 * pc), (2 * pr, 2 * pc + 1), (2 * pr + 1, 2 * pc), (2 * pr + 1, 2 * pc + 1)] {
+                for (f, &v) in maps[r * CONV_SIDE + c].iter().enumerate() {
+                    let out = (f * POOL_SIDE + pr) * POOL_SIDE + pc;
+                    if v > values[out] {
+                        values[out] = v;
+                        sources[out] = (r, c);
+                    }
+                }
+            }
+        }
+    }
+    Features { values, sources }
+}
+
+fn predict(conv: &Layer2D<f64, FILTERS, PATCH>, head: &Sequential<f64>, images: &[Vec<f64>]) -> Vec<usize> {
+    images.iter().map(|image| argmax(&head.forward(&extract(conv, image).values))).collect()
+}
+
+/// One mini-batch SGD step on the filters and the head; returns the summed loss.
+fn train_batch(
+    conv: &mut Layer2D<f64, FILTERS, PATCH>,
+    head: &mut Sequential<f64>,
+    images: &[&Vec<f64>],
+    labels: &[usize],
+    learning_rate: f64,
+) -> f64 {
+    let loss = Loss::CrossEntropy;
+    let mut total = 0.0;
+    for (image, &label) in images.iter().zip(labels) {
+        let features = extract(conv, image);
+        let (value, feature_grad) = backward_pass_with(&mut head.layers, &features.values, |outputs| {
+            (loss.forward_class(outputs, label), loss.derivative_class(outputs, label))
+        });
+        total += value;
+        // Each pooled value came from one conv output that passed the ReLU (or was zero,
+        // in which case no gradient flows), so its gradient goes to that single patch.
+        for (k, &g) in feature_grad.iter().enumerate() {
+            if g == 0.0 || features.values[k] <= 0.0 {
+                continue;
+            }
+            let (r, c) = features.sources[k];
+            let mut grad_output = [0.0; FILTERS];
+            grad_output[k / (POOL_SIDE * POOL_SIDE)] = g;
+            Layer::backward(conv, &patch(image, r, c), &[], &grad_output);
+        }
+    }
+    let step = learning_rate / images.len() as f64;
+    head.sgd_step(step);
+    for (param, grad) in conv.params_mut() {
+        *param -= grad * step;
+    }
+    head.zero_grad();
+    conv.zero_grad();
+    total
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let ((train_x, train_y), (test_x, test_y), epochs) = match std::env::args().nth(1) {
+        Some(dir) => (read_split(Path::new(&dir), "train", 10_000)?, read_split(Path::new(&dir), "t10k", 2000)?, 3),
+        None => {
+            println!("no MNIST directory given, using synthetic digits");
+            let mut rng = Rng::new(42);
+            (synthetic(2000, &mut rng), synthetic(500, &mut rng), 5)
+        }
+    };
+    println!("{} training and {} test images", train_x.len(), test_x.len());
+
+    // He initialization for the filters: each sees 9 inputs
+    let mut rng = Rng::new(7);
+    let scale = (2.0 / PATCH as f64).sqrt();
+    let mut conv = Layer2D::new(std::array::from_fn(|_| std::array::from_fn(|_| scale * rng.next_normal())), [0.0; FILTERS]);
+    let mut head = ModelBuilder::new(FEATURES).dense(CLASSES).softmax().seed(1).build::<f64>()?;
+
+    let mut order: Vec<usize> = (0..train_x.len()).collect();
+    for epoch in 0..epochs {
+        rng.shuffle(&mut order);
+        let mut loss = 0.0;
+        for batch in order.chunks(32) {
+            let images: Vec<&Vec<f64>> = batch.iter().map(|&i| &train_x[i]).collect();
+            let labels: Vec<usize> = batch.iter().map(|&i| train_y[i]).collect();
+            loss += train_batch(&mut conv, &mut head, &images, &labels, 0.05);
+        }
+        let test_accuracy = accuracy(&predict(&conv, &head, &test_x), &test_y);
+        println!("epoch {}: loss {:.4}, test accuracy {:.3}", epoch, loss / train_x.len() as f64, test_accuracy);
+    }
+
+    // Save the filters and the head, reload them and check the predictions agree
+    let dir = tempfile::tempdir()?;
+    let filters_path = dir.path().join("filters.json");
+    let head_path = dir.path().join("head.json");
+    let filters: Vec<Vec<f64>> = conv.filters.iter().map(|f| f.to_vec()).chain([conv.biases.to_vec()]).collect();
+    std::fs::write(&filters_path, serde_json::to_string(&filters)?)?;
+    head.save_json(&head_path)?;
+
+    let saved: Vec<Vec<f64>> = serde_json::from_str(&std::fs::read_to_string(&filters_path)?)?;
+    let reloaded_conv = Layer2D::new(
+        std::array::from_fn(|i| std::array::from_fn(|j| saved[i][j])),
+        std::array::from_fn(|i| saved[FILTERS][i]),
+    );
+    let reloaded_head = Sequential::<f64>::load_json(&head_path)?;
+    assert_eq!(predict(&reloaded_conv, &reloaded_head, &test_x), predict(&conv, &head, &test_x));
+    println!("reloaded model gives identical predictions");
+    Ok(())
+}
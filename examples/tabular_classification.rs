@@ -0,0 +1,87 @@
+//! Tabular classification from a CSV file, end to end:
+//! generate data -> write/read CSV -> robust scaling -> stratified split -> train an MLP
+//! -> evaluate -> save and reload the model.
+//!
+//! Run with `cargo run --example tabular_classification`.
+
+use neuralnet::data_handling::read_csv;
+use neuralnet::dataset::{stratified_split, ClassificationGenerator};
+use neuralnet::loss_fn::Loss;
+use neuralnet::metrics::{accuracy, argmax, confusion_matrix};
+use neuralnet::model::{ModelBuilder, Sequential};
+use neuralnet::preprocessing::{RobustScaler, Transformer};
+use neuralnet::random::Rng;
+
+fn one_hot(label: usize, n_classes: usize) -> Vec<f64> {
+    (0..n_classes).map(|k| if k == label { 1.0 } else { 0.0 }).collect()
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let csv_path = dir.path().join("data.csv");
+
+    // 1. A synthetic, slightly imbalanced 3-class problem written to CSV
+    let generator = ClassificationGenerator {
+        n_classes: 3,
+        n_informative: 3,
+        n_redundant: 1,
+        label_noise: 0.02,
+        class_sep: 1.5,
+        seed: 7,
+        ..ClassificationGenerator::new(600, 6)
+    }
+    .imbalance_ratio(1.5);
+    let (features, labels) = generator.generate();
+    let mut text = String::from("f0,f1,f2,f3,f4,f5,label\n");
+    for (row, label) in features.iter().zip(labels.iter()) {
+        let cells: Vec<String> = row.iter().map(|v| format!("{:.6}", v)).collect();
+        text.push_str(&format!("{},{}\n", cells.join(","), label));
+    }
+    std::fs::write(&csv_path, text)?;
+
+    // 2. Load it back (`read_csv` consumes the header row)
+    let rows = read_csv(&csv_path)?;
+    let mut features: Vec<Vec<f64>> = Vec::new();
+    let mut labels: Vec<usize> = Vec::new();
+    for row in &rows {
+        let (label, values) = row.split_last().expect("empty row");
+        features.push(values.iter().map(|v| v.parse()).collect::<Result<_, _>>()?);
+        labels.push(label.parse()?);
+    }
+
+    // 3. Stratified train/test split and scaling fitted on the training part only
+    let mut parts = stratified_split(&features, &labels, &[0.8, 0.2], 1).into_iter();
+    let (train_x, train_y) = parts.next().unwrap();
+    let (test_x, test_y) = parts.next().unwrap();
+    let mut scaler = RobustScaler::new();
+    let train_x = scaler.fit_transform(&train_x);
+    let test_x = scaler.transform(&test_x);
+
+    // 4. Train
+    let mut model = ModelBuilder::new(6).dense(16).relu().dense(3).softmax().seed(3).build::<f64>()?;
+    let targets: Vec<Vec<f64>> = train_y.iter().map(|&y| one_hot(y, 3)).collect();
+    let mut rng = Rng::new(0);
+    let mut order: Vec<usize> = (0..train_x.len()).collect();
+    for epoch in 0..30 {
+        rng.shuffle(&mut order);
+        let x: Vec<Vec<f64>> = order.iter().map(|&i| train_x[i].clone()).collect();
+        let y: Vec<Vec<f64>> = order.iter().map(|&i| targets[i].clone()).collect();
+        let loss = model.train_epoch(&x, &y, Loss::CrossEntropy, 0.05);
+        if epoch % 10 == 0 {
+            println!("epoch {:>2}: loss {:.4}", epoch, loss);
+        }
+    }
+
+    // 5. Evaluate
+    let predictions: Vec<usize> = model.predict(&test_x).iter().map(|p| argmax(p)).collect();
+    println!("test accuracy: {:.3}", accuracy(&predictions, &test_y));
+    println!("confusion matrix (rows = true class): {:?}", confusion_matrix(&predictions, &test_y, 3));
+
+    // 6. Save, reload and check the reloaded model predicts the same
+    let model_path = dir.path().join("model.json");
+    model.save_json(&model_path)?;
+    let reloaded = Sequential::<f64>::load_json(&model_path)?;
+    assert_eq!(reloaded.predict(&test_x), model.predict(&test_x));
+    println!("reloaded model from {}", model_path.display());
+    Ok(())
+}
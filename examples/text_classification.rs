@@ -0,0 +1,66 @@
+//! Sentiment classification of short texts with bag-of-n-grams features.
+//!
+//! Run with `cargo run --example text_classification`.
+
+use neuralnet::loss_fn::Loss;
+use neuralnet::metrics::{accuracy, argmax};
+use neuralnet::model::{ModelBuilder, Sequential};
+use neuralnet::text::{Analyzer, CountVectorizer};
+
+const TRAIN: &[(&str, usize)] = &[
+    ("a great and wonderful film", 1),
+    ("loved the acting, great story", 1),
+    ("wonderful soundtrack and a moving story", 1),
+    ("one of the best films this year", 1),
+    ("truly great, I loved it", 1),
+    ("the best cast and a moving ending", 1),
+    ("a boring and terrible film", 0),
+    ("hated the acting, awful story", 0),
+    ("terrible soundtrack and a dull plot", 0),
+    ("one of the worst films this year", 0),
+    ("truly awful, I hated it", 0),
+    ("the worst cast and a boring ending", 0),
+];
+
+const TEST: &[(&str, usize)] = &[
+    ("great story and wonderful acting", 1),
+    ("the best, loved it", 1),
+    ("awful and boring", 0),
+    ("the worst soundtrack, hated it", 0),
+];
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let train_docs: Vec<&str> = TRAIN.iter().map(|(d, _)| *d).collect();
+    let test_docs: Vec<&str> = TEST.iter().map(|(d, _)| *d).collect();
+
+    let mut vectorizer = CountVectorizer::new(Analyzer::Word, 1, 2);
+    vectorizer.binary = true;
+    let train_x: Vec<Vec<f64>> = vectorizer.fit_transform(&train_docs);
+    let test_x: Vec<Vec<f64>> = vectorizer.transform(&test_docs);
+    println!("vocabulary: {} n-grams", vectorizer.n_features());
+
+    let targets: Vec<Vec<f64>> = TRAIN.iter()
+        .map(|&(_, y)| if y == 1 { vec![0.0, 1.0] } else { vec![1.0, 0.0] })
+        .collect();
+    let mut model = ModelBuilder::new(vectorizer.n_features()).dense(8).relu().dense(2).softmax().seed(9).build::<f64>()?;
+    for epoch in 0..200 {
+        let loss = model.train_epoch(&train_x, &targets, Loss::CrossEntropy, 0.05);
+        if epoch % 50 == 0 {
+            println!("epoch {:>3}: loss {:.4}", epoch, loss);
+        }
+    }
+
+    let predictions: Vec<usize> = model.predict(&test_x).iter().map(|p| argmax(p)).collect();
+    let labels: Vec<usize> = TEST.iter().map(|(_, y)| *y).collect();
+    for (doc, p) in test_docs.iter().zip(predictions.iter()) {
+        println!("{:>40} -> {}", doc, if *p == 1 { "positive" } else { "negative" });
+    }
+    println!("test accuracy: {:.3}", accuracy(&predictions, &labels));
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("sentiment.json");
+    model.save_json(&path)?;
+    let reloaded = Sequential::<f64>::load_json(&path)?;
+    assert_eq!(reloaded.predict(&test_x), model.predict(&test_x));
+    Ok(())
+}
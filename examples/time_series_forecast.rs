@@ -0,0 +1,51 @@
+//! Multi-step forecasting of a noisy seasonal series from a window of past values.
+//!
+//! `dataset::sliding_windows` turns the series into `WINDOW`-value inputs and the
+//! `HORIZON` values that follow, and the model predicts all of them at once (one output
+//! per step ahead). The split is chronological (no shuffling
+//! across the boundary), and the model is compared step by step with the naive "repeat
+//! the last value" forecast.
+//!
+//! Run with `cargo run --example time_series_forecast`.
+
+use neuralnet::dataset::sliding_windows;
+use neuralnet::loss_fn::Loss;
+use neuralnet::metrics::regression_errors;
+use neuralnet::model::{ModelBuilder, Sequential};
+use neuralnet::random::Rng;
+use neuralnet::training::Trainer;
+
+const WINDOW: usize = 12;
+const HORIZON: usize = 3;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut rng = Rng::new(3);
+    let series: Vec<f64> = (0..600)
+        .map(|t| (2.0 * std::f64::consts::PI * t as f64 / 24.0).sin() + 0.1 * rng.next_normal())
+        .collect();
+
+    let (windows, next) = sliding_windows(&series, WINDOW, HORIZON, 1);
+    let split = windows.len() * 4 / 5;
+    let (train_x, test_x) = windows.split_at(split);
+    let (train_y, test_y) = next.split_at(split);
+
+    let mut model = ModelBuilder::new(WINDOW).dense(16).tanh().dense(HORIZON).seed(5).build::<f64>()?;
+    let history = Trainer::new(Loss::MeanSquaredError, 0.02, 40).fit(&mut model, train_x, train_y);
+    for record in history.records.iter().step_by(10) {
+        println!("epoch {:>2}: mse {:.4}", record.epoch, record.train_loss);
+    }
+
+    let naive: Vec<Vec<f64>> = test_x.iter().map(|w| vec![w[WINDOW - 1]; HORIZON]).collect();
+    let model_errors = regression_errors(&model.predict(test_x), test_y);
+    let naive_errors = regression_errors(&naive, test_y);
+    for (k, (m, n)) in model_errors.iter().zip(naive_errors.iter()).enumerate() {
+        println!("t+{}: model RMSE {:.4}, naive RMSE {:.4}", k + 1, m.mse.sqrt(), n.mse.sqrt());
+    }
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("forecaster.json");
+    model.save_json(&path)?;
+    let reloaded = Sequential::<f64>::load_json(&path)?;
+    assert_eq!(reloaded.predict(test_x), model.predict(test_x));
+    Ok(())
+}
@@ -1,17 +1,35 @@
+use alloc::vec::Vec;
 use crate::numbers::*;
 
 /// Computes the sigmoid activation for a single value.
 ///
 /// # Arguments
-/// * `x` - Input value of type implementing `Number`.
+/// * `x` - Input value of type implementing `Real`.
 ///
 /// # Returns
-/// * Sigmoid activation: `1 / (1 + exp(-x))`
+/// * Sigmoid activation: `1 / (1 + exp(-x))`.
+///   Evaluated as `exp(x) / (1 + exp(x))` for negative `x`, so only `exp(-|x|)` is ever
+///   computed and large-magnitude inputs saturate to `0` or `1` instead of overflowing.
+pub(crate) fn sigmoid<T: Real>(x: T) -> T {
+    if x.ge(T::zero()) {
+        T::one() / (T::one() + (-x).exp())
+    } else {
+        let e = x.exp();
+        e / (T::one() + e)
+    }
+}
+
+/// Computes the logarithm of the sigmoid for a single value.
+///
+/// # Arguments
+/// * `x` - Input value of type implementing `Real`.
 ///
-/// # Panics
-/// Panics for integer types, as `exp` is not implemented for them.
-fn sigmoid<T: Number>(x: T) -> T {
-    T::one() / (T::one() + (-x).exp())
+/// # Returns
+/// * `ln(sigmoid(x)) = -ln(1 + exp(-x))`, evaluated as `min(x, 0) - ln(1 + exp(-|x|))`.
+///   Unlike `sigmoid(x).ln()` it stays finite for large negative inputs, where the
+///   sigmoid itself underflows to `0`.
+pub fn log_sigmoid<T: Real>(x: T) -> T {
+    x.min(T::zero()) - (-x.abs()).exp().ln_1p()
 }
 
 /// Applies the sigmoid activation function element-wise to an array.
@@ -21,7 +39,7 @@ fn sigmoid<T: Number>(x: T) -> T {
 ///
 /// # Returns
 /// * Array of sigmoid-activated values.
-pub fn sigmoid_layer<T: Number, const N: usize>(inputs: &[T; N]) -> [T; N] {
+pub fn sigmoid_layer<T: Real, const N: usize>(inputs: &[T; N]) -> [T; N] {
     let mut outputs = [T::zero(); N];
     for i in 0..N {
         outputs[i] = sigmoid(inputs[i]);
@@ -37,7 +55,7 @@ pub fn sigmoid_layer<T: Number, const N: usize>(inputs: &[T; N]) -> [T; N] {
 /// # Returns
 /// * If `x > 0`, returns `x`; otherwise returns zero.
 fn relu<T: Number>(x: T) -> T {
-    if x.gt(T::zero()) { x } else { T::zero() }
+    x.max(T::zero())
 }
 
 /// Applies the ReLU activation function element-wise to an array.
@@ -58,14 +76,11 @@ pub fn relu_layer<T: Number, const N: usize>(inputs: &[T; N]) -> [T; N] {
 /// Computes the hyperbolic tangent (tanh) activation for a single value.
 ///
 /// # Arguments
-/// * `x` - Input value of type implementing `Number`.
+/// * `x` - Input value of type implementing `Real`.
 ///
 /// # Returns
 /// * Tanh activation: `tanh(x)`
-///
-/// # Panics
-/// Panics for integer types, as `tanh` is not implemented for them.
-fn tanh<T: Number>(x: T) -> T {
+fn tanh<T: Real>(x: T) -> T {
     x.tanh()
 }
 
@@ -76,7 +91,7 @@ fn tanh<T: Number>(x: T) -> T {
 ///
 /// # Returns
 /// * Array of tanh-activated values.
-pub fn tanh_layer<T: Number, const N: usize>(inputs: &[T; N]) -> [T; N] {
+pub fn tanh_layer<T: Real, const N: usize>(inputs: &[T; N]) -> [T; N] {
     let mut outputs = [T::zero(); N];
     for i in 0..N {
         outputs[i] = tanh(inputs[i]);
@@ -84,22 +99,182 @@ pub fn tanh_layer<T: Number, const N: usize>(inputs: &[T; N]) -> [T; N] {
     outputs
 }
 
+/// Computes the softplus activation for a single value.
+///
+/// # Arguments
+/// * `x` - Input value of type implementing `Real`.
+///
+/// # Returns
+/// * Softplus activation: `ln(1 + exp(x))`, a smooth approximation of ReLU.
+///   Evaluated as `max(x, 0) + ln(1 + exp(-|x|))` so large inputs do not overflow.
+fn softplus<T: Real>(x: T) -> T {
+    x.max(T::zero()) + (-x.abs()).exp().ln_1p()
+}
+
+/// Applies the softplus activation function element-wise to an array.
+///
+/// # Arguments
+/// * `inputs` - Array of input values.
+///
+/// # Returns
+/// * Array of softplus-activated values.
+pub fn softplus_layer<T: Real, const N: usize>(inputs: &[T; N]) -> [T; N] {
+    let mut outputs = [T::zero(); N];
+    for i in 0..N {
+        outputs[i] = softplus(inputs[i]);
+    }
+    outputs
+}
+
+/// Computes the hard sigmoid activation for a single value.
+///
+/// # Arguments
+/// * `x` - Input value of type implementing `Real`.
+///
+/// # Returns
+/// * Piecewise-linear sigmoid approximation: `clamp(x / 6 + 1/2, 0, 1)`.
+///   It needs no `exp`, which makes it cheap on embedded targets.
+fn hard_sigmoid<T: Real>(x: T) -> T {
+    let two = T::one() + T::one();
+    let six = two * (two + T::one());
+    (x / six + T::one() / two).max(T::zero()).min(T::one())
+}
+
+/// Applies the hard sigmoid activation function element-wise to an array.
+///
+/// # Arguments
+/// * `inputs` - Array of input values.
+///
+/// # Returns
+/// * Array of hard-sigmoid-activated values.
+pub fn hard_sigmoid_layer<T: Real, const N: usize>(inputs: &[T; N]) -> [T; N] {
+    let mut outputs = [T::zero(); N];
+    for i in 0..N {
+        outputs[i] = hard_sigmoid(inputs[i]);
+    }
+    outputs
+}
+
+/// Computes `ln(sum_i exp(values[i]))` without overflow.
+///
+/// # Arguments
+/// * `values` - Slice of values, e.g. the logits of a classifier.
+///
+/// # Returns
+/// * `max + ln(sum_i exp(values[i] - max))`: shifting by the maximum keeps every
+///   exponent `<= 0`, so large logits do not overflow and at least one term is `1`.
+///   An empty slice gives `NEG_INFINITY`, the log of an empty sum; if the maximum is
+///   infinite it is returned unchanged.
+pub fn logsumexp<T: Real>(values: &[T]) -> T {
+    let max = values.iter().copied().fold(T::NEG_INFINITY, T::max);
+    if max.eq(T::INFINITY) || max.eq(T::NEG_INFINITY) {
+        return max;
+    }
+    max + values.iter().fold(T::zero(), |acc, &v| acc + (v - max).exp()).ln()
+}
+
+/// Numerically stable softmax: shifts by the maximum before exponentiating.
+///
+/// # Arguments
+/// * `values` - Slice of logits.
+///
+/// # Returns
+/// * Vector of probabilities `exp(values[i]) / sum_j exp(values[j])`, summing to one.
+pub fn softmax<T: Real>(values: &[T]) -> Vec<T> {
+    let mut outputs = values.to_vec();
+    softmax_in_place(&mut outputs);
+    outputs
+}
+
+/// Like `softmax`, overwriting `values` with the probabilities.
+pub fn softmax_in_place<T: Real>(values: &mut [T]) {
+    let max = values.iter().copied().fold(T::NEG_INFINITY, T::max);
+    values.iter_mut().for_each(|v| *v = (*v - max).exp());
+    let sum = values.iter().copied().fold(T::zero(), |acc, e| acc + e);
+    values.iter_mut().for_each(|e| *e = *e / sum);
+}
+
+/// Logarithm of `softmax`, computed as `values[i] - logsumexp(values)`.
+///
+/// # Arguments
+/// * `values` - Slice of logits.
+///
+/// # Returns
+/// * Vector of log-probabilities. Unlike `softmax(values)` followed by `ln`, it stays
+///   finite for classes whose probability underflows to zero.
+pub fn log_softmax<T: Real>(values: &[T]) -> Vec<T> {
+    let lse = logsumexp(values);
+    values.iter().map(|&v| v - lse).collect()
+}
+
+/// Threshold activations for perceptrons. Unlike `Activation` they only need
+/// comparisons, so they work for every `Number`, including `i32` and `i64`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub enum StepActivation {
+    /// Heaviside step: `1` if `x >= 0`, otherwise `0`.
+    Heaviside,
+    /// Sign step: `1` if `x >= 0`, otherwise `-1`.
+    Sign,
+}
+
+impl StepActivation {
+    /// Applies the step to a single value.
+    pub fn apply<T: Number>(&self, x: T) -> T {
+        let fired = x.ge(T::zero());
+        match self {
+            StepActivation::Heaviside => if fired { T::one() } else { T::zero() },
+            StepActivation::Sign => if fired { T::one() } else { -T::one() },
+        }
+    }
+
+    /// Applies the step element-wise to an array.
+    pub fn forward<T: Number, const N: usize>(&self, inputs: &[T; N]) -> [T; N] {
+        let mut outputs = [T::zero(); N];
+        for i in 0..N {
+            outputs[i] = self.apply(inputs[i]);
+        }
+        outputs
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
 pub enum Activation {
     Sigmoid,
     ReLU,
     Tanh,
+    Softplus,
+    HardSigmoid,
+    /// Identity, e.g. for regression output layers that must not squash their outputs.
+    Linear,
 }
 
 impl Activation {
-    pub fn forward<T: Number, const N: usize>(&self, inputs: &[T; N]) -> [T; N] {
+    /// Applies the activation to a single value.
+    pub fn apply<T: Real>(&self, x: T) -> T {
+        match self {
+            Activation::Sigmoid => sigmoid(x),
+            Activation::ReLU => relu(x),
+            Activation::Tanh => tanh(x),
+            Activation::Softplus => softplus(x),
+            Activation::HardSigmoid => hard_sigmoid(x),
+            Activation::Linear => x,
+        }
+    }
+
+    pub fn forward<T: Real, const N: usize>(&self, inputs: &[T; N]) -> [T; N] {
         match self {
             Activation::Sigmoid => sigmoid_layer(inputs),
             Activation::ReLU => relu_layer(inputs),
             Activation::Tanh => tanh_layer(inputs),
+            Activation::Softplus => softplus_layer(inputs),
+            Activation::HardSigmoid => hard_sigmoid_layer(inputs),
+            Activation::Linear => *inputs,
         }
     }
 
-    pub fn derivative<T: Number>(&self, x: T) -> T {
+    pub fn derivative<T: Real>(&self, x: T) -> T {
         match self {
             Activation::Sigmoid => {
                 let sig = sigmoid(x);
@@ -112,6 +287,39 @@ impl Activation {
                 let t = tanh(x);
                 T::one() - t * t
             }
+            // d/dx ln(1 + exp(x)) = sigmoid(x)
+            Activation::Softplus => sigmoid(x),
+            Activation::HardSigmoid => {
+                let three = T::one() + T::one() + T::one();
+                if x.gt(-three) && x.lt(three) { T::one() / (three + three) } else { T::zero() }
+            }
+            Activation::Linear => T::one(),
         }
     }
+
+    /// Applies `derivative` element-wise to an array of pre-activation values.
+    ///
+    /// # Arguments
+    /// * `pre_activations` - Array of values the activation was applied to.
+    ///
+    /// # Returns
+    /// * Array of derivatives, one per input.
+    pub fn derivative_layer<T: Real, const N: usize>(&self, pre_activations: &[T; N]) -> [T; N] {
+        let mut outputs = [T::zero(); N];
+        for i in 0..N {
+            outputs[i] = self.derivative(pre_activations[i]);
+        }
+        outputs
+    }
+
+    /// Applies `derivative` element-wise to a slice of pre-activation values.
+    ///
+    /// # Arguments
+    /// * `pre_activations` - Slice of values the activation was applied to.
+    ///
+    /// # Returns
+    /// * Vector of derivatives, one per input.
+    pub fn derivative_vec<T: Real>(&self, pre_activations: &[T]) -> Vec<T> {
+        pre_activations.iter().map(|&x| self.derivative(x)).collect()
+    }
 }
\ No newline at end of file
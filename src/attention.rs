@@ -0,0 +1,484 @@
+//! Scaled dot-product and multi-head attention over sequences, and the transformer
+//! encoder block built from them.
+//!
+//! A sequence is a slice of rows, one `Vec<T>` per position, so a sequence of `n`
+//! tokens embedded in `d` dimensions is an `n x d` matrix. All layers follow the
+//! conventions of `model::Dense`: `backward` takes the inputs of the matching forward
+//! pass, recomputes what it needs, adds the parameter gradients to the layer and returns
+//! the gradients with respect to the inputs.
+//!
+//! ```
+//! use neuralnet::attention::MultiHeadAttention;
+//! use neuralnet::random::Rng;
+//!
+//! let attention = MultiHeadAttention::<f64>::new(8, 2, &mut Rng::new(0));
+//! let tokens = vec![vec![0.1; 8], vec![0.2; 8], vec![0.3; 8]];
+//! let outputs = attention.forward_self(&tokens);
+//! assert_eq!((outputs.len(), outputs[0].len()), (3, 8));
+//! ```
+
+use std::ops::Range;
+use num_traits::FromPrimitive;
+use serde::{Deserialize, Serialize};
+use crate::activation_fn::{softmax_in_place, Activation};
+use crate::backend::Naive;
+use crate::layers::{Layer, LayerError, Parameter, ParameterMut};
+use crate::model::{Dense, LayerNorm};
+use crate::numbers::{Number, Real};
+use crate::random::Rng;
+
+/// `softmax(Q K^T / sqrt(d)) V` for queries `Q` (`n x d`), keys `K` (`m x d`) and values
+/// `V` (`m x d_v`). The layer has no parameters.
+///
+/// With `causal` set, query `i` only attends to keys `0..=i`, as in an autoregressive
+/// decoder.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScaledDotProductAttention {
+    pub causal: bool,
+}
+
+/// Gradients of the loss with respect to the three inputs of an attention pass.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttentionGradients<T> {
+    pub query: Vec<Vec<T>>,
+    pub key: Vec<Vec<T>>,
+    pub value: Vec<Vec<T>>,
+}
+
+impl ScaledDotProductAttention {
+    pub fn new() -> Self {
+        ScaledDotProductAttention { causal: false }
+    }
+
+    /// Attention with the causal mask.
+    pub fn causal() -> Self {
+        ScaledDotProductAttention { causal: true }
+    }
+
+    /// Attention weights `softmax(Q K^T / sqrt(d))`, one row of `keys.len()` weights per
+    /// query. Panics on mismatched shapes; see `try_forward`.
+    pub fn weights<T: Real + FromPrimitive>(&self, queries: &[Vec<T>], keys: &[Vec<T>]) -> Vec<Vec<T>> {
+        let scale = T::one() / T::to_number::<T>(row_width(queries, keys) as f64).sqrt();
+        queries.iter().enumerate()
+            .map(|(i, q)| {
+                let mut scores: Vec<T> = keys.iter().enumerate()
+                    .map(|(j, k)| if self.causal && j > i { T::NEG_INFINITY } else { dot(q, k) * scale })
+                    .collect();
+                softmax_in_place(&mut scores);
+                scores
+            })
+            .collect()
+    }
+
+    /// Attends every query to `keys` and returns the weighted sums of `values`, one row
+    /// per query. Panics on mismatched shapes; see `try_forward`.
+    pub fn forward<T: Real + FromPrimitive>(&self, queries: &[Vec<T>], keys: &[Vec<T>], values: &[Vec<T>]) -> Vec<Vec<T>> {
+        self.try_forward(queries, keys, values).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `forward`, but returns an error if the query and key rows differ in length,
+    /// the value rows differ in length, or there is not one value per key.
+    pub fn try_forward<T: Real + FromPrimitive>(&self, queries: &[Vec<T>], keys: &[Vec<T>], values: &[Vec<T>]) -> Result<Vec<Vec<T>>, LayerError> {
+        let value_dim = validate(queries, keys, values)?;
+        Ok(self.weights(queries, keys).iter().map(|w| weighted_sum(w, values, value_dim)).collect())
+    }
+
+    /// Backward pass for the inputs of a `forward` call and the gradient of the loss with
+    /// respect to its outputs. Panics on mismatched shapes.
+    pub fn backward<T: Real + FromPrimitive>(&self, queries: &[Vec<T>], keys: &[Vec<T>], values: &[Vec<T>], grad_output: &[Vec<T>]) -> AttentionGradients<T> {
+        let value_dim = validate(queries, keys, values).unwrap_or_else(|e| panic!("{}", e));
+        assert_eq!(grad_output.len(), queries.len(), "grad_output must have one row per query");
+        let dim = row_width(queries, keys);
+        let scale = T::one() / T::to_number::<T>(dim as f64).sqrt();
+        let weights = self.weights(queries, keys);
+
+        let mut grads = AttentionGradients {
+            query: vec![vec![T::zero(); dim]; queries.len()],
+            key: vec![vec![T::zero(); dim]; keys.len()],
+            value: vec![vec![T::zero(); value_dim]; values.len()],
+        };
+        for (i, (w, g)) in weights.iter().zip(grad_output.iter()).enumerate() {
+            // dV += w^T g and dw = g V^T
+            let grad_weights: Vec<T> = values.iter().zip(grads.value.iter_mut()).zip(w.iter())
+                .map(|((v, dv), &a)| {
+                    add_scaled(dv, a, g);
+                    dot(g, v)
+                })
+                .collect();
+            // Through the softmax: ds_j = w_j (dw_j - sum_l w_l dw_l)
+            let mean = w.iter().zip(grad_weights.iter()).fold(T::zero(), |acc, (&a, &d)| acc + a * d);
+            for (j, (&a, &d)) in w.iter().zip(grad_weights.iter()).enumerate() {
+                let grad_score = a * (d - mean) * scale;
+                add_scaled(&mut grads.query[i], grad_score, &keys[j]);
+                add_scaled(&mut grads.key[j], grad_score, &queries[i]);
+            }
+        }
+        grads
+    }
+}
+
+/// Multi-head attention: the queries, keys and values are projected by dense layers,
+/// split into `n_heads` heads of `d_model / n_heads` columns that attend independently,
+/// concatenated again and mixed by the `output` projection.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MultiHeadAttention<T: Number> {
+    pub n_heads: usize,
+    pub causal: bool,
+    pub query: Dense<T>,
+    pub key: Dense<T>,
+    pub value: Dense<T>,
+    pub output: Dense<T>,
+}
+
+impl<T: Number + FromPrimitive> MultiHeadAttention<T> {
+    /// Creates a layer with `d_model` inputs and outputs and Glorot-uniform projections.
+    /// Panics if `n_heads` is zero or does not divide `d_model`.
+    pub fn new(d_model: usize, n_heads: usize, rng: &mut Rng) -> Self {
+        assert!(n_heads > 0 && d_model.is_multiple_of(n_heads), "n_heads must divide d_model");
+        let mut projection = || Dense::glorot_uniform(d_model, d_model, rng);
+        MultiHeadAttention { n_heads, causal: false, query: projection(), key: projection(), value: projection(), output: projection() }
+    }
+
+    /// Sets the causal mask of every head, see `ScaledDotProductAttention::causal`.
+    pub fn with_causal(mut self, causal: bool) -> Self {
+        self.causal = causal;
+        self
+    }
+}
+
+impl<T: Number> MultiHeadAttention<T> {
+    pub fn d_model(&self) -> usize {
+        self.output.output_dim()
+    }
+
+    /// Columns per head.
+    pub fn head_dim(&self) -> usize {
+        self.d_model() / self.n_heads
+    }
+
+    fn head(&self, h: usize) -> Range<usize> {
+        h * self.head_dim()..(h + 1) * self.head_dim()
+    }
+
+    pub fn zero_grad(&mut self) {
+        self.projections_mut().into_iter().for_each(Layer::zero_grad);
+    }
+
+    /// Applies the accumulated gradients, `w -= learning_rate * grad`.
+    pub fn sgd_step(&mut self, learning_rate: T) {
+        for dense in self.projections_mut() {
+            for (param, grad) in dense.params_mut() {
+                *param = *param - grad * learning_rate;
+            }
+        }
+    }
+
+    /// Named views of the projection weights, e.g. `query.weights` or `output.biases`.
+    pub fn parameters(&self) -> Vec<Parameter<'_, T>> {
+        [("query", &self.query), ("key", &self.key), ("value", &self.value), ("output", &self.output)].into_iter()
+            .flat_map(|(name, dense)| dense.parameters().into_iter().map(move |p| p.prefixed(name)))
+            .collect()
+    }
+
+    /// Mutable named views of the projection weights, named like `parameters`.
+    pub fn parameters_mut(&mut self) -> Vec<ParameterMut<'_, T>> {
+        let MultiHeadAttention { query, key, value, output, .. } = self;
+        [("query", query), ("key", key), ("value", value), ("output", output)].into_iter()
+            .flat_map(|(name, dense)| dense.parameters_mut().into_iter().map(move |p| p.prefixed(name)))
+            .collect()
+    }
+
+    fn projections_mut(&mut self) -> [&mut Dense<T>; 4] {
+        [&mut self.query, &mut self.key, &mut self.value, &mut self.output]
+    }
+}
+
+impl<T: Real + FromPrimitive> MultiHeadAttention<T> {
+    /// Attends every row of `queries` to the rows of `keys_values`, which provide both the
+    /// keys and the values. Panics if a row does not have `d_model` values; see `try_forward`.
+    pub fn forward(&self, queries: &[Vec<T>], keys_values: &[Vec<T>]) -> Vec<Vec<T>> {
+        self.try_forward(queries, keys_values).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Self-attention: `forward(inputs, inputs)`.
+    pub fn forward_self(&self, inputs: &[Vec<T>]) -> Vec<Vec<T>> {
+        self.forward(inputs, inputs)
+    }
+
+    /// Fallible version of `forward`.
+    pub fn try_forward(&self, queries: &[Vec<T>], keys_values: &[Vec<T>]) -> Result<Vec<Vec<T>>, LayerError> {
+        self.check_rows(queries)?;
+        self.check_rows(keys_values)?;
+        let concatenated = self.attend(queries, keys_values).0;
+        Ok(concatenated.iter().map(|row| self.output.forward(row)).collect())
+    }
+
+    /// Backward pass for the inputs of a `forward` call. Adds the projection gradients to
+    /// the layer and returns the gradients with respect to `queries` and `keys_values`;
+    /// for self-attention the input gradient is their sum.
+    pub fn backward(&mut self, queries: &[Vec<T>], keys_values: &[Vec<T>], grad_output: &[Vec<T>]) -> (Vec<Vec<T>>, Vec<Vec<T>>) {
+        self.check_rows(queries).unwrap_or_else(|e| panic!("{}", e));
+        self.check_rows(keys_values).unwrap_or_else(|e| panic!("{}", e));
+        assert_eq!(grad_output.len(), queries.len(), "grad_output must have one row per query");
+        let (concatenated, (q, k, v)) = self.attend(queries, keys_values);
+
+        // Step 1: Output projection
+        let grad_concatenated: Vec<Vec<T>> = concatenated.iter().zip(grad_output.iter())
+            .map(|(x, g)| self.output.backward_on(&Naive, x, g))
+            .collect();
+
+        // Step 2: Every head, scattered back into full-width gradients
+        let attention = ScaledDotProductAttention { causal: self.causal };
+        let d_model = self.d_model();
+        let mut grad_q = vec![vec![T::zero(); d_model]; queries.len()];
+        let mut grad_k = vec![vec![T::zero(); d_model]; keys_values.len()];
+        let mut grad_v = vec![vec![T::zero(); d_model]; keys_values.len()];
+        for h in 0..self.n_heads {
+            let range = self.head(h);
+            let grads = attention.backward(&columns(&q, &range), &columns(&k, &range), &columns(&v, &range), &columns(&grad_concatenated, &range));
+            for (full, head) in [(&mut grad_q, grads.query), (&mut grad_k, grads.key), (&mut grad_v, grads.value)] {
+                for (row, head_row) in full.iter_mut().zip(head) {
+                    row[range.clone()].copy_from_slice(&head_row);
+                }
+            }
+        }
+
+        // Step 3: Input projections
+        let grad_queries = queries.iter().zip(grad_q.iter()).map(|(x, g)| self.query.backward_on(&Naive, x, g)).collect();
+        let grad_keys_values = keys_values.iter().zip(grad_k.iter().zip(grad_v.iter()))
+            .map(|(x, (gk, gv))| {
+                let from_keys = self.key.backward_on(&Naive, x, gk);
+                let from_values = self.value.backward_on(&Naive, x, gv);
+                from_keys.into_iter().zip(from_values).map(|(a, b)| a + b).collect()
+            })
+            .collect();
+        (grad_queries, grad_keys_values)
+    }
+
+    /// Projects the inputs and runs every head; returns the concatenated head outputs and
+    /// the projected queries, keys and values.
+    #[allow(clippy::type_complexity)]
+    fn attend(&self, queries: &[Vec<T>], keys_values: &[Vec<T>]) -> (Vec<Vec<T>>, (Vec<Vec<T>>, Vec<Vec<T>>, Vec<Vec<T>>)) {
+        let q: Vec<Vec<T>> = queries.iter().map(|x| self.query.forward(x)).collect();
+        let k: Vec<Vec<T>> = keys_values.iter().map(|x| self.key.forward(x)).collect();
+        let v: Vec<Vec<T>> = keys_values.iter().map(|x| self.value.forward(x)).collect();
+        let attention = ScaledDotProductAttention { causal: self.causal };
+        let mut concatenated = vec![Vec::with_capacity(self.d_model()); queries.len()];
+        for h in 0..self.n_heads {
+            let range = self.head(h);
+            let heads = attention.forward(&columns(&q, &range), &columns(&k, &range), &columns(&v, &range));
+            for (row, head_row) in concatenated.iter_mut().zip(heads) {
+                row.extend(head_row);
+            }
+        }
+        (concatenated, (q, k, v))
+    }
+
+    fn check_rows(&self, rows: &[Vec<T>]) -> Result<(), LayerError> {
+        match rows.iter().find(|row| row.len() != self.query.input_dim()) {
+            Some(row) => Err(LayerError::WrongLength { expected: self.query.input_dim(), found: row.len() }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Post-norm transformer encoder block, as in "Attention Is All You Need":
+///
+/// ```text
+/// h = LayerNorm(x + MultiHeadAttention(x))
+/// y = LayerNorm(h + W2 relu(W1 h))
+/// ```
+///
+/// Every position is a row of `d_model` values; the feedforward network has `d_ff`
+/// hidden units and is applied to every position independently.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransformerEncoderBlock<T: Number> {
+    pub attention: MultiHeadAttention<T>,
+    pub attention_norm: LayerNorm<T>,
+    pub feedforward_hidden: Dense<T>,
+    pub feedforward_output: Dense<T>,
+    pub feedforward_norm: LayerNorm<T>,
+}
+
+/// Intermediate values of a block's forward pass, one row per position.
+struct EncoderPass<T> {
+    residual: Vec<Vec<T>>,
+    hidden: Vec<Vec<T>>,
+    pre_activation: Vec<Vec<T>>,
+    activated: Vec<Vec<T>>,
+    feedforward_residual: Vec<Vec<T>>,
+}
+
+impl<T: Real + FromPrimitive> TransformerEncoderBlock<T> {
+    /// Creates a block with `n_heads` attention heads and `d_ff` feedforward units, with
+    /// Glorot-uniform attention weights, He-uniform feedforward weights and identity
+    /// layer norms. Panics if `n_heads` does not divide `d_model`.
+    pub fn new(d_model: usize, n_heads: usize, d_ff: usize, rng: &mut Rng) -> Self {
+        TransformerEncoderBlock {
+            attention: MultiHeadAttention::new(d_model, n_heads, rng),
+            attention_norm: LayerNorm::new(d_model),
+            feedforward_hidden: Dense::he_uniform(d_model, d_ff, rng),
+            feedforward_output: Dense::glorot_uniform(d_ff, d_model, rng),
+            feedforward_norm: LayerNorm::new(d_model),
+        }
+    }
+
+    /// Sets the causal mask of the attention, see `ScaledDotProductAttention::causal`.
+    pub fn with_causal(mut self, causal: bool) -> Self {
+        self.attention.causal = causal;
+        self
+    }
+
+    pub fn d_model(&self) -> usize {
+        self.attention.d_model()
+    }
+
+    /// Runs the block over a sequence. Panics if a row does not have `d_model` values;
+    /// see `try_forward`.
+    pub fn forward(&self, inputs: &[Vec<T>]) -> Vec<Vec<T>> {
+        self.try_forward(inputs).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Fallible version of `forward`.
+    pub fn try_forward(&self, inputs: &[Vec<T>]) -> Result<Vec<Vec<T>>, LayerError> {
+        self.attention.check_rows(inputs)?;
+        let pass = self.run(inputs);
+        Ok(pass.feedforward_residual.iter().map(|row| self.feedforward_norm.forward(row)).collect())
+    }
+
+    /// Backward pass for the inputs of a `forward` call. Adds the gradients of every
+    /// sub-layer to the block and returns the gradient with respect to `inputs`.
+    pub fn backward(&mut self, inputs: &[Vec<T>], grad_output: &[Vec<T>]) -> Vec<Vec<T>> {
+        self.attention.check_rows(inputs).unwrap_or_else(|e| panic!("{}", e));
+        assert_eq!(grad_output.len(), inputs.len(), "grad_output must have one row per position");
+        let pass = self.run(inputs);
+
+        // Step 1: Feedforward sub-layer and its residual connection
+        let mut grad_hidden = Vec::with_capacity(inputs.len());
+        for (i, g) in grad_output.iter().enumerate() {
+            let grad_residual = self.feedforward_norm.backward(&pass.feedforward_residual[i], &[], g);
+            let grad_activated = self.feedforward_output.backward_on(&Naive, &pass.activated[i], &grad_residual);
+            let grad_pre: Vec<T> = grad_activated.iter().zip(pass.pre_activation[i].iter())
+                .map(|(&d, &z)| d * Activation::ReLU.derivative(z))
+                .collect();
+            let through_feedforward = self.feedforward_hidden.backward_on(&Naive, &pass.hidden[i], &grad_pre);
+            grad_hidden.push(add(&grad_residual, &through_feedforward));
+        }
+
+        // Step 2: Attention sub-layer and its residual connection
+        let grad_residual: Vec<Vec<T>> = pass.residual.iter().zip(grad_hidden.iter())
+            .map(|(r, g)| self.attention_norm.backward(r, &[], g))
+            .collect();
+        let (grad_queries, grad_keys_values) = self.attention.backward(inputs, inputs, &grad_residual);
+        grad_residual.iter().zip(grad_queries.iter().zip(grad_keys_values.iter()))
+            .map(|(r, (q, kv))| add(&add(r, q), kv))
+            .collect()
+    }
+
+    fn run(&self, inputs: &[Vec<T>]) -> EncoderPass<T> {
+        let attended = self.attention.forward_self(inputs);
+        let residual: Vec<Vec<T>> = inputs.iter().zip(attended.iter()).map(|(x, a)| add(x, a)).collect();
+        let hidden: Vec<Vec<T>> = residual.iter().map(|r| self.attention_norm.forward(r)).collect();
+        let pre_activation: Vec<Vec<T>> = hidden.iter().map(|h| self.feedforward_hidden.forward(h)).collect();
+        let activated: Vec<Vec<T>> = pre_activation.iter().map(|z| z.iter().map(|&x| Activation::ReLU.apply(x)).collect()).collect();
+        let feedforward_residual = hidden.iter().zip(activated.iter())
+            .map(|(h, a)| add(h, &self.feedforward_output.forward(a)))
+            .collect();
+        EncoderPass { residual, hidden, pre_activation, activated, feedforward_residual }
+    }
+
+    pub fn zero_grad(&mut self) {
+        self.attention.zero_grad();
+        self.attention_norm.zero_grad();
+        self.feedforward_hidden.zero_grad();
+        self.feedforward_output.zero_grad();
+        self.feedforward_norm.zero_grad();
+    }
+
+    /// Applies the accumulated gradients, `w -= learning_rate * grad`.
+    pub fn sgd_step(&mut self, learning_rate: T) {
+        self.attention.sgd_step(learning_rate);
+        let layers: [&mut dyn Layer<T>; 4] = [&mut self.attention_norm, &mut self.feedforward_hidden, &mut self.feedforward_output, &mut self.feedforward_norm];
+        for layer in layers {
+            for (param, grad) in layer.params_mut() {
+                *param = *param - grad * learning_rate;
+            }
+        }
+    }
+
+    /// Named views of every parameter, e.g. `attention.query.weights` or
+    /// `feedforward_norm.gamma`.
+    pub fn parameters(&self) -> Vec<Parameter<'_, T>> {
+        let attention = self.attention.parameters().into_iter().map(|p| p.prefixed("attention"));
+        let layers: [(&str, &dyn Layer<T>); 4] = [
+            ("attention_norm", &self.attention_norm),
+            ("feedforward_hidden", &self.feedforward_hidden),
+            ("feedforward_output", &self.feedforward_output),
+            ("feedforward_norm", &self.feedforward_norm),
+        ];
+        attention.chain(layers.into_iter().flat_map(|(name, layer)| layer.parameters().into_iter().map(move |p| p.prefixed(name))))
+            .collect()
+    }
+
+    /// Mutable named views of every parameter, named like `parameters`.
+    pub fn parameters_mut(&mut self) -> Vec<ParameterMut<'_, T>> {
+        let TransformerEncoderBlock { attention, attention_norm, feedforward_hidden, feedforward_output, feedforward_norm } = self;
+        let attention = attention.parameters_mut().into_iter().map(|p| p.prefixed("attention"));
+        let layers: [(&str, &mut dyn Layer<T>); 4] = [
+            ("attention_norm", attention_norm),
+            ("feedforward_hidden", feedforward_hidden),
+            ("feedforward_output", feedforward_output),
+            ("feedforward_norm", feedforward_norm),
+        ];
+        attention.chain(layers.into_iter().flat_map(|(name, layer)| layer.parameters_mut().into_iter().map(move |p| p.prefixed(name))))
+            .collect()
+    }
+}
+
+fn add<T: Number>(a: &[T], b: &[T]) -> Vec<T> {
+    a.iter().zip(b.iter()).map(|(&x, &y)| x + y).collect()
+}
+
+/// Length of the query (or, without queries, key) rows.
+fn row_width<T>(queries: &[Vec<T>], keys: &[Vec<T>]) -> usize {
+    queries.first().or(keys.first()).map_or(0, Vec::len).max(1)
+}
+
+/// Checks the shapes of an attention pass and returns the length of the value rows.
+fn validate<T>(queries: &[Vec<T>], keys: &[Vec<T>], values: &[Vec<T>]) -> Result<usize, LayerError> {
+    let dim = queries.first().or(keys.first()).map_or(0, Vec::len);
+    if let Some(row) = queries.iter().chain(keys.iter()).find(|row| row.len() != dim) {
+        return Err(LayerError::WrongLength { expected: dim, found: row.len() });
+    }
+    if keys.len() != values.len() {
+        return Err(LayerError::WrongLength { expected: keys.len(), found: values.len() });
+    }
+    let value_dim = values.first().map_or(0, Vec::len);
+    if let Some(row) = values.iter().find(|row| row.len() != value_dim) {
+        return Err(LayerError::WrongLength { expected: value_dim, found: row.len() });
+    }
+    Ok(value_dim)
+}
+
+fn dot<T: Number>(a: &[T], b: &[T]) -> T {
+    a.iter().zip(b.iter()).fold(T::zero(), |acc, (&x, &y)| acc + x * y)
+}
+
+/// `out += scale * values`.
+fn add_scaled<T: Number>(out: &mut [T], scale: T, values: &[T]) {
+    out.iter_mut().zip(values.iter()).for_each(|(o, &v)| *o = *o + scale * v);
+}
+
+/// `sum_j weights[j] * values[j]`.
+fn weighted_sum<T: Number>(weights: &[T], values: &[Vec<T>], dim: usize) -> Vec<T> {
+    let mut out = vec![T::zero(); dim];
+    for (&w, v) in weights.iter().zip(values.iter()) {
+        add_scaled(&mut out, w, v);
+    }
+    out
+}
+
+/// The columns `range` of every row.
+fn columns<T: Copy>(rows: &[Vec<T>], range: &Range<usize>) -> Vec<Vec<T>> {
+    rows.iter().map(|row| row[range.clone()].to_vec()).collect()
+}
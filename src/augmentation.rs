@@ -0,0 +1,130 @@
+//! Data augmentation for tabular batches.
+//!
+//! Each transform implements [`Augmentation`] and modifies one batch of rows (and, for
+//! mixup, their targets) in place. An [`AugmentationPipeline`] chains transforms, owns the
+//! random state, and gathers batches from the index lists produced by the samplers in
+//! `dataset` (e.g. `BucketSampler::batches` or `k_fold_indices`), so augmentation slots in
+//! between batching and `Sequential::train_epoch`:
+//!
+//! ```text
+//! for batch in sampler.batches(epoch) {
+//!     let (x, y) = pipeline.batch(&rows, &targets, &batch);
+//!     model.train_epoch(&x, &y, loss, learning_rate);
+//! }
+//! ```
+//!
+//! Augment only the training data; evaluate on the original rows.
+
+use num_traits::{FromPrimitive, ToPrimitive};
+use crate::dataset::select;
+use crate::numbers::Number;
+use crate::random::Rng;
+
+/// A random transform of one batch.
+pub trait Augmentation<T> {
+    /// Transforms `rows` (and possibly `targets`, one entry per row) in place.
+    fn apply(&self, rows: &mut [Vec<T>], targets: &mut [Vec<T>], rng: &mut Rng);
+}
+
+/// Adds independent `N(0, std^2)` noise to every feature. Targets are unchanged.
+///
+/// Noise is added on the scale of the features, so standardize them first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GaussianNoise {
+    pub std: f64,
+}
+
+impl<T: Number + FromPrimitive + ToPrimitive> Augmentation<T> for GaussianNoise {
+    fn apply(&self, rows: &mut [Vec<T>], _targets: &mut [Vec<T>], rng: &mut Rng) {
+        for value in rows.iter_mut().flatten() {
+            *value = T::to_number(value.to_f64().unwrap() + self.std * rng.next_normal());
+        }
+    }
+}
+
+/// Sets each feature to zero with probability `rate`. Targets are unchanged.
+///
+/// Unlike inverted dropout the kept features are not rescaled: a zero reads as "missing"
+/// after standardization, which teaches the model not to rely on any single column.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeatureDropout {
+    pub rate: f64,
+}
+
+impl<T: Number> Augmentation<T> for FeatureDropout {
+    fn apply(&self, rows: &mut [Vec<T>], _targets: &mut [Vec<T>], rng: &mut Rng) {
+        for value in rows.iter_mut().flatten() {
+            if rng.next_f64() < self.rate {
+                *value = T::zero();
+            }
+        }
+    }
+}
+
+/// **Mixup**: replaces every sample by a convex combination of itself and a random
+/// partner from the same batch, mixing features and targets with the same weight.
+///
+/// # Behavior
+/// - The weight `lambda` is drawn from `Beta(alpha, alpha)` once per sample; small
+///   `alpha` (e.g. `0.2`) keeps most samples close to an original one.
+/// - For regression the targets are mixed directly; for classification pass one-hot (or
+///   `0`/`1`) targets, which become soft labels suited to `CrossEntropy` or BCE.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mixup {
+    pub alpha: f64,
+}
+
+impl<T: Number + FromPrimitive> Augmentation<T> for Mixup {
+    fn apply(&self, rows: &mut [Vec<T>], targets: &mut [Vec<T>], rng: &mut Rng) {
+        assert_eq!(rows.len(), targets.len(), "rows and targets must have the same length");
+        let mut partners: Vec<usize> = (0..rows.len()).collect();
+        rng.shuffle(&mut partners);
+        // Mix from copies so that every sample is combined with an original partner
+        let (original_rows, original_targets) = (rows.to_vec(), targets.to_vec());
+        for (i, &j) in partners.iter().enumerate() {
+            let lambda: T = T::to_number(rng.next_beta(self.alpha, self.alpha));
+            let mix = |a: &mut Vec<T>, b: &[T]| {
+                for (x, &y) in a.iter_mut().zip(b.iter()) {
+                    *x = lambda * *x + (T::one() - lambda) * y;
+                }
+            };
+            mix(&mut rows[i], &original_rows[j]);
+            mix(&mut targets[i], &original_targets[j]);
+        }
+    }
+}
+
+/// A chain of augmentations applied in order, with its own seeded random state.
+pub struct AugmentationPipeline<T> {
+    transforms: Vec<Box<dyn Augmentation<T>>>,
+    rng: Rng,
+}
+
+impl<T: Clone> AugmentationPipeline<T> {
+    /// Creates an empty pipeline; equal seeds give equal augmentations.
+    pub fn new(seed: u64) -> Self {
+        AugmentationPipeline { transforms: Vec::new(), rng: Rng::new(seed) }
+    }
+
+    /// Appends a transform.
+    pub fn with(mut self, transform: impl Augmentation<T> + 'static) -> Self {
+        self.transforms.push(Box::new(transform));
+        self
+    }
+
+    /// Applies every transform to the batch in place.
+    pub fn apply(&mut self, rows: &mut [Vec<T>], targets: &mut [Vec<T>]) {
+        for transform in &self.transforms {
+            transform.apply(rows, targets, &mut self.rng);
+        }
+    }
+
+    /// Gathers the samples at `indices` and returns an augmented copy of them.
+    pub fn batch(&mut self, rows: &[Vec<T>], targets: &[Vec<T>], indices: &[usize]) -> (Vec<Vec<T>>, Vec<Vec<T>>) {
+        assert_eq!(rows.len(), targets.len(), "rows and targets must have the same length");
+        let mut batch_rows = select(rows, indices);
+        let mut batch_targets = select(targets, indices);
+        self.apply(&mut batch_rows, &mut batch_targets);
+        (batch_rows, batch_targets)
+    }
+}
@@ -1,9 +1,163 @@
 use crate::loss_fn::*;
-use crate::layers::Layer1D;
+use crate::layers::{Layer, Layer1D};
 use crate::numbers::*;
-use num_traits::FromPrimitive;
+use num_traits::{FromPrimitive, ToPrimitive};
 
-pub fn backward_pass_1d<T: Number + FromPrimitive, const OUT: usize, const IN: usize>(
+/// Backpropagates the loss of one sample through a stack of layers.
+///
+/// # Arguments
+/// * `layers` - Layers applied in order, e.g. `Sequential::layers`.
+/// * `inputs` - Input of the first layer.
+/// * `targets` - Targets for the output of the last layer.
+/// * `loss` - Loss applied to the output, reduced over its entries with
+///   `Loss::output_reduction` (averaged for regression losses, summed otherwise).
+///
+/// # Returns
+/// * The loss value. Every layer's parameter gradients have been added to its
+///   accumulated gradients; call `Layer::zero_grad` first to start from zero.
+pub fn backward_pass<T, L>(layers: &mut [L], inputs: &[T], targets: &[T], loss: Loss) -> T
+where
+    T: Real + FromPrimitive,
+    L: Layer<T>,
+{
+    backward_pass_with(layers, inputs, |outputs| {
+        let reduction = loss.output_reduction();
+        let value = loss.forward_reduced(outputs, targets, reduction).scalar().unwrap();
+        (value, loss.derivative_reduced(outputs, targets, reduction))
+    }).0
+}
+
+/// Like `backward_pass`, with the loss supplied by `output_grad`, which receives the
+/// output of the last layer and returns the loss value and its gradient.
+///
+/// # Returns
+/// * `(loss, input_grad)` - The loss and its gradient with respect to `inputs`, so the
+///   stack can itself sit under further layers (e.g. a model body feeding a `heads` head).
+pub fn backward_pass_with<T, L, F>(layers: &mut [L], inputs: &[T], output_grad: F) -> (T, Vec<T>)
+where
+    T: Number,
+    L: Layer<T>,
+    F: FnOnce(&[T]) -> (T, Vec<T>),
+{
+    let mut trace = vec![inputs.to_vec()];
+    for layer in layers.iter() {
+        let next = layer.forward(trace.last().unwrap());
+        trace.push(next);
+    }
+    let (value, mut grad) = output_grad(trace.last().unwrap());
+    for (i, layer) in layers.iter_mut().enumerate().rev() {
+        grad = layer.backward(&trace[i], &trace[i + 1], &grad);
+    }
+    (value, grad)
+}
+
+/// Analytic gradients of `backward_pass` next to their central-difference estimates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GradientCheck {
+    /// Parameter gradients from backpropagation, layer by layer in `Layer::params_mut` order.
+    pub analytic: Vec<f64>,
+    /// Central-difference estimates of the same parameter gradients.
+    pub numeric: Vec<f64>,
+    /// Gradient with respect to the inputs from backpropagation.
+    pub analytic_input: Vec<f64>,
+    /// Central-difference estimate of the input gradient.
+    pub numeric_input: Vec<f64>,
+}
+
+impl GradientCheck {
+    /// Largest `|a - n| / max(1, |a|, |n|)` over all parameters and inputs: the absolute
+    /// error for small gradients and the relative error for large ones.
+    pub fn max_error(&self) -> f64 {
+        let error = |(a, n): (&f64, &f64)| (a - n).abs() / 1f64.max(a.abs()).max(n.abs());
+        self.analytic.iter().zip(self.numeric.iter())
+            .chain(self.analytic_input.iter().zip(self.numeric_input.iter()))
+            .map(error)
+            .fold(0.0, f64::max)
+    }
+
+    /// Whether `max_error` is at most `tolerance`.
+    pub fn passes(&self, tolerance: f64) -> bool {
+        self.max_error() <= tolerance
+    }
+}
+
+/// Compares the gradients of `backward_pass` with central finite differences.
+///
+/// # Arguments
+/// * `layers` - Any stack of `Layer`s, e.g. `Sequential::layers`.
+/// * `inputs`, `targets`, `loss` - One sample, as for `backward_pass`.
+/// * `epsilon` - Step of the central differences, e.g. `1e-6` for `f64`.
+///
+/// # Steps
+/// 1. Run `backward_pass` from zeroed gradients and read the parameter gradients back
+///    through `Layer::params_mut`.
+/// 2. For every parameter `w`, estimate `(L(w + eps) - L(w - eps)) / (2 eps)` and restore `w`.
+/// 3. Do the same for every input.
+///
+/// # Notes
+/// - The parameters are left unchanged; the accumulated gradients hold the analytic ones.
+/// - The estimate is wrong within `epsilon` of a kink (`ReLU` at zero, hinge margins), so
+///   check kinked models on inputs away from them.
+pub fn gradient_check<T, L>(layers: &mut [L], inputs: &[T], targets: &[T], loss: Loss, epsilon: f64) -> GradientCheck
+where
+    T: Real + FromPrimitive + ToPrimitive,
+    L: Layer<T>,
+{
+    let reduction = loss.output_reduction();
+    let loss_at = |layers: &[L], inputs: &[T]| -> f64 {
+        let outputs = layers.iter().fold(inputs.to_vec(), |values, layer| layer.forward(&values));
+        loss.forward_reduced(&outputs, targets, reduction).scalar().unwrap().to_f64().unwrap()
+    };
+    let central = |plus: f64, minus: f64| (plus - minus) / (2.0 * epsilon);
+    let step = T::to_number::<T>(epsilon);
+
+    // Step 1: Analytic gradients
+    layers.iter_mut().for_each(|layer| layer.zero_grad());
+    let (_, input_grad) = backward_pass_with(layers, inputs, |outputs| {
+        let value = loss.forward_reduced(outputs, targets, reduction).scalar().unwrap();
+        (value, loss.derivative_reduced(outputs, targets, reduction))
+    });
+    let to_f64 = |v: T| v.to_f64().unwrap();
+    let analytic: Vec<f64> = layers.iter_mut().flat_map(|layer| layer.params_mut()).map(|(_, g)| to_f64(g)).collect();
+
+    // Step 2: Parameter estimates
+    let mut numeric = Vec::with_capacity(analytic.len());
+    for k in 0..analytic.len() {
+        let original = *param_at(layers, k);
+        *param_at(layers, k) = original + step;
+        let plus = loss_at(layers, inputs);
+        *param_at(layers, k) = original - step;
+        let minus = loss_at(layers, inputs);
+        *param_at(layers, k) = original;
+        numeric.push(central(plus, minus));
+    }
+
+    // Step 3: Input estimates
+    let mut shifted = inputs.to_vec();
+    let numeric_input = (0..inputs.len()).map(|j| {
+        shifted[j] = inputs[j] + step;
+        let plus = loss_at(layers, &shifted);
+        shifted[j] = inputs[j] - step;
+        let minus = loss_at(layers, &shifted);
+        shifted[j] = inputs[j];
+        central(plus, minus)
+    }).collect();
+
+    GradientCheck { analytic, numeric, analytic_input: input_grad.into_iter().map(to_f64).collect(), numeric_input }
+}
+
+/// The `k`-th parameter of `layers`, counting across layers in `params_mut` order.
+fn param_at<T: Number, L: Layer<T>>(layers: &mut [L], k: usize) -> &mut T {
+    layers.iter_mut().flat_map(|layer| layer.params_mut()).nth(k).expect("parameter index out of range").0
+}
+
+/// Applies the loss gradient of `predictions` directly to every layer's weights and biases.
+///
+/// This is the original placeholder update: it does not use the layer inputs, so the
+/// weight gradients are not real backpropagation. `Layer1D` implements `Layer`, so use
+/// `backward_pass` followed by an optimizer step (or `Layer::params_mut`) instead.
+#[deprecated(note = "not real backpropagation; use `backward_pass` with the layer inputs")]
+pub fn backward_pass_1d<T: Real + FromPrimitive, const OUT: usize, const IN: usize>(
     layers: &mut [Layer1D<T, OUT, IN>],
     loss_fn: Loss,
     predictions: &[T],
@@ -21,8 +175,6 @@ pub fn backward_pass_1d<T: Number + FromPrimitive, const OUT: usize, const IN: u
     );
 
     // Build simple bias gradients = gradients and replicate to form weight gradients.
-    // NOTE: without activations/inputs this is a placeholder update strategy;
-    // for real backprop you must compute weight gradients from upstream gradients and layer inputs.
     let mut weight_grads = [[T::zero(); IN]; OUT];
     let mut bias_grads = [T::zero(); OUT];
 
@@ -38,4 +190,4 @@ pub fn backward_pass_1d<T: Number + FromPrimitive, const OUT: usize, const IN: u
     for layer in layers.iter_mut().rev() {
         layer.update_weights(&weight_grads, &bias_grads, lr);
     }
-}
\ No newline at end of file
+}
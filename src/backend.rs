@@ -0,0 +1,302 @@
+//! Compute backends for the dense kernels.
+//!
+//! Layers do not loop over their weights themselves: `Dense` and `Sequential` hand the
+//! matrix-vector products, the gradient outer products, the batched matrix products and
+//! the element-wise maps to a [`Backend`]. A faster implementation (SIMD, BLAS, later a
+//! GPU) then only has to provide these kernels, and every layer picks it up unchanged.
+//!
+//! - [`Naive`]: straightforward loops; the default, used by `Dense::forward` and training.
+//! - [`Unrolled`]: dot products split over four independent accumulators, which the
+//!   compiler turns into SIMD instructions on wide layers. Results can differ from
+//!   `Naive` in the last bits because the sums are added in a different order.
+//! - `MatrixMultiply` (feature `matrixmultiply`): the batched products on the
+//!   `matrixmultiply` crate's GEMM kernels, for `f32` and `f64`.
+//!
+//! ```
+//! use neuralnet::backend::Unrolled;
+//! use neuralnet::model::{ModelBuilder, Workspace};
+//!
+//! let model = ModelBuilder::new(3).dense(4).relu().dense(2).build::<f64>().unwrap();
+//! let mut workspace = Workspace::new();
+//! let outputs = model.forward_on(&Unrolled, &[0.1, 0.2, 0.3], &mut workspace);
+//! assert_eq!(outputs.len(), 2);
+//! ```
+
+use crate::numbers::Number;
+
+/// Kernels used by the dense layers. Weight matrices are row-major `Vec<Vec<T>>` with
+/// `weights[i][j]` the weight from input `j` to output `i`, as in `Dense`.
+///
+/// Implementations may assume the shapes fit: callers check them first.
+pub trait Backend {
+    /// `out = biases + W * inputs`, replacing the contents of `out`.
+    fn matvec<T: Number>(&self, weights: &[Vec<T>], biases: &[T], inputs: &[T], out: &mut Vec<T>);
+
+    /// `out = W^T * grad`, replacing the contents of `out` with `input_dim` values; the
+    /// gradient passed to the previous layer in the backward pass.
+    fn matvec_transposed<T: Number>(&self, weights: &[Vec<T>], grad: &[T], input_dim: usize, out: &mut Vec<T>);
+
+    /// `acc += grad * inputs^T`, accumulating the weight gradient of a dense layer.
+    fn add_outer<T: Number>(&self, acc: &mut [Vec<T>], grad: &[T], inputs: &[T]);
+
+    /// `c += a * b` for row-major matrices: `a` is `m x k`, `b` is `k x n` and `c` is
+    /// `m x n`, with `shape = (m, k, n)`. Used by the batched dense passes, where `m`
+    /// is the batch size.
+    fn gemm<T: Number>(&self, a: &[T], b: &[T], shape: (usize, usize, usize), c: &mut [T]) {
+        let (m, k, n) = shape;
+        for i in 0..m {
+            for j in 0..n {
+                let dot = (0..k).fold(T::zero(), |acc, p| acc + a[i * k + p] * b[p * n + j]);
+                c[i * n + j] = c[i * n + j] + dot;
+            }
+        }
+    }
+
+    /// `c += a * b` like `gemm`, reading `a` and `b` through `(row, column)` strides:
+    /// element `(i, p)` of `a` is `a[i * a_strides.0 + p * a_strides.1]`, and likewise for
+    /// `b`. `c` stays row-major. A transposed operand is then just swapped strides, with
+    /// no copy; `gemm` is the case `a_strides = (k, 1)`, `b_strides = (n, 1)`.
+    fn gemm_strided<T: Number>(
+        &self, a: &[T], a_strides: (usize, usize), b: &[T], b_strides: (usize, usize), shape: (usize, usize, usize), c: &mut [T],
+    ) {
+        gemm_strided_loops(a, a_strides, b, b_strides, shape, c);
+    }
+
+    /// Applies `f` to every value in place, e.g. an activation function.
+    fn map_in_place<T: Number, F: Fn(T) -> T>(&self, values: &mut [T], f: F) {
+        values.iter_mut().for_each(|x| *x = f(*x));
+    }
+}
+
+/// Default `Backend::gemm_strided`: one dot product per output, in index order.
+fn gemm_strided_loops<T: Number>(
+    a: &[T], a_strides: (usize, usize), b: &[T], b_strides: (usize, usize), shape: (usize, usize, usize), c: &mut [T],
+) {
+    let (m, k, n) = shape;
+    for i in 0..m {
+        for j in 0..n {
+            let dot = (0..k).fold(T::zero(), |acc, p| acc + a[i * a_strides.0 + p * a_strides.1] * b[p * b_strides.0 + j * b_strides.1]);
+            c[i * n + j] = c[i * n + j] + dot;
+        }
+    }
+}
+
+/// Plain loops in index order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Naive;
+
+impl Backend for Naive {
+    fn matvec<T: Number>(&self, weights: &[Vec<T>], biases: &[T], inputs: &[T], out: &mut Vec<T>) {
+        out.clear();
+        out.extend(weights.iter().zip(biases.iter())
+            .map(|(row, &bias)| row.iter().zip(inputs.iter()).fold(bias, |acc, (&w, &x)| acc + w * x)));
+    }
+
+    fn matvec_transposed<T: Number>(&self, weights: &[Vec<T>], grad: &[T], input_dim: usize, out: &mut Vec<T>) {
+        out.clear();
+        out.resize(input_dim, T::zero());
+        for (row, &g) in weights.iter().zip(grad.iter()) {
+            for (o, &w) in out.iter_mut().zip(row.iter()) {
+                *o = *o + w * g;
+            }
+        }
+    }
+
+    fn add_outer<T: Number>(&self, acc: &mut [Vec<T>], grad: &[T], inputs: &[T]) {
+        for (row, &g) in acc.iter_mut().zip(grad.iter()) {
+            for (a, &x) in row.iter_mut().zip(inputs.iter()) {
+                *a = *a + g * x;
+            }
+        }
+    }
+}
+
+/// Loops unrolled over four lanes so the compiler can vectorize them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Unrolled;
+
+const LANES: usize = 4;
+
+/// Dot product with one accumulator per lane, summed at the end.
+fn dot_unrolled<T: Number>(a: &[T], b: &[T]) -> T {
+    let mut lanes = [T::zero(); LANES];
+    let (a_chunks, b_chunks) = (a.chunks_exact(LANES), b.chunks_exact(LANES));
+    let tail = a_chunks.remainder().iter().zip(b_chunks.remainder().iter())
+        .fold(T::zero(), |acc, (&x, &y)| acc + x * y);
+    for (x, y) in a_chunks.zip(b_chunks) {
+        for k in 0..LANES {
+            lanes[k] = lanes[k] + x[k] * y[k];
+        }
+    }
+    (lanes[0] + lanes[1]) + (lanes[2] + lanes[3]) + tail
+}
+
+/// `out += scale * values`, four lanes at a time.
+fn axpy_unrolled<T: Number>(out: &mut [T], scale: T, values: &[T]) {
+    let mut out_chunks = out.chunks_exact_mut(LANES);
+    let mut value_chunks = values.chunks_exact(LANES);
+    for (o, v) in (&mut out_chunks).zip(&mut value_chunks) {
+        for k in 0..LANES {
+            o[k] = o[k] + scale * v[k];
+        }
+    }
+    for (o, &v) in out_chunks.into_remainder().iter_mut().zip(value_chunks.remainder().iter()) {
+        *o = *o + scale * v;
+    }
+}
+
+impl Backend for Unrolled {
+    fn matvec<T: Number>(&self, weights: &[Vec<T>], biases: &[T], inputs: &[T], out: &mut Vec<T>) {
+        out.clear();
+        out.extend(weights.iter().zip(biases.iter()).map(|(row, &bias)| bias + dot_unrolled(row, inputs)));
+    }
+
+    fn matvec_transposed<T: Number>(&self, weights: &[Vec<T>], grad: &[T], input_dim: usize, out: &mut Vec<T>) {
+        out.clear();
+        out.resize(input_dim, T::zero());
+        for (row, &g) in weights.iter().zip(grad.iter()) {
+            axpy_unrolled(out, g, row);
+        }
+    }
+
+    fn add_outer<T: Number>(&self, acc: &mut [Vec<T>], grad: &[T], inputs: &[T]) {
+        for (row, &g) in acc.iter_mut().zip(grad.iter()) {
+            axpy_unrolled(row, g, inputs);
+        }
+    }
+
+    /// Row by row: every row of `c` gains `a[i][p]` times row `p` of `b`, so all three
+    /// matrices are read sequentially.
+    fn gemm<T: Number>(&self, a: &[T], b: &[T], shape: (usize, usize, usize), c: &mut [T]) {
+        let (m, k, n) = shape;
+        if n == 0 {
+            return;
+        }
+        for (a_row, c_row) in a.chunks_exact(k.max(1)).zip(c.chunks_exact_mut(n)).take(m) {
+            for (&scale, b_row) in a_row.iter().zip(b.chunks_exact(n)).take(k) {
+                axpy_unrolled(c_row, scale, b_row);
+            }
+        }
+    }
+
+    /// `gemm` when both operands are row-major, the plain loops otherwise.
+    fn gemm_strided<T: Number>(
+        &self, a: &[T], a_strides: (usize, usize), b: &[T], b_strides: (usize, usize), shape: (usize, usize, usize), c: &mut [T],
+    ) {
+        let (_, k, n) = shape;
+        if a_strides == (k, 1) && b_strides == (n, 1) {
+            self.gemm(a, b, shape, c);
+        } else {
+            gemm_strided_loops(a, a_strides, b, b_strides, shape, c);
+        }
+    }
+}
+
+/// Matrix products through the `matrixmultiply` crate's blocked, SIMD `sgemm`/`dgemm`
+/// kernels, which read strided operands directly. The vector kernels are those of
+/// `Unrolled`, as are the products for number types other than `f32` and `f64`.
+#[cfg(feature = "matrixmultiply")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MatrixMultiply;
+
+#[cfg(feature = "matrixmultiply")]
+impl Backend for MatrixMultiply {
+    fn matvec<T: Number>(&self, weights: &[Vec<T>], biases: &[T], inputs: &[T], out: &mut Vec<T>) {
+        Unrolled.matvec(weights, biases, inputs, out);
+    }
+
+    fn matvec_transposed<T: Number>(&self, weights: &[Vec<T>], grad: &[T], input_dim: usize, out: &mut Vec<T>) {
+        Unrolled.matvec_transposed(weights, grad, input_dim, out);
+    }
+
+    fn add_outer<T: Number>(&self, acc: &mut [Vec<T>], grad: &[T], inputs: &[T]) {
+        Unrolled.add_outer(acc, grad, inputs);
+    }
+
+    fn gemm<T: Number>(&self, a: &[T], b: &[T], shape: (usize, usize, usize), c: &mut [T]) {
+        let (_, k, n) = shape;
+        self.gemm_strided(a, (k, 1), b, (n, 1), shape, c);
+    }
+
+    fn gemm_strided<T: Number>(
+        &self, a: &[T], a_strides: (usize, usize), b: &[T], b_strides: (usize, usize), shape: (usize, usize, usize), c: &mut [T],
+    ) {
+        let done = gemm_kernel::<T, f32>(a, a_strides, b, b_strides, shape, c)
+            || gemm_kernel::<T, f64>(a, a_strides, b, b_strides, shape, c);
+        if !done {
+            Unrolled.gemm_strided(a, a_strides, b, b_strides, shape, c);
+        }
+    }
+}
+
+/// Number types with a `matrixmultiply` GEMM kernel.
+#[cfg(feature = "matrixmultiply")]
+trait GemmKernel: Number {
+    /// `c = a * b + c` on raw strided operands, as `matrixmultiply::sgemm`/`dgemm`.
+    ///
+    /// # Safety
+    /// Every element addressed by the shape and strides must lie inside its allocation,
+    /// and `c` must not alias `a` or `b`.
+    unsafe fn gemm(
+        shape: (usize, usize, usize), a: *const Self, a_strides: (isize, isize), b: *const Self, b_strides: (isize, isize), c: *mut Self, c_strides: (isize, isize),
+    );
+}
+
+#[cfg(feature = "matrixmultiply")]
+impl GemmKernel for f32 {
+    unsafe fn gemm(
+        (m, k, n): (usize, usize, usize), a: *const f32, a_strides: (isize, isize), b: *const f32, b_strides: (isize, isize), c: *mut f32, c_strides: (isize, isize),
+    ) {
+        // SAFETY: upheld by the caller
+        unsafe { matrixmultiply::sgemm(m, k, n, 1.0, a, a_strides.0, a_strides.1, b, b_strides.0, b_strides.1, 1.0, c, c_strides.0, c_strides.1) }
+    }
+}
+
+#[cfg(feature = "matrixmultiply")]
+impl GemmKernel for f64 {
+    unsafe fn gemm(
+        (m, k, n): (usize, usize, usize), a: *const f64, a_strides: (isize, isize), b: *const f64, b_strides: (isize, isize), c: *mut f64, c_strides: (isize, isize),
+    ) {
+        // SAFETY: upheld by the caller
+        unsafe { matrixmultiply::dgemm(m, k, n, 1.0, a, a_strides.0, a_strides.1, b, b_strides.0, b_strides.1, 1.0, c, c_strides.0, c_strides.1) }
+    }
+}
+
+/// Panics unless element `(rows - 1, columns - 1)` of a strided matrix lies in `values`,
+/// which keeps the raw-pointer kernels in bounds.
+#[cfg(feature = "matrixmultiply")]
+fn check_strided<T>(values: &[T], strides: (usize, usize), rows: usize, columns: usize) {
+    if rows > 0 && columns > 0 {
+        assert!((rows - 1) * strides.0 + (columns - 1) * strides.1 < values.len(), "strided matrix out of bounds");
+    }
+}
+
+/// `c += a * b` on `K`'s kernel if `T` is `K`, with operands and shape as in
+/// `Backend::gemm_strided`; returns `false` without touching `c` otherwise.
+#[cfg(feature = "matrixmultiply")]
+fn gemm_kernel<T: Number, K: GemmKernel>(
+    a: &[T], a_strides: (usize, usize), b: &[T], b_strides: (usize, usize), shape: (usize, usize, usize), c: &mut [T],
+) -> bool {
+    if core::any::TypeId::of::<T>() != core::any::TypeId::of::<K>() {
+        return false;
+    }
+    let (m, k, n) = shape;
+    check_strided(a, a_strides, m, k);
+    check_strided(b, b_strides, k, n);
+    check_strided(c, (n, 1), m, n);
+    if m == 0 || n == 0 {
+        return true;
+    }
+    // SAFETY: `T` and `K` are the same type, so the pointer casts change nothing; the
+    // checks above keep every accessed element inside the slices, and `c` is borrowed
+    // mutably so it cannot alias `a` or `b`.
+    unsafe {
+        K::gemm(
+            shape,
+            a.as_ptr().cast(), (a_strides.0 as isize, a_strides.1 as isize),
+            b.as_ptr().cast(), (b_strides.0 as isize, b_strides.1 as isize),
+            c.as_mut_ptr().cast(), (n as isize, 1),
+        );
+    }
+    true
+}
@@ -0,0 +1,240 @@
+//! Probability calibration for binary classifiers.
+//!
+//! A sigmoid output trained to minimize a loss is often over- or under-confident. A
+//! calibration map, fitted on held-out validation scores, turns those scores into
+//! probabilities that match the observed frequencies:
+//! - [`PlattScaling`] fits a logistic curve on the logit of the score (two parameters,
+//!   suited to small validation sets).
+//! - [`IsotonicRegression`] fits a non-decreasing piecewise-linear map (no shape assumption,
+//!   needs more data).
+//!
+//! [`Calibrator`] selects either one and wraps a model's `predict_proba`.
+
+use serde::{Deserialize, Serialize};
+use num_traits::ToPrimitive;
+use crate::numbers::Number;
+
+/// Scores are clamped into `[EPS, 1 - EPS]` before taking their logit.
+const EPS: f64 = 1e-12;
+
+fn logit(p: f64) -> f64 {
+    let p = p.clamp(EPS, 1.0 - EPS);
+    (p / (1.0 - p)).ln()
+}
+
+fn sigmoid(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+fn to_f64s<T: Number + ToPrimitive>(scores: &[T]) -> Vec<f64> {
+    scores.iter().map(|s| s.to_f64().unwrap()).collect()
+}
+
+/// Platt scaling: `p = sigmoid(a * logit(score) + b)`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PlattScaling {
+    pub a: f64,
+    pub b: f64,
+}
+
+impl PlattScaling {
+    /// Fits `a` and `b` by Newton's method on the log loss of the validation `scores`
+    /// (probabilities in `[0, 1]`) against their `targets`.
+    ///
+    /// # Notes
+    /// - Following Platt, the targets are smoothed to `(n+ + 1) / (n+ + 2)` and
+    ///   `1 / (n- + 2)`, which keeps the fit finite on separable data.
+    /// - Panics if the slices differ in length or are empty.
+    pub fn fit<T: Number + ToPrimitive>(scores: &[T], targets: &[bool]) -> Self {
+        assert_eq!(scores.len(), targets.len(), "scores and targets must have the same length");
+        assert!(!scores.is_empty(), "calibration needs at least one sample");
+        let x: Vec<f64> = to_f64s(scores).into_iter().map(logit).collect();
+        let positives = targets.iter().filter(|&&t| t).count() as f64;
+        let negatives = targets.len() as f64 - positives;
+        let (high, low) = ((positives + 1.0) / (positives + 2.0), 1.0 / (negatives + 2.0));
+        let t: Vec<f64> = targets.iter().map(|&y| if y { high } else { low }).collect();
+
+        let loss = |a: f64, b: f64| x.iter().zip(t.iter()).map(|(&x, &t)| {
+            // log(1 + e^z) - t * z, computed without overflow
+            let z = a * x + b;
+            z.max(0.0) + (-z.abs()).exp().ln_1p() - t * z
+        }).sum::<f64>();
+
+        let (mut a, mut b) = (1.0, 0.0);
+        let mut current = loss(a, b);
+        for _ in 0..100 {
+            // Step 1: Gradient and Hessian of the log loss
+            let (mut ga, mut gb, mut haa, mut hab, mut hbb) = (0.0, 0.0, 1e-12, 0.0, 1e-12);
+            for (&x, &t) in x.iter().zip(t.iter()) {
+                let p = sigmoid(a * x + b);
+                let w = p * (1.0 - p);
+                ga += (p - t) * x;
+                gb += p - t;
+                haa += w * x * x;
+                hab += w * x;
+                hbb += w;
+            }
+            if ga.abs() < 1e-10 && gb.abs() < 1e-10 {
+                break;
+            }
+
+            // Step 2: Newton direction, halved until the loss decreases
+            let det = haa * hbb - hab * hab;
+            let (da, db) = ((hbb * ga - hab * gb) / det, (haa * gb - hab * ga) / det);
+            let mut step = 1.0;
+            while step > 1e-10 {
+                let next = loss(a - step * da, b - step * db);
+                if next < current {
+                    a -= step * da;
+                    b -= step * db;
+                    current = next;
+                    break;
+                }
+                step /= 2.0;
+            }
+            if step <= 1e-10 {
+                break;
+            }
+        }
+        PlattScaling { a, b }
+    }
+
+    pub fn calibrate(&self, score: f64) -> f64 {
+        sigmoid(self.a * logit(score) + self.b)
+    }
+}
+
+/// Isotonic regression: a non-decreasing, piecewise-linear map from score to probability.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IsotonicRegression {
+    /// Increasing scores at which the map is known; empty only before fitting.
+    pub thresholds: Vec<f64>,
+    /// Calibrated probability at every threshold, non-decreasing.
+    pub values: Vec<f64>,
+}
+
+impl IsotonicRegression {
+    /// Fits the map with the pool-adjacent-violators algorithm: the validation samples are
+    /// sorted by score and neighbouring blocks whose mean target decreases are merged.
+    /// Every block contributes one point at its mean score.
+    ///
+    /// Panics if the slices differ in length or are empty.
+    pub fn fit<T: Number + ToPrimitive>(scores: &[T], targets: &[bool]) -> Self {
+        assert_eq!(scores.len(), targets.len(), "scores and targets must have the same length");
+        assert!(!scores.is_empty(), "calibration needs at least one sample");
+        let scores = to_f64s(scores);
+        let mut order: Vec<usize> = (0..scores.len()).collect();
+        order.sort_by(|&i, &j| scores[i].partial_cmp(&scores[j]).unwrap_or(std::cmp::Ordering::Equal));
+
+        // (sum of scores, sum of targets, count) per block
+        let mut blocks: Vec<(f64, f64, f64)> = Vec::new();
+        for i in order {
+            blocks.push((scores[i], if targets[i] { 1.0 } else { 0.0 }, 1.0));
+            while blocks.len() > 1 {
+                let (s2, t2, n2) = blocks[blocks.len() - 1];
+                let (s1, t1, n1) = blocks[blocks.len() - 2];
+                if t1 / n1 < t2 / n2 {
+                    break;
+                }
+                blocks.pop();
+                *blocks.last_mut().unwrap() = (s1 + s2, t1 + t2, n1 + n2);
+            }
+        }
+        let thresholds = blocks.iter().map(|(s, _, n)| s / n).collect();
+        let values = blocks.iter().map(|(_, t, n)| t / n).collect();
+        IsotonicRegression { thresholds, values }
+    }
+
+    /// Interpolates linearly between the fitted points; scores outside the fitted range
+    /// get the value of the nearest end.
+    pub fn calibrate(&self, score: f64) -> f64 {
+        let (x, y) = (&self.thresholds, &self.values);
+        let upper = x.partition_point(|&t| t < score);
+        if upper == 0 {
+            return y[0];
+        }
+        if upper == x.len() {
+            return y[x.len() - 1];
+        }
+        let (x0, x1) = (x[upper - 1], x[upper]);
+        let weight = if x1 > x0 { (score - x0) / (x1 - x0) } else { 1.0 };
+        y[upper - 1] + weight * (y[upper] - y[upper - 1])
+    }
+}
+
+/// Which calibration map `Calibrator::fit` builds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CalibrationMethod {
+    Platt,
+    Isotonic,
+}
+
+/// A fitted calibration map for the positive-class probability of a binary classifier.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Calibrator {
+    Platt(PlattScaling),
+    Isotonic(IsotonicRegression),
+}
+
+impl Calibrator {
+    /// Fits `method` on validation `scores` (uncalibrated probabilities) and `targets`.
+    /// The validation samples must not have been used for training.
+    pub fn fit<T: Number + ToPrimitive>(method: CalibrationMethod, scores: &[T], targets: &[bool]) -> Self {
+        match method {
+            CalibrationMethod::Platt => Calibrator::Platt(PlattScaling::fit(scores, targets)),
+            CalibrationMethod::Isotonic => Calibrator::Isotonic(IsotonicRegression::fit(scores, targets)),
+        }
+    }
+
+    /// Calibrated probability for an uncalibrated `score`.
+    pub fn calibrate(&self, score: f64) -> f64 {
+        match self {
+            Calibrator::Platt(platt) => platt.calibrate(score),
+            Calibrator::Isotonic(isotonic) => isotonic.calibrate(score),
+        }
+    }
+
+    /// Wraps a model's `predict_proba` so it returns calibrated probabilities.
+    pub fn wrap<'a, T, F>(&'a self, predict_proba: F) -> impl Fn(&[T]) -> f64 + 'a
+    where
+        T: Number + ToPrimitive,
+        F: Fn(&[T]) -> T + 'a,
+    {
+        move |features| self.calibrate(predict_proba(features).to_f64().unwrap())
+    }
+
+    /// Runs `predict_proba` on every row and returns its calibrated probability.
+    pub fn predict_proba<T, F>(&self, mut predict_proba: F, features: &[Vec<T>]) -> Vec<f64>
+    where
+        T: Number + ToPrimitive,
+        F: FnMut(&[T]) -> T,
+    {
+        features.iter().map(|row| self.calibrate(predict_proba(row).to_f64().unwrap())).collect()
+    }
+}
+
+/// Mean squared difference between `probabilities` and the 0/1 `targets`.
+pub fn brier_score(probabilities: &[f64], targets: &[bool]) -> f64 {
+    assert_eq!(probabilities.len(), targets.len(), "probabilities and targets must have the same length");
+    let total = probabilities.iter().zip(targets.iter())
+        .map(|(&p, &t)| (p - if t { 1.0 } else { 0.0 }).powi(2))
+        .sum::<f64>();
+    total / targets.len().max(1) as f64
+}
+
+/// Expected calibration error over `n_bins` equal-width probability bins: the
+/// sample-weighted mean of `|mean probability - positive rate|` per bin.
+/// Panics if `n_bins == 0`.
+pub fn expected_calibration_error(probabilities: &[f64], targets: &[bool], n_bins: usize) -> f64 {
+    assert_eq!(probabilities.len(), targets.len(), "probabilities and targets must have the same length");
+    assert!(n_bins > 0, "n_bins must be positive");
+    // (sum of probabilities, positives) per bin; `n_b |mean_p - rate| = |sum_p - positives|`
+    let mut bins = vec![(0.0, 0.0); n_bins];
+    for (&p, &t) in probabilities.iter().zip(targets.iter()) {
+        let bin = ((p * n_bins as f64) as usize).min(n_bins - 1);
+        bins[bin].0 += p;
+        bins[bin].1 += if t { 1.0 } else { 0.0 };
+    }
+    let total = bins.iter().map(|&(p, t)| (p - t).abs()).sum::<f64>();
+    total / targets.len().max(1) as f64
+}
@@ -0,0 +1,139 @@
+//! Declarative experiment definitions.
+//!
+//! A [`ModelConfig`] describes the architecture, the loss, the optimizer and the
+//! training hyperparameters of an experiment, and is read from JSON or TOML:
+//!
+//! ```toml
+//! seed = 7
+//! layers = [{ Dense = { units = 8 } }, { Activation = "ReLU" }, { Dense = { units = 3 } }, "Softmax"]
+//! loss = "CrossEntropy"
+//! optimizer = { Sgd = { learning_rate = 0.05 } }
+//!
+//! [training]
+//! epochs = 50
+//! shuffle = true
+//! ```
+//!
+//! `input_dim` may be left out when it is only known from the data; see
+//! [`ModelConfig::build_for_input`].
+
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use num_traits::FromPrimitive;
+use crate::dataset::LastBatch;
+use crate::loss_fn::Loss;
+use crate::model::{BuildError, Initializer, LayerSpec, ModelBuilder, Sequential};
+use crate::numbers::Number;
+use crate::training::Trainer;
+
+/// Optimizer settings.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum OptimizerConfig {
+    /// Plain (mini-batch) SGD.
+    Sgd { learning_rate: f64 },
+}
+
+/// Training-loop hyperparameters.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TrainingConfig {
+    pub epochs: usize,
+    /// Reshuffle the samples before every epoch (seeded with `ModelConfig::seed`).
+    #[serde(default)]
+    pub shuffle: bool,
+    /// Samples per SGD step; one when absent. See `Trainer::batch_size`.
+    #[serde(default)]
+    pub batch_size: Option<usize>,
+    /// Final-batch policy; see `Trainer::last_batch`.
+    #[serde(default)]
+    pub last_batch: LastBatch,
+}
+
+/// A complete experiment definition.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelConfig {
+    /// Number of input features; inferred from the first dense layer's `inputs` when absent.
+    #[serde(default)]
+    pub input_dim: Option<usize>,
+    pub layers: Vec<LayerSpec>,
+    pub loss: Loss,
+    pub optimizer: OptimizerConfig,
+    pub training: TrainingConfig,
+    /// Seed for weight initialization and shuffling.
+    #[serde(default)]
+    pub seed: u64,
+    /// Weight initialization; chosen per layer from its activation when absent.
+    #[serde(default)]
+    pub init: Initializer,
+}
+
+impl ModelConfig {
+    pub fn from_json_str(text: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_str(text)?)
+    }
+
+    pub fn from_toml_str(text: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(toml::from_str(text)?)
+    }
+
+    /// Reads a config file, as TOML when the extension is `.toml` and as JSON otherwise.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let text = fs::read_to_string(&path)?;
+        match path.as_ref().extension().and_then(|e| e.to_str()) {
+            Some("toml") => Self::from_toml_str(&text),
+            _ => Self::from_json_str(&text),
+        }
+    }
+
+    /// Writes the config as TOML when the extension is `.toml` and as JSON otherwise.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let text = match path.as_ref().extension().and_then(|e| e.to_str()) {
+            Some("toml") => toml::to_string(self)?,
+            _ => serde_json::to_string_pretty(self)?,
+        };
+        fs::write(path, text)?;
+        Ok(())
+    }
+
+    /// Size of the model output: the units of the last dense layer.
+    pub fn output_dim(&self) -> Option<usize> {
+        self.layers.iter().rev().find_map(|spec| match spec {
+            LayerSpec::Dense { units, .. } => Some(*units),
+            _ => None,
+        })
+    }
+
+    /// Model builder holding the layers, seed and initializer of the config.
+    pub fn builder(&self) -> ModelBuilder {
+        let builder = match self.input_dim {
+            Some(dim) => ModelBuilder::new(dim),
+            None => ModelBuilder::default(),
+        };
+        self.layers.iter().fold(builder.seed(self.seed).init(self.init), |b, &spec| b.layer(spec))
+    }
+
+    /// Builds the model described by the config.
+    pub fn build<T: Number + FromPrimitive>(&self) -> Result<Sequential<T>, BuildError> {
+        self.builder().build()
+    }
+
+    /// Builds the model for `input_dim` features, e.g. the width of the training data.
+    /// Fails with `ShapeMismatch` if the config declares a different input size.
+    pub fn build_for_input<T: Number + FromPrimitive>(&self, input_dim: usize) -> Result<Sequential<T>, BuildError> {
+        if let Some(found) = self.input_dim.filter(|&d| d != input_dim) {
+            return Err(BuildError::ShapeMismatch { layer: 0, expected: input_dim, found });
+        }
+        ModelConfig { input_dim: Some(input_dim), ..self.clone() }.build()
+    }
+
+    /// Trainer running the configured loss, optimizer and training loop.
+    pub fn trainer(&self) -> Trainer {
+        let OptimizerConfig::Sgd { learning_rate } = self.optimizer;
+        let mut trainer = Trainer::new(self.loss, learning_rate, self.training.epochs).last_batch(self.training.last_batch);
+        if let Some(batch_size) = self.training.batch_size {
+            trainer = trainer.batch_size(batch_size);
+        }
+        if self.training.shuffle { trainer.shuffle(self.seed) } else { trainer }
+    }
+}
@@ -0,0 +1,120 @@
+//! Split-conformal prediction.
+//!
+//! A model is calibrated on held-out data that was not used for training. Given a
+//! miscoverage level `alpha`, the resulting intervals (regression) or label sets
+//! (classification) contain the true value with probability at least `1 - alpha`,
+//! assuming calibration and test samples are exchangeable.
+
+use serde::{Deserialize, Serialize};
+use num_traits::ToPrimitive;
+use crate::numbers::Number;
+
+/// Returns the conformal quantile of `scores`: the `ceil((n + 1) * (1 - alpha))`-th smallest score.
+///
+/// Returns `f64::INFINITY` when the calibration set is too small for the requested level,
+/// i.e. when `ceil((n + 1) * (1 - alpha)) > n`.
+/// Panics if `scores` is empty or `alpha` is not in `(0, 1)`.
+pub fn conformal_quantile(scores: &[f64], alpha: f64) -> f64 {
+    assert!(!scores.is_empty(), "conformal calibration needs at least one sample");
+    assert!(alpha > 0.0 && alpha < 1.0, "alpha must be in (0, 1)");
+    let n = scores.len();
+    let rank = ((n + 1) as f64 * (1.0 - alpha)).ceil() as usize;
+    if rank > n {
+        return f64::INFINITY;
+    }
+    let mut sorted = scores.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    sorted[rank.max(1) - 1]
+}
+
+/// Split-conformal wrapper for regression models, using absolute residuals as scores.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConformalRegressor {
+    pub alpha: f64,
+    /// Half-width of every prediction interval.
+    pub radius: f64,
+}
+
+impl ConformalRegressor {
+    /// Calibrates on model `predictions` for a calibration set with known `targets`.
+    /// Panics if the slices differ in length (see `conformal_quantile` for other panics).
+    pub fn calibrate<T: Number + ToPrimitive>(predictions: &[T], targets: &[T], alpha: f64) -> Self {
+        assert_eq!(predictions.len(), targets.len(), "predictions and targets must have the same length");
+        let scores: Vec<f64> = predictions.iter().zip(targets.iter())
+            .map(|(p, t)| (p.to_f64().unwrap() - t.to_f64().unwrap()).abs())
+            .collect();
+        ConformalRegressor { alpha, radius: conformal_quantile(&scores, alpha) }
+    }
+
+    /// Prediction interval `(lower, upper)` around a point `prediction`.
+    pub fn interval<T: Number + ToPrimitive>(&self, prediction: T) -> (f64, f64) {
+        let p = prediction.to_f64().unwrap();
+        (p - self.radius, p + self.radius)
+    }
+
+    /// Runs `predict` on every row and returns its interval.
+    pub fn predict_intervals<T, F>(&self, mut predict: F, features: &[Vec<T>]) -> Vec<(f64, f64)>
+    where
+        T: Number + ToPrimitive,
+        F: FnMut(&[T]) -> T,
+    {
+        features.iter().map(|row| self.interval(predict(row))).collect()
+    }
+}
+
+/// Split-conformal wrapper for classifiers, using `1 - p(true class)` as score.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConformalClassifier {
+    pub alpha: f64,
+    /// A class is included in the label set when `1 - p(class) <= threshold`.
+    pub threshold: f64,
+}
+
+impl ConformalClassifier {
+    /// Calibrates on predicted class `probabilities` for a calibration set with known `targets`.
+    /// Panics if the slices differ in length (see `conformal_quantile` for other panics).
+    pub fn calibrate<T: Number + ToPrimitive>(probabilities: &[Vec<T>], targets: &[usize], alpha: f64) -> Self {
+        assert_eq!(probabilities.len(), targets.len(), "probabilities and targets must have the same length");
+        let scores: Vec<f64> = probabilities.iter().zip(targets.iter())
+            .map(|(row, &t)| 1.0 - row[t].to_f64().unwrap())
+            .collect();
+        ConformalClassifier { alpha, threshold: conformal_quantile(&scores, alpha) }
+    }
+
+    /// Classes whose probability is high enough to be included in the label set, in index order.
+    /// The set may be empty for inputs unlike anything in the calibration data.
+    pub fn label_set<T: Number + ToPrimitive>(&self, probabilities: &[T]) -> Vec<usize> {
+        probabilities.iter().enumerate()
+            .filter(|(_, p)| 1.0 - p.to_f64().unwrap() <= self.threshold)
+            .map(|(class, _)| class)
+            .collect()
+    }
+
+    /// Runs `predict_proba` on every row and returns its label set.
+    pub fn predict_sets<T, F>(&self, mut predict_proba: F, features: &[Vec<T>]) -> Vec<Vec<usize>>
+    where
+        T: Number + ToPrimitive,
+        F: FnMut(&[T]) -> Vec<T>,
+    {
+        features.iter().map(|row| self.label_set(&predict_proba(row))).collect()
+    }
+}
+
+/// Fraction of `intervals` that contain the matching target.
+pub fn interval_coverage<T: Number + ToPrimitive>(intervals: &[(f64, f64)], targets: &[T]) -> f64 {
+    assert_eq!(intervals.len(), targets.len(), "intervals and targets must have the same length");
+    let covered = intervals.iter().zip(targets.iter())
+        .filter(|((lo, hi), t)| {
+            let t = t.to_f64().unwrap();
+            *lo <= t && t <= *hi
+        })
+        .count();
+    covered as f64 / targets.len().max(1) as f64
+}
+
+/// Fraction of label `sets` that contain the matching target.
+pub fn set_coverage(sets: &[Vec<usize>], targets: &[usize]) -> f64 {
+    assert_eq!(sets.len(), targets.len(), "sets and targets must have the same length");
+    let covered = sets.iter().zip(targets.iter()).filter(|(set, t)| set.contains(t)).count();
+    covered as f64 / targets.len().max(1) as f64
+}
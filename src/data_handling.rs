@@ -1,53 +1,264 @@
+use std::collections::HashMap;
 use std::error::Error;
+use std::fmt;
 use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Cursor, Read, Write};
 use std::path::Path;
+#[cfg(feature = "fetch")]
+use std::path::PathBuf;
+#[cfg(feature = "fetch")]
+use std::time::Duration;
 use csv::ReaderBuilder;
 use serde_json::Value;
 use calamine::{open_workbook_auto, Reader, DataType};
+use flate2::read::MultiGzDecoder;
+use num_traits::FromPrimitive;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use zip::ZipArchive;
+use crate::numbers::Number;
+use crate::layers::{Embedding, OovInit};
+use crate::random::Rng;
+
+/// Opens `path` for reading, decompressing it on the fly when it is compressed.
+///
+/// # Behavior
+/// - Gzip files (including concatenated members) are detected by their magic bytes
+///   `1f 8b`, whatever their extension.
+/// - Zip archives (magic `PK\x03\x04`) must contain exactly one file, which is read into
+///   memory; directories are ignored.
+/// - Anything else is read as-is.
+fn open_decompressed<P: AsRef<Path>>(path: P) -> Result<Box<dyn Read>, Box<dyn Error>> {
+    let path = path.as_ref();
+    let mut file = BufReader::new(File::open(path)?);
+    let magic = file.fill_buf()?;
+    if magic.starts_with(&[0x1f, 0x8b]) {
+        Ok(Box::new(MultiGzDecoder::new(file)))
+    } else if magic.starts_with(b"PK\x03\x04") {
+        let mut archive = ZipArchive::new(file)?;
+        let mut entries = Vec::new();
+        for i in 0..archive.len() {
+            if archive.by_index(i)?.is_file() {
+                entries.push(i);
+            }
+        }
+        if entries.len() != 1 {
+            return Err(format!("{}: zip archive must contain exactly one file, found {}", path.display(), entries.len()).into());
+        }
+        let mut data = Vec::new();
+        archive.by_index(entries[0])?.read_to_end(&mut data)?;
+        Ok(Box::new(Cursor::new(data)))
+    } else {
+        Ok(Box::new(file))
+    }
+}
 
 /// Reads a CSV file from the given path and returns its records as a vector of string vectors.
 /// 
+/// The file is comma-separated and its first row is a header, which is skipped; use
+/// `read_csv_with` for other layouts.
+/// 
 /// # Arguments
-/// * `path` - Path to the CSV file.
+/// * `path` - Path to the CSV file, optionally gzip-compressed or in a single-file zip archive.
 /// 
 /// # Returns
 /// * `Ok(Vec<Vec<String>>)` - Each inner vector represents a row of the CSV file.
 /// * `Err(Box<dyn Error>)` - If the file cannot be read or parsed.
 /// 
 pub fn read_csv<P: AsRef<Path>>(path: P) -> Result<Vec<Vec<String>>, Box<dyn Error>> {
-    // Open the file at the given path
-    let file = File::open(path)?;
-    // Create a CSV reader with headers enabled
-    let mut rdr = ReaderBuilder::new().has_headers(true).from_reader(file);
-    let mut records = Vec::new();
+    read_csv_with(path, &CsvOptions::default())
+}
+
+/// Which columns `read_csv_with` keeps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ColumnSelection {
+    All,
+    Indices(Vec<usize>),
+    Names(Vec<String>),
+}
+
+/// Layout of a CSV file for `read_csv_with`.
+///
+/// The default matches `read_csv`: comma-separated, `"`-quoted, with a header row and no
+/// comments. For a semicolon-separated export without a header:
+///
+/// ```text
+/// CsvOptions::new().delimiter(b';').has_headers(false)
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvOptions {
+    delimiter: u8,
+    quote: u8,
+    has_headers: bool,
+    comment_char: Option<u8>,
+    skip_rows: usize,
+    columns: ColumnSelection,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions { delimiter: b',', quote: b'"', has_headers: true, comment_char: None, skip_rows: 0, columns: ColumnSelection::All }
+    }
+}
+
+impl CsvOptions {
+    pub fn new() -> Self {
+        CsvOptions::default()
+    }
+
+    /// Field separator, e.g. `b';'` or `b'\t'` for TSV files.
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Quote character around fields containing the delimiter; doubled inside a field.
+    pub fn quote(mut self, quote: u8) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    /// Whether the first row (after `skip_rows`) is a header. The header is never returned.
+    pub fn has_headers(mut self, has_headers: bool) -> Self {
+        self.has_headers = has_headers;
+        self
+    }
 
-    // Iterate over each record (row) in the CSV
-    for result in rdr.records() {
+    /// Lines starting with this character are ignored.
+    pub fn comment_char(mut self, comment_char: u8) -> Self {
+        self.comment_char = Some(comment_char);
+        self
+    }
+
+    /// Number of raw lines to drop before parsing, e.g. a title above the header.
+    pub fn skip_rows(mut self, skip_rows: usize) -> Self {
+        self.skip_rows = skip_rows;
+        self
+    }
+
+    /// Keeps only the columns at `indices`, in that order.
+    pub fn columns(mut self, indices: &[usize]) -> Self {
+        self.columns = ColumnSelection::Indices(indices.to_vec());
+        self
+    }
+
+    /// Keeps only the columns with these header names, in that order. Requires a header.
+    pub fn named_columns(mut self, names: &[&str]) -> Self {
+        self.columns = ColumnSelection::Names(names.iter().map(|n| n.to_string()).collect());
+        self
+    }
+}
+
+/// Like `read_csv`, with the file layout described by `options`.
+/// 
+/// # Arguments
+/// * `path` - Path to the CSV file, optionally compressed as for `read_csv`.
+/// * `options` - Delimiter, quoting, header, comment, skipped rows and column selection.
+/// 
+/// # Returns
+/// * `Ok(Vec<Vec<String>>)` - The records after the header, reduced to the selected columns.
+/// * `Err(Box<dyn Error>)` - If the file cannot be read or parsed, a named column is not in
+///   the header (or there is no header), or a selected index is past the end of a row.
+/// 
+pub fn read_csv_with<P: AsRef<Path>>(path: P, options: &CsvOptions) -> Result<Vec<Vec<String>>, Box<dyn Error>> {
+    // Open the file, dropping the skipped lines before the CSV parser sees them
+    let mut file = BufReader::new(open_decompressed(path)?);
+    for _ in 0..options.skip_rows {
+        file.read_until(b'\n', &mut Vec::new())?;
+    }
+    let mut rdr = ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .quote(options.quote)
+        .has_headers(options.has_headers)
+        .comment(options.comment_char)
+        .from_reader(file);
+
+    // Resolve the selected columns to indices
+    let indices = match &options.columns {
+        ColumnSelection::All => None,
+        ColumnSelection::Indices(indices) => Some(indices.clone()),
+        ColumnSelection::Names(names) => {
+            if !options.has_headers {
+                return Err("named columns need a header row".into());
+            }
+            let headers = rdr.headers()?;
+            let find = |name: &String| headers.iter().position(|h| h == name).ok_or_else(|| format!("column {:?} not found in header", name));
+            Some(names.iter().map(find).collect::<Result<Vec<_>, _>>()?)
+        }
+    };
+
+    let mut records = Vec::new();
+    for (row, result) in rdr.records().enumerate() {
         let record = result?;
-        // Convert each field to String and collect into a vector
-        records.push(record.iter().map(|s| s.to_string()).collect());
+        records.push(match &indices {
+            None => record.iter().map(|s| s.to_string()).collect(),
+            Some(indices) => indices.iter()
+                .map(|&j| record.get(j).map(str::to_string).ok_or_else(|| format!("row {}: column {} out of range", row, j)))
+                .collect::<Result<Vec<_>, _>>()?,
+        });
     }
 
     Ok(records)
 }
 
+/// Writes a CSV file with an optional header row, the counterpart of `read_csv`.
+/// 
+/// # Arguments
+/// * `path` - Path of the CSV file; an existing file is overwritten.
+/// * `headers` - Column names written as the first row; pass `&[]` to omit the header.
+/// * `rows` - Records to write; fields may be strings or numbers (anything `ToString`).
+/// 
+/// # Returns
+/// * `Ok(())` - If every row was written.
+/// * `Err(Box<dyn Error>)` - If the file cannot be written, or a row has a different
+///   number of fields than the header (or the first row).
+/// 
+pub fn write_csv<P: AsRef<Path>, S: ToString>(path: P, headers: &[&str], rows: &[Vec<S>]) -> Result<(), Box<dyn Error>> {
+    let mut writer = csv::Writer::from_path(path)?;
+    if !headers.is_empty() {
+        writer.write_record(headers)?;
+    }
+    for row in rows {
+        writer.write_record(row.iter().map(|field| field.to_string()))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
 /// Reads a JSON file from the given path and returns its contents as a serde_json::Value.
 /// 
 /// # Arguments
-/// * `path` - Path to the JSON file.
+/// * `path` - Path to the JSON file, optionally gzip-compressed or in a single-file zip archive.
 /// 
 /// # Returns
 /// * `Ok(Value)` - Parsed JSON data.
 /// * `Err(Box<dyn Error>)` - If the file cannot be read or parsed.
 ///
 pub fn read_json<P: AsRef<Path>>(path: P) -> Result<Value, Box<dyn Error>> {
-    // Open the file at the given path
-    let file = File::open(path)?;
+    // Open the file at the given path, decompressing it if needed
+    let file = open_decompressed(path)?;
     // Parse the file contents as JSON
     let json: Value = serde_json::from_reader(file)?;
     Ok(json)
 }
 
+/// Writes `value` as pretty-printed JSON, the counterpart of `read_json`.
+/// 
+/// # Arguments
+/// * `path` - Path of the JSON file; an existing file is overwritten.
+/// * `value` - Any serializable value: a `serde_json::Value`, a metrics map, a `History`.
+/// 
+/// # Returns
+/// * `Ok(())` - If the file was written.
+/// * `Err(Box<dyn Error>)` - If the file cannot be written or the value cannot be serialized.
+/// 
+pub fn write_json<P: AsRef<Path>, V: Serialize + ?Sized>(path: P, value: &V) -> Result<(), Box<dyn Error>> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(&mut writer, value)?;
+    writer.flush()?;
+    Ok(())
+}
+
 /// Reads an Excel file from the given path and returns its first sheet as a vector of string vectors.
 /// 
 /// # Arguments
@@ -58,6 +269,148 @@ pub fn read_json<P: AsRef<Path>>(path: P) -> Result<Value, Box<dyn Error>> {
 /// * `Err(Box<dyn Error>)` - If the file cannot be read or parsed.
 /// 
 pub fn read_excel<P: AsRef<Path>>(path: P) -> Result<Vec<Vec<String>>, Box<dyn Error>> {
+    // Convert each typed cell to its string form
+    let rows = read_excel_typed(path)?;
+    Ok(rows.into_iter().map(|row| row.iter().map(Cell::to_string).collect()).collect())
+}
+
+/// A spreadsheet cell with its type preserved.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Empty,
+    String(String),
+    Float(f64),
+    Int(i64),
+    Bool(bool),
+    /// Excel serial date: days since 1899-12-30, with the time of day as the fraction.
+    DateTime(f64),
+    /// A formula error such as `#DIV/0!`.
+    Error(String),
+}
+
+impl Cell {
+    /// The cell as a number: floats, integers, dates and booleans (as 0 or 1) convert,
+    /// strings are parsed after trimming, anything else is `None`.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Cell::Float(f) | Cell::DateTime(f) => Some(*f),
+            Cell::Int(i) => Some(*i as f64),
+            Cell::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+            Cell::String(s) => s.trim().parse().ok(),
+            Cell::Empty | Cell::Error(_) => None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Cell::Empty)
+    }
+}
+
+impl From<&DataType> for Cell {
+    fn from(cell: &DataType) -> Self {
+        match cell {
+            DataType::Empty => Cell::Empty,
+            DataType::String(s) => Cell::String(s.clone()),
+            DataType::Float(f) => Cell::Float(*f),
+            DataType::Int(i) => Cell::Int(*i),
+            DataType::Bool(b) => Cell::Bool(*b),
+            DataType::DateTime(f) => Cell::DateTime(*f),
+            DataType::Error(e) => Cell::Error(format!("{:?}", e)),
+        }
+    }
+}
+
+/// The string form used by `read_excel`; empty cells are empty strings.
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cell::Empty => Ok(()),
+            Cell::String(s) => write!(f, "{}", s),
+            Cell::Float(x) | Cell::DateTime(x) => write!(f, "{}", x),
+            Cell::Int(i) => write!(f, "{}", i),
+            Cell::Bool(b) => write!(f, "{}", b),
+            Cell::Error(e) => write!(f, "Error: {}", e),
+        }
+    }
+}
+
+/// Reads the first sheet of an Excel file, keeping the type of every cell.
+/// 
+/// # Arguments
+/// * `path` - Path to the Excel file (.xls, .xlsx, etc.).
+/// 
+/// # Returns
+/// * `Ok(Vec<Vec<Cell>>)` - Each inner vector represents a row of the first sheet.
+/// * `Err(Box<dyn Error>)` - If the file cannot be read or parsed.
+/// 
+pub fn read_excel_typed<P: AsRef<Path>>(path: P) -> Result<Vec<Vec<Cell>>, Box<dyn Error>> {
+    Ok(read_excel_chunks(path, usize::MAX)?.flatten().collect())
+}
+
+/// Iterator over the rows of a sheet in chunks of at most `chunk_size` typed rows.
+/// 
+/// Created by `read_excel_chunks`.
+#[derive(Debug, Clone)]
+pub struct ExcelChunks {
+    range: calamine::Range<DataType>,
+    chunk_size: usize,
+    next_row: usize,
+}
+
+impl ExcelChunks {
+    /// Number of rows in the sheet, including those already yielded.
+    pub fn n_rows(&self) -> usize {
+        self.range.height()
+    }
+
+    /// Number of cells in every row.
+    pub fn n_columns(&self) -> usize {
+        self.range.width()
+    }
+}
+
+impl Iterator for ExcelChunks {
+    type Item = Vec<Vec<Cell>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (height, width) = self.range.get_size();
+        if self.next_row >= height {
+            return None;
+        }
+        let end = self.next_row.saturating_add(self.chunk_size).min(height);
+        let chunk = (self.next_row..end)
+            .map(|i| (0..width).map(|j| self.range.get((i, j)).map_or(Cell::Empty, Cell::from)).collect())
+            .collect();
+        self.next_row = end;
+        Some(chunk)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.range.height().saturating_sub(self.next_row).div_ceil(self.chunk_size);
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for ExcelChunks {}
+
+/// Opens the first sheet of an Excel file and returns an iterator over chunks of typed rows.
+/// 
+/// # Arguments
+/// * `path` - Path to the Excel file (.xls, .xlsx, etc.).
+/// * `chunk_size` - Maximum number of rows per chunk; the last chunk may be shorter.
+/// 
+/// # Returns
+/// * `Ok(ExcelChunks)` - Yields `Vec<Vec<Cell>>` chunks in sheet order.
+/// * `Err(Box<dyn Error>)` - If the file cannot be read or parsed, or `chunk_size` is zero.
+/// 
+/// # Notes
+/// - The workbook parser decodes the whole sheet when it is opened; rows are converted
+///   to `Cell`s only as their chunk is requested, so a large sheet is never held twice.
+/// 
+pub fn read_excel_chunks<P: AsRef<Path>>(path: P, chunk_size: usize) -> Result<ExcelChunks, Box<dyn Error>> {
+    if chunk_size == 0 {
+        return Err("chunk_size must be positive".into());
+    }
     // Open the Excel workbook at the given path
     let mut workbook = open_workbook_auto(path)?;
     // Get the names of all sheets in the workbook
@@ -68,23 +421,348 @@ pub fn read_excel<P: AsRef<Path>>(path: P) -> Result<Vec<Vec<String>>, Box<dyn E
     // Try to get the range (data) of the first sheet
     let range = workbook.worksheet_range(&sheet_names[0])
         .ok_or("Cannot find the first sheet")??;
+    Ok(ExcelChunks { range, chunk_size, next_row: 0 })
+}
 
-    let mut records = Vec::new();
-    // Iterate over each row in the sheet
-    for row in range.rows() {
-        // Convert each cell to String based on its type
-        let record = row.iter()
-            .map(|cell| match cell {
-                DataType::Empty => "".to_string(),
-                DataType::String(s) => s.clone(),
-                DataType::Float(f) => f.to_string(),
-                DataType::Int(i) => i.to_string(),
-                DataType::Bool(b) => b.to_string(),
-                DataType::Error(e) => format!("Error: {:?}", e),
-                DataType::DateTime(f) => f.to_string(),
-            })
-            .collect();
-        records.push(record);
-    }
+
+/// Reads a JSON file containing an array of objects and deserializes each element into `T`.
+///
+/// # Arguments
+/// * `path` - Path to the JSON file. The top-level value must be an array.
+///
+/// # Returns
+/// * `Ok(Vec<T>)` - One typed record per array element.
+/// * `Err(Box<dyn Error>)` - If the file cannot be read, is not an array, or an element does not match `T`.
+///
+pub fn read_json_records<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> Result<Vec<T>, Box<dyn Error>> {
+    // Open the file at the given path, decompressing it if needed
+    let file = open_decompressed(path)?;
+    // Deserialize directly into a vector of typed records
+    let records: Vec<T> = serde_json::from_reader(file)?;
     Ok(records)
 }
+
+/// Flattens a JSON array of objects into a feature matrix using a declared field -> column mapping.
+///
+/// # Arguments
+/// * `json` - JSON value, expected to be an array of objects.
+/// * `columns` - Field names in column order: `columns[j]` is the field stored in column `j`.
+///   Nested fields can be addressed with dots, e.g. `"features.age"`.
+///
+/// # Returns
+/// * `Ok(Vec<Vec<T>>)` - One row per object, one column per entry of `columns`.
+/// * `Err(Box<dyn Error>)` - If `json` is not an array of objects, a field is missing, or a field is not numeric.
+///
+/// # Behavior
+/// - Numbers are converted to `T` via `T::to_number`.
+/// - Booleans are converted to one (`true`) or zero (`false`).
+///
+pub fn json_to_matrix<T: Number + FromPrimitive>(json: &Value, columns: &[&str]) -> Result<Vec<Vec<T>>, Box<dyn Error>> {
+    let rows = json.as_array().ok_or("Expected a JSON array of objects")?;
+
+    let mut matrix = Vec::with_capacity(rows.len());
+    for (i, row) in rows.iter().enumerate() {
+        if !row.is_object() {
+            return Err(format!("Element {} is not a JSON object", i).into());
+        }
+        let mut values = Vec::with_capacity(columns.len());
+        for column in columns {
+            // Walk the dotted path down into nested objects
+            let field = column.split('.')
+                .try_fold(row, |value, key| value.get(key))
+                .ok_or_else(|| format!("Field '{}' missing in element {}", column, i))?;
+            let number = match field {
+                Value::Number(n) => n.as_f64().ok_or_else(|| format!("Field '{}' in element {} is not representable as f64", column, i))?,
+                Value::Bool(b) => if *b { 1.0 } else { 0.0 },
+                _ => return Err(format!("Field '{}' in element {} is not numeric", column, i).into()),
+            };
+            values.push(T::to_number(number));
+        }
+        matrix.push(values);
+    }
+    Ok(matrix)
+}
+
+/// Loads pretrained word/category embeddings from a GloVe or word2vec text file into an `Embedding`.
+///
+/// # Arguments
+/// * `path` - Path to the embedding file. Each line holds a token followed by `DIM` values
+///   separated by whitespace. An optional word2vec header line (`<count> <dim>`) is detected and skipped.
+///   Gzip-compressed files and single-file zip archives are decompressed on the fly.
+/// * `vocabulary` - Tokens in index order: row `i` of the resulting table is the vector for `vocabulary[i]`.
+/// * `oov` - Initialization used for vocabulary tokens not present in the file.
+/// * `frozen` - Whether the resulting table should be excluded from weight updates.
+///
+/// # Returns
+/// * `Ok((Embedding, Vec<usize>))` - The embedding table and the indices of out-of-vocabulary tokens.
+/// * `Err(Box<dyn Error>)` - If the file cannot be read, or a line has a vector of the wrong dimension
+///   or a non-numeric value.
+///
+/// # Behavior
+/// - The file is streamed line by line; only vectors for tokens in `vocabulary` are kept in memory,
+///   so large pretrained files can be aligned to a small vocabulary.
+/// - If a token occurs more than once in the file, the first occurrence wins.
+///
+pub fn load_pretrained_embeddings<T: Number + FromPrimitive, const DIM: usize, P: AsRef<Path>>(
+    path: P,
+    vocabulary: &[&str],
+    oov: OovInit,
+    frozen: bool,
+) -> Result<(Embedding<T, DIM>, Vec<usize>), Box<dyn Error>> {
+    let reader = BufReader::new(open_decompressed(path)?);
+
+    // Map each vocabulary token to its row index
+    let index: HashMap<&str, usize> = vocabulary.iter().enumerate().map(|(i, t)| (*t, i)).collect();
+    let mut rows: Vec<Option<[T; DIM]>> = vec![None; vocabulary.len()];
+
+    for (line_no, line) in reader.lines().enumerate() {
+        let line = line?;
+        let mut fields = line.split_whitespace();
+        let token = match fields.next() {
+            Some(token) => token,
+            None => continue,
+        };
+        let values: Vec<&str> = fields.collect();
+        // Skip the word2vec header line "<count> <dim>"
+        if line_no == 0 && values.len() == 1 && token.parse::<usize>().is_ok() && values[0].parse::<usize>().is_ok() {
+            continue;
+        }
+        let row = match index.get(token) {
+            Some(&row) if rows[row].is_none() => row,
+            _ => continue,
+        };
+        if values.len() != DIM {
+            return Err(format!("Line {}: expected {} values for '{}', found {}", line_no + 1, DIM, token, values.len()).into());
+        }
+        let mut vector = [T::zero(); DIM];
+        for (j, value) in values.iter().enumerate() {
+            let v = value.parse::<f64>().map_err(|e| format!("Line {}: invalid value '{}': {}", line_no + 1, value, e))?;
+            vector[j] = T::to_number(v);
+        }
+        rows[row] = Some(vector);
+    }
+
+    let mut rng = match oov {
+        OovInit::Uniform { seed, .. } => Some(Rng::new(seed)),
+        OovInit::Zeros => None,
+    };
+    let mut missing = Vec::new();
+    let mut weights = Vec::with_capacity(rows.len());
+    for (i, row) in rows.into_iter().enumerate() {
+        match row {
+            Some(vector) => weights.push(vector),
+            None => {
+                missing.push(i);
+                let mut vector = [T::zero(); DIM];
+                if let (OovInit::Uniform { scale, .. }, Some(rng)) = (oov, rng.as_mut()) {
+                    for v in vector.iter_mut() {
+                        *v = T::to_number((rng.next_f64() * 2.0 - 1.0) * scale);
+                    }
+                }
+                weights.push(vector);
+            }
+        }
+    }
+
+    let mut embedding = Embedding::new(weights);
+    embedding.frozen = frozen;
+    Ok((embedding, missing))
+}
+
+/// Images read from an IDX file by `read_idx_images`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdxImages<T> {
+    /// One flattened, row-major image per entry, with pixels scaled to `[0, 1]`.
+    pub images: Vec<Vec<T>>,
+    /// Image height in pixels.
+    pub rows: usize,
+    /// Image width in pixels.
+    pub cols: usize,
+}
+
+/// Reads an IDX file of unsigned bytes (the format of the MNIST and Fashion-MNIST files)
+/// and returns its dimensions and data.
+///
+/// # Behavior
+/// - Gzip-compressed files (e.g. `train-images-idx3-ubyte.gz`) are detected by their
+///   magic bytes and decompressed on the fly, so the downloaded files can be used as-is
+///   (see `open_decompressed`).
+/// - The header is `0x00 0x00 <type> <ndims>` followed by `ndims` big-endian `u32` sizes;
+///   only the unsigned-byte type (`0x08`) is supported.
+fn read_idx<P: AsRef<Path>>(path: P, expected_dims: usize) -> Result<(Vec<usize>, Vec<u8>), Box<dyn Error>> {
+    let path = path.as_ref();
+    let mut bytes = Vec::new();
+    open_decompressed(path)?.read_to_end(&mut bytes)?;
+
+    if bytes.len() < 4 || bytes[0] != 0 || bytes[1] != 0 {
+        return Err(format!("{}: not an IDX file", path.display()).into());
+    }
+    if bytes[2] != 0x08 {
+        return Err(format!("{}: unsupported IDX data type 0x{:02x} (only unsigned bytes are supported)", path.display(), bytes[2]).into());
+    }
+    let ndims = bytes[3] as usize;
+    if ndims != expected_dims {
+        return Err(format!("{}: expected {} dimensions, found {}", path.display(), expected_dims, ndims).into());
+    }
+    let header_len = 4 + 4 * ndims;
+    if bytes.len() < header_len {
+        return Err(format!("{}: truncated IDX header", path.display()).into());
+    }
+    let dims: Vec<usize> = bytes[4..header_len]
+        .chunks(4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize)
+        .collect();
+    let expected_len: usize = dims.iter().product();
+    let data = &bytes[header_len..];
+    if data.len() != expected_len {
+        return Err(format!("{}: expected {} data bytes, found {}", path.display(), expected_len, data.len()).into());
+    }
+    Ok((dims, data.to_vec()))
+}
+
+/// Reads an IDX3 image file such as MNIST's `train-images-idx3-ubyte(.gz)`.
+///
+/// # Arguments
+/// * `path` - Path to the IDX file, optionally gzip-compressed.
+///
+/// # Returns
+/// * `Ok(IdxImages<T>)` - Flattened images (`rows * cols` values each, scaled to `[0, 1]`
+///   by dividing by 255), ready for a dense input layer.
+/// * `Err(Box<dyn Error>)` - If the file cannot be read, is not a 3-dimensional
+///   unsigned-byte IDX file, or is truncated.
+pub fn read_idx_images<T: Number + FromPrimitive, P: AsRef<Path>>(path: P) -> Result<IdxImages<T>, Box<dyn Error>> {
+    let (dims, data) = read_idx(path, 3)?;
+    let (rows, cols) = (dims[1], dims[2]);
+    // Build the 256 possible pixel values once
+    let scale: Vec<T> = (0..=255u8).map(|v| T::to_number(v as f64 / 255.0)).collect();
+    let images = if rows * cols == 0 {
+        vec![Vec::new(); dims[0]]
+    } else {
+        data.chunks(rows * cols).map(|image| image.iter().map(|&v| scale[v as usize]).collect()).collect()
+    };
+    Ok(IdxImages { images, rows, cols })
+}
+
+/// Reads an IDX1 label file such as MNIST's `train-labels-idx1-ubyte(.gz)`.
+///
+/// # Returns
+/// * `Ok(Vec<usize>)` - One class index per image, in file order.
+/// * `Err(Box<dyn Error>)` - If the file cannot be read or is not a 1-dimensional
+///   unsigned-byte IDX file.
+pub fn read_idx_labels<P: AsRef<Path>>(path: P) -> Result<Vec<usize>, Box<dyn Error>> {
+    let (_, data) = read_idx(path, 1)?;
+    Ok(data.into_iter().map(usize::from).collect())
+}
+
+/// Environment variable overriding the cache directory of `fetch`.
+#[cfg(feature = "fetch")]
+pub const CACHE_DIR_ENV: &str = "NEURALNET_CACHE";
+
+/// Time allowed for connecting to the server, and between two reads of a download.
+#[cfg(feature = "fetch")]
+pub const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Directory `fetch` stores downloads in: `$NEURALNET_CACHE` if set, otherwise
+/// `$HOME/.cache/neuralnet`, otherwise `neuralnet` under the system temporary directory.
+#[cfg(feature = "fetch")]
+pub fn cache_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os(CACHE_DIR_ENV) {
+        return PathBuf::from(dir);
+    }
+    match std::env::var_os("HOME") {
+        Some(home) => PathBuf::from(home).join(".cache").join("neuralnet"),
+        None => std::env::temp_dir().join("neuralnet"),
+    }
+}
+
+/// Downloads `url` into `cache_dir()` unless it is already there, and returns the local path.
+///
+/// The returned file can be passed straight to the readers above; compressed downloads
+/// (e.g. `.csv.gz`) are decompressed when read. See `fetch_to` for details.
+#[cfg(feature = "fetch")]
+pub fn fetch(url: &str) -> Result<PathBuf, Box<dyn Error>> {
+    fetch_to(url, cache_dir(), None)
+}
+
+/// Like `fetch`, checking the download against the hex SHA-256 digest `sha256`.
+#[cfg(feature = "fetch")]
+pub fn fetch_sha256(url: &str, sha256: &str) -> Result<PathBuf, Box<dyn Error>> {
+    fetch_to(url, cache_dir(), Some(sha256))
+}
+
+/// Like `fetch`, caching in `dir` and optionally checking a checksum.
+///
+/// # Arguments
+/// * `url` - An `https://` or `http://` URL, or a `file://` URL copied into the cache.
+/// * `dir` - Cache directory; created if missing.
+/// * `sha256` - Expected hex SHA-256 digest of the file (case-insensitive), if known.
+///
+/// # Returns
+/// * `Ok(path)` - `dir/<hash>-<file name>`, where the hash of the full URL keeps files with
+///   the same name from different sources apart.
+/// * `Err(Box<dyn Error>)` - If the download fails or times out, the server does not
+///   answer `200 OK`, or the checksum does not match.
+///
+/// # Behavior
+/// - A file already in the cache is returned without any network access; with `sha256`
+///   it is checked first and downloaded again if it does not match.
+/// - The download is checked and written to a temporary file that is renamed once
+///   complete, so neither an interrupted nor a corrupted download ends up in the cache.
+/// - Redirects are followed (up to 5). Connecting and every read time out after
+///   `FETCH_TIMEOUT`.
+#[cfg(feature = "fetch")]
+pub fn fetch_to<P: AsRef<Path>>(url: &str, dir: P, sha256: Option<&str>) -> Result<PathBuf, Box<dyn Error>> {
+    let dir = dir.as_ref();
+    let name = url.rsplit('/').find(|segment| !segment.is_empty()).unwrap_or("download");
+    let name: String = name.split(['?', '#']).next().unwrap_or(name).chars()
+        .map(|c| if c.is_ascii_alphanumeric() || "._-".contains(c) { c } else { '_' })
+        .collect();
+    let hash = url.bytes().fold(0xcbf2_9ce4_8422_2325u64, |h, b| (h ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3));
+    let path = dir.join(format!("{:016x}-{}", hash, name));
+    if path.is_file() && sha256.is_none_or(|expected| check_sha256(&std::fs::read(&path).unwrap_or_default(), expected).is_ok()) {
+        return Ok(path);
+    }
+
+    let bytes = download(url).map_err(|e| format!("{}: {}", url, e))?;
+    if let Some(expected) = sha256 {
+        check_sha256(&bytes, expected).map_err(|e| format!("{}: {}", url, e))?;
+    }
+    std::fs::create_dir_all(dir)?;
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".part");
+    std::fs::write(&partial, bytes)?;
+    std::fs::rename(&partial, &path)?;
+    Ok(path)
+}
+
+#[cfg(feature = "fetch")]
+fn download(url: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    if let Some(path) = url.strip_prefix("file://") {
+        return Ok(std::fs::read(path)?);
+    }
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(FETCH_TIMEOUT)
+        .timeout_read(FETCH_TIMEOUT)
+        .redirects(5)
+        .user_agent("neuralnet")
+        .build();
+    // 4xx and 5xx responses are errors in ureq; other non-200 answers are rejected here
+    let response = agent.get(url).call()?;
+    if response.status() != 200 {
+        return Err(format!("HTTP status {}", response.status()).into());
+    }
+    let mut bytes = Vec::new();
+    response.into_reader().read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(feature = "fetch")]
+fn check_sha256(bytes: &[u8], expected: &str) -> Result<(), String> {
+    use sha2::{Digest, Sha256};
+    let digest: String = Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect();
+    if digest.eq_ignore_ascii_case(expected.trim()) {
+        Ok(())
+    } else {
+        Err(format!("SHA-256 mismatch: expected {}, found {}", expected.trim(), digest))
+    }
+}
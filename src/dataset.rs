@@ -0,0 +1,865 @@
+//! Dataset splitting, sampling, profiling and synthetic data generation.
+//!
+//! Features are stored row-major as `Vec<Vec<T>>` (one inner vector per sample),
+//! matching the output of `data_handling` and `preprocessing`.
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use serde::{Deserialize, Serialize};
+use crate::random::Rng;
+#[cfg(feature = "polars")]
+use polars::prelude::{polars_bail, Column, DataFrame, DataType, PolarsResult};
+
+/// Computes index sets for a **stratified split** of `labels` into `ratios.len()` parts.
+///
+/// # Arguments
+/// * `labels` - Class label of every sample.
+/// * `ratios` - Relative size of each part, e.g. `[0.7, 0.15, 0.15]` for train/val/test.
+///   Ratios are normalized, so `[7.0, 1.5, 1.5]` is equivalent.
+/// * `seed` - Seed for shuffling samples within each class.
+///
+/// # Returns
+/// * One vector of sample indices per ratio; every sample appears in exactly one part.
+///
+/// # Behavior
+/// - Samples of each class are shuffled and divided separately, so every part keeps the
+///   class proportions of the full dataset.
+/// - Each class contributes `floor(ratio * n_class)` samples to each part; leftover samples
+///   go to the parts with the largest fractional remainder (earlier parts win ties).
+/// - Classes are processed in order of first appearance, keeping results deterministic for a seed.
+/// - Panics if `ratios` is empty, contains a negative value, or sums to zero.
+pub fn stratified_split_indices<L: Eq + Hash>(labels: &[L], ratios: &[f64], seed: u64) -> Vec<Vec<usize>> {
+    assert!(!ratios.is_empty(), "ratios must not be empty");
+    assert!(ratios.iter().all(|&r| r >= 0.0), "ratios must be non-negative");
+    let total: f64 = ratios.iter().sum();
+    assert!(total > 0.0, "ratios must sum to a positive value");
+
+    // Group sample indices by class, in order of first appearance
+    let mut class_order: Vec<&L> = Vec::new();
+    let mut by_class: HashMap<&L, Vec<usize>> = HashMap::new();
+    for (i, label) in labels.iter().enumerate() {
+        by_class.entry(label).or_insert_with(|| {
+            class_order.push(label);
+            Vec::new()
+        }).push(i);
+    }
+
+    let mut rng = Rng::new(seed);
+    let mut parts: Vec<Vec<usize>> = vec![Vec::new(); ratios.len()];
+    for label in class_order {
+        let mut members = by_class.remove(label).unwrap_or_default();
+        rng.shuffle(&mut members);
+        let n = members.len();
+
+        // Whole-number allocation first, then hand out the remainder by largest fraction
+        let exact: Vec<f64> = ratios.iter().map(|r| r / total * n as f64).collect();
+        let mut counts: Vec<usize> = exact.iter().map(|e| e.floor() as usize).collect();
+        let mut remaining = n - counts.iter().sum::<usize>();
+        let mut by_fraction: Vec<usize> = (0..ratios.len()).collect();
+        by_fraction.sort_by(|&a, &b| {
+            let fa = exact[a] - exact[a].floor();
+            let fb = exact[b] - exact[b].floor();
+            fb.total_cmp(&fa).then(a.cmp(&b))
+        });
+        for &part in by_fraction.iter().cycle() {
+            if remaining == 0 {
+                break;
+            }
+            if ratios[part] > 0.0 {
+                counts[part] += 1;
+                remaining -= 1;
+            }
+        }
+
+        let mut start = 0;
+        for (part, &count) in counts.iter().enumerate() {
+            parts[part].extend_from_slice(&members[start..start + count]);
+            start += count;
+        }
+    }
+    parts
+}
+
+/// Splits `features` and `labels` into `ratios.len()` parts preserving class proportions.
+///
+/// # Returns
+/// * One `(features, labels)` pair per ratio, e.g. train/validation/test for three ratios.
+///
+/// See `stratified_split_indices` for how samples are allocated.
+/// Panics if `features` and `labels` have different lengths.
+pub fn stratified_split<T: Clone, L: Clone + Eq + Hash>(
+    features: &[Vec<T>],
+    labels: &[L],
+    ratios: &[f64],
+    seed: u64,
+) -> Vec<(Vec<Vec<T>>, Vec<L>)> {
+    assert_eq!(features.len(), labels.len(), "features and labels must have the same length");
+    stratified_split_indices(labels, ratios, seed)
+        .into_iter()
+        .map(|indices| {
+            let x = indices.iter().map(|&i| features[i].clone()).collect();
+            let y = indices.iter().map(|&i| labels[i].clone()).collect();
+            (x, y)
+        })
+        .collect()
+}
+
+/// Splits the indices `0..n_samples` into `k` folds for **k-fold cross-validation**.
+///
+/// # Returns
+/// * One `(train, validation)` pair per fold. Every sample is in exactly one validation
+///   set, and each training set holds all samples outside its validation set.
+///
+/// # Behavior
+/// - Indices are shuffled with `seed` before being cut into folds.
+/// - Fold sizes differ by at most one; the first `n_samples % k` folds are the larger ones.
+/// - Panics if `k < 2` or `k > n_samples`.
+pub fn k_fold_indices(n_samples: usize, k: usize, seed: u64) -> Vec<(Vec<usize>, Vec<usize>)> {
+    assert!(k >= 2, "k must be at least 2");
+    assert!(k <= n_samples, "k must not exceed the number of samples");
+    let mut indices: Vec<usize> = (0..n_samples).collect();
+    Rng::new(seed).shuffle(&mut indices);
+
+    let mut folds = Vec::with_capacity(k);
+    let mut start = 0;
+    for fold in 0..k {
+        let size = n_samples / k + usize::from(fold < n_samples % k);
+        let validation = indices[start..start + size].to_vec();
+        let train = indices[..start].iter().chain(&indices[start + size..]).copied().collect();
+        folds.push((train, validation));
+        start += size;
+    }
+    folds
+}
+
+/// Collects `values[i]` for every index in `indices`, in order.
+pub fn select<T: Clone>(values: &[T], indices: &[usize]) -> Vec<T> {
+    indices.iter().map(|&i| values[i].clone()).collect()
+}
+
+/// What `BatchIter` does with a final batch smaller than the batch size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LastBatch {
+    /// Yield the remaining samples as a smaller batch.
+    #[default]
+    Smaller,
+    /// Skip the remaining samples.
+    Drop,
+    /// Fill the final batch up to the batch size by repeating samples from the start of
+    /// the order, for consumers that need fixed-size batches. Only the first `Batch::len`
+    /// samples are real; see `Batch::mask`.
+    Pad,
+}
+
+/// Sample indices of one batch from a `BatchIter`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Batch {
+    /// Indices of the batch samples; with `LastBatch::Pad` the final batch ends with
+    /// repeated padding samples.
+    pub indices: Vec<usize>,
+    /// True batch size: the number of real samples at the start of `indices`. Average
+    /// gradients over this, not `indices.len()`.
+    pub len: usize,
+}
+
+impl Batch {
+    /// Indices of the real samples.
+    pub fn real(&self) -> &[usize] {
+        &self.indices[..self.len]
+    }
+
+    pub fn is_padded(&self) -> bool {
+        self.len < self.indices.len()
+    }
+
+    /// `true` for the real samples and `false` for the padding, e.g. as sample weights.
+    pub fn mask(&self) -> Vec<bool> {
+        (0..self.indices.len()).map(|i| i < self.len).collect()
+    }
+
+    /// Collects the batch rows of `values`, padding included (see `select`).
+    pub fn gather<T: Clone>(&self, values: &[T]) -> Vec<T> {
+        select(values, &self.indices)
+    }
+}
+
+/// Iterator over consecutive batches of `order`, with a `LastBatch` policy for the
+/// remainder.
+///
+/// ```
+/// use neuralnet::dataset::{BatchIter, LastBatch};
+///
+/// let order = [4, 3, 2, 1, 0];
+/// let batches: Vec<_> = BatchIter::new(&order, 2, LastBatch::Pad).collect();
+/// assert_eq!(batches[2].indices, vec![0, 4]);
+/// assert_eq!(batches[2].len, 1);
+/// assert_eq!(BatchIter::new(&order, 2, LastBatch::Drop).count(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BatchIter<'a> {
+    order: Cow<'a, [usize]>,
+    batch_size: usize,
+    last: LastBatch,
+    next: usize,
+}
+
+impl<'a> BatchIter<'a> {
+    /// Batches of `batch_size` samples of `order` (e.g. a shuffled index permutation).
+    /// Panics if `batch_size == 0`.
+    pub fn new(order: &'a [usize], batch_size: usize, last: LastBatch) -> Self {
+        Self::from_order(Cow::Borrowed(order), batch_size, last)
+    }
+
+    fn from_order(order: Cow<'a, [usize]>, batch_size: usize, last: LastBatch) -> Self {
+        assert!(batch_size > 0, "batch_size must be positive");
+        BatchIter { order, batch_size, last, next: 0 }
+    }
+
+    /// The sample order being batched.
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+}
+
+impl Iterator for BatchIter<'_> {
+    type Item = Batch;
+
+    fn next(&mut self) -> Option<Batch> {
+        let remaining = self.order.len() - self.next;
+        if remaining == 0 || (remaining < self.batch_size && self.last == LastBatch::Drop) {
+            return None;
+        }
+        let len = remaining.min(self.batch_size);
+        let mut indices = self.order[self.next..self.next + len].to_vec();
+        if self.last == LastBatch::Pad {
+            indices.extend((0..self.batch_size - len).map(|i| self.order[i % self.order.len()]));
+        }
+        self.next += len;
+        Some(Batch { indices, len })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.order.len() - self.next;
+        let n = match self.last {
+            LastBatch::Drop => remaining / self.batch_size,
+            _ => remaining.div_ceil(self.batch_size),
+        };
+        (n, Some(n))
+    }
+}
+
+impl ExactSizeIterator for BatchIter<'_> {}
+
+/// Training rows and targets with a batching policy, for hand-written training loops.
+///
+/// `epochs` reshuffles the samples before every epoch from one seeded generator, so
+/// each epoch sees a different but reproducible order, the same one a `Trainer` with
+/// `shuffle(seed)` uses.
+///
+/// ```
+/// use neuralnet::dataset::Dataset;
+///
+/// let rows: Vec<Vec<f64>> = (0..10).map(|i| vec![i as f64]).collect();
+/// let targets = rows.clone();
+/// let dataset = Dataset::new(rows, targets).batch_size(4);
+/// for (epoch, batches) in dataset.epochs(3, 7).enumerate() {
+///     for batch in batches {
+///         let (rows, _targets) = dataset.gather(&batch);
+///         assert!(rows.len() <= 4, "epoch {}", epoch);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dataset<R, Y> {
+    pub rows: Vec<R>,
+    pub targets: Vec<Y>,
+    batch_size: usize,
+    last_batch: LastBatch,
+}
+
+impl<R, Y> Dataset<R, Y> {
+    /// Pairs every row with its target; batches hold one sample until `batch_size` is
+    /// set. Panics if the lengths differ.
+    pub fn new(rows: Vec<R>, targets: Vec<Y>) -> Self {
+        assert_eq!(rows.len(), targets.len(), "rows and targets must have the same length");
+        Dataset { rows, targets, batch_size: 1, last_batch: LastBatch::Smaller }
+    }
+
+    /// Panics if `batch_size == 0`.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        assert!(batch_size > 0, "batch_size must be positive");
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn last_batch(mut self, last_batch: LastBatch) -> Self {
+        self.last_batch = last_batch;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Batches of the samples in their stored order.
+    pub fn batches(&self) -> BatchIter<'static> {
+        BatchIter::from_order(Cow::Owned((0..self.len()).collect()), self.batch_size, self.last_batch)
+    }
+
+    /// `n` epochs of batches, reshuffled before every epoch from `Rng::new(seed)`.
+    pub fn epochs(&self, n: usize, seed: u64) -> Epochs {
+        Epochs { rng: Rng::new(seed), order: (0..self.len()).collect(), remaining: n, batch_size: self.batch_size, last_batch: self.last_batch }
+    }
+
+    /// References to the rows and targets of `batch`, padding included.
+    pub fn gather(&self, batch: &Batch) -> (Vec<&R>, Vec<&Y>) {
+        (batch.indices.iter().map(|&i| &self.rows[i]).collect(), batch.indices.iter().map(|&i| &self.targets[i]).collect())
+    }
+}
+
+/// Iterator returned by `Dataset::epochs`, yielding the batches of one epoch at a time.
+#[derive(Debug, Clone)]
+pub struct Epochs {
+    rng: Rng,
+    order: Vec<usize>,
+    remaining: usize,
+    batch_size: usize,
+    last_batch: LastBatch,
+}
+
+impl Iterator for Epochs {
+    type Item = BatchIter<'static>;
+
+    fn next(&mut self) -> Option<BatchIter<'static>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        // Shuffling the previous order (not a fresh one) matches `Trainer`
+        self.rng.shuffle(&mut self.order);
+        Some(BatchIter::from_order(Cow::Owned(self.order.clone()), self.batch_size, self.last_batch))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for Epochs {}
+
+/// Batch sampler that groups sequences of similar length to minimize padding.
+///
+/// Each epoch the sample indices are shuffled and cut into pools of
+/// `batch_size * pool_batches` samples; every pool is sorted by length and sliced into
+/// batches, and finally the batch order is shuffled. Batches therefore hold sequences of
+/// similar length while still being random across epochs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BucketSampler {
+    lengths: Vec<usize>,
+    pub batch_size: usize,
+    /// Number of batches sorted together; larger pools give tighter buckets but less randomness.
+    pub pool_batches: usize,
+    /// Drop a final batch smaller than `batch_size`.
+    pub drop_last: bool,
+}
+
+impl BucketSampler {
+    /// Creates a sampler for sequences with the given `lengths`. Panics if `batch_size == 0`.
+    pub fn new(lengths: Vec<usize>, batch_size: usize) -> Self {
+        assert!(batch_size > 0, "batch_size must be positive");
+        BucketSampler { lengths, batch_size, pool_batches: 50, drop_last: false }
+    }
+
+    /// Returns the batches of sample indices for one epoch.
+    pub fn batches(&self, seed: u64) -> Vec<Vec<usize>> {
+        let mut rng = Rng::new(seed);
+        let mut indices: Vec<usize> = (0..self.lengths.len()).collect();
+        rng.shuffle(&mut indices);
+
+        let pool_size = self.batch_size * self.pool_batches.max(1);
+        let mut batches = Vec::new();
+        for pool in indices.chunks_mut(pool_size) {
+            // Stable sort keeps the shuffled order among equal lengths
+            pool.sort_by_key(|&i| self.lengths[i]);
+            for batch in pool.chunks(self.batch_size) {
+                if self.drop_last && batch.len() < self.batch_size {
+                    continue;
+                }
+                batches.push(batch.to_vec());
+            }
+        }
+        rng.shuffle(&mut batches);
+        batches
+    }
+}
+
+/// Pads the sequences selected by `batch` with `pad` to the length of the longest one.
+///
+/// # Returns
+/// * `(padded, lengths)` - padded sequences in batch order and their original lengths,
+///   so losses can mask out the padding (see `loss_fn::sequence_mask`).
+pub fn pad_batch<T: Clone>(sequences: &[Vec<T>], batch: &[usize], pad: T) -> (Vec<Vec<T>>, Vec<usize>) {
+    let max_len = batch.iter().map(|&i| sequences[i].len()).max().unwrap_or(0);
+    let mut padded = Vec::with_capacity(batch.len());
+    let mut lengths = Vec::with_capacity(batch.len());
+    for &i in batch {
+        let mut row = sequences[i].clone();
+        lengths.push(row.len());
+        row.resize(max_len, pad.clone());
+        padded.push(row);
+    }
+    (padded, lengths)
+}
+
+/// Counts how many padding elements `batches` would need for sequences of the given `lengths`.
+pub fn padding_waste(lengths: &[usize], batches: &[Vec<usize>]) -> usize {
+    batches.iter().map(|batch| {
+        let max_len = batch.iter().map(|&i| lengths[i]).max().unwrap_or(0);
+        batch.iter().map(|&i| max_len - lengths[i]).sum::<usize>()
+    }).sum()
+}
+
+/// Seedable synthetic classification data in the style of scikit-learn's `make_classification`.
+///
+/// Every class is a Gaussian cluster around a vertex of a hypercube with side
+/// `2 * class_sep` in the informative subspace. Redundant features are random linear
+/// combinations of the informative ones, the rest are pure noise. Columns are laid out
+/// as `[informative | redundant | noise]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassificationGenerator {
+    pub n_samples: usize,
+    pub n_features: usize,
+    pub n_informative: usize,
+    pub n_redundant: usize,
+    pub n_classes: usize,
+    /// Relative class frequencies; `None` gives balanced classes.
+    pub weights: Option<Vec<f64>>,
+    /// Fraction of labels replaced by a uniformly random class after generation.
+    pub label_noise: f64,
+    /// Distance of the cluster centres from the origin along every informative axis.
+    pub class_sep: f64,
+    pub seed: u64,
+}
+
+impl ClassificationGenerator {
+    /// Balanced binary problem; all features informative (up to 2), the rest noise.
+    pub fn new(n_samples: usize, n_features: usize) -> Self {
+        ClassificationGenerator {
+            n_samples,
+            n_features,
+            n_informative: n_features.min(2),
+            n_redundant: 0,
+            n_classes: 2,
+            weights: None,
+            label_noise: 0.0,
+            class_sep: 1.0,
+            seed: 0,
+        }
+    }
+
+    /// Sets class weights so that every class is `ratio` times as frequent as the next one;
+    /// with two classes, `ratio = 9.0` gives a 90/10 split.
+    pub fn imbalance_ratio(mut self, ratio: f64) -> Self {
+        assert!(ratio >= 1.0, "imbalance ratio must be at least 1");
+        self.weights = Some((0..self.n_classes).map(|k| ratio.powi(-(k as i32))).collect());
+        self
+    }
+
+    /// Number of samples drawn for every class before label noise is applied.
+    pub fn class_counts(&self) -> Vec<usize> {
+        let weights = self.weights.clone().unwrap_or_else(|| vec![1.0; self.n_classes]);
+        assert_eq!(weights.len(), self.n_classes, "weights must have one entry per class");
+        let total: f64 = weights.iter().sum();
+        let exact: Vec<f64> = weights.iter().map(|w| w / total * self.n_samples as f64).collect();
+        let mut counts: Vec<usize> = exact.iter().map(|e| e.floor() as usize).collect();
+        // Hand out the remainder by largest fraction, like `stratified_split_indices`
+        let mut by_fraction: Vec<usize> = (0..self.n_classes).collect();
+        by_fraction.sort_by(|&a, &b| {
+            let fa = exact[a] - exact[a].floor();
+            let fb = exact[b] - exact[b].floor();
+            fb.total_cmp(&fa).then(a.cmp(&b))
+        });
+        let remaining = self.n_samples - counts.iter().sum::<usize>();
+        for &k in by_fraction.iter().take(remaining) {
+            counts[k] += 1;
+        }
+        counts
+    }
+
+    /// Generates `(features, labels)`. Samples are shuffled.
+    ///
+    /// Panics if `n_informative + n_redundant > n_features`, if there are no informative
+    /// features, or if `n_classes` exceeds the `2^n_informative` hypercube vertices.
+    pub fn generate(&self) -> (Vec<Vec<f64>>, Vec<usize>) {
+        assert!(self.n_informative > 0, "at least one informative feature is required");
+        assert!(self.n_informative + self.n_redundant <= self.n_features, "n_informative + n_redundant must not exceed n_features");
+        assert!(self.n_classes >= 1, "n_classes must be positive");
+        assert!(
+            self.n_informative >= usize::BITS as usize || self.n_classes <= 1usize << self.n_informative,
+            "n_classes must not exceed 2^n_informative"
+        );
+        assert!((0.0..=1.0).contains(&self.label_noise), "label_noise must be in [0, 1]");
+
+        let mut rng = Rng::new(self.seed);
+        let mixing: Vec<Vec<f64>> = (0..self.n_redundant)
+            .map(|_| (0..self.n_informative).map(|_| rng.next_f64() * 2.0 - 1.0).collect())
+            .collect();
+
+        let mut features = Vec::with_capacity(self.n_samples);
+        let mut labels = Vec::with_capacity(self.n_samples);
+        for (class, &count) in self.class_counts().iter().enumerate() {
+            // Vertex of the hypercube given by the bits of the class index
+            let centre: Vec<f64> = (0..self.n_informative)
+                .map(|d| if d < usize::BITS as usize && (class >> d) & 1 == 1 { self.class_sep } else { -self.class_sep })
+                .collect();
+            for _ in 0..count {
+                let informative: Vec<f64> = centre.iter().map(|c| c + rng.next_normal()).collect();
+                let mut row = informative.clone();
+                for weights in &mixing {
+                    row.push(weights.iter().zip(informative.iter()).map(|(w, x)| w * x).sum());
+                }
+                while row.len() < self.n_features {
+                    row.push(rng.next_normal());
+                }
+                features.push(row);
+                labels.push(class);
+            }
+        }
+
+        for label in labels.iter_mut() {
+            if rng.next_f64() < self.label_noise {
+                *label = rng.gen_index(self.n_classes);
+            }
+        }
+
+        shuffled(features, labels, &mut rng)
+    }
+}
+
+/// Shuffles samples and their labels together.
+fn shuffled(features: Vec<Vec<f64>>, labels: Vec<usize>, rng: &mut Rng) -> (Vec<Vec<f64>>, Vec<usize>) {
+    let mut order: Vec<usize> = (0..features.len()).collect();
+    rng.shuffle(&mut order);
+    (select(&features, &order), select(&labels, &order))
+}
+
+/// Splits `n_samples` into `parts` counts differing by at most one (earlier parts larger).
+fn even_counts(n_samples: usize, parts: usize) -> Vec<usize> {
+    (0..parts).map(|k| n_samples / parts + usize::from(k < n_samples % parts)).collect()
+}
+
+/// Two interleaving half circles in 2D (scikit-learn's `make_moons`).
+///
+/// # Arguments
+/// * `n_samples` - Total number of points; the outer moon (label `0`) gets the extra one.
+/// * `noise` - Standard deviation of Gaussian noise added to every coordinate.
+/// * `seed` - Seed for the noise and the shuffle.
+///
+/// # Returns
+/// * `(features, labels)` with two features per sample, shuffled. The classes are not
+///   linearly separable, so a hidden layer is needed.
+pub fn make_moons(n_samples: usize, noise: f64, seed: u64) -> (Vec<Vec<f64>>, Vec<usize>) {
+    let mut rng = Rng::new(seed);
+    let mut features = Vec::with_capacity(n_samples);
+    let mut labels = Vec::with_capacity(n_samples);
+    for (label, &count) in even_counts(n_samples, 2).iter().enumerate() {
+        for i in 0..count {
+            let t = std::f64::consts::PI * i as f64 / count.saturating_sub(1).max(1) as f64;
+            let (x, y) = if label == 0 { (t.cos(), t.sin()) } else { (1.0 - t.cos(), 0.5 - t.sin()) };
+            features.push(vec![x + noise * rng.next_normal(), y + noise * rng.next_normal()]);
+            labels.push(label);
+        }
+    }
+    shuffled(features, labels, &mut rng)
+}
+
+/// A circle inside another in 2D (scikit-learn's `make_circles`).
+///
+/// # Arguments
+/// * `n_samples` - Total number of points; the outer circle (label `0`) gets the extra one.
+/// * `factor` - Radius of the inner circle (label `1`) relative to the outer one, in `(0, 1)`.
+/// * `noise` - Standard deviation of Gaussian noise added to every coordinate.
+/// * `seed` - Seed for the noise and the shuffle.
+pub fn make_circles(n_samples: usize, factor: f64, noise: f64, seed: u64) -> (Vec<Vec<f64>>, Vec<usize>) {
+    assert!(factor > 0.0 && factor < 1.0, "factor must lie in (0, 1)");
+    let mut rng = Rng::new(seed);
+    let mut features = Vec::with_capacity(n_samples);
+    let mut labels = Vec::with_capacity(n_samples);
+    for (label, &count) in even_counts(n_samples, 2).iter().enumerate() {
+        let radius = if label == 0 { 1.0 } else { factor };
+        for i in 0..count {
+            let t = 2.0 * std::f64::consts::PI * i as f64 / count as f64;
+            features.push(vec![radius * t.cos() + noise * rng.next_normal(), radius * t.sin() + noise * rng.next_normal()]);
+            labels.push(label);
+        }
+    }
+    shuffled(features, labels, &mut rng)
+}
+
+/// Isotropic Gaussian clusters (scikit-learn's `make_blobs`).
+///
+/// # Arguments
+/// * `n_samples` - Total number of points, split as evenly as possible over the clusters.
+/// * `centers` - Centre of every cluster; cluster `k` gets label `k`. All centres must have
+///   the same dimension, which is the number of features.
+/// * `std` - Standard deviation of every cluster along every axis.
+/// * `seed` - Seed for the samples and the shuffle.
+pub fn make_blobs(n_samples: usize, centers: &[Vec<f64>], std: f64, seed: u64) -> (Vec<Vec<f64>>, Vec<usize>) {
+    assert!(!centers.is_empty(), "centers must not be empty");
+    assert!(centers.iter().all(|c| c.len() == centers[0].len()), "every centre must have the same dimension");
+    let mut rng = Rng::new(seed);
+    let mut features = Vec::with_capacity(n_samples);
+    let mut labels = Vec::with_capacity(n_samples);
+    for (label, &count) in even_counts(n_samples, centers.len()).iter().enumerate() {
+        for _ in 0..count {
+            features.push(centers[label].iter().map(|c| c + std * rng.next_normal()).collect());
+            labels.push(label);
+        }
+    }
+    shuffled(features, labels, &mut rng)
+}
+
+/// The XOR problem: points drawn uniformly from `[-1, 1]^2`, labelled `1` when the two
+/// coordinates have different signs and `0` otherwise.
+///
+/// # Arguments
+/// * `n_samples` - Number of points.
+/// * `seed` - Seed for the points.
+///
+/// # Notes
+/// - No single line separates the classes; this is the classic example of a problem a
+///   perceptron cannot learn but a network with one hidden layer can.
+pub fn xor(n_samples: usize, seed: u64) -> (Vec<Vec<f64>>, Vec<usize>) {
+    let mut rng = Rng::new(seed);
+    (0..n_samples)
+        .map(|_| {
+            let (x, y) = (rng.next_f64() * 2.0 - 1.0, rng.next_f64() * 2.0 - 1.0);
+            (vec![x, y], usize::from((x < 0.0) != (y < 0.0)))
+        })
+        .unzip()
+}
+
+/// Converts column-major data to the row-major layout used throughout the crate.
+///
+/// Dataframe libraries (Polars, Arrow) store one vector per column; collect each numeric
+/// column into a `Vec` and pass them here to get one row per sample. With the `polars`
+/// feature, `Dataset::try_from_dataframe` does this for a Polars `DataFrame`.
+///
+/// # Notes
+/// - Panics if the columns differ in length.
+pub fn columns_to_rows<T: Clone>(columns: &[Vec<T>]) -> Vec<Vec<T>> {
+    let n_rows = columns.first().map_or(0, Vec::len);
+    assert!(columns.iter().all(|c| c.len() == n_rows), "columns must have the same length");
+    (0..n_rows).map(|i| columns.iter().map(|c| c[i].clone()).collect()).collect()
+}
+
+/// Inverse of `columns_to_rows`: one vector per column, e.g. to build a dataframe from
+/// features or predictions.
+///
+/// # Notes
+/// - Panics if the rows differ in length.
+pub fn rows_to_columns<T: Clone>(rows: &[Vec<T>]) -> Vec<Vec<T>> {
+    let n_columns = rows.first().map_or(0, Vec::len);
+    assert!(rows.iter().all(|r| r.len() == n_columns), "rows must have the same length");
+    (0..n_columns).map(|j| rows.iter().map(|r| r[j].clone()).collect()).collect()
+}
+
+/// Casts every column to `f64` and returns the values column-major; fails on a column
+/// that cannot be cast or holds nulls.
+#[cfg(feature = "polars")]
+fn float_columns(columns: &[&Column]) -> PolarsResult<Vec<Vec<f64>>> {
+    columns.iter()
+        .map(|column| {
+            let values = column.cast(&DataType::Float64)?;
+            if values.null_count() > 0 {
+                polars_bail!(ComputeError: "column {} has null values", column.name());
+            }
+            Ok(values.f64()?.into_no_null_iter().collect())
+        })
+        .collect()
+}
+
+#[cfg(feature = "polars")]
+impl Dataset<Vec<f64>, Vec<f64>> {
+    /// Builds a dataset from a Polars `DataFrame`: the `targets` columns become the target
+    /// of every row and all other columns, in frame order, its features.
+    ///
+    /// # Errors
+    /// - A `targets` name that is not a column of `frame`.
+    /// - A column that cannot be cast to `f64` (e.g. strings) or holds nulls.
+    pub fn try_from_dataframe(frame: &DataFrame, targets: &[&str]) -> PolarsResult<Self> {
+        let target_columns = targets.iter().map(|name| frame.column(name)).collect::<PolarsResult<Vec<_>>>()?;
+        let feature_columns: Vec<&Column> = frame.columns().iter()
+            .filter(|column| !targets.contains(&column.name().as_str()))
+            .collect();
+        // Without columns there is nothing to transpose, but still one (empty) row per sample
+        let to_rows = |columns: &[&Column]| -> PolarsResult<Vec<Vec<f64>>> {
+            if columns.is_empty() {
+                return Ok(vec![Vec::new(); frame.height()]);
+            }
+            Ok(columns_to_rows(&float_columns(columns)?))
+        };
+        Ok(Dataset::new(to_rows(&feature_columns)?, to_rows(&target_columns)?))
+    }
+
+    /// Converts the dataset to a `DataFrame` with the feature columns `x0, x1, ...`
+    /// followed by the target columns `y0, y1, ...`.
+    ///
+    /// # Errors
+    /// Rows or targets that differ in length.
+    pub fn to_dataframe(&self) -> PolarsResult<DataFrame> {
+        let mut columns = Vec::new();
+        for (prefix, values) in [("x", &self.rows), ("y", &self.targets)] {
+            let width = values.first().map_or(0, Vec::len);
+            if values.iter().any(|v| v.len() != width) {
+                polars_bail!(ShapeMismatch: "every {} row must have {} values", prefix, width);
+            }
+            for (j, column) in rows_to_columns(values).into_iter().enumerate() {
+                columns.push(Column::new(format!("{}{}", prefix, j).into(), column));
+            }
+        }
+        DataFrame::new(self.len(), columns)
+    }
+}
+
+/// Uses the last column of the frame as the (single) target and every other column as a
+/// feature, the same layout the examples read from CSV. See `Dataset::try_from_dataframe`
+/// to pick the target columns by name.
+#[cfg(feature = "polars")]
+impl TryFrom<&DataFrame> for Dataset<Vec<f64>, Vec<f64>> {
+    type Error = polars::prelude::PolarsError;
+
+    fn try_from(frame: &DataFrame) -> PolarsResult<Self> {
+        let Some(last) = frame.columns().last() else {
+            polars_bail!(NoData: "a dataset needs at least a target column");
+        };
+        Dataset::try_from_dataframe(frame, &[last.name().as_str()])
+    }
+}
+
+#[cfg(feature = "polars")]
+impl TryFrom<&Dataset<Vec<f64>, Vec<f64>>> for DataFrame {
+    type Error = polars::prelude::PolarsError;
+
+    fn try_from(dataset: &Dataset<Vec<f64>, Vec<f64>>) -> PolarsResult<Self> {
+        dataset.to_dataframe()
+    }
+}
+
+/// Cuts a time series into `(window, horizon)` pairs for forecasting.
+///
+/// # Arguments
+/// * `series` - Values in time order; an element may itself be a feature vector.
+/// * `window` - Number of past values in every input.
+/// * `horizon` - Number of following values in every target.
+/// * `stride` - Offset between the starts of consecutive windows.
+///
+/// # Returns
+/// * `(inputs, targets)` - Pair `k` holds `series[s..s + window]` and
+///   `series[s + window..s + window + horizon]` for `s = k * stride`. Windows that would
+///   run past the end of the series are dropped.
+///
+/// # Notes
+/// - Pairs are in time order; split them chronologically (e.g. with `split_at`) so no
+///   test window overlaps the training period.
+/// - Panics if `window`, `horizon` or `stride` is zero.
+pub fn sliding_windows<T: Clone>(series: &[T], window: usize, horizon: usize, stride: usize) -> (Vec<Vec<T>>, Vec<Vec<T>>) {
+    assert!(window > 0 && horizon > 0, "window and horizon must be positive");
+    assert!(stride > 0, "stride must be positive");
+    series.windows(window + horizon)
+        .step_by(stride)
+        .map(|w| (w[..window].to_vec(), w[window..].to_vec()))
+        .unzip()
+}
+
+/// Summary statistics of one column of raw (string) records, as returned by `profile`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnProfile {
+    /// Zero-based column index.
+    pub column: usize,
+    /// Number of fields that parse as finite numbers.
+    pub numeric: usize,
+    /// Number of missing values: empty fields, fields parsing to NaN (`NaN`, `nan`) and
+    /// fields absent from rows shorter than the widest row.
+    pub nan_count: usize,
+    /// Number of non-empty fields that do not parse as numbers, e.g. category names.
+    pub non_numeric: usize,
+    /// Number of distinct non-missing values, compared as strings.
+    pub cardinality: usize,
+    /// Statistics of the numeric fields; `None` when the column has none.
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub mean: Option<f64>,
+    /// Population standard deviation of the numeric fields.
+    pub std: Option<f64>,
+}
+
+impl ColumnProfile {
+    /// Whether every non-missing field is numeric.
+    pub fn is_numeric(&self) -> bool {
+        self.non_numeric == 0 && self.numeric > 0
+    }
+
+    /// Whether the column holds at most one distinct value, so it carries no information.
+    pub fn is_constant(&self) -> bool {
+        self.cardinality <= 1
+    }
+}
+
+/// Profiles every column of records loaded with `data_handling::read_csv` or `read_excel`.
+///
+/// # Arguments
+/// * `records` - Rows of string fields, without a header row.
+///
+/// # Returns
+/// * One `ColumnProfile` per column of the widest row.
+///
+/// # Notes
+/// - Fields are trimmed before parsing; infinite values count as non-numeric, so they
+///   stand out instead of turning the mean into infinity.
+/// - Useful before training to spot columns with missing values, text in numeric
+///   columns, constant columns or outliers (`min` / `max` far from `mean`).
+pub fn profile(records: &[Vec<String>]) -> Vec<ColumnProfile> {
+    let width = records.iter().map(Vec::len).max().unwrap_or(0);
+    (0..width)
+        .map(|column| {
+            let mut values = Vec::new();
+            let mut distinct = HashSet::new();
+            let (mut nan_count, mut non_numeric) = (0, 0);
+            for field in records.iter().map(|row| row.get(column).map(|f| f.trim())) {
+                match field {
+                    None | Some("") => nan_count += 1,
+                    Some(field) => match field.parse::<f64>() {
+                        Ok(v) if v.is_nan() => nan_count += 1,
+                        Ok(v) if v.is_finite() => {
+                            values.push(v);
+                            distinct.insert(field);
+                        }
+                        _ => {
+                            non_numeric += 1;
+                            distinct.insert(field);
+                        }
+                    },
+                }
+            }
+            let n = values.len() as f64;
+            let mean = (!values.is_empty()).then(|| values.iter().sum::<f64>() / n);
+            ColumnProfile {
+                column,
+                numeric: values.len(),
+                nan_count,
+                non_numeric,
+                cardinality: distinct.len(),
+                min: values.iter().copied().reduce(f64::min),
+                max: values.iter().copied().reduce(f64::max),
+                mean,
+                std: mean.map(|m| (values.iter().map(|v| (v - m).powi(2)).sum::<f64>() / n).sqrt()),
+            }
+        })
+        .collect()
+}
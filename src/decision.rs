@@ -0,0 +1,225 @@
+//! Decision rules that turn predicted probabilities into labels.
+//!
+//! Models output probabilities; how those become decisions (which threshold, which
+//! class) is a separate, tunable step fitted on validation data.
+
+use serde::{Deserialize, Serialize};
+use num_traits::ToPrimitive;
+use crate::numbers::Number;
+use crate::metrics::argmax;
+
+/// Per-label decision thresholds for multi-label (sigmoid) outputs.
+///
+/// Label `j` is predicted positive when its probability is `>= thresholds[j]`.
+/// The thresholds serialize with serde, so they can be stored next to the model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MultiLabelThresholds {
+    pub thresholds: Vec<f64>,
+}
+
+impl MultiLabelThresholds {
+    /// Uses the same `threshold` for all `n_labels` labels.
+    pub fn uniform(n_labels: usize, threshold: f64) -> Self {
+        MultiLabelThresholds { thresholds: vec![threshold; n_labels] }
+    }
+
+    /// Searches, for every label independently, the threshold maximizing F1 on validation data.
+    ///
+    /// # Arguments
+    /// * `probabilities` - Validation predictions: one row per sample, one probability per label.
+    /// * `labels` - Validation targets, same shape; values `> 0.5` count as positive.
+    ///
+    /// # Steps
+    /// 1. Sort the label's scores in decreasing order.
+    /// 2. Sweep the cut point: predicting the top `k` scores positive corresponds to the
+    ///    threshold `score[k-1]`. Cuts are only placed between distinct scores.
+    /// 3. Track true/false positives incrementally and keep the threshold with the highest F1
+    ///    (the higher threshold wins ties).
+    ///
+    /// # Notes
+    /// - A label without positives in the validation data keeps the default `0.5`.
+    /// - Panics if `probabilities` and `labels` differ in shape or are empty.
+    pub fn fit<T: Number + ToPrimitive>(probabilities: &[Vec<T>], labels: &[Vec<T>]) -> Self {
+        assert_eq!(probabilities.len(), labels.len(), "probabilities and labels must have the same length");
+        assert!(!probabilities.is_empty(), "threshold search needs validation samples");
+        let n_labels = probabilities[0].len();
+
+        let mut thresholds = Vec::with_capacity(n_labels);
+        for j in 0..n_labels {
+            let mut scored: Vec<(f64, bool)> = probabilities.iter().zip(labels.iter())
+                .map(|(p, y)| {
+                    assert_eq!(p.len(), n_labels, "every probability row must have one entry per label");
+                    assert_eq!(y.len(), n_labels, "every label row must have one entry per label");
+                    (p[j].to_f64().unwrap(), y[j].to_f64().unwrap() > 0.5)
+                })
+                .collect();
+            let positives = scored.iter().filter(|(_, y)| *y).count();
+            if positives == 0 {
+                thresholds.push(0.5);
+                continue;
+            }
+            scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+            let mut best = (0.0f64, 0.5f64);
+            let mut tp = 0usize;
+            let mut fp = 0usize;
+            for k in 0..scored.len() {
+                if scored[k].1 { tp += 1 } else { fp += 1 }
+                let is_boundary = k + 1 == scored.len() || scored[k + 1].0 < scored[k].0;
+                if !is_boundary {
+                    continue;
+                }
+                let f1 = 2.0 * tp as f64 / (tp + fp + positives) as f64;
+                if f1 > best.0 {
+                    best = (f1, scored[k].0);
+                }
+            }
+            thresholds.push(best.1);
+        }
+        MultiLabelThresholds { thresholds }
+    }
+
+    /// Applies the thresholds to one row of label probabilities.
+    /// Panics if the row length differs from the number of thresholds.
+    pub fn predict<T: Number + ToPrimitive>(&self, probabilities: &[T]) -> Vec<bool> {
+        assert_eq!(probabilities.len(), self.thresholds.len(), "expected one probability per label");
+        probabilities.iter().zip(self.thresholds.iter())
+            .map(|(p, &t)| p.to_f64().unwrap() >= t)
+            .collect()
+    }
+}
+
+/// Misclassification costs for cost-sensitive prediction.
+///
+/// `costs[t][p]` is the cost of predicting class `p` when the true class is `t`
+/// (same layout as `metrics::confusion_matrix`). The diagonal is usually zero.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostMatrix {
+    pub costs: Vec<Vec<f64>>,
+}
+
+impl CostMatrix {
+    /// Creates a cost matrix. Panics if `costs` is empty or not square.
+    pub fn new(costs: Vec<Vec<f64>>) -> Self {
+        assert!(!costs.is_empty(), "cost matrix must not be empty");
+        assert!(costs.iter().all(|row| row.len() == costs.len()), "cost matrix must be square");
+        CostMatrix { costs }
+    }
+
+    /// The 0-1 cost matrix: every error costs 1, so `predict` reduces to argmax.
+    pub fn zero_one(n_classes: usize) -> Self {
+        let costs = (0..n_classes)
+            .map(|t| (0..n_classes).map(|p| if t == p { 0.0 } else { 1.0 }).collect())
+            .collect();
+        CostMatrix::new(costs)
+    }
+
+    pub fn n_classes(&self) -> usize {
+        self.costs.len()
+    }
+
+    /// Expected cost of predicting each class: `E[cost | p] = sum_t P(t) * costs[t][p]`.
+    /// Panics if `probabilities` does not have one entry per class.
+    pub fn expected_costs<T: Number + ToPrimitive>(&self, probabilities: &[T]) -> Vec<f64> {
+        assert_eq!(probabilities.len(), self.n_classes(), "expected one probability per class");
+        let probabilities: Vec<f64> = probabilities.iter().map(|p| p.to_f64().unwrap()).collect();
+        (0..self.n_classes())
+            .map(|p| (0..self.n_classes()).map(|t| probabilities[t] * self.costs[t][p]).sum())
+            .collect()
+    }
+
+    /// Returns the class with the lowest expected cost (the lowest index wins ties).
+    pub fn predict<T: Number + ToPrimitive>(&self, probabilities: &[T]) -> usize {
+        let expected = self.expected_costs(probabilities);
+        let mut best = 0;
+        for p in 1..expected.len() {
+            if expected[p] < expected[best] {
+                best = p;
+            }
+        }
+        best
+    }
+
+    /// Applies `predict` to every row of class probabilities.
+    pub fn predict_batch<T: Number + ToPrimitive>(&self, probabilities: &[Vec<T>]) -> Vec<usize> {
+        probabilities.iter().map(|row| self.predict(row)).collect()
+    }
+}
+
+/// Predicts the most probable class, or abstains (`None`) when its probability is below `threshold`.
+pub fn predict_with_reject<T: Number + ToPrimitive>(probabilities: &[T], threshold: f64) -> Option<usize> {
+    let best = argmax(probabilities);
+    if probabilities[best].to_f64().unwrap() >= threshold { Some(best) } else { None }
+}
+
+/// Reject option based on Monte Carlo dropout: `samples` holds the class probabilities of
+/// several stochastic forward passes for the same input.
+///
+/// # Returns
+/// * The argmax of the mean probabilities, or `None` when the variance of the predicted
+///   class's probability across passes exceeds `max_variance`.
+///
+/// Panics if `samples` is empty or the rows differ in length.
+pub fn predict_with_reject_mc<T: Number + ToPrimitive>(samples: &[Vec<T>], max_variance: f64) -> Option<usize> {
+    assert!(!samples.is_empty(), "MC rejection needs at least one sample");
+    let n_classes = samples[0].len();
+    let mut mean = vec![0.0f64; n_classes];
+    for sample in samples {
+        assert_eq!(sample.len(), n_classes, "every sample must have one probability per class");
+        for (m, p) in mean.iter_mut().zip(sample.iter()) {
+            *m += p.to_f64().unwrap() / samples.len() as f64;
+        }
+    }
+    let best = argmax(&mean);
+    let variance = samples.iter()
+        .map(|s| (s[best].to_f64().unwrap() - mean[best]).powi(2))
+        .sum::<f64>() / samples.len() as f64;
+    if variance <= max_variance { Some(best) } else { None }
+}
+
+/// Coverage and accuracy of `predict_with_reject` at one confidence threshold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RejectReport {
+    pub threshold: f64,
+    /// Number of samples that were not rejected.
+    pub accepted: usize,
+    /// Fraction of samples that were not rejected.
+    pub coverage: f64,
+    /// Accuracy on the accepted samples (`1.0` when everything is rejected).
+    pub accuracy: f64,
+}
+
+/// Evaluates the reject option at every threshold, giving the coverage-vs-accuracy trade-off.
+///
+/// # Arguments
+/// * `probabilities` - Predicted class probabilities, one row per sample.
+/// * `targets` - True class index of every sample.
+/// * `thresholds` - Confidence thresholds to evaluate.
+///
+/// # Returns
+/// * One `RejectReport` per threshold, in the given order.
+pub fn coverage_accuracy_curve<T: Number + ToPrimitive>(
+    probabilities: &[Vec<T>],
+    targets: &[usize],
+    thresholds: &[f64],
+) -> Vec<RejectReport> {
+    assert_eq!(probabilities.len(), targets.len(), "probabilities and targets must have the same length");
+    thresholds.iter().map(|&threshold| {
+        let mut accepted = 0;
+        let mut correct = 0;
+        for (row, &target) in probabilities.iter().zip(targets.iter()) {
+            if let Some(class) = predict_with_reject(row, threshold) {
+                accepted += 1;
+                if class == target {
+                    correct += 1;
+                }
+            }
+        }
+        RejectReport {
+            threshold,
+            accepted,
+            coverage: if targets.is_empty() { 0.0 } else { accepted as f64 / targets.len() as f64 },
+            accuracy: if accepted == 0 { 1.0 } else { correct as f64 / accepted as f64 },
+        }
+    }).collect()
+}
@@ -0,0 +1,318 @@
+//! Data and training diagnostics.
+//!
+//! Noise estimation, run before training, splits the variance of every feature into a
+//! signal part and a noise part. Features whose noise dwarfs their signal carry little
+//! usable information and are candidates for removal or denoising.
+//!
+//! During training, [`HistogramLog`] records a [`histogram`] of every weight and gradient
+//! tensor per epoch and exports them as CSV or JSON. Gradients piling up at exactly zero
+//! point to dead ReLUs; pre-activation weights growing without bound point to saturated
+//! sigmoid or tanh units.
+
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use num_traits::ToPrimitive;
+use crate::model::Sequential;
+use crate::numbers::{Number, Real};
+
+/// Signal and noise variance of a single feature.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoiseEstimate {
+    pub feature: usize,
+    pub signal_variance: f64,
+    pub noise_variance: f64,
+}
+
+impl NoiseEstimate {
+    /// Signal-to-noise ratio `signal_variance / noise_variance` (infinite without noise).
+    pub fn snr(&self) -> f64 {
+        if self.noise_variance > 0.0 { self.signal_variance / self.noise_variance } else { f64::INFINITY }
+    }
+}
+
+/// Per-feature noise estimates with the SNR threshold used to flag features.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoiseReport {
+    pub estimates: Vec<NoiseEstimate>,
+    /// Features with `snr() < min_snr` are flagged.
+    pub min_snr: f64,
+}
+
+impl NoiseReport {
+    pub fn new(estimates: Vec<NoiseEstimate>, min_snr: f64) -> Self {
+        NoiseReport { estimates, min_snr }
+    }
+
+    /// Indices of the features whose noise dwarfs their signal.
+    pub fn flagged(&self) -> Vec<usize> {
+        self.estimates.iter().filter(|e| e.snr() < self.min_snr).map(|e| e.feature).collect()
+    }
+
+    /// Human-readable summary, one line per feature.
+    pub fn summary(&self, feature_names: Option<&[&str]>) -> String {
+        let mut out = String::new();
+        for e in &self.estimates {
+            let name = feature_names.and_then(|names| names.get(e.feature).copied())
+                .map_or_else(|| format!("feature {}", e.feature), |n| n.to_string());
+            let flag = if e.snr() < self.min_snr { "  NOISY" } else { "" };
+            out.push_str(&format!(
+                "{}: signal var {:.4}, noise var {:.4}, snr {:.3}{}\n",
+                name, e.signal_variance, e.noise_variance, e.snr(), flag
+            ));
+        }
+        out
+    }
+}
+
+/// Population variance of `values` (zero for fewer than two values).
+fn variance(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64
+}
+
+/// Estimates noise from **repeated measurements** of the same samples.
+///
+/// # Arguments
+/// * `measurements` - One entry per sample, holding two or more measurement rows of that sample.
+/// * `min_snr` - Threshold below which a feature is flagged.
+///
+/// # Steps
+/// 1. Noise variance is the pooled within-sample variance (unbiased, `k - 1` denominator).
+/// 2. Signal variance is the variance of the per-sample means minus the part of it
+///    caused by noise (`noise / k`), floored at zero.
+///
+/// # Notes
+/// - Panics if a sample has fewer than two measurements or rows differ in length.
+pub fn estimate_noise_repeated<T: Number + ToPrimitive>(measurements: &[Vec<Vec<T>>], min_snr: f64) -> NoiseReport {
+    assert!(!measurements.is_empty(), "noise estimation needs at least one sample");
+    let n_features = measurements[0].first().map_or(0, |row| row.len());
+
+    let mut estimates = Vec::with_capacity(n_features);
+    for j in 0..n_features {
+        let mut means = Vec::with_capacity(measurements.len());
+        let mut within = 0.0;
+        let mut within_dof = 0usize;
+        let mut repeats = 0usize;
+        for sample in measurements {
+            assert!(sample.len() >= 2, "every sample needs at least two measurements");
+            let values: Vec<f64> = sample.iter()
+                .map(|row| {
+                    assert_eq!(row.len(), n_features, "every measurement must have the same number of features");
+                    row[j].to_f64().unwrap()
+                })
+                .collect();
+            let k = values.len();
+            let mean = values.iter().sum::<f64>() / k as f64;
+            within += values.iter().map(|v| (v - mean).powi(2)).sum::<f64>();
+            within_dof += k - 1;
+            repeats += k;
+            means.push(mean);
+        }
+        let noise_variance = within / within_dof as f64;
+        let mean_repeats = repeats as f64 / measurements.len() as f64;
+        let signal_variance = (variance(&means) - noise_variance / mean_repeats).max(0.0);
+        estimates.push(NoiseEstimate { feature: j, signal_variance, noise_variance });
+    }
+    NoiseReport::new(estimates, min_snr)
+}
+
+/// Estimates noise by **residual analysis** against a denoised reconstruction.
+///
+/// # Arguments
+/// * `features` - Observed rows.
+/// * `fitted` - Reconstructed rows of the same shape, e.g. from a smoother, an autoencoder
+///   or a model predicting each feature from the others.
+/// * `min_snr` - Threshold below which a feature is flagged.
+///
+/// # Returns
+/// * Noise variance is the variance of `features - fitted`, signal variance that of `fitted`.
+pub fn estimate_noise_residual<T: Number + ToPrimitive>(features: &[Vec<T>], fitted: &[Vec<T>], min_snr: f64) -> NoiseReport {
+    assert_eq!(features.len(), fitted.len(), "features and fitted must have the same length");
+    let n_features = features.first().map_or(0, |row| row.len());
+    let estimates = (0..n_features).map(|j| {
+        let (residuals, signal): (Vec<f64>, Vec<f64>) = features.iter().zip(fitted.iter())
+            .map(|(x, f)| {
+                let f = f[j].to_f64().unwrap();
+                (x[j].to_f64().unwrap() - f, f)
+            })
+            .unzip();
+        NoiseEstimate { feature: j, signal_variance: variance(&signal), noise_variance: variance(&residuals) }
+    }).collect();
+    NoiseReport::new(estimates, min_snr)
+}
+
+/// Counts of values in `counts.len()` equal-width bins spanning `[min, max]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Histogram {
+    pub min: f64,
+    pub max: f64,
+    pub counts: Vec<usize>,
+}
+
+impl Histogram {
+    /// The `counts.len() + 1` bin edges, from `min` to `max`.
+    pub fn edges(&self) -> Vec<f64> {
+        let bins = self.counts.len();
+        (0..=bins).map(|k| {
+            if k == bins { self.max } else { self.min + (self.max - self.min) * k as f64 / bins as f64 }
+        }).collect()
+    }
+
+    /// Number of values counted.
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+}
+
+/// Histogram of `parameters` with `bins` equal-width bins between their smallest and
+/// largest finite value.
+///
+/// # Notes
+/// - NaN and infinite values are not counted.
+/// - When all values are equal (e.g. gradients that are all zero), every value falls in
+///   the first bin and `min == max`; without finite values the range is `[0, 0]`.
+/// - Panics if `bins == 0`.
+pub fn histogram<T: Number + ToPrimitive>(parameters: &[T], bins: usize) -> Histogram {
+    histogram_of(parameters.iter().map(|p| p.to_f64().unwrap()), bins)
+}
+
+fn histogram_of<I: Iterator<Item = f64> + Clone>(values: I, bins: usize) -> Histogram {
+    assert!(bins > 0, "a histogram needs at least one bin");
+    let finite = values.filter(|v| v.is_finite());
+    let (min, max) = finite.clone().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    let (min, max) = if min <= max { (min, max) } else { (0.0, 0.0) };
+    let mut counts = vec![0; bins];
+    for v in finite {
+        let bin = if max > min { ((v - min) / (max - min) * bins as f64) as usize } else { 0 };
+        counts[bin.min(bins - 1)] += 1;
+    }
+    Histogram { min, max, counts }
+}
+
+/// Whether a `ParameterHistogram` counts parameter values or their gradients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HistogramKind {
+    Values,
+    Gradients,
+}
+
+impl HistogramKind {
+    fn name(&self) -> &'static str {
+        match self {
+            HistogramKind::Values => "values",
+            HistogramKind::Gradients => "gradients",
+        }
+    }
+}
+
+/// Histogram of one parameter tensor (e.g. `"0.weights"`) after one epoch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParameterHistogram {
+    pub epoch: usize,
+    pub parameter: String,
+    pub kind: HistogramKind,
+    pub histogram: Histogram,
+}
+
+/// Per-epoch weight and gradient histograms of a `Sequential` model.
+///
+/// Call `record` once per epoch, e.g. from the `Trainer::fit_with` callback:
+///
+/// ```
+/// use neuralnet::diagnostics::HistogramLog;
+/// use neuralnet::loss_fn::Loss;
+/// use neuralnet::model::ModelBuilder;
+/// use neuralnet::training::Trainer;
+///
+/// let mut model = ModelBuilder::new(2).dense(4).relu().dense(1).build::<f64>().unwrap();
+/// let mut log = HistogramLog::new(10);
+/// let rows = vec![vec![0.0, 1.0], vec![1.0, 0.0]];
+/// let targets = vec![vec![1.0], vec![0.0]];
+/// Trainer::new(Loss::MeanSquaredError, 0.1, 3).fit_with(&mut model, &rows, &targets, |m| {
+///     log.record(m);
+///     Vec::new()
+/// });
+/// // Values and gradients of 4 tensors in each of 3 epochs
+/// assert_eq!(log.entries.len(), 3 * 4 * 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistogramLog {
+    pub bins: usize,
+    /// Number of `record` calls so far.
+    pub epochs: usize,
+    pub entries: Vec<ParameterHistogram>,
+}
+
+impl HistogramLog {
+    /// Panics if `bins == 0`.
+    pub fn new(bins: usize) -> Self {
+        assert!(bins > 0, "a histogram needs at least one bin");
+        HistogramLog { bins, epochs: 0, entries: Vec::new() }
+    }
+
+    /// Records the values and the accumulated gradients (see `Sequential::gradients`) of
+    /// every parameter tensor of `model`.
+    ///
+    /// The entries get epoch `self.epochs`, so they line up with `EpochRecord::epoch` when
+    /// `record` is called once per epoch from the start of training. Tensors without
+    /// gradients yet only get a values histogram.
+    pub fn record<T: Real + ToPrimitive>(&mut self, model: &Sequential<T>) {
+        let epoch = self.epochs;
+        let entry = |parameter: &str, kind, values: &[&T]| ParameterHistogram {
+            epoch,
+            parameter: parameter.to_string(),
+            kind,
+            histogram: histogram_of(values.iter().map(|v| v.to_f64().unwrap()), self.bins),
+        };
+        let gradients = model.gradients();
+        for parameter in model.parameters() {
+            let values_entry = entry(&parameter.name, HistogramKind::Values, &parameter.values);
+            self.entries.push(values_entry);
+            if let Some(g) = gradients.iter().find(|g| g.name == parameter.name && !g.values.is_empty()) {
+                let gradients_entry = entry(&g.name, HistogramKind::Gradients, &g.values);
+                self.entries.push(gradients_entry);
+            }
+        }
+        self.epochs += 1;
+    }
+
+    /// Entries of one parameter tensor and kind, in epoch order.
+    pub fn series(&self, parameter: &str, kind: HistogramKind) -> Vec<&ParameterHistogram> {
+        self.entries.iter().filter(|e| e.parameter == parameter && e.kind == kind).collect()
+    }
+
+    /// Writes one row per bin with header `epoch,parameter,kind,bin,lower,upper,count`,
+    /// where `kind` is `values` or `gradients`.
+    pub fn to_csv<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let mut writer = csv::Writer::from_path(path)?;
+        writer.write_record(["epoch", "parameter", "kind", "bin", "lower", "upper", "count"])?;
+        for entry in &self.entries {
+            let edges = entry.histogram.edges();
+            for (bin, count) in entry.histogram.counts.iter().enumerate() {
+                writer.write_record([
+                    entry.epoch.to_string(),
+                    entry.parameter.clone(),
+                    entry.kind.name().to_string(),
+                    bin.to_string(),
+                    edges[bin].to_string(),
+                    edges[bin + 1].to_string(),
+                    count.to_string(),
+                ])?;
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Writes the log as JSON.
+    pub fn to_json<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        serde_json::to_writer_pretty(BufWriter::new(File::create(path)?), self)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+//! Model ensembles.
+//!
+//! [`Bagging`] trains every member on its own bootstrap sample of the training data
+//! (drawn with replacement) and combines their predictions, which lowers the variance
+//! of small, easily overfitted networks.
+//!
+//! With the `rayon` feature the members are trained in parallel on rayon's global thread
+//! pool. Every member has its own seed, so the result does not depend on the feature or
+//! on the number of threads.
+
+use num_traits::{FromPrimitive, ToPrimitive};
+use serde::{Deserialize, Serialize};
+use crate::dataset::select;
+use crate::metrics::argmax;
+use crate::model::Sequential;
+use crate::numbers::{Number, Real};
+use crate::random::Rng;
+use crate::training::{History, Trainer};
+
+/// Draws a bootstrap sample of `n_samples` indices with replacement.
+///
+/// # Returns
+/// * `(sample, out_of_bag)` - The `n_samples` drawn indices, and the sorted indices that
+///   were never drawn (about 37% of them), usable as a validation set for the member.
+pub fn bootstrap_indices(n_samples: usize, rng: &mut Rng) -> (Vec<usize>, Vec<usize>) {
+    let sample: Vec<usize> = (0..n_samples).map(|_| rng.gen_index(n_samples)).collect();
+    let mut drawn = vec![false; n_samples];
+    for &i in &sample {
+        drawn[i] = true;
+    }
+    let out_of_bag = (0..n_samples).filter(|&i| !drawn[i]).collect();
+    (sample, out_of_bag)
+}
+
+/// Bootstrap-aggregated ensemble of `Sequential` models.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bagging<T: Number> {
+    pub models: Vec<Sequential<T>>,
+    /// Out-of-bag sample indices of every member; see `bootstrap_indices`.
+    pub out_of_bag: Vec<Vec<usize>>,
+}
+
+impl<T: Real + FromPrimitive + ToPrimitive> Bagging<T> {
+    /// Trains `n_models` members, each on its own bootstrap sample of `rows` and `targets`.
+    ///
+    /// # Arguments
+    /// * `model_factory` - Builds the untrained member `k`; give each a different seed so
+    ///   the members start from different weights.
+    /// * `trainer` - Training settings shared by all members.
+    /// * `seed` - Member `k` draws its bootstrap sample from `Rng::new(seed + k)`.
+    ///
+    /// # Returns
+    /// * `(ensemble, histories)` - The trained ensemble and one training history per member.
+    ///
+    /// # Notes
+    /// - Panics if `rows` and `targets` differ in length, or if `n_models == 0`.
+    pub fn fit<M, R, Y>(model_factory: M, rows: &[R], targets: &[Y], n_models: usize, trainer: &Trainer, seed: u64) -> (Self, Vec<History>)
+    where
+        T: Send + Sync,
+        M: FnMut(usize) -> Sequential<T>,
+        R: AsRef<[T]> + Clone + Sync,
+        Y: AsRef<[T]> + Clone + Sync,
+    {
+        assert_eq!(rows.len(), targets.len(), "rows and targets must have the same length");
+        assert!(n_models > 0, "a bagging ensemble needs at least one model");
+
+        // Step 1: Members and their bootstrap samples, in order
+        let mut members: Vec<(Sequential<T>, Vec<usize>)> = Vec::with_capacity(n_models);
+        let mut out_of_bag = Vec::with_capacity(n_models);
+        for (k, model) in (0..n_models).map(model_factory).enumerate() {
+            let (sample, oob) = bootstrap_indices(rows.len(), &mut Rng::new(seed.wrapping_add(k as u64)));
+            members.push((model, sample));
+            out_of_bag.push(oob);
+        }
+
+        // Step 2: Training, independent per member
+        let train = |(model, sample): &mut (Sequential<T>, Vec<usize>)| {
+            trainer.fit(model, &select(rows, sample), &select(targets, sample))
+        };
+        let histories = train_all(&mut members, train);
+        let models = members.into_iter().map(|(model, _)| model).collect();
+        (Bagging { models, out_of_bag }, histories)
+    }
+
+    pub fn len(&self) -> usize {
+        self.models.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.models.is_empty()
+    }
+
+    /// Mean of the member outputs for one sample (regression values or class probabilities).
+    ///
+    /// # Notes
+    /// - Panics if the ensemble has no models.
+    pub fn predict(&self, inputs: &[T]) -> Vec<T> {
+        assert!(!self.models.is_empty(), "the ensemble has no models");
+        let mut mean = self.models[0].forward(inputs);
+        for model in &self.models[1..] {
+            for (m, o) in mean.iter_mut().zip(model.forward(inputs)) {
+                *m = *m + o;
+            }
+        }
+        let n = T::to_number::<T>(self.models.len() as f64);
+        mean.into_iter().map(|m| m / n).collect()
+    }
+
+    /// `predict` for every row.
+    pub fn predict_batch(&self, rows: &[Vec<T>]) -> Vec<Vec<T>> {
+        rows.iter().map(|row| self.predict(row)).collect()
+    }
+
+    /// Majority vote over the members' `argmax` classes; ties go to the smallest class.
+    ///
+    /// # Notes
+    /// - Panics if the ensemble has no models, like `predict`.
+    pub fn predict_class(&self, inputs: &[T]) -> usize {
+        let votes = self.votes(inputs);
+        let best = votes.iter().copied().max().unwrap_or(0);
+        votes.iter().position(|&v| v == best).unwrap_or(0)
+    }
+
+    /// Number of members voting for each class.
+    ///
+    /// # Notes
+    /// - Panics if the ensemble has no models, like `predict`.
+    pub fn votes(&self, inputs: &[T]) -> Vec<usize> {
+        assert!(!self.models.is_empty(), "the ensemble has no models");
+        let mut votes = vec![0; self.models[0].output_dim()];
+        for model in &self.models {
+            votes[argmax(&model.forward(inputs))] += 1;
+        }
+        votes
+    }
+}
+
+/// Runs `train` on every member, in parallel with the `rayon` feature. The histories
+/// are returned in member order either way.
+#[cfg(feature = "rayon")]
+fn train_all<E: Send, F>(members: &mut [E], train: F) -> Vec<History>
+where
+    F: Fn(&mut E) -> History + Sync + Send,
+{
+    use rayon::prelude::*;
+    members.par_iter_mut().map(train).collect()
+}
+
+#[cfg(not(feature = "rayon"))]
+fn train_all<E, F>(members: &mut [E], train: F) -> Vec<History>
+where
+    F: Fn(&mut E) -> History,
+{
+    members.iter_mut().map(train).collect()
+}
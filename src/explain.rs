@@ -0,0 +1,102 @@
+//! Model interpretability.
+//!
+//! [`permutation_importance`] measures how much a model relies on each input feature:
+//! it shuffles one feature column at a time, which breaks the link between that feature
+//! and the targets while keeping its distribution, and reports how much the metric drops.
+//! It needs no access to the model internals and no retraining.
+
+use serde::{Deserialize, Serialize};
+use crate::dataset::Dataset;
+use crate::model::Sequential;
+use crate::numbers::Real;
+use crate::random::Rng;
+
+/// Metric drop caused by shuffling one feature.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureImportance {
+    pub feature: usize,
+    /// Mean of `baseline - permuted score` over the repeats.
+    pub mean: f64,
+    /// Population standard deviation of the drops.
+    pub std: f64,
+    /// Metric value after each shuffle.
+    pub scores: Vec<f64>,
+}
+
+/// Result of `permutation_importance`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PermutationImportance {
+    /// Metric on the unshuffled data.
+    pub baseline: f64,
+    /// One entry per feature, in column order.
+    pub features: Vec<FeatureImportance>,
+}
+
+impl PermutationImportance {
+    /// Feature indices from most to least important (ties keep column order).
+    pub fn ranking(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.features.len()).collect();
+        order.sort_by(|&a, &b| self.features[b].mean.total_cmp(&self.features[a].mean));
+        order
+    }
+}
+
+/// Permutation feature importance of `model` on `dataset`.
+///
+/// # Arguments
+/// * `metric` - Score of the model predictions against the targets, **higher is better**
+///   (e.g. accuracy or R²); pass the negated value of a loss.
+/// * `n_repeats` - Shuffles per feature; their spread is reported in `std`.
+/// * `seed` - Seed of the `Rng` drawing every permutation, so the result is reproducible.
+///
+/// # Returns
+/// * The baseline score and, per feature, the drop of the metric when that column is
+///   shuffled. A mean drop near zero means the model ignores the feature; a negative one
+///   means the feature only adds noise.
+///
+/// # Notes
+/// - Correlated features share their importance: shuffling one leaves the model its copy.
+/// - Use held-out data; on the training set the importances also reflect overfitting.
+/// - Panics if `n_repeats == 0` or the dataset is empty.
+pub fn permutation_importance<T, R, Y, M>(
+    model: &Sequential<T>,
+    dataset: &Dataset<R, Y>,
+    mut metric: M,
+    n_repeats: usize,
+    seed: u64,
+) -> PermutationImportance
+where
+    T: Real,
+    R: AsRef<[T]>,
+    M: FnMut(&[Vec<T>], &[Y]) -> f64,
+{
+    assert!(n_repeats > 0, "n_repeats must be positive");
+    assert!(!dataset.is_empty(), "permutation importance needs at least one sample");
+    let rows: Vec<Vec<T>> = dataset.rows.iter().map(|r| r.as_ref().to_vec()).collect();
+    let baseline = metric(&model.predict(&rows), &dataset.targets);
+
+    // Step 1: Shuffle every column `n_repeats` times, restoring it before the next one
+    let mut rng = Rng::new(seed);
+    let mut shuffled = rows.clone();
+    let n_features = model.input_dim();
+    let features = (0..n_features).map(|j| {
+        let mut order: Vec<usize> = (0..rows.len()).collect();
+        let scores: Vec<f64> = (0..n_repeats).map(|_| {
+            rng.shuffle(&mut order);
+            for (row, &source) in shuffled.iter_mut().zip(order.iter()) {
+                row[j] = rows[source][j];
+            }
+            metric(&model.predict(&shuffled), &dataset.targets)
+        }).collect();
+        for (row, original) in shuffled.iter_mut().zip(rows.iter()) {
+            row[j] = original[j];
+        }
+
+        // Step 2: Mean and spread of the drops
+        let drops: Vec<f64> = scores.iter().map(|s| baseline - s).collect();
+        let mean = drops.iter().sum::<f64>() / n_repeats as f64;
+        let std = (drops.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / n_repeats as f64).sqrt();
+        FeatureImportance { feature: j, mean, std, scores }
+    }).collect();
+    PermutationImportance { baseline, features }
+}
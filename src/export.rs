@@ -0,0 +1,366 @@
+//! Export of trained models to standalone Rust source.
+//!
+//! [`to_rust_source`] turns a `Sequential` model into a single `.rs` file with the
+//! weights baked into `static` arrays and a `predict` function over fixed-size arrays.
+//! The file only uses `core` (no `std`, no `alloc`, no crates), so a tiny model can be
+//! dropped into a firmware or another binary as `mod model;` and called without this
+//! library. The output depends only on the model, so regenerating an unchanged model
+//! gives the same file.
+//!
+//! `core` has no `exp` or `ln` for floats, so the generated file carries its own
+//! series implementations when an activation needs them; they agree with the standard
+//! library to within a few ulps. Models without sigmoid, tanh, softplus or softmax
+//! layers compute exactly what `Sequential::forward` computes.
+
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use num_traits::ToPrimitive;
+use crate::activation_fn::Activation;
+use crate::model::{ModelLayer, Sequential};
+use crate::numbers::Number;
+
+/// Floating-point type of the generated weights and `predict` signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Scalar {
+    F32,
+    #[default]
+    F64,
+}
+
+impl Scalar {
+    fn name(&self) -> &'static str {
+        match self {
+            Scalar::F32 => "f32",
+            Scalar::F64 => "f64",
+        }
+    }
+
+    /// Rust literal of `value` that parses back to the same value of this type.
+    fn literal(&self, value: f64) -> String {
+        let name = self.name();
+        if value.is_nan() {
+            format!("{}::NAN", name)
+        } else if value.is_infinite() {
+            format!("{}::{}INFINITY", name, if value < 0.0 { "NEG_" } else { "" })
+        } else {
+            match self {
+                Scalar::F32 => format!("{:?}", value as f32),
+                Scalar::F64 => format!("{:?}", value),
+            }
+        }
+    }
+}
+
+/// `to_rust_source_as` with `f64` weights.
+pub fn to_rust_source<T: Number + ToPrimitive>(model: &Sequential<T>) -> String {
+    to_rust_source_as(model, Scalar::F64)
+}
+
+/// Generates the source of a standalone module computing `model.forward`.
+///
+/// # Returns
+/// * Rust source defining `pub const INPUTS`, `pub const OUTPUTS` and
+///   `pub fn predict(inputs: &[S; INPUTS]) -> [S; OUTPUTS]` for the `scalar` type `S`,
+///   plus private `static` weight arrays (`W0`, `B0`, ... named after the layer index)
+///   and the helpers the layers need.
+///
+/// # Notes
+/// - The file has no inner attributes, so it also works with `include!`.
+/// - Values that do not fit `f32` are rounded (or become infinite) with `Scalar::F32`.
+pub fn to_rust_source_as<T: Number + ToPrimitive>(model: &Sequential<T>, scalar: Scalar) -> String {
+    let s = scalar.name();
+    let lit = |v: &T| scalar.literal(v.to_f64().unwrap());
+    let mut items = String::new();
+    let mut body = String::new();
+    let mut needs = Needs::default();
+    let mut width = model.input_dim();
+    let mut current = "x".to_string();
+    let mut summary = Vec::with_capacity(model.layers.len());
+
+    // Step 1: Weights and one statement per layer
+    for (i, layer) in model.layers.iter().enumerate() {
+        let next = format!("h{}", i);
+        let call = match layer {
+            ModelLayer::Dense(dense) => {
+                let (outputs, inputs) = dense.shape();
+                let rows: Vec<String> = dense.weights.iter()
+                    .map(|row| format!("    [{}],\n", row.iter().map(lit).collect::<Vec<_>>().join(", ")))
+                    .collect();
+                items.push_str(&format!("static W{}: [[{}; {}]; {}] = [\n{}];\n", i, s, inputs, outputs, rows.concat()));
+                items.push_str(&format!(
+                    "static B{}: [{}; {}] = [{}];\n\n", i, s, outputs, dense.biases.iter().map(lit).collect::<Vec<_>>().join(", ")
+                ));
+                needs.dense = true;
+                width = outputs;
+                summary.push(format!("Dense({})", outputs));
+                format!("dense(&W{}, &B{}, &{})", i, i, current)
+            }
+            ModelLayer::PReLU(prelu) => {
+                let slopes: Vec<String> = (0..width).map(|c| lit(&prelu.alpha[if prelu.alpha.len() == 1 { 0 } else { c }])).collect();
+                items.push_str(&format!("static ALPHA{}: [{}; {}] = [{}];\n\n", i, s, width, slopes.join(", ")));
+                needs.prelu = true;
+                summary.push("PReLU".to_string());
+                format!("prelu(&ALPHA{}, {})", i, current)
+            }
+            ModelLayer::Activation(Activation::Linear) => {
+                summary.push("Linear".to_string());
+                continue;
+            }
+            ModelLayer::Activation(activation) => {
+                let name = match activation {
+                    Activation::Sigmoid => "sigmoid",
+                    Activation::ReLU => "relu",
+                    Activation::Tanh => "tanh",
+                    Activation::Softplus => "softplus",
+                    Activation::HardSigmoid => "hard_sigmoid",
+                    Activation::Linear => unreachable!(),
+                };
+                needs.activations.push(*activation);
+                summary.push(format!("{:?}", activation));
+                format!("map({}, {})", current, name)
+            }
+            ModelLayer::Softmax => {
+                needs.softmax = true;
+                summary.push("Softmax".to_string());
+                format!("softmax({})", current)
+            }
+        };
+        body.push_str(&format!("    let {} = {};\n", next, call));
+        current = next;
+    }
+
+    // Step 2: Header, public interface and helpers
+    let mut out = String::new();
+    out.push_str("// Generated by neuralnet::export::to_rust_source. Do not edit.\n");
+    out.push_str(&format!("// Layers: {}\n", if summary.is_empty() { "none".to_string() } else { summary.join(", ") }));
+    out.push_str("// Uses only `core`; call `predict` with one sample.\n\n");
+    out.push_str(&format!("pub const INPUTS: usize = {};\n", model.input_dim()));
+    out.push_str(&format!("pub const OUTPUTS: usize = {};\n\n", width));
+    out.push_str(&items);
+    out.push_str(&format!("pub fn predict(inputs: &[{s}; INPUTS]) -> [{s}; OUTPUTS] {{\n    let x = *inputs;\n", s = s));
+    out.push_str(&body);
+    out.push_str(&format!("    {}\n}}\n", current));
+    out.push_str(&needs.helpers(scalar));
+    out
+}
+
+/// Writes `to_rust_source_as(model, scalar)` to `path`.
+pub fn write_rust_source<T: Number + ToPrimitive, P: AsRef<Path>>(model: &Sequential<T>, scalar: Scalar, path: P) -> Result<(), Box<dyn Error>> {
+    fs::write(path, to_rust_source_as(model, scalar))?;
+    Ok(())
+}
+
+/// Helpers used by the generated layers.
+#[derive(Default)]
+struct Needs {
+    dense: bool,
+    prelu: bool,
+    softmax: bool,
+    activations: Vec<Activation>,
+}
+
+impl Needs {
+    fn uses(&self, activation: Activation) -> bool {
+        self.activations.contains(&activation)
+    }
+
+    fn helpers(&self, scalar: Scalar) -> String {
+        let uses_exp = self.softmax || self.uses(Activation::Sigmoid) || self.uses(Activation::Softplus);
+        let mut templates = Vec::new();
+        if self.dense {
+            templates.push(DENSE);
+        }
+        if self.prelu {
+            templates.push(PRELU);
+        }
+        if !self.activations.is_empty() {
+            templates.push(MAP);
+        }
+        for (activation, template) in [
+            (Activation::Sigmoid, SIGMOID),
+            (Activation::ReLU, RELU),
+            (Activation::Tanh, TANH),
+            (Activation::Softplus, SOFTPLUS),
+            (Activation::HardSigmoid, HARD_SIGMOID),
+        ] {
+            if self.uses(activation) {
+                templates.push(template);
+            }
+        }
+        if self.softmax {
+            templates.push(SOFTMAX);
+        }
+        if uses_exp {
+            templates.push(EXP);
+        }
+        if self.uses(Activation::Tanh) {
+            templates.push(EXP_M1);
+        }
+        if self.uses(Activation::Softplus) {
+            templates.push(LN_1P);
+        }
+        if uses_exp || self.uses(Activation::Tanh) {
+            templates.push(EXP_F64);
+        }
+        let (widen, narrow) = match scalar {
+            Scalar::F32 => (" as f64", " as f32"),
+            Scalar::F64 => ("", ""),
+        };
+        templates.iter()
+            .map(|t| format!("\n{}", t.replace("SCALAR", scalar.name()).replace(" WIDEN", widen).replace(" NARROW", narrow)))
+            .collect()
+    }
+}
+
+const DENSE: &str = "fn dense<const OUT: usize, const IN: usize>(weights: &[[SCALAR; IN]; OUT], biases: &[SCALAR; OUT], x: &[SCALAR; IN]) -> [SCALAR; OUT] {
+    let mut y = *biases;
+    for (out, row) in y.iter_mut().zip(weights.iter()) {
+        for (w, v) in row.iter().zip(x.iter()) {
+            *out += w * v;
+        }
+    }
+    y
+}
+";
+
+const PRELU: &str = "fn prelu<const N: usize>(alpha: &[SCALAR; N], x: [SCALAR; N]) -> [SCALAR; N] {
+    let mut y = x;
+    for (v, a) in y.iter_mut().zip(alpha.iter()) {
+        if !(*v > 0.0) {
+            *v *= a;
+        }
+    }
+    y
+}
+";
+
+const MAP: &str = "fn map<const N: usize>(x: [SCALAR; N], f: fn(SCALAR) -> SCALAR) -> [SCALAR; N] {
+    let mut y = x;
+    for v in y.iter_mut() {
+        *v = f(*v);
+    }
+    y
+}
+";
+
+const SIGMOID: &str = "fn sigmoid(x: SCALAR) -> SCALAR {
+    if x >= 0.0 {
+        1.0 / (1.0 + exp(-x))
+    } else {
+        let e = exp(x);
+        e / (1.0 + e)
+    }
+}
+";
+
+const RELU: &str = "fn relu(x: SCALAR) -> SCALAR {
+    if x > 0.0 { x } else { 0.0 }
+}
+";
+
+const TANH: &str = "fn tanh(x: SCALAR) -> SCALAR {
+    // tanh(|x|) = -expm1(-2|x|) / (2 + expm1(-2|x|))
+    let e = exp_m1(if x < 0.0 { 2.0 * x } else { -2.0 * x });
+    let t = -e / (2.0 + e);
+    if x < 0.0 { -t } else { t }
+}
+";
+
+const SOFTPLUS: &str = "fn softplus(x: SCALAR) -> SCALAR {
+    let positive = if x > 0.0 { x } else { 0.0 };
+    let magnitude = if x < 0.0 { -x } else { x };
+    positive + ln_1p(exp(-magnitude))
+}
+";
+
+const HARD_SIGMOID: &str = "fn hard_sigmoid(x: SCALAR) -> SCALAR {
+    let y = x / 6.0 + 0.5;
+    if y < 0.0 { 0.0 } else if y > 1.0 { 1.0 } else { y }
+}
+";
+
+const SOFTMAX: &str = "fn softmax<const N: usize>(x: [SCALAR; N]) -> [SCALAR; N] {
+    let mut max = SCALAR::NEG_INFINITY;
+    for &v in x.iter() {
+        if v > max {
+            max = v;
+        }
+    }
+    let mut y = x;
+    let mut sum = 0.0;
+    for v in y.iter_mut() {
+        *v = exp(*v - max);
+        sum += *v;
+    }
+    for v in y.iter_mut() {
+        *v /= sum;
+    }
+    y
+}
+";
+
+const EXP: &str = "fn exp(x: SCALAR) -> SCALAR {
+    exp_f64(x WIDEN) NARROW
+}
+";
+
+const EXP_M1: &str = "fn exp_m1(x: SCALAR) -> SCALAR {
+    let x = x WIDEN;
+    if x > -0.5 && x < 0.5 {
+        // Taylor series without the leading 1, which would cancel
+        let mut term = x;
+        let mut sum = x;
+        for i in 2..=20 {
+            term *= x / i as f64;
+            sum += term;
+        }
+        sum NARROW
+    } else {
+        let y = exp_f64(x) - 1.0;
+        y NARROW
+    }
+}
+";
+
+const LN_1P: &str = "// ln(1 + y) for 0 <= y <= 1, as 2 atanh(y / (2 + y))
+fn ln_1p(y: SCALAR) -> SCALAR {
+    let s = y WIDEN / (2.0 + y WIDEN);
+    let mut term = s;
+    let mut sum = s;
+    for k in 1..=20 {
+        term *= s * s;
+        sum += term / (2 * k + 1) as f64;
+    }
+    let y = 2.0 * sum;
+    y NARROW
+}
+";
+
+const EXP_F64: &str = "// exp(x) = 2^k exp(r) with |r| <= ln(2) / 2
+fn exp_f64(x: f64) -> f64 {
+    const LN2_HI: f64 = 0.6931471803691238;
+    const LN2_LO: f64 = 1.9082149292705877e-10;
+    if x.is_nan() {
+        return x;
+    }
+    if x > 709.782712893384 {
+        return f64::INFINITY;
+    }
+    if x < -745.1332191019411 {
+        return 0.0;
+    }
+    let k = (x * core::f64::consts::LOG2_E + if x < 0.0 { -0.5 } else { 0.5 }) as i32;
+    let r = (x - k as f64 * LN2_HI) - k as f64 * LN2_LO;
+    let mut sum = 1.0;
+    for i in (1..=13).rev() {
+        sum = 1.0 + r * sum / i as f64;
+    }
+    // Two factors keep 2^k representable down to the subnormal range
+    sum * pow2(k / 2) * pow2(k - k / 2)
+}
+
+fn pow2(n: i32) -> f64 {
+    f64::from_bits(((n + 1023) as u64) << 52)
+}
+";
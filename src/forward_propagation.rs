@@ -1,5 +1,6 @@
-use crate::numbers::Number;
-use crate::layers::{Layer1D, Layer2D};
+use crate::numbers::{Number, Real};
+use crate::layers::{Layer1D, Layer2D, LayerError};
+use crate::activation_fn::Activation;
 
 /// Performs forward propagation for a dense (fully connected) linear layer.
 ///
@@ -23,24 +24,89 @@ use crate::layers::{Layer1D, Layer2D};
 pub fn dense_linear<T: Number, const IN: usize, const OUT: usize>(
     inputs: &[T; IN],
     layer: &Layer1D<T, OUT, IN>,
-) -> [T; OUT]
-where
-    T: Number,
-{
-    let Layer1D { weights, biases } = layer;
+) -> [T; OUT] {
     let mut outputs = [T::zero(); OUT];
-    for i in 0..OUT {
-        // Step 1: Initialize output with bias for neuron i
-        outputs[i] = biases[i];
-        for j in 0..IN {
-            // Step 2: Add weighted input: inputs[j] * weights[i][j]
-            outputs[i] = outputs[i] + inputs[j] * weights[i][j];
-        }
-    }
-    // Step 3: Return outputs
+    dense_linear_into(inputs, layer, &mut outputs);
     outputs
 }
 
+/// Like `dense_linear`, writing the outputs into `out` instead of returning a new array,
+/// so a loop can reuse one buffer for every sample.
+pub fn dense_linear_into<T: Number, const IN: usize, const OUT: usize>(
+    inputs: &[T; IN],
+    layer: &Layer1D<T, OUT, IN>,
+    out: &mut [T; OUT],
+) {
+    dense_linear_flat(layer.weights_flat(), &layer.biases, inputs, out);
+}
+
+/// Dense kernel over row-major flat weights (e.g. `Layer1D::weights_flat`):
+/// `outputs[i] = biases[i] + sum_j inputs[j] * weights[i * inputs.len() + j]`.
+///
+/// # Arguments
+/// * `weights` - `outputs.len() * inputs.len()` weights; row `i` holds those of output `i`.
+/// * `biases` - One bias per output.
+/// * `inputs` - Input values.
+/// * `outputs` - Overwritten with the results.
+///
+/// # Notes
+/// - Works on plain slices and does not allocate, so it serves any layer whose weights
+///   are stored contiguously; the rows are walked with iterators rather than indexing.
+/// - Sums in the same order as `dense_linear` always has, so results are bit-identical.
+/// - Panics if the slice lengths do not match.
+pub fn dense_linear_flat<T: Number>(weights: &[T], biases: &[T], inputs: &[T], outputs: &mut [T]) {
+    assert_eq!(biases.len(), outputs.len(), "biases must have one entry per output");
+    assert_eq!(weights.len(), outputs.len() * inputs.len(), "weights must have one row of inputs.len() values per output");
+    let n_in = inputs.len();
+    for (i, (output, &bias)) in outputs.iter_mut().zip(biases.iter()).enumerate() {
+        let row = &weights[i * n_in..(i + 1) * n_in];
+        *output = row.iter().zip(inputs.iter()).fold(bias, |acc, (&w, &x)| acc + x * w);
+    }
+}
+
+/// Performs forward propagation for a dense layer followed by an activation, in a single pass.
+///
+/// # Arguments
+/// * `inputs` - Array of input values of length `IN`.
+/// * `layer` - Reference to a `Layer1D` struct (see `dense_linear`).
+/// * `activation` - Activation applied to each pre-activation output.
+///
+/// # Returns
+/// * `([T; OUT], [T; OUT])` - Pre-activation outputs `z` and activated outputs `a = activation(z)`.
+///
+/// # Steps
+/// 1. Compute `z[i] = biases[i] + sum_j inputs[j] * weights[i][j]` with `dense_linear_flat`.
+/// 2. Apply the activation: `a[i] = activation(z[i])`.
+/// 3. Return both arrays, which are exactly the values backpropagation needs to cache.
+///
+pub fn dense_linear_activated<T: Real, const IN: usize, const OUT: usize>(
+    inputs: &[T; IN],
+    layer: &Layer1D<T, OUT, IN>,
+    activation: &Activation,
+) -> ([T; OUT], [T; OUT]) {
+    let mut pre_activations = [T::zero(); OUT];
+    let mut activations = [T::zero(); OUT];
+    dense_linear_activated_into(inputs, layer, activation, &mut pre_activations, &mut activations);
+    (pre_activations, activations)
+}
+
+/// Like `dense_linear_activated`, writing into caller-owned buffers: `pre_activations`
+/// receives `z` and `activations` receives `activation(z)`.
+pub fn dense_linear_activated_into<T: Real, const IN: usize, const OUT: usize>(
+    inputs: &[T; IN],
+    layer: &Layer1D<T, OUT, IN>,
+    activation: &Activation,
+    pre_activations: &mut [T; OUT],
+    activations: &mut [T; OUT],
+) {
+    // Step 1: Weighted sums plus biases, over the flat row-major weights
+    dense_linear_flat(layer.weights_flat(), &layer.biases, inputs, pre_activations);
+    // Step 2: Activate each pre-activation
+    for (a, &z) in activations.iter_mut().zip(pre_activations.iter()) {
+        *a = activation.apply(z);
+    }
+}
+
 /// Performs forward propagation for a dense 1D convolutional layer.
 ///
 /// # Arguments
@@ -60,14 +126,14 @@ where
 /// 2. Repeat for all output neurons.
 /// 3. Return the array of computed outputs.
 ///
+/// # Notes
+/// - Filter positions `j >= IN` are skipped silently; `try_dense_conv2d` rejects such layers.
+///
 pub fn dense_conv2d<T: Number, const IN: usize, const OUT: usize, const FILTER_SIZE: usize>(
     inputs: &[T; IN],
     layer: &Layer2D<T, OUT, FILTER_SIZE>,
-) -> [T; OUT]
-where
-    T: Number,
-{
-    let Layer2D { filters, biases } = layer;
+) -> [T; OUT] {
+    let Layer2D { filters, biases, .. } = layer;
     let mut outputs = [T::zero(); OUT];
     for i in 0..OUT {
         // Step 1: Initialize output with bias for filter i
@@ -81,4 +147,19 @@ where
     }
     // Step 3: Return outputs
     outputs
+}
+
+/// Like `dense_conv2d`, but fails instead of skipping filter positions when the filters
+/// are longer than the inputs (`FILTER_SIZE > IN`).
+///
+/// # Errors
+/// `LayerError::IndexOutOfRange` for the first filter position `IN` with no matching input.
+pub fn try_dense_conv2d<T: Number, const IN: usize, const OUT: usize, const FILTER_SIZE: usize>(
+    inputs: &[T; IN],
+    layer: &Layer2D<T, OUT, FILTER_SIZE>,
+) -> Result<[T; OUT], LayerError> {
+    if FILTER_SIZE > IN {
+        return Err(LayerError::IndexOutOfRange { index: IN, len: IN });
+    }
+    Ok(dense_conv2d(inputs, layer))
 }
\ No newline at end of file
@@ -0,0 +1,212 @@
+//! Output heads that bundle the last dense layer, its sigmoid and the matching loss.
+//!
+//! A head works on the logits directly: the loss is `binary_cross_entropy_with_logits`
+//! and the gradient of the loss with respect to each logit is `sigmoid(z) - t`. This
+//! avoids the `ln(0)` and vanishing-gradient problems of chaining a `Sigmoid` layer with
+//! `Loss::BinaryCrossEntropy` on saturated outputs.
+//!
+//! A head sits on top of a feature extractor (any `Sequential`, called the body) and
+//! trains together with it through `train_step`.
+
+use std::error::Error;
+use std::fmt;
+use num_traits::FromPrimitive;
+use crate::activation_fn::sigmoid;
+use crate::back_propagation::backward_pass_with;
+use crate::layers::{Layer, LayerError};
+use crate::loss_fn::binary_cross_entropy_with_logits;
+use crate::model::{Dense, Sequential};
+use crate::numbers::{Number, Real};
+use crate::random::Rng;
+
+/// Error returned when a head is built from a dense layer of the wrong shape.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeadError {
+    /// A `BinaryHead` needs exactly one output unit.
+    NotSingleOutput { found: usize },
+    /// A `MultiLabelHead` needs at least one output unit.
+    NoLabels,
+}
+
+impl fmt::Display for HeadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeadError::NotSingleOutput { found } => write!(f, "binary head needs one output unit, found {}", found),
+            HeadError::NoLabels => write!(f, "multi-label head needs at least one output unit"),
+        }
+    }
+}
+
+impl Error for HeadError {}
+
+/// Summed BCE over all logits and its gradient with respect to them.
+fn logit_loss_and_grad<T: Real>(logits: &[T], targets: &[T]) -> (T, Vec<T>) {
+    assert_eq!(logits.len(), targets.len(), "targets must have one entry per output unit");
+    let loss = logits.iter().zip(targets.iter())
+        .fold(T::zero(), |acc, (&z, &t)| acc + binary_cross_entropy_with_logits(z, t));
+    let grad = logits.iter().zip(targets.iter()).map(|(&z, &t)| sigmoid(z) - t).collect();
+    (loss, grad)
+}
+
+/// Adds the loss gradients of one sample to `dense` and returns `(loss, feature_grad)`.
+fn dense_backward<T: Real>(dense: &mut Dense<T>, features: &[T], targets: &[T]) -> Result<(T, Vec<T>), LayerError> {
+    if features.len() != dense.input_dim() {
+        return Err(LayerError::WrongLength { expected: dense.input_dim(), found: features.len() });
+    }
+    let logits = dense.forward(features);
+    let (loss, grad) = logit_loss_and_grad(&logits, targets);
+    let feature_grad = dense.backward(features, &logits, &grad);
+    Ok((loss, feature_grad))
+}
+
+/// Backpropagates through the head and `body` and takes one SGD step on both.
+fn body_train_step<T: Real>(dense: &mut Dense<T>, body: &mut Sequential<T>, inputs: &[T], targets: &[T], learning_rate: T) -> T {
+    assert_eq!(inputs.len(), body.input_dim(), "inputs must match the model input size");
+    body.zero_grad();
+    dense.zero_grad();
+    let (loss, _) = backward_pass_with(&mut body.layers, inputs, |features| {
+        dense_backward(dense, features, targets).unwrap_or_else(|e| panic!("{}", e))
+    });
+    body.sgd_step(learning_rate);
+    for (param, grad) in dense.params_mut() {
+        *param = *param - grad * learning_rate;
+    }
+    loss
+}
+
+/// Binary classification head: one logit, sigmoid probability and BCE loss.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BinaryHead<T: Number> {
+    pub dense: Dense<T>,
+}
+
+impl<T: Number> BinaryHead<T> {
+    /// Wraps a dense layer with one output unit; see `try_new`.
+    pub fn new(dense: Dense<T>) -> Self {
+        Self::try_new(dense).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Fallible version of `new`.
+    pub fn try_new(dense: Dense<T>) -> Result<Self, HeadError> {
+        match dense.output_dim() {
+            1 => Ok(BinaryHead { dense }),
+            found => Err(HeadError::NotSingleOutput { found }),
+        }
+    }
+
+    /// Number of features the head expects.
+    pub fn input_dim(&self) -> usize {
+        self.dense.input_dim()
+    }
+}
+
+impl<T: Number + FromPrimitive> BinaryHead<T> {
+    /// Creates a head over `input_dim` features with Glorot-uniform weights.
+    pub fn with_seed(input_dim: usize, seed: u64) -> Self {
+        BinaryHead { dense: Dense::glorot_uniform(input_dim, 1, &mut Rng::new(seed)) }
+    }
+}
+
+impl<T: Real> BinaryHead<T> {
+    /// Logit `z = w . features + b`.
+    pub fn logit(&self, features: &[T]) -> T {
+        self.dense.forward(features)[0]
+    }
+
+    /// Probability of the positive class, `sigmoid(z)`.
+    pub fn predict_proba(&self, features: &[T]) -> T {
+        sigmoid(self.logit(features))
+    }
+
+    /// Whether the positive class is predicted at `threshold` on the probability.
+    pub fn predict(&self, features: &[T], threshold: T) -> bool {
+        self.predict_proba(features).ge(threshold)
+    }
+
+    /// BCE loss of the sample, with `target` in `[0, 1]`.
+    pub fn loss(&self, features: &[T], target: T) -> T {
+        binary_cross_entropy_with_logits(self.logit(features), target)
+    }
+
+    /// Adds the gradients of `loss` to the head's dense layer.
+    ///
+    /// # Returns
+    /// * `(loss, feature_grad)` - The loss and its gradient with respect to `features`,
+    ///   to backpropagate into whatever produced the features.
+    pub fn backward(&mut self, features: &[T], target: T) -> (T, Vec<T>) {
+        dense_backward(&mut self.dense, features, &[target]).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// One SGD step on a body followed by this head. Returns the loss before the update.
+    pub fn train_step(&mut self, body: &mut Sequential<T>, inputs: &[T], target: T, learning_rate: T) -> T {
+        body_train_step(&mut self.dense, body, inputs, &[target], learning_rate)
+    }
+}
+
+/// Multi-label head: one independent sigmoid per label and the BCE summed over labels.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct MultiLabelHead<T: Number> {
+    pub dense: Dense<T>,
+}
+
+impl<T: Number> MultiLabelHead<T> {
+    /// Wraps a dense layer with one output unit per label; see `try_new`.
+    pub fn new(dense: Dense<T>) -> Self {
+        Self::try_new(dense).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Fallible version of `new`.
+    pub fn try_new(dense: Dense<T>) -> Result<Self, HeadError> {
+        if dense.output_dim() == 0 {
+            return Err(HeadError::NoLabels);
+        }
+        Ok(MultiLabelHead { dense })
+    }
+
+    pub fn input_dim(&self) -> usize {
+        self.dense.input_dim()
+    }
+
+    pub fn n_labels(&self) -> usize {
+        self.dense.output_dim()
+    }
+}
+
+impl<T: Number + FromPrimitive> MultiLabelHead<T> {
+    /// Creates a head over `input_dim` features for `n_labels` labels with Glorot-uniform weights.
+    pub fn with_seed(input_dim: usize, n_labels: usize, seed: u64) -> Self {
+        Self::new(Dense::glorot_uniform(input_dim, n_labels, &mut Rng::new(seed)))
+    }
+}
+
+impl<T: Real> MultiLabelHead<T> {
+    /// One logit per label.
+    pub fn logits(&self, features: &[T]) -> Vec<T> {
+        self.dense.forward(features)
+    }
+
+    /// Independent probability of every label.
+    pub fn predict_proba(&self, features: &[T]) -> Vec<T> {
+        self.logits(features).into_iter().map(sigmoid).collect()
+    }
+
+    /// Labels whose probability reaches `threshold`.
+    pub fn predict(&self, features: &[T], threshold: T) -> Vec<bool> {
+        self.predict_proba(features).into_iter().map(|p| p.ge(threshold)).collect()
+    }
+
+    /// BCE summed over labels, with `targets[k]` in `[0, 1]` for label `k`.
+    pub fn loss(&self, features: &[T], targets: &[T]) -> T {
+        logit_loss_and_grad(&self.logits(features), targets).0
+    }
+
+    /// Adds the gradients of `loss` to the head's dense layer; see `BinaryHead::backward`.
+    pub fn backward(&mut self, features: &[T], targets: &[T]) -> (T, Vec<T>) {
+        dense_backward(&mut self.dense, features, targets).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// One SGD step on a body followed by this head. Returns the loss before the update.
+    pub fn train_step(&mut self, body: &mut Sequential<T>, inputs: &[T], targets: &[T], learning_rate: T) -> T {
+        body_train_step(&mut self.dense, body, inputs, targets, learning_rate)
+    }
+}
@@ -1,16 +1,174 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
 use crate::numbers::*;
 use crate::forward_propagation::*;
+use crate::activation_fn::Activation;
+use num_traits::ToPrimitive;
+
+/// Error returned by the fallible (`try_*`) layer constructors and forward passes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayerError {
+    /// A slice or array had `found` entries where `expected` were required.
+    WrongLength { expected: usize, found: usize },
+    /// Index `index` is outside a table of `len` rows.
+    IndexOutOfRange { index: usize, len: usize },
+    /// Two models or layers differ in the named parameter (its name or its size).
+    ParameterMismatch { name: String },
+    /// Averaging weight `index` is negative or not finite.
+    InvalidWeight { index: usize },
+    /// The averaging weights do not have a positive, finite sum.
+    InvalidWeightTotal,
+}
+
+impl fmt::Display for LayerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayerError::WrongLength { expected, found } => write!(f, "expected {} values, found {}", expected, found),
+            LayerError::IndexOutOfRange { index, len } => write!(f, "index {} is out of range for {} rows", index, len),
+            LayerError::ParameterMismatch { name } => write!(f, "parameter {} does not match", name),
+            LayerError::InvalidWeight { index } => write!(f, "weight {} is negative or not finite", index),
+            LayerError::InvalidWeightTotal => write!(f, "weights must have a positive, finite sum"),
+        }
+    }
+}
+
+impl core::error::Error for LayerError {}
+
+/// Named, flattened (row-major) view of one parameter tensor of a layer or model.
+#[derive(Debug, PartialEq)]
+pub struct Parameter<'a, T> {
+    pub name: String,
+    pub values: Vec<&'a T>,
+}
+
+/// Mutable counterpart of `Parameter`, e.g. for weight decay or clipping.
+#[derive(Debug)]
+pub struct ParameterMut<'a, T> {
+    pub name: String,
+    pub values: Vec<&'a mut T>,
+}
+
+impl<T> Parameter<'_, T> {
+    /// Prefixes the name with `prefix.`, e.g. the index of the layer in a model.
+    pub fn prefixed(mut self, prefix: &str) -> Self {
+        self.name = format!("{}.{}", prefix, self.name);
+        self
+    }
+}
+
+impl<T> ParameterMut<'_, T> {
+    /// Prefixes the name with `prefix.`, e.g. the index of the layer in a model.
+    pub fn prefixed(mut self, prefix: &str) -> Self {
+        self.name = format!("{}.{}", prefix, self.name);
+        self
+    }
+}
+
+/// Common interface of layers that work on runtime-sized slices, such as the layers of
+/// `model::Sequential`.
+///
+/// A layer owns the gradients of its parameters: `backward` adds to them, an optimizer
+/// reads them next to the values through `params_mut`, and `zero_grad` resets them
+/// before the next batch.
+pub trait Layer<T: Number> {
+    /// Forward pass for one sample.
+    fn forward(&self, inputs: &[T]) -> Vec<T>;
+
+    /// Backward pass for one sample.
+    ///
+    /// # Arguments
+    /// * `inputs` - Inputs of the matching forward pass.
+    /// * `outputs` - Outputs of the matching forward pass.
+    /// * `grad_output` - Gradient of the loss with respect to `outputs`.
+    ///
+    /// # Returns
+    /// * Gradient of the loss with respect to `inputs`. Parameter gradients are added to
+    ///   the layer's accumulated gradients.
+    fn backward(&mut self, inputs: &[T], outputs: &[T], grad_output: &[T]) -> Vec<T>;
+
+    /// Every parameter together with its accumulated gradient, in a fixed order.
+    /// Empty for layers without parameters.
+    fn params_mut(&mut self) -> Vec<(&mut T, T)>;
+
+    /// Resets the accumulated gradients to zero.
+    fn zero_grad(&mut self);
+
+    /// Named views of the parameter tensors, in the same order as `params_mut`.
+    fn parameters(&self) -> Vec<Parameter<'_, T>> {
+        Vec::new()
+    }
+
+    /// Mutable named views of the parameter tensors, in the same order as `params_mut`.
+    fn parameters_mut(&mut self) -> Vec<ParameterMut<'_, T>> {
+        Vec::new()
+    }
+}
+
+/// Boxed layers, so stacks of different layer types (`Vec<Box<dyn Layer<T>>>`) can be
+/// passed to `backward_pass` and friends.
+impl<T: Number, L: Layer<T> + ?Sized> Layer<T> for Box<L> {
+    fn forward(&self, inputs: &[T]) -> Vec<T> {
+        (**self).forward(inputs)
+    }
+
+    fn backward(&mut self, inputs: &[T], outputs: &[T], grad_output: &[T]) -> Vec<T> {
+        (**self).backward(inputs, outputs, grad_output)
+    }
+
+    fn params_mut(&mut self) -> Vec<(&mut T, T)> {
+        (**self).params_mut()
+    }
+
+    fn zero_grad(&mut self) {
+        (**self).zero_grad()
+    }
+
+    fn parameters(&self) -> Vec<Parameter<'_, T>> {
+        (**self).parameters()
+    }
+
+    fn parameters_mut(&mut self) -> Vec<ParameterMut<'_, T>> {
+        (**self).parameters_mut()
+    }
+}
+
+impl<T: Real> Layer<T> for Activation {
+    fn forward(&self, inputs: &[T]) -> Vec<T> {
+        inputs.iter().map(|&x| self.apply(x)).collect()
+    }
+
+    fn backward(&mut self, inputs: &[T], _outputs: &[T], grad_output: &[T]) -> Vec<T> {
+        grad_output.iter().zip(inputs.iter()).map(|(&g, &z)| g * self.derivative(z)).collect()
+    }
+
+    fn params_mut(&mut self) -> Vec<(&mut T, T)> {
+        Vec::new()
+    }
+
+    fn zero_grad(&mut self) {}
+}
 
 /// Fully-connected layer with OUT outputs and IN inputs.
 /// weights[i][j] is weight for output i and input j.
+/// The nested arrays are contiguous and row-major; `weights_flat` views them as one slice.
+///
+/// Build it with `new` (or `linear` / `try_linear`): the gradients accumulated by the
+/// `Layer` impl live in a private field, read through `Layer::params_mut`.
 pub struct Layer1D<T: Number, const OUT: usize, const IN: usize> {
     pub weights: [[T; IN]; OUT],
     pub biases: [T; OUT],
+    /// Gradients accumulated by `Layer::backward`: the weights row-major, then the biases.
+    /// Empty until the first backward pass or `zero_grad`.
+    grads: Vec<T>,
 }
 
 impl<T: Number, const OUT: usize, const IN: usize> Layer1D<T, OUT, IN> {
     pub fn new(weights: [[T; IN]; OUT], biases: [T; OUT]) -> Self {
-        Layer1D { weights, biases }
+        Layer1D { weights, biases, grads: Vec::new() }
     }
 
     /// Forward pass: compute outputs = biases + W * inputs
@@ -18,24 +176,63 @@ impl<T: Number, const OUT: usize, const IN: usize> Layer1D<T, OUT, IN> {
         crate::forward_propagation::dense_linear(inputs, self)
     }
 
+    /// Like `forward`, writing the outputs into a reusable buffer.
+    pub fn forward_into(&self, inputs: &[T; IN], out: &mut [T; OUT]) {
+        crate::forward_propagation::dense_linear_into(inputs, self, out)
+    }
+
+    /// Forward pass followed by `activation`, computed in one pass.
+    /// Returns `(pre_activations, activations)`.
+    pub fn forward_activated(&self, inputs: &[T; IN], activation: &Activation) -> ([T; OUT], [T; OUT])
+    where
+        T: Real,
+    {
+        dense_linear_activated(inputs, self, activation)
+    }
+
+    /// All weights as one row-major slice of `OUT * IN` values: weight `(i, j)` is at
+    /// index `i * IN + j`. This is a view of `weights`, not a copy.
+    pub fn weights_flat(&self) -> &[T] {
+        self.weights.as_flattened()
+    }
+
+    /// Mutable version of `weights_flat`.
+    pub fn weights_flat_mut(&mut self) -> &mut [T] {
+        self.weights.as_flattened_mut()
+    }
+
+    /// Weight of output `i` for input `j`.
+    pub fn weight(&self, i: usize, j: usize) -> T {
+        self.weights_flat()[i * IN + j]
+    }
+
     /// Update weights and biases in-place given gradients and learning rate.
     /// weight_grads has same shape as weights: [OUT][IN], bias_grads length OUT.
     pub fn update_weights(&mut self, weight_grads: &[[T; IN]; OUT], bias_grads: &[T; OUT], learning_rate: T) {
-        for i in 0..OUT {
-            self.biases[i] = self.biases[i] - bias_grads[i] * learning_rate;
-            for j in 0..IN {
-                self.weights[i][j] = self.weights[i][j] - weight_grads[i][j] * learning_rate;
-            }
+        for (b, &g) in self.biases.iter_mut().zip(bias_grads.iter()) {
+            *b = *b - g * learning_rate;
+        }
+        for (w, &g) in self.weights_flat_mut().iter_mut().zip(weight_grads.as_flattened().iter()) {
+            *w = *w - g * learning_rate;
         }
     }
 }
 
+/// Bank of `FILTERS` filters over `FILTER_SIZE` inputs. Like `Layer1D`, it is built with
+/// `new` (or `conv2d` / `try_conv2d`) and keeps its accumulated gradients private.
 pub struct Layer2D<T: Number, const FILTERS: usize, const FILTER_SIZE: usize> {
     pub filters: [[T; FILTER_SIZE]; FILTERS],
     pub biases: [T; FILTERS],
+    /// Gradients accumulated by `Layer::backward`: the filters row-major, then the biases.
+    /// Empty until the first backward pass or `zero_grad`.
+    grads: Vec<T>,
 }
 
 impl<T: Number, const FILTERS: usize, const FILTER_SIZE: usize> Layer2D<T, FILTERS, FILTER_SIZE> {
+    pub fn new(filters: [[T; FILTER_SIZE]; FILTERS], biases: [T; FILTERS]) -> Self {
+        Layer2D { filters, biases, grads: Vec::new() }
+    }
+
     pub fn forward(&self, inputs: &[T; FILTER_SIZE]) -> [T; FILTERS] { 
         dense_conv2d(inputs, self)
     }
@@ -60,6 +257,7 @@ impl<T: Number, const FILTERS: usize, const FILTER_SIZE: usize> Layer2D<T, FILTE
 /// - If `values.len() < N`, remaining elements are filled with `T::zero()`.
 /// - If `values.len() > N`, excess values are ignored.
 /// - Bias array is always initialized to zeros.
+/// - Use `try_linear` to reject slices of the wrong length instead.
 ///
 pub fn linear<T: Number, const N: usize, const IN: usize>(values: &[T]) -> Layer1D<T, N, IN> {
     // Create a weight matrix with shape [N][IN] and fill it from the flattened `values` slice.
@@ -78,10 +276,7 @@ pub fn linear<T: Number, const N: usize, const IN: usize>(values: &[T]) -> Layer
         }
     }
 
-    Layer1D {
-        weights,
-        biases: [T::zero(); N],
-    }
+    Layer1D::new(weights, [T::zero(); N])
 }
 
 /// Creates a fixed-size 2D array representing a Conv2D layer's filters.
@@ -104,6 +299,7 @@ pub fn linear<T: Number, const N: usize, const IN: usize>(values: &[T]) -> Layer
 /// - If `values.len() < FILTERS * FILTER_SIZE`, remaining elements are filled with `T::zero()`.
 /// - If `values.len() > FILTERS * FILTER_SIZE`, excess values are ignored.
 /// - Bias array is always initialized to zeros.
+/// - Use `try_conv2d` to reject slices of the wrong length instead.
 ///
 pub fn conv2d<T: Number, const FILTERS: usize, const FILTER_SIZE: usize>(values: &[T]) -> Layer2D<T, FILTERS, FILTER_SIZE> {
     let mut arr = [[T::zero(); FILTER_SIZE]; FILTERS];
@@ -116,8 +312,255 @@ pub fn conv2d<T: Number, const FILTERS: usize, const FILTER_SIZE: usize>(values:
             }
         }
     }
-    Layer2D {
-        filters: arr,
-        biases: [T::zero(); FILTERS],
+    Layer2D::new(arr, [T::zero(); FILTERS])
+}
+
+/// Strict version of `linear`: fails unless `values` holds exactly `N * IN` weights.
+pub fn try_linear<T: Number, const N: usize, const IN: usize>(values: &[T]) -> Result<Layer1D<T, N, IN>, LayerError> {
+    if values.len() != N * IN {
+        return Err(LayerError::WrongLength { expected: N * IN, found: values.len() });
+    }
+    Ok(linear(values))
+}
+
+/// Strict version of `conv2d`: fails unless `values` holds exactly `FILTERS * FILTER_SIZE` weights.
+pub fn try_conv2d<T: Number, const FILTERS: usize, const FILTER_SIZE: usize>(values: &[T]) -> Result<Layer2D<T, FILTERS, FILTER_SIZE>, LayerError> {
+    if values.len() != FILTERS * FILTER_SIZE {
+        return Err(LayerError::WrongLength { expected: FILTERS * FILTER_SIZE, found: values.len() });
+    }
+    Ok(conv2d(values))
+}
+
+/// How rows of an `Embedding` are initialized for tokens missing from a pretrained file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OovInit {
+    /// Out-of-vocabulary rows are all zeros.
+    Zeros,
+    /// Out-of-vocabulary rows are drawn uniformly from `[-scale, scale]`.
+    Uniform { scale: f64, seed: u64 },
+}
+
+/// Embedding lookup table mapping token indices to DIM-dimensional vectors.
+/// weights[i] is the vector for token index i.
+pub struct Embedding<T: Number, const DIM: usize> {
+    pub weights: Vec<[T; DIM]>,
+    /// When `true`, `update_weights` leaves the table unchanged (e.g. for pretrained vectors),
+    /// and through the `Layer` trait the table has no parameters.
+    pub frozen: bool,
+    /// Gradients accumulated by `Layer::backward`, one row per token. Empty until the
+    /// first backward pass or `zero_grad`.
+    pub grads: Vec<[T; DIM]>,
+}
+
+impl<T: Number, const DIM: usize> Embedding<T, DIM> {
+    pub fn new(weights: Vec<[T; DIM]>) -> Self {
+        Embedding { weights, frozen: false, grads: Vec::new() }
+    }
+
+    /// Creates a trainable table of `vocab_size` zero vectors.
+    pub fn zeros(vocab_size: usize) -> Self {
+        Embedding::new(vec![[T::zero(); DIM]; vocab_size])
+    }
+
+    /// Number of rows (tokens) in the table.
+    pub fn vocab_size(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// Forward pass: look up the vector for token `index`. Panics if `index` is out of range.
+    pub fn forward(&self, index: usize) -> [T; DIM] {
+        self.weights[index]
+    }
+
+    /// Look up the vectors for a sequence of token indices.
+    pub fn forward_sequence(&self, indices: &[usize]) -> Vec<[T; DIM]> {
+        indices.iter().map(|&i| self.weights[i]).collect()
+    }
+
+    /// Like `forward`, but returns an error instead of panicking for an unknown token.
+    pub fn try_forward(&self, index: usize) -> Result<[T; DIM], LayerError> {
+        self.weights.get(index).copied().ok_or(LayerError::IndexOutOfRange { index, len: self.weights.len() })
+    }
+
+    /// Like `forward_sequence`, failing on the first unknown token.
+    pub fn try_forward_sequence(&self, indices: &[usize]) -> Result<Vec<[T; DIM]>, LayerError> {
+        indices.iter().map(|&i| self.try_forward(i)).collect()
+    }
+
+    /// Update the row for token `index` in-place given its gradient and learning rate.
+    /// Does nothing when the table is frozen.
+    pub fn update_weights(&mut self, index: usize, grads: &[T; DIM], learning_rate: T) {
+        if self.frozen {
+            return;
+        }
+        for j in 0..DIM {
+            self.weights[index][j] = self.weights[index][j] - grads[j] * learning_rate;
+        }
+    }
+}
+
+/// Backward pass of `outputs = biases + W * inputs` for a row-major `W` with `inputs.len()`
+/// columns: adds the weight gradients and then the bias gradients to `grads`, and returns
+/// the gradient with respect to `inputs`.
+fn dense_backward<T: Number>(weights: &[T], grads: &mut [T], inputs: &[T], grad_output: &[T]) -> Vec<T> {
+    let columns = inputs.len();
+    let (weight_grads, bias_grads) = grads.split_at_mut(weights.len());
+    let mut upstream = vec![T::zero(); columns];
+    for (i, &g) in grad_output.iter().enumerate() {
+        bias_grads[i] = bias_grads[i] + g;
+        for j in 0..columns {
+            weight_grads[i * columns + j] = weight_grads[i * columns + j] + g * inputs[j];
+            upstream[j] = upstream[j] + weights[i * columns + j] * g;
+        }
+    }
+    upstream
+}
+
+/// Runtime-sized access for stacking with other layers: `forward` and `backward` panic
+/// unless `inputs` has `IN` values.
+impl<T: Number, const OUT: usize, const IN: usize> Layer<T> for Layer1D<T, OUT, IN> {
+    fn forward(&self, inputs: &[T]) -> Vec<T> {
+        let inputs: &[T; IN] = inputs.try_into().expect("inputs must have one entry per weight column");
+        Layer1D::forward(self, inputs).to_vec()
+    }
+
+    fn backward(&mut self, inputs: &[T], _outputs: &[T], grad_output: &[T]) -> Vec<T> {
+        assert_eq!(inputs.len(), IN, "inputs must have one entry per weight column");
+        assert_eq!(grad_output.len(), OUT, "grad_output must have one entry per output");
+        if self.grads.len() != OUT * IN + OUT {
+            self.zero_grad();
+        }
+        dense_backward(self.weights.as_flattened(), &mut self.grads, inputs, grad_output)
+    }
+
+    fn params_mut(&mut self) -> Vec<(&mut T, T)> {
+        if self.grads.len() != OUT * IN + OUT {
+            self.zero_grad();
+        }
+        self.weights.as_flattened_mut().iter_mut().chain(self.biases.iter_mut()).zip(self.grads.iter().copied()).collect()
+    }
+
+    fn zero_grad(&mut self) {
+        self.grads = vec![T::zero(); OUT * IN + OUT];
+    }
+
+    fn parameters(&self) -> Vec<Parameter<'_, T>> {
+        vec![
+            Parameter { name: "weights".to_string(), values: self.weights.as_flattened().iter().collect() },
+            Parameter { name: "biases".to_string(), values: self.biases.iter().collect() },
+        ]
+    }
+
+    fn parameters_mut(&mut self) -> Vec<ParameterMut<'_, T>> {
+        vec![
+            ParameterMut { name: "weights".to_string(), values: self.weights.as_flattened_mut().iter_mut().collect() },
+            ParameterMut { name: "biases".to_string(), values: self.biases.iter_mut().collect() },
+        ]
+    }
+}
+
+/// Runtime-sized access for stacking with other layers: `forward` and `backward` panic
+/// unless `inputs` has `FILTER_SIZE` values.
+impl<T: Number, const FILTERS: usize, const FILTER_SIZE: usize> Layer<T> for Layer2D<T, FILTERS, FILTER_SIZE> {
+    fn forward(&self, inputs: &[T]) -> Vec<T> {
+        let inputs: &[T; FILTER_SIZE] = inputs.try_into().expect("inputs must have one entry per filter position");
+        Layer2D::forward(self, inputs).to_vec()
+    }
+
+    fn backward(&mut self, inputs: &[T], _outputs: &[T], grad_output: &[T]) -> Vec<T> {
+        assert_eq!(inputs.len(), FILTER_SIZE, "inputs must have one entry per filter position");
+        assert_eq!(grad_output.len(), FILTERS, "grad_output must have one entry per filter");
+        if self.grads.len() != FILTERS * FILTER_SIZE + FILTERS {
+            self.zero_grad();
+        }
+        dense_backward(self.filters.as_flattened(), &mut self.grads, inputs, grad_output)
+    }
+
+    fn params_mut(&mut self) -> Vec<(&mut T, T)> {
+        if self.grads.len() != FILTERS * FILTER_SIZE + FILTERS {
+            self.zero_grad();
+        }
+        self.filters.as_flattened_mut().iter_mut().chain(self.biases.iter_mut()).zip(self.grads.iter().copied()).collect()
+    }
+
+    fn zero_grad(&mut self) {
+        self.grads = vec![T::zero(); FILTERS * FILTER_SIZE + FILTERS];
+    }
+
+    fn parameters(&self) -> Vec<Parameter<'_, T>> {
+        vec![
+            Parameter { name: "filters".to_string(), values: self.filters.as_flattened().iter().collect() },
+            Parameter { name: "biases".to_string(), values: self.biases.iter().collect() },
+        ]
+    }
+
+    fn parameters_mut(&mut self) -> Vec<ParameterMut<'_, T>> {
+        vec![
+            ParameterMut { name: "filters".to_string(), values: self.filters.as_flattened_mut().iter_mut().collect() },
+            ParameterMut { name: "biases".to_string(), values: self.biases.iter_mut().collect() },
+        ]
+    }
+}
+
+/// Token indices in, concatenated vectors out: `inputs` holds one index per position
+/// (as a number, e.g. `3.0`) and `forward` returns `inputs.len() * DIM` values. The
+/// indices are not differentiable, so `backward` returns zeros for them; it panics for
+/// an index that is negative, fractional or out of range.
+impl<T: Number + ToPrimitive, const DIM: usize> Layer<T> for Embedding<T, DIM> {
+    fn forward(&self, inputs: &[T]) -> Vec<T> {
+        inputs.iter().flat_map(|&x| self.weights[token_index(x, self.weights.len())]).collect()
+    }
+
+    fn backward(&mut self, inputs: &[T], _outputs: &[T], grad_output: &[T]) -> Vec<T> {
+        assert_eq!(grad_output.len(), inputs.len() * DIM, "grad_output must have DIM entries per input");
+        if !self.frozen {
+            if self.grads.len() != self.weights.len() {
+                self.zero_grad();
+            }
+            for (&x, g) in inputs.iter().zip(grad_output.chunks_exact(DIM.max(1))) {
+                let row = &mut self.grads[token_index(x, self.weights.len())];
+                for (r, &gi) in row.iter_mut().zip(g.iter()) {
+                    *r = *r + gi;
+                }
+            }
+        }
+        vec![T::zero(); inputs.len()]
+    }
+
+    fn params_mut(&mut self) -> Vec<(&mut T, T)> {
+        if self.frozen {
+            return Vec::new();
+        }
+        if self.grads.len() != self.weights.len() {
+            self.zero_grad();
+        }
+        self.weights.as_flattened_mut().iter_mut().zip(self.grads.as_flattened().iter().copied()).collect()
+    }
+
+    fn zero_grad(&mut self) {
+        self.grads = vec![[T::zero(); DIM]; self.weights.len()];
+    }
+
+    fn parameters(&self) -> Vec<Parameter<'_, T>> {
+        if self.frozen {
+            return Vec::new();
+        }
+        vec![Parameter { name: "weights".to_string(), values: self.weights.as_flattened().iter().collect() }]
+    }
+
+    fn parameters_mut(&mut self) -> Vec<ParameterMut<'_, T>> {
+        if self.frozen {
+            return Vec::new();
+        }
+        vec![ParameterMut { name: "weights".to_string(), values: self.weights.as_flattened_mut().iter_mut().collect() }]
     }
-}
\ No newline at end of file
+}
+
+/// Row of the token index `x` in a table of `len` rows.
+fn token_index<T: ToPrimitive>(x: T, len: usize) -> usize {
+    let index = x.to_f64().and_then(|v| v.to_usize().filter(|&i| i as f64 == v));
+    match index {
+        Some(index) if index < len => index,
+        _ => panic!("embedding inputs must be token indices below {}", len),
+    }
+}
@@ -1,7 +1,73 @@
+//! Small neural network library over generic `Number` types.
+//!
+//! With the default `std` feature every module is available. Without it the crate is
+//! `#![no_std]` (it still needs `alloc`) and only the inference core is compiled:
+//! `numbers`, `layers`, `activation_fn`, `forward_propagation` and `perceptron`, so
+//! trained models can run on microcontrollers.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![allow(clippy::needless_range_loop)]
+
+extern crate alloc;
+
 pub mod numbers;
+#[cfg(feature = "std")]
 pub mod data_handling;
 pub mod layers;
 pub mod activation_fn;
 pub mod forward_propagation;
+pub mod perceptron;
+#[cfg(feature = "std")]
 pub mod loss_fn;
-pub mod back_propagation;
\ No newline at end of file
+#[cfg(feature = "std")]
+pub mod back_propagation;
+#[cfg(feature = "std")]
+pub mod metrics;
+#[cfg(feature = "std")]
+pub mod random;
+#[cfg(feature = "std")]
+pub mod training;
+#[cfg(feature = "std")]
+pub mod preprocessing;
+#[cfg(feature = "std")]
+pub mod text;
+#[cfg(feature = "std")]
+pub mod dataset;
+#[cfg(feature = "std")]
+pub mod augmentation;
+#[cfg(feature = "std")]
+pub mod decision;
+#[cfg(feature = "std")]
+pub mod backend;
+#[cfg(feature = "std")]
+pub mod model;
+#[cfg(feature = "std")]
+pub mod attention;
+#[cfg(feature = "std")]
+pub mod vae;
+#[cfg(feature = "std")]
+pub mod heads;
+#[cfg(feature = "std")]
+pub mod conformal;
+#[cfg(feature = "std")]
+pub mod ensemble;
+#[cfg(feature = "std")]
+pub mod calibration;
+#[cfg(feature = "std")]
+pub mod quantization;
+#[cfg(feature = "std")]
+pub mod pruning;
+#[cfg(feature = "std")]
+pub mod diagnostics;
+#[cfg(feature = "std")]
+pub mod explain;
+#[cfg(feature = "std")]
+pub mod export;
+#[cfg(feature = "std")]
+pub mod config;
+#[cfg(feature = "viz")]
+pub mod viz;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "python")]
+pub mod python;
@@ -1,16 +1,27 @@
 //! Loss functions implemented generically over a numeric type `T`.
 //!
-//! This module provides three common loss functions used in machine learning:
+//! This module provides common loss functions used in machine learning:
 //! - Mean Squared Error (MSE)
+//! - Mean Absolute Error (MAE)
 //! - Cross-Entropy (element-wise)
 //! - Binary Cross-Entropy (scalar, single-prediction binary case)
+//! - Kullback-Leibler divergence (distribution targets)
+//! - Hinge and squared hinge (margin classifiers with `-1`/`1` targets)
+//! - Quantile (pinball) loss, for predicting conditional quantiles
+//! - Poisson and Tweedie deviances (counts, claim amounts and other non-negative targets)
+//!
+//! Custom losses implement the `LossFn` trait and can be looked up by name in a
+//! `LossRegistry`. `masked_forward` / `masked_derivative` ignore the padded timesteps
+//! of variable-length sequences.
 //!
 //! Each function is generic over `T` which is expected to implement the project's
 //! `Number` trait (for arithmetic and numeric helpers) and `FromPrimitive` (to
 //! construct constants like `2.0` from primitive floats). The implementations
 //! assume `T` behaves like a floating-point numeric type for correct results.
 
-use crate::numbers::Number;
+use std::collections::BTreeMap;
+use crate::activation_fn::logsumexp;
+use crate::numbers::{Number, Real};
 use num_traits::FromPrimitive;
 
 /// Compute the **mean squared error (MSE)** between `predictions` and `targets`.
@@ -49,6 +60,19 @@ pub fn mean_squared_error<T: Number + FromPrimitive>(predictions: &[T], targets:
     sum / n
 }
 
+/// Compute the **mean absolute error (MAE)**, `1/n * sum_i |p_i - t_i|`.
+///
+/// Same preconditions as `mean_squared_error`. Large errors weigh linearly rather than
+/// quadratically, so a few outlying targets pull the fit less.
+pub fn mean_absolute_error<T: Number + FromPrimitive>(predictions: &[T], targets: &[T]) -> T {
+    let n = T::to_number(predictions.len() as f64);
+    let mut sum = T::zero();
+    for i in 0..predictions.len() {
+        sum = sum + (predictions[i] - targets[i]).abs();
+    }
+    sum / n
+}
+
 /// Compute the (element-wise) **cross-entropy loss** between `predictions` and `targets`.
 ///
 /// This function implements the usual cross-entropy term applied element-wise
@@ -63,33 +87,43 @@ pub fn mean_squared_error<T: Number + FromPrimitive>(predictions: &[T], targets:
 /// 1. Convert the length `n` to `T` with `T::to_number(predictions.len() as f64)`.
 /// 2. Initialize an accumulator `sum` to `T::zero()`.
 /// 3. For each element `i`:
-///    - Clamp `predictions[i]` to a small positive lower bound (`eps = 1e-15`) to
-///      avoid `ln(0)`. The code chooses `p = max(predictions[i], eps)` implemented
-///      by comparing with `T::to_number(1e-15)`.
+///    - Clamp `predictions[i]` to a small positive lower bound `eps` (see
+///      `probability_epsilon`) to avoid `ln(0)`: `p = max(predictions[i], eps)`.
 ///    - Add `- targets[i] * p.ln()` to the accumulator.
 /// 4. Return the average `sum / n`.
 ///
 /// # Preconditions and notes
 /// - `predictions` should contain values in `[0, 1]` representing probabilities.
 /// - `targets` is typically one-hot encoded (0 or 1) or soft labels in `[0, 1]`.
-/// - The function prevents `ln(0)` by clamping `p` to `1e-15` converted to `T`.
+/// - The function prevents `ln(0)` by clamping `p` to `eps`.
 /// - The natural logarithm (`ln`) is used.
 ///
-pub fn cross_entropy_loss<T: Number + FromPrimitive>(predictions: &[T], targets: &[T]) -> T {
+pub fn cross_entropy_loss<T: Real + FromPrimitive>(predictions: &[T], targets: &[T]) -> T {
     let n = T::to_number(predictions.len() as f64);
     let mut sum = T::zero();
     for i in 0..predictions.len() {
         // To avoid log(0), we clamp predictions to a small positive value
-        let p = if predictions[i] < T::to_number(1e-15) {
-            T::to_number(1e-15)
-        } else {
-            predictions[i]
-        };
+        let p = predictions[i].max(probability_epsilon());
         sum = sum - targets[i] * p.ln();
     }
     sum / n
 }
 
+/// Cross-entropy against a class index: `cross_entropy_loss` with a one-hot target,
+/// without building the one-hot vector.
+///
+/// # Returns
+/// * `-ln(max(predictions[class], eps)) / n`, where `n = predictions.len()`, so it equals
+///   `cross_entropy_loss(predictions, one_hot)` with `one_hot[class] = 1`.
+///
+/// # Notes
+/// - Panics if `class >= predictions.len()`.
+pub fn sparse_cross_entropy_loss<T: Real + FromPrimitive>(predictions: &[T], class: usize) -> T {
+    assert!(class < predictions.len(), "class index out of range");
+    let n = T::to_number(predictions.len() as f64);
+    - predictions[class].max(probability_epsilon()).ln() / n
+}
+
 /// Compute the **binary cross-entropy** (BCE) for a *single* scalar prediction and target.
 ///
 /// This implements the scalar binary cross-entropy term:
@@ -100,27 +134,314 @@ pub fn cross_entropy_loss<T: Number + FromPrimitive>(predictions: &[T], targets:
 /// target (commonly `0` or `1`, but soft labels in `[0,1]` are supported).
 ///
 /// # Steps performed by the function
-/// 1. Convert `eps = 1e-15` to `T` using `T::to_number(1e-15)`.
-/// 2. Clamp `prediction` to the interval `[eps, 1 - eps]` to avoid `ln(0)` and to
-///    prevent numerical infinities. This yields the stable `p` used below.
-/// 3. Compute `one_minus_p = 1 - p` and clamp it to `eps` as well to keep the
+/// 1. Clamp `prediction` to the interval `[eps, 1 - eps]` (see `probability_epsilon`)
+///    to avoid `ln(0)` and to prevent numerical infinities. This yields the stable `p` used below.
+/// 2. Compute `one_minus_p = 1 - p` and clamp it to `eps` as well to keep the
 ///    logarithm numerically stable.
-/// 4. Return `- (target * ln(p) + (1 - target) * ln(1 - p))`.
+/// 3. Return `- (target * ln(p) + (1 - target) * ln(1 - p))`.
 ///
 /// # Preconditions and notes
 /// - This function is scalar: it computes BCE for a single `prediction` and `target`.
 /// - For batched binary BCE, call this per element and average (or implement a
 ///   batched wrapper).
-/// - Clamping uses `1e-15` as a practical epsilon; depending on `T`'s range you
-///   may choose a different epsilon.
 ///
-pub fn binary_cross_entropy_loss<T: Number + FromPrimitive>(prediction: T, target: T) -> T {
-    let eps = T::to_number(1e-15);
-    let p = if prediction < eps { eps } else if prediction > T::one() - eps { T::one() - eps } else { prediction };
-    let one_minus_p = if T::one() - p < eps { eps } else { T::one() - p };
+pub fn binary_cross_entropy_loss<T: Real + FromPrimitive>(prediction: T, target: T) -> T {
+    let (p, one_minus_p) = clamp_probability(prediction);
     - (target * p.ln() + (T::one() - target) * one_minus_p.ln())
 }
 
+/// Binary cross-entropy of `sigmoid(logit)` against `target`, computed from the logit.
+///
+/// # Returns
+/// * `max(z, 0) - z * t + ln(1 + exp(-|z|))`, which equals
+///   `binary_cross_entropy_loss(sigmoid(z), t)` but never overflows or takes `ln(0)`.
+///   Its derivative with respect to `z` is simply `sigmoid(z) - t`.
+pub fn binary_cross_entropy_with_logits<T: Real>(logit: T, target: T) -> T {
+    logit.max(T::zero()) - logit * target + (-logit.abs()).exp().ln_1p()
+}
+
+/// Cross-entropy of `softmax(logits)` against `targets`, computed from the logits.
+///
+/// # Returns
+/// * `-(1/n) * sum_i targets[i] * log_softmax(logits)[i]`, which equals
+///   `cross_entropy_loss(softmax(logits), targets)` without the clamp: the
+///   log-probabilities come from `logsumexp`, so they stay finite for any logits.
+///
+/// # Notes
+/// - The gradient with respect to `logits[i]` is `(softmax(logits)[i] - targets[i]) / n`
+///   for targets that sum to one.
+pub fn cross_entropy_with_logits<T: Real + FromPrimitive>(logits: &[T], targets: &[T]) -> T {
+    assert_eq!(logits.len(), targets.len(), "logits and targets must have the same length");
+    let lse = logsumexp(logits);
+    let n = T::to_number(logits.len() as f64);
+    logits.iter().zip(targets.iter()).fold(T::zero(), |acc, (&z, &t)| acc - t * (z - lse)) / n
+}
+
+/// Compute the **Kullback-Leibler divergence** `KL(targets || predictions)`, averaged over elements.
+///
+/// $$L = \frac{1}{n} \sum_{i=0}^{n-1} t_i \ln\frac{t_i}{p_i}$$
+///
+/// # Preconditions and notes
+/// - Both slices should hold probability distributions; terms with `t_i = 0` contribute
+///   zero (`0 ln 0 = 0`), and `p_i` is clamped to `eps` like in `cross_entropy_loss`.
+/// - KL differs from cross-entropy only by the target entropy, which does not depend on
+///   the predictions, so both have the same derivative `- t_i / p_i`. Unlike cross-entropy
+///   it is zero when the predictions equal the targets, which makes it easier to read
+///   for soft targets (e.g. distillation).
+pub fn kl_divergence<T: Real + FromPrimitive>(predictions: &[T], targets: &[T]) -> T {
+    let n = T::to_number(predictions.len() as f64);
+    let mut sum = T::zero();
+    for i in 0..predictions.len() {
+        sum = sum + kl_term(predictions[i], targets[i]);
+    }
+    sum / n
+}
+
+/// `t ln(t / p)` with `0 ln 0 = 0` and `p` clamped to `eps`.
+fn kl_term<T: Real + FromPrimitive>(p: T, t: T) -> T {
+    if t.gt(T::zero()) { t * (t.ln() - p.max(probability_epsilon()).ln()) } else { T::zero() }
+}
+
+/// Compute the **hinge loss** for margin classifiers, averaged over elements.
+///
+/// $$L = \frac{1}{n} \sum_{i=0}^{n-1} \max(0, 1 - t_i p_i)$$
+///
+/// # Preconditions and notes
+/// - `predictions` are raw scores (no activation); `targets` are `-1` or `1`.
+/// - Scores on the correct side of the margin (`t_i p_i >= 1`) cost nothing.
+pub fn hinge_loss<T: Number + FromPrimitive>(predictions: &[T], targets: &[T]) -> T {
+    let n = T::to_number(predictions.len() as f64);
+    let mut sum = T::zero();
+    for i in 0..predictions.len() {
+        sum = sum + hinge_margin(predictions[i], targets[i]);
+    }
+    sum / n
+}
+
+/// Compute the **squared hinge loss**, `1/n * sum_i max(0, 1 - t_i p_i)^2`.
+///
+/// Same preconditions as `hinge_loss`; the square makes the loss differentiable at the
+/// margin and penalizes large violations more.
+pub fn squared_hinge_loss<T: Number + FromPrimitive>(predictions: &[T], targets: &[T]) -> T {
+    let n = T::to_number(predictions.len() as f64);
+    let mut sum = T::zero();
+    for i in 0..predictions.len() {
+        let margin = hinge_margin(predictions[i], targets[i]);
+        sum = sum + margin * margin;
+    }
+    sum / n
+}
+
+/// Compute the **quantile (pinball) loss** at level `tau`,
+/// `1/n * sum_i max(tau (t_i - p_i), (tau - 1) (t_i - p_i))`.
+///
+/// Minimizing it makes `p` the conditional `tau`-quantile of the targets: under-predictions
+/// cost `tau` per unit and over-predictions `1 - tau`, so `tau = 0.9` gives a P90
+/// forecast and `tau = 0.5` half the mean absolute error (the median).
+pub fn quantile_loss<T: Number + FromPrimitive>(predictions: &[T], targets: &[T], tau: f64) -> T {
+    let n = T::to_number(predictions.len() as f64);
+    let tau = T::to_number(tau);
+    let mut sum = T::zero();
+    for i in 0..predictions.len() {
+        sum = sum + pinball(predictions[i], targets[i], tau);
+    }
+    sum / n
+}
+
+fn pinball<T: Number>(p: T, t: T, tau: T) -> T {
+    let diff = t - p;
+    if diff.ge(T::zero()) { tau * diff } else { (tau - T::one()) * diff }
+}
+
+/// Compute the **Poisson deviance**, averaged over elements.
+///
+/// $$L = \frac{1}{n} \sum_{i=0}^{n-1} 2 \left(t_i \ln\frac{t_i}{\mu_i} - t_i + \mu_i\right)$$
+///
+/// # Preconditions and notes
+/// - The predictions are the expected counts `mu_i` and must be positive, e.g. the output
+///   of a softplus layer; the targets are non-negative counts (`0 ln 0 = 0`).
+/// - Equals `tweedie_deviance` with `power = 1`. It is zero when `mu = t`, and its
+///   derivative is `2 (1 - t / mu)`.
+pub fn poisson_deviance<T: Real + FromPrimitive>(predictions: &[T], targets: &[T]) -> T {
+    tweedie_deviance(predictions, targets, 1.0)
+}
+
+/// Compute the **Tweedie deviance** with variance power `power`, averaged over elements.
+///
+/// # Behavior
+/// - `power = 0`: squared error `(t - mu)^2`.
+/// - `power = 1`: Poisson deviance (see `poisson_deviance`).
+/// - `1 < power < 2`: compound Poisson-gamma, the usual choice for insurance claims, which
+///   are exactly zero for most policies and continuous otherwise.
+/// - `power = 2`: gamma deviance `2 (ln(mu / t) + t / mu - 1)`.
+/// - Otherwise `2 (t^(2-p) / ((1-p)(2-p)) - t mu^(1-p) / (1-p) + mu^(2-p) / (2-p))`.
+///
+/// # Notes
+/// - The derivative with respect to `mu` is `2 mu^(-p) (mu - t)` for every power.
+/// - `Loss::Tweedie` accepts `power = 0` or `power >= 1`, positive predictions, and
+///   non-negative targets (positive for `power >= 2`).
+pub fn tweedie_deviance<T: Real + FromPrimitive>(predictions: &[T], targets: &[T], power: f64) -> T {
+    let n = T::to_number(predictions.len() as f64);
+    let mut sum = T::zero();
+    for i in 0..predictions.len() {
+        sum = sum + tweedie_term(predictions[i], targets[i], power);
+    }
+    sum / n
+}
+
+/// Unit deviance of one prediction `mu` against target `t`.
+fn tweedie_term<T: Real + FromPrimitive>(mu: T, t: T, power: f64) -> T {
+    let two = T::to_number::<T>(2.0);
+    if power == 0.0 {
+        let diff = t - mu;
+        diff * diff
+    } else if power == 1.0 {
+        let t_ln = if t.gt(T::zero()) { t * (t / mu).ln() } else { T::zero() };
+        two * (t_ln - t + mu)
+    } else if power == 2.0 {
+        two * ((mu / t).ln() + t / mu - T::one())
+    } else {
+        let (one_p, two_p) = (T::to_number::<T>(1.0 - power), T::to_number::<T>(2.0 - power));
+        let t_term = if t.gt(T::zero()) { t.powf(two_p) / (one_p * two_p) } else { T::zero() };
+        two * (t_term - t * mu.powf(one_p) / one_p + mu.powf(two_p) / two_p)
+    }
+}
+
+/// `max(0, 1 - t p)`.
+fn hinge_margin<T: Number>(p: T, t: T) -> T {
+    (T::one() - t * p).max(T::zero())
+}
+
+/// Moves the index of an `InvalidProbability` error from a row to the flat batch.
+fn shift_index(error: LossError, offset: usize) -> LossError {
+    match error {
+        LossError::InvalidProbability { index } => LossError::InvalidProbability { index: index + offset },
+        error => error,
+    }
+}
+
+/// Epsilon used to keep probabilities away from `0` and `1` before taking logarithms
+/// or dividing: `1e-15`, or `T::EPSILON` if that is larger (e.g. for `f32`, where
+/// `1 - 1e-15` rounds to `1`).
+fn probability_epsilon<T: Number + FromPrimitive>() -> T {
+    T::to_number::<T>(1e-15).max(T::EPSILON)
+}
+
+/// Clamp a probability into `[eps, 1 - eps]` and return it together with a clamped `1 - p`.
+fn clamp_probability<T: Number + FromPrimitive>(p: T) -> (T, T) {
+    let eps = probability_epsilon::<T>();
+    let p = p.max(eps).min(T::one() - eps);
+    (p, (T::one() - p).max(eps))
+}
+
+/// Error returned by the fallible `Loss::try_forward` / `Loss::try_derivative`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LossError {
+    /// `predictions` and `targets` have different lengths.
+    LengthMismatch { predictions: usize, targets: usize },
+    /// `predictions` and `targets` are empty.
+    EmptyInput,
+    /// `BinaryCrossEntropy::forward` needs exactly one prediction and one target.
+    ExpectedSingleValue { found: usize },
+    /// A probability-valued prediction or target at `index` lies outside `[0, 1]` (or is NaN).
+    InvalidProbability { index: usize },
+    /// A `Hinge` / `SquaredHinge` target at `index` is neither `-1` nor `1`.
+    InvalidMarginTarget { index: usize },
+    /// A class index target is not below the number of predictions.
+    ClassOutOfRange { class: usize, classes: usize },
+    /// The loss does not take class index targets (see `Loss::try_forward_class`).
+    UnsupportedClassTarget,
+    /// The level of `Loss::Quantile` is not in `(0, 1)`.
+    InvalidQuantile { tau: f64 },
+    /// The power of `Loss::Tweedie` is neither `0` nor at least `1`.
+    InvalidTweediePower { power: f64 },
+    /// A `Poisson` / `Tweedie` prediction at `index` is not positive, or its target is
+    /// negative (non-positive for powers of at least `2`), or either is NaN.
+    OutOfDomain { index: usize },
+    /// A loss parameter (a quantile level or a Tweedie power) cannot be represented in
+    /// the number type of the predictions.
+    Unrepresentable { value: f64 },
+}
+
+impl std::fmt::Display for LossError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LossError::LengthMismatch { predictions, targets } => write!(
+                f, "predictions and targets must have the same length (got {} and {})", predictions, targets
+            ),
+            LossError::EmptyInput => write!(f, "predictions and targets must not be empty"),
+            LossError::ExpectedSingleValue { found } => write!(
+                f, "BinaryCrossEntropy loss expects single prediction and target values (got {}).", found
+            ),
+            LossError::InvalidProbability { index } => write!(
+                f, "value at index {} must be a probability in [0, 1]", index
+            ),
+            LossError::InvalidMarginTarget { index } => write!(
+                f, "hinge target at index {} must be -1 or 1", index
+            ),
+            LossError::ClassOutOfRange { class, classes } => write!(
+                f, "class index {} is out of range for {} predictions", class, classes
+            ),
+            LossError::UnsupportedClassTarget => write!(
+                f, "only CrossEntropy and KLDivergence accept class index targets"
+            ),
+            LossError::InvalidQuantile { tau } => write!(
+                f, "quantile level {} must be in (0, 1)", tau
+            ),
+            LossError::InvalidTweediePower { power } => write!(
+                f, "Tweedie power {} must be 0 or at least 1", power
+            ),
+            LossError::OutOfDomain { index } => write!(
+                f, "prediction or target at index {} is outside the domain of the deviance", index
+            ),
+            LossError::Unrepresentable { value } => write!(
+                f, "loss parameter {} is not representable in the number type", value
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LossError {}
+
+/// How per-element loss terms are combined into the reported loss.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Reduction {
+    /// Average of the per-element terms (the behavior of `Loss::forward`).
+    #[default]
+    Mean,
+    /// Sum of the per-element terms.
+    Sum,
+    /// No reduction: one loss value per element.
+    None,
+}
+
+/// Loss value produced by `Loss::forward_reduced`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LossOutput<T> {
+    /// A single reduced value (`Reduction::Mean` or `Reduction::Sum`).
+    Scalar(T),
+    /// One value per element (`Reduction::None`).
+    PerElement(Vec<T>),
+}
+
+impl<T: Copy> LossOutput<T> {
+    /// The reduced value, or `None` for per-element output.
+    pub fn scalar(&self) -> Option<T> {
+        match self {
+            LossOutput::Scalar(v) => Some(*v),
+            LossOutput::PerElement(_) => None,
+        }
+    }
+
+    /// The per-element values, or `None` for reduced output.
+    pub fn per_element(&self) -> Option<&[T]> {
+        match self {
+            LossOutput::Scalar(_) => None,
+            LossOutput::PerElement(v) => Some(v),
+        }
+    }
+}
+
 /// A small enum wrapper over the implemented loss functions with convenience
 /// `forward` and `derivative` helpers.
 ///
@@ -128,92 +449,844 @@ pub fn binary_cross_entropy_loss<T: Number + FromPrimitive>(prediction: T, targe
 /// - `derivative` computes the derivative of the loss with respect to a single
 ///   `prediction` scalar (i.e. `dL/d(prediction)`). Important: `derivative`
 ///   returns the derivative **per sample** (it does not average over a batch).
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum Loss {
     MeanSquaredError,
+    /// See `mean_absolute_error`.
+    MeanAbsoluteError,
     CrossEntropy,
     BinaryCrossEntropy,
+    /// `KL(targets || predictions)`; see `kl_divergence`.
+    KLDivergence,
+    /// Margin loss on raw scores with `-1`/`1` targets; see `hinge_loss`.
+    Hinge,
+    /// Squared margin loss; see `squared_hinge_loss`.
+    SquaredHinge,
+    /// Pinball loss at quantile level `tau` in `(0, 1)`; see `quantile_loss`.
+    Quantile(f64),
+    /// Deviance of a Poisson mean; see `poisson_deviance`.
+    Poisson,
+    /// Deviance with the given variance power; see `tweedie_deviance`.
+    Tweedie(f64),
 }
 
 impl Loss {
+    /// How training reduces the loss over the outputs of one sample.
+    ///
+    /// - `MeanSquaredError` / `MeanAbsoluteError` / `Quantile` / `Poisson` / `Tweedie`:
+    ///   `Reduction::Mean`, so a model with `K` regression outputs minimizes the error
+    ///   averaged across output dimensions and its gradients keep the same scale for any `K`.
+    /// - Every other variant: `Reduction::Sum` over classes or labels.
+    pub fn output_reduction(&self) -> Reduction {
+        match self {
+            Loss::MeanSquaredError
+            | Loss::MeanAbsoluteError
+            | Loss::Quantile(_)
+            | Loss::Poisson
+            | Loss::Tweedie(_) => Reduction::Mean,
+            _ => Reduction::Sum,
+        }
+    }
+
     /// Compute the forward loss value for the enum variant.
     ///
     /// # Behavior
-    /// - For `MeanSquaredError` and `CrossEntropy` this expects `predictions` and
+    /// - For every variant except `BinaryCrossEntropy` this expects `predictions` and
     ///   `targets` to be slices of the same length and computes the averaged loss.
     /// - For `BinaryCrossEntropy` the function **expects** `predictions.len() == 1`
-    ///   and `targets.len() == 1`. If that is not the case it will `panic!` with a
-    ///   message indicating the expectation.
-    pub fn forward<T: Number + FromPrimitive>(&self, predictions: &[T], targets: &[T]) -> T {
-        match self {
+    ///   and `targets.len() == 1`.
+    ///
+    /// # Panics
+    /// Panics with the `LossError` message wherever `try_forward` would return an error.
+    pub fn forward<T: Real + FromPrimitive>(&self, predictions: &[T], targets: &[T]) -> T {
+        self.try_forward(predictions, targets).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Fallible version of `forward`.
+    ///
+    /// # Errors
+    /// - `LossError::LengthMismatch` if the slices differ in length.
+    /// - `LossError::EmptyInput` if the slices are empty.
+    /// - `LossError::ExpectedSingleValue` for `BinaryCrossEntropy` with more than one value.
+    /// - `LossError::InvalidProbability` for `CrossEntropy` / `BinaryCrossEntropy` /
+    ///   `KLDivergence` when a prediction or target lies outside `[0, 1]`.
+    /// - `LossError::InvalidMarginTarget` for `Hinge` / `SquaredHinge` when a target is
+    ///   neither `-1` nor `1`.
+    /// - `LossError::InvalidQuantile` for `Quantile(tau)` with `tau` outside `(0, 1)`.
+    /// - `LossError::InvalidTweediePower` for `Tweedie(power)` with `0 < power < 1` or
+    ///   `power < 0`.
+    /// - `LossError::OutOfDomain` for `Poisson` / `Tweedie` when a prediction or target is
+    ///   outside the domain of the deviance.
+    pub fn try_forward<T: Real + FromPrimitive>(&self, predictions: &[T], targets: &[T]) -> Result<T, LossError> {
+        self.validate(predictions, targets)?;
+        Ok(match self {
             Loss::MeanSquaredError => mean_squared_error(predictions, targets),
+            Loss::MeanAbsoluteError => mean_absolute_error(predictions, targets),
             Loss::CrossEntropy => cross_entropy_loss(predictions, targets),
             Loss::BinaryCrossEntropy => {
-                if predictions.len() != 1 || targets.len() != 1 {
-                    panic!("BinaryCrossEntropy loss expects single prediction and target values.");
+                if predictions.len() != 1 {
+                    return Err(LossError::ExpectedSingleValue { found: predictions.len() });
                 }
                 binary_cross_entropy_loss(predictions[0], targets[0])
             }
+            Loss::KLDivergence => kl_divergence(predictions, targets),
+            Loss::Hinge => hinge_loss(predictions, targets),
+            Loss::SquaredHinge => squared_hinge_loss(predictions, targets),
+            Loss::Quantile(tau) => quantile_loss(predictions, targets, *tau),
+            Loss::Poisson => poisson_deviance(predictions, targets),
+            Loss::Tweedie(power) => tweedie_deviance(predictions, targets, *power),
+        })
+    }
+
+    /// Variance power of `Poisson` (`1`) and `Tweedie`, `None` for every other variant.
+    fn deviance_power(&self) -> Option<f64> {
+        match *self {
+            Loss::Poisson => Some(1.0),
+            Loss::Tweedie(power) => Some(power),
+            _ => None,
+        }
+    }
+
+    /// The quantile level or Tweedie power converted to `T`, if the variant has one.
+    fn parameter<T: Number + FromPrimitive>(&self) -> Result<Option<T>, LossError> {
+        let value = match *self {
+            Loss::Quantile(tau) => tau,
+            _ => match self.deviance_power() {
+                Some(power) => power,
+                None => return Ok(None),
+            },
+        };
+        T::try_to_number::<T>(value).map(Some).ok_or(LossError::Unrepresentable { value })
+    }
+
+    /// Checks the preconditions shared by `try_forward` and `try_derivative`.
+    fn validate<T: Number + FromPrimitive>(&self, predictions: &[T], targets: &[T]) -> Result<(), LossError> {
+        if predictions.len() != targets.len() {
+            return Err(LossError::LengthMismatch { predictions: predictions.len(), targets: targets.len() });
+        }
+        if predictions.is_empty() {
+            return Err(LossError::EmptyInput);
+        }
+        self.parameter::<T>()?;
+        if let Loss::Quantile(tau) = *self
+            && !(tau > 0.0 && tau < 1.0)
+        {
+            return Err(LossError::InvalidQuantile { tau });
+        }
+        if let Some(power) = self.deviance_power() {
+            if !(power == 0.0 || power >= 1.0) {
+                return Err(LossError::InvalidTweediePower { power });
+            }
+            if power > 0.0 {
+                // Negated checks, so that NaN is rejected too
+                let target_ok = |t: T| if power >= 2.0 { t.gt(T::zero()) } else { t.ge(T::zero()) };
+                for i in 0..predictions.len() {
+                    if !predictions[i].gt(T::zero()) || !target_ok(targets[i]) {
+                        return Err(LossError::OutOfDomain { index: i });
+                    }
+                }
+            }
+        }
+        if let Loss::Hinge | Loss::SquaredHinge = self
+            && let Some(index) = targets.iter().position(|&t| !(t.eq(T::one()) || t.eq(-T::one())))
+        {
+            return Err(LossError::InvalidMarginTarget { index });
+        }
+        if let Loss::CrossEntropy | Loss::BinaryCrossEntropy | Loss::KLDivergence = self {
+            // Written as a negated range check so that NaN is rejected too
+            let is_probability = |v: T| v.ge(T::zero()) && v.le(T::one());
+            for i in 0..predictions.len() {
+                if !is_probability(predictions[i]) || !is_probability(targets[i]) {
+                    return Err(LossError::InvalidProbability { index: i });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Like `forward`, with the target given as a class index instead of a one-hot vector.
+    ///
+    /// # Behavior
+    /// - Equals `forward(predictions, &one_hot)` with `one_hot[class] = 1`, but only reads
+    ///   `predictions[class]`, so no one-hot vector is allocated for models with many classes.
+    /// - Supported for `CrossEntropy` and `KLDivergence`, which coincide for one-hot targets:
+    ///   `-ln(p_class) / n` (see `sparse_cross_entropy_loss`).
+    ///
+    /// # Panics
+    /// Panics with the `LossError` message wherever `try_forward_class` would return an error.
+    pub fn forward_class<T: Real + FromPrimitive>(&self, predictions: &[T], class: usize) -> T {
+        self.try_forward_class(predictions, class).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Fallible version of `forward_class`.
+    ///
+    /// # Errors
+    /// - `LossError::UnsupportedClassTarget` for variants other than `CrossEntropy` and `KLDivergence`.
+    /// - `LossError::ClassOutOfRange` if `class >= predictions.len()`.
+    /// - `LossError::InvalidProbability` if a prediction lies outside `[0, 1]`.
+    pub fn try_forward_class<T: Real + FromPrimitive>(&self, predictions: &[T], class: usize) -> Result<T, LossError> {
+        self.validate_class(predictions, class)?;
+        Ok(sparse_cross_entropy_loss(predictions, class))
+    }
+
+    /// Like `derivative`, with the target given as a class index: `-1 / p_class` at `class`
+    /// (with `p` clamped to `eps`) and `0` everywhere else.
+    ///
+    /// # Panics
+    /// Panics with the `LossError` message wherever `try_forward_class` would return an error.
+    pub fn derivative_class<T: Real + FromPrimitive>(&self, predictions: &[T], class: usize) -> Vec<T> {
+        self.try_derivative_class(predictions, class).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Fallible version of `derivative_class`, with the errors of `try_forward_class`.
+    pub fn try_derivative_class<T: Real + FromPrimitive>(&self, predictions: &[T], class: usize) -> Result<Vec<T>, LossError> {
+        self.validate_class(predictions, class)?;
+        let mut gradient = vec![T::zero(); predictions.len()];
+        gradient[class] = - T::one() / predictions[class].max(probability_epsilon());
+        Ok(gradient)
+    }
+
+    /// Batch version of `forward_class`: the mean loss over samples given as one flat
+    /// row-major slice of `classes.len()` prediction rows, with one class index per row.
+    ///
+    /// Neither one-hot targets nor per-sample vectors are allocated, so this suits large
+    /// batches over many classes.
+    ///
+    /// # Panics
+    /// Panics with the `LossError` message wherever `try_forward_classes` would return an error.
+    pub fn forward_classes<T: Real + FromPrimitive>(&self, predictions: &[T], classes: &[usize]) -> T {
+        self.try_forward_classes(predictions, classes).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Fallible version of `forward_classes`.
+    ///
+    /// # Errors
+    /// - `LossError::EmptyInput` if `classes` is empty.
+    /// - `LossError::LengthMismatch` if `predictions.len()` is not a multiple of `classes.len()`.
+    /// - The errors of `try_forward_class` for any row, with `InvalidProbability` indexing
+    ///   the flat `predictions`.
+    pub fn try_forward_classes<T: Real + FromPrimitive>(&self, predictions: &[T], classes: &[usize]) -> Result<T, LossError> {
+        let width = self.class_rows(predictions, classes)?;
+        let mut total = T::zero();
+        for (i, &class) in classes.iter().enumerate() {
+            let row = &predictions[i * width..(i + 1) * width];
+            total = total + self.try_forward_class(row, class).map_err(|e| shift_index(e, i * width))?;
+        }
+        let n = T::try_to_number::<T>(classes.len() as f64).ok_or(LossError::Unrepresentable { value: classes.len() as f64 })?;
+        Ok(total / n)
+    }
+
+    /// Gradient of `forward_classes` with respect to the flat `predictions`: each row is
+    /// `derivative_class` of that row divided by the number of rows.
+    ///
+    /// # Panics
+    /// Panics with the `LossError` message wherever `try_forward_classes` would return an error.
+    pub fn derivative_classes<T: Real + FromPrimitive>(&self, predictions: &[T], classes: &[usize]) -> Vec<T> {
+        self.try_derivative_classes(predictions, classes).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Fallible version of `derivative_classes`, with the errors of `try_forward_classes`.
+    pub fn try_derivative_classes<T: Real + FromPrimitive>(&self, predictions: &[T], classes: &[usize]) -> Result<Vec<T>, LossError> {
+        let width = self.class_rows(predictions, classes)?;
+        let n = T::try_to_number::<T>(classes.len() as f64).ok_or(LossError::Unrepresentable { value: classes.len() as f64 })?;
+        let mut gradient = vec![T::zero(); predictions.len()];
+        for (i, &class) in classes.iter().enumerate() {
+            let row = &predictions[i * width..(i + 1) * width];
+            self.validate_class(row, class).map_err(|e| shift_index(e, i * width))?;
+            gradient[i * width + class] = - T::one() / (row[class].max(probability_epsilon()) * n);
+        }
+        Ok(gradient)
+    }
+
+    /// Checks that `predictions` splits into one row per class index; returns the row width.
+    fn class_rows<T>(&self, predictions: &[T], classes: &[usize]) -> Result<usize, LossError> {
+        if classes.is_empty() {
+            return Err(LossError::EmptyInput);
+        }
+        if !predictions.len().is_multiple_of(classes.len()) {
+            return Err(LossError::LengthMismatch { predictions: predictions.len(), targets: classes.len() });
+        }
+        Ok(predictions.len() / classes.len())
+    }
+
+    /// Checks the preconditions of the class index variants.
+    fn validate_class<T: Number>(&self, predictions: &[T], class: usize) -> Result<(), LossError> {
+        if !matches!(self, Loss::CrossEntropy | Loss::KLDivergence) {
+            return Err(LossError::UnsupportedClassTarget);
+        }
+        if class >= predictions.len() {
+            return Err(LossError::ClassOutOfRange { class, classes: predictions.len() });
+        }
+        match predictions.iter().position(|&p| !(p.ge(T::zero()) && p.le(T::one()))) {
+            Some(index) => Err(LossError::InvalidProbability { index }),
+            None => Ok(()),
+        }
+    }
+
+    /// Compute the per-element loss terms without reducing them.
+    ///
+    /// - MeanSquaredError: `(p_i - t_i)^2`
+    /// - MeanAbsoluteError: `|p_i - t_i|`
+    /// - CrossEntropy: `-t_i ln(p_i)` (with `p_i` clamped to `eps`)
+    /// - BinaryCrossEntropy: `binary_cross_entropy_loss(p_i, t_i)`, so a batch of scalar
+    ///   predictions may be passed (unlike `forward`, which expects a single one).
+    /// - KLDivergence: `t_i ln(t_i / p_i)` (zero when `t_i = 0`)
+    /// - Hinge: `max(0, 1 - t_i p_i)`; SquaredHinge: its square
+    /// - Quantile: `max(tau (t_i - p_i), (tau - 1) (t_i - p_i))`
+    /// - Poisson / Tweedie: the unit deviance of `p_i` (see `tweedie_deviance`)
+    ///
+    /// # Panics
+    /// Panics with the `LossError` message wherever `try_forward_elementwise` would return an error.
+    pub fn forward_elementwise<T: Real + FromPrimitive>(&self, predictions: &[T], targets: &[T]) -> Vec<T> {
+        self.try_forward_elementwise(predictions, targets).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Fallible version of `forward_elementwise`.
+    ///
+    /// # Errors
+    /// The same checks as `try_forward`, except that `BinaryCrossEntropy` accepts a batch
+    /// of scalar predictions: mismatched or empty slices, values outside the domain of the
+    /// loss, and a quantile level or Tweedie power that is invalid or does not fit `T`.
+    pub fn try_forward_elementwise<T: Real + FromPrimitive>(&self, predictions: &[T], targets: &[T]) -> Result<Vec<T>, LossError> {
+        self.validate(predictions, targets)?;
+        let parameter = self.parameter::<T>()?;
+        let eps = probability_epsilon::<T>();
+        Ok(predictions.iter().zip(targets.iter())
+            .map(|(p, t)| match self {
+                Loss::MeanSquaredError => {
+                    let diff = *p - *t;
+                    diff * diff
+                }
+                Loss::MeanAbsoluteError => (*p - *t).abs(),
+                Loss::CrossEntropy => - *t * p.max(eps).ln(),
+                Loss::BinaryCrossEntropy => binary_cross_entropy_loss(*p, *t),
+                Loss::KLDivergence => kl_term(*p, *t),
+                Loss::Hinge => hinge_margin(*p, *t),
+                Loss::SquaredHinge => {
+                    let margin = hinge_margin(*p, *t);
+                    margin * margin
+                }
+                Loss::Quantile(_) => pinball(*p, *t, parameter.unwrap_or_else(T::zero)),
+                Loss::Poisson => tweedie_term(*p, *t, 1.0),
+                Loss::Tweedie(power) => tweedie_term(*p, *t, *power),
+            })
+            .collect())
+    }
+
+    /// Compute the loss with an explicit `reduction`.
+    ///
+    /// # Behavior
+    /// - `Reduction::Mean` averages the per-element terms; for every variant this equals
+    ///   `forward` on inputs `forward` accepts.
+    /// - `Reduction::Sum` adds them up.
+    /// - `Reduction::None` returns them unchanged (see `forward_elementwise`).
+    ///
+    /// # Panics
+    /// Panics with the `LossError` message wherever `try_forward_reduced` would return an error.
+    pub fn forward_reduced<T: Real + FromPrimitive>(&self, predictions: &[T], targets: &[T], reduction: Reduction) -> LossOutput<T> {
+        self.try_forward_reduced(predictions, targets, reduction).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Fallible version of `forward_reduced`, with the errors of `try_forward_elementwise`.
+    pub fn try_forward_reduced<T: Real + FromPrimitive>(&self, predictions: &[T], targets: &[T], reduction: Reduction) -> Result<LossOutput<T>, LossError> {
+        let terms = self.try_forward_elementwise(predictions, targets)?;
+        Ok(match reduction {
+            Reduction::None => LossOutput::PerElement(terms),
+            Reduction::Sum => LossOutput::Scalar(terms.into_iter().fold(T::zero(), |acc, v| acc + v)),
+            Reduction::Mean => {
+                let n = T::try_to_number::<T>(terms.len() as f64).ok_or(LossError::Unrepresentable { value: terms.len() as f64 })?;
+                LossOutput::Scalar(terms.into_iter().fold(T::zero(), |acc, v| acc + v) / n)
+            }
+        })
+    }
+
+    /// Compute the derivative of the loss with respect to each prediction, consistent with `reduction`.
+    ///
+    /// - `Reduction::Sum` and `Reduction::None`: the per-sample derivatives of `derivative`
+    ///   (for `None`, element `i` is the derivative of the `i`-th loss term).
+    /// - `Reduction::Mean`: the per-sample derivatives divided by `n`, the gradient of the averaged loss.
+    pub fn derivative_reduced<T: Real + FromPrimitive>(&self, predictions: &[T], targets: &[T], reduction: Reduction) -> Vec<T> {
+        let gradients = self.derivative(predictions, targets);
+        match reduction {
+            Reduction::Sum | Reduction::None => gradients,
+            Reduction::Mean => {
+                let n = T::to_number(gradients.len() as f64);
+                gradients.into_iter().map(|g| g / n).collect()
+            }
         }
     }
 
     /// Compute the derivative of the loss with respect to each prediction (per-sample).
     ///
     /// # Behavior and steps
-    /// - The function expects `predictions.len() == targets.len()`; it will panic otherwise.
+    /// - The function expects `predictions.len() == targets.len()`.
     /// - Returns a `Vec<T>` with one derivative value per input sample (same order).
     /// - Implemented derivatives:
     ///   - MeanSquaredError: d/dp ( (p - t)^2 ) = 2 * (p - t)
     ///     (this returns the per-sample derivative without averaging by `1/n`).
+    ///   - MeanAbsoluteError: `sign(p - t)`, and `0` where `p = t` (the subgradient at the kink).
     ///   - CrossEntropy: d/dp ( -t ln p ) = - t / p
     ///     - For numerical stability we clamp `p` to `[eps, 1]` before division to avoid division by zero.
     ///   - BinaryCrossEntropy (per-sample): d/dp ( -t ln p - (1-t) ln(1-p) ) =
     ///     - t / p + (1 - t) / (1 - p), with signs handled as:
-    ///     = - ( t / p ) + (1 - t) / (1 - p)
+    ///       = - ( t / p ) + (1 - t) / (1 - p)
     ///     - For numerical stability we clamp `p` into `[eps, 1 - eps]` and also clamp `1 - p`.
+    ///   - KLDivergence: d/dp ( t ln(t / p) ) = - t / p, the same clamped value as CrossEntropy.
+    ///   - Hinge: `-t` inside the margin (`t p < 1`), otherwise `0` (the subgradient at the kink).
+    ///   - SquaredHinge: d/dp ( max(0, 1 - t p)^2 ) = -2 t max(0, 1 - t p).
+    ///   - Quantile: `-tau` where `p < t`, `1 - tau` where `p > t`, and `0` where `p = t`.
+    ///   - Poisson: `2 (1 - t / p)`; Tweedie: `2 p^(-power) (p - t)`.
     ///
     /// # Notes
-    /// - Clamping uses `eps = 1e-15` converted to `T` via `T::to_number`.
+    /// - Clamping uses the same `eps` as the loss functions (`1e-15`, or `T::EPSILON` if larger).
     /// - If you compute a batched/averaged forward loss, divide these per-sample derivatives
     ///   by the batch size yourself to obtain gradients of the averaged loss.
-    pub fn derivative<T: Number + FromPrimitive>(&self, predictions: &[T], targets: &[T]) -> Vec<T> {
-        assert_eq!(predictions.len(), targets.len(), "predictions and targets must have the same length");
-        let eps = T::to_number(1e-15);
+    ///
+    /// # Panics
+    /// Panics with the `LossError` message wherever `try_derivative` would return an error.
+    pub fn derivative<T: Real + FromPrimitive>(&self, predictions: &[T], targets: &[T]) -> Vec<T> {
+        self.try_derivative(predictions, targets).unwrap_or_else(|e| panic!("{}", e))
+    }
 
-        match self {
+    /// Fallible version of `derivative`.
+    ///
+    /// # Errors
+    /// Same as `try_forward`, except that `BinaryCrossEntropy` accepts any number of
+    /// samples (the derivative is computed per sample).
+    pub fn try_derivative<T: Real + FromPrimitive>(&self, predictions: &[T], targets: &[T]) -> Result<Vec<T>, LossError> {
+        self.validate(predictions, targets)?;
+        let eps = probability_epsilon::<T>();
+
+        Ok(match self {
             Loss::MeanSquaredError => {
-                let two = T::from_f64(2.0).unwrap();
+                let two = T::one() + T::one();
                 predictions.iter().zip(targets.iter())
                     .map(|(p, t)| two * (*p - *t))
                     .collect()
             }
-            Loss::CrossEntropy => {
+            Loss::MeanAbsoluteError => {
                 predictions.iter().zip(targets.iter())
                     .map(|(p, t)| {
-                        let p_clamped = if *p < eps { eps } else { *p };
-                        - *t / p_clamped
+                        let diff = *p - *t;
+                        if diff.gt(T::zero()) { T::one() } else if diff.lt(T::zero()) { -T::one() } else { T::zero() }
                     })
                     .collect()
             }
+            Loss::CrossEntropy | Loss::KLDivergence => {
+                predictions.iter().zip(targets.iter())
+                    .map(|(p, t)| - *t / p.max(eps))
+                    .collect()
+            }
+            Loss::Hinge => {
+                predictions.iter().zip(targets.iter())
+                    .map(|(p, t)| if (*t * *p).lt(T::one()) { - *t } else { T::zero() })
+                    .collect()
+            }
+            Loss::SquaredHinge => {
+                let two = T::one() + T::one();
+                predictions.iter().zip(targets.iter())
+                    .map(|(p, t)| - two * *t * hinge_margin(*p, *t))
+                    .collect()
+            }
+            Loss::Quantile(_) => {
+                let tau = self.parameter::<T>()?.unwrap_or_else(T::zero);
+                predictions.iter().zip(targets.iter())
+                    .map(|(p, t)| if p.lt(t) { -tau } else if p.gt(t) { T::one() - tau } else { T::zero() })
+                    .collect()
+            }
+            Loss::Poisson | Loss::Tweedie(_) => {
+                let two = T::one() + T::one();
+                let power = self.parameter::<T>()?.unwrap_or_else(T::one);
+                predictions.iter().zip(targets.iter())
+                    .map(|(p, t)| two * (*p - *t) / p.powf(power))
+                    .collect()
+            }
             Loss::BinaryCrossEntropy => {
                 predictions.iter().zip(targets.iter())
                     .map(|(p, t)| {
-                        // clamp p into [eps, 1 - eps]
-                        let p_clamped = if *p < eps {
-                            eps
-                        } else if *p > T::one() - eps {
-                            T::one() - eps
-                        } else {
-                            *p
-                        };
-                        let one_minus_p = if T::one() - p_clamped < eps {
-                            eps
-                        } else {
-                            T::one() - p_clamped
-                        };
+                        let (p_clamped, one_minus_p) = clamp_probability(*p);
                         - (*t / p_clamped) + ((T::one() - *t) / one_minus_p)
                     })
                     .collect()
             }
+        })
+    }
+
+    /// `forward` with optional per-sample weights, where each element of `predictions`
+    /// is one sample (e.g. a batch of scalar outputs).
+    ///
+    /// # Behavior
+    /// - `None` is `forward`, except that `BinaryCrossEntropy` accepts a batch and
+    ///   averages the per-sample terms of `forward_elementwise`.
+    /// - `Some(weights)` scales the term of sample `i` by `weights[i]`, as
+    ///   `WeightedLoss` with `Weighting::PerSample` does; the terms are still averaged
+    ///   over the number of samples.
+    ///
+    /// For weighting whole training rows, see `Trainer::fit_weighted`.
+    pub fn forward_weighted<T: Real + FromPrimitive>(&self, predictions: &[T], targets: &[T], sample_weights: Option<&[T]>) -> T {
+        match sample_weights {
+            None if *self == Loss::BinaryCrossEntropy => {
+                let n = T::to_number::<T>(predictions.len() as f64);
+                self.forward_elementwise(predictions, targets).into_iter().fold(T::zero(), |sum, term| sum + term) / n
+            }
+            None => self.forward(predictions, targets),
+            Some(weights) => WeightedLoss::new(*self, Weighting::PerSample(weights.to_vec())).forward(predictions, targets),
+        }
+    }
+
+    /// `derivative` with optional per-sample weights; see `forward_weighted`.
+    pub fn derivative_weighted<T: Real + FromPrimitive>(&self, predictions: &[T], targets: &[T], sample_weights: Option<&[T]>) -> Vec<T> {
+        match sample_weights {
+            None => self.derivative(predictions, targets),
+            Some(weights) => WeightedLoss::new(*self, Weighting::PerSample(weights.to_vec())).derivative(predictions, targets),
+        }
+    }
+}
+
+/// How a `WeightedLoss` assigns weights to the terms of the wrapped loss.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Weighting<T> {
+    /// Per-class weights.
+    /// - `MeanSquaredError` / `CrossEntropy`: `weights[i]` scales output (class) `i`, so
+    ///   `weights.len()` must equal `predictions.len()`.
+    /// - `BinaryCrossEntropy`: `weights = [negative_weight, positive_weight]`, scaling the
+    ///   `(1 - t) ln(1 - p)` and `t ln(p)` terms respectively.
+    PerClass(Vec<T>),
+    /// Per-sample weights: `weights[i]` scales the term of element `i`, where each element
+    /// of `predictions` is one sample (e.g. a batch of scalar outputs).
+    PerSample(Vec<T>),
+}
+
+/// A `Loss` with per-class or per-sample weights, for imbalanced classification without resampling.
+///
+/// Weighted terms are still averaged over the number of elements (`1/n`), so all-ones
+/// weights reproduce the unweighted `Loss` exactly.
+pub struct WeightedLoss<T> {
+    pub loss: Loss,
+    pub weighting: Weighting<T>,
+}
+
+impl<T: Real + FromPrimitive> WeightedLoss<T> {
+    pub fn new(loss: Loss, weighting: Weighting<T>) -> Self {
+        WeightedLoss { loss, weighting }
+    }
+
+    /// Returns the `[negative_weight, positive_weight]` pair for weighted binary cross-entropy, if used.
+    fn binary_class_weights(&self) -> Option<(T, T)> {
+        match (&self.loss, &self.weighting) {
+            (Loss::BinaryCrossEntropy, Weighting::PerClass(w)) => {
+                assert_eq!(w.len(), 2, "BinaryCrossEntropy class weights must be [negative_weight, positive_weight]");
+                Some((w[0], w[1]))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the element-wise weights to apply, checking their length against `n`.
+    fn element_weights(&self, n: usize) -> &[T] {
+        let weights = match &self.weighting {
+            Weighting::PerClass(w) | Weighting::PerSample(w) => w,
+        };
+        assert_eq!(weights.len(), n, "weights must have one entry per prediction");
+        weights
+    }
+
+    /// Compute the weighted loss value.
+    ///
+    /// # Behavior
+    /// - `MeanSquaredError`: `1/n * sum_i w_i (p_i - t_i)^2`.
+    /// - `MeanAbsoluteError`: `1/n * sum_i w_i |p_i - t_i|`.
+    /// - `CrossEntropy`: `-1/n * sum_i w_i t_i ln(p_i)` (with the same `eps` clamping as `cross_entropy_loss`).
+    /// - `KLDivergence`, `Hinge`, `SquaredHinge`: `1/n * sum_i w_i l_i` over the terms of `forward_elementwise`.
+    /// - `BinaryCrossEntropy` treats every element as one scalar sample (a batch, as in
+    ///   `forward_elementwise`):
+    ///   - per-class: `-1/n * sum_i (w_pos t_i ln(p_i) + w_neg (1 - t_i) ln(1 - p_i))`,
+    ///   - per-sample: `1/n * sum_i w_i BCE(p_i, t_i)`.
+    pub fn forward(&self, predictions: &[T], targets: &[T]) -> T {
+        assert_eq!(predictions.len(), targets.len(), "predictions and targets must have the same length");
+        let n = T::to_number(predictions.len() as f64);
+        if let Some((negative, positive)) = self.binary_class_weights() {
+            let total = predictions.iter().zip(targets.iter()).fold(T::zero(), |sum, (&p, &t)| {
+                let (p, one_minus_p) = clamp_probability(p);
+                sum - (positive * t * p.ln() + negative * (T::one() - t) * one_minus_p.ln())
+            });
+            return total / n;
+        }
+
+        let weights = self.element_weights(predictions.len());
+        let terms = self.loss.forward_elementwise(predictions, targets);
+        terms.into_iter().zip(weights.iter()).fold(T::zero(), |sum, (term, &w)| sum + w * term) / n
+    }
+
+    /// Compute the weighted per-sample derivative of the loss with respect to each prediction.
+    ///
+    /// Each unweighted derivative from `Loss::derivative` is scaled by its element's weight;
+    /// for per-class weighted binary cross-entropy the two terms are scaled separately:
+    /// `- w_pos t / p + w_neg (1 - t) / (1 - p)`.
+    pub fn derivative(&self, predictions: &[T], targets: &[T]) -> Vec<T> {
+        assert_eq!(predictions.len(), targets.len(), "predictions and targets must have the same length");
+        if let Some((negative, positive)) = self.binary_class_weights() {
+            return predictions.iter().zip(targets.iter())
+                .map(|(p, t)| {
+                    let (p_clamped, one_minus_p) = clamp_probability(*p);
+                    - (positive * *t / p_clamped) + (negative * (T::one() - *t) / one_minus_p)
+                })
+                .collect();
+        }
+
+        let weights = self.element_weights(predictions.len());
+        self.loss.derivative(predictions, targets)
+            .into_iter()
+            .zip(weights.iter())
+            .map(|(d, w)| *w * d)
+            .collect()
+    }
+}
+
+
+/// A loss that training can minimize: the value for one sample and its gradient with
+/// respect to the model outputs.
+///
+/// Implement it for domain-specific losses and pass them to `Trainer::fit_with_loss` or
+/// `Sequential::accumulate_gradients_with`; the built-in `Loss` variants and
+/// `WeightedLoss` implement it too.
+///
+/// # Notes
+/// - `objective_derivative` must return one entry per prediction.
+/// - Use the same reduction over outputs in both methods; training does not rescale them.
+/// - The methods are not called `forward`/`derivative` because `Loss` implements the
+///   trait with a different reduction than its inherent methods of those names.
+pub trait LossFn<T> {
+    /// Loss of one sample, as training minimizes and records it.
+    fn objective(&self, predictions: &[T], targets: &[T]) -> T;
+    /// Gradient of `objective` with respect to `predictions`.
+    fn objective_derivative(&self, predictions: &[T], targets: &[T]) -> Vec<T>;
+}
+
+/// The loss `Sequential::accumulate_gradients` minimizes: the variant reduced over the
+/// outputs with `Loss::output_reduction`. For the summed variants this is `K` times the
+/// inherent `Loss::forward` (always a mean over the `K` outputs).
+impl<T: Real + FromPrimitive> LossFn<T> for Loss {
+    fn objective(&self, predictions: &[T], targets: &[T]) -> T {
+        self.forward_reduced(predictions, targets, self.output_reduction()).scalar().unwrap()
+    }
+
+    fn objective_derivative(&self, predictions: &[T], targets: &[T]) -> Vec<T> {
+        self.derivative_reduced(predictions, targets, self.output_reduction())
+    }
+}
+
+impl<T, L: LossFn<T> + ?Sized> LossFn<T> for Box<L> {
+    fn objective(&self, predictions: &[T], targets: &[T]) -> T {
+        (**self).objective(predictions, targets)
+    }
+
+    fn objective_derivative(&self, predictions: &[T], targets: &[T]) -> Vec<T> {
+        (**self).objective_derivative(predictions, targets)
+    }
+}
+
+impl<T: Real + FromPrimitive> LossFn<T> for WeightedLoss<T> {
+    fn objective(&self, predictions: &[T], targets: &[T]) -> T {
+        WeightedLoss::forward(self, predictions, targets)
+    }
+
+    fn objective_derivative(&self, predictions: &[T], targets: &[T]) -> Vec<T> {
+        WeightedLoss::derivative(self, predictions, targets)
+    }
+}
+
+/// Mask of the real timesteps of padded sequences: `mask[i][t]` is `true` for
+/// `t < lengths[i]`, for sequences padded to `max_len` (e.g. by `dataset::pad_batch`).
+pub fn sequence_mask(lengths: &[usize], max_len: usize) -> Vec<Vec<bool>> {
+    lengths.iter().map(|&len| (0..max_len).map(|t| t < len).collect()).collect()
+}
+
+/// Mean of `loss.objective` over the timesteps whose `mask` entry is `true`, so padded timesteps
+/// contribute nothing.
+///
+/// # Arguments
+/// * `predictions`, `targets` - One output and one target vector per timestep. For a
+///   batch, concatenate the sequences (and their masks from `sequence_mask`).
+/// * `mask` - `true` for real timesteps, `false` for padding.
+///
+/// # Notes
+/// - Masked timesteps are never passed to `loss`, so their (padding) values may be
+///   anything, including targets the loss would reject.
+/// - Returns zero if every timestep is masked.
+pub fn masked_forward<T: Number + FromPrimitive>(loss: &dyn LossFn<T>, predictions: &[Vec<T>], targets: &[Vec<T>], mask: &[bool]) -> T {
+    check_mask(predictions, targets, mask);
+    let valid = mask.iter().filter(|&&m| m).count();
+    if valid == 0 {
+        return T::zero();
+    }
+    let total = predictions.iter().zip(targets.iter()).zip(mask.iter())
+        .filter(|(_, m)| **m)
+        .fold(T::zero(), |sum, ((p, t), _)| sum + loss.objective(p, t));
+    total / T::to_number(valid as f64)
+}
+
+/// Gradient of `masked_forward` with respect to every timestep's predictions: the
+/// `loss` derivative divided by the number of real timesteps, and all zeros for the
+/// masked ones.
+pub fn masked_derivative<T: Number + FromPrimitive>(loss: &dyn LossFn<T>, predictions: &[Vec<T>], targets: &[Vec<T>], mask: &[bool]) -> Vec<Vec<T>> {
+    check_mask(predictions, targets, mask);
+    let valid = T::to_number::<T>(mask.iter().filter(|&&m| m).count().max(1) as f64);
+    predictions.iter().zip(targets.iter()).zip(mask.iter())
+        .map(|((p, t), &m)| {
+            if m {
+                loss.objective_derivative(p, t).into_iter().map(|d| d / valid).collect()
+            } else {
+                vec![T::zero(); p.len()]
+            }
+        })
+        .collect()
+}
+
+fn check_mask<T>(predictions: &[Vec<T>], targets: &[Vec<T>], mask: &[bool]) {
+    assert_eq!(predictions.len(), targets.len(), "predictions and targets must have the same number of timesteps");
+    assert_eq!(predictions.len(), mask.len(), "mask must have one entry per timestep");
+}
+
+/// Losses looked up by name, e.g. to pick a custom loss from a config or command line.
+///
+/// `new` registers every parameter-free `Loss` variant under its name
+/// (`"MeanSquaredError"`, ...); `register` adds or replaces entries, such as
+/// `Loss::Quantile(0.9)` under `"P90"`.
+pub struct LossRegistry<T> {
+    losses: BTreeMap<String, Box<dyn LossFn<T>>>,
+}
+
+impl<T: Real + FromPrimitive + 'static> LossRegistry<T> {
+    pub fn new() -> Self {
+        let mut registry = LossRegistry { losses: BTreeMap::new() };
+        for loss in [
+            Loss::MeanSquaredError,
+            Loss::MeanAbsoluteError,
+            Loss::CrossEntropy,
+            Loss::BinaryCrossEntropy,
+            Loss::KLDivergence,
+            Loss::Hinge,
+            Loss::SquaredHinge,
+            Loss::Poisson,
+        ] {
+            registry.register(format!("{:?}", loss), Box::new(loss));
+        }
+        registry
+    }
+}
+
+impl<T: Real + FromPrimitive + 'static> Default for LossRegistry<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> LossRegistry<T> {
+    /// Registers `loss` under `name`, returning the loss it replaces, if any.
+    pub fn register(&mut self, name: impl Into<String>, loss: Box<dyn LossFn<T>>) -> Option<Box<dyn LossFn<T>>> {
+        self.losses.insert(name.into(), loss)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn LossFn<T>> {
+        self.losses.get(name).map(|loss| loss.as_ref())
+    }
+
+    /// Like `get`, with an error naming the registered losses if `name` is unknown.
+    pub fn try_get(&self, name: &str) -> Result<&dyn LossFn<T>, String> {
+        self.get(name).ok_or_else(|| format!("unknown loss {:?}; registered: {}", name, self.names().join(", ")))
+    }
+
+    /// Registered names in sorted order.
+    pub fn names(&self) -> Vec<&str> {
+        self.losses.keys().map(String::as_str).collect()
+    }
+}
+
+/// Weighted sum of loss terms, e.g. a data loss plus a penalty on the outputs:
+/// `sum_k w_k * L_k(predictions, targets)`, with the gradient summed the same way.
+///
+/// ```
+/// use neuralnet::loss_fn::{CompositeLoss, L1ActivationPenalty, Loss, LossFn};
+///
+/// let loss = CompositeLoss::new()
+///     .with(1.0, Loss::MeanSquaredError)
+///     .with(0.1, L1ActivationPenalty);
+/// let value: f64 = loss.objective(&[0.5, -2.0], &[0.0, -2.0]);
+/// assert!((value - (0.125 + 0.25)).abs() < 1e-12);
+/// ```
+pub struct CompositeLoss<T> {
+    terms: Vec<(T, Box<dyn LossFn<T>>)>,
+}
+
+impl<T: Number> CompositeLoss<T> {
+    /// An empty composite, whose loss is zero.
+    pub fn new() -> Self {
+        CompositeLoss { terms: Vec::new() }
+    }
+
+    /// Adds `loss` with weight `weight`.
+    pub fn with(mut self, weight: T, loss: impl LossFn<T> + 'static) -> Self {
+        self.terms.push((weight, Box::new(loss)));
+        self
+    }
+
+    /// Adds an already boxed loss, such as one from a `LossRegistry`.
+    pub fn push(&mut self, weight: T, loss: Box<dyn LossFn<T>>) {
+        self.terms.push((weight, loss));
+    }
+
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Unweighted value of every term, in the order they were added (e.g. for logging).
+    pub fn term_values(&self, predictions: &[T], targets: &[T]) -> Vec<T> {
+        self.terms.iter().map(|(_, loss)| loss.objective(predictions, targets)).collect()
+    }
+}
+
+impl<T: Number> Default for CompositeLoss<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Number> LossFn<T> for CompositeLoss<T> {
+    fn objective(&self, predictions: &[T], targets: &[T]) -> T {
+        self.terms.iter().fold(T::zero(), |sum, (weight, loss)| sum + *weight * loss.objective(predictions, targets))
+    }
+
+    fn objective_derivative(&self, predictions: &[T], targets: &[T]) -> Vec<T> {
+        let mut grad = vec![T::zero(); predictions.len()];
+        for (weight, loss) in &self.terms {
+            let term = loss.objective_derivative(predictions, targets);
+            assert_eq!(term.len(), grad.len(), "every loss derivative must have one entry per prediction");
+            for (g, d) in grad.iter_mut().zip(term) {
+                *g = *g + *weight * d;
+            }
         }
+        grad
+    }
+}
+
+/// `sum_i |predictions_i|`, ignoring the targets: an L1 penalty that pushes outputs
+/// towards zero when added to a `CompositeLoss`. The subgradient at zero is zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct L1ActivationPenalty;
+
+impl<T: Number> LossFn<T> for L1ActivationPenalty {
+    fn objective(&self, predictions: &[T], _targets: &[T]) -> T {
+        predictions.iter().fold(T::zero(), |sum, &p| sum + p.abs())
+    }
+
+    fn objective_derivative(&self, predictions: &[T], _targets: &[T]) -> Vec<T> {
+        predictions.iter()
+            .map(|&p| if p.gt(T::zero()) { T::one() } else if p.lt(T::zero()) { -T::one() } else { T::zero() })
+            .collect()
     }
 }
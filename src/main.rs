@@ -1,92 +1,176 @@
-#![allow(dead_code)]
+//! Command-line front end for training, scoring and evaluating `Sequential` models.
+//!
+//! ```text
+//! neuralnet train    --data train.csv --config config.json --model model.json
+//! neuralnet predict  --data input.csv --model model.json [--output predictions.csv]
+//! neuralnet evaluate --data test.csv  --model model.json
+//! ```
+//!
+//! Data files are numeric CSV files with a header row. For `train` and `evaluate` the
+//! last `output_dim` columns are the targets; for `predict` every column is an input.
+//!
+//! The training config is a `config::ModelConfig`, as JSON or (with a `.toml` extension) TOML:
+//!
+//! ```text
+//! {
+//!   "layers": [{"Dense": {"units": 8}}, {"Activation": "ReLU"}, {"Dense": {"units": 1}}],
+//!   "loss": "MeanSquaredError",
+//!   "optimizer": {"Sgd": {"learning_rate": 0.01}},
+//!   "training": {"epochs": 100, "shuffle": true},
+//!   "seed": 0
+//! }
+//! ```
+//!
+//! `input_dim` may be omitted; it is taken from the width of the training data. An optional
+//! `"init"` (`"GlorotUniform"` or `"HeUniform"`) overrides the per-layer automatic choice.
 
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::process::ExitCode;
+use neuralnet::config::ModelConfig;
 use neuralnet::data_handling;
-use neuralnet::layers::Layer1D;
-use neuralnet::activation_fn::Activation;
+use neuralnet::metrics::{accuracy, argmax};
+use neuralnet::model::Sequential;
+use neuralnet::training::{ProgressBar, TrainingObserver};
 
-fn main() {
-    // Read CSV of rows of strings -> Vec<Vec<String>>
-    let data = data_handling::read_csv("data.csv").expect("failed to read data.csv");
+const USAGE: &str = "usage:
+  neuralnet train    --data <csv> --config <json|toml> --model <output json>
+  neuralnet predict  --data <csv> --model <json> [--output <csv>]
+  neuralnet evaluate --data <csv> --model <json>";
 
-    // Example expects rows with at least 3 columns: x0, x1, y
-    let mut xdash: Vec<[f64; 3]> = Vec::new(); // 2 features + bias
-    let mut ydash: Vec<f64> = Vec::new();
-
-    for row in data.iter() {
-        if row.len() < 3 { continue; }
-        let x0 = row[0].parse::<f64>().unwrap_or(0.0);
-        let x1 = row[1].parse::<f64>().unwrap_or(0.0);
-        let y = row[2].parse::<f64>().unwrap_or(0.0);
-        xdash.push([x0, x1, 1.0]); // last entry is bias input = 1.0
-        ydash.push(y);
+/// Parses `--name value` pairs.
+fn parse_flags(args: &[String]) -> Result<HashMap<String, String>, String> {
+    let mut flags = HashMap::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let name = arg.strip_prefix("--").ok_or_else(|| format!("unexpected argument `{}`", arg))?;
+        let value = iter.next().ok_or_else(|| format!("missing value for --{}", name))?;
+        flags.insert(name.to_string(), value.clone());
     }
+    Ok(flags)
+}
 
-    // Example shapes: hidden layer = 3 hidden units, input size = 3 (including bias)
-    // output layer produces 1 scalar (OUT=1) from 3 hidden units (IN=3)
-    let mut hidden_layer = Layer1D::<f64, 3, 3>::new(
-        [[0.5; 3]; 3], // weights for 3 outputs each with 3 inputs (for example)
-        [0.1; 3],
-    );
-
-    // output layer: OUT = 1, IN = 3
-    let mut output_layer = Layer1D::<f64, 1, 3>::new([[0.5, 0.5, 0.5]], [0.1]);
+fn required<'a>(flags: &'a HashMap<String, String>, name: &str) -> Result<&'a str, String> {
+    flags.get(name).map(String::as_str).ok_or_else(|| format!("missing required flag --{}", name))
+}
 
-    let activation = Activation::Sigmoid;
-    let lr = 0.01f64;
+/// Reads a numeric CSV file (the header row is skipped by `read_csv`).
+fn read_numeric_csv(path: &str) -> Result<Vec<Vec<f64>>, Box<dyn Error>> {
+    let rows = data_handling::read_csv(path)?;
+    rows.iter().enumerate()
+        .map(|(i, row)| {
+            row.iter()
+                .map(|cell| cell.trim().parse::<f64>().map_err(|_| format!("{}: row {}: `{}` is not a number", path, i + 1, cell).into()))
+                .collect()
+        })
+        .collect()
+}
 
-    let epochs = 100_000usize;
-    for epoch in 0..epochs {
-        let mut epoch_loss = 0.0f64;
+/// Input rows and their target rows.
+type Samples = (Vec<Vec<f64>>, Vec<Vec<f64>>);
 
-        for (input, &target) in xdash.iter().zip(ydash.iter()) {
-            // forward
-            let hidden_out = hidden_layer.forward(input);         // [f64; 3]
-            let hidden_act = activation.forward(&hidden_out);     // [f64; 3]
-            let final_out = output_layer.forward(&hidden_act);    // [f64; 1]
-            let pred_pre = final_out[0];
-            let pred = activation.forward(&final_out)[0];
+/// Splits every row into `(inputs, targets)`, the targets being the last `n_targets` columns.
+fn split_targets(rows: Vec<Vec<f64>>, n_targets: usize) -> Result<Samples, String> {
+    let mut inputs = Vec::with_capacity(rows.len());
+    let mut targets = Vec::with_capacity(rows.len());
+    for (i, mut row) in rows.into_iter().enumerate() {
+        if row.len() <= n_targets {
+            return Err(format!("row {} has {} columns, expected more than {} (inputs + targets)", i + 1, row.len(), n_targets));
+        }
+        targets.push(row.split_off(row.len() - n_targets));
+        inputs.push(row);
+    }
+    Ok((inputs, targets))
+}
 
-            // compute scalar loss (MSE) and accumulate
-            let error = pred - target;
-            epoch_loss += error * error;
+fn train(flags: &HashMap<String, String>) -> Result<(), Box<dyn Error>> {
+    let config = ModelConfig::load(required(flags, "config")?)?;
+    let n_targets = config.output_dim().ok_or("config must contain at least one Dense layer")?;
+    let (inputs, targets) = split_targets(read_numeric_csv(required(flags, "data")?)?, n_targets)?;
+    let input_dim = inputs.first().map_or(0, |row| row.len());
 
-            // derivative w.r.t. pre-activation of output: dL/dz = dL/dy * dy/dz
-            // dL/dy = 2 * (y - t)  (we'll incorporate scalar factor into learning rate or keep as-is)
-            let d_loss_dy = 2.0 * (pred - target);
-            let dy_dz = activation.derivative(pred_pre);
-            let d_out = d_loss_dy * dy_dz;
+    let mut model = config.build_for_input::<f64>(input_dim)?;
+    let trainer = config.trainer();
 
-            // weight grads for output layer (shape [1][3])
-            let mut output_weight_grads = [[0.0f64; 3]; 1];
-            for j in 0..3 {
-                output_weight_grads[0][j] = d_out * hidden_act[j];
-            }
-            let output_bias_grads = [d_out];
+    let mut progress = ProgressBar::stderr();
+    #[cfg(feature = "log")]
+    let mut logger = neuralnet::training::LogObserver::new(log::Level::Debug);
+    let mut observers: Vec<&mut dyn TrainingObserver> = vec![&mut progress];
+    #[cfg(feature = "log")]
+    observers.push(&mut logger);
 
-            // update output layer
-            output_layer.update_weights(&output_weight_grads, &output_bias_grads, lr);
+    let history = trainer.fit_observed(&mut model, &inputs, &targets, |_| Vec::new(), &mut observers);
+    model.save_json(required(flags, "model")?)?;
+    if let Some(last) = history.records.last() {
+        println!("trained {} epochs, final loss {}", history.records.len(), last.train_loss);
+    }
+    Ok(())
+}
 
-            // backprop into hidden layer: compute d_hidden for each hidden neuron
-            let mut hidden_weight_grads = [[0.0f64; 3]; 3]; // shape [OUT_hidden=3][IN=3]
-            let mut hidden_bias_grads = [0.0f64; 3];
-            for h in 0..3 {
-                // weight from hidden h to output 0 is output_layer.weights[0][h]
-                let w_ho = output_layer.weights[0][h];
-                let d_hidden = d_out * w_ho * activation.derivative(hidden_out[h]);
-                // gradient for each input weight to hidden neuron h: d_hidden * input[k]
-                for k in 0..3 {
-                    hidden_weight_grads[h][k] = d_hidden * input[k];
-                }
-                hidden_bias_grads[h] = d_hidden;
-            }
-            hidden_layer.update_weights(&hidden_weight_grads, &hidden_bias_grads, lr);
+fn predict(flags: &HashMap<String, String>) -> Result<(), Box<dyn Error>> {
+    let model = Sequential::<f64>::load_json(required(flags, "model")?)?;
+    let rows = read_numeric_csv(required(flags, "data")?)?;
+    let mut out = String::new();
+    for (i, row) in rows.iter().enumerate() {
+        let prediction = model.try_forward(row).map_err(|e| format!("row {}: {}", i + 1, e))?;
+        let cells: Vec<String> = prediction.iter().map(|v| v.to_string()).collect();
+        out.push_str(&cells.join(","));
+        out.push('\n');
+    }
+    match flags.get("output") {
+        Some(path) => {
+            let header: Vec<String> = (0..model.output_dim()).map(|k| format!("output_{}", k)).collect();
+            fs::write(path, format!("{}\n{}", header.join(","), out))?;
         }
+        None => print!("{}", out),
+    }
+    Ok(())
+}
 
-        // display training outputs every 1000 epochs
-        if epoch % 1000 == 0 {
-            let n = (ydash.len().max(1)) as f64;
-            let mean_loss = epoch_loss / n;
-            println!("Epoch {}/{} - Loss = {}", epoch, epochs, mean_loss);
+fn evaluate(flags: &HashMap<String, String>) -> Result<(), Box<dyn Error>> {
+    let model = Sequential::<f64>::load_json(required(flags, "model")?)?;
+    let (inputs, targets) = split_targets(read_numeric_csv(required(flags, "data")?)?, model.output_dim())?;
+    let mut predictions = Vec::with_capacity(inputs.len());
+    for (i, row) in inputs.iter().enumerate() {
+        predictions.push(model.try_forward(row).map_err(|e| format!("row {}: {}", i + 1, e))?);
+    }
+
+    let n = inputs.len().max(1) as f64;
+    let mse = predictions.iter().zip(targets.iter())
+        .map(|(p, t)| p.iter().zip(t.iter()).map(|(a, b)| (a - b).powi(2)).sum::<f64>() / p.len() as f64)
+        .sum::<f64>() / n;
+    // One output: threshold at 0.5; several outputs: argmax against one-hot targets
+    let class = |v: &Vec<f64>| if v.len() == 1 { usize::from(v[0] >= 0.5) } else { argmax(v) };
+    let predicted: Vec<usize> = predictions.iter().map(class).collect();
+    let actual: Vec<usize> = targets.iter().map(class).collect();
+    println!("samples: {}", inputs.len());
+    println!("mse: {}", mse);
+    println!("accuracy: {}", accuracy(&predicted, &actual));
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some(command) = args.first() else {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    };
+    let result = parse_flags(&args[1..]).map_err(Box::<dyn Error>::from).and_then(|flags| match command.as_str() {
+        "train" => train(&flags),
+        "predict" => predict(&flags),
+        "evaluate" => evaluate(&flags),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        other => Err(format!("unknown command `{}`\n{}", other, USAGE).into()),
+    });
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
         }
     }
 }
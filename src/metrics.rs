@@ -0,0 +1,939 @@
+//! Evaluation metrics and statistical model-comparison helpers.
+//!
+//! The comparison tests take the predictions of two models on the *same* test set
+//! and report whether the observed performance difference is statistically
+//! significant:
+//! - McNemar's test on paired correct/incorrect classification outcomes.
+//! - Paired Student's t-test on per-sample scores (e.g. per-sample losses).
+//!
+//! Error-analysis helpers build confusion matrices and export the most confident
+//! misclassifications for manual inspection.
+//!
+//! Every metric is backed by a [`StreamingMetric`] accumulator holding constant-size
+//! state, so arbitrarily long prediction streams can be evaluated in O(1) memory and
+//! results computed on separate shards can be merged afterwards. The slice-based
+//! functions are thin wrappers that stream the slices through an accumulator.
+//! ROC curves and threshold search are the exception: they rank every score, so they
+//! work on the full slices.
+
+use std::error::Error;
+use std::fmt::Display;
+use std::path::Path;
+use crate::numbers::Number;
+use num_traits::ToPrimitive;
+
+/// Constant-memory metric accumulator.
+///
+/// Feed samples with `accumulate`, combine accumulators built on different shards of the
+/// data with `merge`, and compute the result with `finalize`. Merging two accumulators gives
+/// the same result as accumulating all of their samples into one.
+pub trait StreamingMetric {
+    /// One observation, e.g. a `(prediction, target)` pair.
+    type Input;
+    /// The computed metric.
+    type Output;
+
+    /// Adds one observation.
+    fn accumulate(&mut self, input: Self::Input);
+    /// Adds all observations of `other`, which must be configured identically (e.g. same number of classes).
+    fn merge(&mut self, other: &Self);
+    /// Computes the metric from the observations seen so far.
+    fn finalize(&self) -> Self::Output;
+}
+
+/// Streaming classification accuracy over `(prediction, target)` pairs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Accuracy {
+    correct: usize,
+    total: usize,
+}
+
+impl Accuracy {
+    pub fn new() -> Self {
+        Accuracy::default()
+    }
+}
+
+impl StreamingMetric for Accuracy {
+    type Input = (usize, usize);
+    type Output = f64;
+
+    fn accumulate(&mut self, (prediction, target): (usize, usize)) {
+        if prediction == target {
+            self.correct += 1;
+        }
+        self.total += 1;
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.correct += other.correct;
+        self.total += other.total;
+    }
+
+    /// Fraction of correct predictions; `0` if nothing was accumulated.
+    fn finalize(&self) -> f64 {
+        if self.total == 0 { 0.0 } else { self.correct as f64 / self.total as f64 }
+    }
+}
+
+/// Computes the accuracy of `predictions` against `targets`.
+pub fn accuracy(predictions: &[usize], targets: &[usize]) -> f64 {
+    assert_eq!(predictions.len(), targets.len(), "predictions and targets must have the same length");
+    let mut metric = Accuracy::new();
+    for (&p, &t) in predictions.iter().zip(targets.iter()) {
+        metric.accumulate((p, t));
+    }
+    metric.finalize()
+}
+
+/// Errors of one output of a multi-output regression.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OutputErrors {
+    pub mse: f64,
+    pub mae: f64,
+}
+
+/// Streaming per-output regression errors over `(prediction, target)` vector pairs.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RegressionErrors {
+    squared_sums: Vec<f64>,
+    absolute_sums: Vec<f64>,
+    count: usize,
+}
+
+impl RegressionErrors {
+    pub fn new() -> Self {
+        RegressionErrors::default()
+    }
+}
+
+impl StreamingMetric for RegressionErrors {
+    type Input = (Vec<f64>, Vec<f64>);
+    type Output = Vec<OutputErrors>;
+
+    /// The first pair fixes the number of outputs; later pairs must match it.
+    fn accumulate(&mut self, (prediction, target): (Vec<f64>, Vec<f64>)) {
+        assert_eq!(prediction.len(), target.len(), "prediction and target must have the same length");
+        if self.count == 0 {
+            self.squared_sums = vec![0.0; target.len()];
+            self.absolute_sums = vec![0.0; target.len()];
+        }
+        assert_eq!(target.len(), self.squared_sums.len(), "every sample must have the same number of outputs");
+        for (k, (p, t)) in prediction.iter().zip(target.iter()).enumerate() {
+            self.squared_sums[k] += (p - t).powi(2);
+            self.absolute_sums[k] += (p - t).abs();
+        }
+        self.count += 1;
+    }
+
+    fn merge(&mut self, other: &Self) {
+        if self.count == 0 {
+            *self = other.clone();
+            return;
+        }
+        if other.count == 0 {
+            return;
+        }
+        assert_eq!(self.squared_sums.len(), other.squared_sums.len(), "regression accumulators must have the same number of outputs");
+        for k in 0..self.squared_sums.len() {
+            self.squared_sums[k] += other.squared_sums[k];
+            self.absolute_sums[k] += other.absolute_sums[k];
+        }
+        self.count += other.count;
+    }
+
+    /// One entry per output; empty if nothing was accumulated.
+    fn finalize(&self) -> Vec<OutputErrors> {
+        let n = self.count.max(1) as f64;
+        self.squared_sums.iter().zip(self.absolute_sums.iter())
+            .map(|(&squared, &absolute)| OutputErrors { mse: squared / n, mae: absolute / n })
+            .collect()
+    }
+}
+
+/// Computes the MSE and MAE of every output dimension of a multi-output regression.
+///
+/// # Arguments
+/// * `predictions` - One prediction vector per sample, e.g. from `Sequential::predict`.
+/// * `targets` - Target vectors aligned with `predictions`, all of the same length.
+///
+/// # Returns
+/// * One `OutputErrors` per output dimension, averaged over samples.
+pub fn regression_errors<T: Number + ToPrimitive>(predictions: &[Vec<T>], targets: &[Vec<T>]) -> Vec<OutputErrors> {
+    assert_eq!(predictions.len(), targets.len(), "predictions and targets must have the same length");
+    let to_f64 = |v: &[T]| v.iter().map(|x| x.to_f64().unwrap()).collect::<Vec<_>>();
+    let mut metric = RegressionErrors::new();
+    for (p, t) in predictions.iter().zip(targets.iter()) {
+        metric.accumulate((to_f64(p), to_f64(t)));
+    }
+    metric.finalize()
+}
+
+/// Result of McNemar's test comparing two classifiers on the same samples.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct McNemarResult {
+    /// Number of samples model A classified correctly and model B did not.
+    pub only_a_correct: usize,
+    /// Number of samples model B classified correctly and model A did not.
+    pub only_b_correct: usize,
+    /// Continuity-corrected chi-squared statistic (1 degree of freedom).
+    pub statistic: f64,
+    /// Two-sided p-value of the test.
+    pub p_value: f64,
+}
+
+impl McNemarResult {
+    /// Returns `true` if the difference is significant at level `alpha` (e.g. `0.05`).
+    pub fn is_significant(&self, alpha: f64) -> bool {
+        self.p_value < alpha
+    }
+}
+
+/// Result of a paired two-sided Student's t-test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PairedTTestResult {
+    /// Mean of the per-sample differences `a_i - b_i`.
+    pub mean_difference: f64,
+    /// The t statistic.
+    pub statistic: f64,
+    /// Degrees of freedom (`n - 1`).
+    pub degrees_of_freedom: usize,
+    /// Two-sided p-value of the test.
+    pub p_value: f64,
+}
+
+impl PairedTTestResult {
+    /// Returns `true` if the difference is significant at level `alpha` (e.g. `0.05`).
+    pub fn is_significant(&self, alpha: f64) -> bool {
+        self.p_value < alpha
+    }
+}
+
+/// Runs **McNemar's test** on the predictions of two classifiers over the same test set.
+///
+/// # Arguments
+/// * `predictions_a` - Predicted labels of model A.
+/// * `predictions_b` - Predicted labels of model B.
+/// * `targets` - True labels.
+///
+/// # Steps
+/// 1. Count `b`, the samples only model A got right, and `c`, the samples only model B got right.
+///    Samples both models got right (or wrong) carry no information about the difference.
+/// 2. Compute the continuity-corrected statistic `(|b - c| - 1)^2 / (b + c)`.
+/// 3. Compute the p-value from the chi-squared distribution with one degree of freedom.
+///
+/// # Notes
+/// - If the models never disagree (`b + c == 0`) the statistic is `0` and the p-value `1`.
+/// - All three slices must have the same length; otherwise the function panics.
+pub fn mcnemar_test<L: PartialEq>(predictions_a: &[L], predictions_b: &[L], targets: &[L]) -> McNemarResult {
+    assert_eq!(predictions_a.len(), targets.len(), "predictions_a and targets must have the same length");
+    assert_eq!(predictions_b.len(), targets.len(), "predictions_b and targets must have the same length");
+
+    let mut metric = McNemar::new();
+    for i in 0..targets.len() {
+        metric.accumulate((predictions_a[i] == targets[i], predictions_b[i] == targets[i]));
+    }
+    metric.finalize()
+}
+
+/// Streaming accumulator for `mcnemar_test` over `(a_correct, b_correct)` outcomes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct McNemar {
+    only_a_correct: usize,
+    only_b_correct: usize,
+}
+
+impl McNemar {
+    pub fn new() -> Self {
+        McNemar::default()
+    }
+}
+
+impl StreamingMetric for McNemar {
+    type Input = (bool, bool);
+    type Output = McNemarResult;
+
+    fn accumulate(&mut self, (a_correct, b_correct): (bool, bool)) {
+        if a_correct && !b_correct {
+            self.only_a_correct += 1;
+        } else if b_correct && !a_correct {
+            self.only_b_correct += 1;
+        }
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.only_a_correct += other.only_a_correct;
+        self.only_b_correct += other.only_b_correct;
+    }
+
+    fn finalize(&self) -> McNemarResult {
+        let McNemar { only_a_correct, only_b_correct } = *self;
+        let discordant = only_a_correct + only_b_correct;
+        if discordant == 0 {
+            return McNemarResult { only_a_correct, only_b_correct, statistic: 0.0, p_value: 1.0 };
+        }
+
+        let diff = (only_a_correct as f64 - only_b_correct as f64).abs() - 1.0;
+        let diff = if diff < 0.0 { 0.0 } else { diff };
+        let statistic = diff * diff / discordant as f64;
+        // Survival function of chi-squared with 1 dof: P(X > s) = erfc(sqrt(s / 2))
+        let p_value = erfc((statistic / 2.0).sqrt());
+
+        McNemarResult { only_a_correct, only_b_correct, statistic, p_value }
+    }
+}
+
+/// Runs a **paired two-sided t-test** on per-sample scores of two models.
+///
+/// # Arguments
+/// * `scores_a` - Per-sample scores of model A (e.g. per-sample loss or correctness).
+/// * `scores_b` - Per-sample scores of model B, aligned with `scores_a`.
+///
+/// # Steps
+/// 1. Compute the differences `d_i = a_i - b_i`, their mean and sample standard deviation.
+/// 2. Compute `t = mean / (sd / sqrt(n))` with `n - 1` degrees of freedom.
+/// 3. Compute the two-sided p-value from Student's t distribution.
+///
+/// # Notes
+/// - Requires at least two samples and slices of equal length; otherwise the function panics.
+/// - If all differences are identical the standard deviation is zero: a zero mean gives
+///   `t = 0, p = 1`, any other mean gives an infinite `t` and `p = 0`.
+pub fn paired_t_test<T: Number + ToPrimitive>(scores_a: &[T], scores_b: &[T]) -> PairedTTestResult {
+    assert_eq!(scores_a.len(), scores_b.len(), "scores_a and scores_b must have the same length");
+
+    let mut metric = PairedTTest::new();
+    for (a, b) in scores_a.iter().zip(scores_b.iter()) {
+        metric.accumulate((a.to_f64().unwrap(), b.to_f64().unwrap()));
+    }
+    metric.finalize()
+}
+
+/// Streaming accumulator for `paired_t_test` over `(score_a, score_b)` pairs.
+///
+/// Keeps the running count, mean and sum of squared deviations of the differences
+/// (Welford's algorithm; shards are combined with Chan et al.'s parallel update).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PairedTTest {
+    count: usize,
+    mean: f64,
+    m2: f64,
+}
+
+impl PairedTTest {
+    pub fn new() -> Self {
+        PairedTTest::default()
+    }
+}
+
+impl StreamingMetric for PairedTTest {
+    type Input = (f64, f64);
+    type Output = PairedTTestResult;
+
+    fn accumulate(&mut self, (a, b): (f64, f64)) {
+        let d = a - b;
+        self.count += 1;
+        let delta = d - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (d - self.mean);
+    }
+
+    fn merge(&mut self, other: &Self) {
+        if other.count == 0 {
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 += other.m2 + delta * delta * (self.count as f64 * other.count as f64) / count as f64;
+        self.count = count;
+    }
+
+    /// Panics if fewer than two pairs were accumulated.
+    fn finalize(&self) -> PairedTTestResult {
+        assert!(self.count >= 2, "paired t-test needs at least two samples");
+        let n = self.count;
+        let mean = self.mean;
+        let variance = self.m2 / (n - 1) as f64;
+        let degrees_of_freedom = n - 1;
+
+        let (statistic, p_value) = if variance == 0.0 {
+            if mean == 0.0 { (0.0, 1.0) } else { (mean.signum() * f64::INFINITY, 0.0) }
+        } else {
+            let t = mean / (variance / n as f64).sqrt();
+            (t, student_t_two_sided_p(t, degrees_of_freedom as f64))
+        };
+
+        PairedTTestResult { mean_difference: mean, statistic, degrees_of_freedom, p_value }
+    }
+}
+
+/// Two-sided tail probability `P(|T| > |t|)` of Student's t distribution with `dof` degrees of freedom.
+fn student_t_two_sided_p(t: f64, dof: f64) -> f64 {
+    let x = dof / (dof + t * t);
+    regularized_incomplete_beta(x, dof / 2.0, 0.5)
+}
+
+/// Complementary error function, accurate to about `1.2e-7` (Chebyshev fit from Numerical Recipes).
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let ans = t * (-z * z - 1.26551223
+        + t * (1.00002368
+        + t * (0.37409196
+        + t * (0.09678418
+        + t * (-0.18628806
+        + t * (0.27886807
+        + t * (-1.13520398
+        + t * (1.48851587
+        + t * (-0.82215223
+        + t * 0.17087277))))))))).exp();
+    if x >= 0.0 { ans } else { 2.0 - ans }
+}
+
+/// Natural logarithm of the gamma function (Lanczos approximation).
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFS: [f64; 6] = [
+        76.18009172947146,
+        -86.50532032941677,
+        24.01409824083091,
+        -1.231739572450155,
+        0.1208650973866179e-2,
+        -0.5395239384953e-5,
+    ];
+    let mut y = x;
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let mut series = 1.000000000190015;
+    for c in COEFFS.iter() {
+        y += 1.0;
+        series += c / y;
+    }
+    -tmp + (2.5066282746310005 * series / x).ln()
+}
+
+/// Regularized incomplete beta function `I_x(a, b)`.
+fn regularized_incomplete_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    // The continued fraction converges quickly only on one side of the mean; use symmetry otherwise.
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(x, a, b) / a
+    } else {
+        1.0 - front * beta_continued_fraction(1.0 - x, b, a) / b
+    }
+}
+
+/// Continued fraction for the incomplete beta function (modified Lentz's method).
+fn beta_continued_fraction(x: f64, a: f64, b: f64) -> f64 {
+    const MAX_ITERATIONS: usize = 200;
+    const EPS: f64 = 3e-14;
+    const TINY: f64 = 1e-300;
+
+    let qab = a + b;
+    let qap = a + 1.0;
+    let qam = a - 1.0;
+    let mut c = 1.0;
+    let mut d = 1.0 - qab * x / qap;
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut h = d;
+    for m in 1..=MAX_ITERATIONS {
+        let m = m as f64;
+        let m2 = 2.0 * m;
+        let aa = m * (b - m) * x / ((qam + m2) * (a + m2));
+        d = 1.0 + aa * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1.0 + aa / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        h *= d * c;
+        let aa = -(a + m) * (qab + m) * x / ((a + m2) * (qap + m2));
+        d = 1.0 + aa * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1.0 + aa / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        let del = d * c;
+        h *= del;
+        if (del - 1.0).abs() < EPS {
+            break;
+        }
+    }
+    h
+}
+
+/// Builds a confusion matrix: `matrix[t][p]` counts samples of true class `t` predicted as `p`.
+///
+/// Panics if the slices differ in length or a label is `>= n_classes`.
+pub fn confusion_matrix(predictions: &[usize], targets: &[usize], n_classes: usize) -> Vec<Vec<usize>> {
+    assert_eq!(predictions.len(), targets.len(), "predictions and targets must have the same length");
+    let mut metric = ConfusionMatrix::new(n_classes);
+    for (&p, &t) in predictions.iter().zip(targets.iter()) {
+        metric.accumulate((p, t));
+    }
+    metric.finalize()
+}
+
+/// Streaming accumulator for `confusion_matrix` over `(prediction, target)` pairs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfusionMatrix {
+    counts: Vec<Vec<usize>>,
+}
+
+impl ConfusionMatrix {
+    pub fn new(n_classes: usize) -> Self {
+        ConfusionMatrix { counts: vec![vec![0usize; n_classes]; n_classes] }
+    }
+}
+
+impl StreamingMetric for ConfusionMatrix {
+    type Input = (usize, usize);
+    type Output = Vec<Vec<usize>>;
+
+    /// Panics if a label is `>= n_classes`.
+    fn accumulate(&mut self, (prediction, target): (usize, usize)) {
+        self.counts[target][prediction] += 1;
+    }
+
+    fn merge(&mut self, other: &Self) {
+        assert_eq!(self.counts.len(), other.counts.len(), "confusion matrices must have the same number of classes");
+        for (row, other_row) in self.counts.iter_mut().zip(other.counts.iter()) {
+            for (c, o) in row.iter_mut().zip(other_row.iter()) {
+                *c += o;
+            }
+        }
+    }
+
+    fn finalize(&self) -> Vec<Vec<usize>> {
+        self.counts.clone()
+    }
+}
+
+/// Index of the largest value (first one on ties). Panics on an empty slice.
+pub fn argmax<T: Number>(values: &[T]) -> usize {
+    assert!(!values.is_empty(), "argmax of an empty slice");
+    let mut best = 0;
+    for i in 1..values.len() {
+        if values[i] > values[best] {
+            best = i;
+        }
+    }
+    best
+}
+
+/// A misclassified sample selected for error analysis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Misclassification<T> {
+    /// Row index of the sample in the evaluated feature matrix.
+    pub row: usize,
+    pub true_class: usize,
+    pub predicted_class: usize,
+    /// Predicted probability of `predicted_class`.
+    pub confidence: T,
+}
+
+/// Collects the `top_n` most confident misclassifications for every off-diagonal confusion-matrix cell.
+///
+/// # Arguments
+/// * `predict_proba` - The trained classifier: maps a feature row to class probabilities.
+/// * `features` - Test feature rows.
+/// * `targets` - True class of each row.
+/// * `top_n` - Maximum number of samples kept per `(true_class, predicted_class)` cell.
+///
+/// # Returns
+/// * Misclassifications ordered by true class, then predicted class, then decreasing confidence.
+///
+/// # Notes
+/// - The predicted class is the argmax of `predict_proba`.
+/// - Confident mistakes are the most informative to inspect: they often reveal label noise
+///   or systematically confused classes.
+pub fn top_misclassifications<T, F>(predict_proba: F, features: &[Vec<T>], targets: &[usize], top_n: usize) -> Vec<Misclassification<T>>
+where
+    T: Number,
+    F: Fn(&[T]) -> Vec<T>,
+{
+    assert_eq!(features.len(), targets.len(), "features and targets must have the same length");
+    let mut errors: Vec<Misclassification<T>> = Vec::new();
+    for (row, (x, &true_class)) in features.iter().zip(targets.iter()).enumerate() {
+        let probabilities = predict_proba(x);
+        let predicted_class = argmax(&probabilities);
+        if predicted_class != true_class {
+            errors.push(Misclassification { row, true_class, predicted_class, confidence: probabilities[predicted_class] });
+        }
+    }
+
+    errors.sort_by(|a, b| {
+        a.true_class.cmp(&b.true_class)
+            .then(a.predicted_class.cmp(&b.predicted_class))
+            .then(b.confidence.partial_cmp(&a.confidence).unwrap_or(std::cmp::Ordering::Equal))
+    });
+
+    let mut selected: Vec<Misclassification<T>> = Vec::new();
+    let mut cell_count = 0;
+    for error in errors {
+        let same_cell = selected.last()
+            .is_some_and(|last| last.true_class == error.true_class && last.predicted_class == error.predicted_class);
+        cell_count = if same_cell { cell_count + 1 } else { 1 };
+        if cell_count <= top_n {
+            selected.push(error);
+        }
+    }
+    selected
+}
+
+/// Writes misclassifications and their feature values to a CSV file for manual error analysis.
+///
+/// # Arguments
+/// * `path` - Output CSV path.
+/// * `errors` - Output of `top_misclassifications`.
+/// * `features` - The same feature rows passed to `top_misclassifications`.
+/// * `feature_names` - Column names for the features; if shorter than a row, `f<j>` is used.
+///
+/// The header is `row,true_class,predicted_class,confidence,<feature columns...>`.
+pub fn export_misclassifications_csv<T: Number + Display, P: AsRef<Path>>(
+    path: P,
+    errors: &[Misclassification<T>],
+    features: &[Vec<T>],
+    feature_names: &[&str],
+) -> Result<(), Box<dyn Error>> {
+    let mut writer = csv::Writer::from_path(path)?;
+    let n_features = errors.first().map(|e| features[e.row].len()).unwrap_or(feature_names.len());
+
+    let mut header = vec!["row".to_string(), "true_class".to_string(), "predicted_class".to_string(), "confidence".to_string()];
+    for j in 0..n_features {
+        header.push(feature_names.get(j).map(|s| s.to_string()).unwrap_or_else(|| format!("f{}", j)));
+    }
+    writer.write_record(&header)?;
+
+    for error in errors {
+        let mut record = vec![
+            error.row.to_string(),
+            error.true_class.to_string(),
+            error.predicted_class.to_string(),
+            error.confidence.to_string(),
+        ];
+        record.extend(features[error.row].iter().map(|v| v.to_string()));
+        writer.write_record(&record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// One non-empty bin of a calibration (reliability) curve.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalibrationBin {
+    /// Lower edge of the bin (inclusive).
+    pub lower: f64,
+    /// Upper edge of the bin (exclusive, except for the last bin).
+    pub upper: f64,
+    /// Number of predictions falling into the bin.
+    pub count: usize,
+    /// Mean predicted probability in the bin (x-axis of a reliability diagram).
+    pub mean_predicted: f64,
+    /// Fraction of positive labels in the bin (y-axis of a reliability diagram).
+    pub observed_frequency: f64,
+}
+
+/// Calibration curve points together with summary calibration errors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalibrationCurve {
+    /// Non-empty bins in increasing probability order.
+    pub bins: Vec<CalibrationBin>,
+    /// Expected calibration error: count-weighted mean of `|observed - predicted|` over bins.
+    pub ece: f64,
+    /// Maximum calibration error: largest `|observed - predicted|` over bins.
+    pub mce: f64,
+}
+
+impl CalibrationCurve {
+    /// Writes the curve points to a CSV file with header
+    /// `lower,upper,count,mean_predicted,observed_frequency`.
+    pub fn to_csv<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let mut writer = csv::Writer::from_path(path)?;
+        writer.write_record(["lower", "upper", "count", "mean_predicted", "observed_frequency"])?;
+        for bin in &self.bins {
+            writer.write_record(&[
+                bin.lower.to_string(),
+                bin.upper.to_string(),
+                bin.count.to_string(),
+                bin.mean_predicted.to_string(),
+                bin.observed_frequency.to_string(),
+            ])?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Computes a **calibration curve** for binary predicted probabilities.
+///
+/// # Arguments
+/// * `probabilities` - Predicted probability of the positive class, in `[0, 1]`.
+/// * `labels` - Binary targets (`0` or `1`), aligned with `probabilities`.
+/// * `n_bins` - Number of equal-width bins over `[0, 1]`.
+///
+/// # Steps
+/// 1. Assign each prediction to bin `floor(p * n_bins)` (a prediction of exactly `1` goes to the last bin).
+/// 2. For every non-empty bin compute the mean prediction and the observed positive frequency.
+/// 3. `ece = sum_b (count_b / n) * |observed_b - predicted_b|`, `mce = max_b |observed_b - predicted_b|`.
+///
+/// # Notes
+/// - A perfectly calibrated model has points on the diagonal and `ece = mce = 0`.
+/// - Panics if the slices differ in length, are empty, or `n_bins == 0`.
+pub fn calibration_curve<T: Number + ToPrimitive>(probabilities: &[T], labels: &[T], n_bins: usize) -> CalibrationCurve {
+    assert_eq!(probabilities.len(), labels.len(), "probabilities and labels must have the same length");
+
+    let mut metric = Calibration::new(n_bins);
+    for (p, y) in probabilities.iter().zip(labels.iter()) {
+        metric.accumulate((p.to_f64().unwrap(), y.to_f64().unwrap()));
+    }
+    metric.finalize()
+}
+
+/// Streaming accumulator for `calibration_curve` over `(probability, label)` pairs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Calibration {
+    counts: Vec<usize>,
+    predicted_sums: Vec<f64>,
+    positive_sums: Vec<f64>,
+}
+
+impl Calibration {
+    /// Creates an accumulator with `n_bins` equal-width bins. Panics if `n_bins == 0`.
+    pub fn new(n_bins: usize) -> Self {
+        assert!(n_bins > 0, "n_bins must be positive");
+        Calibration { counts: vec![0; n_bins], predicted_sums: vec![0.0; n_bins], positive_sums: vec![0.0; n_bins] }
+    }
+}
+
+impl StreamingMetric for Calibration {
+    type Input = (f64, f64);
+    type Output = CalibrationCurve;
+
+    fn accumulate(&mut self, (probability, label): (f64, f64)) {
+        let n_bins = self.counts.len();
+        let p = probability.clamp(0.0, 1.0);
+        let bin = ((p * n_bins as f64) as usize).min(n_bins - 1);
+        self.counts[bin] += 1;
+        self.predicted_sums[bin] += p;
+        self.positive_sums[bin] += label;
+    }
+
+    fn merge(&mut self, other: &Self) {
+        assert_eq!(self.counts.len(), other.counts.len(), "calibration accumulators must have the same number of bins");
+        for b in 0..self.counts.len() {
+            self.counts[b] += other.counts[b];
+            self.predicted_sums[b] += other.predicted_sums[b];
+            self.positive_sums[b] += other.positive_sums[b];
+        }
+    }
+
+    /// Panics if nothing was accumulated.
+    fn finalize(&self) -> CalibrationCurve {
+        let total: usize = self.counts.iter().sum();
+        assert!(total > 0, "calibration curve needs at least one prediction");
+
+        let n_bins = self.counts.len();
+        let n = total as f64;
+        let mut bins = Vec::new();
+        let mut ece = 0.0;
+        let mut mce = 0.0f64;
+        for b in 0..n_bins {
+            let count = self.counts[b];
+            if count == 0 {
+                continue;
+            }
+            let mean_predicted = self.predicted_sums[b] / count as f64;
+            let observed_frequency = self.positive_sums[b] / count as f64;
+            let gap = (observed_frequency - mean_predicted).abs();
+            ece += count as f64 / n * gap;
+            mce = mce.max(gap);
+            bins.push(CalibrationBin {
+                lower: b as f64 / n_bins as f64,
+                upper: (b + 1) as f64 / n_bins as f64,
+                count,
+                mean_predicted,
+                observed_frequency,
+            });
+        }
+        CalibrationCurve { bins, ece, mce }
+    }
+}
+
+/// Precision, recall and F1 of binary predictions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BinaryScores {
+    pub precision: f64,
+    pub recall: f64,
+    pub f1: f64,
+}
+
+/// Streaming accumulator of binary `(predicted_positive, actually_positive)` outcomes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BinaryClassification {
+    pub true_positives: usize,
+    pub false_positives: usize,
+    pub false_negatives: usize,
+    pub true_negatives: usize,
+}
+
+impl BinaryClassification {
+    pub fn new() -> Self {
+        BinaryClassification::default()
+    }
+}
+
+impl StreamingMetric for BinaryClassification {
+    type Input = (bool, bool);
+    type Output = BinaryScores;
+
+    fn accumulate(&mut self, (predicted, actual): (bool, bool)) {
+        match (predicted, actual) {
+            (true, true) => self.true_positives += 1,
+            (true, false) => self.false_positives += 1,
+            (false, true) => self.false_negatives += 1,
+            (false, false) => self.true_negatives += 1,
+        }
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.true_positives += other.true_positives;
+        self.false_positives += other.false_positives;
+        self.false_negatives += other.false_negatives;
+        self.true_negatives += other.true_negatives;
+    }
+
+    /// Scores with an undefined ratio (e.g. precision without positive predictions) are `0`.
+    fn finalize(&self) -> BinaryScores {
+        let ratio = |num: usize, den: usize| if den == 0 { 0.0 } else { num as f64 / den as f64 };
+        let tp = self.true_positives;
+        let precision = ratio(tp, tp + self.false_positives);
+        let recall = ratio(tp, tp + self.false_negatives);
+        let f1 = ratio(2 * tp, 2 * tp + self.false_positives + self.false_negatives);
+        BinaryScores { precision, recall, f1 }
+    }
+}
+
+/// Computes precision, recall and F1 of binary `predictions` against `targets`.
+pub fn binary_scores(predictions: &[bool], targets: &[bool]) -> BinaryScores {
+    assert_eq!(predictions.len(), targets.len(), "predictions and targets must have the same length");
+    let mut metric = BinaryClassification::new();
+    for (&p, &t) in predictions.iter().zip(targets.iter()) {
+        metric.accumulate((p, t));
+    }
+    metric.finalize()
+}
+
+/// One point of a ROC curve: the rates obtained by predicting `score >= threshold` positive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RocPoint {
+    pub threshold: f64,
+    pub false_positive_rate: f64,
+    pub true_positive_rate: f64,
+}
+
+/// Scores sorted by decreasing value, paired with whether the label is positive (`> 0.5`).
+fn ranked_scores<T: Number + ToPrimitive>(scores: &[T], labels: &[T]) -> Vec<(f64, bool)> {
+    assert_eq!(scores.len(), labels.len(), "scores and labels must have the same length");
+    let mut ranked: Vec<(f64, bool)> = scores.iter().zip(labels.iter())
+        .map(|(s, y)| (s.to_f64().unwrap(), y.to_f64().unwrap() > 0.5))
+        .collect();
+    ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+    ranked
+}
+
+/// Computes the **ROC curve** of binary classifier scores.
+///
+/// # Arguments
+/// * `scores` - Classifier scores or probabilities; higher means more likely positive.
+/// * `labels` - Binary targets, aligned with `scores`; values `> 0.5` count as positive.
+///
+/// # Steps
+/// 1. Sort the scores in decreasing order.
+/// 2. Lower the threshold through every distinct score, counting true and false positives.
+/// 3. Emit one point per distinct score, preceded by `(0, 0)` at threshold `+inf`.
+///
+/// # Notes
+/// - Panics if the slices differ in length or the labels do not contain both classes.
+pub fn roc_curve<T: Number + ToPrimitive>(scores: &[T], labels: &[T]) -> Vec<RocPoint> {
+    let ranked = ranked_scores(scores, labels);
+    let positives = ranked.iter().filter(|(_, y)| *y).count();
+    let negatives = ranked.len() - positives;
+    assert!(positives > 0 && negatives > 0, "ROC curve needs both positive and negative labels");
+
+    let mut points = vec![RocPoint { threshold: f64::INFINITY, false_positive_rate: 0.0, true_positive_rate: 0.0 }];
+    let (mut tp, mut fp) = (0usize, 0usize);
+    for k in 0..ranked.len() {
+        if ranked[k].1 { tp += 1 } else { fp += 1 }
+        if k + 1 == ranked.len() || ranked[k + 1].0 < ranked[k].0 {
+            points.push(RocPoint {
+                threshold: ranked[k].0,
+                false_positive_rate: fp as f64 / negatives as f64,
+                true_positive_rate: tp as f64 / positives as f64,
+            });
+        }
+    }
+    points
+}
+
+/// Area under a ROC curve (trapezoidal rule): `1.0` for a perfect ranking, `0.5` for chance.
+pub fn auc(curve: &[RocPoint]) -> f64 {
+    curve.windows(2)
+        .map(|w| (w[1].false_positive_rate - w[0].false_positive_rate) * (w[0].true_positive_rate + w[1].true_positive_rate) / 2.0)
+        .sum()
+}
+
+/// Shorthand for `auc(&roc_curve(scores, labels))`.
+pub fn roc_auc_score<T: Number + ToPrimitive>(scores: &[T], labels: &[T]) -> f64 {
+    auc(&roc_curve(scores, labels))
+}
+
+/// Searches the decision threshold maximizing F1, e.g. on validation scores of a BCE-trained model.
+///
+/// # Returns
+/// * `(threshold, scores)` - predicting `score >= threshold` positive gives the returned
+///   precision/recall/F1. The higher threshold wins ties.
+///
+/// # Notes
+/// - Without positive labels every threshold has F1 `0`, and the highest score is returned.
+/// - Panics if the slices differ in length or are empty.
+pub fn best_threshold_by_f1<T: Number + ToPrimitive>(scores: &[T], labels: &[T]) -> (f64, BinaryScores) {
+    let ranked = ranked_scores(scores, labels);
+    assert!(!ranked.is_empty(), "threshold search needs at least one score");
+    let positives = ranked.iter().filter(|(_, y)| *y).count();
+
+    let mut best: Option<(f64, BinaryClassification)> = None;
+    let mut counts = BinaryClassification { false_negatives: positives, true_negatives: ranked.len() - positives, ..Default::default() };
+    for k in 0..ranked.len() {
+        if ranked[k].1 {
+            counts.true_positives += 1;
+            counts.false_negatives -= 1;
+        } else {
+            counts.false_positives += 1;
+            counts.true_negatives -= 1;
+        }
+        if k + 1 < ranked.len() && ranked[k + 1].0 == ranked[k].0 {
+            continue;
+        }
+        if best.is_none_or(|(_, b)| counts.finalize().f1 > b.finalize().f1) {
+            best = Some((ranked[k].0, counts));
+        }
+    }
+    let (threshold, counts) = best.unwrap();
+    (threshold, counts.finalize())
+}
@@ -0,0 +1,1453 @@
+//! Runtime-shaped models assembled from layer specs.
+//!
+//! `Layer1D` fixes its shape through const generics, which makes stacking layers of
+//! different sizes awkward and turns shape mistakes into compile errors far from their
+//! cause. [`ModelBuilder`] instead collects layer specs, checks that consecutive shapes
+//! fit together when `build` is called, and returns a ready [`Sequential`].
+//!
+//! ```
+//! use neuralnet::model::ModelBuilder;
+//!
+//! let model = ModelBuilder::new(4).dense(8).relu().dense(3).softmax().build::<f64>().unwrap();
+//! let probabilities = model.forward(&[0.1, 0.2, 0.3, 0.4]);
+//! assert_eq!(probabilities.len(), 3);
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use num_traits::{FromPrimitive, ToPrimitive};
+use crate::numbers::{Number, Real};
+use crate::activation_fn::{softmax, softmax_in_place, Activation};
+use crate::back_propagation::{backward_pass, backward_pass_with};
+use crate::backend::{Backend, Naive};
+use crate::layers::{Layer, Layer1D, LayerError, Parameter, ParameterMut};
+use crate::loss_fn::{Loss, LossFn};
+use crate::random::Rng;
+#[cfg(feature = "ndarray")]
+use ndarray::{Array1, Array2, ArrayView1, ArrayView2};
+
+/// One entry of a `ModelBuilder`, before weights are allocated.
+/// Serializes with serde, so architectures can be written in config files.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum LayerSpec {
+    /// Fully-connected layer with `units` outputs. `inputs` is the expected input size
+    /// when given explicitly, and is inferred from the previous layer otherwise.
+    Dense {
+        #[serde(default)]
+        inputs: Option<usize>,
+        units: usize,
+    },
+    /// Element-wise activation.
+    Activation(Activation),
+    /// Parametric ReLU with one learnable negative slope per input, all starting at `alpha`.
+    PReLU { alpha: f64 },
+    /// Softmax over the outputs; only allowed as the last layer.
+    Softmax,
+}
+
+/// Reason why `ModelBuilder::build` rejected a model.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildError {
+    /// The builder has no layers.
+    EmptyModel,
+    /// No input size was given and the first dense layer does not declare one.
+    MissingInputDim,
+    /// The dense layer at `layer` has zero units.
+    ZeroUnits { layer: usize },
+    /// The layer at `layer` declares `found` inputs but the previous layer produces `expected`.
+    ShapeMismatch { layer: usize, expected: usize, found: usize },
+    /// Softmax at `layer` is followed by other layers.
+    SoftmaxNotLast { layer: usize },
+    /// The layer at `layer` could not be constructed, e.g. a PReLU on zero inputs.
+    InvalidLayer { layer: usize, error: LayerError },
+    /// A hyperparameter of the layer at `layer` (such as a PReLU slope) is not
+    /// representable in the model's number type.
+    Unrepresentable { layer: usize, value: f64 },
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::EmptyModel => write!(f, "model has no layers"),
+            BuildError::MissingInputDim => write!(f, "input size is unknown: use ModelBuilder::new or give the first dense layer its input size"),
+            BuildError::ZeroUnits { layer } => write!(f, "layer {} has zero units", layer),
+            BuildError::ShapeMismatch { layer, expected, found } => write!(
+                f, "layer {} expects {} inputs but the previous layer produces {}", layer, found, expected
+            ),
+            BuildError::SoftmaxNotLast { layer } => write!(f, "softmax at layer {} must be the last layer", layer),
+            BuildError::InvalidLayer { layer, error } => write!(f, "layer {}: {}", layer, error),
+            BuildError::Unrepresentable { layer, value } => write!(
+                f, "layer {}: {} is not representable in the number type", layer, value
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// Fully-connected layer whose shape is known only at runtime.
+/// `weights[i][j]` is the weight for output `i` and input `j`, as in `Layer1D`.
+///
+/// Accumulated gradients (see `Layer`) are neither serialized nor compared by `==`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dense<T: Number> {
+    pub weights: Vec<Vec<T>>,
+    pub biases: Vec<T>,
+    #[serde(skip)]
+    grads: DenseGradients<T>,
+}
+
+impl<T: Number> PartialEq for Dense<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.weights == other.weights && self.biases == other.biases
+    }
+}
+
+impl<T: Number + FromPrimitive> Dense<T> {
+    /// Creates a layer with Glorot-uniform weights, drawn from `U(-l, l)` with
+    /// `l = sqrt(6 / (inputs + units))`, and zero biases.
+    pub fn glorot_uniform(inputs: usize, units: usize, rng: &mut Rng) -> Self {
+        let limit = (6.0 / (inputs + units) as f64).sqrt();
+        let weights = (0..units)
+            .map(|_| (0..inputs).map(|_| T::to_number((rng.next_f64() * 2.0 - 1.0) * limit)).collect())
+            .collect();
+        Dense::new(weights, vec![T::zero(); units])
+    }
+
+    /// Creates a layer with He-uniform weights, drawn from `U(-l, l)` with
+    /// `l = sqrt(6 / inputs)`, and zero biases. Suited to layers followed by ReLU.
+    pub fn he_uniform(inputs: usize, units: usize, rng: &mut Rng) -> Self {
+        let limit = (6.0 / inputs.max(1) as f64).sqrt();
+        let weights = (0..units)
+            .map(|_| (0..inputs).map(|_| T::to_number((rng.next_f64() * 2.0 - 1.0) * limit)).collect())
+            .collect();
+        Dense::new(weights, vec![T::zero(); units])
+    }
+}
+
+impl<T: Number> Dense<T> {
+    /// Creates a layer from explicit weights. Panics if the rows differ in length
+    /// or `biases` does not have one entry per row; see `try_new`.
+    pub fn new(weights: Vec<Vec<T>>, biases: Vec<T>) -> Self {
+        assert_eq!(weights.len(), biases.len(), "weights and biases must have the same length");
+        if let Some(first) = weights.first() {
+            assert!(weights.iter().all(|row| row.len() == first.len()), "every weight row must have the same length");
+        }
+        Dense { weights, biases, grads: DenseGradients::default() }
+    }
+
+    /// Fallible version of `new`.
+    pub fn try_new(weights: Vec<Vec<T>>, biases: Vec<T>) -> Result<Self, LayerError> {
+        if weights.len() != biases.len() {
+            return Err(LayerError::WrongLength { expected: weights.len(), found: biases.len() });
+        }
+        let columns = weights.first().map_or(0, |row| row.len());
+        if let Some(row) = weights.iter().find(|row| row.len() != columns) {
+            return Err(LayerError::WrongLength { expected: columns, found: row.len() });
+        }
+        Ok(Dense { weights, biases, grads: DenseGradients::default() })
+    }
+
+    pub fn input_dim(&self) -> usize {
+        self.weights.first().map_or(0, |row| row.len())
+    }
+
+    pub fn output_dim(&self) -> usize {
+        self.biases.len()
+    }
+
+    /// Shape of the weight matrix, `(output_dim, input_dim)`.
+    pub fn shape(&self) -> (usize, usize) {
+        (self.output_dim(), self.input_dim())
+    }
+
+    /// Weights flattened row by row, the layout of a standard (C-order) matrix in array
+    /// libraries such as ndarray: `Array2::from_shape_vec(layer.shape(), layer.weights_row_major())`.
+    pub fn weights_row_major(&self) -> Vec<T> {
+        self.weights.iter().flatten().copied().collect()
+    }
+
+    /// Creates a layer from row-major weights of the given `(outputs, inputs)` shape,
+    /// the inverse of `shape` and `weights_row_major`.
+    ///
+    /// # Errors
+    /// `LayerError::WrongLength` if `weights` does not hold `outputs * inputs` values or
+    /// `biases` does not hold `outputs`.
+    pub fn try_from_row_major(shape: (usize, usize), weights: Vec<T>, biases: Vec<T>) -> Result<Self, LayerError> {
+        let (outputs, inputs) = shape;
+        if weights.len() != outputs * inputs {
+            return Err(LayerError::WrongLength { expected: outputs * inputs, found: weights.len() });
+        }
+        let rows = if inputs == 0 { vec![Vec::new(); outputs] } else { weights.chunks(inputs).map(<[T]>::to_vec).collect() };
+        Dense::try_new(rows, biases)
+    }
+
+    /// Forward pass: `outputs = biases + W * inputs`.
+    pub fn forward(&self, inputs: &[T]) -> Vec<T> {
+        let mut outputs = Vec::with_capacity(self.output_dim());
+        self.forward_into(inputs, &mut outputs);
+        outputs
+    }
+
+    /// Like `forward`, replacing the contents of `out` and reusing its allocation.
+    pub fn forward_into(&self, inputs: &[T], out: &mut Vec<T>) {
+        self.forward_on(&Naive, inputs, out);
+    }
+
+    /// Like `forward_into`, computing the product with `backend`.
+    pub fn forward_on<B: Backend>(&self, backend: &B, inputs: &[T], out: &mut Vec<T>) {
+        assert_eq!(inputs.len(), self.input_dim(), "inputs must have one entry per weight column");
+        backend.matvec(&self.weights, &self.biases, inputs, out);
+    }
+
+    /// `Layer::backward` with the kernels of `backend`: accumulates the gradients for
+    /// `grad_output` and returns the gradient with respect to `inputs`.
+    pub fn backward_on<B: Backend>(&mut self, backend: &B, inputs: &[T], grad_output: &[T]) -> Vec<T> {
+        self.ensure_grads();
+        for (b, &g) in self.grads.biases.iter_mut().zip(grad_output.iter()) {
+            *b = *b + g;
+        }
+        backend.add_outer(&mut self.grads.weights, grad_output, inputs);
+        let mut upstream = Vec::with_capacity(inputs.len());
+        backend.matvec_transposed(&self.weights, grad_output, inputs.len(), &mut upstream);
+        upstream
+    }
+
+    /// Forward pass over a batch, computed as one matrix product on `backend`: row `b` of
+    /// the result equals `forward(&rows[b])` up to rounding.
+    pub fn forward_batch_on<B: Backend>(&self, backend: &B, rows: &[Vec<T>]) -> Vec<Vec<T>> {
+        let (outputs, inputs) = self.shape();
+        assert!(rows.iter().all(|row| row.len() == inputs), "inputs must have one entry per weight column");
+        let x: Vec<T> = rows.concat();
+        let mut y: Vec<T> = rows.iter().flat_map(|_| self.biases.iter().copied()).collect();
+        // Y = X W^T, reading the row-major weights with swapped strides
+        backend.gemm_strided(&x, (inputs, 1), &self.weights.concat(), (1, inputs), (rows.len(), inputs, outputs), &mut y);
+        split_rows(y, outputs, rows.len())
+    }
+
+    /// `backward_on` for a batch: accumulates the gradients of every `(rows[b], grads[b])`
+    /// pair with two matrix products and returns the gradient for every row.
+    pub fn backward_batch_on<B: Backend>(&mut self, backend: &B, rows: &[Vec<T>], grads: &[Vec<T>]) -> Vec<Vec<T>> {
+        let (outputs, inputs) = self.shape();
+        assert_eq!(rows.len(), grads.len(), "rows and grads must have the same length");
+        assert!(rows.iter().all(|row| row.len() == inputs), "inputs must have one entry per weight column");
+        assert!(grads.iter().all(|g| g.len() == outputs), "grads must have one entry per output");
+        self.ensure_grads();
+        let batch = rows.len();
+        for g in grads {
+            for (b, &gi) in self.grads.biases.iter_mut().zip(g.iter()) {
+                *b = *b + gi;
+            }
+        }
+        // dW += G^T X
+        let mut weight_grads = self.grads.weights.concat();
+        backend.gemm_strided(&grads.concat(), (1, outputs), &rows.concat(), (inputs, 1), (outputs, batch, inputs), &mut weight_grads);
+        for (row, chunk) in self.grads.weights.iter_mut().zip(weight_grads.chunks(inputs.max(1))) {
+            row.copy_from_slice(chunk);
+        }
+        // Upstream = G W
+        let mut upstream = vec![T::zero(); batch * inputs];
+        backend.gemm(&grads.concat(), &self.weights.concat(), (batch, outputs, inputs), &mut upstream);
+        split_rows(upstream, inputs, batch)
+    }
+
+    /// Gradients accumulated by `Layer::backward` since the last `zero_grad`.
+    pub fn grads(&self) -> &DenseGradients<T> {
+        &self.grads
+    }
+
+    /// Allocates zeroed gradients when they do not match the weights, e.g. after deserializing.
+    fn ensure_grads(&mut self) {
+        if self.grads.biases.len() != self.biases.len() || self.grads.weights.len() != self.weights.len() {
+            self.zero_grad();
+        }
+    }
+}
+
+/// Splits a flat row-major matrix into `n_rows` rows of `width` values.
+fn split_rows<T: Number>(values: Vec<T>, width: usize, n_rows: usize) -> Vec<Vec<T>> {
+    if width == 0 {
+        return vec![Vec::new(); n_rows];
+    }
+    values.chunks(width).map(<[T]>::to_vec).collect()
+}
+
+/// Converts a fixed-size layer so it can be trained and stacked through the `Layer` trait.
+impl<T: Number, const OUT: usize, const IN: usize> From<Layer1D<T, OUT, IN>> for Dense<T> {
+    fn from(layer: Layer1D<T, OUT, IN>) -> Self {
+        Dense::new(layer.weights.iter().map(|row| row.to_vec()).collect(), layer.biases.to_vec())
+    }
+}
+
+/// The weight matrix as an `outputs x inputs` array; the biases are `Array1::from(dense.biases.clone())`.
+#[cfg(feature = "ndarray")]
+impl<T: Number> From<&Dense<T>> for Array2<T> {
+    fn from(dense: &Dense<T>) -> Self {
+        Array2::from_shape_fn(dense.shape(), |(i, j)| dense.weights[i][j])
+    }
+}
+
+/// A layer with the given `outputs x inputs` weights and zero biases.
+#[cfg(feature = "ndarray")]
+impl<T: Number> TryFrom<Array2<T>> for Dense<T> {
+    type Error = LayerError;
+
+    fn try_from(weights: Array2<T>) -> Result<Self, LayerError> {
+        let biases = Array1::from_elem(weights.nrows(), T::zero());
+        Dense::try_from((weights, biases))
+    }
+}
+
+/// A layer from `(weights, biases)`, in any memory layout. Fails with
+/// `LayerError::WrongLength` unless there is one bias per weight row.
+#[cfg(feature = "ndarray")]
+impl<T: Number> TryFrom<(Array2<T>, Array1<T>)> for Dense<T> {
+    type Error = LayerError;
+
+    fn try_from((weights, biases): (Array2<T>, Array1<T>)) -> Result<Self, LayerError> {
+        Dense::try_new(weights.rows().into_iter().map(|row| row.to_vec()).collect(), biases.to_vec())
+    }
+}
+
+#[cfg(feature = "ndarray")]
+impl<T: Number> Dense<T> {
+    /// `forward` on an ndarray vector, which may be a strided view.
+    ///
+    /// # Errors
+    /// `LayerError::WrongLength` if `inputs` does not have `input_dim` values.
+    pub fn try_forward_array(&self, inputs: ArrayView1<'_, T>) -> Result<Array1<T>, LayerError> {
+        if inputs.len() != self.input_dim() {
+            return Err(LayerError::WrongLength { expected: self.input_dim(), found: inputs.len() });
+        }
+        Ok(Array1::from(self.forward(&inputs.to_vec())))
+    }
+}
+
+impl<T: Number> Layer<T> for Dense<T> {
+    fn forward(&self, inputs: &[T]) -> Vec<T> {
+        Dense::forward(self, inputs)
+    }
+
+    fn backward(&mut self, inputs: &[T], _outputs: &[T], grad_output: &[T]) -> Vec<T> {
+        self.backward_on(&Naive, inputs, grad_output)
+    }
+
+    fn params_mut(&mut self) -> Vec<(&mut T, T)> {
+        self.ensure_grads();
+        let weights = self.weights.iter_mut().flatten().zip(self.grads.weights.iter().flatten().copied());
+        let biases = self.biases.iter_mut().zip(self.grads.biases.iter().copied());
+        weights.chain(biases).collect()
+    }
+
+    fn zero_grad(&mut self) {
+        self.grads = DenseGradients {
+            weights: vec![vec![T::zero(); self.input_dim()]; self.output_dim()],
+            biases: vec![T::zero(); self.output_dim()],
+        };
+    }
+
+    fn parameters(&self) -> Vec<Parameter<'_, T>> {
+        vec![
+            Parameter { name: "weights".to_string(), values: self.weights.iter().flatten().collect() },
+            Parameter { name: "biases".to_string(), values: self.biases.iter().collect() },
+        ]
+    }
+
+    fn parameters_mut(&mut self) -> Vec<ParameterMut<'_, T>> {
+        vec![
+            ParameterMut { name: "weights".to_string(), values: self.weights.iter_mut().flatten().collect() },
+            ParameterMut { name: "biases".to_string(), values: self.biases.iter_mut().collect() },
+        ]
+    }
+}
+
+/// Parametric ReLU: `x` for positive inputs and `alpha * x` otherwise.
+///
+/// The slopes are learned like weights. `alpha` holds one slope per channel (input
+/// position), or a single slope shared by all of them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PReLU<T: Number> {
+    pub alpha: Vec<T>,
+    #[serde(skip)]
+    alpha_grad: Vec<T>,
+}
+
+impl<T: Number> PartialEq for PReLU<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.alpha == other.alpha
+    }
+}
+
+impl<T: Number> PReLU<T> {
+    /// Creates the layer from explicit slopes. Panics if `alpha` is empty; see `try_new`.
+    pub fn new(alpha: Vec<T>) -> Self {
+        assert!(!alpha.is_empty(), "PReLU needs at least one slope");
+        PReLU { alpha, alpha_grad: Vec::new() }
+    }
+
+    /// Fallible version of `new`.
+    pub fn try_new(alpha: Vec<T>) -> Result<Self, LayerError> {
+        if alpha.is_empty() {
+            return Err(LayerError::WrongLength { expected: 1, found: 0 });
+        }
+        Ok(PReLU { alpha, alpha_grad: Vec::new() })
+    }
+
+    /// Whether the layer accepts inputs of length `dim`.
+    pub fn accepts(&self, dim: usize) -> bool {
+        self.alpha.len() == 1 || self.alpha.len() == dim
+    }
+
+    fn slope(&self, channel: usize) -> T {
+        if self.alpha.len() == 1 { self.alpha[0] } else { self.alpha[channel] }
+    }
+
+    pub fn forward(&self, inputs: &[T]) -> Vec<T> {
+        assert!(self.accepts(inputs.len()), "inputs must have one entry per PReLU slope");
+        inputs.iter().enumerate()
+            .map(|(i, &x)| if x.gt(T::zero()) { x } else { self.slope(i) * x })
+            .collect()
+    }
+
+    /// Backward pass for the upstream gradient `grad` at `inputs`.
+    ///
+    /// # Returns
+    /// * `(input_gradient, alpha_gradient)` - `alpha_gradient` is shaped like `alpha`; a shared
+    ///   slope collects the gradient of every channel.
+    pub fn backward(&self, inputs: &[T], grad: &[T]) -> (Vec<T>, Vec<T>) {
+        assert_eq!(inputs.len(), grad.len(), "inputs and grad must have the same length");
+        let mut alpha_grad = vec![T::zero(); self.alpha.len()];
+        let input_grad = inputs.iter().zip(grad.iter()).enumerate()
+            .map(|(i, (&x, &g))| {
+                if x.gt(T::zero()) {
+                    g
+                } else {
+                    let k = if self.alpha.len() == 1 { 0 } else { i };
+                    alpha_grad[k] = alpha_grad[k] + g * x;
+                    g * self.slope(i)
+                }
+            })
+            .collect();
+        (input_grad, alpha_grad)
+    }
+
+    /// Slope gradients accumulated by `Layer::backward` since the last `zero_grad`.
+    pub fn alpha_grad(&self) -> &[T] {
+        &self.alpha_grad
+    }
+}
+
+impl<T: Number> Layer<T> for PReLU<T> {
+    fn forward(&self, inputs: &[T]) -> Vec<T> {
+        PReLU::forward(self, inputs)
+    }
+
+    fn backward(&mut self, inputs: &[T], _outputs: &[T], grad_output: &[T]) -> Vec<T> {
+        let (input_grad, alpha_grad) = PReLU::backward(self, inputs, grad_output);
+        if self.alpha_grad.len() != self.alpha.len() {
+            self.zero_grad();
+        }
+        for (acc, g) in self.alpha_grad.iter_mut().zip(alpha_grad) {
+            *acc = *acc + g;
+        }
+        input_grad
+    }
+
+    fn params_mut(&mut self) -> Vec<(&mut T, T)> {
+        if self.alpha_grad.len() != self.alpha.len() {
+            self.zero_grad();
+        }
+        self.alpha.iter_mut().zip(self.alpha_grad.iter().copied()).collect()
+    }
+
+    fn zero_grad(&mut self) {
+        self.alpha_grad = vec![T::zero(); self.alpha.len()];
+    }
+
+    fn parameters(&self) -> Vec<Parameter<'_, T>> {
+        vec![Parameter { name: "alpha".to_string(), values: self.alpha.iter().collect() }]
+    }
+
+    fn parameters_mut(&mut self) -> Vec<ParameterMut<'_, T>> {
+        vec![ParameterMut { name: "alpha".to_string(), values: self.alpha.iter_mut().collect() }]
+    }
+}
+
+/// Layer normalization: every sample is shifted and scaled to zero mean and unit
+/// variance over its features, then mapped through the learned `gamma * x + beta`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayerNorm<T: Number> {
+    pub gamma: Vec<T>,
+    pub beta: Vec<T>,
+    /// Added to the variance before the square root.
+    pub epsilon: T,
+    #[serde(skip)]
+    grads: (Vec<T>, Vec<T>),
+}
+
+impl<T: Number> PartialEq for LayerNorm<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.gamma == other.gamma && self.beta == other.beta && self.epsilon == other.epsilon
+    }
+}
+
+impl<T: Number + FromPrimitive> LayerNorm<T> {
+    /// Identity-initialized layer (`gamma = 1`, `beta = 0`) over `dim` features, with
+    /// `epsilon = 1e-5`.
+    pub fn new(dim: usize) -> Self {
+        LayerNorm { gamma: vec![T::one(); dim], beta: vec![T::zero(); dim], epsilon: T::to_number(1e-5), grads: (Vec::new(), Vec::new()) }
+    }
+}
+
+impl<T: Number> LayerNorm<T> {
+    pub fn dim(&self) -> usize {
+        self.gamma.len()
+    }
+
+    /// `(gamma, beta)` gradients accumulated by `Layer::backward` since the last `zero_grad`.
+    pub fn grads(&self) -> (&[T], &[T]) {
+        (&self.grads.0, &self.grads.1)
+    }
+}
+
+impl<T: Real + FromPrimitive> LayerNorm<T> {
+    fn ensure_grads(&mut self) {
+        if self.grads.0.len() != self.gamma.len() || self.grads.1.len() != self.beta.len() {
+            self.zero_grad();
+        }
+    }
+
+    /// The normalized inputs `(x - mean) / sqrt(var + epsilon)` and `1 / sqrt(var + epsilon)`.
+    fn normalize(&self, inputs: &[T]) -> (Vec<T>, T) {
+        assert_eq!(inputs.len(), self.dim(), "inputs must have one entry per LayerNorm feature");
+        let n = T::to_number::<T>(inputs.len().max(1) as f64);
+        let mean = inputs.iter().fold(T::zero(), |acc, &x| acc + x) / n;
+        let variance = inputs.iter().fold(T::zero(), |acc, &x| acc + (x - mean) * (x - mean)) / n;
+        let inv_std = T::one() / (variance + self.epsilon).sqrt();
+        (inputs.iter().map(|&x| (x - mean) * inv_std).collect(), inv_std)
+    }
+}
+
+impl<T: Real + FromPrimitive> Layer<T> for LayerNorm<T> {
+    fn forward(&self, inputs: &[T]) -> Vec<T> {
+        let (normalized, _) = self.normalize(inputs);
+        normalized.iter().zip(self.gamma.iter().zip(self.beta.iter())).map(|(&x, (&g, &b))| g * x + b).collect()
+    }
+
+    fn backward(&mut self, inputs: &[T], _outputs: &[T], grad_output: &[T]) -> Vec<T> {
+        self.ensure_grads();
+        let (normalized, inv_std) = self.normalize(inputs);
+        let n = T::to_number::<T>(inputs.len().max(1) as f64);
+        let mut grad_normalized = Vec::with_capacity(inputs.len());
+        for (i, (&g, &x)) in grad_output.iter().zip(normalized.iter()).enumerate() {
+            self.grads.0[i] = self.grads.0[i] + g * x;
+            self.grads.1[i] = self.grads.1[i] + g;
+            grad_normalized.push(g * self.gamma[i]);
+        }
+        // dx = (dxhat - mean(dxhat) - xhat * mean(dxhat * xhat)) / std
+        let mean = grad_normalized.iter().fold(T::zero(), |acc, &d| acc + d) / n;
+        let mean_scaled = grad_normalized.iter().zip(normalized.iter()).fold(T::zero(), |acc, (&d, &x)| acc + d * x) / n;
+        grad_normalized.iter().zip(normalized.iter()).map(|(&d, &x)| (d - mean - x * mean_scaled) * inv_std).collect()
+    }
+
+    fn params_mut(&mut self) -> Vec<(&mut T, T)> {
+        self.ensure_grads();
+        let gamma = self.gamma.iter_mut().zip(self.grads.0.iter().copied());
+        let beta = self.beta.iter_mut().zip(self.grads.1.iter().copied());
+        gamma.chain(beta).collect()
+    }
+
+    fn zero_grad(&mut self) {
+        self.grads = (vec![T::zero(); self.gamma.len()], vec![T::zero(); self.beta.len()]);
+    }
+
+    fn parameters(&self) -> Vec<Parameter<'_, T>> {
+        vec![
+            Parameter { name: "gamma".to_string(), values: self.gamma.iter().collect() },
+            Parameter { name: "beta".to_string(), values: self.beta.iter().collect() },
+        ]
+    }
+
+    fn parameters_mut(&mut self) -> Vec<ParameterMut<'_, T>> {
+        vec![
+            ParameterMut { name: "gamma".to_string(), values: self.gamma.iter_mut().collect() },
+            ParameterMut { name: "beta".to_string(), values: self.beta.iter_mut().collect() },
+        ]
+    }
+}
+
+/// A built layer of a `Sequential` model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ModelLayer<T: Number> {
+    Dense(Dense<T>),
+    Activation(Activation),
+    PReLU(PReLU<T>),
+    Softmax,
+}
+
+impl<T: Real> Layer<T> for ModelLayer<T> {
+    fn forward(&self, inputs: &[T]) -> Vec<T> {
+        match self {
+            ModelLayer::Dense(dense) => dense.forward(inputs),
+            ModelLayer::Activation(activation) => Layer::forward(activation, inputs),
+            ModelLayer::PReLU(prelu) => prelu.forward(inputs),
+            ModelLayer::Softmax => softmax(inputs),
+        }
+    }
+
+    /// Softmax is differentiated through its full Jacobian, so it can be combined with
+    /// any loss; with `Loss::CrossEntropy` this reduces to `p - t`.
+    fn backward(&mut self, inputs: &[T], outputs: &[T], grad_output: &[T]) -> Vec<T> {
+        match self {
+            ModelLayer::Dense(dense) => Layer::backward(dense, inputs, outputs, grad_output),
+            ModelLayer::Activation(activation) => activation.backward(inputs, outputs, grad_output),
+            ModelLayer::PReLU(prelu) => Layer::backward(prelu, inputs, outputs, grad_output),
+            ModelLayer::Softmax => {
+                let dot = grad_output.iter().zip(outputs.iter()).fold(T::zero(), |acc, (&g, &p)| acc + g * p);
+                grad_output.iter().zip(outputs.iter()).map(|(&g, &p)| p * (g - dot)).collect()
+            }
+        }
+    }
+
+    fn params_mut(&mut self) -> Vec<(&mut T, T)> {
+        match self {
+            ModelLayer::Dense(dense) => dense.params_mut(),
+            ModelLayer::PReLU(prelu) => prelu.params_mut(),
+            ModelLayer::Activation(_) | ModelLayer::Softmax => Vec::new(),
+        }
+    }
+
+    fn zero_grad(&mut self) {
+        match self {
+            ModelLayer::Dense(dense) => dense.zero_grad(),
+            ModelLayer::PReLU(prelu) => prelu.zero_grad(),
+            ModelLayer::Activation(_) | ModelLayer::Softmax => {}
+        }
+    }
+
+    fn parameters(&self) -> Vec<Parameter<'_, T>> {
+        match self {
+            ModelLayer::Dense(dense) => dense.parameters(),
+            ModelLayer::PReLU(prelu) => prelu.parameters(),
+            ModelLayer::Activation(_) | ModelLayer::Softmax => Vec::new(),
+        }
+    }
+
+    fn parameters_mut(&mut self) -> Vec<ParameterMut<'_, T>> {
+        match self {
+            ModelLayer::Dense(dense) => dense.parameters_mut(),
+            ModelLayer::PReLU(prelu) => prelu.parameters_mut(),
+            ModelLayer::Activation(_) | ModelLayer::Softmax => Vec::new(),
+        }
+    }
+}
+
+/// Reusable buffers for `Sequential::forward_with`.
+///
+/// The buffers grow to the widest layer on first use and are then reused, so a loop over
+/// many samples allocates only once.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Workspace<T> {
+    values: Vec<T>,
+    scratch: Vec<T>,
+}
+
+impl<T> Workspace<T> {
+    pub fn new() -> Self {
+        Workspace { values: Vec::new(), scratch: Vec::new() }
+    }
+}
+
+/// Identifies a hook added by `Sequential::register_forward_hook`, for removing it again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HookHandle(u64);
+
+type ForwardHook<T> = Arc<Mutex<dyn FnMut(&[T]) + Send>>;
+
+/// Forward hooks of a model, by layer index.
+///
+/// Hooks are runtime-only state: they are not serialized, do not take part in `==`, and
+/// a clone shares the hooks registered so far with the original.
+struct ForwardHooks<T> {
+    next_id: u64,
+    hooks: Vec<(HookHandle, usize, ForwardHook<T>)>,
+}
+
+impl<T> ForwardHooks<T> {
+    /// Calls the hooks of `layer`, in registration order.
+    fn call(&self, layer: usize, outputs: &[T]) {
+        for (_, _, hook) in self.hooks.iter().filter(|(_, l, _)| *l == layer) {
+            let mut hook = hook.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            (*hook)(outputs);
+        }
+    }
+}
+
+impl<T> Default for ForwardHooks<T> {
+    fn default() -> Self {
+        ForwardHooks { next_id: 0, hooks: Vec::new() }
+    }
+}
+
+impl<T> Clone for ForwardHooks<T> {
+    fn clone(&self) -> Self {
+        ForwardHooks { next_id: self.next_id, hooks: self.hooks.clone() }
+    }
+}
+
+impl<T> PartialEq for ForwardHooks<T> {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl<T> fmt::Debug for ForwardHooks<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let layers: Vec<usize> = self.hooks.iter().map(|(_, layer, _)| *layer).collect();
+        f.debug_struct("ForwardHooks").field("layers", &layers).finish()
+    }
+}
+
+/// Stack of layers applied in order. Serializes with serde, e.g. to JSON for the `wasm` bindings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sequential<T: Number> {
+    pub layers: Vec<ModelLayer<T>>,
+    input_dim: usize,
+    #[serde(skip)]
+    hooks: ForwardHooks<T>,
+}
+
+impl<T: Number> Sequential<T> {
+    /// Assembles a model from already built layers, checking their shapes.
+    pub fn from_layers(input_dim: usize, layers: Vec<ModelLayer<T>>) -> Result<Self, BuildError> {
+        let model = Sequential { layers, input_dim, hooks: ForwardHooks::default() };
+        model.check_shapes()?;
+        Ok(model)
+    }
+
+    pub fn input_dim(&self) -> usize {
+        self.input_dim
+    }
+
+    /// Calls `hook` with the output of layer `layer_index` on every inference forward pass
+    /// (`forward`, `forward_with`, `forward_on`, `predict` and `predict_on`, once per row),
+    /// e.g. to extract features, probe hidden representations or debug saturation.
+    ///
+    /// # Notes
+    /// - Hooks of the same layer run in registration order; the model itself is unchanged.
+    /// - Training passes (`backward`, `accumulate_gradients`, `Trainer`) do not call hooks.
+    /// - Clones share the hooks registered before cloning; serialized models have none.
+    /// - Panics if `layer_index >= self.layers.len()`.
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use neuralnet::model::ModelBuilder;
+    ///
+    /// let mut model = ModelBuilder::new(2).dense(3).relu().dense(1).build::<f64>().unwrap();
+    /// let hidden = Arc::new(Mutex::new(Vec::new()));
+    /// let sink = Arc::clone(&hidden);
+    /// let handle = model.register_forward_hook(1, move |outputs: &[f64]| sink.lock().unwrap().push(outputs.to_vec()));
+    /// model.forward(&[0.5, -0.5]);
+    /// assert_eq!(hidden.lock().unwrap()[0].len(), 3);
+    /// assert!(model.remove_forward_hook(handle));
+    /// ```
+    pub fn register_forward_hook<F>(&mut self, layer_index: usize, hook: F) -> HookHandle
+    where
+        F: FnMut(&[T]) + Send + 'static,
+    {
+        assert!(layer_index < self.layers.len(), "layer index {} out of range for {} layers", layer_index, self.layers.len());
+        let handle = HookHandle(self.hooks.next_id);
+        self.hooks.next_id += 1;
+        self.hooks.hooks.push((handle, layer_index, Arc::new(Mutex::new(hook))));
+        handle
+    }
+
+    /// Removes the hook registered under `handle`; returns whether it was present.
+    pub fn remove_forward_hook(&mut self, handle: HookHandle) -> bool {
+        let before = self.hooks.hooks.len();
+        self.hooks.hooks.retain(|(h, _, _)| *h != handle);
+        self.hooks.hooks.len() != before
+    }
+
+    /// Removes every forward hook.
+    pub fn clear_forward_hooks(&mut self) {
+        self.hooks.hooks.clear();
+    }
+
+    /// Checks that every dense layer accepts the output of the previous one, e.g. after
+    /// deserializing a model from an untrusted source.
+    pub fn check_shapes(&self) -> Result<(), BuildError> {
+        let mut current = self.input_dim;
+        for (layer, model_layer) in self.layers.iter().enumerate() {
+            match model_layer {
+                ModelLayer::Dense(dense) => {
+                    let ragged = dense.weights.iter().any(|row| row.len() != current) || dense.weights.len() != dense.biases.len();
+                    if dense.input_dim() != current || ragged {
+                        return Err(BuildError::ShapeMismatch { layer, expected: current, found: dense.input_dim() });
+                    }
+                    current = dense.output_dim();
+                }
+                ModelLayer::PReLU(prelu) if !prelu.accepts(current) => {
+                    return Err(BuildError::ShapeMismatch { layer, expected: current, found: prelu.alpha.len() });
+                }
+                ModelLayer::Softmax if layer + 1 != self.layers.len() => {
+                    return Err(BuildError::SoftmaxNotLast { layer });
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Size of the model output: the units of the last dense layer, or the input size if there is none.
+    pub fn output_dim(&self) -> usize {
+        self.layers.iter().rev()
+            .find_map(|layer| match layer {
+                ModelLayer::Dense(dense) => Some(dense.output_dim()),
+                _ => None,
+            })
+            .unwrap_or(self.input_dim)
+    }
+}
+
+impl<T: Real> Sequential<T> {
+    /// Runs `inputs` through every layer. Panics if `inputs.len() != self.input_dim()`;
+    /// see `try_forward`.
+    pub fn forward(&self, inputs: &[T]) -> Vec<T> {
+        assert_eq!(inputs.len(), self.input_dim, "inputs must match the model input size");
+        self.try_forward(inputs).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `forward`, but returns an error instead of panicking when `inputs` or an
+    /// intermediate value does not fit the next layer (e.g. in a model deserialized
+    /// without `check_shapes`).
+    pub fn try_forward(&self, inputs: &[T]) -> Result<Vec<T>, LayerError> {
+        self.try_forward_with(inputs, &mut Workspace::new()).map(<[T]>::to_vec)
+    }
+
+    /// Like `forward`, computing every layer inside `workspace` and returning a view of
+    /// the outputs. Reusing one workspace across calls avoids allocating per sample.
+    pub fn forward_with<'w>(&self, inputs: &[T], workspace: &'w mut Workspace<T>) -> &'w [T] {
+        assert_eq!(inputs.len(), self.input_dim, "inputs must match the model input size");
+        self.try_forward_with(inputs, workspace).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Fallible version of `forward_with`; see `try_forward`.
+    pub fn try_forward_with<'w>(&self, inputs: &[T], workspace: &'w mut Workspace<T>) -> Result<&'w [T], LayerError> {
+        self.try_forward_on(&Naive, inputs, workspace)
+    }
+
+    /// Like `forward_with`, running the dense and element-wise kernels on `backend`.
+    pub fn forward_on<'w, B: Backend>(&self, backend: &B, inputs: &[T], workspace: &'w mut Workspace<T>) -> &'w [T] {
+        assert_eq!(inputs.len(), self.input_dim, "inputs must match the model input size");
+        self.try_forward_on(backend, inputs, workspace).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Fallible version of `forward_on`; see `try_forward`.
+    pub fn try_forward_on<'w, B: Backend>(&self, backend: &B, inputs: &[T], workspace: &'w mut Workspace<T>) -> Result<&'w [T], LayerError> {
+        if inputs.len() != self.input_dim {
+            return Err(LayerError::WrongLength { expected: self.input_dim, found: inputs.len() });
+        }
+        let Workspace { values, scratch } = workspace;
+        values.clear();
+        values.extend_from_slice(inputs);
+        for (i, layer) in self.layers.iter().enumerate() {
+            match layer {
+                ModelLayer::Dense(dense) => {
+                    if values.len() != dense.input_dim() {
+                        return Err(LayerError::WrongLength { expected: dense.input_dim(), found: values.len() });
+                    }
+                    dense.forward_on(backend, values, scratch);
+                    std::mem::swap(values, scratch);
+                }
+                ModelLayer::Activation(activation) => backend.map_in_place(values, |x| activation.apply(x)),
+                ModelLayer::PReLU(prelu) => {
+                    if !prelu.accepts(values.len()) {
+                        return Err(LayerError::WrongLength { expected: prelu.alpha.len(), found: values.len() });
+                    }
+                    for (i, x) in values.iter_mut().enumerate() {
+                        if !(*x).gt(T::zero()) {
+                            *x = prelu.slope(i) * *x;
+                        }
+                    }
+                }
+                ModelLayer::Softmax => softmax_in_place(values),
+            }
+            self.hooks.call(i, values);
+        }
+        Ok(values)
+    }
+
+    /// Applies `forward` to every row, reusing one `Workspace`.
+    pub fn predict(&self, rows: &[Vec<T>]) -> Vec<Vec<T>> {
+        let mut workspace = Workspace::new();
+        rows.iter().map(|row| self.forward_with(row, &mut workspace).to_vec()).collect()
+    }
+
+    /// Like `predict`, running the whole batch through each layer at once so every dense
+    /// layer is a single `Backend::gemm` call. Worth it for wide layers and large batches.
+    ///
+    /// Panics if a row does not have `input_dim` values.
+    pub fn predict_on<B: Backend>(&self, backend: &B, rows: &[Vec<T>]) -> Vec<Vec<T>> {
+        assert!(rows.iter().all(|row| row.len() == self.input_dim), "inputs must match the model input size");
+        let mut values = rows.to_vec();
+        for (i, layer) in self.layers.iter().enumerate() {
+            match layer {
+                ModelLayer::Dense(dense) => values = dense.forward_batch_on(backend, &values),
+                ModelLayer::Activation(activation) => {
+                    values.iter_mut().for_each(|row| backend.map_in_place(row, |x| activation.apply(x)));
+                }
+                other => values.iter_mut().for_each(|row| *row = other.forward(row)),
+            }
+            values.iter().for_each(|row| self.hooks.call(i, row));
+        }
+        values
+    }
+
+    /// Resets the accumulated gradients of every layer.
+    pub fn zero_grad(&mut self) {
+        self.layers.iter_mut().for_each(|layer| layer.zero_grad());
+    }
+
+    /// Named views of all parameters, e.g. `"0.weights"` for the weights of layer 0.
+    pub fn parameters(&self) -> Vec<Parameter<'_, T>> {
+        self.layers.iter().enumerate()
+            .flat_map(|(i, layer)| layer.parameters().into_iter().map(move |p| p.prefixed(&i.to_string())))
+            .collect()
+    }
+
+    /// Mutable named views of all parameters, named like `parameters`.
+    pub fn parameters_mut(&mut self) -> Vec<ParameterMut<'_, T>> {
+        self.layers.iter_mut().enumerate()
+            .flat_map(|(i, layer)| layer.parameters_mut().into_iter().map(move |p| p.prefixed(&i.to_string())))
+            .collect()
+    }
+
+    /// Named views of the gradients accumulated since the last `zero_grad`, named and
+    /// ordered like `parameters`. A view is empty until its layer has run `backward`
+    /// (e.g. right after deserializing).
+    pub fn gradients(&self) -> Vec<Parameter<'_, T>> {
+        let mut gradients = Vec::new();
+        for (i, layer) in self.layers.iter().enumerate() {
+            let views = match layer {
+                ModelLayer::Dense(dense) => vec![
+                    Parameter { name: "weights".to_string(), values: dense.grads.weights.iter().flatten().collect() },
+                    Parameter { name: "biases".to_string(), values: dense.grads.biases.iter().collect() },
+                ],
+                ModelLayer::PReLU(prelu) => vec![Parameter { name: "alpha".to_string(), values: prelu.alpha_grad().iter().collect() }],
+                ModelLayer::Activation(_) | ModelLayer::Softmax => Vec::new(),
+            };
+            gradients.extend(views.into_iter().map(|p| p.prefixed(&i.to_string())));
+        }
+        gradients
+    }
+
+    /// Total number of scalar parameters.
+    pub fn n_parameters(&self) -> usize {
+        self.parameters().iter().map(|p| p.values.len()).sum()
+    }
+
+    /// Fails unless `other` has the same parameter names and sizes as `self`.
+    fn check_same_parameters(&self, other: &Self) -> Result<(), LayerError> {
+        let ours = self.parameters();
+        let theirs = other.parameters();
+        for (i, a) in ours.iter().enumerate() {
+            match theirs.get(i) {
+                Some(b) if a.name == b.name && a.values.len() == b.values.len() => {}
+                _ => return Err(LayerError::ParameterMismatch { name: a.name.clone() }),
+            }
+        }
+        if let Some(extra) = theirs.get(ours.len()) {
+            return Err(LayerError::ParameterMismatch { name: extra.name.clone() });
+        }
+        Ok(())
+    }
+
+    /// Copies every parameter of `other` into `self`, e.g. to start ensemble members or
+    /// federated clients from the same weights. Both models must have the same architecture.
+    pub fn clone_weights_from(&mut self, other: &Self) -> Result<(), LayerError> {
+        self.check_same_parameters(other)?;
+        for (target, source) in self.parameters_mut().into_iter().zip(other.parameters()) {
+            for (t, &s) in target.values.into_iter().zip(source.values) {
+                *t = s;
+            }
+        }
+        Ok(())
+    }
+
+    /// Plain SGD on the accumulated gradients: `p -= learning_rate * grad` for every parameter.
+    pub fn sgd_step(&mut self, learning_rate: T) {
+        for layer in self.layers.iter_mut() {
+            for (param, grad) in layer.params_mut() {
+                *param = *param - grad * learning_rate;
+            }
+        }
+    }
+}
+
+/// Gradients of one dense layer, shaped like its weights and biases.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DenseGradients<T> {
+    pub weights: Vec<Vec<T>>,
+    pub biases: Vec<T>,
+}
+
+impl<T> Default for DenseGradients<T> {
+    fn default() -> Self {
+        DenseGradients { weights: Vec::new(), biases: Vec::new() }
+    }
+}
+
+/// Gradients of one trainable layer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayerGradients<T> {
+    Dense(DenseGradients<T>),
+    /// Gradient of the PReLU slopes, shaped like `PReLU::alpha`.
+    PReLU(Vec<T>),
+}
+
+#[cfg(feature = "ndarray")]
+impl<T: Real> Sequential<T> {
+    /// `try_forward` on an ndarray vector, which may be a strided view.
+    pub fn try_forward_array(&self, inputs: ArrayView1<'_, T>) -> Result<Array1<T>, LayerError> {
+        self.try_forward(&inputs.to_vec()).map(Array1::from)
+    }
+
+    /// Runs every row of `rows` (shape `(n, input_dim)`) through the model and returns
+    /// the outputs with shape `(n, output_dim)`.
+    ///
+    /// # Errors
+    /// `LayerError::WrongLength` if `rows` does not have `input_dim` columns, or a layer
+    /// does not fit (see `try_forward`).
+    pub fn try_predict_array(&self, rows: ArrayView2<'_, T>) -> Result<Array2<T>, LayerError> {
+        if rows.ncols() != self.input_dim {
+            return Err(LayerError::WrongLength { expected: self.input_dim, found: rows.ncols() });
+        }
+        let mut outputs = Array2::from_elem((rows.nrows(), self.output_dim()), T::zero());
+        let mut workspace = Workspace::new();
+        for (row, mut out) in rows.rows().into_iter().zip(outputs.rows_mut()) {
+            let values = self.try_forward_with(&row.to_vec(), &mut workspace)?;
+            if values.len() != out.len() {
+                return Err(LayerError::WrongLength { expected: out.len(), found: values.len() });
+            }
+            out.iter_mut().zip(values.iter()).for_each(|(o, &v)| *o = v);
+        }
+        Ok(outputs)
+    }
+}
+
+impl<T: Real + FromPrimitive> Sequential<T> {
+    /// Averages the parameters of `models` (federated averaging, weight-averaged ensembles).
+    ///
+    /// # Returns
+    /// * A copy of the first model whose parameters are the element-wise mean over all models,
+    ///   or an error if `models` is empty or the architectures differ.
+    pub fn average_weights(models: &[&Self]) -> Result<Self, LayerError> {
+        Self::weighted_average_weights(models, &vec![1.0; models.len()])
+    }
+
+    /// Like `average_weights`, with one non-negative weight per model, e.g. the number of
+    /// samples each federated client trained on. The weights are normalized to sum to one.
+    ///
+    /// # Errors
+    /// - `LayerError::WrongLength` if there is not one weight per model.
+    /// - `LayerError::InvalidWeight` for a negative or non-finite weight.
+    /// - `LayerError::InvalidWeightTotal` if the weights sum to zero (or overflow).
+    /// - The errors of `average_weights`.
+    pub fn weighted_average_weights(models: &[&Self], weights: &[f64]) -> Result<Self, LayerError> {
+        if models.len() != weights.len() {
+            return Err(LayerError::WrongLength { expected: models.len(), found: weights.len() });
+        }
+        if let Some(index) = weights.iter().position(|w| !(w.is_finite() && *w >= 0.0)) {
+            return Err(LayerError::InvalidWeight { index });
+        }
+        let first = models.first().ok_or(LayerError::WrongLength { expected: 1, found: 0 })?;
+        for model in &models[1..] {
+            first.check_same_parameters(model)?;
+        }
+        let total: f64 = weights.iter().sum();
+        if !(total.is_finite() && total > 0.0) {
+            return Err(LayerError::InvalidWeightTotal);
+        }
+        let mut averaged = (*first).clone();
+        let sums: Vec<Vec<T>> = averaged.parameters().iter()
+            .map(|p| vec![T::zero(); p.values.len()])
+            .collect();
+        let sums = models.iter().zip(weights.iter()).fold(sums, |mut sums, (model, &w)| {
+            let w = T::to_number(w / total);
+            for (sum, param) in sums.iter_mut().zip(model.parameters()) {
+                for (s, &v) in sum.iter_mut().zip(param.values) {
+                    *s = *s + v * w;
+                }
+            }
+            sums
+        });
+        for (param, sum) in averaged.parameters_mut().into_iter().zip(sums) {
+            for (p, s) in param.values.into_iter().zip(sum) {
+                *p = s;
+            }
+        }
+        Ok(averaged)
+    }
+
+    /// Backpropagates the loss of one sample without touching the model.
+    ///
+    /// # Returns
+    /// * `(loss, gradients)` - the loss (reduced over outputs as in `accumulate_gradients`)
+    ///   and one `LayerGradients` per trainable layer (dense and PReLU), in layer order.
+    ///
+    /// # Notes
+    /// - Softmax is differentiated through its full Jacobian, so it can be combined with
+    ///   any loss; with `Loss::CrossEntropy` this reduces to `p - t`.
+    pub fn backward(&self, inputs: &[T], targets: &[T], loss: Loss) -> (T, Vec<LayerGradients<T>>) {
+        let mut model = self.clone();
+        model.zero_grad();
+        let value = model.accumulate_gradients(inputs, targets, loss);
+        let gradients = model.layers.iter().filter_map(|layer| match layer {
+            ModelLayer::Dense(dense) => Some(LayerGradients::Dense(dense.grads().clone())),
+            ModelLayer::PReLU(prelu) => Some(LayerGradients::PReLU(prelu.alpha_grad().to_vec())),
+            _ => None,
+        }).collect();
+        (value, gradients)
+    }
+
+    /// Backpropagates the loss of one sample, adding the parameter gradients to the
+    /// gradients accumulated in every layer (see `zero_grad` and `sgd_step`).
+    /// Returns the loss, reduced over outputs with `Loss::output_reduction`.
+    pub fn accumulate_gradients(&mut self, inputs: &[T], targets: &[T], loss: Loss) -> T {
+        assert_eq!(inputs.len(), self.input_dim, "inputs must match the model input size");
+        backward_pass(&mut self.layers, inputs, targets, loss)
+    }
+
+    /// Like `accumulate_gradients`, for any `LossFn`, such as a custom loss.
+    /// Returns `loss.objective` of the model output.
+    pub fn accumulate_gradients_with(&mut self, inputs: &[T], targets: &[T], loss: &dyn LossFn<T>) -> T {
+        assert_eq!(inputs.len(), self.input_dim, "inputs must match the model input size");
+        backward_pass_with(&mut self.layers, inputs, |outputs| {
+            let grad = loss.objective_derivative(outputs, targets);
+            assert_eq!(grad.len(), outputs.len(), "the loss derivative must have one entry per output");
+            (loss.objective(outputs, targets), grad)
+        }).0
+    }
+
+    /// Applies `gradients` (one per trainable layer, as returned by `backward`) with plain SGD.
+    pub fn apply_gradients(&mut self, gradients: &[LayerGradients<T>], learning_rate: T) {
+        let trainable = self.layers.iter_mut().filter(|layer| matches!(layer, ModelLayer::Dense(_) | ModelLayer::PReLU(_)));
+        for (layer, grads) in trainable.zip(gradients.iter()) {
+            match (layer, grads) {
+                (ModelLayer::Dense(dense), LayerGradients::Dense(grads)) => {
+                    for (row, grad_row) in dense.weights.iter_mut().zip(grads.weights.iter()) {
+                        for (w, &g) in row.iter_mut().zip(grad_row.iter()) {
+                            *w = *w - g * learning_rate;
+                        }
+                    }
+                    for (b, &g) in dense.biases.iter_mut().zip(grads.biases.iter()) {
+                        *b = *b - g * learning_rate;
+                    }
+                }
+                (ModelLayer::PReLU(prelu), LayerGradients::PReLU(grads)) => {
+                    for (a, &g) in prelu.alpha.iter_mut().zip(grads.iter()) {
+                        *a = *a - g * learning_rate;
+                    }
+                }
+                _ => panic!("gradients do not match the model layers"),
+            }
+        }
+    }
+
+    /// One SGD step on a single sample. Returns the loss before the update.
+    pub fn train_step(&mut self, inputs: &[T], targets: &[T], loss: Loss, learning_rate: T) -> T {
+        self.zero_grad();
+        let value = self.accumulate_gradients(inputs, targets, loss);
+        self.sgd_step(learning_rate);
+        value
+    }
+
+    /// One epoch of per-sample SGD over `rows` in the given order. Returns the mean loss.
+    pub fn train_epoch<R: AsRef<[T]>, Y: AsRef<[T]>>(&mut self, rows: &[R], targets: &[Y], loss: Loss, learning_rate: T) -> T {
+        assert_eq!(rows.len(), targets.len(), "rows and targets must have the same length");
+        let mut total = T::zero();
+        for (row, target) in rows.iter().zip(targets.iter()) {
+            total = total + self.train_step(row.as_ref(), target.as_ref(), loss, learning_rate);
+        }
+        total / T::to_number(rows.len().max(1) as f64)
+    }
+}
+
+impl<T: Number + FromPrimitive> Sequential<T> {
+    /// Builds the (untrained) model described by a config file; see `config::ModelConfig::load`.
+    /// The config must declare `input_dim`.
+    pub fn from_config<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        Ok(crate::config::ModelConfig::load(path)?.build()?)
+    }
+}
+
+impl<T: Number + Serialize + DeserializeOwned> Sequential<T> {
+    /// Writes the model as JSON.
+    pub fn save_json<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        serde_json::to_writer(BufWriter::new(File::create(path)?), self)?;
+        Ok(())
+    }
+
+    /// Reads a model written by `save_json` and checks its layer shapes.
+    pub fn load_json<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let model: Sequential<T> = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        model.check_shapes()?;
+        Ok(model)
+    }
+}
+
+/// Rows per forward batch in `Sequential::predict_csv`.
+pub const PREDICT_BATCH_SIZE: usize = 1024;
+
+impl<T: Real + FromPrimitive + ToPrimitive> Sequential<T> {
+    /// Scores every row of a CSV file and writes the rows, followed by their predictions,
+    /// to `output_path`.
+    ///
+    /// # Arguments
+    /// * `input_path` - CSV file with a header row and `input_dim` numeric columns.
+    /// * `output_path` - Destination CSV file; created or truncated.
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - Number of rows scored.
+    /// * `Err(Box<dyn Error>)` - If a file cannot be read or written, or a row is not numeric
+    ///   or has the wrong number of columns (the message names the row).
+    ///
+    /// # Notes
+    /// - The input is streamed `PREDICT_BATCH_SIZE` rows at a time, so the file never needs
+    ///   to fit in memory; see `predict_csv_batched`.
+    /// - The output header is the input header followed by `output_0`, `output_1`, ...
+    /// - Input cells are copied unchanged.
+    pub fn predict_csv<P: AsRef<Path>, Q: AsRef<Path>>(&self, input_path: P, output_path: Q) -> Result<usize, Box<dyn Error>> {
+        self.predict_csv_batched(input_path, output_path, PREDICT_BATCH_SIZE)
+    }
+
+    /// Like `predict_csv`, with `batch_size` rows per batch.
+    pub fn predict_csv_batched<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        input_path: P,
+        output_path: Q,
+        batch_size: usize,
+    ) -> Result<usize, Box<dyn Error>> {
+        assert!(batch_size > 0, "batch_size must be positive");
+        let mut reader = csv::ReaderBuilder::new().has_headers(true).from_path(input_path)?;
+        let mut writer = csv::Writer::from_path(output_path)?;
+
+        let mut header = reader.headers()?.clone();
+        (0..self.output_dim()).for_each(|k| header.push_field(&format!("output_{}", k)));
+        writer.write_record(&header)?;
+
+        let mut records = reader.records();
+        let mut batch: Vec<csv::StringRecord> = Vec::with_capacity(batch_size);
+        let mut scored = 0;
+        loop {
+            // Step 1: Read the next batch
+            batch.clear();
+            for record in records.by_ref().take(batch_size) {
+                batch.push(record?);
+            }
+            if batch.is_empty() {
+                break;
+            }
+            // Step 2: Parse and run the forward passes
+            let rows = batch.iter().enumerate()
+                .map(|(i, record)| parse_row(record, scored + i + 1))
+                .collect::<Result<Vec<Vec<T>>, _>>()?;
+            let predictions = rows.iter().enumerate()
+                .map(|(i, row)| self.try_forward(row).map_err(|e| format!("row {}: {}", scored + i + 1, e)))
+                .collect::<Result<Vec<Vec<T>>, _>>()?;
+            // Step 3: Write each input row followed by its predictions
+            for (record, prediction) in batch.iter_mut().zip(predictions) {
+                prediction.iter().for_each(|v| record.push_field(&v.to_f64().unwrap_or(f64::NAN).to_string()));
+                writer.write_record(&*record)?;
+            }
+            scored += batch.len();
+        }
+        writer.flush()?;
+        Ok(scored)
+    }
+}
+
+/// Parses the cells of data row `row` (1-based) as numbers.
+fn parse_row<T: Number + FromPrimitive>(record: &csv::StringRecord, row: usize) -> Result<Vec<T>, String> {
+    record.iter()
+        .map(|cell| {
+            cell.trim().parse::<f64>().ok()
+                .and_then(T::try_to_number::<T>)
+                .ok_or_else(|| format!("row {}: `{}` is not a number", row, cell))
+        })
+        .collect()
+}
+
+/// Weight initialization of the dense layers built by a `ModelBuilder`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Initializer {
+    /// Chosen per dense layer from the layer that follows it: `HeUniform` before `ReLU`,
+    /// `Softplus` or PReLU, `GlorotUniform` before anything else (including no layer).
+    #[default]
+    Auto,
+    /// Glorot (Xavier) uniform, see `Dense::glorot_uniform`.
+    GlorotUniform,
+    /// He (Kaiming) uniform, see `Dense::he_uniform`.
+    HeUniform,
+}
+
+impl Initializer {
+    /// The scheme used for a dense layer followed by `next`.
+    fn resolve(self, next: Option<&LayerSpec>) -> Initializer {
+        match (self, next) {
+            (Initializer::Auto, Some(LayerSpec::Activation(Activation::ReLU | Activation::Softplus) | LayerSpec::PReLU { .. })) => {
+                Initializer::HeUniform
+            }
+            (Initializer::Auto, _) => Initializer::GlorotUniform,
+            (explicit, _) => explicit,
+        }
+    }
+}
+
+/// Collects layer specs and builds a shape-checked `Sequential` model.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelBuilder {
+    input_dim: Option<usize>,
+    specs: Vec<LayerSpec>,
+    seed: u64,
+    init: Initializer,
+}
+
+impl ModelBuilder {
+    /// Starts a model taking `input_dim` features.
+    pub fn new(input_dim: usize) -> Self {
+        ModelBuilder { input_dim: Some(input_dim), ..Default::default() }
+    }
+
+    /// Seed for weight initialization.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Weight initialization of every dense layer; `Initializer::Auto` by default.
+    pub fn init(mut self, init: Initializer) -> Self {
+        self.init = init;
+        self
+    }
+
+    /// Appends a layer spec.
+    pub fn layer(mut self, spec: LayerSpec) -> Self {
+        self.specs.push(spec);
+        self
+    }
+
+    /// Appends a dense layer whose input size is inferred from the previous layer.
+    pub fn dense(self, units: usize) -> Self {
+        self.layer(LayerSpec::Dense { inputs: None, units })
+    }
+
+    /// Appends a dense layer declaring its input size, which `build` checks against the previous layer.
+    pub fn dense_from(self, inputs: usize, units: usize) -> Self {
+        self.layer(LayerSpec::Dense { inputs: Some(inputs), units })
+    }
+
+    pub fn activation(self, activation: Activation) -> Self {
+        self.layer(LayerSpec::Activation(activation))
+    }
+
+    pub fn relu(self) -> Self {
+        self.activation(Activation::ReLU)
+    }
+
+    pub fn sigmoid(self) -> Self {
+        self.activation(Activation::Sigmoid)
+    }
+
+    pub fn tanh(self) -> Self {
+        self.activation(Activation::Tanh)
+    }
+
+    /// Appends a PReLU layer with one learnable slope per input, initialized to `alpha`.
+    pub fn prelu(self, alpha: f64) -> Self {
+        self.layer(LayerSpec::PReLU { alpha })
+    }
+
+    pub fn softmax(self) -> Self {
+        self.layer(LayerSpec::Softmax)
+    }
+
+    /// Checks the layer specs and returns the output size of every layer.
+    pub fn validate(&self) -> Result<Vec<usize>, BuildError> {
+        if self.specs.is_empty() {
+            return Err(BuildError::EmptyModel);
+        }
+        let mut current = self.input_dim;
+        let mut shapes = Vec::with_capacity(self.specs.len());
+        for (layer, spec) in self.specs.iter().enumerate() {
+            match *spec {
+                LayerSpec::Dense { inputs, units } => {
+                    if units == 0 {
+                        return Err(BuildError::ZeroUnits { layer });
+                    }
+                    match (current, inputs) {
+                        (Some(expected), Some(found)) if expected != found => {
+                            return Err(BuildError::ShapeMismatch { layer, expected, found });
+                        }
+                        (None, None) => return Err(BuildError::MissingInputDim),
+                        _ => {}
+                    }
+                    current = Some(units);
+                }
+                LayerSpec::Softmax if layer + 1 != self.specs.len() => {
+                    return Err(BuildError::SoftmaxNotLast { layer });
+                }
+                _ => {}
+            }
+            shapes.push(current.ok_or(BuildError::MissingInputDim)?);
+        }
+        Ok(shapes)
+    }
+
+    /// Validates the specs and allocates the layers.
+    ///
+    /// # Notes
+    /// - Dense weights are drawn uniformly from `[-limit, limit]`, with the He limit
+    ///   `sqrt(6 / inputs)` or the Glorot limit `sqrt(6 / (inputs + units))` as selected
+    ///   by the builder's `Initializer`; biases start at zero.
+    /// - For integer `T` the initial weights round to zero.
+    pub fn build<T: Number + FromPrimitive>(&self) -> Result<Sequential<T>, BuildError> {
+        self.validate()?;
+        let input_dim = match (self.input_dim, self.specs.first()) {
+            (Some(dim), _) => dim,
+            (None, Some(LayerSpec::Dense { inputs: Some(dim), .. })) => *dim,
+            _ => return Err(BuildError::MissingInputDim),
+        };
+
+        let mut rng = Rng::new(self.seed);
+        let mut current = input_dim;
+        let mut layers = Vec::with_capacity(self.specs.len());
+        for (i, spec) in self.specs.iter().enumerate() {
+            layers.push(match *spec {
+                LayerSpec::Dense { units, .. } => {
+                    let dense = match self.init.resolve(self.specs.get(i + 1)) {
+                        Initializer::HeUniform => Dense::he_uniform(current, units, &mut rng),
+                        _ => Dense::glorot_uniform(current, units, &mut rng),
+                    };
+                    current = units;
+                    ModelLayer::Dense(dense)
+                }
+                LayerSpec::Activation(activation) => ModelLayer::Activation(activation),
+                LayerSpec::PReLU { alpha } => {
+                    let alpha = T::try_to_number::<T>(alpha).ok_or(BuildError::Unrepresentable { layer: i, value: alpha })?;
+                    let prelu = PReLU::try_new(vec![alpha; current]).map_err(|error| BuildError::InvalidLayer { layer: i, error })?;
+                    ModelLayer::PReLU(prelu)
+                }
+                LayerSpec::Softmax => ModelLayer::Softmax,
+            });
+        }
+        Ok(Sequential { layers, input_dim, hooks: ForwardHooks::default() })
+    }
+}
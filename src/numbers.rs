@@ -1,4 +1,4 @@
-use num_traits::FromPrimitive;
+use num_traits::{Float, FromPrimitive};
 
 /// The `Number` trait provides a unified interface for numeric types (integers and floats).
 ///
@@ -7,23 +7,28 @@ use num_traits::FromPrimitive;
 ///
 /// # Required Methods
 /// - `zero()` and `one()`: Return the additive and multiplicative identity for the type.
-/// - `exp(self)`: Exponential function. Only implemented for floating-point types; panics for integers.
-/// - `tanh(self)`: Hyperbolic tangent function. Only implemented for floating-point types; panics for integers.
+/// - `abs`, `powi`, `max`, `min`: Supported for all types (`powi` panics for integers with a negative exponent).
+/// - Constants `EPSILON`, `INFINITY`, `NEG_INFINITY`: machine epsilon and infinities for floats;
+///   `0`, `MAX` and `MIN` for integers.
 /// - Logical comparisons: `and`, `or`, `not`, `eq`, `ne`, `gt`, `lt`, `ge`, `le`
 ///
+/// Transcendental functions (`exp`, `tanh`, `ln`, ...) live in the [`Real`] subtrait,
+/// so using them with an integer type is a compile error rather than a runtime panic.
+///
 /// # Implementations
-/// - `f32`, `f64`: Fully supported, including `exp`, `tanh`, and logical comparisons.
-/// - `i32`, `i64`, `usize`: Supported for arithmetic, identity, and logical comparisons, but `exp` and `tanh` will panic if called.
+/// - `f32`, `f64`: Fully supported; also implement `Real`.
+/// - `i32`, `i64`: Supported for arithmetic, identity, and logical comparisons.
 ///
 pub trait Number:
     Copy
+    + 'static
     + Default
-    + std::fmt::Debug
-    + std::ops::Add<Output = Self>
-    + std::ops::Sub<Output = Self>
-    + std::ops::Mul<Output = Self>
-    + std::ops::Div<Output = Self>
-    + std::ops::Neg<Output = Self>
+    + core::fmt::Debug
+    + core::ops::Add<Output = Self>
+    + core::ops::Sub<Output = Self>
+    + core::ops::Mul<Output = Self>
+    + core::ops::Div<Output = Self>
+    + core::ops::Neg<Output = Self>
     + PartialOrd
     + PartialEq
 {
@@ -31,14 +36,21 @@ pub trait Number:
     fn zero() -> Self;
     /// Returns the multiplicative identity (one) for the type.
     fn one() -> Self;
-    /// Returns the exponential of the value.
-    /// Only implemented for floating-point types; panics for integers.
-    fn exp(self) -> Self;
-    /// Returns the hyperbolic tangent of the value.
-    /// Only implemented for floating-point types; panics for integers.
-    fn tanh(self) -> Self;
+    /// Difference between `1` and the next representable value; zero for integers.
+    const EPSILON: Self;
+    /// Positive infinity; the largest value for integers.
+    const INFINITY: Self;
+    /// Negative infinity; the smallest value for integers.
+    const NEG_INFINITY: Self;
 
-    fn ln(self) -> Self;
+    /// Absolute value.
+    fn abs(self) -> Self;
+    /// Raises to an integer power. Panics for integers with a negative exponent.
+    fn powi(self, n: i32) -> Self;
+    /// Larger of two values. For floats a NaN operand is ignored, as in `f64::max`.
+    fn max(self, rhs: Self) -> Self;
+    /// Smaller of two values. For floats a NaN operand is ignored, as in `f64::min`.
+    fn min(self, rhs: Self) -> Self;
 
     /// Logical AND: returns one if both are non-zero, else zero.
     fn and(self, rhs: Self) -> Self;
@@ -59,16 +71,50 @@ pub trait Number:
     fn ge(self, rhs: Self) -> bool;
     /// Less than or equal.
     fn le(self, rhs: Self) -> bool;
-    fn to_number<T: Number + FromPrimitive>(x: f64) -> T;
+    /// Converts an `f64` constant to `T`. Panics if `x` is not representable in `T`
+    /// (e.g. NaN for integers); fallible code uses `try_to_number` instead.
+    fn to_number<T: Number + FromPrimitive>(x: f64) -> T {
+        Self::try_to_number::<T>(x).unwrap_or_else(|| panic!("{} is not representable in the target number type", x))
+    }
+    /// Converts an `f64` constant to `T`, returning `None` if it is not representable.
+    fn try_to_number<T: Number + FromPrimitive>(x: f64) -> Option<T> {
+        T::from_f64(x)
+    }
+
+}
+
+/// Floating-point numbers: `Number` plus the transcendental functions needed by
+/// activations and losses. Implemented for `f32` and `f64`.
+pub trait Real: Number {
+    /// Returns the exponential of the value.
+    fn exp(self) -> Self;
+    /// Returns the hyperbolic tangent of the value.
+    fn tanh(self) -> Self;
+    /// Natural logarithm.
+    fn ln(self) -> Self;
+    /// Square root.
+    fn sqrt(self) -> Self;
+    /// Raises to a power of the same type.
+    fn powf(self, n: Self) -> Self;
+    /// Base-10 logarithm.
+    fn log10(self) -> Self;
+    /// `ln(1 + x)`, accurate for small `x`.
+    fn ln_1p(self) -> Self;
 }
 
 
 impl Number for f32 {
     fn zero() -> Self { 0.0 }
     fn one() -> Self { 1.0 }
-    fn exp(self) -> Self { self.exp() }
-    fn tanh(self) -> Self { self.tanh() }
-    fn ln(self) -> Self { self.ln() }
+
+    const EPSILON: Self = f32::EPSILON;
+    const INFINITY: Self = f32::INFINITY;
+    const NEG_INFINITY: Self = f32::NEG_INFINITY;
+
+    fn abs(self) -> Self { Float::abs(self) }
+    fn powi(self, n: i32) -> Self { Float::powi(self, n) }
+    fn max(self, rhs: Self) -> Self { Float::max(self, rhs) }
+    fn min(self, rhs: Self) -> Self { Float::min(self, rhs) }
 
     fn and(self, rhs: Self) -> Self {
         if self != 0.0 && rhs != 0.0 { Self::one() } else { Self::zero() }
@@ -86,17 +132,20 @@ impl Number for f32 {
     fn lt(self, rhs: Self) -> bool { self < rhs }
     fn ge(self, rhs: Self) -> bool { self >= rhs }
     fn le(self, rhs: Self) -> bool { self <= rhs }
-    fn to_number<T: Number + FromPrimitive>(x: f64) -> T {
-        T::from_f64(x).unwrap()
-    }
 }
 
 impl Number for f64 {
     fn zero() -> Self { 0.0 }
     fn one() -> Self { 1.0 }
-    fn exp(self) -> Self { self.exp() }
-    fn tanh(self) -> Self { self.tanh() }
-    fn ln(self) -> Self { self.ln() }
+
+    const EPSILON: Self = f64::EPSILON;
+    const INFINITY: Self = f64::INFINITY;
+    const NEG_INFINITY: Self = f64::NEG_INFINITY;
+
+    fn abs(self) -> Self { Float::abs(self) }
+    fn powi(self, n: i32) -> Self { Float::powi(self, n) }
+    fn max(self, rhs: Self) -> Self { Float::max(self, rhs) }
+    fn min(self, rhs: Self) -> Self { Float::min(self, rhs) }
 
     fn and(self, rhs: Self) -> Self {
         if self != 0.0 && rhs != 0.0 { Self::one() } else { Self::zero() }
@@ -114,17 +163,43 @@ impl Number for f64 {
     fn lt(self, rhs: Self) -> bool { self < rhs }
     fn ge(self, rhs: Self) -> bool { self >= rhs }
     fn le(self, rhs: Self) -> bool { self <= rhs }
-    fn to_number<T: Number + FromPrimitive>(x: f64) -> T {
-        T::from_f64(x).unwrap()
-    }
+}
+
+impl Real for f32 {
+    fn exp(self) -> Self { Float::exp(self) }
+    fn tanh(self) -> Self { Float::tanh(self) }
+    fn ln(self) -> Self { Float::ln(self) }
+    fn sqrt(self) -> Self { Float::sqrt(self) }
+    fn powf(self, n: Self) -> Self { Float::powf(self, n) }
+    fn log10(self) -> Self { Float::log10(self) }
+    fn ln_1p(self) -> Self { Float::ln_1p(self) }
+}
+
+impl Real for f64 {
+    fn exp(self) -> Self { Float::exp(self) }
+    fn tanh(self) -> Self { Float::tanh(self) }
+    fn ln(self) -> Self { Float::ln(self) }
+    fn sqrt(self) -> Self { Float::sqrt(self) }
+    fn powf(self, n: Self) -> Self { Float::powf(self, n) }
+    fn log10(self) -> Self { Float::log10(self) }
+    fn ln_1p(self) -> Self { Float::ln_1p(self) }
 }
 
 impl Number for i32 {
     fn zero() -> Self { 0 }
     fn one() -> Self { 1 }
-    fn exp(self) -> Self { panic!("exp not supported for i32") }
-    fn tanh(self) -> Self { panic!("tanh not supported for i32") }
-    fn ln(self) -> Self { panic!("ln not supported for i32") }
+
+    const EPSILON: Self = 0;
+    const INFINITY: Self = i32::MAX;
+    const NEG_INFINITY: Self = i32::MIN;
+
+    fn abs(self) -> Self { self.abs() }
+    fn powi(self, n: i32) -> Self {
+        assert!(n >= 0, "negative exponent not supported for i32");
+        self.pow(n as u32)
+    }
+    fn max(self, rhs: Self) -> Self { Ord::max(self, rhs) }
+    fn min(self, rhs: Self) -> Self { Ord::min(self, rhs) }
 
     fn and(self, rhs: Self) -> Self {
         if self != 0 && rhs != 0 { Self::one() } else { Self::zero() }
@@ -142,17 +217,23 @@ impl Number for i32 {
     fn lt(self, rhs: Self) -> bool { self < rhs }
     fn ge(self, rhs: Self) -> bool { self >= rhs }
     fn le(self, rhs: Self) -> bool { self <= rhs }
-    fn to_number<T: Number + FromPrimitive>(x: f64) -> T {
-        T::from_f64(x).unwrap()
-    }
 }
 
 impl Number for i64 {
     fn zero() -> Self { 0 }
     fn one() -> Self { 1 }
-    fn exp(self) -> Self { panic!("exp not supported for i64") }
-    fn tanh(self) -> Self { panic!("tanh not supported for i64") }
-    fn ln(self) -> Self { panic!("ln not supported for i64") }
+
+    const EPSILON: Self = 0;
+    const INFINITY: Self = i64::MAX;
+    const NEG_INFINITY: Self = i64::MIN;
+
+    fn abs(self) -> Self { self.abs() }
+    fn powi(self, n: i32) -> Self {
+        assert!(n >= 0, "negative exponent not supported for i64");
+        self.pow(n as u32)
+    }
+    fn max(self, rhs: Self) -> Self { Ord::max(self, rhs) }
+    fn min(self, rhs: Self) -> Self { Ord::min(self, rhs) }
 
     fn and(self, rhs: Self) -> Self {
         if self != 0 && rhs != 0 { Self::one() } else { Self::zero() }
@@ -170,7 +251,62 @@ impl Number for i64 {
     fn lt(self, rhs: Self) -> bool { self < rhs }
     fn ge(self, rhs: Self) -> bool { self >= rhs }
     fn le(self, rhs: Self) -> bool { self <= rhs }
-    fn to_number<T: Number + FromPrimitive>(x: f64) -> T {
-        T::from_f64(x).unwrap()
+}
+
+/// `Number` and `Real` for the half-precision types of the `half` crate.
+///
+/// Values are stored in 16 bits; math functions are evaluated in `f32`
+/// and rounded back, which is exact to within the half-precision rounding error.
+#[cfg(feature = "half")]
+mod half_impls {
+    use super::*;
+    use half::{bf16, f16};
+
+    macro_rules! impl_half {
+        ($ty:ty) => {
+            impl Number for $ty {
+                fn zero() -> Self { <$ty>::ZERO }
+                fn one() -> Self { <$ty>::ONE }
+
+                const EPSILON: Self = <$ty>::EPSILON;
+                const INFINITY: Self = <$ty>::INFINITY;
+                const NEG_INFINITY: Self = <$ty>::NEG_INFINITY;
+
+                fn abs(self) -> Self { <$ty>::from_f32(Float::abs(self.to_f32())) }
+                fn powi(self, n: i32) -> Self { <$ty>::from_f32(Float::powi(self.to_f32(), n)) }
+                fn max(self, rhs: Self) -> Self { <$ty>::max(self, rhs) }
+                fn min(self, rhs: Self) -> Self { <$ty>::min(self, rhs) }
+
+                fn and(self, rhs: Self) -> Self {
+                    if self != Self::zero() && rhs != Self::zero() { Self::one() } else { Self::zero() }
+                }
+                fn or(self, rhs: Self) -> Self {
+                    if self != Self::zero() || rhs != Self::zero() { Self::one() } else { Self::zero() }
+                }
+                fn not(self) -> Self {
+                    if self == Self::zero() { Self::one() } else { Self::zero() }
+                }
+
+                fn eq(self, rhs: Self) -> bool { self == rhs }
+                fn ne(self, rhs: Self) -> bool { self != rhs }
+                fn gt(self, rhs: Self) -> bool { self > rhs }
+                fn lt(self, rhs: Self) -> bool { self < rhs }
+                fn ge(self, rhs: Self) -> bool { self >= rhs }
+                fn le(self, rhs: Self) -> bool { self <= rhs }
+            }
+
+            impl Real for $ty {
+                fn exp(self) -> Self { <$ty>::from_f32(Float::exp(self.to_f32())) }
+                fn tanh(self) -> Self { <$ty>::from_f32(Float::tanh(self.to_f32())) }
+                fn ln(self) -> Self { <$ty>::from_f32(Float::ln(self.to_f32())) }
+                fn sqrt(self) -> Self { <$ty>::from_f32(Float::sqrt(self.to_f32())) }
+                fn powf(self, n: Self) -> Self { <$ty>::from_f32(Float::powf(self.to_f32(), n.to_f32())) }
+                fn log10(self) -> Self { <$ty>::from_f32(Float::log10(self.to_f32())) }
+                fn ln_1p(self) -> Self { <$ty>::from_f32(Float::ln_1p(self.to_f32())) }
+            }
+        };
     }
+
+    impl_half!(f16);
+    impl_half!(bf16);
 }
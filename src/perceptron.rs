@@ -0,0 +1,84 @@
+//! Classic (Rosenblatt) perceptrons.
+//!
+//! Training only adds, subtracts and multiplies, and prediction only compares against
+//! zero, so a perceptron over `i32` or `i64` trains exactly and deterministically. Like
+//! the rest of the inference core this module is available without `std`.
+
+use crate::numbers::Number;
+use crate::layers::Layer1D;
+use crate::activation_fn::StepActivation;
+use crate::forward_propagation::dense_linear;
+
+/// A single layer of `OUT` threshold units over `IN` inputs.
+pub struct Perceptron<T: Number, const OUT: usize, const IN: usize> {
+    pub layer: Layer1D<T, OUT, IN>,
+    pub activation: StepActivation,
+    /// Step size of the update rule; `1` is the classic choice for integers.
+    pub learning_rate: T,
+}
+
+impl<T: Number, const OUT: usize, const IN: usize> Perceptron<T, OUT, IN> {
+    /// Creates a perceptron with all weights and biases at zero and a learning rate of one.
+    pub fn new(activation: StepActivation) -> Self {
+        Self::from_layer(Layer1D::new([[T::zero(); IN]; OUT], [T::zero(); OUT]), activation)
+    }
+
+    /// Wraps existing weights.
+    pub fn from_layer(layer: Layer1D<T, OUT, IN>, activation: StepActivation) -> Self {
+        Perceptron { layer, activation, learning_rate: T::one() }
+    }
+
+    /// Weighted sums followed by the step activation.
+    pub fn predict(&self, inputs: &[T; IN]) -> [T; OUT] {
+        self.activation.forward(&dense_linear(inputs, &self.layer))
+    }
+
+    /// Applies the **perceptron rule** to one sample.
+    ///
+    /// # Arguments
+    /// * `inputs` - Sample features.
+    /// * `targets` - Desired outputs, using the values of `activation` (`0`/`1` or `-1`/`1`).
+    ///
+    /// # Returns
+    /// * Number of outputs that were wrong before the update.
+    ///
+    /// # Steps
+    /// 1. Predict `y = step(W x + b)`.
+    /// 2. For each output `i` with `y[i] != t[i]`, set `e = learning_rate * (t[i] - y[i])`.
+    /// 3. Update `W[i][j] += e * x[j]` and `b[i] += e`.
+    ///
+    /// # Notes
+    /// - Integer weights grow without bound on data that is not linearly separable and
+    ///   eventually overflow; cap the number of epochs (see `fit`).
+    pub fn update(&mut self, inputs: &[T; IN], targets: &[T; OUT]) -> usize {
+        let outputs = self.predict(inputs);
+        let mut errors = 0;
+        for i in 0..OUT {
+            if outputs[i].ne(targets[i]) {
+                errors += 1;
+                let step = self.learning_rate * (targets[i] - outputs[i]);
+                self.layer.biases[i] = self.layer.biases[i] + step;
+                let row = &mut self.layer.weights_flat_mut()[i * IN..(i + 1) * IN];
+                for (w, &x) in row.iter_mut().zip(inputs.iter()) {
+                    *w = *w + step * x;
+                }
+            }
+        }
+        errors
+    }
+
+    /// One pass of `update` over the samples, in order. Returns the total number of errors.
+    pub fn train_epoch(&mut self, samples: &[[T; IN]], targets: &[[T; OUT]]) -> usize {
+        assert_eq!(samples.len(), targets.len(), "samples and targets must have the same length");
+        samples.iter().zip(targets.iter()).map(|(x, t)| self.update(x, t)).sum()
+    }
+
+    /// Trains until an epoch makes no errors, for at most `max_epochs` epochs.
+    ///
+    /// # Returns
+    /// * `Some(epochs)` - Number of epochs run, the last one error-free.
+    /// * `None` - The data was not separated within `max_epochs`.
+    pub fn fit(&mut self, samples: &[[T; IN]], targets: &[[T; OUT]], max_epochs: usize) -> Option<usize> {
+        (1..=max_epochs).find(|_| self.train_epoch(samples, targets) == 0)
+    }
+}
@@ -0,0 +1,536 @@
+//! Feature preprocessing transforms for tabular data.
+//!
+//! Datasets are represented as `Vec<Vec<T>>`: one inner vector per row (sample),
+//! one entry per column (feature). Every transform implements [`Transformer`], so
+//! it can be fitted on training data and then applied to any later rows.
+
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use num_traits::{FromPrimitive, ToPrimitive};
+use serde::{Deserialize, Serialize};
+use crate::numbers::Number;
+
+/// Common interface of preprocessing transforms.
+pub trait Transformer<T: Number> {
+    /// Learns the transform's parameters from `rows`. Stateless transforms do nothing.
+    fn fit(&mut self, rows: &[Vec<T>]);
+
+    /// Transforms a single row.
+    fn transform_row(&self, row: &[T]) -> Vec<T>;
+
+    /// Transforms every row.
+    fn transform(&self, rows: &[Vec<T>]) -> Vec<Vec<T>> {
+        rows.iter().map(|row| self.transform_row(row)).collect()
+    }
+
+    /// Fits on `rows` and returns them transformed.
+    fn fit_transform(&mut self, rows: &[Vec<T>]) -> Vec<Vec<T>> {
+        self.fit(rows);
+        self.transform(rows)
+    }
+}
+
+/// Expands input columns into all polynomial and interaction terms up to `degree`.
+///
+/// For inputs `[a, b]` and `degree = 2` the output is `[1, a, b, a*a, a*b, b*b]`
+/// (the leading `1` only when `include_bias` is set). Terms are ordered by degree,
+/// then lexicographically by column index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolynomialFeatures {
+    pub degree: usize,
+    pub include_bias: bool,
+}
+
+impl PolynomialFeatures {
+    pub fn new(degree: usize, include_bias: bool) -> Self {
+        PolynomialFeatures { degree, include_bias }
+    }
+
+    /// Lists the column indices multiplied together for each output term.
+    /// The bias term, if enabled, is the empty combination.
+    pub fn combinations(&self, n_inputs: usize) -> Vec<Vec<usize>> {
+        let mut terms = Vec::new();
+        if self.include_bias {
+            terms.push(Vec::new());
+        }
+        // Grow degree-d combinations from degree-(d-1) ones, keeping indices non-decreasing
+        let mut previous: Vec<Vec<usize>> = vec![Vec::new()];
+        for _ in 0..self.degree {
+            let mut current = Vec::new();
+            for combo in &previous {
+                let start = combo.last().copied().unwrap_or(0);
+                for j in start..n_inputs {
+                    let mut next = combo.clone();
+                    next.push(j);
+                    current.push(next);
+                }
+            }
+            terms.extend(current.iter().cloned());
+            previous = current;
+        }
+        terms
+    }
+
+    /// Number of output columns produced for `n_inputs` input columns.
+    pub fn n_output_features(&self, n_inputs: usize) -> usize {
+        self.combinations(n_inputs).len()
+    }
+}
+
+impl<T: Number> Transformer<T> for PolynomialFeatures {
+    fn fit(&mut self, _rows: &[Vec<T>]) {}
+
+    fn transform_row(&self, row: &[T]) -> Vec<T> {
+        self.combinations(row.len())
+            .iter()
+            .map(|combo| combo.iter().fold(T::one(), |acc, &j| acc * row[j]))
+            .collect()
+    }
+}
+
+/// Returns the `q`-quantiles of column `j` of `rows`, interpolating linearly between
+/// the two nearest ranks. Panics if `rows` is empty.
+fn column_quantiles<T: Number + ToPrimitive>(rows: &[Vec<T>], j: usize, qs: &[f64]) -> Vec<f64> {
+    assert!(!rows.is_empty(), "cannot fit on an empty dataset");
+    let mut column: Vec<f64> = rows.iter().map(|row| row[j].to_f64().unwrap()).collect();
+    column.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    qs.iter().map(|&q| {
+        let position = q.clamp(0.0, 1.0) * (column.len() - 1) as f64;
+        let lower = position.floor() as usize;
+        let upper = position.ceil() as usize;
+        column[lower] + (column[upper] - column[lower]) * (position - lower as f64)
+    }).collect()
+}
+
+/// Scales each column by its median and interquartile range, `(x - median) / IQR`.
+///
+/// Unlike mean/standard-deviation scaling, the statistics ignore the tails, so a few
+/// extreme values do not squash the bulk of the data. Columns with zero spread are
+/// only centered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RobustScaler {
+    /// Quantiles spanning the scale, `(0.25, 0.75)` for the IQR.
+    pub quantile_range: (f64, f64),
+    pub with_centering: bool,
+    pub with_scaling: bool,
+    /// Fitted per-column medians.
+    pub center: Vec<f64>,
+    /// Fitted per-column spreads.
+    pub scale: Vec<f64>,
+}
+
+impl Default for RobustScaler {
+    fn default() -> Self {
+        RobustScaler::new()
+    }
+}
+
+impl RobustScaler {
+    pub fn new() -> Self {
+        RobustScaler { quantile_range: (0.25, 0.75), with_centering: true, with_scaling: true, center: Vec::new(), scale: Vec::new() }
+    }
+}
+
+impl<T: Number + FromPrimitive + ToPrimitive> Transformer<T> for RobustScaler {
+    fn fit(&mut self, rows: &[Vec<T>]) {
+        let n_columns = rows.first().map_or(0, |row| row.len());
+        let (low, high) = self.quantile_range;
+        self.center.clear();
+        self.scale.clear();
+        for j in 0..n_columns {
+            let q = column_quantiles(rows, j, &[low, 0.5, high]);
+            self.center.push(if self.with_centering { q[1] } else { 0.0 });
+            let spread = q[2] - q[0];
+            self.scale.push(if self.with_scaling && spread > 0.0 { spread } else { 1.0 });
+        }
+    }
+
+    fn transform_row(&self, row: &[T]) -> Vec<T> {
+        assert_eq!(row.len(), self.center.len(), "row length must match the fitted number of columns");
+        row.iter().enumerate()
+            .map(|(j, x)| T::to_number((x.to_f64().unwrap() - self.center[j]) / self.scale[j]))
+            .collect()
+    }
+}
+
+/// Clips each column to quantiles learned at fit time, e.g. the 1st and 99th percentiles.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Winsorizer {
+    pub lower_quantile: f64,
+    pub upper_quantile: f64,
+    /// Fitted per-column lower bounds.
+    pub lower: Vec<f64>,
+    /// Fitted per-column upper bounds.
+    pub upper: Vec<f64>,
+}
+
+impl Winsorizer {
+    /// Panics unless `0 <= lower_quantile <= upper_quantile <= 1`.
+    pub fn new(lower_quantile: f64, upper_quantile: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&lower_quantile) && (0.0..=1.0).contains(&upper_quantile) && lower_quantile <= upper_quantile,
+            "quantiles must satisfy 0 <= lower <= upper <= 1"
+        );
+        Winsorizer { lower_quantile, upper_quantile, lower: Vec::new(), upper: Vec::new() }
+    }
+}
+
+impl<T: Number + FromPrimitive + ToPrimitive> Transformer<T> for Winsorizer {
+    fn fit(&mut self, rows: &[Vec<T>]) {
+        let n_columns = rows.first().map_or(0, |row| row.len());
+        self.lower.clear();
+        self.upper.clear();
+        for j in 0..n_columns {
+            let q = column_quantiles(rows, j, &[self.lower_quantile, self.upper_quantile]);
+            self.lower.push(q[0]);
+            self.upper.push(q[1]);
+        }
+    }
+
+    fn transform_row(&self, row: &[T]) -> Vec<T> {
+        assert_eq!(row.len(), self.lower.len(), "row length must match the fitted number of columns");
+        row.iter().enumerate()
+            .map(|(j, &x)| {
+                let v = x.to_f64().unwrap();
+                if v < self.lower[j] {
+                    T::to_number(self.lower[j])
+                } else if v > self.upper[j] {
+                    T::to_number(self.upper[j])
+                } else {
+                    x
+                }
+            })
+            .collect()
+    }
+}
+
+/// Encodes periodic columns (hour of day, month, wind direction) as `sin`/`cos` pairs.
+///
+/// The value `x` of a column with period `p` becomes `[sin(2 pi x / p), cos(2 pi x / p)]`,
+/// so the ends of the cycle (hour 23 and hour 0) end up next to each other. Each
+/// configured column is replaced in place by its pair; other columns pass through.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CyclicEncoder {
+    /// `(column index, period)` of every column to encode.
+    pub columns: Vec<(usize, f64)>,
+}
+
+impl CyclicEncoder {
+    pub fn new() -> Self {
+        CyclicEncoder { columns: Vec::new() }
+    }
+
+    /// Adds column `column` with the given `period`, e.g. `24.0` for hours or `360.0` for degrees.
+    /// Panics if `period` is not positive.
+    pub fn column(mut self, column: usize, period: f64) -> Self {
+        assert!(period > 0.0, "period must be positive");
+        self.columns.push((column, period));
+        self
+    }
+
+    /// Number of output columns produced for `n_inputs` input columns.
+    pub fn n_output_features(&self, n_inputs: usize) -> usize {
+        n_inputs + self.columns.iter().filter(|(c, _)| *c < n_inputs).count()
+    }
+
+    fn period_of(&self, column: usize) -> Option<f64> {
+        self.columns.iter().find(|(c, _)| *c == column).map(|(_, p)| *p)
+    }
+}
+
+impl Default for CyclicEncoder {
+    fn default() -> Self {
+        CyclicEncoder::new()
+    }
+}
+
+impl<T: Number + FromPrimitive + ToPrimitive> Transformer<T> for CyclicEncoder {
+    fn fit(&mut self, _rows: &[Vec<T>]) {}
+
+    fn transform_row(&self, row: &[T]) -> Vec<T> {
+        let mut output = Vec::with_capacity(self.n_output_features(row.len()));
+        for (j, &x) in row.iter().enumerate() {
+            match self.period_of(j) {
+                Some(period) => {
+                    let angle = 2.0 * std::f64::consts::PI * x.to_f64().unwrap() / period;
+                    output.push(T::to_number(angle.sin()));
+                    output.push(T::to_number(angle.cos()));
+                }
+                None => output.push(x),
+            }
+        }
+        output
+    }
+}
+
+/// Maps string categories to one of `n_buckets` indices by hashing them.
+///
+/// Unlike a vocabulary, nothing is learned or stored, so high-cardinality columns (user
+/// ids, zip codes) and categories unseen during training are encoded in constant memory.
+/// Distinct categories may collide in the same bucket; more buckets make that rarer.
+/// The hash is 64-bit FNV-1a, so indices are stable across runs and platforms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashingEncoder {
+    pub n_buckets: usize,
+}
+
+impl HashingEncoder {
+    /// Panics if `n_buckets` is zero.
+    pub fn new(n_buckets: usize) -> Self {
+        assert!(n_buckets > 0, "n_buckets must be positive");
+        HashingEncoder { n_buckets }
+    }
+
+    /// Bucket index of `category`, in `0..n_buckets`.
+    pub fn bucket(&self, category: &str) -> usize {
+        let hash = category.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+        (hash % self.n_buckets as u64) as usize
+    }
+
+    /// Bucket index of every category in a column, e.g. as inputs of an `Embedding`.
+    pub fn transform<S: AsRef<str>>(&self, categories: &[S]) -> Vec<usize> {
+        categories.iter().map(|c| self.bucket(c.as_ref())).collect()
+    }
+
+    /// Indicator vector of length `n_buckets` with a one at the bucket of `category`.
+    pub fn one_hot<T: Number>(&self, category: &str) -> Vec<T> {
+        let mut encoded = vec![T::zero(); self.n_buckets];
+        encoded[self.bucket(category)] = T::one();
+        encoded
+    }
+}
+
+/// Error returned by `LabelEncoder` for labels or ids it was not fitted on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LabelError {
+    /// The label was not among the fitted classes.
+    UnknownLabel { label: String },
+    /// The id is not below the number of fitted classes.
+    UnknownId { id: usize, n_classes: usize },
+}
+
+impl fmt::Display for LabelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LabelError::UnknownLabel { label } => write!(f, "unknown label {:?}", label),
+            LabelError::UnknownId { id, n_classes } => write!(f, "label id {} is out of range for {} classes", id, n_classes),
+        }
+    }
+}
+
+impl Error for LabelError {}
+
+/// Maps string labels to contiguous ids `0..n_classes` and back.
+///
+/// Classes are sorted, so the ids do not depend on the order of the training labels.
+/// Save the fitted encoder next to the model (`save_json`) to decode predicted class
+/// ids, e.g. from `metrics::argmax`, back to the original labels at inference.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LabelEncoder {
+    /// Distinct labels in sorted order; the id of a label is its index.
+    pub classes: Vec<String>,
+}
+
+impl LabelEncoder {
+    pub fn new() -> Self {
+        LabelEncoder { classes: Vec::new() }
+    }
+
+    pub fn n_classes(&self) -> usize {
+        self.classes.len()
+    }
+
+    /// Learns the distinct labels, replacing any previous fit.
+    pub fn fit<S: AsRef<str>>(&mut self, labels: &[S]) {
+        let mut classes: Vec<String> = labels.iter().map(|l| l.as_ref().to_string()).collect();
+        classes.sort();
+        classes.dedup();
+        self.classes = classes;
+    }
+
+    /// Id of every label. Panics on a label that was not seen by `fit`.
+    pub fn transform<S: AsRef<str>>(&self, labels: &[S]) -> Vec<usize> {
+        self.try_transform(labels).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Fallible version of `transform`.
+    pub fn try_transform<S: AsRef<str>>(&self, labels: &[S]) -> Result<Vec<usize>, LabelError> {
+        labels.iter()
+            .map(|l| {
+                let label = l.as_ref();
+                self.classes.binary_search_by(|c| c.as_str().cmp(label))
+                    .map_err(|_| LabelError::UnknownLabel { label: label.to_string() })
+            })
+            .collect()
+    }
+
+    /// Fits on `labels` and returns their ids.
+    pub fn fit_transform<S: AsRef<str>>(&mut self, labels: &[S]) -> Vec<usize> {
+        self.fit(labels);
+        self.transform(labels)
+    }
+
+    /// Label of every id. Panics on an id `>= n_classes`.
+    pub fn inverse_transform(&self, ids: &[usize]) -> Vec<String> {
+        self.try_inverse_transform(ids).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Fallible version of `inverse_transform`.
+    pub fn try_inverse_transform(&self, ids: &[usize]) -> Result<Vec<String>, LabelError> {
+        ids.iter()
+            .map(|&id| self.classes.get(id).cloned().ok_or(LabelError::UnknownId { id, n_classes: self.n_classes() }))
+            .collect()
+    }
+
+    /// Writes the encoder as JSON.
+    pub fn save_json<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        serde_json::to_writer(BufWriter::new(File::create(path)?), self)?;
+        Ok(())
+    }
+
+    /// Reads an encoder written by `save_json`. Fails if the classes are not sorted and
+    /// distinct, as `fit` leaves them.
+    pub fn load_json<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let encoder: LabelEncoder = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        if !encoder.classes.windows(2).all(|pair| pair[0] < pair[1]) {
+            return Err("label encoder classes must be sorted and distinct".into());
+        }
+        Ok(encoder)
+    }
+}
+
+/// Invertible transform applied to regression targets before training.
+///
+/// Heavy-tailed or strictly positive targets are often easier to fit in log-like
+/// space; `TransformedTargetModel` trains on transformed targets and inverts
+/// predictions back to the original scale.
+pub trait TargetTransform {
+    /// Learns the transform's parameters from the training targets.
+    fn fit(&mut self, targets: &[f64]);
+    fn transform(&self, y: f64) -> f64;
+    fn inverse_transform(&self, z: f64) -> f64;
+}
+
+/// `ln(1 + y)`, inverted with `exp(z) - 1`. Needs `y > -1`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Log1pTransform;
+
+impl TargetTransform for Log1pTransform {
+    fn fit(&mut self, _targets: &[f64]) {}
+
+    fn transform(&self, y: f64) -> f64 {
+        y.ln_1p()
+    }
+
+    fn inverse_transform(&self, z: f64) -> f64 {
+        z.exp_m1()
+    }
+}
+
+/// Box-Cox power transform `(y^lambda - 1) / lambda` (`ln(y)` for `lambda = 0`). Needs `y > 0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoxCox {
+    pub lambda: f64,
+}
+
+impl Default for BoxCox {
+    fn default() -> Self {
+        BoxCox { lambda: 1.0 }
+    }
+}
+
+impl BoxCox {
+    /// Box-Cox with a fixed `lambda`; calling `fit` replaces it with the fitted value.
+    pub fn new(lambda: f64) -> Self {
+        BoxCox { lambda }
+    }
+
+    /// Profile log-likelihood of `lambda` for normally distributed transformed targets.
+    pub fn log_likelihood(targets: &[f64], lambda: f64) -> f64 {
+        let n = targets.len() as f64;
+        let transform = BoxCox::new(lambda);
+        let z: Vec<f64> = targets.iter().map(|&y| transform.transform(y)).collect();
+        let mean = z.iter().sum::<f64>() / n;
+        let variance = z.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+        let log_sum: f64 = targets.iter().map(|y| y.ln()).sum();
+        (lambda - 1.0) * log_sum - n / 2.0 * variance.ln()
+    }
+}
+
+impl TargetTransform for BoxCox {
+    /// Fits `lambda` by maximizing `log_likelihood` with a golden-section search on `[-5, 5]`.
+    /// Panics if a target is not strictly positive or there are fewer than two targets.
+    fn fit(&mut self, targets: &[f64]) {
+        assert!(targets.len() >= 2, "Box-Cox needs at least two targets");
+        assert!(targets.iter().all(|&y| y > 0.0), "Box-Cox requires strictly positive targets");
+        let ratio = (5f64.sqrt() - 1.0) / 2.0;
+        let (mut a, mut b) = (-5.0f64, 5.0f64);
+        let mut c = b - ratio * (b - a);
+        let mut d = a + ratio * (b - a);
+        while b - a > 1e-8 {
+            if BoxCox::log_likelihood(targets, c) > BoxCox::log_likelihood(targets, d) {
+                b = d;
+            } else {
+                a = c;
+            }
+            c = b - ratio * (b - a);
+            d = a + ratio * (b - a);
+        }
+        self.lambda = (a + b) / 2.0;
+    }
+
+    fn transform(&self, y: f64) -> f64 {
+        if self.lambda.abs() < 1e-12 { y.ln() } else { (y.powf(self.lambda) - 1.0) / self.lambda }
+    }
+
+    fn inverse_transform(&self, z: f64) -> f64 {
+        if self.lambda.abs() < 1e-12 {
+            z.exp()
+        } else {
+            // Outside the transform's range the base would be negative; clamp to the boundary
+            (self.lambda * z + 1.0).max(0.0).powf(1.0 / self.lambda)
+        }
+    }
+}
+
+/// A model trained on transformed targets whose predictions are inverted automatically.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransformedTargetModel<M, R> {
+    pub model: M,
+    pub transform: R,
+}
+
+impl<M, R: TargetTransform> TransformedTargetModel<M, R> {
+    /// Fits `transform` on `targets` and calls `train` with the transformed targets.
+    ///
+    /// # Arguments
+    /// * `transform` - Target transform; fitted here.
+    /// * `targets` - Training targets on the original scale.
+    /// * `train` - Trains a model on the transformed targets and returns it.
+    pub fn fit<T, F>(mut transform: R, targets: &[T], train: F) -> Self
+    where
+        T: Number + FromPrimitive + ToPrimitive,
+        F: FnOnce(&[T]) -> M,
+    {
+        let raw: Vec<f64> = targets.iter().map(|y| y.to_f64().unwrap()).collect();
+        transform.fit(&raw);
+        let transformed: Vec<T> = raw.iter().map(|&y| T::to_number(transform.transform(y))).collect();
+        TransformedTargetModel { model: train(&transformed), transform }
+    }
+
+    /// Predicts every row with `predict` and maps the results back to the original target scale.
+    pub fn predict<T, P>(&self, predict: P, features: &[Vec<T>]) -> Vec<T>
+    where
+        T: Number + FromPrimitive + ToPrimitive,
+        P: Fn(&M, &[T]) -> T,
+    {
+        features.iter()
+            .map(|row| T::to_number(self.transform.inverse_transform(predict(&self.model, row).to_f64().unwrap())))
+            .collect()
+    }
+}
@@ -0,0 +1,237 @@
+//! Magnitude pruning and sparse inference for dense models.
+//!
+//! `magnitude_prune` zeroes the smallest weights of a `Sequential` model and returns a
+//! `PruningMask` that keeps them at zero while the model is fine-tuned. Once pruned, a
+//! `SparseModel` stores every dense layer in compressed sparse row form, so the forward
+//! pass only touches the remaining weights. Biases are never pruned.
+
+use std::cmp::Ordering;
+use num_traits::FromPrimitive;
+use crate::layers::Layer;
+use crate::loss_fn::Loss;
+use crate::model::{Dense, ModelLayer, Sequential};
+use crate::numbers::{Number, Real};
+
+/// Which weights of every dense layer survived pruning.
+///
+/// `keep[l][i][j]` tells whether weight `(i, j)` of the `l`-th dense layer (counting
+/// dense layers only, in model order) is kept.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PruningMask {
+    pub keep: Vec<Vec<Vec<bool>>>,
+}
+
+impl PruningMask {
+    /// Zeroes every pruned weight of `model` again, e.g. after an optimizer step.
+    ///
+    /// Panics if `model` does not have the dense layer shapes the mask was built for.
+    pub fn apply<T: Number>(&self, model: &mut Sequential<T>) {
+        let dense_layers: Vec<&mut Dense<T>> = model.layers.iter_mut()
+            .filter_map(|layer| match layer {
+                ModelLayer::Dense(dense) => Some(dense),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(dense_layers.len(), self.keep.len(), "mask must have one entry per dense layer");
+        for (dense, keep) in dense_layers.into_iter().zip(self.keep.iter()) {
+            assert_eq!(dense.weights.len(), keep.len(), "mask does not match the layer shape");
+            for (row, keep_row) in dense.weights.iter_mut().zip(keep.iter()) {
+                assert_eq!(row.len(), keep_row.len(), "mask does not match the layer shape");
+                for (w, &kept) in row.iter_mut().zip(keep_row.iter()) {
+                    if !kept {
+                        *w = T::zero();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Fraction of masked-out weights.
+    pub fn sparsity(&self) -> f64 {
+        let (total, pruned) = self.keep.iter().flatten().flatten()
+            .fold((0usize, 0usize), |(total, pruned), &kept| (total + 1, pruned + usize::from(!kept)));
+        if total == 0 { 0.0 } else { pruned as f64 / total as f64 }
+    }
+
+    /// One epoch of `Sequential::train_epoch`, re-applying the mask after every step so
+    /// pruned weights stay at zero. Returns the mean loss.
+    pub fn train_epoch<T: Real + FromPrimitive>(&self, model: &mut Sequential<T>, rows: &[Vec<T>], targets: &[Vec<T>], loss: Loss, learning_rate: T) -> T {
+        assert_eq!(rows.len(), targets.len(), "rows and targets must have the same length");
+        let mut total = T::zero();
+        for (row, target) in rows.iter().zip(targets.iter()) {
+            total = total + model.train_step(row, target, loss, learning_rate);
+            self.apply(model);
+        }
+        total / T::to_number(rows.len().max(1) as f64)
+    }
+}
+
+/// Zeroes the fraction `sparsity` of dense-layer weights with the smallest magnitude.
+///
+/// # Arguments
+/// * `model` - Model to prune in place.
+/// * `sparsity` - Fraction of weights to remove, in `[0, 1]`.
+///
+/// # Returns
+/// * The mask of kept weights; pass it to `PruningMask::apply` (or use
+///   `PruningMask::train_epoch`) to keep the pruned weights at zero during fine-tuning.
+///
+/// # Notes
+/// - The threshold is global: all dense layers are ranked together, so layers with many
+///   small weights lose more of them.
+/// - `floor(sparsity * n_weights)` weights are removed; ties are broken by position.
+/// - Panics if `sparsity` lies outside `[0, 1]`.
+pub fn magnitude_prune<T: Number>(model: &mut Sequential<T>, sparsity: f64) -> PruningMask {
+    assert!((0.0..=1.0).contains(&sparsity), "sparsity must be in [0, 1]");
+    // Step 1: Rank all weights by magnitude
+    let mut ranked: Vec<(T, usize, usize, usize)> = Vec::new();
+    let mut keep = Vec::new();
+    for (l, dense) in dense_layers(model).enumerate() {
+        for (i, row) in dense.weights.iter().enumerate() {
+            ranked.extend(row.iter().enumerate().map(|(j, w)| (w.abs(), l, i, j)));
+        }
+        keep.push(dense.weights.iter().map(|row| vec![true; row.len()]).collect::<Vec<_>>());
+    }
+    ranked.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+    // Step 2: Mask the smallest ones and zero them in the model
+    let n_pruned = (sparsity * ranked.len() as f64).floor() as usize;
+    for &(_, l, i, j) in &ranked[..n_pruned] {
+        keep[l][i][j] = false;
+    }
+    let mask = PruningMask { keep };
+    mask.apply(model);
+    mask
+}
+
+/// Fraction of dense-layer weights of `model` that are exactly zero.
+pub fn sparsity<T: Number>(model: &Sequential<T>) -> f64 {
+    let (total, zeros) = dense_layers(model).flat_map(|dense| dense.weights.iter().flatten())
+        .fold((0usize, 0usize), |(total, zeros), &w| (total + 1, zeros + usize::from(w.eq(T::zero()))));
+    if total == 0 { 0.0 } else { zeros as f64 / total as f64 }
+}
+
+fn dense_layers<T: Number>(model: &Sequential<T>) -> impl Iterator<Item = &Dense<T>> {
+    model.layers.iter().filter_map(|layer| match layer {
+        ModelLayer::Dense(dense) => Some(dense),
+        _ => None,
+    })
+}
+
+/// A dense layer in compressed sparse row (CSR) form, storing only non-zero weights.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparseDense<T> {
+    /// Non-zero weights, row by row.
+    pub values: Vec<T>,
+    /// Input index of every entry of `values`.
+    pub columns: Vec<usize>,
+    /// Row `i` occupies `values[row_starts[i]..row_starts[i + 1]]`.
+    pub row_starts: Vec<usize>,
+    pub biases: Vec<T>,
+    input_dim: usize,
+}
+
+impl<T: Number> SparseDense<T> {
+    /// Compresses `layer`, dropping its zero weights.
+    pub fn from_dense(layer: &Dense<T>) -> Self {
+        let mut values = Vec::new();
+        let mut columns = Vec::new();
+        let mut row_starts = vec![0];
+        for row in &layer.weights {
+            for (j, &w) in row.iter().enumerate() {
+                if w.ne(T::zero()) {
+                    values.push(w);
+                    columns.push(j);
+                }
+            }
+            row_starts.push(values.len());
+        }
+        SparseDense { values, columns, row_starts, biases: layer.biases.clone(), input_dim: layer.input_dim() }
+    }
+
+    pub fn input_dim(&self) -> usize {
+        self.input_dim
+    }
+
+    pub fn output_dim(&self) -> usize {
+        self.biases.len()
+    }
+
+    /// Number of stored (non-zero) weights.
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /// `outputs = biases + W * inputs`, visiting only the stored weights.
+    pub fn forward(&self, inputs: &[T]) -> Vec<T> {
+        assert_eq!(inputs.len(), self.input_dim, "inputs must have one entry per weight column");
+        self.biases.iter().enumerate()
+            .map(|(i, &bias)| {
+                let range = self.row_starts[i]..self.row_starts[i + 1];
+                self.values[range.clone()].iter().zip(self.columns[range].iter())
+                    .fold(bias, |acc, (&w, &j)| acc + w * inputs[j])
+            })
+            .collect()
+    }
+
+    /// Expands the layer back to a `Dense` layer.
+    pub fn to_dense(&self) -> Dense<T> {
+        let mut weights = vec![vec![T::zero(); self.input_dim]; self.output_dim()];
+        for (i, row) in weights.iter_mut().enumerate() {
+            for k in self.row_starts[i]..self.row_starts[i + 1] {
+                row[self.columns[k]] = self.values[k];
+            }
+        }
+        Dense::new(weights, self.biases.clone())
+    }
+}
+
+/// A built layer of a `SparseModel`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SparseModelLayer<T: Number> {
+    Dense(SparseDense<T>),
+    /// Any non-dense layer, run unchanged.
+    Other(ModelLayer<T>),
+}
+
+/// Inference copy of a (pruned) `Sequential` model with sparse dense layers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparseModel<T: Number> {
+    pub layers: Vec<SparseModelLayer<T>>,
+}
+
+impl<T: Real> SparseModel<T> {
+    /// Compresses every dense layer of `model`; other layers are copied.
+    pub fn from_sequential(model: &Sequential<T>) -> Self {
+        let layers = model.layers.iter().map(|layer| match layer {
+            ModelLayer::Dense(dense) => SparseModelLayer::Dense(SparseDense::from_dense(dense)),
+            other => SparseModelLayer::Other(other.clone()),
+        }).collect();
+        SparseModel { layers }
+    }
+
+    /// Runs `inputs` through the model; equal to `Sequential::forward` on the source model.
+    pub fn forward(&self, inputs: &[T]) -> Vec<T> {
+        let mut values = inputs.to_vec();
+        for layer in &self.layers {
+            values = match layer {
+                SparseModelLayer::Dense(dense) => dense.forward(&values),
+                SparseModelLayer::Other(other) => other.forward(&values),
+            };
+        }
+        values
+    }
+
+    /// Applies `forward` to every row.
+    pub fn predict(&self, rows: &[Vec<T>]) -> Vec<Vec<T>> {
+        rows.iter().map(|row| self.forward(row)).collect()
+    }
+
+    /// Number of stored dense-layer weights.
+    pub fn nnz(&self) -> usize {
+        self.layers.iter().map(|layer| match layer {
+            SparseModelLayer::Dense(dense) => dense.nnz(),
+            SparseModelLayer::Other(_) => 0,
+        }).sum()
+    }
+}
@@ -0,0 +1,168 @@
+//! Python bindings for training and inference, enabled with the `python` feature.
+//!
+//! Build with `maturin develop --features python` (maturin adds the `cdylib` crate type
+//! itself) and use from Python with NumPy arrays of `float64`, one sample per row:
+//!
+//! ```python
+//! from neuralnet import Dataset, Model, Trainer
+//!
+//! model = Model.from_config(open("config.json").read(), input_dim=4)
+//! trainer = Trainer("CrossEntropy", learning_rate=0.05, epochs=20, batch_size=8, shuffle=7)
+//! losses = trainer.fit(model, Dataset(x_train, y_train))
+//! probabilities = model.predict(x_test)
+//! ```
+
+use numpy::{PyArray2, PyReadonlyArray2, PyUntypedArrayMethods};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use crate::config::ModelConfig;
+use crate::dataset;
+use crate::loss_fn::Loss;
+use crate::model::Sequential;
+use crate::training;
+
+fn value_error<E: std::fmt::Display>(e: E) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+fn to_rows(array: &PyReadonlyArray2<'_, f64>) -> Vec<Vec<f64>> {
+    array.as_array().rows().into_iter().map(|row| row.to_vec()).collect()
+}
+
+/// A `Sequential<f64>` model exposed to Python.
+#[pyclass(name = "Model")]
+pub struct PyModel {
+    model: Sequential<f64>,
+}
+
+#[pymethods]
+impl PyModel {
+    /// Builds the model of a `ModelConfig` given as JSON or TOML text. `input_dim`
+    /// is required when the config does not declare it.
+    #[staticmethod]
+    #[pyo3(signature = (config, input_dim=None))]
+    pub fn from_config(config: &str, input_dim: Option<usize>) -> PyResult<Self> {
+        let config = ModelConfig::from_json_str(config).or_else(|_| ModelConfig::from_toml_str(config)).map_err(value_error)?;
+        let model = match input_dim {
+            Some(dim) => config.build_for_input(dim),
+            None => config.build(),
+        };
+        Ok(PyModel { model: model.map_err(value_error)? })
+    }
+
+    /// Loads a model from the JSON produced by `to_json`. Fails if the JSON is
+    /// malformed or the layer shapes do not fit together.
+    #[staticmethod]
+    pub fn from_json(json: &str) -> PyResult<Self> {
+        let model: Sequential<f64> = serde_json::from_str(json).map_err(value_error)?;
+        model.check_shapes().map_err(value_error)?;
+        Ok(PyModel { model })
+    }
+
+    pub fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.model).map_err(value_error)
+    }
+
+    #[getter]
+    pub fn input_dim(&self) -> usize {
+        self.model.input_dim()
+    }
+
+    #[getter]
+    pub fn output_dim(&self) -> usize {
+        self.model.output_dim()
+    }
+
+    #[getter]
+    pub fn n_parameters(&self) -> usize {
+        self.model.n_parameters()
+    }
+
+    /// Runs every row of `inputs` (shape `(n, input_dim)`) through the model and
+    /// returns the outputs with shape `(n, output_dim)`.
+    pub fn predict<'py>(&self, py: Python<'py>, inputs: PyReadonlyArray2<'py, f64>) -> PyResult<Bound<'py, PyArray2<f64>>> {
+        if inputs.shape()[1] != self.model.input_dim() {
+            return Err(PyValueError::new_err("inputs must have one column per model input"));
+        }
+        let outputs = self.model.predict(&to_rows(&inputs));
+        if outputs.is_empty() {
+            return Ok(PyArray2::zeros(py, [0, self.model.output_dim()], false));
+        }
+        PyArray2::from_vec2(py, &outputs).map_err(value_error)
+    }
+}
+
+/// Rows and targets of a training set, copied out of two NumPy arrays.
+#[pyclass(name = "Dataset")]
+pub struct PyDataset {
+    dataset: dataset::Dataset<Vec<f64>, Vec<f64>>,
+}
+
+#[pymethods]
+impl PyDataset {
+    /// Pairs the rows of `x` with the rows of `y`; fails if their row counts differ.
+    #[new]
+    pub fn new(x: PyReadonlyArray2<'_, f64>, y: PyReadonlyArray2<'_, f64>) -> PyResult<Self> {
+        if x.shape()[0] != y.shape()[0] {
+            return Err(PyValueError::new_err("x and y must have the same number of rows"));
+        }
+        Ok(PyDataset { dataset: dataset::Dataset::new(to_rows(&x), to_rows(&y)) })
+    }
+
+    pub fn __len__(&self) -> usize {
+        self.dataset.len()
+    }
+}
+
+/// A `Trainer` exposed to Python.
+#[pyclass(name = "Trainer")]
+pub struct PyTrainer {
+    trainer: training::Trainer,
+}
+
+#[pymethods]
+impl PyTrainer {
+    /// `loss` is a `Loss` variant in its serde form: a name such as `"MeanSquaredError"`,
+    /// or JSON such as `{"Quantile": 0.9}` for losses with a parameter. `shuffle` is the
+    /// seed for reshuffling before every epoch; `None` keeps the dataset order.
+    #[new]
+    #[pyo3(signature = (loss, learning_rate, epochs, batch_size=1, shuffle=None))]
+    pub fn new(loss: &str, learning_rate: f64, epochs: usize, batch_size: usize, shuffle: Option<u64>) -> PyResult<Self> {
+        let loss: Loss = serde_json::from_str(loss)
+            .or_else(|_| serde_json::from_value(serde_json::Value::String(loss.to_string())))
+            .map_err(value_error)?;
+        if batch_size == 0 {
+            return Err(PyValueError::new_err("batch_size must be positive"));
+        }
+        let trainer = training::Trainer::new(loss, learning_rate, epochs).batch_size(batch_size);
+        Ok(PyTrainer { trainer: match shuffle { Some(seed) => trainer.shuffle(seed), None => trainer } })
+    }
+
+    /// The trainer of a `ModelConfig` given as JSON or TOML text.
+    #[staticmethod]
+    pub fn from_config(config: &str) -> PyResult<Self> {
+        let config = ModelConfig::from_json_str(config).or_else(|_| ModelConfig::from_toml_str(config)).map_err(value_error)?;
+        Ok(PyTrainer { trainer: config.trainer() })
+    }
+
+    /// Trains `model` in place on `dataset` and returns the training loss of every epoch.
+    pub fn fit(&self, py: Python<'_>, model: &mut PyModel, dataset: &PyDataset) -> PyResult<Vec<f64>> {
+        let (rows, targets) = (&dataset.dataset.rows, &dataset.dataset.targets);
+        if rows.first().is_some_and(|row| row.len() != model.model.input_dim()) {
+            return Err(PyValueError::new_err("dataset rows must have one column per model input"));
+        }
+        if targets.first().is_some_and(|target| target.len() != model.model.output_dim()) {
+            return Err(PyValueError::new_err("dataset targets must have one column per model output"));
+        }
+        let history = py.allow_threads(|| self.trainer.fit(&mut model.model, rows, targets));
+        Ok(history.train_losses())
+    }
+}
+
+#[pymodule]
+fn neuralnet(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyModel>()?;
+    m.add_class::<PyDataset>()?;
+    m.add_class::<PyTrainer>()?;
+    Ok(())
+}
@@ -0,0 +1,170 @@
+//! Post-training int8 quantization for inference.
+//!
+//! Weights are stored as `i8` with one affine `scale`/`zero_point` pair per layer:
+//! `real = scale * (q - zero_point)`. Activations are quantized on the fly before every
+//! dense layer, products are accumulated in `i32`, and the result is dequantized once
+//! per output. Biases stay in `f32`; they are a negligible part of the model size.
+
+use crate::activation_fn::{softmax, Activation};
+use crate::layers::Layer1D;
+use crate::model::{Dense, ModelLayer, PReLU, Sequential};
+
+/// Affine mapping between `f32` values and `i8` codes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuantParams {
+    pub scale: f32,
+    pub zero_point: i8,
+}
+
+impl QuantParams {
+    /// Parameters covering `[min, max]` (extended to include zero, so zero is exact).
+    pub fn from_range(min: f32, max: f32) -> Self {
+        let min = min.min(0.0);
+        let max = max.max(0.0);
+        let scale = (max - min) / 255.0;
+        if scale <= 0.0 || !scale.is_finite() {
+            return QuantParams { scale: 1.0, zero_point: 0 };
+        }
+        let zero_point = (-128.0 - min / scale).round().clamp(-128.0, 127.0) as i8;
+        QuantParams { scale, zero_point }
+    }
+
+    /// Parameters covering the range of `values`.
+    pub fn fit(values: &[f32]) -> Self {
+        let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        QuantParams::from_range(min, max)
+    }
+
+    pub fn quantize(&self, x: f32) -> i8 {
+        (x / self.scale + self.zero_point as f32).round().clamp(-128.0, 127.0) as i8
+    }
+
+    pub fn dequantize(&self, q: i8) -> f32 {
+        self.scale * (q as i32 - self.zero_point as i32) as f32
+    }
+}
+
+/// Quantizes `values` with parameters fitted to their range.
+pub fn quantize(values: &[f32]) -> (Vec<i8>, QuantParams) {
+    let params = QuantParams::fit(values);
+    (values.iter().map(|&x| params.quantize(x)).collect(), params)
+}
+
+/// Dense layer with `i8` weights. `weights[i][j]` is the weight for output `i` and input `j`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantizedLayer {
+    pub weights: Vec<Vec<i8>>,
+    pub params: QuantParams,
+    pub biases: Vec<f32>,
+}
+
+impl QuantizedLayer {
+    /// Quantizes a runtime-shaped dense layer.
+    pub fn from_dense(layer: &Dense<f32>) -> Self {
+        QuantizedLayer::from_rows(layer.weights.iter().map(|row| row.as_slice()), &layer.biases)
+    }
+
+    /// Quantizes a const-generic `Layer1D`.
+    pub fn from_layer1d<const OUT: usize, const IN: usize>(layer: &Layer1D<f32, OUT, IN>) -> Self {
+        QuantizedLayer::from_rows(layer.weights.iter().map(|row| row.as_slice()), &layer.biases)
+    }
+
+    fn from_rows<'a>(rows: impl Iterator<Item = &'a [f32]> + Clone, biases: &[f32]) -> Self {
+        let all: Vec<f32> = rows.clone().flat_map(|row| row.iter().copied()).collect();
+        let params = QuantParams::fit(&all);
+        let weights = rows.map(|row| row.iter().map(|&w| params.quantize(w)).collect()).collect();
+        QuantizedLayer { weights, params, biases: biases.to_vec() }
+    }
+
+    pub fn input_dim(&self) -> usize {
+        self.weights.first().map_or(0, |row| row.len())
+    }
+
+    pub fn output_dim(&self) -> usize {
+        self.biases.len()
+    }
+
+    /// Returns the dequantized weights, e.g. to measure the quantization error.
+    pub fn dequantized_weights(&self) -> Vec<Vec<f32>> {
+        self.weights.iter().map(|row| row.iter().map(|&q| self.params.dequantize(q)).collect()).collect()
+    }
+}
+
+/// Quantized forward kernel: `outputs = biases + W * inputs` with `i8` operands and `i32` accumulation.
+///
+/// # Arguments
+/// * `inputs` - Quantized inputs, one per weight column.
+/// * `input_params` - Quantization parameters of `inputs`.
+/// * `layer` - Quantized layer.
+///
+/// # Returns
+/// * Dequantized `f32` outputs, one per layer output.
+pub fn dense_linear_q8(inputs: &[i8], input_params: QuantParams, layer: &QuantizedLayer) -> Vec<f32> {
+    assert_eq!(inputs.len(), layer.input_dim(), "inputs must have one entry per weight column");
+    let input_zero = input_params.zero_point as i32;
+    let weight_zero = layer.params.zero_point as i32;
+    let output_scale = input_params.scale * layer.params.scale;
+    layer.weights.iter().zip(layer.biases.iter())
+        .map(|(row, &bias)| {
+            let mut acc: i32 = 0;
+            for (&w, &x) in row.iter().zip(inputs.iter()) {
+                acc += (w as i32 - weight_zero) * (x as i32 - input_zero);
+            }
+            bias + output_scale * acc as f32
+        })
+        .collect()
+}
+
+/// A built layer of a `QuantizedModel`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QuantizedModelLayer {
+    Dense(QuantizedLayer),
+    Activation(Activation),
+    PReLU(PReLU<f32>),
+    Softmax,
+}
+
+/// Int8 version of a `Sequential<f32>` model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantizedModel {
+    pub layers: Vec<QuantizedModelLayer>,
+}
+
+impl QuantizedModel {
+    /// Quantizes every dense layer of `model`; activations are kept as they are.
+    pub fn from_sequential(model: &Sequential<f32>) -> Self {
+        let layers = model.layers.iter().map(|layer| match layer {
+            ModelLayer::Dense(dense) => QuantizedModelLayer::Dense(QuantizedLayer::from_dense(dense)),
+            ModelLayer::Activation(activation) => QuantizedModelLayer::Activation(*activation),
+            ModelLayer::PReLU(prelu) => QuantizedModelLayer::PReLU(prelu.clone()),
+            ModelLayer::Softmax => QuantizedModelLayer::Softmax,
+        }).collect();
+        QuantizedModel { layers }
+    }
+
+    /// Runs `inputs` through the model, quantizing the activations before every dense layer.
+    pub fn forward(&self, inputs: &[f32]) -> Vec<f32> {
+        let mut values = inputs.to_vec();
+        for layer in &self.layers {
+            values = match layer {
+                QuantizedModelLayer::Dense(dense) => {
+                    let (quantized, params) = quantize(&values);
+                    dense_linear_q8(&quantized, params, dense)
+                }
+                QuantizedModelLayer::Activation(activation) => values.iter().map(|&x| activation.apply(x)).collect(),
+                QuantizedModelLayer::PReLU(prelu) => prelu.forward(&values),
+                QuantizedModelLayer::Softmax => softmax(&values),
+            };
+        }
+        values
+    }
+
+    /// Size of the quantized weights in bytes (one byte per weight).
+    pub fn weight_bytes(&self) -> usize {
+        self.layers.iter().map(|layer| match layer {
+            QuantizedModelLayer::Dense(dense) => dense.weights.iter().map(|row| row.len()).sum(),
+            _ => 0,
+        }).sum()
+    }
+}
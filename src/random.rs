@@ -0,0 +1,179 @@
+//! Small, dependency-free pseudo-random number generation.
+//!
+//! Everything in the crate that needs randomness (shuffling, subsampling,
+//! initialization) takes an explicit seed so results are reproducible.
+
+/// Seedable pseudo-random number generator based on SplitMix64.
+///
+/// SplitMix64 is fast, has a 64-bit state and passes common statistical test
+/// suites, which is more than enough for shuffling and sampling.
+/// Serializes its state, so a training run can be checkpointed mid-sequence.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Creates a generator from a seed. Equal seeds produce equal sequences.
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    /// Returns the next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a uniformly distributed `f64` in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        // Use the top 53 bits so every value is exactly representable
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Returns a uniformly distributed index in `[0, upper)`.
+    ///
+    /// # Panics
+    /// Panics if `upper == 0`.
+    pub fn gen_index(&mut self, upper: usize) -> usize {
+        assert!(upper > 0, "upper bound must be positive");
+        (self.next_f64() * upper as f64) as usize
+    }
+
+    /// Returns a standard normal sample (Box-Muller transform).
+    pub fn next_normal(&mut self) -> f64 {
+        // 1 - u keeps the argument of ln in (0, 1]
+        let u1 = 1.0 - self.next_f64();
+        let u2 = self.next_f64();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+
+    /// Returns a `Gamma(shape, 1)` sample (Marsaglia-Tsang).
+    ///
+    /// # Panics
+    /// Panics if `shape <= 0`.
+    pub fn next_gamma(&mut self, shape: f64) -> f64 {
+        assert!(shape > 0.0, "shape must be positive");
+        if shape < 1.0 {
+            // Gamma(a) = Gamma(a + 1) * U^(1 / a)
+            let u = 1.0 - self.next_f64();
+            return self.next_gamma(shape + 1.0) * u.powf(1.0 / shape);
+        }
+        let d = shape - 1.0 / 3.0;
+        let c = 1.0 / (9.0 * d).sqrt();
+        loop {
+            let x = self.next_normal();
+            let v = (1.0 + c * x).powi(3);
+            if v <= 0.0 {
+                continue;
+            }
+            let u = 1.0 - self.next_f64();
+            if u.ln() < 0.5 * x * x + d - d * v + d * v.ln() {
+                return d * v;
+            }
+        }
+    }
+
+    /// Returns a `Beta(a, b)` sample in `[0, 1]`.
+    ///
+    /// # Panics
+    /// Panics if `a <= 0` or `b <= 0`.
+    pub fn next_beta(&mut self, a: f64, b: f64) -> f64 {
+        let x = self.next_gamma(a);
+        let y = self.next_gamma(b);
+        x / (x + y)
+    }
+
+    /// Shuffles a slice in place (Fisher-Yates).
+    pub fn shuffle<T>(&mut self, values: &mut [T]) {
+        for i in (1..values.len()).rev() {
+            let j = self.gen_index(i + 1);
+            values.swap(i, j);
+        }
+    }
+}
+
+const PHILOX_M0: u32 = 0xD251_1F53;
+const PHILOX_M1: u32 = 0xCD9E_8D57;
+const PHILOX_W0: u32 = 0x9E37_79B9;
+const PHILOX_W1: u32 = 0xBB67_AE85;
+const PHILOX_ROUNDS: usize = 10;
+
+/// Counter-based pseudo-random number generator (Philox4x32-10).
+///
+/// Unlike [`Rng`], a counter-based generator has no mutable state: the random bits
+/// for a given `(stream, index)` pair are a pure function of the key. Dropout masks
+/// and noise can therefore be generated in any order — sequentially, in parallel
+/// chunks or on another device — and still produce identical results.
+///
+/// A typical convention is to use the layer/step as `stream` and the element
+/// position as `index`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Philox4x32 {
+    key: [u32; 2],
+}
+
+impl Philox4x32 {
+    /// Creates a generator keyed by `seed`.
+    pub fn new(seed: u64) -> Self {
+        Philox4x32 { key: [seed as u32, (seed >> 32) as u32] }
+    }
+
+    /// Computes one Philox block: four 32-bit random words for a 128-bit counter.
+    pub fn block(&self, counter: [u32; 4]) -> [u32; 4] {
+        let mut ctr = counter;
+        let mut key = self.key;
+        for round in 0..PHILOX_ROUNDS {
+            if round > 0 {
+                key[0] = key[0].wrapping_add(PHILOX_W0);
+                key[1] = key[1].wrapping_add(PHILOX_W1);
+            }
+            let p0 = PHILOX_M0 as u64 * ctr[0] as u64;
+            let p1 = PHILOX_M1 as u64 * ctr[2] as u64;
+            ctr = [
+                ((p1 >> 32) as u32) ^ ctr[1] ^ key[0],
+                p1 as u32,
+                ((p0 >> 32) as u32) ^ ctr[3] ^ key[1],
+                p0 as u32,
+            ];
+        }
+        ctr
+    }
+
+    /// Returns the random block addressed by `(stream, index)`.
+    fn block_at(&self, stream: u64, index: u64) -> [u32; 4] {
+        self.block([index as u32, (index >> 32) as u32, stream as u32, (stream >> 32) as u32])
+    }
+
+    /// Returns a uniformly distributed `f64` in `[0, 1)` for `(stream, index)`.
+    pub fn uniform(&self, stream: u64, index: u64) -> f64 {
+        let words = self.block_at(stream, index);
+        let bits = ((words[0] as u64) << 32) | words[1] as u64;
+        (bits >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Returns a standard normal sample for `(stream, index)` (Box-Muller transform).
+    pub fn normal(&self, stream: u64, index: u64) -> f64 {
+        let words = self.block_at(stream, index);
+        let to_unit = |hi: u32, lo: u32| ((((hi as u64) << 32) | lo as u64) >> 11) as f64 * (1.0 / (1u64 << 53) as f64);
+        // Shift u1 into (0, 1] so the logarithm stays finite
+        let u1 = 1.0 - to_unit(words[0], words[1]);
+        let u2 = to_unit(words[2], words[3]);
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+
+    /// Builds a dropout keep-mask of length `len` for `stream`.
+    ///
+    /// Element `i` is kept (`true`) when its uniform draw is at least `rate`, so each
+    /// element is dropped with probability `rate` independently of evaluation order.
+    ///
+    /// # Panics
+    /// Panics if `rate` is outside `[0, 1)`.
+    pub fn dropout_mask(&self, stream: u64, len: usize, rate: f64) -> Vec<bool> {
+        assert!((0.0..1.0).contains(&rate), "dropout rate must lie in [0, 1)");
+        (0..len).map(|i| self.uniform(stream, i as u64) >= rate).collect()
+    }
+}
@@ -0,0 +1,304 @@
+//! Text processing: tokenization utilities feeding the `Embedding` layer.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::numbers::Number;
+
+/// Token emitted for characters never seen during training.
+pub const UNK_TOKEN: &str = "<unk>";
+/// Symbol appended to every word so merges can learn word endings and decoding can restore spaces.
+pub const END_OF_WORD: &str = "</w>";
+
+/// Byte-pair-encoding (BPE) subword tokenizer.
+///
+/// Training starts from single characters and repeatedly merges the most frequent
+/// adjacent symbol pair until the vocabulary reaches the requested size. Encoding
+/// replays the learned merges in order, so frequent words become single tokens
+/// while rare words fall back to smaller subwords.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BpeTokenizer {
+    /// Token strings in id order.
+    vocab: Vec<String>,
+    /// Learned merges in rank order (earlier merges apply first).
+    merges: Vec<(String, String)>,
+    #[serde(skip)]
+    token_ids: HashMap<String, usize>,
+    #[serde(skip)]
+    merge_ranks: HashMap<(String, String), usize>,
+}
+
+impl BpeTokenizer {
+    /// Trains a tokenizer on `corpus` until the vocabulary holds `vocab_size` tokens
+    /// (or no pair occurs anymore).
+    ///
+    /// # Steps
+    /// 1. Split every document on whitespace and count word frequencies.
+    /// 2. Represent each word as its characters followed by `END_OF_WORD`; the base
+    ///    vocabulary is `UNK_TOKEN`, `END_OF_WORD` and every character seen.
+    /// 3. Count adjacent symbol pairs weighted by word frequency and merge the most
+    ///    frequent one everywhere (ties broken by the lexicographically smallest pair).
+    /// 4. Repeat step 3 until the vocabulary is full.
+    pub fn train(corpus: &[&str], vocab_size: usize) -> Self {
+        let mut word_counts: HashMap<&str, usize> = HashMap::new();
+        for document in corpus {
+            for word in document.split_whitespace() {
+                *word_counts.entry(word).or_insert(0) += 1;
+            }
+        }
+        // Sort for a deterministic vocabulary order
+        let mut words: Vec<(Vec<String>, usize)> = word_counts.into_iter()
+            .map(|(w, c)| (split_word(w), c))
+            .collect();
+        words.sort();
+
+        let mut vocab = vec![UNK_TOKEN.to_string(), END_OF_WORD.to_string()];
+        let mut alphabet: Vec<String> = words.iter()
+            .flat_map(|(symbols, _)| symbols.iter().cloned())
+            .filter(|s| s != END_OF_WORD)
+            .collect();
+        alphabet.sort();
+        alphabet.dedup();
+        vocab.extend(alphabet);
+
+        let mut merges = Vec::new();
+        while vocab.len() < vocab_size {
+            let mut pair_counts: HashMap<(&str, &str), usize> = HashMap::new();
+            for (symbols, count) in &words {
+                for pair in symbols.windows(2) {
+                    *pair_counts.entry((pair[0].as_str(), pair[1].as_str())).or_insert(0) += count;
+                }
+            }
+            let best = pair_counts.into_iter()
+                .max_by(|(a, ca), (b, cb)| ca.cmp(cb).then_with(|| b.cmp(a)))
+                .map(|((a, b), _)| (a.to_string(), b.to_string()));
+            let (left, right) = match best {
+                Some(pair) => pair,
+                None => break,
+            };
+            for (symbols, _) in words.iter_mut() {
+                merge_pair(symbols, &left, &right);
+            }
+            vocab.push(format!("{}{}", left, right));
+            merges.push((left, right));
+        }
+
+        BpeTokenizer::from_parts(vocab, merges)
+    }
+
+    /// Builds a tokenizer from a vocabulary and a ranked merge list.
+    fn from_parts(vocab: Vec<String>, merges: Vec<(String, String)>) -> Self {
+        let mut tokenizer = BpeTokenizer { vocab, merges, token_ids: HashMap::new(), merge_ranks: HashMap::new() };
+        tokenizer.rebuild_lookups();
+        tokenizer
+    }
+
+    fn rebuild_lookups(&mut self) {
+        self.token_ids = self.vocab.iter().enumerate().map(|(i, t)| (t.clone(), i)).collect();
+        self.merge_ranks = self.merges.iter().enumerate().map(|(i, m)| (m.clone(), i)).collect();
+    }
+
+    /// Number of tokens in the vocabulary.
+    pub fn vocab_size(&self) -> usize {
+        self.vocab.len()
+    }
+
+    /// Learned merges in the order they are applied.
+    pub fn merges(&self) -> &[(String, String)] {
+        &self.merges
+    }
+
+    /// Id of `token`, if it is in the vocabulary.
+    pub fn token_to_id(&self, token: &str) -> Option<usize> {
+        self.token_ids.get(token).copied()
+    }
+
+    /// Token string for `id`, if it is in range.
+    pub fn id_to_token(&self, id: usize) -> Option<&str> {
+        self.vocab.get(id).map(|s| s.as_str())
+    }
+
+    /// Splits `text` on whitespace and encodes every word into subword token ids.
+    /// Characters not seen during training map to the id of `UNK_TOKEN`.
+    pub fn encode(&self, text: &str) -> Vec<usize> {
+        let unk = self.token_ids[UNK_TOKEN];
+        let mut ids = Vec::new();
+        for word in text.split_whitespace() {
+            let mut symbols = split_word(word);
+            // Apply the lowest-ranked applicable merge until none is left
+            loop {
+                let best = symbols.windows(2)
+                    .filter_map(|pair| self.merge_ranks.get(&(pair[0].clone(), pair[1].clone())).map(|&rank| (rank, pair)))
+                    .min_by_key(|(rank, _)| *rank)
+                    .map(|(_, pair)| (pair[0].clone(), pair[1].clone()));
+                match best {
+                    Some((left, right)) => merge_pair(&mut symbols, &left, &right),
+                    None => break,
+                }
+            }
+            ids.extend(symbols.iter().map(|s| self.token_ids.get(s).copied().unwrap_or(unk)));
+        }
+        ids
+    }
+
+    /// Decodes token ids back to text, turning word endings into single spaces.
+    /// Ids outside the vocabulary are skipped.
+    pub fn decode(&self, ids: &[usize]) -> String {
+        let joined: String = ids.iter().filter_map(|&id| self.id_to_token(id)).collect();
+        joined.replace(END_OF_WORD, " ").trim_end().to_string()
+    }
+
+    /// Saves the vocabulary and merges to a JSON file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let file = File::create(path)?;
+        serde_json::to_writer(file, self)?;
+        Ok(())
+    }
+
+    /// Loads a tokenizer previously written with `save`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let file = File::open(path)?;
+        let mut tokenizer: BpeTokenizer = serde_json::from_reader(file)?;
+        if !tokenizer.vocab.iter().any(|t| t == UNK_TOKEN) {
+            return Err(format!("Tokenizer vocabulary is missing '{}'", UNK_TOKEN).into());
+        }
+        tokenizer.rebuild_lookups();
+        Ok(tokenizer)
+    }
+}
+
+/// Splits a word into single-character symbols terminated by `END_OF_WORD`.
+fn split_word(word: &str) -> Vec<String> {
+    let mut symbols: Vec<String> = word.chars().map(|c| c.to_string()).collect();
+    symbols.push(END_OF_WORD.to_string());
+    symbols
+}
+
+/// Replaces every adjacent occurrence of `(left, right)` in `symbols` with the merged symbol.
+fn merge_pair(symbols: &mut Vec<String>, left: &str, right: &str) {
+    let mut merged = Vec::with_capacity(symbols.len());
+    let mut i = 0;
+    while i < symbols.len() {
+        if i + 1 < symbols.len() && symbols[i] == left && symbols[i + 1] == right {
+            merged.push(format!("{}{}", left, right));
+            i += 2;
+        } else {
+            merged.push(std::mem::take(&mut symbols[i]));
+            i += 1;
+        }
+    }
+    *symbols = merged;
+}
+
+/// Returns the word n-grams of `text` (whitespace tokenized), joined by single spaces.
+pub fn word_ngrams(text: &str, n: usize) -> Vec<String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if n == 0 || words.len() < n {
+        return Vec::new();
+    }
+    words.windows(n).map(|w| w.join(" ")).collect()
+}
+
+/// Returns the character n-grams of `text` after collapsing runs of whitespace to one space.
+pub fn char_ngrams(text: &str, n: usize) -> Vec<String> {
+    let normalized: Vec<char> = text.split_whitespace().collect::<Vec<_>>().join(" ").chars().collect();
+    if n == 0 || normalized.len() < n {
+        return Vec::new();
+    }
+    normalized.windows(n).map(|w| w.iter().collect()).collect()
+}
+
+/// Which units a vectorizer builds its n-gram features from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Analyzer {
+    /// Word n-grams.
+    Word,
+    /// Character n-grams over the whole (whitespace-normalized) text, spanning word boundaries.
+    Char,
+    /// Character n-grams inside each word, padded with a space on both sides,
+    /// so `"cat"` yields `" ca"`, `"cat"`, `"at "` for `n = 3`.
+    CharWordBoundary,
+}
+
+/// Bag-of-n-grams vectorizer: maps documents to count vectors over a fitted vocabulary.
+///
+/// Features are all n-grams with `min_n <= n <= max_n` of the chosen `analyzer`.
+/// Vocabulary entries are sorted, so column `j` is the `j`-th n-gram in sorted order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CountVectorizer {
+    pub analyzer: Analyzer,
+    pub min_n: usize,
+    pub max_n: usize,
+    /// Lowercase documents before extracting n-grams.
+    pub lowercase: bool,
+    /// Emit 1 for present n-grams instead of their counts.
+    pub binary: bool,
+    vocabulary: HashMap<String, usize>,
+}
+
+impl CountVectorizer {
+    /// Creates an unfitted vectorizer. Panics if `min_n == 0` or `min_n > max_n`.
+    pub fn new(analyzer: Analyzer, min_n: usize, max_n: usize) -> Self {
+        assert!(min_n > 0 && min_n <= max_n, "n-gram range must satisfy 0 < min_n <= max_n");
+        CountVectorizer { analyzer, min_n, max_n, lowercase: true, binary: false, vocabulary: HashMap::new() }
+    }
+
+    /// Extracts all n-grams of `document` according to the vectorizer's options.
+    pub fn analyze(&self, document: &str) -> Vec<String> {
+        let document = if self.lowercase { document.to_lowercase() } else { document.to_string() };
+        let mut grams = Vec::new();
+        for n in self.min_n..=self.max_n {
+            match self.analyzer {
+                Analyzer::Word => grams.extend(word_ngrams(&document, n)),
+                Analyzer::Char => grams.extend(char_ngrams(&document, n)),
+                Analyzer::CharWordBoundary => {
+                    for word in document.split_whitespace() {
+                        // Window the padded word directly; `char_ngrams` would trim the padding
+                        let padded: Vec<char> = format!(" {} ", word).chars().collect();
+                        grams.extend(padded.windows(n).map(|w| w.iter().collect::<String>()));
+                    }
+                }
+            }
+        }
+        grams
+    }
+
+    /// Builds the vocabulary from a corpus.
+    pub fn fit(&mut self, documents: &[&str]) {
+        let mut grams: Vec<String> = documents.iter().flat_map(|d| self.analyze(d)).collect();
+        grams.sort();
+        grams.dedup();
+        self.vocabulary = grams.into_iter().enumerate().map(|(i, g)| (g, i)).collect();
+    }
+
+    /// Number of features (vocabulary size).
+    pub fn n_features(&self) -> usize {
+        self.vocabulary.len()
+    }
+
+    /// Column index of an n-gram, if it is in the vocabulary.
+    pub fn feature_index(&self, gram: &str) -> Option<usize> {
+        self.vocabulary.get(gram).copied()
+    }
+
+    /// Converts documents to count vectors. N-grams outside the vocabulary are ignored.
+    pub fn transform<T: Number>(&self, documents: &[&str]) -> Vec<Vec<T>> {
+        documents.iter().map(|document| {
+            let mut row = vec![T::zero(); self.vocabulary.len()];
+            for gram in self.analyze(document) {
+                if let Some(&j) = self.vocabulary.get(&gram) {
+                    row[j] = if self.binary { T::one() } else { row[j] + T::one() };
+                }
+            }
+            row
+        }).collect()
+    }
+
+    /// Fits on `documents` and returns their count vectors.
+    pub fn fit_transform<T: Number>(&mut self, documents: &[&str]) -> Vec<Vec<T>> {
+        self.fit(documents);
+        self.transform(documents)
+    }
+}
@@ -0,0 +1,854 @@
+//! Training-loop utilities and model-selection routines.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use num_traits::{FromPrimitive, ToPrimitive};
+use crate::dataset::{k_fold_indices, select, BatchIter, LastBatch};
+use crate::layers::Layer;
+use crate::loss_fn::{Loss, LossFn};
+use crate::model::{Sequential, Workspace};
+use crate::numbers::{Number, Real};
+use crate::random::Rng;
+
+/// Scores collected for one training-set size of a learning curve.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LearningCurvePoint {
+    /// Number of training samples used.
+    pub train_size: usize,
+    /// Training-set metric, one entry per repeat.
+    pub train_scores: Vec<f64>,
+    /// Validation-set metric, one entry per repeat.
+    pub validation_scores: Vec<f64>,
+}
+
+impl LearningCurvePoint {
+    /// Mean training score across repeats.
+    pub fn mean_train_score(&self) -> f64 {
+        mean(&self.train_scores)
+    }
+
+    /// Mean validation score across repeats.
+    pub fn mean_validation_score(&self) -> f64 {
+        mean(&self.validation_scores)
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Invalid arguments to `try_learning_curve`, detected before any model is trained.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LearningCurveError {
+    /// `n_samples` is zero.
+    NoSamples,
+    /// `repeats` is zero.
+    NoRepeats,
+    /// `fractions[index]` lies outside `(0, 1]` or is NaN.
+    InvalidFraction { index: usize, fraction: f64 },
+}
+
+impl fmt::Display for LearningCurveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LearningCurveError::NoSamples => write!(f, "n_samples must be positive"),
+            LearningCurveError::NoRepeats => write!(f, "repeats must be positive"),
+            LearningCurveError::InvalidFraction { index, fraction } => {
+                write!(f, "fraction {} at index {} does not lie in (0, 1]", fraction, index)
+            }
+        }
+    }
+}
+
+impl Error for LearningCurveError {}
+
+/// Relative tolerance below which `fraction * n_samples` counts as a whole number, so
+/// that e.g. `0.07 * 100 = 7.000000000000001` gives 7 samples rather than 8.
+const TRAIN_SIZE_EPSILON: f64 = 1e-9;
+
+/// `ceil(fraction * n_samples)`, ignoring floating-point error, clamped to `1..=n_samples`.
+fn train_size(fraction: f64, n_samples: usize) -> usize {
+    let exact = fraction * n_samples as f64;
+    let nearest = exact.round();
+    let size = if (exact - nearest).abs() <= TRAIN_SIZE_EPSILON * nearest.max(1.0) { nearest } else { exact.ceil() };
+    (size as usize).clamp(1, n_samples)
+}
+
+/// Generates a **learning curve**: model performance as a function of training-set size.
+///
+/// # Arguments
+/// * `n_samples` - Number of samples in the full training set.
+/// * `fractions` - Training-set fractions to evaluate, each in `(0, 1]`.
+/// * `repeats` - How many random subsets to draw per fraction.
+/// * `seed` - Seed for subset sampling.
+/// * `fit_and_score` - Trains a fresh model on the given training indices and returns
+///   `(train_score, validation_score)`, with validation measured on the caller's held-out set.
+///
+/// # Steps
+/// 1. For each fraction, compute `train_size = ceil(fraction * n_samples)` (at least one
+///    sample), treating products within a relative `1e-9` of a whole number as exact.
+/// 2. For each repeat, shuffle the sample indices and pass the first `train_size` to `fit_and_score`.
+/// 3. Collect the returned scores into one `LearningCurvePoint` per fraction.
+///
+/// # Notes
+/// - A validation score that keeps improving with size suggests more data will help;
+///   train and validation scores that converge to a poor value suggest a bigger model is needed.
+/// - Panics, before calling `fit_and_score`, wherever `try_learning_curve` would return an error.
+pub fn learning_curve<F>(n_samples: usize, fractions: &[f64], repeats: usize, seed: u64, fit_and_score: F) -> Vec<LearningCurvePoint>
+where
+    F: FnMut(&[usize]) -> (f64, f64),
+{
+    try_learning_curve(n_samples, fractions, repeats, seed, fit_and_score).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Like `learning_curve`, but returns an error for invalid arguments. Every argument is
+/// checked before the first call to `fit_and_score`, so no training is wasted on a run
+/// that would fail part-way.
+pub fn try_learning_curve<F>(
+    n_samples: usize,
+    fractions: &[f64],
+    repeats: usize,
+    seed: u64,
+    mut fit_and_score: F,
+) -> Result<Vec<LearningCurvePoint>, LearningCurveError>
+where
+    F: FnMut(&[usize]) -> (f64, f64),
+{
+    if n_samples == 0 {
+        return Err(LearningCurveError::NoSamples);
+    }
+    if repeats == 0 {
+        return Err(LearningCurveError::NoRepeats);
+    }
+    if let Some((index, &fraction)) = fractions.iter().enumerate().find(|(_, f)| !(**f > 0.0 && **f <= 1.0)) {
+        return Err(LearningCurveError::InvalidFraction { index, fraction });
+    }
+
+    let mut rng = Rng::new(seed);
+    let mut indices: Vec<usize> = (0..n_samples).collect();
+    let mut curve = Vec::with_capacity(fractions.len());
+
+    for &fraction in fractions {
+        let train_size = train_size(fraction, n_samples);
+
+        let mut train_scores = Vec::with_capacity(repeats);
+        let mut validation_scores = Vec::with_capacity(repeats);
+        for _ in 0..repeats {
+            rng.shuffle(&mut indices);
+            let (train_score, validation_score) = fit_and_score(&indices[..train_size]);
+            train_scores.push(train_score);
+            validation_scores.push(validation_score);
+        }
+        curve.push(LearningCurvePoint { train_size, train_scores, validation_scores });
+    }
+    Ok(curve)
+}
+
+/// Outcome of training and validating on one cross-validation fold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FoldResult {
+    /// Zero-based fold index.
+    pub fold: usize,
+    pub train_size: usize,
+    pub validation_size: usize,
+    /// Training history of the fold's model.
+    pub history: History,
+    /// `"validation_loss"` plus the metrics returned by the `cross_validate` callback.
+    pub metrics: BTreeMap<String, f64>,
+}
+
+/// Per-fold results of `cross_validate`, with aggregates across folds.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CrossValidation {
+    pub folds: Vec<FoldResult>,
+}
+
+impl CrossValidation {
+    /// Values of the metric `name` for the folds that recorded it.
+    pub fn metric(&self, name: &str) -> Vec<f64> {
+        self.folds.iter().filter_map(|f| f.metrics.get(name).copied()).collect()
+    }
+
+    /// Mean of the metric `name` across folds; `NaN` if no fold recorded it.
+    pub fn mean(&self, name: &str) -> f64 {
+        mean(&self.metric(name))
+    }
+
+    /// Population standard deviation of the metric `name` across folds.
+    pub fn std(&self, name: &str) -> f64 {
+        let values = self.metric(name);
+        let m = mean(&values);
+        mean(&values.iter().map(|v| (v - m).powi(2)).collect::<Vec<_>>()).sqrt()
+    }
+
+    /// Mean validation loss across folds.
+    pub fn mean_validation_loss(&self) -> f64 {
+        self.mean("validation_loss")
+    }
+
+    /// `(mean, std)` of every recorded metric, by name.
+    pub fn summary(&self) -> BTreeMap<String, (f64, f64)> {
+        let names: BTreeSet<&String> = self.folds.iter().flat_map(|f| f.metrics.keys()).collect();
+        names.into_iter().map(|name| (name.clone(), (self.mean(name), self.std(name)))).collect()
+    }
+}
+
+/// Runs **k-fold cross-validation**: trains one fresh model per fold and scores it on the
+/// held-out samples.
+///
+/// # Arguments
+/// * `model_factory` - Builds an untrained model for the given fold index; use a fixed
+///   seed (or clone a template model) so that folds differ only in their data.
+/// * `rows`, `targets` - The full dataset.
+/// * `k` - Number of folds.
+/// * `trainer` - Loss, learning rate and epochs used for every fold; its loss also gives
+///   the recorded `"validation_loss"`, reduced over the outputs like the `train_loss` of
+///   the fold's `History` (see `LossFn::objective`).
+/// * `seed` - Seed for assigning samples to folds (see `dataset::k_fold_indices`).
+/// * `metrics` - Called with the trained model and the validation rows and targets;
+///   returns extra `(name, value)` pairs to record, e.g. accuracy.
+///
+/// # Steps
+/// 1. Shuffle the sample indices and cut them into `k` folds.
+/// 2. For each fold, build a model, fit it on the other `k - 1` folds with `trainer`.
+/// 3. Record the mean loss and the callback metrics on the held-out fold.
+///
+/// # Notes
+/// - Panics if `rows` and `targets` differ in length, or if `k < 2` or `k > rows.len()`.
+pub fn cross_validate<T, M, F>(
+    mut model_factory: M,
+    rows: &[Vec<T>],
+    targets: &[Vec<T>],
+    k: usize,
+    trainer: &Trainer,
+    seed: u64,
+    mut metrics: F,
+) -> CrossValidation
+where
+    T: Real + FromPrimitive + ToPrimitive,
+    M: FnMut(usize) -> Sequential<T>,
+    F: FnMut(&Sequential<T>, &[Vec<T>], &[Vec<T>]) -> Vec<(String, f64)>,
+{
+    assert_eq!(rows.len(), targets.len(), "rows and targets must have the same length");
+    let mut results = CrossValidation::default();
+    for (fold, (train, validation)) in k_fold_indices(rows.len(), k, seed).into_iter().enumerate() {
+        let (train_rows, train_targets) = (select(rows, &train), select(targets, &train));
+        let (val_rows, val_targets) = (select(rows, &validation), select(targets, &validation));
+
+        let mut model = model_factory(fold);
+        let history = trainer.fit(&mut model, &train_rows, &train_targets);
+
+        let mut workspace = Workspace::new();
+        let total = val_rows.iter().zip(val_targets.iter())
+            .map(|(row, target)| LossFn::objective(&trainer.loss, model.forward_with(row, &mut workspace), target).to_f64().unwrap())
+            .sum::<f64>();
+        let mut fold_metrics: BTreeMap<String, f64> = metrics(&model, &val_rows, &val_targets).into_iter().collect();
+        fold_metrics.insert("validation_loss".to_string(), total / val_rows.len() as f64);
+
+        results.folds.push(FoldResult {
+            fold,
+            train_size: train.len(),
+            validation_size: validation.len(),
+            history,
+            metrics: fold_metrics,
+        });
+    }
+    results
+}
+
+/// Loss and metrics recorded after one training epoch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EpochRecord {
+    /// Zero-based epoch index.
+    pub epoch: usize,
+    /// Mean training loss over the epoch.
+    pub train_loss: f64,
+    /// Extra metrics returned by the `Trainer::fit_with` callback, by name.
+    pub metrics: BTreeMap<String, f64>,
+}
+
+/// Per-epoch training history, exportable for plotting.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct History {
+    pub records: Vec<EpochRecord>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        History::default()
+    }
+
+    pub fn push(&mut self, record: EpochRecord) {
+        self.records.push(record);
+    }
+
+    /// Training loss of every epoch, in order.
+    pub fn train_losses(&self) -> Vec<f64> {
+        self.records.iter().map(|r| r.train_loss).collect()
+    }
+
+    /// Values of the metric `name` for the epochs that recorded it.
+    pub fn metric(&self, name: &str) -> Vec<f64> {
+        self.records.iter().filter_map(|r| r.metrics.get(name).copied()).collect()
+    }
+
+    /// Names of all recorded metrics, sorted.
+    pub fn metric_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.records.iter().flat_map(|r| r.metrics.keys().cloned()).collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Writes one row per epoch with header `epoch,train_loss,<metric names...>`.
+    /// Metrics missing from an epoch are left empty.
+    pub fn to_csv<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let names = self.metric_names();
+        let mut writer = csv::Writer::from_path(path)?;
+        let mut header = vec!["epoch".to_string(), "train_loss".to_string()];
+        header.extend(names.iter().cloned());
+        writer.write_record(&header)?;
+        for record in &self.records {
+            let mut row = vec![record.epoch.to_string(), record.train_loss.to_string()];
+            row.extend(names.iter().map(|n| record.metrics.get(n).map_or_else(String::new, |v| v.to_string())));
+            writer.write_record(&row)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Writes the history as JSON.
+    pub fn to_json<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        serde_json::to_writer_pretty(BufWriter::new(File::create(path)?), self)?;
+        Ok(())
+    }
+}
+
+/// Mini-batch SGD training loop for `Sequential` models that records a `History`.
+/// By default every batch is a single sample.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Trainer {
+    pub loss: Loss,
+    pub learning_rate: f64,
+    pub epochs: usize,
+    /// Seed for reshuffling the samples before every epoch; `None` keeps the given order.
+    pub shuffle_seed: Option<u64>,
+    /// Check every step for NaN or infinite values; see `Trainer::detect_anomaly`.
+    #[serde(default)]
+    pub detect_anomaly: bool,
+    /// Samples per SGD step; see `Trainer::batch_size`.
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    /// Handling of the final, smaller batch of every epoch; see `Trainer::last_batch`.
+    #[serde(default)]
+    pub last_batch: LastBatch,
+}
+
+fn default_batch_size() -> usize {
+    1
+}
+
+impl Trainer {
+    pub fn new(loss: Loss, learning_rate: f64, epochs: usize) -> Self {
+        Trainer { loss, learning_rate, epochs, shuffle_seed: None, detect_anomaly: false, batch_size: 1, last_batch: LastBatch::Smaller }
+    }
+
+    /// Accumulates the gradients of `batch_size` consecutive samples (in the shuffled
+    /// order) and takes one step with their mean; the last batch of an epoch may be
+    /// smaller (see `last_batch`). Panics if `batch_size == 0`.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        assert!(batch_size > 0, "batch_size must be positive");
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Sets what happens to the final batch of an epoch when the samples do not divide
+    /// into `batch_size`.
+    ///
+    /// # Notes
+    /// - Gradients are always averaged over the true batch size, so `LastBatch::Pad` trains
+    ///   exactly like `LastBatch::Smaller` (the padding samples are not trained on).
+    /// - With `LastBatch::Drop` the dropped samples change every epoch when shuffling, and
+    ///   the recorded training loss is the mean over the samples trained on.
+    pub fn last_batch(mut self, last_batch: LastBatch) -> Self {
+        self.last_batch = last_batch;
+        self
+    }
+
+    /// Reshuffles the samples before every epoch.
+    pub fn shuffle(mut self, seed: u64) -> Self {
+        self.shuffle_seed = Some(seed);
+        self
+    }
+
+    /// Checks the layer outputs, the loss and the gradients of every step for NaN or
+    /// infinite values and stops at the first one, instead of training on.
+    ///
+    /// # Notes
+    /// - The check runs before the update, so the model keeps its last finite parameters.
+    /// - `fit` and its variants panic with an `AnomalyError`; `fit_checkpointed` and
+    ///   `resume` return it.
+    /// - Every step runs an extra forward pass, so leave it off once training is stable.
+    pub fn detect_anomaly(mut self, enabled: bool) -> Self {
+        self.detect_anomaly = enabled;
+        self
+    }
+
+    /// Trains `model` on `rows` and `targets` and returns the per-epoch training loss.
+    ///
+    /// Rows and targets can be `Vec`s or fixed-size arrays such as `[T; K]`; each target
+    /// has one entry per model output, so a `K`-output regression trains on `K`-element
+    /// targets (see `Loss::output_reduction` for how they are combined).
+    pub fn fit<T, R, Y>(&self, model: &mut Sequential<T>, rows: &[R], targets: &[Y]) -> History
+    where
+        T: Real + FromPrimitive + ToPrimitive,
+        R: AsRef<[T]>,
+        Y: AsRef<[T]>,
+    {
+        self.fit_with(model, rows, targets, |_| Vec::new())
+    }
+
+    /// Like `fit`, calling `metrics` on the model after every epoch (e.g. to measure
+    /// validation loss or accuracy) and recording the returned `(name, value)` pairs.
+    pub fn fit_with<T, R, Y, F>(&self, model: &mut Sequential<T>, rows: &[R], targets: &[Y], metrics: F) -> History
+    where
+        T: Real + FromPrimitive + ToPrimitive,
+        R: AsRef<[T]>,
+        Y: AsRef<[T]>,
+        F: FnMut(&Sequential<T>) -> Vec<(String, f64)>,
+    {
+        self.fit_observed(model, rows, targets, metrics, &mut [])
+    }
+
+    /// Like `fit`, minimizing `loss` instead of `self.loss`, e.g. a custom `LossFn` or a
+    /// `Box<dyn LossFn<T>>` taken from a `LossRegistry`. The recorded training loss is the
+    /// mean of `loss.objective` over the samples.
+    pub fn fit_with_loss<T, R, Y>(&self, model: &mut Sequential<T>, loss: &dyn LossFn<T>, rows: &[R], targets: &[Y]) -> History
+    where
+        T: Real + FromPrimitive + ToPrimitive,
+        R: AsRef<[T]>,
+        Y: AsRef<[T]>,
+    {
+        let mut state = TrainState::new(self, rows.len());
+        self.run(model, Objective::new(loss), rows, targets, &mut state, |_, _| Ok(()))
+            .unwrap_or_else(|e: Box<dyn Error>| panic!("{}", e));
+        state.history
+    }
+
+    /// Like `fit`, scaling the loss and gradients of sample `i` by `sample_weights[i]`
+    /// (survey or importance weights).
+    ///
+    /// # Notes
+    /// - The recorded training loss is `sum_i w_i l_i / n`, so all-ones weights
+    ///   reproduce `fit` exactly, as with `Weighting::PerSample`.
+    /// - Panics if `sample_weights.len() != rows.len()`.
+    pub fn fit_weighted<T, R, Y>(&self, model: &mut Sequential<T>, rows: &[R], targets: &[Y], sample_weights: &[T]) -> History
+    where
+        T: Real + FromPrimitive + ToPrimitive,
+        R: AsRef<[T]>,
+        Y: AsRef<[T]>,
+    {
+        let mut state = TrainState::new(self, rows.len());
+        let objective = Objective { loss: &self.loss, sample_weights: Some(sample_weights) };
+        self.run(model, objective, rows, targets, &mut state, |_, _| Ok(()))
+            .unwrap_or_else(|e: Box<dyn Error>| panic!("{}", e));
+        state.history
+    }
+
+    /// Like `fit_with`, reporting every epoch to `observers` (progress display, logging).
+    pub fn fit_observed<T, R, Y, F>(
+        &self,
+        model: &mut Sequential<T>,
+        rows: &[R],
+        targets: &[Y],
+        mut metrics: F,
+        observers: &mut [&mut dyn TrainingObserver],
+    ) -> History
+    where
+        T: Real + FromPrimitive + ToPrimitive,
+        R: AsRef<[T]>,
+        Y: AsRef<[T]>,
+        F: FnMut(&Sequential<T>) -> Vec<(String, f64)>,
+    {
+        let mut state = TrainState::new(self, rows.len());
+        let epochs = self.epochs;
+        let report = |model: &Sequential<T>, state: &mut TrainState| {
+            let record = state.history.records.last_mut().unwrap();
+            record.metrics = metrics(model).into_iter().collect();
+            for observer in observers.iter_mut() {
+                observer.on_epoch_end(record, epochs);
+            }
+            Ok(())
+        };
+        self.run(model, Objective::new(&self.loss), rows, targets, &mut state, report)
+            .unwrap_or_else(|e: Box<dyn Error>| panic!("{}", e));
+        for observer in observers.iter_mut() {
+            observer.on_train_end(&state.history);
+        }
+        state.history
+    }
+
+    /// Like `fit`, writing a `Checkpoint` to `path` after every `every` epochs and after
+    /// the last one, so an interrupted run can continue with `Trainer::resume`.
+    ///
+    /// # Notes
+    /// - The checkpoint is written to a temporary file next to `path` and then renamed,
+    ///   so an interruption while saving leaves the previous checkpoint intact.
+    /// - Panics if `every == 0`.
+    pub fn fit_checkpointed<T, R, Y, P>(&self, model: &mut Sequential<T>, rows: &[R], targets: &[Y], path: P, every: usize) -> Result<History, Box<dyn Error>>
+    where
+        T: Real + FromPrimitive + ToPrimitive + Serialize + DeserializeOwned,
+        R: AsRef<[T]>,
+        Y: AsRef<[T]>,
+        P: AsRef<Path>,
+    {
+        let mut state = TrainState::new(self, rows.len());
+        self.run_checkpointed(model, rows, targets, &mut state, path.as_ref(), every)?;
+        Ok(state.history)
+    }
+
+    /// Continues a run started with `fit_checkpointed` from the checkpoint at `path`.
+    ///
+    /// # Arguments
+    /// * `path` - Checkpoint file; it keeps being updated as training continues.
+    /// * `rows`, `targets` - The same training data as the original run.
+    /// * `every` - Checkpoint interval for the remaining epochs.
+    ///
+    /// # Returns
+    /// * `(model, history)` - The trained model and the history of all epochs, including
+    ///   those run before the interruption. Shuffling continues from the saved random state,
+    ///   so the result equals an uninterrupted run.
+    pub fn resume<T, P>(path: P, rows: &[Vec<T>], targets: &[Vec<T>], every: usize) -> Result<(Sequential<T>, History), Box<dyn Error>>
+    where
+        T: Real + FromPrimitive + ToPrimitive + Serialize + DeserializeOwned,
+        P: AsRef<Path>,
+    {
+        let Checkpoint { trainer, mut model, state } = Checkpoint::load(&path)?;
+        if state.order.len() != rows.len() {
+            return Err(format!("checkpoint was saved for {} samples, got {}", state.order.len(), rows.len()).into());
+        }
+        let mut state = state;
+        trainer.run_checkpointed(&mut model, rows, targets, &mut state, path.as_ref(), every)?;
+        Ok((model, state.history))
+    }
+
+    fn run_checkpointed<T, R, Y>(&self, model: &mut Sequential<T>, rows: &[R], targets: &[Y], state: &mut TrainState, path: &Path, every: usize) -> Result<(), Box<dyn Error>>
+    where
+        T: Real + FromPrimitive + ToPrimitive + Serialize + DeserializeOwned,
+        R: AsRef<[T]>,
+        Y: AsRef<[T]>,
+    {
+        assert!(every > 0, "every must be positive");
+        let epochs = self.epochs;
+        self.run(model, Objective::new(&self.loss), rows, targets, state, |model, state| {
+            if state.epoch.is_multiple_of(every) || state.epoch == epochs {
+                Checkpoint { trainer: *self, model: model.clone(), state: state.clone() }.save(path)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Runs the remaining epochs of `state`, calling `after_epoch` once each is recorded
+    /// (it may fill in the record's metrics).
+    fn run<T, R, Y, C>(
+        &self,
+        model: &mut Sequential<T>,
+        objective: Objective<'_, T>,
+        rows: &[R],
+        targets: &[Y],
+        state: &mut TrainState,
+        mut after_epoch: C,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        T: Real + FromPrimitive + ToPrimitive,
+        R: AsRef<[T]>,
+        Y: AsRef<[T]>,
+        C: FnMut(&Sequential<T>, &mut TrainState) -> Result<(), Box<dyn Error>>,
+    {
+        assert_eq!(rows.len(), targets.len(), "rows and targets must have the same length");
+        if let Some(weights) = objective.sample_weights {
+            assert_eq!(weights.len(), rows.len(), "sample_weights must have one entry per row");
+        }
+        let learning_rate: T = T::to_number(self.learning_rate);
+        while state.epoch < self.epochs {
+            if let Some(rng) = state.rng.as_mut() {
+                rng.shuffle(&mut state.order);
+            }
+            let (mut total, mut trained) = (T::zero(), 0);
+            for batch in BatchIter::new(&state.order, self.batch_size.max(1), self.last_batch) {
+                model.zero_grad();
+                for &i in batch.real() {
+                    let (row, target) = (rows[i].as_ref(), targets[i].as_ref());
+                    let loss = &objective.sample(i);
+                    total = total + if self.detect_anomaly {
+                        guarded_accumulate(model, row, target, loss)
+                            .map_err(|anomaly| AnomalyError { epoch: state.epoch, sample: i, anomaly })?
+                    } else {
+                        model.accumulate_gradients_with(row, target, loss)
+                    };
+                }
+                model.sgd_step(learning_rate / T::to_number(batch.len as f64));
+                trained += batch.len;
+            }
+            let train_loss = total.to_f64().unwrap() / trained.max(1) as f64;
+            let record = EpochRecord { epoch: state.epoch, train_loss, metrics: BTreeMap::new() };
+            state.history.push(record);
+            state.epoch += 1;
+            after_epoch(model, state)?;
+        }
+        Ok(())
+    }
+}
+
+/// What a `Trainer` run minimizes: a loss with optional per-sample weights.
+struct Objective<'a, T> {
+    loss: &'a dyn LossFn<T>,
+    sample_weights: Option<&'a [T]>,
+}
+
+impl<'a, T: Number> Objective<'a, T> {
+    fn new(loss: &'a dyn LossFn<T>) -> Self {
+        Objective { loss, sample_weights: None }
+    }
+
+    /// The loss of sample `i`; an unweighted sample is scaled by exactly one.
+    fn sample(&self, i: usize) -> SampleWeighted<'a, T> {
+        let weight = self.sample_weights.map_or(T::one(), |weights| weights[i]);
+        SampleWeighted { loss: self.loss, weight }
+    }
+}
+
+/// `weight * loss`, for one sample.
+struct SampleWeighted<'a, T> {
+    loss: &'a dyn LossFn<T>,
+    weight: T,
+}
+
+impl<T: Number> LossFn<T> for SampleWeighted<'_, T> {
+    fn objective(&self, predictions: &[T], targets: &[T]) -> T {
+        self.weight * self.loss.objective(predictions, targets)
+    }
+
+    fn objective_derivative(&self, predictions: &[T], targets: &[T]) -> Vec<T> {
+        self.loss.objective_derivative(predictions, targets).into_iter().map(|d| self.weight * d).collect()
+    }
+}
+
+/// Where a NaN or infinite value first appeared during a training step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anomaly {
+    /// Output `index` of layer `layer` is not finite.
+    Activation { layer: usize, index: usize },
+    /// The loss of the sample is not finite.
+    Loss,
+    /// Gradient of parameter `index` of layer `layer` is not finite, counting the
+    /// parameters in `Layer::params_mut` order (row-major weights, then biases).
+    Gradient { layer: usize, parameter: usize },
+}
+
+/// Error raised by a `Trainer` with `detect_anomaly` enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnomalyError {
+    /// Zero-based epoch of the failing step.
+    pub epoch: usize,
+    /// Index of the failing sample in the training rows.
+    pub sample: usize,
+    pub anomaly: Anomaly,
+}
+
+impl fmt::Display for AnomalyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "non-finite value in epoch {}, sample {}: ", self.epoch, self.sample)?;
+        match self.anomaly {
+            Anomaly::Activation { layer, index } => write!(f, "output {} of layer {}", index, layer),
+            Anomaly::Loss => write!(f, "loss"),
+            Anomaly::Gradient { layer, parameter } => write!(f, "gradient of parameter {} of layer {}", parameter, layer),
+        }
+    }
+}
+
+impl Error for AnomalyError {}
+
+fn is_finite<T: ToPrimitive>(value: T) -> bool {
+    value.to_f64().is_some_and(f64::is_finite)
+}
+
+/// `Sequential::accumulate_gradients_with` that checks outputs, loss and gradients.
+fn guarded_accumulate<T>(model: &mut Sequential<T>, inputs: &[T], targets: &[T], loss: &dyn LossFn<T>) -> Result<T, Anomaly>
+where
+    T: Real + FromPrimitive + ToPrimitive,
+{
+    // Step 1: Forward pass, layer by layer
+    let mut values = inputs.to_vec();
+    for (layer, l) in model.layers.iter().enumerate() {
+        values = l.forward(&values);
+        if let Some(index) = values.iter().position(|&v| !is_finite(v)) {
+            return Err(Anomaly::Activation { layer, index });
+        }
+    }
+
+    // Step 2: Loss and gradients, added to those of the batch so far
+    let value = model.accumulate_gradients_with(inputs, targets, loss);
+    if !is_finite(value) {
+        return Err(Anomaly::Loss);
+    }
+    for (layer, l) in model.layers.iter_mut().enumerate() {
+        if let Some(parameter) = l.params_mut().into_iter().position(|(_, grad)| !is_finite(grad)) {
+            return Err(Anomaly::Gradient { layer, parameter });
+        }
+    }
+    Ok(value)
+}
+
+/// Progress of a training run: everything besides the model needed to continue it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrainState {
+    /// Number of completed epochs.
+    pub epoch: usize,
+    /// Shuffling generator, advanced by every completed epoch; `None` without shuffling.
+    pub rng: Option<Rng>,
+    /// Current sample order (each epoch reshuffles the previous one).
+    pub order: Vec<usize>,
+    pub history: History,
+}
+
+impl TrainState {
+    fn new(trainer: &Trainer, n_samples: usize) -> Self {
+        TrainState { epoch: 0, rng: trainer.shuffle_seed.map(Rng::new), order: (0..n_samples).collect(), history: History::new() }
+    }
+}
+
+/// Everything needed to continue an interrupted `Trainer` run, saved as JSON.
+///
+/// `Trainer` runs plain SGD, whose only optimizer state is the learning rate stored in
+/// `trainer`; there is no learning-rate schedule to save.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint<T: Number> {
+    pub trainer: Trainer,
+    pub model: Sequential<T>,
+    pub state: TrainState,
+}
+
+impl<T: Number + Serialize + DeserializeOwned> Checkpoint<T> {
+    /// Writes the checkpoint as JSON, replacing `path` atomically.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let path = path.as_ref();
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+        serde_json::to_writer(&mut writer, self)?;
+        writer.flush()?;
+        drop(writer);
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Reads a checkpoint written by `save` and checks the model's layer shapes.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let checkpoint: Checkpoint<T> = serde_json::from_reader(io::BufReader::new(File::open(path)?))?;
+        checkpoint.model.check_shapes()?;
+        Ok(checkpoint)
+    }
+}
+
+/// Receives training events from `Trainer::fit_observed`.
+pub trait TrainingObserver {
+    /// Called after every epoch with its record and the total number of epochs.
+    fn on_epoch_end(&mut self, record: &EpochRecord, epochs: usize);
+
+    /// Called once after the last epoch.
+    fn on_train_end(&mut self, _history: &History) {}
+}
+
+/// Formats the loss and metrics of a record as `train_loss=... name=...`.
+fn format_record(record: &EpochRecord) -> String {
+    let mut out = format!("train_loss={:.6}", record.train_loss);
+    for (name, value) in &record.metrics {
+        out.push_str(&format!(" {}={:.6}", name, value));
+    }
+    out
+}
+
+/// Single-line progress display, redrawn in place with `\r`:
+/// `[##########          ] 50/100 train_loss=0.123456`.
+///
+/// Redraws at most `max_redraws` times per run so long runs do not flood the terminal.
+pub struct ProgressBar<W: Write> {
+    writer: W,
+    pub width: usize,
+    pub max_redraws: usize,
+}
+
+impl ProgressBar<io::Stderr> {
+    /// Progress bar drawn on standard error.
+    pub fn stderr() -> Self {
+        ProgressBar::new(io::stderr())
+    }
+}
+
+impl<W: Write> ProgressBar<W> {
+    pub fn new(writer: W) -> Self {
+        ProgressBar { writer, width: 30, max_redraws: 100 }
+    }
+
+    /// Consumes the bar and returns the writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> TrainingObserver for ProgressBar<W> {
+    fn on_epoch_end(&mut self, record: &EpochRecord, epochs: usize) {
+        let done = record.epoch + 1;
+        let every = epochs.div_ceil(self.max_redraws.max(1)).max(1);
+        if !done.is_multiple_of(every) && done != epochs {
+            return;
+        }
+        let filled = self.width * done / epochs.max(1);
+        // Progress output is best effort: a closed stream must not abort training
+        let _ = write!(
+            self.writer, "\r[{}{}] {}/{} {}",
+            "#".repeat(filled), " ".repeat(self.width - filled), done, epochs, format_record(record)
+        );
+        let _ = self.writer.flush();
+    }
+
+    fn on_train_end(&mut self, _history: &History) {
+        let _ = writeln!(self.writer);
+    }
+}
+
+/// Emits one `log` record per epoch (target `neuralnet::training`) at `level`, e.g.
+/// `epoch=3/10 train_loss=0.123456 accuracy=0.900000`. `tracing` subscribers receive
+/// them through `tracing-log`.
+#[cfg(feature = "log")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogObserver {
+    pub level: log::Level,
+}
+
+#[cfg(feature = "log")]
+impl LogObserver {
+    pub fn new(level: log::Level) -> Self {
+        LogObserver { level }
+    }
+}
+
+#[cfg(feature = "log")]
+impl TrainingObserver for LogObserver {
+    fn on_epoch_end(&mut self, record: &EpochRecord, epochs: usize) {
+        log::log!(target: "neuralnet::training", self.level, "epoch={}/{} {}", record.epoch + 1, epochs, format_record(record));
+    }
+
+    fn on_train_end(&mut self, history: &History) {
+        if let Some(last) = history.records.last() {
+            log::log!(target: "neuralnet::training", self.level, "training finished: {}", format_record(last));
+        }
+    }
+}
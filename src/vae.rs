@@ -0,0 +1,240 @@
+//! Variational autoencoders.
+//!
+//! A [`VAE`] encodes every sample into the mean and log-variance of a diagonal Gaussian
+//! over a latent space, draws a latent vector from it with the reparameterization trick
+//! (`z = mean + exp(log_variance / 2) * noise`, `noise ~ N(0, 1)`), and decodes `z`
+//! back into a reconstruction. Because the noise is an input rather than part of the
+//! graph, gradients flow through the sampling step into the encoder.
+//!
+//! Training minimizes [`VaeLoss`]: the reconstruction loss plus `beta` times the KL
+//! divergence between the encoded Gaussian and the standard normal prior.
+//!
+//! ```
+//! use neuralnet::loss_fn::Loss;
+//! use neuralnet::model::ModelBuilder;
+//! use neuralnet::random::Rng;
+//! use neuralnet::vae::{VaeLoss, VAE};
+//!
+//! let mut rng = Rng::new(0);
+//! let encoder = ModelBuilder::new(4).dense(8).tanh().build::<f64>().unwrap();
+//! let decoder = ModelBuilder::new(2).dense(8).tanh().dense(4).sigmoid().build::<f64>().unwrap();
+//! let mut vae = VAE::new(encoder, decoder, &mut rng);
+//! let loss = VaeLoss::new(Loss::BinaryCrossEntropy);
+//! let step = vae.train_step(&[1.0, 0.0, 0.0, 1.0], loss, 0.1, &mut rng);
+//! assert!(step.kl >= 0.0);
+//! ```
+
+use num_traits::FromPrimitive;
+use serde::{Deserialize, Serialize};
+use crate::back_propagation::backward_pass_with;
+use crate::backend::Naive;
+use crate::layers::{Layer, LayerError};
+use crate::loss_fn::Loss;
+use crate::model::{Dense, Sequential};
+use crate::numbers::{Number, Real};
+use crate::random::Rng;
+
+/// Draws `mean + exp(log_variance / 2) * noise`; with `noise` from a standard normal
+/// this samples `N(mean, exp(log_variance))`.
+pub fn reparameterize<T: Real + FromPrimitive>(mean: &[T], log_variance: &[T], noise: &[T]) -> Vec<T> {
+    assert!(mean.len() == log_variance.len() && mean.len() == noise.len(), "mean, log_variance and noise must have the same length");
+    let half = T::to_number::<T>(0.5);
+    mean.iter().zip(log_variance.iter()).zip(noise.iter())
+        .map(|((&m, &v), &e)| m + (half * v).exp() * e)
+        .collect()
+}
+
+/// KL divergence of `N(mean, exp(log_variance))` from the standard normal:
+/// `-1/2 sum(1 + log_variance - mean^2 - exp(log_variance))`.
+pub fn kl_divergence_standard_normal<T: Real + FromPrimitive>(mean: &[T], log_variance: &[T]) -> T {
+    assert_eq!(mean.len(), log_variance.len(), "mean and log_variance must have the same length");
+    let half = T::to_number::<T>(0.5);
+    mean.iter().zip(log_variance.iter())
+        .fold(T::zero(), |acc, (&m, &v)| acc + half * (m * m + v.exp() - T::one() - v))
+}
+
+/// Gradients of `kl_divergence_standard_normal` with respect to `mean` and `log_variance`.
+pub fn kl_divergence_standard_normal_derivative<T: Real + FromPrimitive>(mean: &[T], log_variance: &[T]) -> (Vec<T>, Vec<T>) {
+    assert_eq!(mean.len(), log_variance.len(), "mean and log_variance must have the same length");
+    let half = T::to_number::<T>(0.5);
+    (mean.to_vec(), log_variance.iter().map(|&v| half * (v.exp() - T::one())).collect())
+}
+
+/// Objective of a `VAE`: `reconstruction(decoded, inputs) + beta * KL`.
+///
+/// The reconstruction loss is reduced over the outputs with `Loss::output_reduction`, as
+/// in `Sequential::accumulate_gradients`. `beta = 1` is the evidence lower bound; larger
+/// values trade reconstruction quality for a more regular latent space (beta-VAE).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VaeLoss {
+    pub reconstruction: Loss,
+    pub beta: f64,
+}
+
+impl VaeLoss {
+    pub fn new(reconstruction: Loss) -> Self {
+        VaeLoss { reconstruction, beta: 1.0 }
+    }
+
+    pub fn with_beta(mut self, beta: f64) -> Self {
+        self.beta = beta;
+        self
+    }
+}
+
+/// The two terms of a `VaeLoss` for one sample (or averaged over an epoch).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VaeStep<T> {
+    pub reconstruction: T,
+    pub kl: T,
+    /// `reconstruction + beta * kl`.
+    pub total: T,
+}
+
+/// Variational autoencoder: `encoder` maps an input to a hidden representation, the
+/// `mean` and `log_variance` heads map it to the latent Gaussian, and `decoder` maps a
+/// latent vector back to the input space.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VAE<T: Number> {
+    pub encoder: Sequential<T>,
+    pub mean: Dense<T>,
+    pub log_variance: Dense<T>,
+    pub decoder: Sequential<T>,
+}
+
+impl<T: Real + FromPrimitive> VAE<T> {
+    /// Creates a VAE whose latent size is `decoder.input_dim()`, with Glorot-uniform heads
+    /// on top of `encoder`. Panics if the decoder output does not match the encoder input;
+    /// see `try_new`.
+    pub fn new(encoder: Sequential<T>, decoder: Sequential<T>, rng: &mut Rng) -> Self {
+        Self::try_new(encoder, decoder, rng).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Fallible version of `new`.
+    pub fn try_new(encoder: Sequential<T>, decoder: Sequential<T>, rng: &mut Rng) -> Result<Self, LayerError> {
+        if decoder.output_dim() != encoder.input_dim() {
+            return Err(LayerError::WrongLength { expected: encoder.input_dim(), found: decoder.output_dim() });
+        }
+        let (hidden, latent) = (encoder.output_dim(), decoder.input_dim());
+        let mean = Dense::glorot_uniform(hidden, latent, rng);
+        // A zero log-variance head starts every sample at unit variance
+        let log_variance = Dense::new(vec![vec![T::zero(); hidden]; latent], vec![T::zero(); latent]);
+        Ok(VAE { encoder, mean, log_variance, decoder })
+    }
+
+    pub fn input_dim(&self) -> usize {
+        self.encoder.input_dim()
+    }
+
+    pub fn latent_dim(&self) -> usize {
+        self.decoder.input_dim()
+    }
+
+    /// Mean and log-variance of the latent Gaussian for `inputs`.
+    pub fn encode(&self, inputs: &[T]) -> (Vec<T>, Vec<T>) {
+        let hidden = self.encoder.forward(inputs);
+        (self.mean.forward(&hidden), self.log_variance.forward(&hidden))
+    }
+
+    pub fn decode(&self, latent: &[T]) -> Vec<T> {
+        self.decoder.forward(latent)
+    }
+
+    /// Deterministic reconstruction: decodes the latent mean.
+    pub fn reconstruct(&self, inputs: &[T]) -> Vec<T> {
+        self.decode(&self.encode(inputs).0)
+    }
+
+    /// Stochastic forward pass: encodes, samples a latent vector and decodes it.
+    pub fn forward(&self, inputs: &[T], rng: &mut Rng) -> Vec<T> {
+        let (mean, log_variance) = self.encode(inputs);
+        self.decode(&reparameterize(&mean, &log_variance, &self.noise(rng)))
+    }
+
+    /// Generates a new sample by decoding a draw from the standard normal prior.
+    pub fn sample(&self, rng: &mut Rng) -> Vec<T> {
+        self.decode(&self.noise(rng))
+    }
+
+    fn noise(&self, rng: &mut Rng) -> Vec<T> {
+        (0..self.latent_dim()).map(|_| T::to_number(rng.next_normal())).collect()
+    }
+
+    /// Backpropagates `loss` for one sample through the decoder, the sampling step and
+    /// the encoder, adding the parameter gradients to every layer (see `zero_grad` and
+    /// `sgd_step`). The sampling noise is drawn from `rng`.
+    pub fn accumulate_gradients(&mut self, inputs: &[T], loss: VaeLoss, rng: &mut Rng) -> VaeStep<T> {
+        assert_eq!(inputs.len(), self.input_dim(), "inputs must match the model input size");
+        let noise = self.noise(rng);
+        let beta = T::to_number::<T>(loss.beta);
+        let half = T::to_number::<T>(0.5);
+        let VAE { encoder, mean: mean_head, log_variance: log_variance_head, decoder } = self;
+        let mut step = VaeStep { reconstruction: T::zero(), kl: T::zero(), total: T::zero() };
+
+        backward_pass_with(&mut encoder.layers, inputs, |hidden| {
+            // Step 1: Latent Gaussian and sample
+            let mean = mean_head.forward(hidden);
+            let log_variance = log_variance_head.forward(hidden);
+            let latent = reparameterize(&mean, &log_variance, &noise);
+
+            // Step 2: Decoder and reconstruction loss, back to the latent sample
+            let reduction = loss.reconstruction.output_reduction();
+            let (reconstruction, grad_latent) = backward_pass_with(&mut decoder.layers, &latent, |outputs| {
+                let value = loss.reconstruction.forward_reduced(outputs, inputs, reduction).scalar().unwrap();
+                (value, loss.reconstruction.derivative_reduced(outputs, inputs, reduction))
+            });
+
+            // Step 3: Through the sampling step, plus the KL term
+            let kl = kl_divergence_standard_normal(&mean, &log_variance);
+            let (kl_mean, kl_log_variance) = kl_divergence_standard_normal_derivative(&mean, &log_variance);
+            let grad_mean: Vec<T> = grad_latent.iter().zip(kl_mean.iter()).map(|(&g, &k)| g + beta * k).collect();
+            let grad_log_variance: Vec<T> = grad_latent.iter().zip(log_variance.iter()).zip(noise.iter()).zip(kl_log_variance.iter())
+                .map(|(((&g, &v), &e), &k)| g * half * e * (half * v).exp() + beta * k)
+                .collect();
+
+            // Step 4: Both heads feed the gradient back to the encoder output
+            let from_mean = mean_head.backward_on(&Naive, hidden, &grad_mean);
+            let from_log_variance = log_variance_head.backward_on(&Naive, hidden, &grad_log_variance);
+            step = VaeStep { reconstruction, kl, total: reconstruction + beta * kl };
+            (step.total, from_mean.iter().zip(from_log_variance.iter()).map(|(&a, &b)| a + b).collect())
+        });
+        step
+    }
+
+    pub fn zero_grad(&mut self) {
+        self.encoder.zero_grad();
+        self.mean.zero_grad();
+        self.log_variance.zero_grad();
+        self.decoder.zero_grad();
+    }
+
+    /// Applies the accumulated gradients, `w -= learning_rate * grad`.
+    pub fn sgd_step(&mut self, learning_rate: T) {
+        self.encoder.sgd_step(learning_rate);
+        self.decoder.sgd_step(learning_rate);
+        for head in [&mut self.mean, &mut self.log_variance] {
+            for (param, grad) in head.params_mut() {
+                *param = *param - grad * learning_rate;
+            }
+        }
+    }
+
+    /// One SGD step on a single sample. Returns the loss terms before the update.
+    pub fn train_step(&mut self, inputs: &[T], loss: VaeLoss, learning_rate: T, rng: &mut Rng) -> VaeStep<T> {
+        self.zero_grad();
+        let step = self.accumulate_gradients(inputs, loss, rng);
+        self.sgd_step(learning_rate);
+        step
+    }
+
+    /// One pass of `train_step` over `rows` in order. Returns the mean loss terms.
+    pub fn train_epoch<R: AsRef<[T]>>(&mut self, rows: &[R], loss: VaeLoss, learning_rate: T, rng: &mut Rng) -> VaeStep<T> {
+        let mut sum = VaeStep { reconstruction: T::zero(), kl: T::zero(), total: T::zero() };
+        for row in rows {
+            let step = self.train_step(row.as_ref(), loss, learning_rate, rng);
+            sum = VaeStep { reconstruction: sum.reconstruction + step.reconstruction, kl: sum.kl + step.kl, total: sum.total + step.total };
+        }
+        let n = T::to_number::<T>(rows.len().max(1) as f64);
+        VaeStep { reconstruction: sum.reconstruction / n, kl: sum.kl / n, total: sum.total / n }
+    }
+}
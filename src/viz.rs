@@ -0,0 +1,196 @@
+//! Plots for inspecting training results, rendered with `plotters`.
+//!
+//! Enabled by the `viz` feature. The output format follows the file extension: `.svg`
+//! or `.png`. Text (titles, axis labels) is only drawn in SVG output, since bitmap text
+//! would need a system font.
+
+use std::error::Error;
+use std::path::Path;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use crate::training::History;
+
+/// Size of every rendered image, in pixels.
+pub const PLOT_SIZE: (u32, u32) = (640, 480);
+
+/// Class and series colours, cycled when there are more.
+const PALETTE: [RGBColor; 6] = [
+    RGBColor(31, 119, 180),
+    RGBColor(255, 127, 14),
+    RGBColor(44, 160, 44),
+    RGBColor(214, 39, 40),
+    RGBColor(148, 103, 189),
+    RGBColor(140, 86, 75),
+];
+
+fn color(index: usize) -> RGBColor {
+    PALETTE[index % PALETTE.len()]
+}
+
+/// A figure that can be drawn on any `plotters` backend.
+trait Figure {
+    fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>, text: bool) -> Result<(), Box<dyn Error>>
+    where
+        DB::ErrorType: 'static;
+}
+
+/// Renders `figure` to `path` with the backend chosen by the extension.
+fn render<F: Figure>(figure: &F, path: &Path) -> Result<(), Box<dyn Error>> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("svg") => {
+            let root = SVGBackend::new(path, PLOT_SIZE).into_drawing_area();
+            figure.draw(&root, true)?;
+            root.present()?;
+        }
+        Some("png") => {
+            let root = BitMapBackend::new(path, PLOT_SIZE).into_drawing_area();
+            figure.draw(&root, false)?;
+            root.present()?;
+        }
+        _ => return Err(format!("{}: unsupported plot format (use .svg or .png)", path.display()).into()),
+    }
+    Ok(())
+}
+
+/// Range `[min, max]` of `values`, widened by 5% on each side and never empty.
+fn padded_range(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    let (min, max) = values.filter(|v| v.is_finite()).fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    if min > max {
+        return (0.0, 1.0);
+    }
+    let pad = if max > min { 0.05 * (max - min) } else { 0.5 };
+    (min - pad, max + pad)
+}
+
+struct LossCurves<'a> {
+    series: &'a [(&'a str, &'a [f64])],
+}
+
+impl Figure for LossCurves<'_> {
+    fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>, text: bool) -> Result<(), Box<dyn Error>>
+    where
+        DB::ErrorType: 'static,
+    {
+        root.fill(&WHITE)?;
+        let epochs = self.series.iter().map(|(_, values)| values.len()).max().unwrap_or(0).max(2);
+        let (y_min, y_max) = padded_range(self.series.iter().flat_map(|(_, values)| values.iter().copied()));
+
+        let mut builder = ChartBuilder::on(root);
+        builder.margin(16);
+        if text {
+            builder.caption("Loss", ("sans-serif", 20)).x_label_area_size(36).y_label_area_size(56);
+        }
+        let mut chart = builder.build_cartesian_2d(0f64..(epochs - 1) as f64, y_min..y_max)?;
+        let mut mesh = chart.configure_mesh();
+        if text {
+            mesh.x_desc("epoch");
+        } else {
+            mesh.disable_x_axis().disable_y_axis();
+        }
+        mesh.draw()?;
+
+        for (i, (name, values)) in self.series.iter().enumerate() {
+            let style = color(i).stroke_width(2);
+            let line = chart.draw_series(LineSeries::new(values.iter().enumerate().map(|(e, &v)| (e as f64, v)), style))?;
+            if text {
+                line.label(*name).legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 16, y)], style));
+            }
+        }
+        if text {
+            chart.configure_series_labels().background_style(WHITE.mix(0.8)).border_style(BLACK).draw()?;
+        }
+        Ok(())
+    }
+}
+
+/// Plots one line per named series of per-epoch values, e.g. training and validation loss.
+///
+/// # Arguments
+/// * `series` - `(name, values)` pairs; value `e` is plotted at epoch `e`.
+/// * `path` - Output file ending in `.svg` or `.png`.
+pub fn plot_loss_curves<P: AsRef<Path>>(series: &[(&str, &[f64])], path: P) -> Result<(), Box<dyn Error>> {
+    render(&LossCurves { series }, path.as_ref())
+}
+
+/// Plots the training loss of `history` together with every recorded metric whose name
+/// contains `"loss"` (e.g. `"validation_loss"` from a `Trainer::fit_with` callback).
+pub fn plot_history<P: AsRef<Path>>(history: &History, path: P) -> Result<(), Box<dyn Error>> {
+    let mut owned = vec![("train_loss".to_string(), history.train_losses())];
+    for name in history.metric_names().into_iter().filter(|n| n.contains("loss")) {
+        let values = history.metric(&name);
+        owned.push((name, values));
+    }
+    let series: Vec<(&str, &[f64])> = owned.iter().map(|(n, v)| (n.as_str(), v.as_slice())).collect();
+    plot_loss_curves(&series, path)
+}
+
+struct DecisionBoundary<'a, F> {
+    classify: F,
+    points: &'a [Vec<f64>],
+    labels: &'a [usize],
+    resolution: usize,
+}
+
+impl<F: Fn(&[f64]) -> usize> Figure for DecisionBoundary<'_, F> {
+    fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>, text: bool) -> Result<(), Box<dyn Error>>
+    where
+        DB::ErrorType: 'static,
+    {
+        root.fill(&WHITE)?;
+        let (x_min, x_max) = padded_range(self.points.iter().map(|p| p[0]));
+        let (y_min, y_max) = padded_range(self.points.iter().map(|p| p[1]));
+
+        let mut builder = ChartBuilder::on(root);
+        builder.margin(16);
+        if text {
+            builder.caption("Decision boundary", ("sans-serif", 20)).x_label_area_size(36).y_label_area_size(48);
+        }
+        let mut chart = builder.build_cartesian_2d(x_min..x_max, y_min..y_max)?;
+        let mut mesh = chart.configure_mesh();
+        mesh.disable_mesh();
+        if !text {
+            mesh.disable_x_axis().disable_y_axis();
+        }
+        mesh.draw()?;
+
+        // Step 1: Colour a grid of cells by the predicted class at their centre
+        let n = self.resolution;
+        let (dx, dy) = ((x_max - x_min) / n as f64, (y_max - y_min) / n as f64);
+        let cells = (0..n).flat_map(|i| (0..n).map(move |j| (i, j))).map(|(i, j)| {
+            let (x, y) = (x_min + i as f64 * dx, y_min + j as f64 * dy);
+            let class = (self.classify)(&[x + dx / 2.0, y + dy / 2.0]);
+            Rectangle::new([(x, y), (x + dx, y + dy)], color(class).mix(0.25).filled())
+        });
+        chart.draw_series(cells)?;
+
+        // Step 2: Overlay the samples in the colour of their true class
+        chart.draw_series(self.points.iter().zip(self.labels.iter()).map(|(p, &label)| {
+            Circle::new((p[0], p[1]), 3, color(label).filled())
+        }))?;
+        Ok(())
+    }
+}
+
+/// Plots the decision regions of a 2D classifier with the labelled samples on top.
+///
+/// # Arguments
+/// * `classify` - Predicted class of a point `[x, y]`, e.g.
+///   `|p| metrics::argmax(&model.forward(p))` for a softmax model.
+/// * `points` - Samples with two features each; they also set the plotted area.
+/// * `labels` - True class of every sample, used for its marker colour.
+/// * `resolution` - Number of grid cells along each axis (e.g. `100`).
+/// * `path` - Output file ending in `.svg` or `.png`.
+///
+/// # Notes
+/// - `classify` is called `resolution^2` times.
+/// - Panics if `points` and `labels` differ in length or a point does not have two features.
+pub fn plot_decision_boundary<F, P>(classify: F, points: &[Vec<f64>], labels: &[usize], resolution: usize, path: P) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(&[f64]) -> usize,
+    P: AsRef<Path>,
+{
+    assert_eq!(points.len(), labels.len(), "points and labels must have the same length");
+    assert!(points.iter().all(|p| p.len() == 2), "decision boundaries need two features per point");
+    assert!(resolution > 0, "resolution must be positive");
+    render(&DecisionBoundary { classify, points, labels, resolution }, path.as_ref())
+}
@@ -0,0 +1,69 @@
+//! JavaScript bindings for browser inference, enabled with the `wasm` feature.
+//!
+//! The crate is built as an `rlib` by default (a `cdylib` would need a panic handler and
+//! a global allocator in `no_std` builds), so ask for the `cdylib` explicitly:
+//!
+//! ```sh
+//! cargo rustc --lib --release --target wasm32-unknown-unknown --features wasm --crate-type cdylib
+//! wasm-bindgen --target web target/wasm32-unknown-unknown/release/neuralnet.wasm --out-dir pkg
+//! ```
+//!
+//! and use from JavaScript:
+//!
+//! ```js
+//! const model = WasmModel.fromJson(json);      // JSON of a `Sequential<f64>`
+//! const output = model.predict(new Float64Array([0.1, 0.2, 0.3, 0.4]));
+//! ```
+
+use js_sys::Float64Array;
+use wasm_bindgen::prelude::*;
+use crate::model::Sequential;
+
+/// A `Sequential<f64>` model exposed to JavaScript.
+#[wasm_bindgen]
+pub struct WasmModel {
+    model: Sequential<f64>,
+}
+
+#[wasm_bindgen]
+impl WasmModel {
+    /// Loads a model from the JSON produced by `serde_json::to_string(&sequential)`.
+    /// Fails if the JSON is malformed or the layer shapes do not fit together.
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(json: &str) -> Result<WasmModel, JsValue> {
+        let model: Sequential<f64> = serde_json::from_str(json).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        model.check_shapes().map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(WasmModel { model })
+    }
+
+    #[wasm_bindgen(getter, js_name = inputDim)]
+    pub fn input_dim(&self) -> usize {
+        self.model.input_dim()
+    }
+
+    #[wasm_bindgen(getter, js_name = outputDim)]
+    pub fn output_dim(&self) -> usize {
+        self.model.output_dim()
+    }
+
+    /// Runs one sample through the model.
+    pub fn predict(&self, inputs: &Float64Array) -> Result<Float64Array, JsValue> {
+        let inputs = inputs.to_vec();
+        if inputs.len() != self.model.input_dim() {
+            return Err(JsValue::from_str("inputs must match the model input size"));
+        }
+        Ok(Float64Array::from(self.model.forward(&inputs).as_slice()))
+    }
+
+    /// Runs a row-major batch (`n_rows * inputDim` values) and returns the outputs row-major.
+    #[wasm_bindgen(js_name = predictBatch)]
+    pub fn predict_batch(&self, inputs: &Float64Array) -> Result<Float64Array, JsValue> {
+        let inputs = inputs.to_vec();
+        let dim = self.model.input_dim();
+        if dim == 0 || !inputs.len().is_multiple_of(dim) {
+            return Err(JsValue::from_str("batch length must be a multiple of the model input size"));
+        }
+        let outputs: Vec<f64> = inputs.chunks(dim).flat_map(|row| self.model.forward(row)).collect();
+        Ok(Float64Array::from(outputs.as_slice()))
+    }
+}
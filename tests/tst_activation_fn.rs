@@ -112,4 +112,128 @@ mod tests {
         let expected = 1.0 - t * t;
         assert!((act.derivative(x) - expected).abs() < 1e-6);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_activation_derivative_layer() {
+        let inputs = [-1.0f32, 0.0, 2.0];
+        for act in [Activation::Sigmoid, Activation::ReLU, Activation::Tanh] {
+            let d = act.derivative_layer(&inputs);
+            for i in 0..3 {
+                assert_eq!(d[i], act.derivative(inputs[i]));
+            }
+        }
+        assert_eq!(Activation::ReLU.derivative_layer(&inputs), [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_activation_derivative_vec() {
+        let inputs = vec![0.0f64, 1.0];
+        let d = Activation::Sigmoid.derivative_vec(&inputs);
+        assert_eq!(d.len(), 2);
+        assert!((d[0] - 0.25).abs() < 1e-12);
+        assert_eq!(d[1], Activation::Sigmoid.derivative(1.0));
+        assert!(Activation::Tanh.derivative_vec::<f64>(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_activation_apply_scalar() {
+        assert_eq!(Activation::ReLU.apply(-3.0f32), 0.0);
+        assert!((Activation::Sigmoid.apply(0.0f64) - 0.5).abs() < 1e-12);
+        assert_eq!(Activation::Tanh.apply(0.5f32), Activation::Tanh.forward(&[0.5f32])[0]);
+    }
+
+    #[test]
+    fn test_softplus() {
+        let act = Activation::Softplus;
+        assert!((act.apply(0.0f64) - 2.0f64.ln()).abs() < 1e-12);
+        assert!((act.apply(1.5f64) - (1.0 + 1.5f64.exp()).ln()).abs() < 1e-12);
+        // large inputs neither overflow nor lose the linear part
+        assert_eq!(act.apply(1000.0f64), 1000.0);
+        assert_eq!(act.apply(-1000.0f64), 0.0);
+        assert!((act.derivative(0.0f32) - 0.5).abs() < 1e-6);
+        let out = softplus_layer(&[-1.0f32, 0.0, 1.0]);
+        assert!(out.iter().all(|v| *v > 0.0));
+    }
+
+    #[test]
+    fn test_hard_sigmoid() {
+        let act = Activation::HardSigmoid;
+        assert_eq!(hard_sigmoid_layer(&[-4.0f32, -3.0, 0.0, 1.5, 3.0, 4.0]), [0.0, 0.0, 0.5, 0.75, 1.0, 1.0]);
+        assert!((act.derivative(0.0f64) - 1.0 / 6.0).abs() < 1e-12);
+        assert_eq!(act.derivative(3.5f64), 0.0);
+        assert_eq!(act.derivative(-3.5f64), 0.0);
+    }
+
+    #[test]
+    fn test_new_activation_derivatives_match_finite_differences() {
+        let h = 1e-6;
+        for act in [Activation::Softplus, Activation::HardSigmoid] {
+            for x in [-2.0f64, -0.3, 0.7, 2.5] {
+                let numeric = (act.apply(x + h) - act.apply(x - h)) / (2.0 * h);
+                assert!((act.derivative(x) - numeric).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_step_activation_on_integers() {
+        assert_eq!(StepActivation::Heaviside.forward(&[-3i32, 0, 5]), [0, 1, 1]);
+        assert_eq!(StepActivation::Sign.forward(&[-3i64, 0, 5]), [-1, 1, 1]);
+        assert_eq!(StepActivation::Heaviside.apply(-0.5f64), 0.0);
+    }
+
+    #[test]
+    fn test_linear_is_identity() {
+        let act = Activation::Linear;
+        assert_eq!(act.forward(&[-2.5f32, 0.0, 3.0]), [-2.5, 0.0, 3.0]);
+        assert_eq!(act.apply(-1e6f64), -1e6);
+        assert_eq!(act.derivative_layer(&[-2.0f64, 0.0, 7.5]), [1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_sigmoid_saturates_without_overflow() {
+        assert_eq!(sigmoid_layer(&[-1000.0f64, 1000.0]), [0.0, 1.0]);
+        assert_eq!(sigmoid_layer(&[-200.0f32, 200.0]), [0.0, 1.0]);
+        let tiny = sigmoid_layer(&[-700.0f64])[0];
+        assert!(tiny > 0.0 && tiny.is_finite());
+        assert!(Activation::Sigmoid.derivative(-1000.0f64).is_finite());
+    }
+
+    #[test]
+    fn test_log_sigmoid_is_stable() {
+        for x in [-3.0f64, -0.5, 0.0, 0.5, 3.0] {
+            assert!((log_sigmoid(x) - sigmoid_layer(&[x])[0].ln()).abs() < 1e-12);
+        }
+        // sigmoid(-1000) underflows to zero, its logarithm does not
+        assert_eq!(log_sigmoid(-1000.0f64), -1000.0);
+        assert_eq!(log_sigmoid(1000.0f64), 0.0);
+        assert!(log_sigmoid(-200.0f32).is_finite());
+    }
+
+    #[test]
+    fn test_logsumexp_is_stable() {
+        let values = [1.0f64, 2.0, 3.0];
+        let naive = values.iter().map(|v| v.exp()).sum::<f64>().ln();
+        assert!((logsumexp(&values) - naive).abs() < 1e-12);
+        // exp(1000) overflows, the shifted sum does not
+        assert!((logsumexp(&[1000.0f64, 1000.0]) - (1000.0 + 2.0f64.ln())).abs() < 1e-9);
+        assert_eq!(logsumexp::<f64>(&[]), f64::NEG_INFINITY);
+        assert_eq!(logsumexp(&[f64::NEG_INFINITY, f64::NEG_INFINITY]), f64::NEG_INFINITY);
+        assert_eq!(logsumexp(&[0.0f32, f32::INFINITY]), f32::INFINITY);
+    }
+
+    #[test]
+    fn test_softmax_and_log_softmax() {
+        let probabilities = softmax(&[1000.0f64, 999.0, -1000.0]);
+        assert!((probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        assert!(probabilities[0] > probabilities[1] && probabilities[2] == 0.0);
+
+        let log_probabilities = log_softmax(&[1000.0f64, 999.0, -1000.0]);
+        assert!((log_probabilities[0] - probabilities[0].ln()).abs() < 1e-12);
+        assert!((log_probabilities[2] + 2000.0 + (1.0 + (-1.0f64).exp()).ln()).abs() < 1e-9);
+
+        let mut values = [0.0f32, 0.0];
+        softmax_in_place(&mut values);
+        assert_eq!(values, [0.5, 0.5]);
+    }
+}
@@ -0,0 +1,176 @@
+#![cfg(feature = "std")]
+
+use neuralnet::attention::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neuralnet::random::Rng;
+
+    const EPSILON: f64 = 1e-6;
+
+    fn random_rows(n: usize, d: usize, rng: &mut Rng) -> Vec<Vec<f64>> {
+        (0..n).map(|_| (0..d).map(|_| rng.next_normal()).collect()).collect()
+    }
+
+    /// `sum(grad * outputs)`, whose gradient with respect to the outputs is `grad`.
+    fn weighted(outputs: &[Vec<f64>], grad: &[Vec<f64>]) -> f64 {
+        outputs.iter().flatten().zip(grad.iter().flatten()).map(|(o, g)| o * g).sum()
+    }
+
+    /// Central-difference gradient of `f` with respect to every entry of `rows`.
+    fn numeric_gradient(rows: &[Vec<f64>], f: impl Fn(&[Vec<f64>]) -> f64) -> Vec<f64> {
+        let mut gradient = Vec::new();
+        for i in 0..rows.len() {
+            for j in 0..rows[i].len() {
+                let (mut plus, mut minus) = (rows.to_vec(), rows.to_vec());
+                plus[i][j] += EPSILON;
+                minus[i][j] -= EPSILON;
+                gradient.push((f(&plus) - f(&minus)) / (2.0 * EPSILON));
+            }
+        }
+        gradient
+    }
+
+    fn assert_close(analytic: &[Vec<f64>], numeric: &[f64]) {
+        for (a, n) in analytic.iter().flatten().zip(numeric.iter()) {
+            assert!((a - n).abs() < 1e-6, "{} != {}", a, n);
+        }
+    }
+
+    #[test]
+    fn test_scaled_dot_product_attention_forward() {
+        // Identical keys give uniform weights, so every output is the mean value
+        let attention = ScaledDotProductAttention::new();
+        let keys = vec![vec![1.0, 0.0]; 2];
+        let values = vec![vec![2.0], vec![4.0]];
+        assert_eq!(attention.forward(&[vec![0.3, -0.4]], &keys, &values), vec![vec![3.0]]);
+
+        let weights = attention.weights(&[vec![2.0_f64.sqrt(), 0.0]], &[vec![1.0, 0.0], vec![0.0, 0.0]]);
+        let e = std::f64::consts::E;
+        assert!((weights[0][0] - e / (e + 1.0)).abs() < 1e-12);
+
+        // The first query of a causal pass sees only the first key
+        let causal = ScaledDotProductAttention::causal().forward(&[vec![1.0, 0.0], vec![0.0, 1.0]], &keys, &values);
+        assert_eq!(causal[0], vec![2.0]);
+        assert_eq!(causal[1], vec![3.0]);
+
+        assert!(attention.try_forward(&[vec![1.0]], &keys, &values).is_err());
+        assert!(attention.try_forward(&[vec![1.0, 0.0]], &keys, &values[..1]).is_err());
+    }
+
+    #[test]
+    fn test_scaled_dot_product_attention_gradients() {
+        let mut rng = Rng::new(3);
+        for attention in [ScaledDotProductAttention::new(), ScaledDotProductAttention::causal()] {
+            let (q, k, v) = (random_rows(3, 4, &mut rng), random_rows(3, 4, &mut rng), random_rows(3, 2, &mut rng));
+            let grad = random_rows(3, 2, &mut rng);
+            let grads = attention.backward(&q, &k, &v, &grad);
+            assert_close(&grads.query, &numeric_gradient(&q, |q| weighted(&attention.forward(q, &k, &v), &grad)));
+            assert_close(&grads.key, &numeric_gradient(&k, |k| weighted(&attention.forward(&q, k, &v), &grad)));
+            assert_close(&grads.value, &numeric_gradient(&v, |v| weighted(&attention.forward(&q, &k, v), &grad)));
+        }
+    }
+
+    #[test]
+    fn test_multi_head_attention_gradients() {
+        let mut rng = Rng::new(5);
+        let mut attention = MultiHeadAttention::<f64>::new(4, 2, &mut rng).with_causal(true);
+        assert_eq!((attention.d_model(), attention.head_dim()), (4, 2));
+        let (queries, keys_values) = (random_rows(3, 4, &mut rng), random_rows(3, 4, &mut rng));
+        let grad = random_rows(3, 4, &mut rng);
+
+        let reference = attention.clone();
+        let (grad_queries, grad_keys_values) = attention.backward(&queries, &keys_values, &grad);
+        assert_close(&grad_queries, &numeric_gradient(&queries, |q| weighted(&reference.forward(q, &keys_values), &grad)));
+        assert_close(&grad_keys_values, &numeric_gradient(&keys_values, |kv| weighted(&reference.forward(&queries, kv), &grad)));
+
+        // Parameter gradients, checked through the query projection weights
+        let numeric = numeric_gradient(&reference.query.weights, |w| {
+            let mut perturbed = reference.clone();
+            perturbed.query.weights = w.to_vec();
+            weighted(&perturbed.forward(&queries, &keys_values), &grad)
+        });
+        assert_close(&attention.query.grads().weights, &numeric);
+    }
+
+    #[test]
+    fn test_multi_head_attention_training_reduces_loss() {
+        let mut rng = Rng::new(9);
+        let mut attention = MultiHeadAttention::<f64>::new(4, 2, &mut rng);
+        let inputs = random_rows(4, 4, &mut rng);
+        let targets = random_rows(4, 4, &mut rng);
+        let loss = |attention: &MultiHeadAttention<f64>| {
+            attention.forward_self(&inputs).iter().flatten().zip(targets.iter().flatten()).map(|(o, t)| (o - t).powi(2)).sum::<f64>()
+        };
+
+        let before = loss(&attention);
+        for _ in 0..50 {
+            let outputs = attention.forward_self(&inputs);
+            let grad: Vec<Vec<f64>> = outputs.iter().zip(targets.iter())
+                .map(|(o, t)| o.iter().zip(t.iter()).map(|(o, t)| 2.0 * (o - t)).collect())
+                .collect();
+            attention.zero_grad();
+            attention.backward(&inputs, &inputs, &grad);
+            attention.sgd_step(0.05);
+        }
+        assert!(loss(&attention) < before * 0.5);
+        assert_eq!(attention.parameters().len(), 8);
+        assert_eq!(attention.parameters()[0].name, "query.weights");
+        assert!(attention.try_forward(&inputs, &[vec![0.0; 3]]).is_err());
+    }
+
+    #[test]
+    fn test_transformer_encoder_block_gradients() {
+        let mut rng = Rng::new(11);
+        let mut block = TransformerEncoderBlock::<f64>::new(4, 2, 6, &mut rng);
+        let inputs = random_rows(3, 4, &mut rng);
+        let grad = random_rows(3, 4, &mut rng);
+
+        let reference = block.clone();
+        let grad_inputs = block.backward(&inputs, &grad);
+        assert_close(&grad_inputs, &numeric_gradient(&inputs, |x| weighted(&reference.forward(x), &grad)));
+
+        let numeric = numeric_gradient(&reference.feedforward_hidden.weights, |w| {
+            let mut perturbed = reference.clone();
+            perturbed.feedforward_hidden.weights = w.to_vec();
+            weighted(&perturbed.forward(&inputs), &grad)
+        });
+        assert_close(&block.feedforward_hidden.grads().weights, &numeric);
+
+        let numeric = numeric_gradient(&reference.attention.value.weights, |w| {
+            let mut perturbed = reference.clone();
+            perturbed.attention.value.weights = w.to_vec();
+            weighted(&perturbed.forward(&inputs), &grad)
+        });
+        assert_close(&block.attention.value.grads().weights, &numeric);
+    }
+
+    #[test]
+    fn test_transformer_encoder_block_trains() {
+        let mut rng = Rng::new(12);
+        let mut block = TransformerEncoderBlock::<f64>::new(4, 2, 8, &mut rng).with_causal(true);
+        let inputs = random_rows(5, 4, &mut rng);
+        let targets = random_rows(5, 4, &mut rng);
+        let loss = |block: &TransformerEncoderBlock<f64>| {
+            block.forward(&inputs).iter().flatten().zip(targets.iter().flatten()).map(|(o, t)| (o - t).powi(2)).sum::<f64>()
+        };
+
+        let before = loss(&block);
+        for _ in 0..100 {
+            let grad: Vec<Vec<f64>> = block.forward(&inputs).iter().zip(targets.iter())
+                .map(|(o, t)| o.iter().zip(t.iter()).map(|(o, t)| 2.0 * (o - t)).collect())
+                .collect();
+            block.zero_grad();
+            block.backward(&inputs, &grad);
+            block.sgd_step(0.02);
+        }
+        assert!(loss(&block) < before * 0.5, "{} -> {}", before, loss(&block));
+
+        let names: Vec<String> = block.parameters().into_iter().map(|p| p.name).collect();
+        assert_eq!(names.len(), 16);
+        assert!(names.contains(&"attention.key.biases".to_string()) && names.contains(&"feedforward_norm.gamma".to_string()));
+        assert_eq!(block.parameters_mut().len(), 16);
+        assert!(block.try_forward(&[vec![0.0; 5]]).is_err());
+    }
+}
@@ -0,0 +1,75 @@
+#![cfg(feature = "std")]
+
+use neuralnet::augmentation::*;
+use neuralnet::random::Rng;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch() -> (Vec<Vec<f64>>, Vec<Vec<f64>>) {
+        let rows = (0..200).map(|i| vec![i as f64, 1.0, -1.0]).collect();
+        let targets = (0..200).map(|i| vec![f64::from(u8::from(i % 2 == 0)), f64::from(u8::from(i % 2 == 1))]).collect();
+        (rows, targets)
+    }
+
+    #[test]
+    fn test_gaussian_noise_has_requested_spread() {
+        let (mut rows, mut targets) = batch();
+        let original = (rows.clone(), targets.clone());
+        GaussianNoise { std: 0.1 }.apply(&mut rows, &mut targets, &mut Rng::new(1));
+        assert_eq!(targets, original.1);
+
+        let diffs: Vec<f64> = rows.iter().flatten().zip(original.0.iter().flatten()).map(|(a, b)| a - b).collect();
+        let mean = diffs.iter().sum::<f64>() / diffs.len() as f64;
+        let std = (diffs.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / diffs.len() as f64).sqrt();
+        assert!(mean.abs() < 0.02);
+        assert!((std - 0.1).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_feature_dropout_zeroes_about_rate_of_features() {
+        let (mut rows, mut targets) = batch();
+        let original = rows.clone();
+        FeatureDropout { rate: 0.25 }.apply(&mut rows, &mut targets, &mut Rng::new(2));
+        let mut dropped = 0;
+        for (row, orig) in rows.iter().zip(original.iter()) {
+            for (&v, &o) in row.iter().zip(orig.iter()) {
+                assert!(v == o || v == 0.0);
+                dropped += usize::from(v == 0.0 && o != 0.0);
+            }
+        }
+        let fraction = dropped as f64 / 599.0;
+        assert!((fraction - 0.25).abs() < 0.05, "{}", fraction);
+    }
+
+    #[test]
+    fn test_mixup_keeps_convex_combinations() {
+        let (mut rows, mut targets) = batch();
+        Mixup { alpha: 0.4 }.apply(&mut rows, &mut targets, &mut Rng::new(3));
+        for (row, target) in rows.iter().zip(targets.iter()) {
+            // Constant columns stay constant, soft labels still sum to one
+            assert!((row[1] - 1.0).abs() < 1e-12 && (row[2] + 1.0).abs() < 1e-12);
+            assert!((target[0] + target[1] - 1.0).abs() < 1e-12);
+            assert!(target.iter().all(|&t| (0.0..=1.0).contains(&t)));
+            assert!((0.0..=199.0).contains(&row[0]));
+        }
+        assert!(targets.iter().any(|t| t[0] > 0.01 && t[0] < 0.99));
+    }
+
+    #[test]
+    fn test_pipeline_is_seeded_and_gathers_batches() {
+        let (rows, targets) = batch();
+        let make = || AugmentationPipeline::new(9).with(GaussianNoise { std: 0.5 }).with(Mixup { alpha: 0.2 });
+        let indices = [4, 0, 7];
+        let (a_rows, a_targets) = make().batch(&rows, &targets, &indices);
+        let (b_rows, b_targets) = make().batch(&rows, &targets, &indices);
+        assert_eq!((a_rows.len(), a_targets.len()), (3, 3));
+        assert_eq!(a_rows, b_rows);
+        assert_eq!(a_targets, b_targets);
+
+        let (plain, plain_targets) = AugmentationPipeline::new(0).batch(&rows, &targets, &indices);
+        assert_eq!(plain, vec![rows[4].clone(), rows[0].clone(), rows[7].clone()]);
+        assert_eq!(plain_targets[2], targets[7]);
+    }
+}
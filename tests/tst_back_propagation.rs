@@ -0,0 +1,108 @@
+#![cfg(feature = "std")]
+
+use neuralnet::back_propagation::*;
+
+/// Property tests: proptest draws the network shape, the loss and a seed for the
+/// sample values, and shrinks a failing case to the smallest network that still fails.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neuralnet::activation_fn::Activation;
+    use neuralnet::loss_fn::Loss;
+    use neuralnet::layers::Layer;
+    use neuralnet::model::{ModelBuilder, ModelLayer, Sequential};
+    use neuralnet::random::Rng;
+    use proptest::prelude::*;
+
+    const EPSILON: f64 = 1e-6;
+    const TOLERANCE: f64 = 1e-5;
+    /// Pre-activations closer than this to the ReLU / PReLU kink make the numerical
+    /// gradient meaningless (a dead ReLU feeding a zero bias lands exactly on it).
+    const KINK_MARGIN: f64 = 1e-4;
+
+    /// A network of 1-3 dense layers with the given hidden widths and activations, one of
+    /// three loss heads, and a sample drawn from `seed`.
+    fn build_case(input_dim: usize, hidden: &[(usize, usize)], head: usize, seed: u64) -> (Sequential<f64>, Loss, Vec<f64>, Vec<f64>) {
+        let mut rng = Rng::new(seed);
+        let mut builder = ModelBuilder::new(input_dim).seed(seed);
+        for &(width, activation) in hidden {
+            builder = builder.dense(width);
+            builder = match activation {
+                0 => builder.activation(Activation::Sigmoid),
+                1 => builder.activation(Activation::Tanh),
+                2 => builder.activation(Activation::Softplus),
+                3 => builder.relu(),
+                _ => builder.prelu(0.1 + rng.next_f64()),
+            };
+        }
+        let (builder, loss, targets) = match head {
+            0 => {
+                let n = 1 + rng.gen_index(3);
+                (builder.dense(n), Loss::MeanSquaredError, (0..n).map(|_| rng.next_normal()).collect())
+            }
+            1 => {
+                let n = 2 + rng.gen_index(3);
+                let class = rng.gen_index(n);
+                let targets = (0..n).map(|k| if k == class { 1.0 } else { 0.0 }).collect();
+                (builder.dense(n).softmax(), Loss::CrossEntropy, targets)
+            }
+            _ => (builder.dense(1).sigmoid(), Loss::BinaryCrossEntropy, vec![rng.next_f64()]),
+        };
+        let inputs = (0..input_dim).map(|_| rng.next_normal()).collect();
+        (builder.build().unwrap(), loss, inputs, targets)
+    }
+
+    /// Whether a ReLU or PReLU input of `model` on `inputs` lies within `KINK_MARGIN` of zero.
+    fn near_kink(model: &Sequential<f64>, inputs: &[f64]) -> bool {
+        let mut x = inputs.to_vec();
+        for layer in &model.layers {
+            let piecewise = matches!(layer, ModelLayer::Activation(Activation::ReLU) | ModelLayer::PReLU(_));
+            if piecewise && x.iter().any(|v| v.abs() < KINK_MARGIN) {
+                return true;
+            }
+            x = layer.forward(&x);
+        }
+        false
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(100))]
+
+        #[test]
+        fn test_backward_pass_matches_numerical_gradients(
+            input_dim in 1usize..5,
+            hidden in prop::collection::vec((1usize..6, 0usize..5), 0..3),
+            head in 0usize..3,
+            seed in any::<u64>(),
+        ) {
+            let (mut model, loss, inputs, targets) = build_case(input_dim, &hidden, head, seed);
+            prop_assume!(!near_kink(&model, &inputs));
+            let check = gradient_check(&mut model.layers, &inputs, &targets, loss, EPSILON);
+            prop_assert_eq!(check.analytic.len(), model.n_parameters());
+            prop_assert!(check.passes(TOLERANCE), "{:?} max error {}", loss, check.max_error());
+        }
+
+        #[test]
+        fn test_gradient_check_leaves_parameters_unchanged(
+            input_dim in 1usize..5,
+            hidden in prop::collection::vec((1usize..6, 0usize..5), 0..3),
+            head in 0usize..3,
+            seed in any::<u64>(),
+        ) {
+            let (mut model, loss, inputs, targets) = build_case(input_dim, &hidden, head, seed);
+            let before = model.clone();
+            gradient_check(&mut model.layers, &inputs, &targets, loss, EPSILON);
+            prop_assert_eq!(model, before);
+        }
+    }
+
+    #[test]
+    fn test_gradient_check_detects_wrong_gradients() {
+        let check = GradientCheck { analytic: vec![1.0, 0.5], numeric: vec![1.0, 0.4], analytic_input: vec![], numeric_input: vec![] };
+        assert!((check.max_error() - 0.1).abs() < 1e-12);
+        assert!(!check.passes(1e-3));
+        // Large gradients are compared relatively
+        let check = GradientCheck { analytic: vec![1000.0], numeric: vec![1000.5], analytic_input: vec![2.0], numeric_input: vec![2.0] };
+        assert!(check.passes(1e-3));
+    }
+}
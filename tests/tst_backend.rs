@@ -0,0 +1,156 @@
+#![cfg(feature = "std")]
+
+use neuralnet::backend::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neuralnet::layers::Layer;
+    use neuralnet::model::{Dense, ModelBuilder, Workspace};
+    use neuralnet::random::Rng;
+
+    fn random_matrix(rows: usize, columns: usize, rng: &mut Rng) -> Vec<Vec<f64>> {
+        (0..rows).map(|_| (0..columns).map(|_| rng.next_normal()).collect()).collect()
+    }
+
+    fn assert_close(a: &[f64], b: &[f64]) {
+        assert_eq!(a.len(), b.len());
+        for (x, y) in a.iter().zip(b.iter()) {
+            assert!((x - y).abs() < 1e-10, "{} != {}", x, y);
+        }
+    }
+
+    #[test]
+    fn test_naive_kernels() {
+        let weights = vec![vec![1.0, 2.0, 3.0], vec![-1.0, 0.5, 0.0]];
+        let mut out = vec![9.0; 7];
+        Naive.matvec(&weights, &[0.5, 1.0], &[1.0, 1.0, 2.0], &mut out);
+        assert_eq!(out, vec![9.5, 0.5]);
+        Naive.matvec_transposed(&weights, &[1.0, 2.0], 3, &mut out);
+        assert_eq!(out, vec![-1.0, 3.0, 3.0]);
+
+        let mut acc = vec![vec![1.0, 0.0], vec![0.0, 0.0]];
+        Naive.add_outer(&mut acc, &[2.0, -1.0], &[1.0, 3.0]);
+        assert_eq!(acc, vec![vec![3.0, 6.0], vec![-1.0, -3.0]]);
+
+        let mut values = [1.0, -2.0];
+        Naive.map_in_place(&mut values, |x: f64| x * x);
+        assert_eq!(values, [1.0, 4.0]);
+    }
+
+    #[test]
+    fn test_unrolled_matches_naive_including_remainders() {
+        let mut rng = Rng::new(4);
+        for columns in [0, 1, 3, 4, 7, 16, 33] {
+            let weights = random_matrix(5, columns, &mut rng);
+            let biases: Vec<f64> = (0..5).map(|_| rng.next_normal()).collect();
+            let inputs: Vec<f64> = (0..columns).map(|_| rng.next_normal()).collect();
+            let grad: Vec<f64> = (0..5).map(|_| rng.next_normal()).collect();
+
+            let (mut naive, mut unrolled) = (Vec::new(), Vec::new());
+            Naive.matvec(&weights, &biases, &inputs, &mut naive);
+            Unrolled.matvec(&weights, &biases, &inputs, &mut unrolled);
+            assert_close(&naive, &unrolled);
+
+            Naive.matvec_transposed(&weights, &grad, columns, &mut naive);
+            Unrolled.matvec_transposed(&weights, &grad, columns, &mut unrolled);
+            assert_close(&naive, &unrolled);
+
+            let (mut naive_acc, mut unrolled_acc) = (weights.clone(), weights.clone());
+            Naive.add_outer(&mut naive_acc, &grad, &inputs);
+            Unrolled.add_outer(&mut unrolled_acc, &grad, &inputs);
+            assert_close(&naive_acc.concat(), &unrolled_acc.concat());
+        }
+    }
+
+    #[test]
+    fn test_layers_on_backends() {
+        let model = ModelBuilder::new(9).seed(2).dense(13).tanh().dense(6).relu().dense(3).softmax().build::<f64>().unwrap();
+        let inputs: Vec<f64> = (0..9).map(|i| i as f64 / 4.0 - 1.0).collect();
+        let mut workspace = Workspace::new();
+        assert_eq!(model.forward_on(&Naive, &inputs, &mut workspace), &model.forward(&inputs)[..]);
+        assert_close(model.forward_on(&Unrolled, &inputs, &mut workspace), &model.forward(&inputs));
+        assert!(model.try_forward_on(&Unrolled, &inputs[1..], &mut workspace).is_err());
+
+        // `backward_on` accumulates the same gradients as `Layer::backward`
+        let mut reference = Dense::new(vec![vec![0.5, -1.0, 2.0, 0.1, 0.3], vec![1.0, 1.0, 0.0, -0.2, 0.4]], vec![0.0, 0.1]);
+        let mut dense = reference.clone();
+        let (x, g) = ([1.0, 2.0, -1.0, 0.5, 3.0], [0.3, -0.7]);
+        let upstream = Layer::backward(&mut reference, &x, &[], &g);
+        assert_close(&dense.backward_on(&Unrolled, &x, &g), &upstream);
+        assert_close(&dense.grads().weights.concat(), &reference.grads().weights.concat());
+        assert_eq!(dense.grads().biases, reference.grads().biases);
+    }
+
+    #[test]
+    fn test_gemm() {
+        // [[1, 2, 3], [4, 5, 6]] * [[1, 0], [0, 1], [1, 1]] added to ones
+        let (a, b) = ([1.0, 2.0, 3.0, 4.0, 5.0, 6.0], [1.0, 0.0, 0.0, 1.0, 1.0, 1.0]);
+        let mut naive = [1.0; 4];
+        Naive.gemm(&a, &b, (2, 3, 2), &mut naive);
+        assert_eq!(naive, [5.0, 6.0, 11.0, 12.0]);
+        let mut unrolled = [1.0; 4];
+        Unrolled.gemm(&a, &b, (2, 3, 2), &mut unrolled);
+        assert_eq!(unrolled, naive);
+
+        // The same product with a stored as its 3 x 2 transpose and b as its 2 x 3 transpose
+        let (a_t, b_t) = ([1.0, 4.0, 2.0, 5.0, 3.0, 6.0], [1.0, 0.0, 1.0, 0.0, 1.0, 1.0]);
+        for backend_output in [
+            { let mut c = [1.0; 4]; Naive.gemm_strided(&a_t, (1, 2), &b_t, (1, 3), (2, 3, 2), &mut c); c },
+            { let mut c = [1.0; 4]; Unrolled.gemm_strided(&a_t, (1, 2), &b_t, (1, 3), (2, 3, 2), &mut c); c },
+            { let mut c = [1.0; 4]; Unrolled.gemm_strided(&a, (3, 1), &b, (2, 1), (2, 3, 2), &mut c); c },
+        ] {
+            assert_eq!(backend_output, naive);
+        }
+    }
+
+    #[cfg(feature = "matrixmultiply")]
+    #[test]
+    fn test_matrixmultiply_matches_naive() {
+        let mut rng = Rng::new(3);
+        let dense = Dense::new(random_matrix(7, 13, &mut rng), (0..7).map(|_| rng.next_normal()).collect());
+        let rows = random_matrix(9, 13, &mut rng);
+        let grads = random_matrix(9, 7, &mut rng);
+        assert_close(&dense.forward_batch_on(&MatrixMultiply, &rows).concat(), &dense.forward_batch_on(&Naive, &rows).concat());
+
+        let (mut fast, mut reference) = (dense.clone(), dense.clone());
+        let upstream = fast.backward_batch_on(&MatrixMultiply, &rows, &grads);
+        assert_close(&upstream.concat(), &reference.backward_batch_on(&Naive, &rows, &grads).concat());
+        assert_close(&fast.grads().weights.concat(), &reference.grads().weights.concat());
+
+        // f32 goes through sgemm, integers fall back to the generic loops
+        let mut c32 = [1.0f32; 4];
+        MatrixMultiply.gemm(&[1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0], &[1.0, 0.0, 0.0, 1.0, 1.0, 1.0], (2, 3, 2), &mut c32);
+        assert_eq!(c32, [5.0, 6.0, 11.0, 12.0]);
+        let mut c_int = [1i32; 4];
+        MatrixMultiply.gemm_strided(&[1, 4, 2, 5, 3, 6], (1, 2), &[1, 0, 0, 1, 1, 1], (2, 1), (2, 3, 2), &mut c_int);
+        assert_eq!(c_int, [5, 6, 11, 12]);
+    }
+
+    #[test]
+    fn test_batched_dense_passes_match_per_row() {
+        let mut rng = Rng::new(8);
+        let dense = Dense::new(random_matrix(6, 9, &mut rng), (0..6).map(|_| rng.next_normal()).collect());
+        let rows = random_matrix(5, 9, &mut rng);
+        let grads = random_matrix(5, 6, &mut rng);
+
+        let mut reference = dense.clone();
+        let upstream: Vec<Vec<f64>> = rows.iter().zip(grads.iter()).map(|(x, g)| Layer::backward(&mut reference, x, &[], g)).collect();
+        for backend_output in [dense.forward_batch_on(&Naive, &rows), dense.forward_batch_on(&Unrolled, &rows)] {
+            for (batched, row) in backend_output.iter().zip(rows.iter()) {
+                assert_close(batched, &dense.forward(row));
+            }
+        }
+
+        let mut batched = dense.clone();
+        let batched_upstream = batched.backward_batch_on(&Unrolled, &rows, &grads);
+        assert_close(&batched_upstream.concat(), &upstream.concat());
+        assert_close(&batched.grads().weights.concat(), &reference.grads().weights.concat());
+        assert_close(&batched.grads().biases, &reference.grads().biases);
+
+        let model = ModelBuilder::new(4).seed(5).dense(8).relu().prelu(0.2).dense(3).softmax().build::<f64>().unwrap();
+        let rows = random_matrix(7, 4, &mut rng);
+        assert_close(&model.predict_on(&Unrolled, &rows).concat(), &model.predict(&rows).concat());
+        assert!(model.predict_on(&Naive, &[]).is_empty());
+    }
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "std")]
+
+use neuralnet::calibration::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neuralnet::random::Rng;
+
+    /// Overconfident scores `sigmoid(3 * logit(q))` for samples whose true probability is `q`.
+    fn overconfident(n: usize, seed: u64) -> (Vec<f64>, Vec<bool>) {
+        let mut rng = Rng::new(seed);
+        let mut scores = Vec::new();
+        let mut targets = Vec::new();
+        for _ in 0..n {
+            let q = 0.05 + 0.9 * rng.next_f64();
+            let logit = (q / (1.0 - q)).ln();
+            scores.push(1.0 / (1.0 + (-3.0 * logit).exp()));
+            targets.push(rng.next_f64() < q);
+        }
+        (scores, targets)
+    }
+
+    #[test]
+    fn test_platt_scaling_recovers_the_temperature() {
+        let (scores, targets) = overconfident(4000, 1);
+        let platt = PlattScaling::fit(&scores, &targets);
+        assert!((platt.a - 1.0 / 3.0).abs() < 0.05, "{:?}", platt);
+        assert!(platt.b.abs() < 0.1, "{:?}", platt);
+
+        let (test_scores, test_targets) = overconfident(2000, 2);
+        let calibrated: Vec<f64> = test_scores.iter().map(|&s| platt.calibrate(s)).collect();
+        let before = expected_calibration_error(&test_scores, &test_targets, 10);
+        let after = expected_calibration_error(&calibrated, &test_targets, 10);
+        assert!(after < before / 2.0, "{} -> {}", before, after);
+        assert!(brier_score(&calibrated, &test_targets) < brier_score(&test_scores, &test_targets));
+
+        // Separable data stays finite thanks to the smoothed targets
+        let separable = PlattScaling::fit(&[0.1, 0.2, 0.8, 0.9], &[false, false, true, true]);
+        assert!(separable.a.is_finite() && separable.b.is_finite());
+    }
+
+    #[test]
+    fn test_isotonic_regression_pools_violators() {
+        let scores = [0.1, 0.2, 0.3, 0.4, 0.5, 0.6];
+        let targets = [false, true, false, false, true, true];
+        let isotonic = IsotonicRegression::fit(&scores, &targets);
+        // [0.1] [0.2, 0.3, 0.4] [0.5, 0.6]
+        assert_eq!(isotonic.values.len(), 3);
+        assert!((isotonic.values[1] - 1.0 / 3.0).abs() < 1e-12);
+        assert!((isotonic.thresholds[1] - 0.3).abs() < 1e-12);
+        assert!(isotonic.values.windows(2).all(|w| w[0] <= w[1]));
+
+        assert_eq!(isotonic.calibrate(0.0), 0.0);
+        assert_eq!(isotonic.calibrate(1.0), 1.0);
+        assert!((isotonic.calibrate(0.3) - 1.0 / 3.0).abs() < 1e-12);
+        assert!((isotonic.calibrate(0.2) - 1.0 / 6.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_calibrator_wraps_predict_proba() {
+        let (scores, targets) = overconfident(3000, 3);
+        let rows: Vec<Vec<f64>> = scores.iter().map(|&s| vec![s]).collect();
+        let predict_proba = |features: &[f64]| features[0];
+        for method in [CalibrationMethod::Platt, CalibrationMethod::Isotonic] {
+            let calibrator = Calibrator::fit(method, &scores, &targets);
+            let calibrated = calibrator.predict_proba(predict_proba, &rows);
+            assert!(expected_calibration_error(&calibrated, &targets, 10) < expected_calibration_error(&scores, &targets, 10) / 2.0);
+            let wrapped = calibrator.wrap(predict_proba);
+            assert_eq!(wrapped(&rows[5]), calibrated[5]);
+            assert!(calibrated.iter().all(|p| (0.0..=1.0).contains(p)));
+        }
+    }
+}
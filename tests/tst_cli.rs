@@ -0,0 +1,68 @@
+#![cfg(feature = "std")]
+
+use std::fs;
+use std::process::Command;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn neuralnet(args: &[&str]) -> std::process::Output {
+        Command::new(env!("CARGO_BIN_EXE_neuralnet")).args(args).output().unwrap()
+    }
+
+    #[test]
+    fn test_train_predict_evaluate() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = dir.path().join("data.csv");
+        let config = dir.path().join("config.json");
+        let model = dir.path().join("model.json");
+        let output = dir.path().join("predictions.csv");
+
+        // y = 1 when x0 + x1 > 1
+        let mut csv = String::from("x0,x1,y\n");
+        for i in 0..10 {
+            for j in 0..10 {
+                let (x0, x1) = (i as f64 / 9.0, j as f64 / 9.0);
+                csv.push_str(&format!("{},{},{}\n", x0, x1, if x0 + x1 > 1.0 { 1 } else { 0 }));
+            }
+        }
+        fs::write(&data, csv).unwrap();
+        fs::write(&config, r#"{
+            "layers": [{"Dense": {"units": 8}}, {"Activation": "Tanh"}, {"Dense": {"units": 1}}, {"Activation": "Sigmoid"}],
+            "loss": "BinaryCrossEntropy",
+            "optimizer": {"Sgd": {"learning_rate": 0.5}},
+            "training": {"epochs": 100, "shuffle": true},
+            "seed": 1
+        }"#).unwrap();
+
+        let run = neuralnet(&["train", "--data", data.to_str().unwrap(), "--config", config.to_str().unwrap(), "--model", model.to_str().unwrap()]);
+        assert!(run.status.success(), "{}", String::from_utf8_lossy(&run.stderr));
+        assert!(model.exists());
+
+        let run = neuralnet(&["evaluate", "--data", data.to_str().unwrap(), "--model", model.to_str().unwrap()]);
+        let stdout = String::from_utf8(run.stdout).unwrap();
+        let accuracy: f64 = stdout.lines().find_map(|l| l.strip_prefix("accuracy: ")).unwrap().parse().unwrap();
+        assert!(accuracy > 0.9, "{}", stdout);
+
+        let inputs = dir.path().join("inputs.csv");
+        fs::write(&inputs, "x0,x1\n0.0,0.0\n1.0,1.0\n").unwrap();
+        let run = neuralnet(&["predict", "--data", inputs.to_str().unwrap(), "--model", model.to_str().unwrap(), "--output", output.to_str().unwrap()]);
+        assert!(run.status.success());
+        let written = fs::read_to_string(&output).unwrap();
+        let lines: Vec<&str> = written.lines().collect();
+        assert_eq!(lines[0], "output_0");
+        assert!(lines[1].parse::<f64>().unwrap() < 0.5);
+        assert!(lines[2].parse::<f64>().unwrap() > 0.5);
+    }
+
+    #[test]
+    fn test_usage_errors() {
+        assert!(!neuralnet(&[]).status.success());
+        let run = neuralnet(&["train", "--data"]);
+        assert!(!run.status.success());
+        assert!(String::from_utf8_lossy(&run.stderr).contains("missing value for --data"));
+        let run = neuralnet(&["fly"]);
+        assert!(String::from_utf8_lossy(&run.stderr).contains("unknown command `fly`"));
+    }
+}
@@ -0,0 +1,105 @@
+#![cfg(feature = "std")]
+
+use neuralnet::config::*;
+use neuralnet::loss_fn::Loss;
+use neuralnet::model::{BuildError, LayerSpec, Sequential};
+use neuralnet::activation_fn::Activation;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOML: &str = r#"
+        input_dim = 4
+        seed = 7
+        layers = [{ Dense = { units = 8 } }, { Activation = "ReLU" }, { Dense = { units = 3 } }, "Softmax"]
+        loss = "CrossEntropy"
+        optimizer = { Sgd = { learning_rate = 0.05 } }
+
+        [training]
+        epochs = 20
+        shuffle = true
+    "#;
+
+    #[test]
+    fn test_toml_and_json_describe_the_same_config() {
+        let from_toml = ModelConfig::from_toml_str(TOML).unwrap();
+        let from_json = ModelConfig::from_json_str(r#"{
+            "input_dim": 4,
+            "seed": 7,
+            "layers": [{"Dense": {"units": 8}}, {"Activation": "ReLU"}, {"Dense": {"units": 3}}, "Softmax"],
+            "loss": "CrossEntropy",
+            "optimizer": {"Sgd": {"learning_rate": 0.05}},
+            "training": {"epochs": 20, "shuffle": true}
+        }"#).unwrap();
+
+        assert_eq!(from_toml, from_json);
+        assert_eq!(from_toml.layers[1], LayerSpec::Activation(Activation::ReLU));
+        assert_eq!(from_toml.loss, Loss::CrossEntropy);
+        assert_eq!(from_toml.output_dim(), Some(3));
+
+        let trainer = from_toml.trainer();
+        assert_eq!(trainer.learning_rate, 0.05);
+        assert_eq!(trainer.epochs, 20);
+        assert_eq!(trainer.shuffle_seed, Some(7));
+        assert_eq!(trainer.batch_size, 1);
+
+        let batched = ModelConfig::from_toml_str(&format!("{}batch_size = 16\n", TOML)).unwrap();
+        assert_eq!(batched.trainer().batch_size, 16);
+    }
+
+    #[test]
+    fn test_from_config_builds_seeded_model() {
+        let dir = tempfile::tempdir().unwrap();
+        let toml_path = dir.path().join("experiment.toml");
+        let json_path = dir.path().join("experiment.json");
+        let config = ModelConfig::from_toml_str(TOML).unwrap();
+        config.save(&toml_path).unwrap();
+        config.save(&json_path).unwrap();
+
+        let a = Sequential::<f64>::from_config(&toml_path).unwrap();
+        let b = Sequential::<f64>::from_config(&json_path).unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.input_dim(), 4);
+        assert_eq!(a.output_dim(), 3);
+        assert_eq!(a, config.build::<f64>().unwrap());
+    }
+
+    #[test]
+    fn test_input_dim_from_data() {
+        let config = ModelConfig::from_json_str(r#"{
+            "layers": [{"Dense": {"units": 1}}],
+            "loss": "MeanSquaredError",
+            "optimizer": {"Sgd": {"learning_rate": 0.1}},
+            "training": {"epochs": 5}
+        }"#).unwrap();
+
+        assert_eq!(config.build::<f64>().unwrap_err(), BuildError::MissingInputDim);
+        assert_eq!(config.build_for_input::<f64>(6).unwrap().input_dim(), 6);
+        assert_eq!(config.trainer().shuffle_seed, None);
+
+        let declared = ModelConfig { input_dim: Some(4), ..config };
+        assert_eq!(
+            declared.build_for_input::<f64>(6).unwrap_err(),
+            BuildError::ShapeMismatch { layer: 0, expected: 6, found: 4 }
+        );
+    }
+
+    #[test]
+    fn test_invalid_configs_are_rejected() {
+        assert!(ModelConfig::from_json_str(r#"{"layers": [], "loss": "MeanSquaredError"}"#).is_err());
+        assert!(ModelConfig::from_toml_str("layers = [\"Conv\"]").is_err());
+        assert!(ModelConfig::load("does/not/exist.toml").is_err());
+    }
+
+    #[test]
+    fn test_config_init_override() {
+        use neuralnet::model::Initializer;
+
+        let text = r#"{"layers": [{"Dense": {"units": 2}}], "loss": "MeanSquaredError",
+            "optimizer": {"Sgd": {"learning_rate": 0.1}}, "training": {"epochs": 1}}"#;
+        assert_eq!(ModelConfig::from_json_str(text).unwrap().init, Initializer::Auto);
+        let text = text.replacen('{', r#"{"init": "HeUniform", "#, 1);
+        assert_eq!(ModelConfig::from_json_str(&text).unwrap().init, Initializer::HeUniform);
+    }
+}
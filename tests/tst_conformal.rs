@@ -0,0 +1,50 @@
+#![cfg(feature = "std")]
+
+use neuralnet::conformal::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conformal_quantile() {
+        let scores: Vec<f64> = (1..=9).map(|i| i as f64).collect();
+        // n = 9, alpha = 0.2: rank = ceil(10 * 0.8) = 8
+        assert_eq!(conformal_quantile(&scores, 0.2), 8.0);
+        // rank = ceil(10 * 0.95) = 10 > 9
+        assert_eq!(conformal_quantile(&scores, 0.05), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_conformal_regressor_intervals() {
+        let predictions = [1.0f64, 2.0, 3.0, 4.0];
+        let targets = [1.5f64, 1.0, 3.25, 4.0];
+        // residuals 0.5, 1.0, 0.25, 0.0; n = 4, alpha = 0.4: rank = ceil(5 * 0.6) = 3
+        let conformal = ConformalRegressor::calibrate(&predictions, &targets, 0.4);
+        assert_eq!(conformal.radius, 0.5);
+        assert_eq!(conformal.interval(10.0f64), (9.5, 10.5));
+
+        let intervals = conformal.predict_intervals(|row: &[f64]| row[0] * 2.0, &[vec![1.0], vec![2.0]]);
+        assert_eq!(intervals, vec![(1.5, 2.5), (3.5, 4.5)]);
+        assert_eq!(interval_coverage(&intervals, &[2.4f64, 5.0]), 0.5);
+    }
+
+    #[test]
+    fn test_conformal_classifier_label_sets() {
+        let probabilities = vec![
+            vec![0.9f64, 0.1, 0.0],
+            vec![0.2, 0.7, 0.1],
+            vec![0.3, 0.3, 0.4],
+            vec![0.6, 0.4, 0.0],
+        ];
+        let targets = [0, 1, 2, 1];
+        // scores 0.1, 0.3, 0.6, 0.6; rank = ceil(5 * 0.6) = 3 -> threshold 0.6
+        let conformal = ConformalClassifier::calibrate(&probabilities, &targets, 0.4);
+        assert!((conformal.threshold - 0.6).abs() < 1e-12);
+        assert_eq!(conformal.label_set(&[0.5f64, 0.45, 0.05]), vec![0, 1]);
+        assert_eq!(conformal.label_set(&[0.95f64, 0.05, 0.0]), vec![0]);
+
+        let sets = conformal.predict_sets(|row: &[f64]| row.to_vec(), &[vec![0.5, 0.45, 0.05], vec![0.95, 0.05, 0.0]]);
+        assert_eq!(set_coverage(&sets, &[1, 2]), 0.5);
+    }
+}
@@ -1,4 +1,7 @@
+#![cfg(feature = "std")]
+
 use neuralnet::data_handling::*;
+use neuralnet::layers::OovInit;
 
 #[cfg(test)]
 mod tests {
@@ -79,5 +82,363 @@ mod tests {
         let result = read_excel(file.path());
         assert!(result.is_err());
     }
-}
 
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Person {
+        name: String,
+        age: u32,
+    }
+
+    #[test]
+    fn test_read_json_records_basic() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, r#"[{{"name": "Alice", "age": 30}}, {{"name": "Bob", "age": 25}}]"#).unwrap();
+
+        let records: Vec<Person> = read_json_records(file.path()).unwrap();
+        assert_eq!(records, vec![
+            Person { name: "Alice".to_string(), age: 30 },
+            Person { name: "Bob".to_string(), age: 25 },
+        ]);
+    }
+
+    #[test]
+    fn test_read_json_records_type_mismatch() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, r#"[{{"name": "Alice", "age": "thirty"}}]"#).unwrap();
+
+        let result: Result<Vec<Person>, _> = read_json_records(file.path());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_json_to_matrix_column_mapping() {
+        let json = serde_json::json!([
+            {"x": 1.5, "meta": {"y": 2}, "flag": true},
+            {"x": -1.0, "meta": {"y": 4}, "flag": false}
+        ]);
+        let matrix = json_to_matrix::<f64>(&json, &["meta.y", "x", "flag"]).unwrap();
+        assert_eq!(matrix, vec![vec![2.0, 1.5, 1.0], vec![4.0, -1.0, 0.0]]);
+    }
+
+    #[test]
+    fn test_json_to_matrix_missing_or_invalid_field() {
+        let json = serde_json::json!([{"x": 1.0}, {"y": 2.0}]);
+        assert!(json_to_matrix::<f64>(&json, &["x"]).is_err());
+
+        let json = serde_json::json!([{"x": "text"}]);
+        assert!(json_to_matrix::<f64>(&json, &["x"]).is_err());
+
+        let json = serde_json::json!({"x": 1.0});
+        assert!(json_to_matrix::<f64>(&json, &["x"]).is_err());
+    }
+
+    #[test]
+    fn test_load_pretrained_embeddings_glove() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "the 0.1 0.2 0.3\ncat 1.0 2.0 3.0\nunused 9 9 9\ncat 7 7 7").unwrap();
+
+        let (emb, missing) = load_pretrained_embeddings::<f64, 3, _>(
+            file.path(), &["cat", "dog", "the"], OovInit::Zeros, true,
+        ).unwrap();
+        assert_eq!(emb.weights, vec![[1.0, 2.0, 3.0], [0.0, 0.0, 0.0], [0.1, 0.2, 0.3]]);
+        assert_eq!(missing, vec![1]);
+        assert!(emb.frozen);
+    }
+
+    #[test]
+    fn test_load_pretrained_embeddings_word2vec_header_and_uniform_oov() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "2 2\na 0.5 -0.5\nb 1 1").unwrap();
+
+        let (emb, missing) = load_pretrained_embeddings::<f32, 2, _>(
+            file.path(), &["b", "z"], OovInit::Uniform { scale: 0.1, seed: 4 }, false,
+        ).unwrap();
+        assert_eq!(emb.weights[0], [1.0, 1.0]);
+        assert_eq!(missing, vec![1]);
+        assert!(emb.weights[1].iter().all(|v| v.abs() <= 0.1));
+        assert_ne!(emb.weights[1], [0.0, 0.0]);
+        assert!(!emb.frozen);
+    }
+
+    #[test]
+    fn test_load_pretrained_embeddings_dimension_mismatch() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "a 0.5 -0.5 1.0").unwrap();
+        let result = load_pretrained_embeddings::<f32, 2, _>(file.path(), &["a"], OovInit::Zeros, false);
+        assert!(result.is_err());
+    }
+
+    fn idx_bytes(dims: &[u32], data: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0, 0, 0x08, dims.len() as u8];
+        for d in dims {
+            bytes.extend_from_slice(&d.to_be_bytes());
+        }
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    #[test]
+    fn test_read_idx_images_and_labels() {
+        let mut images = NamedTempFile::new().unwrap();
+        images.write_all(&idx_bytes(&[2, 2, 3], &[0, 255, 51, 102, 0, 0, 255, 255, 255, 0, 0, 0])).unwrap();
+        let read = read_idx_images::<f64, _>(images.path()).unwrap();
+        assert_eq!((read.rows, read.cols), (2, 3));
+        assert_eq!(read.images, vec![vec![0.0, 1.0, 0.2, 0.4, 0.0, 0.0], vec![1.0, 1.0, 1.0, 0.0, 0.0, 0.0]]);
+
+        // Gzip-compressed labels, as distributed for MNIST
+        let mut labels = NamedTempFile::new().unwrap();
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&idx_bytes(&[3], &[7, 0, 9])).unwrap();
+        labels.write_all(&encoder.finish().unwrap()).unwrap();
+        assert_eq!(read_idx_labels(labels.path()).unwrap(), vec![7, 0, 9]);
+    }
+
+    #[test]
+    fn test_read_idx_rejects_malformed_files() {
+        let mut truncated = NamedTempFile::new().unwrap();
+        truncated.write_all(&idx_bytes(&[2, 2, 2], &[1, 2, 3])).unwrap();
+        let err = read_idx_images::<f32, _>(truncated.path()).unwrap_err().to_string();
+        assert!(err.contains("expected 8 data bytes, found 3"), "{}", err);
+
+        let mut labels = NamedTempFile::new().unwrap();
+        labels.write_all(&idx_bytes(&[2], &[1, 2])).unwrap();
+        assert!(read_idx_images::<f32, _>(labels.path()).unwrap_err().to_string().contains("expected 3 dimensions"));
+
+        let mut floats = NamedTempFile::new().unwrap();
+        floats.write_all(&[0, 0, 0x0d, 1, 0, 0, 0, 0]).unwrap();
+        assert!(read_idx_labels(floats.path()).unwrap_err().to_string().contains("unsupported IDX data type 0x0d"));
+
+        let mut text = NamedTempFile::new().unwrap();
+        text.write_all(b"label\n1\n").unwrap();
+        assert!(read_idx_labels(text.path()).unwrap_err().to_string().contains("not an IDX file"));
+    }
+
+    #[test]
+    fn test_write_csv_roundtrip() {
+        let file = NamedTempFile::new().unwrap();
+        write_csv(file.path(), &["x", "prediction"], &[vec![1.5, 0.25], vec![-2.0, 1.0]]).unwrap();
+        assert_eq!(std::fs::read_to_string(file.path()).unwrap(), "x,prediction\n1.5,0.25\n-2,1\n");
+        assert_eq!(read_csv(file.path()).unwrap(), vec![vec!["1.5", "0.25"], vec!["-2", "1"]]);
+
+        // Without headers every row is data; rows of different widths are an error
+        write_csv(file.path(), &[], &[vec!["a", "b"]]).unwrap();
+        assert_eq!(std::fs::read_to_string(file.path()).unwrap(), "a,b\n");
+        assert!(write_csv(file.path(), &["only"], &[vec![1, 2]]).is_err());
+    }
+
+    #[test]
+    fn test_write_json_roundtrip() {
+        let file = NamedTempFile::new().unwrap();
+        let metrics: std::collections::BTreeMap<&str, f64> = [("accuracy", 0.75), ("loss", 0.5)].into_iter().collect();
+        write_json(file.path(), &metrics).unwrap();
+        let json = read_json(file.path()).unwrap();
+        assert_eq!(json["accuracy"], 0.75);
+        assert_eq!(json["loss"], 0.5);
+        assert!(write_json("/nonexistent_dir/out.json", &json).is_err());
+    }
+
+    #[test]
+    fn test_read_csv_and_json_from_gzip() {
+        let gzip = |text: &str| {
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(text.as_bytes()).unwrap();
+            encoder.finish().unwrap()
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let csv_path = dir.path().join("data.csv.gz");
+        std::fs::write(&csv_path, gzip("x,y\n1,2\n3,4\n")).unwrap();
+        assert_eq!(read_csv(&csv_path).unwrap(), vec![vec!["1", "2"], vec!["3", "4"]]);
+
+        // Detection uses the magic bytes, not the extension
+        let json_path = dir.path().join("data.json");
+        std::fs::write(&json_path, gzip(r#"{"answer": 42}"#)).unwrap();
+        assert_eq!(read_json(&json_path).unwrap()["answer"], 42);
+    }
+
+    #[test]
+    fn test_read_csv_from_zip_archive() {
+        let zipped = |files: &[(&str, &str)]| {
+            let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+            writer.add_directory("nested/", zip::write::FileOptions::default()).unwrap();
+            for (name, text) in files {
+                writer.start_file(*name, zip::write::FileOptions::default()).unwrap();
+                writer.write_all(text.as_bytes()).unwrap();
+            }
+            writer.finish().unwrap().into_inner()
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.zip");
+        std::fs::write(&path, zipped(&[("nested/data.csv", "a,b\nx,1\n")])).unwrap();
+        assert_eq!(read_csv(&path).unwrap(), vec![vec!["x", "1"]]);
+
+        std::fs::write(&path, zipped(&[("a.csv", "a\n1\n"), ("b.csv", "b\n2\n")])).unwrap();
+        let error = read_csv(&path).unwrap_err().to_string();
+        assert!(error.contains("exactly one file, found 2"), "{}", error);
+    }
+
+    #[cfg(feature = "fetch")]
+    /// Serves one canned HTTP response per entry of `responses`, then stops listening.
+    fn serve(responses: Vec<String>) -> (String, std::thread::JoinHandle<Vec<String>>) {
+        use std::io::{BufRead, BufReader};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request_line = String::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                reader.read_line(&mut request_line).unwrap();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line == "\r\n" || line.is_empty() {
+                        break;
+                    }
+                }
+                requests.push(request_line.trim().to_string());
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+            requests
+        });
+        (address, handle)
+    }
+
+    #[cfg(feature = "fetch")]
+    #[test]
+    fn test_fetch_downloads_once_and_follows_redirects() {
+        let (address, server) = serve(vec![
+            "HTTP/1.0 302 Found\r\nLocation: /files/iris.csv\r\n\r\n".to_string(),
+            "HTTP/1.0 200 OK\r\nContent-Type: text/csv\r\n\r\nx,y\n1,2\n".to_string(),
+        ]);
+        let cache = tempfile::tempdir().unwrap();
+        let url = format!("{}/iris.csv", address);
+        let path = fetch_to(&url, cache.path(), None).unwrap();
+        assert_eq!(server.join().unwrap(), vec!["GET /iris.csv HTTP/1.1", "GET /files/iris.csv HTTP/1.1"]);
+        assert!(path.starts_with(cache.path()));
+        assert!(path.file_name().unwrap().to_str().unwrap().ends_with("-iris.csv"));
+        assert_eq!(read_csv(&path).unwrap(), vec![vec!["1", "2"]]);
+
+        // The server is gone: a second fetch is served from the cache
+        assert_eq!(fetch_to(&url, cache.path(), None).unwrap(), path);
+    }
+
+    #[cfg(feature = "fetch")]
+    #[test]
+    fn test_fetch_reports_failures() {
+        let (address, server) = serve(vec!["HTTP/1.0 404 Not Found\r\n\r\n".to_string()]);
+        let cache = tempfile::tempdir().unwrap();
+        let error = fetch_to(&format!("{}/missing.csv", address), cache.path(), None).unwrap_err().to_string();
+        server.join().unwrap();
+        assert!(error.contains("404"), "{}", error);
+        assert_eq!(std::fs::read_dir(cache.path()).unwrap().count(), 0);
+
+        let (address, server) = serve(vec!["HTTP/1.0 204 No Content\r\n\r\n".to_string()]);
+        let error = fetch_to(&format!("{}/empty.csv", address), cache.path(), None).unwrap_err().to_string();
+        server.join().unwrap();
+        assert!(error.contains("HTTP status 204"), "{}", error);
+        assert_eq!(std::fs::read_dir(cache.path()).unwrap().count(), 0);
+    }
+
+    #[cfg(feature = "fetch")]
+    #[test]
+    fn test_fetch_verifies_sha256() {
+        // SHA-256 of "a\n1\n"
+        let digest = "309b0e45a73d3fc5325e2b6ed0a01ef8b9cde6b05a5633c1f893f970d52bfddc";
+        let mut source = NamedTempFile::new().unwrap();
+        write!(source, "a\n1\n").unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        let url = format!("file://{}", source.path().display());
+
+        let error = fetch_to(&url, cache.path(), Some(&"0".repeat(64))).unwrap_err().to_string();
+        assert!(error.contains("SHA-256 mismatch"), "{}", error);
+        assert_eq!(std::fs::read_dir(cache.path()).unwrap().count(), 0);
+
+        let path = fetch_to(&url, cache.path(), Some(&digest.to_uppercase())).unwrap();
+        assert_eq!(read_csv(&path).unwrap(), vec![vec!["1"]]);
+
+        // A corrupted cache entry is downloaded again
+        std::fs::write(&path, "a\n2\n").unwrap();
+        assert!(fetch_to(&url, cache.path(), Some(digest)).is_ok());
+        assert_eq!(read_csv(&path).unwrap(), vec![vec!["1"]]);
+    }
+
+    #[cfg(feature = "fetch")]
+    #[test]
+    fn test_fetch_copies_file_urls() {
+        let mut source = NamedTempFile::new().unwrap();
+        writeln!(source, "a\n1").unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        let url = format!("file://{}", source.path().display());
+        let path = fetch_to(&url, cache.path().join("nested"), None).unwrap();
+        assert_eq!(read_csv(&path).unwrap(), vec![vec!["1"]]);
+    }
+
+    #[test]
+    fn test_read_csv_with_options() {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "Exported 2024-01-01\n# comment\nname;age;city\n\"Doe; J\";30;Oslo\nBob;25;Rome\n").unwrap();
+        let options = CsvOptions::new().delimiter(b';').comment_char(b'#').skip_rows(1);
+        assert_eq!(read_csv_with(file.path(), &options).unwrap(), vec![vec!["Doe; J", "30", "Oslo"], vec!["Bob", "25", "Rome"]]);
+
+        let selected = read_csv_with(file.path(), &options.clone().named_columns(&["city", "name"])).unwrap();
+        assert_eq!(selected, vec![vec!["Oslo", "Doe; J"], vec!["Rome", "Bob"]]);
+        assert_eq!(read_csv_with(file.path(), &options.clone().columns(&[1])).unwrap(), vec![vec!["30"], vec!["25"]]);
+
+        // Without a header the header row is data
+        let headerless = options.clone().has_headers(false).columns(&[0]);
+        assert_eq!(read_csv_with(file.path(), &headerless).unwrap(), vec![vec!["name"], vec!["Doe; J"], vec!["Bob"]]);
+
+        assert!(read_csv_with(file.path(), &options.clone().named_columns(&["zip"])).is_err());
+        assert!(read_csv_with(file.path(), &options.clone().columns(&[3])).is_err());
+        assert!(read_csv_with(file.path(), &options.has_headers(false).named_columns(&["name"])).is_err());
+    }
+
+    #[test]
+    fn test_read_csv_with_tabs_and_quotes() {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "a\tb\n'x\ty'\t2\n").unwrap();
+        let records = read_csv_with(file.path(), &CsvOptions::new().delimiter(b'\t').quote(b'\'')).unwrap();
+        assert_eq!(records, vec![vec!["x\ty", "2"]]);
+        assert_eq!(read_csv_with(file.path(), &CsvOptions::default()).unwrap(), read_csv(file.path()).unwrap());
+    }
+
+    /// A minimal single-sheet xlsx workbook; `cells` is the `<sheetData>` body.
+    fn write_xlsx(path: &std::path::Path, cells: &str) {
+        let mut writer = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+        let parts = [
+            ("xl/workbook.xml", r#"<workbook xmlns:r="r"><sheets><sheet name="Data" sheetId="1" r:id="rId1"></sheet></sheets></workbook>"#.to_string()),
+            ("xl/_rels/workbook.xml.rels", r#"<Relationships><Relationship Id="rId1" Target="worksheets/sheet1.xml"/></Relationships>"#.to_string()),
+            ("xl/worksheets/sheet1.xml", format!("<worksheet><sheetData>{}</sheetData></worksheet>", cells)),
+        ];
+        for (name, text) in parts {
+            writer.start_file(name, zip::write::FileOptions::default()).unwrap();
+            writer.write_all(text.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn test_read_excel_typed_and_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.xlsx");
+        let row = |r: usize| format!(
+            r#"<row r="{r}"><c r="A{r}" t="inlineStr"><is><t>id{r}</t></is></c><c r="B{r}"><v>{r}.5</v></c><c r="D{r}" t="b"><v>1</v></c></row>"#
+        );
+        write_xlsx(&path, &(1..=5).map(row).collect::<String>());
+
+        let typed = read_excel_typed(&path).unwrap();
+        assert_eq!(typed.len(), 5);
+        assert_eq!(typed[0], vec![Cell::String("id1".into()), Cell::Float(1.5), Cell::Empty, Cell::Bool(true)]);
+        assert_eq!(typed[0][1].as_f64(), Some(1.5));
+        assert!(typed[0][2].is_empty() && typed[0][2].as_f64().is_none());
+        assert_eq!(read_excel(&path).unwrap()[4], vec!["id5", "5.5", "", "true"]);
+
+        let chunks = read_excel_chunks(&path, 2).unwrap();
+        assert_eq!((chunks.n_rows(), chunks.n_columns(), chunks.len()), (5, 4, 3));
+        let chunks: Vec<Vec<Vec<Cell>>> = chunks.collect();
+        assert_eq!(chunks.iter().map(Vec::len).collect::<Vec<_>>(), vec![2, 2, 1]);
+        assert_eq!(chunks.concat(), typed);
+        assert!(read_excel_chunks(&path, 0).is_err());
+    }
+}
@@ -0,0 +1,379 @@
+#![cfg(feature = "std")]
+
+use neuralnet::dataset::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn imbalanced_labels() -> Vec<usize> {
+        // 90 samples of class 0, 10 samples of class 1
+        (0..100).map(|i| if i % 10 == 0 { 1 } else { 0 }).collect()
+    }
+
+    #[test]
+    fn test_stratified_split_preserves_proportions() {
+        let labels = imbalanced_labels();
+        let features: Vec<Vec<f64>> = (0..100).map(|i| vec![i as f64]).collect();
+        let splits = stratified_split(&features, &labels, &[0.6, 0.2, 0.2], 0);
+
+        assert_eq!(splits.len(), 3);
+        let sizes: Vec<usize> = splits.iter().map(|(_, y)| y.len()).collect();
+        assert_eq!(sizes, vec![60, 20, 20]);
+        let minority: Vec<usize> = splits.iter().map(|(_, y)| y.iter().filter(|&&l| l == 1).count()).collect();
+        assert_eq!(minority, vec![6, 2, 2]);
+
+        // features stay aligned with their labels
+        for (x, y) in &splits {
+            for (row, &label) in x.iter().zip(y.iter()) {
+                assert_eq!(labels[row[0] as usize], label);
+            }
+        }
+    }
+
+    #[test]
+    fn test_stratified_split_indices_cover_all_samples() {
+        let labels = vec!["a", "b", "a", "c", "b", "a", "a"];
+        let parts = stratified_split_indices(&labels, &[2.0, 1.0], 5);
+        let mut all: Vec<usize> = parts.concat();
+        all.sort();
+        assert_eq!(all, (0..labels.len()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_stratified_split_is_reproducible() {
+        let labels = imbalanced_labels();
+        assert_eq!(stratified_split_indices(&labels, &[0.5, 0.5], 9), stratified_split_indices(&labels, &[0.5, 0.5], 9));
+        assert_ne!(stratified_split_indices(&labels, &[0.5, 0.5], 9), stratified_split_indices(&labels, &[0.5, 0.5], 10));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_stratified_split_length_mismatch() {
+        let _ = stratified_split(&[vec![1.0f32]], &[0, 1], &[1.0], 0);
+    }
+
+    #[test]
+    fn test_bucket_sampler_covers_all_and_reduces_padding() {
+        let lengths: Vec<usize> = (0..200).map(|i| (i * 37) % 50 + 1).collect();
+        let sampler = BucketSampler::new(lengths.clone(), 8);
+        let batches = sampler.batches(1);
+
+        let mut all: Vec<usize> = batches.concat();
+        all.sort();
+        assert_eq!(all, (0..200).collect::<Vec<_>>());
+        assert!(batches.iter().all(|b| b.len() <= 8));
+
+        // Random batching (pool of one batch = no sorting across batches) pads far more
+        let mut unsorted = sampler.clone();
+        unsorted.pool_batches = 1;
+        let bucketed_waste = padding_waste(&lengths, &batches);
+        let random_waste = padding_waste(&lengths, &unsorted.batches(1));
+        assert!(bucketed_waste * 4 < random_waste);
+    }
+
+    #[test]
+    fn test_bucket_sampler_drop_last_and_seed() {
+        let mut sampler = BucketSampler::new(vec![3; 10], 4);
+        sampler.drop_last = true;
+        let batches = sampler.batches(0);
+        assert_eq!(batches.len(), 2);
+        assert!(batches.iter().all(|b| b.len() == 4));
+        assert_eq!(sampler.batches(5), sampler.batches(5));
+    }
+
+    #[test]
+    fn test_pad_batch() {
+        let sequences = vec![vec![1, 2, 3], vec![4], vec![5, 6]];
+        let (padded, lengths) = pad_batch(&sequences, &[1, 0], 0);
+        assert_eq!(padded, vec![vec![4, 0, 0], vec![1, 2, 3]]);
+        assert_eq!(lengths, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_classification_generator_imbalance_and_shape() {
+        let generator = ClassificationGenerator { n_redundant: 1, seed: 5, ..ClassificationGenerator::new(1000, 5) }
+            .imbalance_ratio(9.0);
+        assert_eq!(generator.class_counts(), vec![900, 100]);
+        let (features, labels) = generator.generate();
+        assert_eq!(features.len(), 1000);
+        assert!(features.iter().all(|row| row.len() == 5));
+        assert_eq!(labels.iter().filter(|&&l| l == 1).count(), 100);
+        // the same seed reproduces the data
+        assert_eq!(generator.generate(), (features, labels));
+    }
+
+    #[test]
+    fn test_classification_generator_separation_and_noise() {
+        let clean = ClassificationGenerator { class_sep: 3.0, seed: 1, ..ClassificationGenerator::new(400, 2) };
+        let (features, labels) = clean.generate();
+        // class 1 sits at +class_sep on the first axis, class 0 at -class_sep
+        let correct = features.iter().zip(labels.iter()).filter(|(x, y)| (x[0] > 0.0) == (**y == 1)).count();
+        assert!(correct > 390, "correct = {}", correct);
+
+        let noisy = ClassificationGenerator { label_noise: 0.5, ..clean.clone() };
+        let (features, labels) = noisy.generate();
+        let correct = features.iter().zip(labels.iter()).filter(|(x, y)| (x[0] > 0.0) == (**y == 1)).count();
+        assert!(correct < 340, "correct = {}", correct);
+    }
+
+    #[test]
+    fn test_k_fold_indices_partition_samples() {
+        let folds = k_fold_indices(11, 3, 5);
+        assert_eq!(folds.len(), 3);
+        let sizes: Vec<usize> = folds.iter().map(|(_, v)| v.len()).collect();
+        assert_eq!(sizes, vec![4, 4, 3]);
+
+        let mut all: Vec<usize> = folds.iter().flat_map(|(_, v)| v.iter().copied()).collect();
+        all.sort();
+        assert_eq!(all, (0..11).collect::<Vec<_>>());
+        for (train, validation) in &folds {
+            assert_eq!(train.len() + validation.len(), 11);
+            assert!(train.iter().all(|i| !validation.contains(i)));
+        }
+        assert_eq!(folds, k_fold_indices(11, 3, 5));
+        assert_eq!(select(&["a", "b", "c"], &[2, 0]), vec!["c", "a"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "k must not exceed the number of samples")]
+    fn test_k_fold_indices_rejects_too_many_folds() {
+        k_fold_indices(3, 4, 0);
+    }
+
+    #[test]
+    fn test_make_moons_shapes_and_determinism() {
+        let (x, y) = make_moons(101, 0.0, 4);
+        assert_eq!((x.len(), y.len()), (101, 101));
+        assert_eq!(y.iter().filter(|&&l| l == 0).count(), 51);
+        for (p, &label) in x.iter().zip(y.iter()) {
+            // Without noise every point lies on its unit half circle
+            let (cx, cy) = if label == 0 { (0.0, 0.0) } else { (1.0, 0.5) };
+            assert!((((p[0] - cx).powi(2) + (p[1] - cy).powi(2)).sqrt() - 1.0).abs() < 1e-12);
+        }
+        assert_eq!(make_moons(50, 0.1, 7), make_moons(50, 0.1, 7));
+        assert_ne!(make_moons(50, 0.1, 7), make_moons(50, 0.1, 8));
+    }
+
+    #[test]
+    fn test_make_circles_radii() {
+        let (x, y) = make_circles(40, 0.3, 0.0, 1);
+        for (p, &label) in x.iter().zip(y.iter()) {
+            let radius = (p[0] * p[0] + p[1] * p[1]).sqrt();
+            assert!((radius - if label == 0 { 1.0 } else { 0.3 }).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_make_blobs_clusters_around_centres() {
+        let centers = vec![vec![5.0, 0.0, 0.0], vec![-5.0, 0.0, 0.0], vec![0.0, 5.0, 5.0]];
+        let (x, y) = make_blobs(300, &centers, 0.5, 2);
+        assert_eq!(x.len(), 300);
+        for (k, centre) in centers.iter().enumerate() {
+            let members: Vec<&Vec<f64>> = x.iter().zip(y.iter()).filter(|(_, l)| **l == k).map(|(p, _)| p).collect();
+            assert_eq!(members.len(), 100);
+            for (d, c) in centre.iter().enumerate() {
+                let mean = members.iter().map(|p| p[d]).sum::<f64>() / 100.0;
+                assert!((mean - c).abs() < 0.2);
+            }
+        }
+    }
+
+    #[test]
+    fn test_xor_labels() {
+        let (x, y) = xor(200, 3);
+        assert_eq!(x.len(), 200);
+        for (p, &label) in x.iter().zip(y.iter()) {
+            assert!(p.iter().all(|v| (-1.0..1.0).contains(v)));
+            assert_eq!(label == 1, p[0] * p[1] < 0.0);
+        }
+        let positives = y.iter().filter(|&&l| l == 1).count();
+        assert!((70..130).contains(&positives));
+    }
+
+    #[test]
+    fn test_profile_reports_statistics_and_bad_values() {
+        let records: Vec<Vec<String>> = [
+            vec!["1.0", "red", "5", ""],
+            vec!["3.0", "blue", "5", "NaN"],
+            vec![" 2.0 ", "red", "5", "x"],
+            vec!["", "green", "5"],
+        ].iter().map(|row| row.iter().map(|s| s.to_string()).collect()).collect();
+        let columns = profile(&records);
+        assert_eq!(columns.len(), 4);
+
+        let first = &columns[0];
+        assert_eq!((first.numeric, first.nan_count, first.non_numeric, first.cardinality), (3, 1, 0, 3));
+        assert_eq!((first.min, first.max, first.mean), (Some(1.0), Some(3.0), Some(2.0)));
+        assert!((first.std.unwrap() - (2.0f64 / 3.0).sqrt()).abs() < 1e-12);
+        assert!(first.is_numeric() && !first.is_constant());
+
+        let text = &columns[1];
+        assert_eq!((text.numeric, text.non_numeric, text.cardinality), (0, 4, 3));
+        assert_eq!((text.mean, text.std), (None, None));
+        assert!(!text.is_numeric());
+
+        assert!(columns[2].is_constant());
+        assert_eq!(columns[2].std, Some(0.0));
+
+        // "", "NaN" and the missing field of the short row are missing; "x" is text
+        assert_eq!((columns[3].nan_count, columns[3].non_numeric, columns[3].numeric), (3, 1, 0));
+        assert!(profile(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_sliding_windows() {
+        let series: Vec<f64> = (0..8).map(f64::from).collect();
+        let (inputs, targets) = sliding_windows(&series, 3, 2, 1);
+        assert_eq!(inputs.len(), 4);
+        assert_eq!(inputs[0], vec![0.0, 1.0, 2.0]);
+        assert_eq!(targets[0], vec![3.0, 4.0]);
+        assert_eq!((inputs[3].clone(), targets[3].clone()), (vec![3.0, 4.0, 5.0], vec![6.0, 7.0]));
+
+        // A stride of 2 keeps every other window; the last partial one is dropped
+        let (inputs, targets) = sliding_windows(&series, 3, 2, 2);
+        assert_eq!(inputs, vec![vec![0.0, 1.0, 2.0], vec![2.0, 3.0, 4.0]]);
+        assert_eq!(targets, vec![vec![3.0, 4.0], vec![5.0, 6.0]]);
+
+        // Multivariate series and series shorter than one window
+        let steps = vec![[0, 1], [2, 3], [4, 5]];
+        assert_eq!(sliding_windows(&steps, 2, 1, 1), (vec![vec![[0, 1], [2, 3]]], vec![vec![[4, 5]]]));
+        assert_eq!(sliding_windows(&series[..4], 3, 2, 1), (vec![], vec![]));
+    }
+
+    #[test]
+    #[should_panic(expected = "stride must be positive")]
+    fn test_sliding_windows_rejects_zero_stride() {
+        sliding_windows(&[1.0, 2.0, 3.0], 1, 1, 0);
+    }
+
+    #[test]
+    fn test_columns_and_rows_roundtrip() {
+        let columns = vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]];
+        let rows = columns_to_rows(&columns);
+        assert_eq!(rows, vec![vec![1.0, 4.0], vec![2.0, 5.0], vec![3.0, 6.0]]);
+        assert_eq!(rows_to_columns(&rows), columns);
+        assert!(columns_to_rows::<f64>(&[]).is_empty());
+        assert_eq!(rows_to_columns(&[Vec::<f64>::new()]), Vec::<Vec<f64>>::new());
+    }
+
+    #[test]
+    #[should_panic(expected = "columns must have the same length")]
+    fn test_columns_to_rows_rejects_ragged_columns() {
+        columns_to_rows(&[vec![1.0], vec![1.0, 2.0]]);
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_dataframe_conversions() {
+        use polars::prelude::{Column, DataFrame};
+        let frame = DataFrame::new(3, vec![
+            Column::new("a".into(), [1.0f64, 2.0, 3.0]),
+            Column::new("b".into(), [4i32, 5, 6]),
+            Column::new("label".into(), [0i64, 1, 0]),
+        ]).unwrap();
+
+        // The last column is the target; integer columns are cast to f64
+        let dataset = Dataset::try_from(&frame).unwrap();
+        assert_eq!(dataset.rows, vec![vec![1.0, 4.0], vec![2.0, 5.0], vec![3.0, 6.0]]);
+        assert_eq!(dataset.targets, vec![vec![0.0], vec![1.0], vec![0.0]]);
+
+        let by_name = Dataset::try_from_dataframe(&frame, &["a", "label"]).unwrap();
+        assert_eq!(by_name.rows, vec![vec![4.0], vec![5.0], vec![6.0]]);
+        assert_eq!(by_name.targets, vec![vec![1.0, 0.0], vec![2.0, 1.0], vec![3.0, 0.0]]);
+        assert!(Dataset::try_from_dataframe(&frame, &["missing"]).is_err());
+
+        // Round trip through a frame with generated column names
+        let out = DataFrame::try_from(&dataset).unwrap();
+        let names: Vec<&str> = out.get_column_names().iter().map(|name| name.as_str()).collect();
+        assert_eq!(names, vec!["x0", "x1", "y0"]);
+        assert_eq!(Dataset::try_from(&out).unwrap(), dataset);
+
+        let strings = DataFrame::new(1, vec![Column::new("s".into(), ["a"]), Column::new("y".into(), [1.0f64])]).unwrap();
+        assert!(Dataset::try_from(&strings).is_err());
+        let nulls = DataFrame::new(2, vec![Column::new("x".into(), [Some(1.0f64), None]), Column::new("y".into(), [1.0f64, 0.0])]).unwrap();
+        assert!(Dataset::try_from(&nulls).is_err());
+        assert!(Dataset::try_from(&DataFrame::empty()).is_err());
+        let ragged = Dataset::new(vec![vec![1.0], vec![1.0, 2.0]], vec![vec![0.0], vec![1.0]]);
+        assert!(ragged.to_dataframe().is_err());
+    }
+
+    #[test]
+    fn test_batch_iter_last_batch_policies() {
+        let order: Vec<usize> = vec![6, 0, 5, 1, 4, 2, 3];
+        let smaller: Vec<Batch> = BatchIter::new(&order, 3, LastBatch::Smaller).collect();
+        assert_eq!(smaller.iter().map(|b| b.indices.clone()).collect::<Vec<_>>(), vec![vec![6, 0, 5], vec![1, 4, 2], vec![3]]);
+        assert!(smaller.iter().all(|b| !b.is_padded() && b.len == b.indices.len()));
+
+        let dropped = BatchIter::new(&order, 3, LastBatch::Drop);
+        assert_eq!(dropped.len(), 2);
+        assert_eq!(dropped.flat_map(|b| b.indices).collect::<Vec<_>>(), vec![6, 0, 5, 1, 4, 2]);
+
+        let padded: Vec<Batch> = BatchIter::new(&order, 3, LastBatch::Pad).collect();
+        let last = &padded[2];
+        assert_eq!((last.indices.clone(), last.len), (vec![3, 6, 0], 1));
+        assert!(last.is_padded());
+        assert_eq!(last.real(), &[3]);
+        assert_eq!(last.mask(), vec![true, false, false]);
+        let rows: Vec<Vec<f64>> = (0..7).map(|i| vec![i as f64]).collect();
+        assert_eq!(last.gather(&rows), vec![vec![3.0], vec![6.0], vec![0.0]]);
+
+        // Padding wraps around when there are fewer samples than one batch
+        let tiny: Vec<Batch> = BatchIter::new(&[1, 0], 5, LastBatch::Pad).collect();
+        assert_eq!((tiny[0].indices.clone(), tiny[0].len), (vec![1, 0, 1, 0, 1], 2));
+        assert_eq!(BatchIter::new(&[1, 0], 5, LastBatch::Drop).count(), 0);
+        assert_eq!(BatchIter::new(&[], 2, LastBatch::Pad).count(), 0);
+    }
+
+    #[test]
+    fn test_dataset_epochs_reshuffle_deterministically() {
+        let rows: Vec<Vec<f64>> = (0..9).map(|i| vec![i as f64]).collect();
+        let dataset = Dataset::new(rows.clone(), rows).batch_size(4).last_batch(LastBatch::Drop);
+        assert_eq!(dataset.len(), 9);
+        assert_eq!(dataset.batches().len(), 2);
+
+        let orders = |seed| dataset.epochs(3, seed).map(|batches| batches.order().to_vec()).collect::<Vec<_>>();
+        let first = orders(1);
+        assert_eq!(first.len(), 3);
+        assert_eq!(first, orders(1));
+        assert_ne!(first, orders(2));
+        assert_ne!(first[0], first[1]);
+        for order in &first {
+            let mut sorted = order.clone();
+            sorted.sort();
+            assert_eq!(sorted, (0..9).collect::<Vec<_>>());
+        }
+
+        let batch = dataset.epochs(1, 1).next().unwrap().next().unwrap();
+        let (batch_rows, batch_targets) = dataset.gather(&batch);
+        assert_eq!(batch_rows.len(), 4);
+        assert_eq!(batch_rows[0][0], first[0][0] as f64);
+        assert_eq!(batch_rows, batch_targets);
+    }
+
+    #[test]
+    fn test_dataset_epochs_match_trainer_order() {
+        use neuralnet::loss_fn::Loss;
+        use neuralnet::model::ModelBuilder;
+        use neuralnet::training::Trainer;
+        let rows: Vec<Vec<f64>> = (0..7).map(|i| vec![i as f64 / 3.0 - 1.0]).collect();
+        let targets: Vec<Vec<f64>> = rows.iter().map(|r| vec![r[0] * r[0]]).collect();
+        let model = ModelBuilder::new(1).seed(3).dense(3).tanh().dense(1).build::<f64>().unwrap();
+
+        let mut trained = model.clone();
+        Trainer::new(Loss::MeanSquaredError, 0.1, 4).batch_size(3).shuffle(11).fit(&mut trained, &rows, &targets);
+
+        let dataset = Dataset::new(rows, targets).batch_size(3);
+        let mut manual = model;
+        for batches in dataset.epochs(4, 11) {
+            for batch in batches {
+                manual.zero_grad();
+                for &i in batch.real() {
+                    manual.accumulate_gradients(&dataset.rows[i], &dataset.targets[i], Loss::MeanSquaredError);
+                }
+                manual.sgd_step(0.1 / batch.len as f64);
+            }
+        }
+        assert_eq!(manual, trained);
+    }
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "std")]
+
+use neuralnet::decision::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multilabel_thresholds_fit_per_label() {
+        // label 0 is separable at 0.3, label 1 at 0.8
+        let probabilities = vec![
+            vec![0.35f64, 0.9],
+            vec![0.32, 0.85],
+            vec![0.2, 0.7],
+            vec![0.1, 0.6],
+        ];
+        let labels = vec![
+            vec![1.0f64, 1.0],
+            vec![1.0, 1.0],
+            vec![0.0, 0.0],
+            vec![0.0, 0.0],
+        ];
+        let thresholds = MultiLabelThresholds::fit(&probabilities, &labels);
+        assert_eq!(thresholds.thresholds, vec![0.32, 0.85]);
+        assert_eq!(thresholds.predict(&[0.33f64, 0.8]), vec![true, false]);
+    }
+
+    #[test]
+    fn test_multilabel_thresholds_no_positives_keeps_default() {
+        let thresholds = MultiLabelThresholds::fit(&[vec![0.9f32], vec![0.1]], &[vec![0.0f32], vec![0.0]]);
+        assert_eq!(thresholds, MultiLabelThresholds::uniform(1, 0.5));
+    }
+
+    #[test]
+    fn test_multilabel_thresholds_serde_roundtrip() {
+        let thresholds = MultiLabelThresholds { thresholds: vec![0.25, 0.75] };
+        let json = serde_json::to_string(&thresholds).unwrap();
+        let restored: MultiLabelThresholds = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, thresholds);
+    }
+
+    #[test]
+    fn test_cost_matrix_overrides_argmax() {
+        // Missing class 1 (e.g. a disease) is ten times worse than a false alarm
+        let costs = CostMatrix::new(vec![vec![0.0, 1.0], vec![10.0, 0.0]]);
+        let probabilities = [0.8f64, 0.2];
+        let expected = costs.expected_costs(&probabilities);
+        assert!((expected[0] - 2.0).abs() < 1e-12);
+        assert!((expected[1] - 0.8).abs() < 1e-12);
+        assert_eq!(costs.predict(&probabilities), 1);
+        assert_eq!(costs.predict_batch(&[vec![0.95f64, 0.05], vec![0.8, 0.2]]), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_cost_matrix_zero_one_is_argmax() {
+        let costs = CostMatrix::zero_one(3);
+        assert_eq!(costs.predict(&[0.2f32, 0.5, 0.3]), 1);
+        assert_eq!(costs.predict(&[0.6f32, 0.1, 0.3]), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "cost matrix must be square")]
+    fn test_cost_matrix_rejects_non_square() {
+        CostMatrix::new(vec![vec![0.0, 1.0], vec![1.0]]);
+    }
+
+    #[test]
+    fn test_predict_with_reject() {
+        assert_eq!(predict_with_reject(&[0.1f64, 0.7, 0.2], 0.6), Some(1));
+        assert_eq!(predict_with_reject(&[0.4f64, 0.35, 0.25], 0.6), None);
+    }
+
+    #[test]
+    fn test_predict_with_reject_mc() {
+        let stable = vec![vec![0.8f64, 0.2], vec![0.8, 0.2], vec![0.8, 0.2]];
+        assert_eq!(predict_with_reject_mc(&stable, 0.01), Some(0));
+        // mean favours class 0, but the passes disagree strongly
+        let unstable = vec![vec![1.0f64, 0.0], vec![0.2, 0.8]];
+        assert_eq!(predict_with_reject_mc(&unstable, 0.01), None);
+    }
+
+    #[test]
+    fn test_coverage_accuracy_curve() {
+        let probabilities = vec![
+            vec![0.9f64, 0.1],
+            vec![0.55, 0.45],
+            vec![0.3, 0.7],
+            vec![0.6, 0.4],
+        ];
+        let targets = [0, 1, 1, 1];
+        let curve = coverage_accuracy_curve(&probabilities, &targets, &[0.0, 0.65, 0.95]);
+        assert_eq!(curve[0].accepted, 4);
+        assert_eq!(curve[0].accuracy, 0.5);
+        assert_eq!(curve[1].accepted, 2);
+        assert_eq!(curve[1].coverage, 0.5);
+        assert_eq!(curve[1].accuracy, 1.0);
+        assert_eq!(curve[2].accepted, 0);
+        assert_eq!(curve[2].accuracy, 1.0);
+    }
+}
@@ -0,0 +1,94 @@
+#![cfg(feature = "std")]
+
+use neuralnet::diagnostics::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_noise_repeated() {
+        // feature 0 varies between samples and is measured exactly;
+        // feature 1 only varies between repeated measurements
+        let measurements = vec![
+            vec![vec![1.0f64, 0.5], vec![1.0, -0.5]],
+            vec![vec![3.0, 0.5], vec![3.0, -0.5]],
+            vec![vec![5.0, -0.5], vec![5.0, 0.5]],
+        ];
+        let report = estimate_noise_repeated(&measurements, 1.0);
+        assert_eq!(report.estimates[0].noise_variance, 0.0);
+        assert!((report.estimates[0].signal_variance - 8.0 / 3.0).abs() < 1e-12);
+        assert_eq!(report.estimates[1].noise_variance, 0.5);
+        assert_eq!(report.estimates[1].signal_variance, 0.0);
+        assert_eq!(report.flagged(), vec![1]);
+        assert!(report.summary(Some(&["clean", "jitter"])).contains("jitter: signal var 0.0000, noise var 0.5000, snr 0.000  NOISY"));
+    }
+
+    #[test]
+    fn test_estimate_noise_residual() {
+        let fitted = vec![vec![0.0f64, 1.0], vec![2.0, 1.0], vec![4.0, 1.0], vec![6.0, 1.0]];
+        let features = vec![vec![0.1f64, 2.0], vec![1.9, 0.0], vec![4.1, 2.0], vec![5.9, 0.0]];
+        let report = estimate_noise_residual(&features, &fitted, 2.0);
+        assert!((report.estimates[0].snr() - 5.0 / 0.01).abs() < 1e-6);
+        assert_eq!(report.estimates[1].noise_variance, 1.0);
+        assert_eq!(report.flagged(), vec![1]);
+    }
+
+    #[test]
+    fn test_histogram_bins() {
+        let h = histogram(&[0.0, 1.0, 2.0, 3.0, 4.0, f64::NAN], 4);
+        assert_eq!((h.min, h.max), (0.0, 4.0));
+        // The maximum falls in the last bin
+        assert_eq!(h.counts, vec![1, 1, 1, 2]);
+        assert_eq!(h.edges(), vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(h.total(), 5);
+
+        let constant = histogram(&[0.0f32; 6], 3);
+        assert_eq!((constant.min, constant.max, constant.counts), (0.0, 0.0, vec![6, 0, 0]));
+        assert_eq!(histogram::<f64>(&[], 2).total(), 0);
+    }
+
+    #[test]
+    fn test_histogram_log_exposes_dead_units() {
+        use neuralnet::loss_fn::Loss;
+        use neuralnet::model::{ModelBuilder, ModelLayer};
+        use neuralnet::training::Trainer;
+        let mut model = ModelBuilder::new(2).seed(1).dense(3).relu().dense(1).build::<f64>().unwrap();
+        // Unit 0 never activates, so its incoming weights never get a gradient
+        if let ModelLayer::Dense(dense) = &mut model.layers[0] {
+            dense.biases[0] = -100.0;
+        }
+        assert!(model.gradients().iter().all(|g| g.values.is_empty()));
+
+        let rows = vec![vec![0.5, 1.0], vec![1.0, -0.5], vec![-1.0, 0.2]];
+        let targets = vec![vec![1.0], vec![0.0], vec![0.5]];
+        let mut log = HistogramLog::new(5);
+        let history = Trainer::new(Loss::MeanSquaredError, 0.05, 4).fit_with(&mut model, &rows, &targets, |m| {
+            log.record(m);
+            Vec::new()
+        });
+        let gradients = model.gradients();
+        assert_eq!(gradients.iter().map(|g| g.name.as_str()).collect::<Vec<_>>(), ["0.weights", "0.biases", "2.weights", "2.biases"]);
+        assert!(gradients[0].values[..2].iter().all(|&&g| g == 0.0));
+        assert!(gradients[2].values.iter().any(|&&g| g != 0.0));
+
+        assert_eq!(log.epochs, history.records.len());
+        let series = log.series("0.weights", HistogramKind::Gradients);
+        assert_eq!(series.iter().map(|e| e.epoch).collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+        assert!(series.iter().all(|e| e.histogram.total() == 6));
+        assert_eq!(log.series("2.biases", HistogramKind::Values)[3].histogram.min, match &model.layers[2] {
+            ModelLayer::Dense(dense) => dense.biases[0],
+            _ => unreachable!(),
+        });
+
+        let csv_path = std::env::temp_dir().join("neuralnet_histograms.csv");
+        log.to_csv(&csv_path).unwrap();
+        let csv = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(csv.starts_with("epoch,parameter,kind,bin,lower,upper,count\n0,0.weights,values,0,"));
+        assert_eq!(csv.lines().count(), 1 + log.entries.len() * 5);
+        let json_path = std::env::temp_dir().join("neuralnet_histograms.json");
+        log.to_json(&json_path).unwrap();
+        let loaded: HistogramLog = serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(loaded, log);
+    }
+}
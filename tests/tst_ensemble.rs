@@ -0,0 +1,76 @@
+#![cfg(feature = "std")]
+
+use neuralnet::ensemble::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neuralnet::dataset::make_blobs;
+    use neuralnet::loss_fn::Loss;
+    use neuralnet::metrics::accuracy;
+    use neuralnet::model::ModelBuilder;
+    use neuralnet::random::Rng;
+    use neuralnet::training::Trainer;
+
+    #[test]
+    fn test_bootstrap_indices() {
+        let (sample, out_of_bag) = bootstrap_indices(200, &mut Rng::new(3));
+        assert_eq!(sample.len(), 200);
+        assert!(sample.iter().all(|&i| i < 200));
+        // Roughly (1 - 1/n)^n of the samples are never drawn
+        assert!(out_of_bag.len() > 50 && out_of_bag.len() < 100, "{}", out_of_bag.len());
+        assert!(out_of_bag.iter().all(|i| !sample.contains(i)));
+        assert!(out_of_bag.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(bootstrap_indices(200, &mut Rng::new(3)).0, sample);
+    }
+
+    #[test]
+    fn test_bagging_averages_regression_members() {
+        let rows: Vec<Vec<f64>> = (0..30).map(|i| vec![i as f64 / 15.0 - 1.0]).collect();
+        let targets: Vec<Vec<f64>> = rows.iter().map(|r| vec![r[0] * r[0]]).collect();
+        let factory = |k: usize| ModelBuilder::new(1).seed(k as u64).dense(6).tanh().dense(1).build::<f64>().unwrap();
+        let trainer = Trainer::new(Loss::MeanSquaredError, 0.05, 20).shuffle(0);
+
+        let (ensemble, histories) = Bagging::fit(factory, &rows, &targets, 4, &trainer, 10);
+        assert_eq!((ensemble.len(), histories.len(), ensemble.out_of_bag.len()), (4, 4, 4));
+        assert!(histories.iter().all(|h| h.records.len() == 20));
+        assert_ne!(ensemble.models[0], ensemble.models[1]);
+
+        let x = [0.3];
+        let mean = ensemble.models.iter().map(|m| m.forward(&x)[0]).sum::<f64>() / 4.0;
+        assert!((ensemble.predict(&x)[0] - mean).abs() < 1e-12);
+        assert_eq!(ensemble.predict_batch(&rows[..2]).len(), 2);
+
+        // Member k trains on the bootstrap sample of `Rng::new(seed + k)`
+        let (sample, _) = bootstrap_indices(30, &mut Rng::new(12));
+        let mut member = factory(2);
+        trainer.fit(&mut member, &neuralnet::dataset::select(&rows, &sample), &neuralnet::dataset::select(&targets, &sample));
+        assert_eq!(member, ensemble.models[2]);
+        assert_eq!(Bagging::fit(factory, &rows, &targets, 4, &trainer, 10).0, ensemble);
+    }
+
+    #[test]
+    fn test_bagging_majority_vote() {
+        let (rows, labels) = make_blobs(90, &[vec![-2.0, 0.0], vec![2.0, 0.0], vec![0.0, 3.0]], 0.5, 1);
+        let targets: Vec<Vec<f64>> = labels.iter().map(|&y| (0..3).map(|k| if k == y { 1.0 } else { 0.0 }).collect()).collect();
+        let factory = |k: usize| ModelBuilder::new(2).seed(k as u64).dense(8).relu().dense(3).softmax().build::<f64>().unwrap();
+        let trainer = Trainer::new(Loss::CrossEntropy, 0.05, 30).shuffle(2);
+        let (ensemble, _) = Bagging::fit(factory, &rows, &targets, 5, &trainer, 0);
+
+        let votes = ensemble.votes(&rows[0]);
+        assert_eq!(votes.iter().sum::<usize>(), 5);
+        let best = *votes.iter().max().unwrap();
+        assert_eq!(ensemble.predict_class(&rows[0]), votes.iter().position(|&v| v == best).unwrap());
+        let predictions: Vec<usize> = rows.iter().map(|r| ensemble.predict_class(r)).collect();
+        assert!(accuracy(&predictions, &labels) > 0.9);
+        let probabilities = ensemble.predict(&rows[0]);
+        assert!((probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    #[should_panic(expected = "the ensemble has no models")]
+    fn test_empty_ensemble_predict_class_panics() {
+        let empty = Bagging::<f64> { models: Vec::new(), out_of_bag: Vec::new() };
+        empty.predict_class(&[0.0]);
+    }
+}
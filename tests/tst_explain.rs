@@ -0,0 +1,42 @@
+#![cfg(feature = "std")]
+
+use neuralnet::explain::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neuralnet::dataset::Dataset;
+    use neuralnet::loss_fn::Loss;
+    use neuralnet::model::ModelBuilder;
+    use neuralnet::random::Rng;
+    use neuralnet::training::Trainer;
+
+    #[test]
+    fn test_permutation_importance_ranks_used_features() {
+        let mut rng = Rng::new(5);
+        let rows: Vec<Vec<f64>> = (0..120).map(|_| (0..3).map(|_| rng.next_f64() * 2.0 - 1.0).collect()).collect();
+        // Feature 2 is ignored by the target
+        let targets: Vec<Vec<f64>> = rows.iter().map(|r| vec![2.0 * r[0] + 0.5 * r[1]]).collect();
+        let mut model = ModelBuilder::new(3).seed(1).dense(1).build::<f64>().unwrap();
+        Trainer::new(Loss::MeanSquaredError, 0.05, 100).shuffle(0).fit(&mut model, &rows, &targets);
+
+        let dataset = Dataset::new(rows, targets);
+        let neg_mse = |predictions: &[Vec<f64>], targets: &[Vec<f64>]| {
+            -predictions.iter().zip(targets).map(|(p, t)| (p[0] - t[0]).powi(2)).sum::<f64>() / targets.len() as f64
+        };
+        let importance = permutation_importance(&model, &dataset, neg_mse, 5, 3);
+        assert!(importance.baseline > -1e-3, "{}", importance.baseline);
+        assert_eq!(importance.ranking(), vec![0, 1, 2]);
+        let features = &importance.features;
+        assert!(features.iter().all(|f| f.scores.len() == 5));
+        // Shuffling x0 adds about 2 * 4 * var(x0) = 8/3 to the MSE
+        assert!((features[0].mean - 8.0 / 3.0).abs() < 1.0, "{}", features[0].mean);
+        assert!(features[1].mean > 0.05 && features[1].mean < features[0].mean);
+        assert!(features[2].mean.abs() < 1e-2 && features[2].std >= 0.0);
+        let mean: f64 = features[1].scores.iter().map(|s| importance.baseline - s).sum::<f64>() / 5.0;
+        assert!((mean - features[1].mean).abs() < 1e-12);
+
+        assert_eq!(permutation_importance(&model, &dataset, neg_mse, 5, 3), importance);
+        assert_ne!(permutation_importance(&model, &dataset, neg_mse, 5, 4), importance);
+    }
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "std")]
+
+use neuralnet::export::*;
+use std::fs;
+use std::process::Command;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neuralnet::activation_fn::Activation;
+    use neuralnet::model::{ModelBuilder, Sequential};
+
+    fn model() -> Sequential<f64> {
+        ModelBuilder::new(3).seed(7)
+            .dense(5).activation(Activation::Tanh)
+            .dense(4).prelu(0.1)
+            .dense(4).activation(Activation::Softplus)
+            .dense(3).softmax()
+            .build::<f64>().unwrap()
+    }
+
+    #[test]
+    fn test_to_rust_source_is_deterministic() {
+        let model = model();
+        let source = to_rust_source(&model);
+        assert_eq!(source, to_rust_source(&model.clone()));
+        assert!(source.contains("pub const INPUTS: usize = 3;"));
+        assert!(source.contains("pub const OUTPUTS: usize = 3;"));
+        assert!(source.contains("pub fn predict(inputs: &[f64; INPUTS]) -> [f64; OUTPUTS]"));
+        assert!(!source.contains("std::") && !source.contains("extern crate"));
+        assert!(!source.contains("fn sigmoid") && !source.contains("fn relu"));
+
+        let f32_source = to_rust_source_as(&model, Scalar::F32);
+        assert!(f32_source.contains("pub fn predict(inputs: &[f32; INPUTS]) -> [f32; OUTPUTS]"));
+        assert!(f32_source.contains("static B0: [f32; 5]"));
+    }
+
+    #[test]
+    fn test_generated_predict_matches_forward() {
+        let model = model();
+        let rows: Vec<Vec<f64>> = vec![vec![0.0, 0.0, 0.0], vec![0.5, -1.25, 2.0], vec![-3.0, 0.75, -0.1]];
+        let dir = tempfile::tempdir().unwrap();
+        write_rust_source(&model, Scalar::F64, dir.path().join("model.rs")).unwrap();
+        let calls: String = rows.iter().map(|r| format!("    println!(\"{{:?}}\", model::predict(&{:?}));\n", r)).collect();
+        fs::write(dir.path().join("main.rs"), format!("mod model;\n\nfn main() {{\n{}}}\n", calls)).unwrap();
+
+        let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+        let status = Command::new(rustc)
+            .current_dir(dir.path())
+            .args(["--edition", "2021", "-D", "warnings", "-o", "predict", "main.rs"])
+            .status().unwrap();
+        assert!(status.success());
+        let output = Command::new(dir.path().join("predict")).output().unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+
+        for (line, row) in stdout.lines().zip(rows.iter()) {
+            let generated: Vec<f64> = line.trim_matches(|c| c == '[' || c == ']').split(", ").map(|v| v.parse().unwrap()).collect();
+            let expected = model.forward(row);
+            assert_eq!(generated.len(), expected.len());
+            for (g, e) in generated.iter().zip(expected.iter()) {
+                assert!((g - e).abs() < 1e-12, "{} vs {}", g, e);
+            }
+        }
+        assert_eq!(stdout.lines().count(), rows.len());
+    }
+}
@@ -1,5 +1,6 @@
 use neuralnet::forward_propagation::*;
 use neuralnet::layers::*;
+use neuralnet::activation_fn::Activation;
 
 #[cfg(test)]
 mod tests {
@@ -11,7 +12,7 @@ mod tests {
         let inputs = [1.0f32, 2.0];
         let weights = [[0.5f32, 0.5], [-1.0, -1.0]]; // weights[0] for output0, weights[1] for output1
         let biases = [0.1f32, -0.2];
-        let layer = Layer1D { weights, biases };
+        let layer = Layer1D::new(weights, biases);
         let outputs = dense_linear::<f32, 2, 2>(&inputs, &layer);
         // outputs[0] = 0.1 + 1.0*0.5 + 2.0*0.5 = 1.6
         // outputs[1] = -0.2 + 1.0*(-1.0) + 2.0*(-1.0) = -3.2
@@ -29,7 +30,7 @@ mod tests {
             [7, 8, 9],    // output 2
         ];
         let biases = [1i32, -1, 1];
-        let layer = Layer1D { weights, biases };
+        let layer = Layer1D::new(weights, biases);
         let outputs = dense_linear::<i32, 3, 3>(&inputs, &layer);
         // outputs computed manually:
         // out0 = 1 + 2*1 + 1*2 + 3*3 = 14
@@ -47,7 +48,7 @@ mod tests {
             [1.5, 2.0, -0.3],    // filter for output 1
         ];
         let biases = [0.1f32, -0.2];
-        let layer = Layer2D { filters, biases };
+        let layer = Layer2D::new(filters, biases);
         let outputs = dense_conv2d::<f32, 3, 2, 3>(&inputs, &layer);
         // outputs[0] = 0.1 + (1.0*0.5) + (2.0*-1.0) + (3.0*0.2) = -0.8
         // outputs[1] = -0.2 + (1.0*1.5) + (2.0*2.0) + (3.0*-0.3) = 4.4
@@ -64,10 +65,67 @@ mod tests {
             [4, 5, 6],    // filter for output 1
         ];
         let biases = [0i32, 1];
-        let layer = Layer2D { filters, biases };
+        let layer = Layer2D::new(filters, biases);
         let outputs = dense_conv2d::<i32, 2, 2, 3>(&inputs, &layer);
         // outputs[0] = 0 + (2*1) + (3*2) = 8
         // outputs[1] = 1 + (2*4) + (3*5) = 24
         assert_eq!(outputs, [8, 24]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_dense_linear_activated_relu() {
+        let inputs = [1.0f32, 2.0];
+        let layer = Layer1D::new([[0.5f32, 0.5], [-1.0, -1.0]], [0.1f32, -0.2]);
+        let (z, a) = dense_linear_activated(&inputs, &layer, &Activation::ReLU);
+        assert!((z[0] - 1.6).abs() < 1e-6);
+        assert!((z[1] + 3.2).abs() < 1e-6);
+        assert_eq!(a, [z[0], 0.0]);
+    }
+
+    #[test]
+    fn test_try_dense_conv2d_rejects_long_filters() {
+        let layer = conv2d::<i32, 2, 3>(&[1, 2, 3, 4, 5, 6]);
+        assert_eq!(try_dense_conv2d::<i32, 3, 2, 3>(&[1, 1, 1], &layer), Ok([6, 15]));
+        assert_eq!(
+            try_dense_conv2d::<i32, 2, 2, 3>(&[1, 1], &layer),
+            Err(LayerError::IndexOutOfRange { index: 2, len: 2 })
+        );
+    }
+
+    #[test]
+    fn test_dense_linear_flat_matches_dense_linear() {
+        let layer = Layer1D::new([[0.5f64, -1.0, 2.0], [1.5, 0.25, -0.75]], [0.1, -0.2]);
+        let inputs = [1.0, -2.0, 0.5];
+        let mut outputs = [0.0; 2];
+        dense_linear_flat(layer.weights_flat(), &layer.biases, &inputs, &mut outputs);
+        assert_eq!(outputs, dense_linear(&inputs, &layer));
+        assert_eq!(outputs, [0.1 + 0.5 + 2.0 + 1.0, -0.2 + 1.5 - 0.5 - 0.375]);
+        let (pre_activations, activations) = dense_linear_activated(&inputs, &layer, &Activation::Tanh);
+        assert_eq!(pre_activations, outputs);
+        assert_eq!(activations, outputs.map(f64::tanh));
+
+        // Zero inputs leave only the biases
+        let mut outputs = [9.0; 2];
+        dense_linear_flat::<f64>(&[], &[1.0, 2.0], &[], &mut outputs);
+        assert_eq!(outputs, [1.0, 2.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "weights must have one row")]
+    fn test_dense_linear_flat_rejects_wrong_weight_count() {
+        dense_linear_flat(&[1.0f64; 5], &[0.0; 2], &[1.0; 3], &mut [0.0; 2]);
+    }
+
+    #[test]
+    fn test_into_variants_overwrite_reused_buffers() {
+        let inputs = [1.0f32, 2.0];
+        let layer = Layer1D::new([[0.5f32, 0.5], [-1.0, -1.0]], [0.1f32, -0.2]);
+        let mut out = [7.0f32; 2];
+        dense_linear_into(&inputs, &layer, &mut out);
+        assert_eq!(out, dense_linear(&inputs, &layer));
+
+        let (mut z, mut a) = ([7.0f32; 2], [7.0f32; 2]);
+        dense_linear_activated_into(&inputs, &layer, &Activation::ReLU, &mut z, &mut a);
+        assert_eq!((z, a), dense_linear_activated(&inputs, &layer, &Activation::ReLU));
+    }
+}
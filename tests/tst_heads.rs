@@ -0,0 +1,109 @@
+#![cfg(feature = "std")]
+
+use neuralnet::heads::*;
+use neuralnet::layers::Layer;
+use neuralnet::loss_fn::{binary_cross_entropy_loss, binary_cross_entropy_with_logits};
+use neuralnet::model::{Dense, ModelBuilder};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bce_with_logits_matches_bce_and_stays_finite() {
+        for &(z, t) in &[(-3.0, 0.0), (0.5, 1.0), (2.0, 0.3)] {
+            let p = 1.0 / (1.0 + f64::exp(-z));
+            assert!((binary_cross_entropy_with_logits(z, t) - binary_cross_entropy_loss(p, t)).abs() < 1e-9);
+        }
+        assert!((binary_cross_entropy_with_logits(-800.0f64, 1.0) - 800.0).abs() < 1e-9);
+        assert_eq!(binary_cross_entropy_with_logits(800.0f64, 1.0), 0.0);
+    }
+
+    #[test]
+    fn test_binary_head_gradients_match_finite_differences() {
+        let mut head = BinaryHead::<f64>::new(Dense::new(vec![vec![0.4, -0.7, 0.2]], vec![0.1]));
+        let features = [0.5, -1.0, 2.0];
+        let (loss, feature_grad) = head.backward(&features, 1.0);
+        assert!((loss - head.loss(&features, 1.0)).abs() < 1e-12);
+
+        let h = 1e-6;
+        for j in 0..3 {
+            let mut plus = features;
+            let mut minus = features;
+            plus[j] += h;
+            minus[j] -= h;
+            let numeric = (head.loss(&plus, 1.0) - head.loss(&minus, 1.0)) / (2.0 * h);
+            assert!((numeric - feature_grad[j]).abs() < 1e-6);
+        }
+        // dL/dw_j = (sigmoid(z) - t) * x_j
+        let delta = head.predict_proba(&features) - 1.0;
+        for (g, x) in head.dense.grads().weights[0].iter().zip(features.iter()) {
+            assert!((g - delta * x).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_binary_head_trains_with_body() {
+        let mut body = ModelBuilder::new(2).seed(2).dense(6).tanh().build::<f64>().unwrap();
+        let mut head = BinaryHead::<f64>::with_seed(6, 5);
+        let rows: Vec<[f64; 2]> = (0..36).map(|i| [(i % 6) as f64 / 5.0, (i / 6) as f64 / 5.0]).collect();
+        let target = |r: &[f64; 2]| if r[0] + r[1] > 1.0 { 1.0 } else { 0.0 };
+
+        let epoch_loss = |body: &mut _, head: &mut BinaryHead<f64>, lr| {
+            rows.iter().map(|r| head.train_step(body, r, target(r), lr)).sum::<f64>() / rows.len() as f64
+        };
+        let first = epoch_loss(&mut body, &mut head, 0.5);
+        let mut last = first;
+        for _ in 0..200 {
+            last = epoch_loss(&mut body, &mut head, 0.5);
+        }
+        assert!(last < first / 4.0);
+        let correct = rows.iter().filter(|r| head.predict(&body.forward(&r[..]), 0.5) == (target(r) == 1.0)).count();
+        assert!(correct >= 34);
+    }
+
+    #[test]
+    fn test_multi_label_head_sums_label_losses() {
+        let mut head = MultiLabelHead::<f64>::new(Dense::new(vec![vec![1.0, 0.0], vec![0.0, -2.0]], vec![0.0, 0.5]));
+        assert_eq!(head.n_labels(), 2);
+        let features = [0.3, 0.8];
+        let targets = [1.0, 0.0];
+        let logits = head.logits(&features);
+        let expected = binary_cross_entropy_with_logits(logits[0], 1.0) + binary_cross_entropy_with_logits(logits[1], 0.0);
+        assert!((head.loss(&features, &targets) - expected).abs() < 1e-12);
+
+        let probabilities = head.predict_proba(&features);
+        assert_eq!(head.predict(&features, 0.5), vec![probabilities[0] >= 0.5, probabilities[1] >= 0.5]);
+
+        head.dense.zero_grad();
+        head.backward(&features, &targets);
+        assert!((head.dense.grads().biases[1] - probabilities[1]).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_multi_label_head_learns_independent_labels() {
+        let mut body = ModelBuilder::new(2).seed(1).dense(6).tanh().build::<f64>().unwrap();
+        let mut head = MultiLabelHead::<f64>::with_seed(6, 2, 3);
+        // Label 0: x0 > 0.5, label 1: x1 > 0.5
+        let rows: Vec<[f64; 2]> = [[0.1, 0.1], [0.9, 0.1], [0.1, 0.9], [0.9, 0.9], [0.2, 0.7], [0.8, 0.3]].to_vec();
+        let targets = |r: &[f64; 2]| [f64::from(u8::from(r[0] > 0.5)), f64::from(u8::from(r[1] > 0.5))];
+        for _ in 0..500 {
+            for r in &rows {
+                head.train_step(&mut body, r, &targets(r), 0.3);
+            }
+        }
+        for r in &rows {
+            let predicted = head.predict(&body.forward(&r[..]), 0.5);
+            assert_eq!(predicted, targets(r).iter().map(|&t| t == 1.0).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_head_shape_errors() {
+        assert_eq!(
+            BinaryHead::try_new(Dense::<f64>::new(vec![vec![0.0]; 2], vec![0.0; 2])).unwrap_err(),
+            HeadError::NotSingleOutput { found: 2 }
+        );
+        assert_eq!(MultiLabelHead::try_new(Dense::<f64>::new(Vec::new(), Vec::new())).unwrap_err(), HeadError::NoLabels);
+    }
+}
@@ -1,4 +1,5 @@
 use neuralnet::layers::*;
+use neuralnet::activation_fn::Activation;
 
 #[cfg(test)]
 mod tests {
@@ -61,4 +62,126 @@ mod tests {
         assert_eq!(layer.filters, [[1.0f64, 2.0, 3.0], [4.0, 5.0, 6.0]]);
         assert_eq!(layer.biases, [0.0f64, 0.0]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_embedding_lookup_and_update() {
+        let mut emb = Embedding::<f32, 2>::new(vec![[1.0, 2.0], [3.0, 4.0]]);
+        assert_eq!(emb.vocab_size(), 2);
+        assert_eq!(emb.forward(1), [3.0, 4.0]);
+        assert_eq!(emb.forward_sequence(&[1, 0, 1]), vec![[3.0, 4.0], [1.0, 2.0], [3.0, 4.0]]);
+
+        emb.update_weights(0, &[1.0, 1.0], 0.5);
+        assert_eq!(emb.weights[0], [0.5, 1.5]);
+
+        emb.frozen = true;
+        emb.update_weights(0, &[1.0, 1.0], 0.5);
+        assert_eq!(emb.weights[0], [0.5, 1.5]);
+    }
+
+    #[test]
+    fn test_layer1d_forward_activated_matches_two_pass() {
+        let layer = Layer1D::new([[0.5f64, -1.0], [2.0, 0.25]], [0.1, -0.3]);
+        let inputs = [1.0, 2.0];
+        let act = Activation::Tanh;
+        let (z, a) = layer.forward_activated(&inputs, &act);
+        assert_eq!(z, layer.forward(&inputs));
+        assert_eq!(a, act.forward(&z));
+    }
+
+    #[test]
+    fn test_try_linear_rejects_wrong_length() {
+        let layer = try_linear::<i32, 2, 2>(&[1, 2, 3, 4]).unwrap();
+        assert_eq!(layer.weights, [[1, 2], [3, 4]]);
+        assert_eq!(try_linear::<i32, 2, 2>(&[1, 2, 3]).err(), Some(LayerError::WrongLength { expected: 4, found: 3 }));
+        assert!(try_linear::<f64, 2, 2>(&[0.0; 5]).is_err());
+    }
+
+    #[test]
+    fn test_try_conv2d_rejects_wrong_length() {
+        assert!(try_conv2d::<i32, 2, 3>(&[1, 2, 3, 4, 5, 6]).is_ok());
+        assert_eq!(try_conv2d::<i32, 2, 3>(&[1, 2, 3]).err(), Some(LayerError::WrongLength { expected: 6, found: 3 }));
+    }
+
+    #[test]
+    fn test_embedding_try_forward() {
+        let embedding = Embedding::new(vec![[1.0f32, 2.0], [3.0, 4.0]]);
+        assert_eq!(embedding.try_forward(1), Ok([3.0, 4.0]));
+        assert_eq!(embedding.try_forward(2), Err(LayerError::IndexOutOfRange { index: 2, len: 2 }));
+        assert_eq!(embedding.try_forward_sequence(&[0, 1]).unwrap().len(), 2);
+        assert!(embedding.try_forward_sequence(&[0, 5]).is_err());
+        assert_eq!(LayerError::IndexOutOfRange { index: 2, len: 2 }.to_string(), "index 2 is out of range for 2 rows");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_fixed_size_layers_through_layer_trait() {
+        use neuralnet::back_propagation::{backward_pass, gradient_check};
+        use neuralnet::loss_fn::Loss;
+        let dense = Layer1D::new([[0.5f64, -1.0, 0.25], [2.0, 0.25, -0.5]], [0.1, -0.3]);
+        let inputs = [0.3, -0.7, 1.1];
+        assert_eq!(Layer::forward(&dense, &inputs), dense.forward(&inputs).to_vec());
+        let mut stack: Vec<Box<dyn Layer<f64>>> = vec![
+            Box::new(dense),
+            Box::new(Activation::Tanh),
+            Box::new(Layer2D::new([[1.0, -0.5], [0.25, 0.75], [-1.0, 0.5]], [0.0, 0.1, -0.1])),
+        ];
+        let check = gradient_check(&mut stack, &inputs, &[0.2, -0.1, 0.4], Loss::MeanSquaredError, 1e-6);
+        assert!(check.passes(1e-6), "{}", check.max_error());
+        assert_eq!(check.analytic.len(), 2 * 3 + 2 + 3 * 2 + 3);
+
+        // Parameters and their gradients line up, and zero_grad resets them
+        let loss = backward_pass(&mut stack[..1], &inputs, &[0.0, 0.0], Loss::MeanSquaredError);
+        assert!(loss > 0.0);
+        let names: Vec<String> = stack[0].parameters().into_iter().map(|p| p.name).collect();
+        assert_eq!(names, vec!["weights", "biases"]);
+        assert!(stack[0].params_mut().iter().any(|(_, g)| *g != 0.0));
+        stack[0].zero_grad();
+        assert!(stack[0].params_mut().iter().all(|(_, g)| *g == 0.0));
+    }
+
+    #[test]
+    fn test_embedding_through_layer_trait() {
+        let mut embedding = Embedding::<f64, 2>::new(vec![[1.0, 2.0], [3.0, 4.0], [5.0, 6.0]]);
+        assert_eq!(Layer::forward(&embedding, &[2.0, 0.0, 2.0]), vec![5.0, 6.0, 1.0, 2.0, 5.0, 6.0]);
+        let upstream = Layer::backward(&mut embedding, &[2.0, 0.0, 2.0], &[], &[1.0, 1.0, 0.5, 0.5, 2.0, -1.0]);
+        assert_eq!(upstream, vec![0.0; 3]);
+        assert_eq!(embedding.grads, vec![[0.5, 0.5], [0.0, 0.0], [3.0, 0.0]]);
+        let params = embedding.params_mut();
+        assert_eq!(params.len(), 6);
+        assert_eq!(params[4].1, 3.0);
+
+        embedding.frozen = true;
+        embedding.zero_grad();
+        Layer::backward(&mut embedding, &[1.0], &[], &[1.0, 1.0]);
+        assert_eq!(embedding.grads[1], [0.0, 0.0]);
+        assert!(embedding.params_mut().is_empty() && embedding.parameters().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "token indices")]
+    fn test_embedding_layer_rejects_fractional_index() {
+        Layer::forward(&Embedding::<f64, 1>::new(vec![[1.0], [2.0]]), &[0.5]);
+    }
+
+    #[test]
+    fn test_layer1d_flat_weights_are_row_major() {
+        let mut layer = Layer1D::new([[1.0f32, 2.0, 3.0], [4.0, 5.0, 6.0]], [0.0, 0.0]);
+        assert_eq!(layer.weights_flat(), &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(layer.weight(1, 0), layer.weights_flat()[3]);
+
+        layer.weights_flat_mut()[5] = -1.0;
+        assert_eq!(layer.weights[1][2], -1.0);
+
+        layer.update_weights(&[[1.0, 0.0, 0.0], [0.0, 0.0, 2.0]], &[1.0, -1.0], 0.5);
+        assert_eq!(layer.weights, [[0.5, 2.0, 3.0], [4.0, 5.0, -2.0]]);
+        assert_eq!(layer.biases, [-0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_layer1d_forward_into_matches_forward() {
+        let layer = Layer1D::new([[1.0f32, 2.0, 3.0], [4.0, 5.0, 6.0]], [0.5, -0.5]);
+        let mut out = [0.0; 2];
+        layer.forward_into(&[1.0, 0.0, -1.0], &mut out);
+        assert_eq!(out, layer.forward(&[1.0, 0.0, -1.0]));
+    }
+}
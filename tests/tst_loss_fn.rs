@@ -1,3 +1,5 @@
+#![cfg(feature = "std")]
+
 use neuralnet::loss_fn::*;
 
 #[cfg(test)]
@@ -81,4 +83,586 @@ mod tests {
         // Should be zero (no loss)
         assert!((bce - 0.0).abs() < 1e-6);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_weighted_loss_unit_weights_match_loss() {
+        let predictions = [0.9f64, 0.2, 0.4];
+        let targets = [1.0f64, 0.0, 1.0];
+        let weighted = WeightedLoss::new(Loss::MeanSquaredError, Weighting::PerClass(vec![1.0; 3]));
+        let plain = Loss::MeanSquaredError;
+        assert!((weighted.forward(&predictions, &targets) - plain.forward(&predictions, &targets)).abs() < 1e-12);
+        assert_eq!(weighted.derivative(&predictions, &targets), plain.derivative(&predictions, &targets));
+    }
+
+    #[test]
+    fn test_weighted_loss_per_class_cross_entropy() {
+        let predictions = [0.5f64, 0.25];
+        let targets = [0.0f64, 1.0];
+        let weighted = WeightedLoss::new(Loss::CrossEntropy, Weighting::PerClass(vec![1.0, 4.0]));
+        // -(4 * ln(0.25)) / 2
+        let expected = -4.0 * 0.25f64.ln() / 2.0;
+        assert!((weighted.forward(&predictions, &targets) - expected).abs() < 1e-12);
+        let d = weighted.derivative(&predictions, &targets);
+        assert!((d[1] + 4.0 / 0.25).abs() < 1e-9);
+        assert_eq!(d[0], 0.0);
+    }
+
+    #[test]
+    fn test_weighted_loss_binary_class_weights() {
+        let weighted = WeightedLoss::new(Loss::BinaryCrossEntropy, Weighting::PerClass(vec![1.0f64, 3.0]));
+        let bce = binary_cross_entropy_loss(0.8f64, 1.0);
+        assert!((weighted.forward(&[0.8], &[1.0]) - 3.0 * bce).abs() < 1e-12);
+        let bce = binary_cross_entropy_loss(0.8f64, 0.0);
+        assert!((weighted.forward(&[0.8], &[0.0]) - bce).abs() < 1e-12);
+
+        let d = weighted.derivative(&[0.8, 0.8], &[1.0, 0.0]);
+        assert!((d[0] + 3.0 / 0.8).abs() < 1e-9);
+        assert!((d[1] - 1.0 / 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_weighted_loss_per_sample() {
+        let predictions = [1.0f32, 2.0];
+        let targets = [0.0f32, 0.0];
+        let weighted = WeightedLoss::new(Loss::MeanSquaredError, Weighting::PerSample(vec![2.0, 0.0]));
+        // (2 * 1 + 0 * 4) / 2 = 1
+        assert!((weighted.forward(&predictions, &targets) - 1.0).abs() < 1e-6);
+        assert_eq!(weighted.derivative(&predictions, &targets), vec![4.0, 0.0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_weighted_loss_wrong_weight_count() {
+        let weighted = WeightedLoss::new(Loss::MeanSquaredError, Weighting::PerClass(vec![1.0f32]));
+        let _ = weighted.forward(&[1.0, 2.0], &[0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_loss_reduction_modes() {
+        let predictions = [1.0f64, 2.0, 3.0];
+        let targets = [1.0f64, 2.0, 5.0];
+        let loss = Loss::MeanSquaredError;
+
+        let none = loss.forward_reduced(&predictions, &targets, Reduction::None);
+        assert_eq!(none.per_element(), Some(&[0.0, 0.0, 4.0][..]));
+        assert_eq!(none.scalar(), None);
+        assert_eq!(loss.forward_reduced(&predictions, &targets, Reduction::Sum).scalar(), Some(4.0));
+        let mean = loss.forward_reduced(&predictions, &targets, Reduction::Mean).scalar().unwrap();
+        assert!((mean - loss.forward(&predictions, &targets)).abs() < 1e-12);
+        assert_eq!(Reduction::default(), Reduction::Mean);
+    }
+
+    #[test]
+    fn test_loss_derivative_respects_reduction() {
+        let predictions = [0.5f64, 0.25];
+        let targets = [1.0f64, 0.0];
+        let loss = Loss::BinaryCrossEntropy;
+        let sum = loss.derivative_reduced(&predictions, &targets, Reduction::Sum);
+        let mean = loss.derivative_reduced(&predictions, &targets, Reduction::Mean);
+        assert_eq!(sum, loss.derivative(&predictions, &targets));
+        for i in 0..2 {
+            assert!((mean[i] - sum[i] / 2.0).abs() < 1e-12);
+        }
+
+        // numerical check: d(mean BCE)/dp_0
+        let h = 1e-6;
+        let f = |p0: f64| loss.forward_reduced(&[p0, 0.25], &targets, Reduction::Mean).scalar().unwrap();
+        let numeric = (f(0.5 + h) - f(0.5 - h)) / (2.0 * h);
+        assert!((numeric - mean[0]).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_loss_forward_elementwise_cross_entropy() {
+        let terms = Loss::CrossEntropy.forward_elementwise(&[0.5f32, 0.2], &[1.0f32, 0.0]);
+        assert!((terms[0] + 0.5f32.ln()).abs() < 1e-6);
+        assert_eq!(terms[1], 0.0);
+    }
+
+    #[test]
+    fn test_loss_try_forward_errors() {
+        let mse = Loss::MeanSquaredError;
+        assert_eq!(mse.try_forward(&[1.0f32, 2.0], &[1.0]), Err(LossError::LengthMismatch { predictions: 2, targets: 1 }));
+        assert_eq!(mse.try_forward::<f32>(&[], &[]), Err(LossError::EmptyInput));
+        // MSE accepts values outside [0, 1]
+        assert_eq!(mse.try_forward(&[3.0f32], &[1.0]), Ok(4.0));
+
+        let bce = Loss::BinaryCrossEntropy;
+        assert_eq!(bce.try_forward(&[0.5f64, 0.5], &[1.0, 0.0]), Err(LossError::ExpectedSingleValue { found: 2 }));
+        assert_eq!(bce.try_forward(&[1.5f64], &[1.0]), Err(LossError::InvalidProbability { index: 0 }));
+        assert_eq!(Loss::CrossEntropy.try_forward(&[0.5f64, f64::NAN], &[1.0, 0.0]), Err(LossError::InvalidProbability { index: 1 }));
+        assert!(bce.try_forward(&[0.5f64], &[1.0]).is_ok());
+    }
+
+    #[test]
+    fn test_loss_try_forward_elementwise_errors() {
+        let mse = Loss::MeanSquaredError;
+        assert_eq!(mse.try_forward_elementwise(&[1.0f64, 3.0], &[1.0, 1.0]), Ok(vec![0.0, 4.0]));
+        assert_eq!(mse.try_forward_elementwise(&[1.0f64, 2.0], &[1.0]), Err(LossError::LengthMismatch { predictions: 2, targets: 1 }));
+        assert_eq!(
+            mse.try_forward_reduced(&[1.0f64], &[1.0, 2.0], Reduction::Sum),
+            Err(LossError::LengthMismatch { predictions: 1, targets: 2 })
+        );
+        assert_eq!(mse.try_forward_reduced(&[1.0f64, 3.0], &[1.0, 1.0], Reduction::Mean), Ok(LossOutput::Scalar(2.0)));
+        // Same validation as try_forward
+        assert_eq!(mse.try_forward_reduced::<f64>(&[], &[], Reduction::Mean), Err(LossError::EmptyInput));
+        assert_eq!(mse.try_forward_elementwise::<f64>(&[], &[]), Err(LossError::EmptyInput));
+        let bce = Loss::BinaryCrossEntropy;
+        assert_eq!(bce.try_forward_elementwise(&[0.5f64, 1.5], &[1.0, 0.0]), Err(LossError::InvalidProbability { index: 1 }));
+        assert_eq!(
+            Loss::CrossEntropy.try_forward_reduced(&[0.5f64, 0.5], &[1.0, -0.5], Reduction::Sum),
+            Err(LossError::InvalidProbability { index: 1 })
+        );
+        assert_eq!(Loss::Hinge.try_forward_elementwise(&[0.5f64], &[0.0]), Err(LossError::InvalidMarginTarget { index: 0 }));
+        assert_eq!(Loss::Poisson.try_forward_elementwise(&[-1.0f64], &[1.0]), Err(LossError::OutOfDomain { index: 0 }));
+        assert!(bce.try_forward_elementwise(&[0.25f64, 0.75], &[0.0, 1.0]).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "predictions and targets must have the same length (got 2 and 1)")]
+    fn test_loss_forward_elementwise_panics_on_length_mismatch() {
+        let _ = Loss::MeanAbsoluteError.forward_elementwise(&[1.0f32, 2.0], &[1.0]);
+    }
+
+    #[test]
+    fn test_loss_try_derivative() {
+        let bce = Loss::BinaryCrossEntropy;
+        assert_eq!(bce.try_derivative(&[0.5f64, 0.5], &[1.0, 0.0]).unwrap(), bce.derivative(&[0.5, 0.5], &[1.0, 0.0]));
+        assert_eq!(bce.try_derivative(&[0.5f64], &[2.0]), Err(LossError::InvalidProbability { index: 0 }));
+        assert_eq!(Loss::MeanSquaredError.try_derivative(&[0.5f64], &[]), Err(LossError::LengthMismatch { predictions: 1, targets: 0 }));
+    }
+
+    #[test]
+    #[should_panic(expected = "BinaryCrossEntropy loss expects single prediction and target values")]
+    fn test_loss_forward_panics_with_error_message() {
+        let _ = Loss::BinaryCrossEntropy.forward(&[0.5f32, 0.5], &[1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_binary_cross_entropy_f32_stays_finite_at_one() {
+        // 1 - 1e-15 rounds to 1 in f32; the epsilon must adapt to the type
+        let loss = binary_cross_entropy_loss(1.0f32, 0.0);
+        assert!(loss.is_finite());
+        let grads = Loss::BinaryCrossEntropy.derivative(&[1.0f32], &[0.0]);
+        assert!(grads[0].is_finite());
+    }
+
+    #[test]
+    fn test_kl_divergence() {
+        let targets = [0.5f64, 0.5, 0.0];
+        assert!(kl_divergence(&targets, &targets).abs() < 1e-12);
+
+        let predictions = [0.25, 0.5, 0.25];
+        let expected = (0.5 * (0.5f64 / 0.25).ln()) / 3.0;
+        assert!((Loss::KLDivergence.forward(&predictions, &targets) - expected).abs() < 1e-12);
+        // KL = CE - H(targets)
+        let entropy = -(0.5f64.ln()) / 3.0;
+        assert!((Loss::CrossEntropy.forward(&predictions, &targets) - entropy - expected).abs() < 1e-12);
+        assert_eq!(Loss::KLDivergence.derivative(&predictions, &targets), Loss::CrossEntropy.derivative(&predictions, &targets));
+        assert_eq!(
+            Loss::KLDivergence.try_forward(&[0.5, 1.5], &[0.5, 0.5]),
+            Err(LossError::InvalidProbability { index: 1 })
+        );
+    }
+
+    #[test]
+    fn test_hinge_losses() {
+        let scores = [2.0f64, 0.5, -0.5, 0.25];
+        let targets = [1.0, 1.0, 1.0, -1.0];
+        // margins: 0, 0.5, 1.5, 1.25
+        assert!((Loss::Hinge.forward(&scores, &targets) - 3.25 / 4.0).abs() < 1e-12);
+        assert!((Loss::SquaredHinge.forward(&scores, &targets) - (0.25 + 2.25 + 1.5625) / 4.0).abs() < 1e-12);
+        assert_eq!(hinge_loss(&scores, &targets), Loss::Hinge.forward(&scores, &targets));
+        assert_eq!(squared_hinge_loss(&scores, &targets), Loss::SquaredHinge.forward(&scores, &targets));
+
+        assert_eq!(Loss::Hinge.derivative(&scores, &targets), vec![0.0, -1.0, -1.0, 1.0]);
+        assert_eq!(Loss::SquaredHinge.derivative(&scores, &targets), vec![0.0, -1.0, -3.0, 2.5]);
+        assert_eq!(
+            Loss::Hinge.forward_reduced(&scores, &targets, Reduction::None).per_element().unwrap(),
+            &[0.0, 0.5, 1.5, 1.25]
+        );
+        assert_eq!(Loss::Hinge.try_forward(&[1.0, 1.0], &[1.0, 0.0]), Err(LossError::InvalidMarginTarget { index: 1 }));
+    }
+
+    #[test]
+    fn test_new_losses_derivatives_match_finite_differences() {
+        let h = 1e-6;
+        let cases: [(Loss, [f64; 3], [f64; 3]); 3] = [
+            (Loss::KLDivergence, [0.2, 0.3, 0.5], [0.1, 0.6, 0.3]),
+            (Loss::Hinge, [0.3, -2.0, 0.7], [1.0, 1.0, -1.0]),
+            (Loss::SquaredHinge, [0.3, -2.0, 0.7], [1.0, 1.0, -1.0]),
+        ];
+        for (loss, predictions, targets) in cases {
+            let analytic = loss.derivative_reduced(&predictions, &targets, Reduction::Sum);
+            for i in 0..3 {
+                let mut plus = predictions;
+                let mut minus = predictions;
+                plus[i] += h;
+                minus[i] -= h;
+                let sum = |p: &[f64]| loss.forward_reduced(p, &targets, Reduction::Sum).scalar().unwrap();
+                let numeric = (sum(&plus) - sum(&minus)) / (2.0 * h);
+                assert!((numeric - analytic[i]).abs() < 1e-5, "{:?} index {}", loss, i);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mean_absolute_error_and_derivative() {
+        let predictions = [1.0f64, 2.5, 3.0];
+        let targets = [2.0f64, 2.0, 3.0];
+        assert!((mean_absolute_error(&predictions, &targets) - 0.5).abs() < 1e-12);
+        assert_eq!(Loss::MeanAbsoluteError.forward(&predictions, &targets), mean_absolute_error(&predictions, &targets));
+        assert_eq!(Loss::MeanAbsoluteError.forward_elementwise(&predictions, &targets), vec![1.0, 0.5, 0.0]);
+        assert_eq!(Loss::MeanAbsoluteError.derivative(&predictions, &targets), vec![-1.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_regression_losses_average_over_outputs_in_training() {
+        assert_eq!(Loss::MeanSquaredError.output_reduction(), Reduction::Mean);
+        assert_eq!(Loss::MeanAbsoluteError.output_reduction(), Reduction::Mean);
+        assert_eq!(Loss::CrossEntropy.output_reduction(), Reduction::Sum);
+        assert_eq!(Loss::Hinge.output_reduction(), Reduction::Sum);
+    }
+
+    #[test]
+    fn test_cross_entropy_with_logits_matches_softmax_cross_entropy() {
+        use neuralnet::activation_fn::softmax;
+        let logits = [0.5f64, -1.0, 2.0];
+        let targets = [0.0, 0.0, 1.0];
+        let expected = cross_entropy_loss(&softmax(&logits), &targets);
+        assert!((cross_entropy_with_logits(&logits, &targets) - expected).abs() < 1e-12);
+        // A confidently wrong logit gives a large but finite loss instead of the clamp
+        let loss = cross_entropy_with_logits(&[1000.0f64, -1000.0], &[0.0, 1.0]);
+        assert!((loss - 1000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_class_index_targets_match_one_hot() {
+        let predictions = [0.1f64, 0.7, 0.2];
+        let one_hot = [0.0, 1.0, 0.0];
+        for loss in [Loss::CrossEntropy, Loss::KLDivergence] {
+            assert!((loss.forward_class(&predictions, 1) - loss.forward(&predictions, &one_hot)).abs() < 1e-12);
+            assert_eq!(loss.derivative_class(&predictions, 1), loss.derivative(&predictions, &one_hot));
+        }
+        assert_eq!(sparse_cross_entropy_loss(&predictions, 1), cross_entropy_loss(&predictions, &one_hot));
+        // A zero probability is clamped instead of giving an infinite loss
+        assert!(Loss::CrossEntropy.forward_class(&[1.0f64, 0.0], 1).is_finite());
+    }
+
+    #[test]
+    fn test_class_index_targets_are_validated() {
+        let predictions = [0.4f64, 0.6];
+        assert_eq!(Loss::CrossEntropy.try_forward_class(&predictions, 2), Err(LossError::ClassOutOfRange { class: 2, classes: 2 }));
+        assert_eq!(Loss::MeanSquaredError.try_forward_class(&predictions, 0), Err(LossError::UnsupportedClassTarget));
+        assert_eq!(Loss::CrossEntropy.try_derivative_class(&[0.5f64, 1.5], 0), Err(LossError::InvalidProbability { index: 1 }));
+        assert_eq!(Loss::CrossEntropy.try_forward_class::<f64>(&[], 0), Err(LossError::ClassOutOfRange { class: 0, classes: 0 }));
+    }
+
+    #[test]
+    fn test_class_index_batches() {
+        let loss = Loss::CrossEntropy;
+        let rows = [[0.1f64, 0.7, 0.2], [0.5, 0.25, 0.25]];
+        let flat = rows.as_flattened();
+        let classes = [1, 0];
+        let mean = (loss.forward_class(&rows[0], 1) + loss.forward_class(&rows[1], 0)) / 2.0;
+        assert!((loss.forward_classes(flat, &classes) - mean).abs() < 1e-12);
+
+        let gradient = loss.derivative_classes(flat, &classes);
+        let expected: Vec<f64> = loss.derivative_class(&rows[0], 1).into_iter()
+            .chain(loss.derivative_class(&rows[1], 0))
+            .map(|g| g / 2.0)
+            .collect();
+        assert_eq!(gradient, expected);
+
+        assert_eq!(loss.try_forward_classes::<f64>(flat, &[]), Err(LossError::EmptyInput));
+        assert_eq!(loss.try_forward_classes(flat, &[0, 1, 2, 0]), Err(LossError::LengthMismatch { predictions: 6, targets: 4 }));
+        assert_eq!(loss.try_forward_classes(flat, &[1, 3]), Err(LossError::ClassOutOfRange { class: 3, classes: 3 }));
+        assert_eq!(loss.try_derivative_classes(&[0.5f64, 0.5, 0.5, 1.5], &[0, 0]), Err(LossError::InvalidProbability { index: 3 }));
+        assert_eq!(Loss::Hinge.try_forward_classes(flat, &classes), Err(LossError::UnsupportedClassTarget));
+    }
+
+    /// Log-cosh: quadratic near zero and linear for large errors.
+    struct LogCosh;
+
+    impl LossFn<f64> for LogCosh {
+        fn objective(&self, predictions: &[f64], targets: &[f64]) -> f64 {
+            predictions.iter().zip(targets.iter()).map(|(p, t)| (p - t).cosh().ln()).sum()
+        }
+
+        fn objective_derivative(&self, predictions: &[f64], targets: &[f64]) -> Vec<f64> {
+            predictions.iter().zip(targets.iter()).map(|(p, t)| (p - t).tanh()).collect()
+        }
+    }
+
+    #[test]
+    fn test_loss_fn_matches_training_reduction() {
+        let (predictions, targets) = ([0.7f64, 0.2, 0.1], [1.0, 0.0, 0.0]);
+        // MSE is averaged over the outputs, cross-entropy summed
+        let mse = LossFn::objective(&Loss::MeanSquaredError, &predictions, &targets);
+        assert!((mse - mean_squared_error(&predictions, &targets)).abs() < 1e-12);
+        let ce = LossFn::objective(&Loss::CrossEntropy, &predictions, &targets);
+        assert!((ce + 0.7f64.ln()).abs() < 1e-12);
+        assert_eq!(LossFn::objective_derivative(&Loss::CrossEntropy, &predictions, &targets), Loss::CrossEntropy.derivative_reduced(&predictions, &targets, Reduction::Sum));
+
+        let weighted = WeightedLoss::new(Loss::MeanSquaredError, Weighting::PerClass(vec![2.0, 1.0, 1.0]));
+        let boxed: Box<dyn LossFn<f64>> = Box::new(weighted);
+        assert_eq!(boxed.objective_derivative(&predictions, &targets).len(), 3);
+    }
+
+    #[test]
+    fn test_custom_loss_trains_through_trainer() {
+        use neuralnet::model::ModelBuilder;
+        use neuralnet::training::Trainer;
+        let rows: Vec<Vec<f64>> = (0..20).map(|i| vec![i as f64 / 10.0 - 1.0]).collect();
+        let targets: Vec<Vec<f64>> = rows.iter().map(|r| vec![3.0 * r[0] - 1.0]).collect();
+        let model = ModelBuilder::new(1).seed(2).dense(1).build::<f64>().unwrap();
+
+        let loss: Box<dyn LossFn<f64>> = Box::new(LogCosh);
+        let mut trained = model.clone();
+        let history = Trainer::new(Loss::MeanSquaredError, 0.1, 200).fit_with_loss(&mut trained, &loss, &rows, &targets);
+        assert!(history.records.last().unwrap().train_loss < 1e-3);
+        assert!((trained.forward(&[0.5])[0] - 0.5).abs() < 0.05);
+
+        // A built-in loss passed as a `LossFn` trains exactly like `fit`
+        let (mut a, mut b) = (model.clone(), model);
+        let trainer = Trainer::new(Loss::MeanAbsoluteError, 0.05, 5).shuffle(3);
+        assert_eq!(trainer.fit_with_loss(&mut a, &Loss::MeanAbsoluteError, &rows, &targets), trainer.fit(&mut b, &rows, &targets));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_loss_registry() {
+        let mut registry = LossRegistry::<f64>::new();
+        assert_eq!(registry.names().len(), 8);
+        let mse = registry.get("MeanSquaredError").unwrap();
+        assert_eq!(mse.objective(&[1.0, 3.0], &[1.0, 1.0]), 2.0);
+
+        assert!(registry.register("LogCosh", Box::new(LogCosh)).is_none());
+        assert!((registry.try_get("LogCosh").unwrap().objective(&[1.0], &[1.0]) - 0.0).abs() < 1e-12);
+        let error = registry.try_get("Huber").err().unwrap();
+        assert!(error.contains("Huber") && error.contains("LogCosh"));
+    }
+
+    #[test]
+    fn test_composite_loss_sums_weighted_terms() {
+        let loss = CompositeLoss::new()
+            .with(1.0, Loss::MeanSquaredError)
+            .with(0.5, L1ActivationPenalty)
+            .with(2.0, LogCosh);
+        assert_eq!(loss.len(), 3);
+        let (predictions, targets) = ([0.3f64, -0.8, 1.5], [0.0, -1.0, 1.0]);
+        let terms = loss.term_values(&predictions, &targets);
+        assert!((loss.objective(&predictions, &targets) - (terms[0] + 0.5 * terms[1] + 2.0 * terms[2])).abs() < 1e-12);
+        assert!((terms[1] - 2.6).abs() < 1e-12);
+
+        let grad = loss.objective_derivative(&predictions, &targets);
+        let h = 1e-6;
+        for k in 0..3 {
+            let (mut plus, mut minus) = (predictions, predictions);
+            plus[k] += h;
+            minus[k] -= h;
+            let numeric = (loss.objective(&plus, &targets) - loss.objective(&minus, &targets)) / (2.0 * h);
+            assert!((numeric - grad[k]).abs() < 1e-6, "{} vs {}", numeric, grad[k]);
+        }
+
+        let empty = CompositeLoss::<f64>::default();
+        assert!(empty.is_empty());
+        assert_eq!(empty.objective(&predictions, &targets), 0.0);
+        assert_eq!(empty.objective_derivative(&predictions, &targets), vec![0.0; 3]);
+    }
+
+    #[test]
+    fn test_activation_penalty_shrinks_outputs() {
+        use neuralnet::model::ModelBuilder;
+        use neuralnet::training::Trainer;
+        let rows: Vec<Vec<f64>> = (0..10).map(|i| vec![i as f64 / 5.0 - 1.0]).collect();
+        let targets: Vec<Vec<f64>> = rows.iter().map(|r| vec![2.0 * r[0]]).collect();
+        let model = ModelBuilder::new(1).seed(1).dense(1).build::<f64>().unwrap();
+        let trainer = Trainer::new(Loss::MeanSquaredError, 0.05, 200);
+
+        let mut plain = model.clone();
+        trainer.fit(&mut plain, &rows, &targets);
+        let mut penalized = model;
+        let mut loss = CompositeLoss::new().with(1.0, Loss::MeanSquaredError);
+        loss.push(0.5, Box::new(L1ActivationPenalty));
+        trainer.fit_with_loss(&mut penalized, &loss, &rows, &targets);
+        let magnitude = |m: &neuralnet::model::Sequential<f64>| rows.iter().map(|r| m.forward(r)[0].abs()).sum::<f64>();
+        assert!(magnitude(&penalized) < magnitude(&plain));
+    }
+
+    #[test]
+    fn test_sample_weighted_forward_and_derivative() {
+        let (predictions, targets) = ([1.0f64, 2.0, 4.0], [1.0, 1.0, 1.0]);
+        let loss = Loss::MeanSquaredError;
+        assert_eq!(loss.forward_weighted(&predictions, &targets, None), loss.forward(&predictions, &targets));
+        assert_eq!(loss.derivative_weighted(&predictions, &targets, None), loss.derivative(&predictions, &targets));
+
+        // (0 + 2 * 1 + 0.5 * 9) / 3
+        let weights = [3.0, 2.0, 0.5];
+        assert!((loss.forward_weighted(&predictions, &targets, Some(&weights)) - 6.5 / 3.0).abs() < 1e-12);
+        let unweighted = loss.derivative(&predictions, &targets);
+        let weighted = loss.derivative_weighted(&predictions, &targets, Some(&weights));
+        for i in 0..3 {
+            assert!((weighted[i] - weights[i] * unweighted[i]).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_sample_weighted_binary_cross_entropy_batch() {
+        let (predictions, targets) = ([0.9f64, 0.2, 0.6], [1.0, 0.0, 0.0]);
+        let weights = [1.0, 3.0, 0.5];
+        let bce = Loss::BinaryCrossEntropy;
+        let terms: Vec<f64> = predictions.iter().zip(targets.iter()).map(|(&p, &t)| binary_cross_entropy_loss(p, t)).collect();
+
+        let unweighted = bce.forward_weighted(&predictions, &targets, None);
+        assert!((unweighted - terms.iter().sum::<f64>() / 3.0).abs() < 1e-12);
+        let expected = (terms[0] + 3.0 * terms[1] + 0.5 * terms[2]) / 3.0;
+        assert!((bce.forward_weighted(&predictions, &targets, Some(&weights)) - expected).abs() < 1e-12);
+        assert!((bce.forward_weighted(&predictions, &targets, Some(&[1.0; 3])) - unweighted).abs() < 1e-12);
+
+        let derivative = bce.derivative(&predictions, &targets);
+        let weighted = bce.derivative_weighted(&predictions, &targets, Some(&weights));
+        for i in 0..3 {
+            assert!((weighted[i] - weights[i] * derivative[i]).abs() < 1e-12);
+        }
+
+        // Per-class weights average over the batch the same way
+        let per_class = WeightedLoss::new(bce, Weighting::PerClass(vec![2.0, 1.0]));
+        let expected = (terms[0] + 2.0 * terms[1] + 2.0 * terms[2]) / 3.0;
+        assert!((per_class.forward(&predictions, &targets) - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_masked_loss_ignores_padding() {
+        use neuralnet::dataset::pad_batch;
+        let sequences = vec![
+            vec![vec![0.2, 0.8], vec![0.6, 0.4], vec![0.9, 0.1]],
+            vec![vec![0.5, 0.5]],
+        ];
+        let targets = vec![
+            vec![vec![0.0, 1.0], vec![1.0, 0.0], vec![1.0, 0.0]],
+            vec![vec![0.0, 1.0]],
+        ];
+        // NaN padding would poison any term it reached
+        let (padded, lengths) = pad_batch(&sequences, &[0, 1], vec![f64::NAN; 2]);
+        let (padded_targets, _) = pad_batch(&targets, &[0, 1], vec![f64::NAN; 2]);
+        let mask = sequence_mask(&lengths, 3);
+        assert_eq!(mask, vec![vec![true, true, true], vec![true, false, false]]);
+
+        let (predictions, targets, mask) = (padded.concat(), padded_targets.concat(), mask.concat());
+        let loss = Loss::CrossEntropy;
+        let expected = [0.8f64, 0.6, 0.9, 0.5].iter().map(|p| -p.ln()).sum::<f64>() / 4.0;
+        assert!((masked_forward(&loss, &predictions, &targets, &mask) - expected).abs() < 1e-12);
+
+        let grad = masked_derivative(&loss, &predictions, &targets, &mask);
+        assert_eq!(grad[4], vec![0.0, 0.0]);
+        assert_eq!(grad[5], vec![0.0, 0.0]);
+        let h = 1e-6;
+        for (t, k) in [(0, 1), (2, 0), (3, 1)] {
+            let (mut plus, mut minus) = (predictions.clone(), predictions.clone());
+            plus[t][k] += h;
+            minus[t][k] -= h;
+            let numeric = (masked_forward(&loss, &plus, &targets, &mask) - masked_forward(&loss, &minus, &targets, &mask)) / (2.0 * h);
+            assert!((numeric - grad[t][k]).abs() < 1e-6, "{} vs {}", numeric, grad[t][k]);
+        }
+
+        assert_eq!(masked_forward(&loss, &predictions, &targets, &[false; 6]), 0.0);
+    }
+
+    #[test]
+    fn test_quantile_loss_and_derivative() {
+        let (predictions, targets) = ([1.0f64, 3.0, 2.0], [2.0, 1.0, 2.0]);
+        // Under-prediction by 1 costs 0.9, over-prediction by 2 costs 2 * 0.1
+        let loss = Loss::Quantile(0.9);
+        assert!((loss.forward(&predictions, &targets) - 1.1 / 3.0).abs() < 1e-12);
+        assert!((quantile_loss(&predictions, &targets, 0.5) - 1.5 / 3.0).abs() < 1e-12);
+        let elementwise = loss.forward_elementwise(&predictions, &targets);
+        assert!((elementwise[0] - 0.9).abs() < 1e-12 && (elementwise[1] - 0.2).abs() < 1e-12);
+        let derivative = loss.derivative(&predictions, &targets);
+        assert!((derivative[0] + 0.9).abs() < 1e-12 && (derivative[1] - 0.1).abs() < 1e-12);
+        assert_eq!(derivative[2], 0.0);
+
+        assert_eq!(Loss::Quantile(1.0).try_forward(&predictions, &targets), Err(LossError::InvalidQuantile { tau: 1.0 }));
+        assert!(Loss::Quantile(0.0).try_derivative(&predictions, &targets).is_err());
+        assert_eq!(serde_json::from_str::<Loss>(r#"{"Quantile":0.1}"#).unwrap(), Loss::Quantile(0.1));
+    }
+
+    #[test]
+    fn test_quantile_training_brackets_targets() {
+        use neuralnet::model::ModelBuilder;
+        use neuralnet::random::Rng;
+        use neuralnet::training::Trainer;
+        let mut rng = Rng::new(4);
+        let rows: Vec<Vec<f64>> = (0..200).map(|i| vec![i as f64 / 100.0 - 1.0]).collect();
+        let targets: Vec<Vec<f64>> = rows.iter().map(|r| vec![r[0] + rng.next_f64() - 0.5]).collect();
+
+        let coverage = |tau: f64| {
+            let mut model = ModelBuilder::new(1).seed(2).dense(1).build::<f64>().unwrap();
+            Trainer::new(Loss::Quantile(tau), 0.02, 100).shuffle(1).fit(&mut model, &rows, &targets);
+            rows.iter().zip(targets.iter()).filter(|(r, t)| t[0] <= model.forward(r)[0]).count() as f64 / 200.0
+        };
+        let (low, high) = (coverage(0.1), coverage(0.9));
+        assert!((0.03..0.2).contains(&low), "{}", low);
+        assert!((0.8..0.97).contains(&high), "{}", high);
+    }
+
+    #[test]
+    fn test_tweedie_deviance_and_derivative() {
+        let (predictions, targets) = ([0.5f64, 2.0, 3.0], [0.0, 2.0, 5.0]);
+        // 2 (0 - 0 + 0.5) + 0 + 2 (5 ln(5/3) - 2)
+        let expected = (1.0 + 2.0 * (5.0 * (5.0f64 / 3.0).ln() - 2.0)) / 3.0;
+        assert!((Loss::Poisson.forward(&predictions, &targets) - expected).abs() < 1e-12);
+        assert!((poisson_deviance(&predictions, &targets) - tweedie_deviance(&predictions, &targets, 1.0)).abs() < 1e-12);
+        assert!((Loss::Tweedie(0.0).forward(&predictions, &targets) - mean_squared_error(&predictions, &targets)).abs() < 1e-12);
+
+        // The general formula approaches the special cases at powers 1 and 2
+        let positive = [1.0, 2.0, 5.0];
+        for power in [1.0, 2.0] {
+            let closed = tweedie_deviance(&predictions, &positive, power);
+            let near = tweedie_deviance(&predictions, &positive, power + 1e-7);
+            assert!((closed - near).abs() < 1e-5, "{}: {} vs {}", power, closed, near);
+        }
+
+        let h = 1e-6;
+        for loss in [Loss::Poisson, Loss::Tweedie(0.0), Loss::Tweedie(1.5), Loss::Tweedie(2.0), Loss::Tweedie(3.0)] {
+            let gradient = loss.derivative(&predictions, &positive);
+            let terms = |p: f64, k: usize| loss.forward_elementwise(&[p], &positive[k..k + 1])[0];
+            for k in 0..3 {
+                let numeric = (terms(predictions[k] + h, k) - terms(predictions[k] - h, k)) / (2.0 * h);
+                assert!((numeric - gradient[k]).abs() < 1e-5, "{:?}: {} vs {}", loss, numeric, gradient[k]);
+            }
+        }
+
+        assert_eq!(Loss::Poisson.try_forward(&[0.0, 1.0], &[1.0, 1.0]), Err(LossError::OutOfDomain { index: 0 }));
+        assert_eq!(Loss::Poisson.try_forward(&[1.0, 1.0], &[1.0, -1.0]), Err(LossError::OutOfDomain { index: 1 }));
+        assert!(Loss::Tweedie(1.5).try_forward(&[1.0], &[0.0]).is_ok());
+        assert_eq!(Loss::Tweedie(2.0).try_derivative(&[1.0], &[0.0]), Err(LossError::OutOfDomain { index: 0 }));
+        assert_eq!(Loss::Tweedie(0.5).try_forward(&[1.0], &[1.0]), Err(LossError::InvalidTweediePower { power: 0.5 }));
+        assert!(Loss::Tweedie(0.0).try_forward(&[-1.0], &[-2.0]).is_ok());
+    }
+
+    #[test]
+    fn test_poisson_training_fits_rates() {
+        use neuralnet::activation_fn::Activation;
+        use neuralnet::model::ModelBuilder;
+        use neuralnet::random::Rng;
+        use neuralnet::training::Trainer;
+        // Knuth's sampler for counts with rate `1 + 4 x`
+        let mut rng = Rng::new(9);
+        let mut sample = |rate: f64| {
+            let (limit, mut product, mut count) = ((-rate).exp(), rng.next_f64(), 0);
+            while product > limit {
+                product *= rng.next_f64();
+                count += 1;
+            }
+            count as f64
+        };
+        let rows: Vec<Vec<f64>> = (0..300).map(|i| vec![(i % 100) as f64 / 100.0]).collect();
+        let targets: Vec<Vec<f64>> = rows.iter().map(|r| vec![sample(1.0 + 4.0 * r[0])]).collect();
+
+        let mut model = ModelBuilder::new(1).seed(3).dense(1).activation(Activation::Softplus).build::<f64>().unwrap();
+        let history = Trainer::new(Loss::Poisson, 0.02, 60).shuffle(0).fit(&mut model, &rows, &targets);
+        assert!(history.records.last().unwrap().train_loss < history.records[0].train_loss);
+        let (low, high) = (model.forward(&[0.1])[0], model.forward(&[0.9])[0]);
+        assert!((low - 1.4).abs() < 0.5 && (high - 4.6).abs() < 0.8, "{} {}", low, high);
+    }
+}
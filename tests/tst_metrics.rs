@@ -0,0 +1,269 @@
+#![cfg(feature = "std")]
+
+use neuralnet::metrics::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mcnemar_counts_and_p_value() {
+        // 10 samples only A gets right, 2 only B gets right, 5 both right, 3 both wrong
+        let mut targets = Vec::new();
+        let mut a = Vec::new();
+        let mut b = Vec::new();
+        for _ in 0..10 { targets.push(1usize); a.push(1usize); b.push(0usize); }
+        for _ in 0..2 { targets.push(1); a.push(0); b.push(1); }
+        for _ in 0..5 { targets.push(0); a.push(0); b.push(0); }
+        for _ in 0..3 { targets.push(0); a.push(1); b.push(1); }
+
+        let result = mcnemar_test(&a, &b, &targets);
+        assert_eq!(result.only_a_correct, 10);
+        assert_eq!(result.only_b_correct, 2);
+        // (|10 - 2| - 1)^2 / 12 = 49 / 12
+        assert!((result.statistic - 49.0 / 12.0).abs() < 1e-12);
+        assert!((result.p_value - 0.04331).abs() < 1e-4);
+        assert!(result.is_significant(0.05));
+        assert!(!result.is_significant(0.01));
+    }
+
+    #[test]
+    fn test_mcnemar_identical_models() {
+        let targets = [0, 1, 1, 0];
+        let preds = [0, 1, 0, 0];
+        let result = mcnemar_test(&preds, &preds, &targets);
+        assert_eq!(result.statistic, 0.0);
+        assert_eq!(result.p_value, 1.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_mcnemar_length_mismatch() {
+        let _ = mcnemar_test(&[0, 1], &[0], &[0, 1]);
+    }
+
+    #[test]
+    fn test_paired_t_test_f64() {
+        let a = [1.0f64, 2.0, 3.0, 4.0, 5.0];
+        let b = [2.0f64, 2.0, 4.0, 5.0, 5.0];
+        let result = paired_t_test(&a, &b);
+        assert!((result.mean_difference + 0.6).abs() < 1e-12);
+        assert!((result.statistic + 2.4494897).abs() < 1e-6);
+        assert_eq!(result.degrees_of_freedom, 4);
+        assert!((result.p_value - 0.0705).abs() < 1e-3);
+        assert!(!result.is_significant(0.05));
+    }
+
+    #[test]
+    fn test_paired_t_test_no_difference() {
+        let a = [0.5f32, 0.25, 0.75];
+        let result = paired_t_test(&a, &a);
+        assert_eq!(result.statistic, 0.0);
+        assert_eq!(result.p_value, 1.0);
+    }
+
+    #[test]
+    fn test_paired_t_test_constant_shift() {
+        let a = [2.0f64, 3.0, 4.0];
+        let b = [1.0f64, 2.0, 3.0];
+        let result = paired_t_test(&a, &b);
+        assert!(result.statistic.is_infinite());
+        assert_eq!(result.p_value, 0.0);
+    }
+
+    #[test]
+    fn test_confusion_matrix_and_argmax() {
+        let matrix = confusion_matrix(&[0, 1, 1, 2], &[0, 1, 2, 2], 3);
+        assert_eq!(matrix, vec![vec![1, 0, 0], vec![0, 1, 0], vec![0, 1, 1]]);
+        assert_eq!(argmax(&[0.1f32, 0.7, 0.2]), 1);
+        assert_eq!(argmax(&[1, 1]), 0);
+    }
+
+    #[test]
+    fn test_top_misclassifications_per_cell() {
+        // The "model" returns the feature row itself as probabilities
+        let features = vec![
+            vec![0.9f64, 0.1], // true 1, predicted 0 (conf 0.9)
+            vec![0.6, 0.4],    // true 1, predicted 0 (conf 0.6)
+            vec![0.7, 0.3],    // true 1, predicted 0 (conf 0.7)
+            vec![0.2, 0.8],    // true 0, predicted 1 (conf 0.8)
+            vec![0.1, 0.9],    // true 1, correct
+        ];
+        let targets = [1, 1, 1, 0, 1];
+        let errors = top_misclassifications(|x: &[f64]| x.to_vec(), &features, &targets, 2);
+
+        assert_eq!(errors.len(), 3);
+        assert_eq!((errors[0].row, errors[0].true_class, errors[0].predicted_class), (3, 0, 1));
+        assert_eq!(errors[1].row, 0);
+        assert_eq!(errors[2].row, 2);
+        assert_eq!(errors[2].confidence, 0.7);
+    }
+
+    #[test]
+    fn test_export_misclassifications_csv() {
+        let features = vec![vec![0.25f64, 0.75], vec![0.5, 0.5]];
+        let errors = vec![Misclassification { row: 0, true_class: 0, predicted_class: 1, confidence: 0.75 }];
+        let file = tempfile::NamedTempFile::new().unwrap();
+        export_misclassifications_csv(file.path(), &errors, &features, &["a"]).unwrap();
+
+        let text = std::fs::read_to_string(file.path()).unwrap();
+        assert_eq!(text, "row,true_class,predicted_class,confidence,a,f1\n0,0,1,0.75,0.25,0.75\n");
+    }
+
+    #[test]
+    fn test_calibration_curve_bins_and_errors() {
+        let probabilities = [0.1f64, 0.1, 0.9, 0.9, 1.0];
+        let labels = [0.0f64, 1.0, 1.0, 1.0, 1.0];
+        let curve = calibration_curve(&probabilities, &labels, 2);
+
+        assert_eq!(curve.bins.len(), 2);
+        assert_eq!(curve.bins[0].count, 2);
+        assert!((curve.bins[0].mean_predicted - 0.1).abs() < 1e-12);
+        assert!((curve.bins[0].observed_frequency - 0.5).abs() < 1e-12);
+        assert_eq!(curve.bins[1].count, 3);
+        assert!((curve.bins[1].observed_frequency - 1.0).abs() < 1e-12);
+
+        // gaps: 0.4 (2 samples) and 1 - 2.8/3 (3 samples)
+        let gap_high = 1.0 - 2.8 / 3.0;
+        assert!((curve.ece - (2.0 / 5.0 * 0.4 + 3.0 / 5.0 * gap_high)).abs() < 1e-12);
+        assert!((curve.mce - 0.4).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_calibration_curve_perfect_and_csv() {
+        let curve = calibration_curve(&[0.0f32, 1.0], &[0.0f32, 1.0], 10);
+        assert_eq!(curve.bins.len(), 2);
+        assert_eq!(curve.ece, 0.0);
+        assert_eq!(curve.mce, 0.0);
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        curve.to_csv(file.path()).unwrap();
+        let text = std::fs::read_to_string(file.path()).unwrap();
+        assert_eq!(text.lines().count(), 3);
+        assert!(text.starts_with("lower,upper,count,mean_predicted,observed_frequency\n0,0.1,1,0,0\n"));
+    }
+
+    #[test]
+    fn test_accuracy_streaming_and_merge() {
+        assert_eq!(accuracy(&[0, 1, 1, 2], &[0, 1, 2, 2]), 0.75);
+        let mut left = Accuracy::new();
+        left.accumulate((1, 1));
+        let mut right = Accuracy::new();
+        right.accumulate((0, 1));
+        right.accumulate((2, 2));
+        left.merge(&right);
+        assert!((left.finalize() - 2.0 / 3.0).abs() < 1e-12);
+        assert_eq!(Accuracy::new().finalize(), 0.0);
+    }
+
+    #[test]
+    fn test_streaming_merge_matches_batch() {
+        let a: Vec<f64> = (0..50).map(|i| ((i * 7) % 11) as f64 / 10.0).collect();
+        let b: Vec<f64> = (0..50).map(|i| ((i * 3) % 13) as f64 / 10.0).collect();
+
+        // paired t-test split into two shards
+        let mut first = PairedTTest::new();
+        let mut second = PairedTTest::new();
+        for i in 0..50 {
+            if i < 17 { first.accumulate((a[i], b[i])) } else { second.accumulate((a[i], b[i])) }
+        }
+        first.merge(&second);
+        let merged = first.finalize();
+        let batch = paired_t_test(&a, &b);
+        assert!((merged.statistic - batch.statistic).abs() < 1e-9);
+        assert!((merged.p_value - batch.p_value).abs() < 1e-9);
+
+        // calibration split into two shards
+        let labels: Vec<f64> = (0..50).map(|i| (i % 2) as f64).collect();
+        let mut first = Calibration::new(5);
+        let mut second = Calibration::new(5);
+        for i in 0..50 {
+            if i % 3 == 0 { first.accumulate((a[i] / 1.1, labels[i])) } else { second.accumulate((a[i] / 1.1, labels[i])) }
+        }
+        first.merge(&second);
+        let scaled: Vec<f64> = a.iter().map(|x| x / 1.1).collect();
+        let batch = calibration_curve(&scaled, &labels, 5);
+        let merged = first.finalize();
+        assert_eq!(merged.bins.len(), batch.bins.len());
+        assert!((merged.ece - batch.ece).abs() < 1e-12);
+
+        // confusion matrix and McNemar
+        let mut cm = ConfusionMatrix::new(2);
+        cm.accumulate((0, 1));
+        let mut other = ConfusionMatrix::new(2);
+        other.accumulate((1, 1));
+        cm.merge(&other);
+        assert_eq!(cm.finalize(), confusion_matrix(&[0, 1], &[1, 1], 2));
+
+        let mut mc = McNemar::new();
+        mc.accumulate((true, false));
+        let mut other = McNemar::new();
+        other.accumulate((false, true));
+        other.accumulate((true, true));
+        mc.merge(&other);
+        assert_eq!(mc.finalize(), mcnemar_test(&[1, 0, 1], &[0, 1, 1], &[1, 1, 1]));
+    }
+
+    #[test]
+    fn test_binary_scores() {
+        let scores = binary_scores(&[true, true, false, false], &[true, false, true, false]);
+        assert_eq!(scores.precision, 0.5);
+        assert_eq!(scores.recall, 0.5);
+        assert_eq!(scores.f1, 0.5);
+        let none = binary_scores(&[false, false], &[false, false]);
+        assert_eq!(none.f1, 0.0);
+    }
+
+    #[test]
+    fn test_roc_curve_and_auc() {
+        let scores = [0.1, 0.4, 0.35, 0.8];
+        let labels = [0.0, 0.0, 1.0, 1.0];
+        let curve = roc_curve(&scores, &labels);
+        let rates: Vec<(f64, f64)> = curve.iter().map(|p| (p.false_positive_rate, p.true_positive_rate)).collect();
+        assert_eq!(rates, vec![(0.0, 0.0), (0.0, 0.5), (0.5, 0.5), (0.5, 1.0), (1.0, 1.0)]);
+        assert_eq!(curve[1].threshold, 0.8);
+        assert!(curve[0].threshold.is_infinite());
+        assert!((auc(&curve) - 0.75).abs() < 1e-12);
+
+        assert_eq!(roc_auc_score(&[0.9, 0.8, 0.2, 0.1], &[1.0, 1.0, 0.0, 0.0]), 1.0);
+        assert_eq!(roc_auc_score(&[0.1, 0.2, 0.8, 0.9], &[1.0, 1.0, 0.0, 0.0]), 0.0);
+        // tied scores become one diagonal step
+        assert_eq!(roc_auc_score(&[0.5f32, 0.5], &[1.0, 0.0]), 0.5);
+    }
+
+    #[test]
+    #[should_panic(expected = "both positive and negative")]
+    fn test_roc_curve_needs_both_classes() {
+        roc_curve(&[0.2, 0.7], &[1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_best_threshold_by_f1() {
+        let scores = [0.9, 0.8, 0.6, 0.55, 0.3, 0.1];
+        let labels = [1.0, 1.0, 0.0, 1.0, 0.0, 0.0];
+        let (threshold, best) = best_threshold_by_f1(&scores, &labels);
+        assert_eq!(threshold, 0.55);
+        assert!((best.f1 - 6.0 / 7.0).abs() < 1e-12);
+        assert_eq!(best.recall, 1.0);
+
+        let predictions: Vec<bool> = scores.iter().map(|&s| s >= threshold).collect();
+        let targets: Vec<bool> = labels.iter().map(|&y| y > 0.5).collect();
+        assert_eq!(binary_scores(&predictions, &targets), best);
+    }
+
+    #[test]
+    fn test_regression_errors_per_output() {
+        let predictions = vec![vec![1.0, 0.0], vec![3.0, 2.0]];
+        let targets = vec![vec![1.0, 1.0], vec![1.0, 2.0]];
+        let errors = regression_errors(&predictions, &targets);
+        assert_eq!(errors, vec![OutputErrors { mse: 2.0, mae: 1.0 }, OutputErrors { mse: 0.5, mae: 0.5 }]);
+
+        let mut first = RegressionErrors::new();
+        first.accumulate((vec![1.0, 0.0], vec![1.0, 1.0]));
+        let mut second = RegressionErrors::new();
+        second.accumulate((vec![3.0, 2.0], vec![1.0, 2.0]));
+        first.merge(&second);
+        assert_eq!(first.finalize(), errors);
+        assert!(RegressionErrors::new().finalize().is_empty());
+    }
+}
@@ -0,0 +1,560 @@
+#![cfg(feature = "std")]
+
+use neuralnet::model::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_infers_shapes() {
+        let builder = ModelBuilder::new(4).dense(8).relu().dense(3).softmax();
+        assert_eq!(builder.validate(), Ok(vec![8, 8, 3, 3]));
+        let model = builder.seed(7).build::<f64>().unwrap();
+        assert_eq!(model.input_dim(), 4);
+        assert_eq!(model.output_dim(), 3);
+        let probabilities = model.forward(&[0.5, -1.0, 2.0, 0.0]);
+        assert_eq!(probabilities.len(), 3);
+        assert!((probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_builder_reports_shape_mismatch() {
+        let result = ModelBuilder::new(4).dense(8).dense_from(6, 2).build::<f32>();
+        assert_eq!(result, Err(BuildError::ShapeMismatch { layer: 1, expected: 8, found: 6 }));
+    }
+
+    #[test]
+    fn test_builder_rejects_invalid_specs() {
+        use neuralnet::layers::LayerError;
+        assert_eq!(ModelBuilder::new(2).build::<f32>(), Err(BuildError::EmptyModel));
+        assert_eq!(ModelBuilder::default().dense(3).build::<f32>(), Err(BuildError::MissingInputDim));
+        assert_eq!(ModelBuilder::new(2).dense(0).build::<f32>(), Err(BuildError::ZeroUnits { layer: 0 }));
+        assert_eq!(ModelBuilder::new(2).softmax().dense(2).build::<f32>(), Err(BuildError::SoftmaxNotLast { layer: 0 }));
+        assert_eq!(
+            ModelBuilder::new(0).prelu(0.1).build::<f32>(),
+            Err(BuildError::InvalidLayer { layer: 0, error: LayerError::WrongLength { expected: 1, found: 0 } })
+        );
+        assert!(matches!(ModelBuilder::new(2).dense(2).prelu(f64::NAN).build::<i32>(), Err(BuildError::Unrepresentable { layer: 1, .. })));
+        // an explicit first layer supplies the input size
+        let model = ModelBuilder::default().dense_from(5, 2).build::<f32>().unwrap();
+        assert_eq!(model.input_dim(), 5);
+    }
+
+    #[test]
+    fn test_dense_forward() {
+        let dense = Dense::new(vec![vec![1.0f32, 2.0], vec![-1.0, 0.5]], vec![0.5, 0.0]);
+        assert_eq!(dense.forward(&[1.0, 1.0]), vec![3.5, -0.5]);
+    }
+
+    #[test]
+    fn test_build_is_deterministic_for_seed() {
+        let builder = ModelBuilder::new(3).dense(4).tanh().dense(1).seed(42);
+        assert_eq!(builder.build::<f64>().unwrap(), builder.build::<f64>().unwrap());
+    }
+
+    #[test]
+    fn test_sequential_json_roundtrip() {
+        let model = ModelBuilder::new(3).dense(4).relu().dense(2).softmax().seed(1).build::<f64>().unwrap();
+        let json = serde_json::to_string(&model).unwrap();
+        let restored: Sequential<f64> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, model);
+        assert_eq!(restored.check_shapes(), Ok(()));
+    }
+
+    #[test]
+    fn test_check_shapes_detects_mismatch() {
+        let json = r#"{"layers":[{"Dense":{"weights":[[1.0,2.0]],"biases":[0.0]}}],"input_dim":3}"#;
+        let model: Sequential<f64> = serde_json::from_str(json).unwrap();
+        assert_eq!(model.check_shapes(), Err(BuildError::ShapeMismatch { layer: 0, expected: 3, found: 2 }));
+    }
+
+    #[test]
+    fn test_backward_matches_finite_differences() {
+        use neuralnet::loss_fn::Loss;
+        let model = ModelBuilder::new(3).dense(4).tanh().dense(3).softmax().seed(11).build::<f64>().unwrap();
+        let inputs = [0.3, -0.8, 0.5];
+        let targets = [0.0, 1.0, 0.0];
+        let (_, gradients) = model.backward(&inputs, &targets, Loss::CrossEntropy);
+
+        let h = 1e-6;
+        for (layer, grads) in [(0usize, &gradients[0]), (2, &gradients[1])] {
+            let LayerGradients::Dense(grads) = grads else { panic!("expected dense gradients") };
+            let mut plus = model.clone();
+            let mut minus = model.clone();
+            if let (ModelLayer::Dense(p), ModelLayer::Dense(m)) = (&mut plus.layers[layer], &mut minus.layers[layer]) {
+                p.weights[1][2] += h;
+                m.weights[1][2] -= h;
+            }
+            let loss = |m: &Sequential<f64>| -(m.forward(&inputs)[1]).ln();
+            let numeric = (loss(&plus) - loss(&minus)) / (2.0 * h);
+            assert!((numeric - grads.weights[1][2]).abs() < 1e-6, "{} vs {}", numeric, grads.weights[1][2]);
+        }
+    }
+
+    #[test]
+    fn test_train_epoch_learns_xor() {
+        use neuralnet::loss_fn::Loss;
+        let rows = vec![vec![0.0, 0.0], vec![0.0, 1.0], vec![1.0, 0.0], vec![1.0, 1.0]];
+        let targets = vec![vec![0.0], vec![1.0], vec![1.0], vec![0.0]];
+        let mut model = ModelBuilder::new(2).dense(8).tanh().dense(1).sigmoid().seed(2).build::<f64>().unwrap();
+        let first = model.train_epoch(&rows, &targets, Loss::MeanSquaredError, 0.5);
+        let mut last = first;
+        for _ in 0..2000 {
+            last = model.train_epoch(&rows, &targets, Loss::MeanSquaredError, 0.5);
+        }
+        assert!(last < first / 10.0, "loss went from {} to {}", first, last);
+        for (row, target) in rows.iter().zip(targets.iter()) {
+            assert_eq!(model.forward(row)[0] > 0.5, target[0] > 0.5);
+        }
+    }
+
+    #[test]
+    fn test_save_and_load_json() {
+        let model = ModelBuilder::new(2).dense(3).relu().dense(1).seed(4).build::<f32>().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("model.json");
+        model.save_json(&path).unwrap();
+        assert_eq!(Sequential::<f32>::load_json(&path).unwrap(), model);
+    }
+
+    #[test]
+    fn test_prelu_forward_and_backward() {
+        let prelu = PReLU::new(vec![0.1, 0.5]);
+        assert_eq!(prelu.forward(&[-2.0, -2.0]), vec![-0.2, -1.0]);
+        assert_eq!(prelu.forward(&[3.0, 4.0]), vec![3.0, 4.0]);
+        let (input_grad, alpha_grad) = prelu.backward(&[-2.0, 4.0], &[1.0, 1.0]);
+        assert_eq!(input_grad, vec![0.1, 1.0]);
+        assert_eq!(alpha_grad, vec![-2.0, 0.0]);
+
+        let shared = PReLU::new(vec![0.25]);
+        let (_, alpha_grad) = shared.backward(&[-1.0, -3.0, 2.0], &[1.0, 2.0, 1.0]);
+        assert_eq!(alpha_grad, vec![-7.0]);
+    }
+
+    #[test]
+    fn test_prelu_alpha_gradient_matches_finite_differences() {
+        use neuralnet::loss_fn::Loss;
+        let model = ModelBuilder::new(2).dense(3).prelu(0.25).dense(1).seed(4).build::<f64>().unwrap();
+        let inputs = [0.7, -1.2];
+        let targets = [0.5];
+        let (_, gradients) = model.backward(&inputs, &targets, Loss::MeanSquaredError);
+        let LayerGradients::PReLU(alpha_grad) = &gradients[1] else { panic!("expected PReLU gradients") };
+
+        let h = 1e-6;
+        for (k, &analytic) in alpha_grad.iter().enumerate() {
+            let mut plus = model.clone();
+            let mut minus = model.clone();
+            if let (ModelLayer::PReLU(p), ModelLayer::PReLU(m)) = (&mut plus.layers[1], &mut minus.layers[1]) {
+                p.alpha[k] += h;
+                m.alpha[k] -= h;
+            }
+            let loss = |m: &Sequential<f64>| (m.forward(&inputs)[0] - targets[0]).powi(2);
+            let numeric = (loss(&plus) - loss(&minus)) / (2.0 * h);
+            assert!((numeric - analytic).abs() < 1e-6, "{} vs {}", numeric, analytic);
+        }
+    }
+
+    #[test]
+    fn test_prelu_slopes_are_trained() {
+        use neuralnet::loss_fn::Loss;
+        // y = x for x > 0 and y = 0.5 * x otherwise: a single PReLU fits it exactly
+        let rows: Vec<Vec<f64>> = (-5..=5).map(|i| vec![i as f64 / 2.0]).collect();
+        let targets: Vec<Vec<f64>> = rows.iter().map(|r| vec![if r[0] > 0.0 { r[0] } else { 0.5 * r[0] }]).collect();
+        let mut model = Sequential::from_layers(1, vec![ModelLayer::PReLU(PReLU::new(vec![0.0]))]).unwrap();
+        for _ in 0..200 {
+            model.train_epoch(&rows, &targets, Loss::MeanSquaredError, 0.05);
+        }
+        let ModelLayer::PReLU(prelu) = &model.layers[0] else { panic!("expected PReLU") };
+        assert!((prelu.alpha[0] - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_check_shapes_rejects_wrong_prelu_width() {
+        let model = ModelBuilder::new(2).dense(3).prelu(0.1).build::<f64>().unwrap();
+        assert!(model.check_shapes().is_ok());
+        let mut broken = model.clone();
+        broken.layers[1] = ModelLayer::PReLU(PReLU::new(vec![0.1, 0.1]));
+        assert_eq!(broken.check_shapes(), Err(BuildError::ShapeMismatch { layer: 1, expected: 3, found: 2 }));
+    }
+
+    #[test]
+    fn test_fallible_constructors_and_forward() {
+        use neuralnet::layers::LayerError;
+        assert!(Dense::try_new(vec![vec![1.0, 2.0]], vec![0.0]).is_ok());
+        assert_eq!(Dense::try_new(vec![vec![1.0, 2.0]], vec![0.0, 0.0]), Err(LayerError::WrongLength { expected: 1, found: 2 }));
+        assert_eq!(Dense::try_new(vec![vec![1.0, 2.0], vec![3.0]], vec![0.0, 0.0]), Err(LayerError::WrongLength { expected: 2, found: 1 }));
+        assert!(PReLU::<f64>::try_new(vec![]).is_err());
+
+        let model = ModelBuilder::new(2).dense(3).relu().dense(1).build::<f64>().unwrap();
+        assert_eq!(model.try_forward(&[1.0, 2.0]).unwrap(), model.forward(&[1.0, 2.0]));
+        assert_eq!(model.try_forward(&[1.0]), Err(LayerError::WrongLength { expected: 2, found: 1 }));
+
+        // a deserialized model that skipped check_shapes fails instead of panicking
+        let json = r#"{"layers":[{"Dense":{"weights":[[1.0,2.0]],"biases":[0.0]}},{"Dense":{"weights":[[1.0,2.0]],"biases":[0.0]}}],"input_dim":2}"#;
+        let broken: Sequential<f64> = serde_json::from_str(json).unwrap();
+        assert_eq!(broken.try_forward(&[1.0, 1.0]), Err(LayerError::WrongLength { expected: 2, found: 1 }));
+    }
+
+    #[test]
+    fn test_dense_layer_trait_accumulates_gradients() {
+        use neuralnet::layers::Layer;
+        let mut dense = Dense::new(vec![vec![1.0, 2.0], vec![3.0, 4.0]], vec![0.5, -0.5]);
+        let inputs = [1.0, -1.0];
+        let outputs = Layer::forward(&dense, &inputs);
+        assert_eq!(outputs, vec![-0.5, -1.5]);
+
+        let upstream = Layer::backward(&mut dense, &inputs, &outputs, &[1.0, 0.5]);
+        assert_eq!(upstream, vec![2.5, 4.0]);
+        Layer::backward(&mut dense, &inputs, &outputs, &[1.0, 0.5]);
+        assert_eq!(dense.grads().weights, vec![vec![2.0, -2.0], vec![1.0, -1.0]]);
+        assert_eq!(dense.grads().biases, vec![2.0, 1.0]);
+
+        let params: Vec<f64> = dense.params_mut().into_iter().map(|(_, g)| g).collect();
+        assert_eq!(params, vec![2.0, -2.0, 1.0, -1.0, 2.0, 1.0]);
+        dense.zero_grad();
+        assert!(dense.params_mut().iter().all(|(_, g)| *g == 0.0));
+    }
+
+    #[test]
+    fn test_layers_used_through_the_trait() {
+        use neuralnet::activation_fn::Activation;
+        use neuralnet::back_propagation::backward_pass;
+        use neuralnet::layers::{linear, Layer};
+        use neuralnet::loss_fn::Loss;
+
+        fn n_params<L: Layer<f64>>(layer: &mut L) -> usize {
+            layer.params_mut().len()
+        }
+        let mut dense: Dense<f64> = linear::<f64, 2, 3>(&[0.1, 0.2, 0.3, 0.4, 0.5, 0.6]).into();
+        assert_eq!(dense.weights, vec![vec![0.1, 0.2, 0.3], vec![0.4, 0.5, 0.6]]);
+        assert_eq!(n_params(&mut dense), 8);
+        assert_eq!(n_params(&mut Activation::Tanh), 0);
+
+        let mut layers = vec![ModelLayer::Dense(dense), ModelLayer::Activation(Activation::Tanh), ModelLayer::Softmax];
+        let model = Sequential::from_layers(3, layers.clone()).unwrap();
+        let (expected_loss, expected) = model.backward(&[1.0, 0.0, -1.0], &[1.0, 0.0], Loss::CrossEntropy);
+        let loss = backward_pass(&mut layers, &[1.0, 0.0, -1.0], &[1.0, 0.0], Loss::CrossEntropy);
+        assert_eq!(loss, expected_loss);
+        let (ModelLayer::Dense(dense), LayerGradients::Dense(grads)) = (&layers[0], &expected[0]) else { panic!("expected dense") };
+        assert_eq!(dense.grads(), grads);
+    }
+
+    #[test]
+    fn test_gradients_are_not_serialized_or_compared() {
+        use neuralnet::loss_fn::Loss;
+        let mut model = ModelBuilder::new(2).dense(2).build::<f64>().unwrap();
+        let untouched = model.clone();
+        model.accumulate_gradients(&[1.0, 1.0], &[0.0, 1.0], Loss::MeanSquaredError);
+        assert_eq!(model, untouched);
+        let json = serde_json::to_string(&model).unwrap();
+        assert!(!json.contains("grads"));
+    }
+
+    #[test]
+    fn test_named_parameters() {
+        let model = ModelBuilder::new(3).dense(4).prelu(0.1).dense(2).softmax().seed(1).build::<f64>().unwrap();
+        let names: Vec<String> = model.parameters().into_iter().map(|p| p.name).collect();
+        assert_eq!(names, vec!["0.weights", "0.biases", "1.alpha", "2.weights", "2.biases"]);
+        assert_eq!(model.n_parameters(), 3 * 4 + 4 + 4 + 4 * 2 + 2);
+
+        let ModelLayer::Dense(first) = &model.layers[0] else { panic!("expected dense") };
+        let weights = &model.parameters()[0];
+        assert_eq!(*weights.values[5], first.weights[1][2]);
+    }
+
+    #[test]
+    fn test_parameters_mut_for_weight_decay_and_diffing() {
+        let mut model = ModelBuilder::new(2).dense(2).build::<f64>().unwrap();
+        let before = model.clone();
+        for param in model.parameters_mut() {
+            if param.name.ends_with("weights") {
+                for w in param.values {
+                    *w *= 0.5;
+                }
+            }
+        }
+        let changed: Vec<String> = model.parameters().into_iter().zip(before.parameters())
+            .filter(|(a, b)| a.values != b.values)
+            .map(|(a, _)| a.name)
+            .collect();
+        assert_eq!(changed, vec!["0.weights"]);
+        let ModelLayer::Dense(dense) = &model.layers[0] else { panic!("expected dense") };
+        let ModelLayer::Dense(original) = &before.layers[0] else { panic!("expected dense") };
+        assert_eq!(dense.weights[0][1], original.weights[0][1] * 0.5);
+    }
+
+    #[test]
+    fn test_seeded_builds_are_reproducible_and_weights_can_be_cloned() {
+        use neuralnet::layers::LayerError;
+        let builder = ModelBuilder::new(3).dense(4).relu().dense(2);
+        assert_eq!(builder.clone().seed(5).build::<f64>().unwrap(), builder.clone().seed(5).build::<f64>().unwrap());
+
+        let source = builder.clone().seed(1).build::<f64>().unwrap();
+        let mut target = builder.clone().seed(2).build::<f64>().unwrap();
+        assert_ne!(source, target);
+        target.clone_weights_from(&source).unwrap();
+        assert_eq!(source, target);
+
+        let mut other = ModelBuilder::new(3).dense(5).build::<f64>().unwrap();
+        assert_eq!(other.clone_weights_from(&source), Err(LayerError::ParameterMismatch { name: "0.weights".to_string() }));
+    }
+
+    #[test]
+    fn test_average_weights() {
+        use neuralnet::layers::LayerError;
+        let a = Sequential::from_layers(1, vec![ModelLayer::Dense(Dense::new(vec![vec![1.0]], vec![0.0]))]).unwrap();
+        let b = Sequential::from_layers(1, vec![ModelLayer::Dense(Dense::new(vec![vec![3.0]], vec![2.0]))]).unwrap();
+        let mean = Sequential::average_weights(&[&a, &b]).unwrap();
+        assert_eq!(mean.forward(&[1.0]), vec![3.0]);
+
+        let weighted = Sequential::weighted_average_weights(&[&a, &b], &[3.0, 1.0]).unwrap();
+        let ModelLayer::Dense(dense) = &weighted.layers[0] else { panic!("expected dense") };
+        assert_eq!(dense.weights, vec![vec![1.5]]);
+        assert_eq!(dense.biases, vec![0.5]);
+
+        assert!(Sequential::<f64>::average_weights(&[]).is_err());
+        let c = ModelBuilder::new(1).dense(2).build::<f64>().unwrap();
+        assert_eq!(Sequential::average_weights(&[&a, &c]), Err(LayerError::ParameterMismatch { name: "0.weights".to_string() }));
+        assert_eq!(Sequential::weighted_average_weights(&[&a, &b], &[1.0]), Err(LayerError::WrongLength { expected: 2, found: 1 }));
+    }
+
+    #[test]
+    fn test_weighted_average_weights_rejects_invalid_weights() {
+        use neuralnet::layers::LayerError;
+        let a = Sequential::from_layers(1, vec![ModelLayer::Dense(Dense::new(vec![vec![1.0]], vec![0.0]))]).unwrap();
+        let b = Sequential::from_layers(1, vec![ModelLayer::Dense(Dense::new(vec![vec![3.0]], vec![2.0]))]).unwrap();
+        let average = |weights: &[f64]| Sequential::weighted_average_weights(&[&a, &b], weights);
+        assert_eq!(average(&[0.0, 0.0]), Err(LayerError::InvalidWeightTotal));
+        assert_eq!(average(&[2.0, -1.0]), Err(LayerError::InvalidWeight { index: 1 }));
+        assert_eq!(average(&[f64::NAN, 1.0]), Err(LayerError::InvalidWeight { index: 0 }));
+        assert_eq!(average(&[f64::INFINITY, 1.0]), Err(LayerError::InvalidWeight { index: 0 }));
+        assert_eq!(average(&[f64::MAX, f64::MAX]), Err(LayerError::InvalidWeightTotal));
+        // A zero weight is allowed as long as another one is positive
+        assert_eq!(average(&[0.0, 1.0]).unwrap().forward(&[1.0]), vec![5.0]);
+    }
+
+    #[test]
+    fn test_predict_csv_appends_predictions_in_batches() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input.csv");
+        let output = dir.path().join("scored.csv");
+        let rows: Vec<Vec<f64>> = (0..7).map(|i| vec![i as f64, 1.0 - i as f64]).collect();
+        let csv: String = rows.iter().map(|r| format!("{},{}\n", r[0], r[1])).collect();
+        std::fs::write(&input, format!("a,b\n{}", csv)).unwrap();
+
+        let model = ModelBuilder::new(2).seed(3).dense(3).sigmoid().build::<f64>().unwrap();
+        assert_eq!(model.predict_csv_batched(&input, &output, 3).unwrap(), 7);
+
+        let text = std::fs::read_to_string(&output).unwrap();
+        let mut lines = text.lines();
+        assert_eq!(lines.next().unwrap(), "a,b,output_0,output_1,output_2");
+        let expected = model.predict(&rows);
+        for (line, (row, prediction)) in lines.zip(rows.iter().zip(expected.iter())) {
+            let cells: Vec<f64> = line.split(',').map(|c| c.parse().unwrap()).collect();
+            assert_eq!(&cells[..2], &row[..]);
+            assert_eq!(&cells[2..], &prediction[..]);
+        }
+        assert_eq!(model.predict_csv(&input, &output).unwrap(), 7);
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), text);
+    }
+
+    #[test]
+    fn test_predict_csv_reports_bad_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("input.csv");
+        let output = dir.path().join("scored.csv");
+        let model = ModelBuilder::new(2).dense(1).build::<f64>().unwrap();
+
+        std::fs::write(&input, "a,b\n1,2\n3,x\n").unwrap();
+        let err = model.predict_csv(&input, &output).unwrap_err().to_string();
+        assert!(err.contains("row 2") && err.contains("`x`"), "{}", err);
+
+        std::fs::write(&input, "a,b,c\n1,2,3\n").unwrap();
+        let err = model.predict_csv(&input, &output).unwrap_err().to_string();
+        assert!(err.contains("row 1"), "{}", err);
+    }
+
+    #[test]
+    fn test_builder_selects_init_per_activation() {
+        use neuralnet::random::Rng;
+
+        let dense_layers = |model: &Sequential<f64>| -> Vec<Dense<f64>> {
+            model.layers.iter().filter_map(|layer| match layer {
+                ModelLayer::Dense(dense) => Some(dense.clone()),
+                _ => None,
+            }).collect()
+        };
+        let builder = ModelBuilder::new(4).seed(9).dense(6).relu().dense(5).tanh().dense(2);
+        let mut rng = Rng::new(9);
+        let expected = vec![
+            Dense::he_uniform(4, 6, &mut rng),
+            Dense::glorot_uniform(6, 5, &mut rng),
+            Dense::glorot_uniform(5, 2, &mut rng),
+        ];
+        assert_eq!(dense_layers(&builder.build().unwrap()), expected);
+
+        let mut rng = Rng::new(9);
+        let forced: Vec<Dense<f64>> = [(4, 6), (6, 5), (5, 2)].iter().map(|&(i, u)| Dense::glorot_uniform(i, u, &mut rng)).collect();
+        assert_eq!(dense_layers(&builder.clone().init(Initializer::GlorotUniform).build().unwrap()), forced);
+
+        // He weights stay within sqrt(6 / inputs)
+        let he = Dense::<f64>::he_uniform(3, 50, &mut Rng::new(1));
+        let limit = 2.0f64.sqrt();
+        assert!(he.weights.iter().flatten().all(|w| w.abs() <= limit));
+        assert!(he.weights.iter().flatten().any(|w| w.abs() > (6.0f64 / 53.0).sqrt()));
+    }
+
+    #[test]
+    fn test_forward_with_reuses_workspace() {
+        let model = Sequential::from_layers(2, vec![
+            ModelLayer::Dense(Dense::new(vec![vec![1.0, -1.0], vec![0.5, 2.0], vec![-1.0, 0.0]], vec![0.0, 0.1, 0.2])),
+            ModelLayer::PReLU(PReLU::new(vec![0.25])),
+            ModelLayer::Dense(Dense::new(vec![vec![1.0, 1.0, 1.0], vec![-1.0, 0.5, 2.0]], vec![0.0, 0.0])),
+            ModelLayer::Activation(neuralnet::activation_fn::Activation::Tanh),
+            ModelLayer::Softmax,
+        ]).unwrap();
+        let mut workspace = Workspace::new();
+        for row in [[1.0, 2.0], [-3.0, 0.5], [0.0, 0.0]] {
+            assert_eq!(model.forward_with(&row, &mut workspace), model.forward(&row).as_slice());
+        }
+        let rows = vec![vec![1.0, 2.0], vec![-3.0, 0.5]];
+        assert_eq!(model.predict(&rows), rows.iter().map(|r| model.forward(r)).collect::<Vec<_>>());
+
+        let mut out = vec![9.0; 5];
+        let dense = Dense::new(vec![vec![1.0, 2.0]], vec![0.5]);
+        dense.forward_into(&[1.0, 1.0], &mut out);
+        assert_eq!(out, vec![3.5]);
+    }
+
+    #[test]
+    fn test_try_forward_with_reports_wrong_lengths() {
+        use neuralnet::layers::LayerError;
+        let model = ModelBuilder::new(2).dense(3).relu().dense(1).build::<f64>().unwrap();
+        let mut workspace = Workspace::new();
+        assert_eq!(model.try_forward_with(&[1.0], &mut workspace), Err(LayerError::WrongLength { expected: 2, found: 1 }));
+        assert!(model.try_forward_with(&[1.0, 2.0], &mut workspace).is_ok());
+    }
+
+    #[test]
+    fn test_dense_row_major_roundtrip() {
+        use neuralnet::layers::LayerError;
+        let dense = Dense::new(vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]], vec![0.5, -0.5]);
+        assert_eq!(dense.shape(), (2, 3));
+        assert_eq!(dense.weights_row_major(), vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let rebuilt = Dense::try_from_row_major(dense.shape(), dense.weights_row_major(), dense.biases.clone()).unwrap();
+        assert_eq!(rebuilt, dense);
+
+        assert_eq!(Dense::try_from_row_major((2, 3), vec![0.0; 5], vec![0.0; 2]), Err(LayerError::WrongLength { expected: 6, found: 5 }));
+        assert_eq!(Dense::try_from_row_major((2, 3), vec![0.0; 6], vec![0.0; 3]), Err(LayerError::WrongLength { expected: 2, found: 3 }));
+        assert_eq!(Dense::<f64>::try_from_row_major((2, 0), vec![], vec![0.0; 2]).unwrap().shape(), (2, 0));
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn test_ndarray_roundtrip() {
+        use ndarray::{array, Array1, Array2};
+        use neuralnet::layers::LayerError;
+        let dense = Dense::new(vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]], vec![0.5, -0.5]);
+        let weights = Array2::from(&dense);
+        assert_eq!(weights, array![[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+        let rebuilt = Dense::try_from((weights.clone(), Array1::from(dense.biases.clone()))).unwrap();
+        assert_eq!(rebuilt, dense);
+        // Column-major arrays convert by element, not by memory order
+        assert_eq!(Dense::try_from((weights.reversed_axes().t().to_owned(), array![0.5, -0.5])).unwrap(), dense);
+        assert_eq!(Dense::try_from(array![[1.0, 2.0]]).unwrap().biases, vec![0.0]);
+        assert_eq!(Dense::try_from((array![[1.0, 2.0]], array![0.0, 0.0])), Err(LayerError::WrongLength { expected: 1, found: 2 }));
+
+        let inputs = array![1.0, 0.0, -1.0];
+        assert_eq!(dense.try_forward_array(inputs.view()).unwrap().to_vec(), dense.forward(&[1.0, 0.0, -1.0]));
+        assert_eq!(dense.try_forward_array(array![1.0].view()), Err(LayerError::WrongLength { expected: 3, found: 1 }));
+
+        let model = ModelBuilder::new(3).seed(2).dense(4).relu().dense(2).softmax().build::<f64>().unwrap();
+        let rows = array![[0.1, 0.2, 0.3], [-1.0, 0.5, 2.0]];
+        let outputs = model.try_predict_array(rows.view()).unwrap();
+        assert_eq!(outputs.dim(), (2, 2));
+        let expected = model.predict(&[vec![0.1, 0.2, 0.3], vec![-1.0, 0.5, 2.0]]);
+        assert_eq!(outputs.rows().into_iter().map(|r| r.to_vec()).collect::<Vec<_>>(), expected);
+        assert_eq!(model.try_forward_array(rows.row(1)).unwrap().to_vec(), expected[1]);
+        assert_eq!(model.try_predict_array(rows.t()), Err(LayerError::WrongLength { expected: 3, found: 2 }));
+    }
+
+    #[test]
+    fn test_layer_norm_forward_and_gradients() {
+        use neuralnet::layers::Layer;
+        let mut norm = LayerNorm::<f64>::new(4);
+        let outputs = Layer::forward(&norm, &[1.0, 2.0, 3.0, 6.0]);
+        let mean = outputs.iter().sum::<f64>() / 4.0;
+        let variance = outputs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / 4.0;
+        assert!(mean.abs() < 1e-12 && (variance - 1.0).abs() < 1e-5);
+
+        norm.gamma = vec![0.5, -1.0, 2.0, 1.5];
+        norm.beta = vec![0.1, 0.0, -0.3, 0.2];
+        let (inputs, grad) = ([0.3, -1.1, 0.8, 2.0], [1.0, -0.5, 0.25, 2.0]);
+        let upstream = Layer::backward(&mut norm, &inputs, &[], &grad);
+        let weighted = |norm: &LayerNorm<f64>, x: &[f64]| Layer::forward(norm, x).iter().zip(grad.iter()).map(|(o, g)| o * g).sum::<f64>();
+        let h = 1e-6;
+        for j in 0..4 {
+            let (mut plus, mut minus) = (inputs, inputs);
+            plus[j] += h;
+            minus[j] -= h;
+            let numeric = (weighted(&norm, &plus) - weighted(&norm, &minus)) / (2.0 * h);
+            assert!((numeric - upstream[j]).abs() < 1e-6, "{} vs {}", numeric, upstream[j]);
+
+            let (mut plus, mut minus) = (norm.clone(), norm.clone());
+            plus.gamma[j] += h;
+            minus.gamma[j] -= h;
+            let numeric = (weighted(&plus, &inputs) - weighted(&minus, &inputs)) / (2.0 * h);
+            assert!((numeric - norm.grads().0[j]).abs() < 1e-6);
+        }
+        assert_eq!(norm.grads().1, &grad[..]);
+    }
+
+    #[test]
+    fn test_forward_hooks_see_layer_outputs() {
+        use std::sync::{Arc, Mutex};
+        use neuralnet::backend::Naive;
+        let mut model = ModelBuilder::new(2).seed(4).dense(3).relu().dense(2).softmax().build::<f64>().unwrap();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let hook = |layer: usize| {
+            let sink = Arc::clone(&seen);
+            move |outputs: &[f64]| sink.lock().unwrap().push((layer, outputs.to_vec()))
+        };
+        let relu = model.register_forward_hook(1, hook(1));
+        model.register_forward_hook(3, hook(3));
+
+        let inputs = [0.4, -0.7];
+        let outputs = model.forward(&inputs);
+        let ModelLayer::Dense(first) = &model.layers[0] else { unreachable!() };
+        let hidden: Vec<f64> = first.forward(&inputs).into_iter().map(|z| z.max(0.0)).collect();
+        assert_eq!(*seen.lock().unwrap(), vec![(1, hidden.clone()), (3, outputs.clone())]);
+
+        // Batched inference calls the hooks once per row; clones share them
+        seen.lock().unwrap().clear();
+        let rows = vec![inputs.to_vec(), vec![1.0, 1.0]];
+        model.clone().predict_on(&Naive, &rows);
+        assert_eq!(seen.lock().unwrap().len(), 4);
+        assert_eq!(seen.lock().unwrap()[0], (1, hidden));
+
+        seen.lock().unwrap().clear();
+        assert!(model.remove_forward_hook(relu));
+        assert!(!model.remove_forward_hook(relu));
+        model.predict(&rows);
+        assert!(seen.lock().unwrap().iter().all(|(layer, _)| *layer == 3));
+        model.clear_forward_hooks();
+        model.forward(&inputs);
+        assert_eq!(seen.lock().unwrap().len(), 2);
+
+        // Hooks are not part of the model's value
+        let plain = ModelBuilder::new(2).seed(4).dense(3).relu().dense(2).softmax().build::<f64>().unwrap();
+        model.register_forward_hook(0, |_: &[f64]| {});
+        assert_eq!(model, plain);
+    }
+
+    #[test]
+    #[should_panic(expected = "layer index 4 out of range")]
+    fn test_forward_hook_rejects_missing_layer() {
+        let mut model = ModelBuilder::new(2).dense(3).relu().dense(2).softmax().build::<f64>().unwrap();
+        model.register_forward_hook(4, |_: &[f64]| {});
+    }
+}
@@ -23,18 +23,16 @@ mod tests {
         assert!((y.tanh() - y.tanh()).abs() < 1e-12);
     }
 
-    #[test]
-    #[should_panic]
-    fn test_exp_int_should_panic() {
-        let x: i32 = 2;
-        let _ = x.exp();
+    fn generic_sigmoid<T: Real>(x: T) -> T {
+        T::one() / (T::one() + Real::exp(-x))
     }
 
     #[test]
-    #[should_panic]
-    fn test_tanh_int_should_panic() {
-        let x: i64 = 2;
-        let _ = x.tanh();
+    fn test_real_functions() {
+        // exp/tanh/ln are only available on `Real`, so this no longer compiles for i32
+        assert_eq!(generic_sigmoid(0.0f32), 0.5);
+        assert!((Real::tanh(1.0f64) - 1.0f64.tanh()).abs() < 1e-12);
+        assert!((Real::ln(std::f64::consts::E) - 1.0).abs() < 1e-12);
     }
 
     #[test]
@@ -73,4 +71,76 @@ mod tests {
         assert!(3i32.ge(3));
         assert!(2i32.le(3));
     }
-}
\ No newline at end of file
+
+    fn generic_max<T: Number>(a: T, b: T) -> T {
+        Number::max(a, b)
+    }
+
+    #[test]
+    fn test_math_functions_float() {
+        assert_eq!(Number::abs(-2.5f32), 2.5);
+        assert_eq!(Real::sqrt(9.0f64), 3.0);
+        assert_eq!(Number::powi(2.0f32, 3), 8.0);
+        assert_eq!(Real::powf(4.0f64, 0.5), 2.0);
+        assert_eq!(Real::log10(1000.0f64), 3.0);
+        assert!((Real::ln_1p(1e-10f64) - 1e-10).abs() < 1e-20);
+        assert_eq!(generic_max(1.0f32, f32::NAN), 1.0);
+        assert_eq!(Number::min(3.0f64, -1.0), -1.0);
+    }
+
+    #[test]
+    fn test_math_functions_int() {
+        assert_eq!(Number::abs(-3i32), 3);
+        assert_eq!(Number::powi(3i64, 4), 81);
+        assert_eq!(generic_max(2i32, 7), 7);
+        assert_eq!(Number::min(2i64, 7), 2);
+    }
+
+    #[test]
+    fn test_constants() {
+        assert_eq!(<f32 as Number>::EPSILON, f32::EPSILON);
+        assert_eq!(<f64 as Number>::INFINITY, f64::INFINITY);
+        assert_eq!(<i32 as Number>::EPSILON, 0);
+        assert_eq!(<i64 as Number>::NEG_INFINITY, i64::MIN);
+    }
+
+    #[cfg(feature = "half")]
+    #[test]
+    fn test_half_precision_numbers() {
+        use half::{bf16, f16};
+
+        assert_eq!(f16::zero(), f16::from_f32(0.0));
+        assert_eq!(<f16 as Number>::to_number::<f16>(0.5), f16::from_f32(0.5));
+        assert_eq!(<bf16 as Number>::to_number::<bf16>(2.0), bf16::from_f32(2.0));
+        assert!((Real::exp(f16::from_f32(1.0)).to_f32() - 1.0f32.exp()).abs() < 1e-2);
+        assert!((Real::tanh(bf16::from_f32(0.5)).to_f32() - 0.5f32.tanh()).abs() < 1e-2);
+        assert_eq!(Number::max(f16::from_f32(1.0), f16::from_f32(-1.0)), f16::from_f32(1.0));
+        assert_eq!(f16::one().and(f16::zero()), f16::zero());
+    }
+
+    #[cfg(all(feature = "half", feature = "std"))]
+    #[test]
+    fn test_half_precision_model() {
+        use half::f16;
+        use neuralnet::model::ModelBuilder;
+
+        let model = ModelBuilder::new(2).dense(3).sigmoid().dense(2).softmax().build::<f16>().unwrap();
+        let outputs = model.forward(&[f16::from_f32(0.5), f16::from_f32(-0.25)]);
+        let total: f32 = outputs.iter().map(|p| p.to_f32()).sum();
+        assert!((total - 1.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_try_to_number() {
+        assert_eq!(i32::try_to_number::<i32>(3.0), Some(3));
+        assert_eq!(i32::try_to_number::<i32>(f64::NAN), None);
+        assert_eq!(i32::try_to_number::<i32>(1e20), None);
+        assert_eq!(f32::try_to_number::<f32>(0.5), Some(0.5));
+    }
+
+    #[test]
+    #[should_panic(expected = "100000000000000000000 is not representable in the target number type")]
+    fn test_to_number_panics_with_value() {
+        let _ = i32::to_number::<i32>(1e20);
+    }
+}
@@ -0,0 +1,53 @@
+use neuralnet::perceptron::*;
+use neuralnet::activation_fn::StepActivation;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUTS: [[i32; 2]; 4] = [[0, 0], [0, 1], [1, 0], [1, 1]];
+
+    #[test]
+    fn test_i32_perceptron_learns_and_or() {
+        let mut and = Perceptron::<i32, 1, 2>::new(StepActivation::Heaviside);
+        let epochs = and.fit(&INPUTS, &[[0], [0], [0], [1]], 20).unwrap();
+        assert!(epochs > 1);
+        let predictions: Vec<i32> = INPUTS.iter().map(|x| and.predict(x)[0]).collect();
+        assert_eq!(predictions, vec![0, 0, 0, 1]);
+
+        let mut or = Perceptron::<i32, 1, 2>::new(StepActivation::Heaviside);
+        assert!(or.fit(&INPUTS, &[[0], [1], [1], [1]], 20).is_some());
+        assert_eq!(or.train_epoch(&INPUTS, &[[0], [1], [1], [1]]), 0);
+    }
+
+    #[test]
+    fn test_training_is_deterministic() {
+        let samples: [[i64; 2]; 6] = [[2, 1], [3, 4], [-1, -2], [-3, 1], [4, -1], [-2, -4]];
+        let targets: [[i64; 2]; 6] = [[1, 1], [1, 1], [-1, -1], [-1, 1], [1, -1], [-1, -1]];
+        let train = || {
+            let mut p = Perceptron::<i64, 2, 2>::new(StepActivation::Sign);
+            let epochs = p.fit(&samples, &targets, 50);
+            (epochs, p.layer.weights, p.layer.biases)
+        };
+        let first = train();
+        assert!(first.0.is_some());
+        assert_eq!(first, train());
+    }
+
+    #[test]
+    fn test_update_rule() {
+        let mut p = Perceptron::<i32, 1, 2>::new(StepActivation::Sign);
+        p.learning_rate = 2;
+        // Zero weights predict +1, so a -1 target is an error: e = 2 * (-1 - 1) = -4
+        assert_eq!(p.update(&[1, 3], &[-1]), 1);
+        assert_eq!(p.layer.weights, [[-4, -12]]);
+        assert_eq!(p.layer.biases, [-4]);
+        assert_eq!(p.update(&[1, 3], &[-1]), 0);
+    }
+
+    #[test]
+    fn test_xor_is_not_separable() {
+        let mut p = Perceptron::<i32, 1, 2>::new(StepActivation::Heaviside);
+        assert_eq!(p.fit(&INPUTS, &[[0], [1], [1], [0]], 100), None);
+    }
+}
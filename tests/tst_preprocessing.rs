@@ -0,0 +1,156 @@
+#![cfg(feature = "std")]
+
+use neuralnet::preprocessing::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_polynomial_features_degree2_with_bias() {
+        let poly = PolynomialFeatures::new(2, true);
+        let out = poly.transform_row(&[2.0f64, 3.0]);
+        // [1, a, b, a^2, ab, b^2]
+        assert_eq!(out, vec![1.0, 2.0, 3.0, 4.0, 6.0, 9.0]);
+        assert_eq!(poly.n_output_features(2), 6);
+    }
+
+    #[test]
+    fn test_polynomial_features_degree3_without_bias() {
+        let poly = PolynomialFeatures::new(3, false);
+        // C(n + d, d) - 1 = C(5, 3) - 1 = 9 terms for 2 inputs up to degree 3
+        assert_eq!(poly.n_output_features(2), 9);
+        let out = poly.transform_row(&[2i32, 1]);
+        assert_eq!(out, vec![2, 1, 4, 2, 1, 8, 4, 2, 1]);
+    }
+
+    #[test]
+    fn test_polynomial_features_fit_transform_rows() {
+        let mut poly = PolynomialFeatures::new(1, true);
+        let rows = vec![vec![1.0f32, 2.0], vec![3.0, 4.0]];
+        let out = poly.fit_transform(&rows);
+        assert_eq!(out, vec![vec![1.0, 1.0, 2.0], vec![1.0, 3.0, 4.0]]);
+    }
+
+    #[test]
+    fn test_robust_scaler_ignores_outlier() {
+        let rows = vec![vec![1.0f64, 5.0], vec![2.0, 5.0], vec![3.0, 5.0], vec![4.0, 5.0], vec![1000.0, 5.0]];
+        let mut scaler = RobustScaler::new();
+        let scaled = scaler.fit_transform(&rows);
+        // median 3, IQR 4 - 2 = 2; the constant column is only centered
+        assert_eq!(scaler.center, vec![3.0, 5.0]);
+        assert_eq!(scaler.scale, vec![2.0, 1.0]);
+        assert_eq!(scaled[0], vec![-1.0, 0.0]);
+        assert_eq!(scaled[4], vec![498.5, 0.0]);
+    }
+
+    #[test]
+    fn test_winsorizer_clips_to_fitted_quantiles() {
+        let rows: Vec<Vec<f64>> = (0..=10).map(|i| vec![i as f64 * 10.0]).collect();
+        let mut winsorizer = Winsorizer::new(0.1, 0.9);
+        winsorizer.fit(&rows);
+        assert_eq!(winsorizer.lower, vec![10.0]);
+        assert_eq!(winsorizer.upper, vec![90.0]);
+        assert_eq!(winsorizer.transform(&[vec![-5.0], vec![55.0], vec![500.0]]), vec![vec![10.0], vec![55.0], vec![90.0]]);
+    }
+
+    #[test]
+    fn test_log1p_transform_roundtrip() {
+        let transform = Log1pTransform;
+        let z = transform.transform(99.0);
+        assert!((z - 100f64.ln()).abs() < 1e-12);
+        assert!((transform.inverse_transform(z) - 99.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_box_cox_fits_log_data() {
+        // log-normal-like data: the fitted lambda should be close to 0 (a log transform)
+        let targets: Vec<f64> = [-1.5f64, -1.0, -0.5, -0.2, 0.0, 0.2, 0.5, 1.0, 1.5].iter().map(|z| z.exp()).collect();
+        let mut box_cox = BoxCox::default();
+        box_cox.fit(&targets);
+        assert!(box_cox.lambda.abs() < 0.05, "lambda = {}", box_cox.lambda);
+        for &y in &targets {
+            assert!((box_cox.inverse_transform(box_cox.transform(y)) - y).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_transformed_target_model_inverts_predictions() {
+        let targets = [1.0f64, 10.0, 100.0];
+        // The "model" predicts the mean of the transformed targets for every row
+        let fitted = TransformedTargetModel::fit(Log1pTransform, &targets, |z: &[f64]| {
+            z.iter().sum::<f64>() / z.len() as f64
+        });
+        let predictions = fitted.predict(|mean: &f64, _row: &[f64]| *mean, &[vec![0.0]]);
+        let expected = ((2f64.ln() + 11f64.ln() + 101f64.ln()) / 3.0).exp() - 1.0;
+        assert!((predictions[0] - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_cyclic_encoder() {
+        let encoder = CyclicEncoder::new().column(0, 24.0).column(2, 360.0);
+        assert_eq!(encoder.n_output_features(3), 5);
+        let encoded = encoder.transform_row(&[6.0f64, 42.0, 180.0]);
+        assert_eq!(encoded.len(), 5);
+        assert!((encoded[0] - 1.0).abs() < 1e-12 && encoded[1].abs() < 1e-12);
+        assert_eq!(encoded[2], 42.0);
+        assert!(encoded[3].abs() < 1e-12 && (encoded[4] + 1.0).abs() < 1e-12);
+
+        // hour 23 is closer to hour 0 than hour 12 is
+        let distance = |a: &[f64], b: &[f64]| ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt();
+        let hours = CyclicEncoder::new().column(0, 24.0);
+        let (h0, h12, h23) = (hours.transform_row(&[0.0f64]), hours.transform_row(&[12.0f64]), hours.transform_row(&[23.0f64]));
+        assert!(distance(&h0, &h23) < distance(&h0, &h12));
+    }
+
+    #[test]
+    fn test_hashing_encoder_is_stable_and_in_range() {
+        let encoder = HashingEncoder::new(16);
+        let categories: Vec<String> = (0..1000).map(|i| format!("user_{}", i)).collect();
+        let buckets = encoder.transform(&categories);
+        assert!(buckets.iter().all(|&b| b < 16));
+        assert_eq!(buckets, encoder.transform(&categories));
+        // Every bucket receives some of the many categories
+        assert!((0..16).all(|b| buckets.contains(&b)));
+        // 64-bit FNV-1a of "a" is 0xaf63dc4c8601ec8c
+        assert_eq!(HashingEncoder::new(1 << 20).bucket("a"), (0xaf63dc4c8601ec8cu64 % (1 << 20)) as usize);
+
+        let encoded: Vec<f64> = encoder.one_hot("red");
+        assert_eq!(encoded.len(), 16);
+        assert_eq!(encoded.iter().sum::<f64>(), 1.0);
+        assert_eq!(encoded[encoder.bucket("red")], 1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "n_buckets must be positive")]
+    fn test_hashing_encoder_rejects_zero_buckets() {
+        HashingEncoder::new(0);
+    }
+
+    #[test]
+    fn test_label_encoder_roundtrip() {
+        let labels = ["dog", "cat", "bird", "cat"];
+        let mut encoder = LabelEncoder::new();
+        assert_eq!(encoder.fit_transform(&labels), vec![2, 1, 0, 1]);
+        assert_eq!(encoder.classes, vec!["bird", "cat", "dog"]);
+        assert_eq!(encoder.inverse_transform(&[0, 2]), vec!["bird", "dog"]);
+
+        assert_eq!(encoder.try_transform(&["fish"]), Err(LabelError::UnknownLabel { label: "fish".to_string() }));
+        assert_eq!(encoder.try_inverse_transform(&[3]), Err(LabelError::UnknownId { id: 3, n_classes: 3 }));
+    }
+
+    #[test]
+    fn test_label_encoder_json_persistence() {
+        let mut encoder = LabelEncoder::new();
+        encoder.fit(&["yes".to_string(), "no".to_string()]);
+        let path = std::env::temp_dir().join("neuralnet_label_encoder.json");
+        encoder.save_json(&path).unwrap();
+        let loaded = LabelEncoder::load_json(&path).unwrap();
+        assert_eq!(loaded, encoder);
+        assert_eq!(loaded.transform(&["yes"]), vec![1]);
+
+        std::fs::write(&path, r#"{"classes":["b","a"]}"#).unwrap();
+        assert!(LabelEncoder::load_json(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
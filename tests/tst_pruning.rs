@@ -0,0 +1,82 @@
+#![cfg(feature = "std")]
+
+use neuralnet::pruning::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neuralnet::loss_fn::Loss;
+    use neuralnet::activation_fn::Activation;
+    use neuralnet::model::{Dense, ModelBuilder, ModelLayer, Sequential};
+
+    fn dense_weights(model: &Sequential<f64>) -> Vec<Vec<f64>> {
+        match &model.layers[0] {
+            ModelLayer::Dense(dense) => dense.weights.clone(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_magnitude_prune_removes_smallest_weights_globally() {
+        let mut model = Sequential::from_layers(2, vec![
+            ModelLayer::Dense(Dense::new(vec![vec![0.1, -2.0], vec![0.5, -0.05]], vec![0.3, 0.0])),
+            ModelLayer::Activation(Activation::ReLU),
+            ModelLayer::Dense(Dense::new(vec![vec![1.0, -0.2]], vec![0.0])),
+        ]).unwrap();
+
+        let mask = magnitude_prune(&mut model, 0.5);
+        // Six weights, the three smallest (0.05, 0.1, 0.2) are removed
+        assert_eq!(mask.keep, vec![vec![vec![false, true], vec![true, false]], vec![vec![true, false]]]);
+        assert_eq!(mask.sparsity(), 0.5);
+        assert_eq!(sparsity(&model), 0.5);
+        assert_eq!(dense_weights(&model), vec![vec![0.0, -2.0], vec![0.5, 0.0]]);
+
+        assert_eq!(magnitude_prune(&mut model.clone(), 0.0).sparsity(), 0.0);
+        assert_eq!(magnitude_prune(&mut model, 1.0).sparsity(), 1.0);
+    }
+
+    #[test]
+    fn test_mask_keeps_pruned_weights_at_zero_while_training() {
+        let mut model = ModelBuilder::new(2).seed(3).dense(4).tanh().dense(1).build::<f64>().unwrap();
+        let rows: Vec<Vec<f64>> = (0..16).map(|i| vec![(i % 4) as f64 / 3.0, (i / 4) as f64 / 3.0]).collect();
+        let targets: Vec<Vec<f64>> = rows.iter().map(|r| vec![r[0] - r[1]]).collect();
+
+        let mask = magnitude_prune(&mut model, 0.4);
+        let first = mask.train_epoch(&mut model, &rows, &targets, Loss::MeanSquaredError, 0.1);
+        let mut last = first;
+        for _ in 0..50 {
+            last = mask.train_epoch(&mut model, &rows, &targets, Loss::MeanSquaredError, 0.1);
+        }
+        assert!(last < first);
+        assert!(sparsity(&model) >= mask.sparsity());
+        let mut reapplied = model.clone();
+        mask.apply(&mut reapplied);
+        assert_eq!(reapplied, model);
+    }
+
+    #[test]
+    fn test_sparse_model_matches_dense_forward() {
+        let mut model = ModelBuilder::new(3).seed(7).dense(5).relu().dense(2).softmax().build::<f64>().unwrap();
+        magnitude_prune(&mut model, 0.6);
+        let sparse = SparseModel::from_sequential(&model);
+        assert_eq!(sparse.nnz(), 25 - 15);
+
+        let rows = vec![vec![0.2, -1.0, 0.7], vec![1.5, 0.3, -0.4]];
+        for (s, d) in sparse.predict(&rows).iter().flatten().zip(model.predict(&rows).iter().flatten()) {
+            assert!((s - d).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_sparse_dense_roundtrip() {
+        let dense = Dense::new(vec![vec![0.0, 1.5, 0.0], vec![-2.0, 0.0, 0.5]], vec![0.1, 0.2]);
+        let sparse = SparseDense::from_dense(&dense);
+        assert_eq!(sparse.nnz(), 3);
+        assert_eq!(sparse.row_starts, vec![0, 1, 3]);
+        assert_eq!(sparse.columns, vec![1, 0, 2]);
+        for (o, e) in sparse.forward(&[1.0, 2.0, 3.0]).iter().zip([3.1f64, -0.3].iter()) {
+            assert!((o - e).abs() < 1e-12);
+        }
+        assert_eq!(sparse.to_dense(), dense);
+    }
+}
@@ -0,0 +1,47 @@
+#![cfg(feature = "std")]
+
+use neuralnet::quantization::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neuralnet::layers::Layer1D;
+    use neuralnet::model::ModelBuilder;
+
+    #[test]
+    fn test_quant_params_roundtrip() {
+        let params = QuantParams::from_range(-1.0, 1.0);
+        assert_eq!(params.dequantize(params.quantize(0.0)), 0.0);
+        for &x in &[-1.0f32, -0.3, 0.5, 1.0] {
+            assert!((params.dequantize(params.quantize(x)) - x).abs() <= params.scale / 2.0 + 1e-6);
+        }
+        // values outside the range saturate
+        assert_eq!(params.quantize(10.0), 127);
+    }
+
+    #[test]
+    fn test_dense_linear_q8_matches_float() {
+        let layer = Layer1D::new([[0.5f32, -1.0, 0.25], [1.5, 0.75, -0.5]], [0.1, -0.2]);
+        let quantized = QuantizedLayer::from_layer1d(&layer);
+        let inputs = [1.0f32, 0.5, -2.0];
+        let (q_inputs, params) = quantize(&inputs);
+        let outputs = dense_linear_q8(&q_inputs, params, &quantized);
+        let expected = layer.forward(&inputs);
+        for (o, e) in outputs.iter().zip(expected.iter()) {
+            assert!((o - e).abs() < 0.05, "{} vs {}", o, e);
+        }
+    }
+
+    #[test]
+    fn test_quantized_model_close_to_float_model() {
+        let model = ModelBuilder::new(4).dense(16).relu().dense(3).softmax().seed(3).build::<f32>().unwrap();
+        let quantized = QuantizedModel::from_sequential(&model);
+        assert_eq!(quantized.weight_bytes(), 4 * 16 + 16 * 3);
+        let inputs = [0.2f32, -0.7, 1.1, 0.4];
+        let expected = model.forward(&inputs);
+        let outputs = quantized.forward(&inputs);
+        for (o, e) in outputs.iter().zip(expected.iter()) {
+            assert!((o - e).abs() < 0.02, "{} vs {}", o, e);
+        }
+    }
+}
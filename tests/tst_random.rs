@@ -0,0 +1,109 @@
+#![cfg(feature = "std")]
+
+use neuralnet::random::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        for _ in 0..10 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        let mut c = Rng::new(43);
+        assert_ne!(Rng::new(42).next_u64(), c.next_u64());
+    }
+
+    #[test]
+    fn test_next_f64_in_unit_interval() {
+        let mut rng = Rng::new(7);
+        for _ in 0..1000 {
+            let x = rng.next_f64();
+            assert!((0.0..1.0).contains(&x));
+        }
+    }
+
+    #[test]
+    fn test_gen_index_in_range() {
+        let mut rng = Rng::new(1);
+        for _ in 0..1000 {
+            assert!(rng.gen_index(5) < 5);
+        }
+    }
+
+    #[test]
+    fn test_shuffle_is_permutation() {
+        let mut rng = Rng::new(3);
+        let mut values: Vec<usize> = (0..20).collect();
+        rng.shuffle(&mut values);
+        assert_ne!(values, (0..20).collect::<Vec<_>>());
+        values.sort();
+        assert_eq!(values, (0..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_philox_known_answers() {
+        // Known-answer vectors of the Random123 reference implementation
+        let zero = Philox4x32::new(0);
+        assert_eq!(zero.block([0, 0, 0, 0]), [0x6627e8d5, 0xe169c58d, 0xbc57ac4c, 0x9b00dbd8]);
+        let ones = Philox4x32::new(u64::MAX);
+        assert_eq!(ones.block([u32::MAX; 4]), [0x408f276d, 0x41c83b0e, 0xa20bc7c6, 0x6d5451fd]);
+    }
+
+    #[test]
+    fn test_philox_order_independent() {
+        let philox = Philox4x32::new(99);
+        let forward: Vec<f64> = (0..16).map(|i| philox.uniform(3, i)).collect();
+        let backward: Vec<f64> = (0..16).rev().map(|i| philox.uniform(3, i)).collect();
+        assert_eq!(forward, backward.into_iter().rev().collect::<Vec<_>>());
+        assert!(forward.iter().all(|x| (0.0..1.0).contains(x)));
+        assert_ne!(philox.uniform(3, 0), philox.uniform(4, 0));
+    }
+
+    #[test]
+    fn test_philox_normal_moments() {
+        let philox = Philox4x32::new(5);
+        let samples: Vec<f64> = (0..20_000).map(|i| philox.normal(0, i)).collect();
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let var = samples.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / samples.len() as f64;
+        assert!(mean.abs() < 0.05);
+        assert!((var - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_philox_dropout_mask() {
+        let philox = Philox4x32::new(1);
+        let mask = philox.dropout_mask(0, 10_000, 0.3);
+        let kept = mask.iter().filter(|&&k| k).count() as f64 / mask.len() as f64;
+        assert!((kept - 0.7).abs() < 0.03);
+        assert_eq!(mask, philox.dropout_mask(0, 10_000, 0.3));
+        assert!(philox.dropout_mask(0, 100, 0.0).iter().all(|&k| k));
+    }
+
+    #[test]
+    fn test_next_normal_moments() {
+        let mut rng = Rng::new(9);
+        let samples: Vec<f64> = (0..20000).map(|_| rng.next_normal()).collect();
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+        assert!(mean.abs() < 0.03);
+        assert!((variance - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_gamma_and_beta_moments() {
+        let mut rng = Rng::new(11);
+        let n = 20000;
+        for &shape in &[0.5, 3.0] {
+            let mean = (0..n).map(|_| rng.next_gamma(shape)).sum::<f64>() / n as f64;
+            assert!((mean - shape).abs() < 0.05 * shape.max(1.0), "{} {}", shape, mean);
+        }
+        let samples: Vec<f64> = (0..n).map(|_| rng.next_beta(2.0, 6.0)).collect();
+        assert!(samples.iter().all(|&x| (0.0..=1.0).contains(&x)));
+        let mean = samples.iter().sum::<f64>() / n as f64;
+        assert!((mean - 0.25).abs() < 0.01);
+    }
+}
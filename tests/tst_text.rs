@@ -0,0 +1,85 @@
+#![cfg(feature = "std")]
+
+use neuralnet::text::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    const CORPUS: [&str; 2] = ["low low low lower lowest", "newer newest wider low"];
+
+    #[test]
+    fn test_bpe_train_learns_frequent_merges() {
+        let tokenizer = BpeTokenizer::train(&CORPUS, 30);
+        assert!(tokenizer.vocab_size() <= 30);
+        assert!(!tokenizer.merges().is_empty());
+        // "low" is the most frequent word and should become a single token
+        assert!(tokenizer.token_to_id("low</w>").is_some());
+        assert_eq!(tokenizer.encode("low").len(), 1);
+    }
+
+    #[test]
+    fn test_bpe_encode_decode_roundtrip() {
+        let tokenizer = BpeTokenizer::train(&CORPUS, 25);
+        let text = "lowest newer low";
+        let ids = tokenizer.encode(text);
+        assert_eq!(tokenizer.decode(&ids), text);
+        // unseen words still decompose into known subwords
+        assert_eq!(tokenizer.decode(&tokenizer.encode("flow")), "<unk>low");
+    }
+
+    #[test]
+    fn test_bpe_unknown_characters() {
+        let tokenizer = BpeTokenizer::train(&CORPUS, 10);
+        let ids = tokenizer.encode("zz");
+        assert_eq!(ids[0], tokenizer.token_to_id(UNK_TOKEN).unwrap());
+        assert_eq!(ids[1], tokenizer.token_to_id(UNK_TOKEN).unwrap());
+    }
+
+    #[test]
+    fn test_bpe_save_load() {
+        let tokenizer = BpeTokenizer::train(&CORPUS, 25);
+        let file = NamedTempFile::new().unwrap();
+        tokenizer.save(file.path()).unwrap();
+        let loaded = BpeTokenizer::load(file.path()).unwrap();
+        assert_eq!(loaded, tokenizer);
+        assert_eq!(loaded.encode("newest lower"), tokenizer.encode("newest lower"));
+    }
+
+    #[test]
+    fn test_bpe_load_invalid_file() {
+        assert!(BpeTokenizer::load("non_existent_tokenizer.json").is_err());
+    }
+
+    #[test]
+    fn test_word_and_char_ngrams() {
+        assert_eq!(word_ngrams("the cat sat", 2), vec!["the cat", "cat sat"]);
+        assert!(word_ngrams("the", 2).is_empty());
+        assert_eq!(char_ngrams("ab  c", 2), vec!["ab", "b ", " c"]);
+    }
+
+    #[test]
+    fn test_count_vectorizer_word_bigrams() {
+        let mut vectorizer = CountVectorizer::new(Analyzer::Word, 1, 2);
+        let counts = vectorizer.fit_transform::<f64>(&["The cat", "the cat the dog"]);
+        // cat, cat the, dog, the, the cat, the dog
+        assert_eq!(vectorizer.n_features(), 6);
+        let the_cat = vectorizer.feature_index("the cat").unwrap();
+        let the = vectorizer.feature_index("the").unwrap();
+        assert_eq!(counts[0][the_cat], 1.0);
+        assert_eq!(counts[1][the], 2.0);
+    }
+
+    #[test]
+    fn test_count_vectorizer_char_word_boundary_binary() {
+        let mut vectorizer = CountVectorizer::new(Analyzer::CharWordBoundary, 3, 3);
+        vectorizer.binary = true;
+        vectorizer.fit(&["cat cat"]);
+        assert_eq!(vectorizer.n_features(), 3);
+        assert!(vectorizer.feature_index(" ca").is_some());
+        let rows = vectorizer.transform::<i32>(&["cat cat", "dog"]);
+        assert_eq!(rows[0], vec![1, 1, 1]);
+        assert_eq!(rows[1], vec![0, 0, 0]);
+    }
+}
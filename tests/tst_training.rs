@@ -0,0 +1,411 @@
+#![cfg(feature = "std")]
+
+use neuralnet::training::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_learning_curve_sizes_and_repeats() {
+        let mut calls = Vec::new();
+        let curve = learning_curve(10, &[0.1, 0.5, 1.0], 3, 0, |train| {
+            calls.push(train.to_vec());
+            (1.0, train.len() as f64)
+        });
+
+        assert_eq!(curve.len(), 3);
+        assert_eq!(curve.iter().map(|p| p.train_size).collect::<Vec<_>>(), vec![1, 5, 10]);
+        assert_eq!(calls.len(), 9);
+        for point in &curve {
+            assert_eq!(point.train_scores.len(), 3);
+            assert_eq!(point.mean_train_score(), 1.0);
+            assert_eq!(point.mean_validation_score(), point.train_size as f64);
+        }
+        // every subset holds distinct, in-range indices
+        for call in &calls {
+            let mut sorted = call.clone();
+            sorted.sort();
+            sorted.dedup();
+            assert_eq!(sorted.len(), call.len());
+            assert!(call.iter().all(|&i| i < 10));
+        }
+    }
+
+    #[test]
+    fn test_learning_curve_is_reproducible() {
+        let mut first = Vec::new();
+        learning_curve(8, &[0.5], 2, 11, |train| { first.push(train.to_vec()); (0.0, 0.0) });
+        let mut second = Vec::new();
+        learning_curve(8, &[0.5], 2, 11, |train| { second.push(train.to_vec()); (0.0, 0.0) });
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    #[should_panic(expected = "fraction 1.5 at index 0 does not lie in (0, 1]")]
+    fn test_learning_curve_invalid_fraction() {
+        learning_curve(10, &[1.5], 1, 0, |_| (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_learning_curve_sizes_ignore_rounding_error() {
+        // 0.07 * 100 = 7.000000000000001 and 0.29 * 100 = 28.999999999999996
+        let curve = learning_curve(100, &[0.07, 0.29, 0.071], 1, 0, |_| (0.0, 0.0));
+        assert_eq!(curve.iter().map(|p| p.train_size).collect::<Vec<_>>(), vec![7, 29, 8]);
+    }
+
+    #[test]
+    fn test_try_learning_curve_validates_before_training() {
+        let mut calls = 0;
+        let result = try_learning_curve(10, &[0.5, 1.0, f64::NAN], 2, 0, |_| { calls += 1; (0.0, 0.0) });
+        assert!(matches!(result, Err(LearningCurveError::InvalidFraction { index: 2, .. })));
+        assert_eq!(calls, 0);
+        assert_eq!(try_learning_curve(0, &[0.5], 1, 0, |_| (0.0, 0.0)), Err(LearningCurveError::NoSamples));
+        assert_eq!(try_learning_curve(10, &[0.5], 0, 0, |_| (0.0, 0.0)), Err(LearningCurveError::NoRepeats));
+    }
+
+    #[test]
+    fn test_trainer_records_history() {
+        use neuralnet::loss_fn::Loss;
+        use neuralnet::model::ModelBuilder;
+        let rows: Vec<Vec<f64>> = (0..20).map(|i| vec![i as f64 / 10.0 - 1.0]).collect();
+        let targets: Vec<Vec<f64>> = rows.iter().map(|r| vec![2.0 * r[0] + 0.5]).collect();
+        let mut model = ModelBuilder::new(1).dense(1).seed(3).build::<f64>().unwrap();
+
+        let trainer = Trainer::new(Loss::MeanSquaredError, 0.05, 30).shuffle(1);
+        let history = trainer.fit_with(&mut model, &rows, &targets, |m| {
+            vec![("prediction_at_zero".to_string(), m.forward(&[0.0])[0])]
+        });
+        assert_eq!(history.records.len(), 30);
+        assert_eq!(history.records[4].epoch, 4);
+        let losses = history.train_losses();
+        assert!(losses[29] < losses[0] / 10.0);
+        assert_eq!(history.metric_names(), vec!["prediction_at_zero"]);
+        assert!((history.metric("prediction_at_zero")[29] - 0.5).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_history_to_csv_and_json() {
+        let mut history = History::new();
+        history.push(EpochRecord { epoch: 0, train_loss: 1.5, metrics: [("accuracy".to_string(), 0.5)].into_iter().collect() });
+        history.push(EpochRecord { epoch: 1, train_loss: 0.75, metrics: Default::default() });
+
+        let dir = tempfile::tempdir().unwrap();
+        let csv_path = dir.path().join("history.csv");
+        history.to_csv(&csv_path).unwrap();
+        assert_eq!(std::fs::read_to_string(&csv_path).unwrap(), "epoch,train_loss,accuracy\n0,1.5,0.5\n1,0.75,\n");
+
+        let json_path = dir.path().join("history.json");
+        history.to_json(&json_path).unwrap();
+        let restored: History = serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(restored, history);
+    }
+
+    #[test]
+    fn test_observers_receive_every_epoch() {
+        use neuralnet::loss_fn::Loss;
+        use neuralnet::model::ModelBuilder;
+        struct Recorder(Vec<(usize, usize)>, bool);
+        impl TrainingObserver for Recorder {
+            fn on_epoch_end(&mut self, record: &EpochRecord, epochs: usize) {
+                self.0.push((record.epoch, epochs));
+            }
+            fn on_train_end(&mut self, history: &History) {
+                self.1 = history.records.len() == self.0.len();
+            }
+        }
+
+        let rows = vec![vec![0.0], vec![1.0]];
+        let targets = vec![vec![0.0], vec![1.0]];
+        let mut model = ModelBuilder::new(1).dense(1).build::<f64>().unwrap();
+        let mut recorder = Recorder(Vec::new(), false);
+        let mut progress = ProgressBar::new(Vec::new());
+        progress.width = 4;
+        Trainer::new(Loss::MeanSquaredError, 0.1, 4)
+            .fit_observed(&mut model, &rows, &targets, |_| Vec::new(), &mut [&mut recorder, &mut progress]);
+
+        assert_eq!(recorder.0, vec![(0, 4), (1, 4), (2, 4), (3, 4)]);
+        assert!(recorder.1);
+        let output = String::from_utf8(progress.into_inner()).unwrap();
+        assert!(output.starts_with("\r[#   ] 1/4 train_loss="));
+        assert!(output.contains("\r[####] 4/4 train_loss="));
+        assert!(output.ends_with('\n'));
+    }
+
+    #[test]
+    fn test_progress_bar_limits_redraws() {
+        let mut progress = ProgressBar::new(Vec::new());
+        progress.max_redraws = 10;
+        for epoch in 0..1000 {
+            progress.on_epoch_end(&EpochRecord { epoch, train_loss: 0.0, metrics: Default::default() }, 1000);
+        }
+        let output = String::from_utf8(progress.into_inner()).unwrap();
+        assert_eq!(output.matches('\r').count(), 10);
+    }
+
+    #[test]
+    fn test_cross_validate_trains_one_model_per_fold() {
+        use neuralnet::loss_fn::Loss;
+        use neuralnet::model::ModelBuilder;
+        let rows: Vec<Vec<f64>> = (0..20).map(|i| vec![i as f64 / 10.0 - 1.0]).collect();
+        let targets: Vec<Vec<f64>> = rows.iter().map(|r| vec![2.0 * r[0] + 0.5]).collect();
+        let trainer = Trainer::new(Loss::MeanSquaredError, 0.05, 30).shuffle(1);
+
+        let mut built = Vec::new();
+        let results = cross_validate(
+            |fold| {
+                built.push(fold);
+                ModelBuilder::new(1).dense(1).seed(3).build::<f64>().unwrap()
+            },
+            &rows,
+            &targets,
+            4,
+            &trainer,
+            9,
+            |model, val_rows, val_targets| {
+                let max_error = val_rows.iter().zip(val_targets)
+                    .map(|(r, t)| (model.forward(r)[0] - t[0]).abs())
+                    .fold(0.0, f64::max);
+                vec![("max_error".to_string(), max_error)]
+            },
+        );
+
+        assert_eq!(built, vec![0, 1, 2, 3]);
+        assert_eq!(results.folds.len(), 4);
+        for fold in &results.folds {
+            assert_eq!((fold.train_size, fold.validation_size), (15, 5));
+            assert_eq!(fold.history.records.len(), 30);
+        }
+        assert_eq!(results.metric("validation_loss").len(), 4);
+        assert!(results.mean_validation_loss() < 0.01);
+        assert!(results.mean("max_error") < 0.2);
+        assert!(results.std("max_error") >= 0.0);
+        let summary = results.summary();
+        assert_eq!(summary.keys().collect::<Vec<_>>(), vec!["max_error", "validation_loss"]);
+        assert_eq!(summary["validation_loss"].0, results.mean_validation_loss());
+    }
+
+    #[test]
+    fn test_cross_validate_loss_uses_training_reduction() {
+        use neuralnet::dataset::k_fold_indices;
+        use neuralnet::loss_fn::{Loss, LossFn};
+        use neuralnet::model::ModelBuilder;
+        let rows: Vec<Vec<f64>> = (0..12).map(|i| vec![i as f64 / 6.0 - 1.0, (i % 3) as f64]).collect();
+        let targets: Vec<Vec<f64>> = (0..12).map(|i| (0..3).map(|c| if c == i % 3 { 1.0 } else { 0.0 }).collect()).collect();
+        let model = ModelBuilder::new(2).dense(3).softmax().seed(5).build::<f64>().unwrap();
+        // A zero learning rate keeps every fold's model equal to `model`
+        let trainer = Trainer::new(Loss::CrossEntropy, 0.0, 1);
+
+        let results = cross_validate(|_| model.clone(), &rows, &targets, 3, &trainer, 2, |_, _, _| Vec::new());
+        let objective = |indices: &[usize]| {
+            indices.iter().map(|&i| LossFn::objective(&Loss::CrossEntropy, &model.forward(&rows[i]), &targets[i])).sum::<f64>() / indices.len() as f64
+        };
+        for (fold, (train, validation)) in results.folds.iter().zip(k_fold_indices(rows.len(), 3, 2)) {
+            assert!((fold.metrics["validation_loss"] - objective(&validation)).abs() < 1e-12);
+            assert!((fold.history.records[0].train_loss - objective(&train)).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_resume_matches_uninterrupted_training() {
+        use neuralnet::loss_fn::Loss;
+        use neuralnet::model::ModelBuilder;
+        let rows: Vec<Vec<f64>> = (0..12).map(|i| vec![i as f64 / 6.0 - 1.0, (i % 3) as f64]).collect();
+        let targets: Vec<Vec<f64>> = rows.iter().map(|r| vec![r[0] - 0.5 * r[1]]).collect();
+        let fresh = || ModelBuilder::new(2).dense(3).tanh().dense(1).seed(4).build::<f64>().unwrap();
+        let trainer = Trainer::new(Loss::MeanSquaredError, 0.05, 10).shuffle(8);
+
+        let mut uninterrupted = fresh();
+        let full_history = trainer.fit(&mut uninterrupted, &rows, &targets);
+
+        // Stop after 4 of the 10 epochs, as if the process had been killed
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.ckpt");
+        let mut model = fresh();
+        let partial = Trainer { epochs: 4, ..trainer }.fit_checkpointed(&mut model, &rows, &targets, &path, 2).unwrap();
+        assert_eq!(partial.records.len(), 4);
+        let mut checkpoint = Checkpoint::<f64>::load(&path).unwrap();
+        assert_eq!(checkpoint.state.epoch, 4);
+        assert_eq!(checkpoint.model, model);
+        checkpoint.trainer.epochs = 10;
+        checkpoint.save(&path).unwrap();
+
+        let (resumed, history) = Trainer::resume::<f64, _>(&path, &rows, &targets, 3).unwrap();
+        assert_eq!(resumed, uninterrupted);
+        assert_eq!(history, full_history);
+        assert_eq!(Checkpoint::<f64>::load(&path).unwrap().state.epoch, 10);
+
+        let err = Trainer::resume::<f64, _>(&path, &rows[..5], &targets[..5], 1).unwrap_err();
+        assert!(err.to_string().contains("12 samples"));
+    }
+
+    #[test]
+    fn test_fit_multi_output_regression_with_array_targets() {
+        use neuralnet::loss_fn::Loss;
+        use neuralnet::metrics::regression_errors;
+        use neuralnet::model::ModelBuilder;
+
+        let rows: Vec<[f64; 2]> = (0..25).map(|i| [(i % 5) as f64 / 4.0, (i / 5) as f64 / 4.0]).collect();
+        let targets: Vec<[f64; 2]> = rows.iter().map(|r| [r[0] + r[1], 2.0 * r[0] - r[1]]).collect();
+        for loss in [Loss::MeanSquaredError, Loss::MeanAbsoluteError] {
+            let mut model = ModelBuilder::new(2).seed(4).dense(2).build::<f64>().unwrap();
+            let history = Trainer::new(loss, 0.05, 300).shuffle(1).fit(&mut model, &rows, &targets);
+            assert!(history.records.last().unwrap().train_loss < history.records[0].train_loss);
+
+            let inputs: Vec<Vec<f64>> = rows.iter().map(|r| r.to_vec()).collect();
+            let expected: Vec<Vec<f64>> = targets.iter().map(|t| t.to_vec()).collect();
+            let errors = regression_errors(&model.predict(&inputs), &expected);
+            assert_eq!(errors.len(), 2);
+            assert!(errors.iter().all(|e| e.mae < 0.05), "{:?}: {:?}", loss, errors);
+        }
+    }
+
+    #[test]
+    fn test_detect_anomaly_reports_the_offending_value() {
+        use neuralnet::loss_fn::Loss;
+        use neuralnet::model::ModelBuilder;
+        let model = ModelBuilder::new(2).seed(1).dense(3).tanh().dense(1).build::<f64>().unwrap();
+        let trainer = Trainer::new(Loss::MeanSquaredError, 0.1, 3).detect_anomaly(true);
+        let rows = vec![vec![0.5, 1.0], vec![f64::NAN, 0.0]];
+        let targets = vec![vec![1.0], vec![0.0]];
+
+        // The NaN input poisons the first dense layer's outputs
+        let mut poisoned = model.clone();
+        let error = Trainer { epochs: 1, ..trainer }
+            .fit_checkpointed(&mut poisoned, &rows, &targets, std::env::temp_dir().join("neuralnet_anomaly.json"), 1)
+            .unwrap_err();
+        let error = error.downcast_ref::<AnomalyError>().unwrap();
+        assert_eq!(*error, AnomalyError { epoch: 0, sample: 1, anomaly: Anomaly::Activation { layer: 0, index: 0 } });
+
+        // An infinite target gives an infinite loss; the model is not updated by that step
+        let mut model_copy = model.clone();
+        let message = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            trainer.fit(&mut model_copy, &[vec![0.5, 1.0]], &[vec![f64::INFINITY]])
+        })).unwrap_err();
+        assert_eq!(message.downcast_ref::<String>().unwrap(), "non-finite value in epoch 0, sample 0: loss");
+        assert_eq!(model_copy, model);
+
+        // Without the guard, training silently carries on
+        let mut unguarded = model.clone();
+        let history = Trainer::new(Loss::MeanSquaredError, 0.1, 2).fit(&mut unguarded, &rows, &targets);
+        assert!(history.train_losses().iter().all(|l| l.is_nan()));
+    }
+
+    #[test]
+    fn test_detect_anomaly_catches_overflowing_gradients() {
+        use neuralnet::loss_fn::Loss;
+        use neuralnet::model::{Dense, ModelLayer, Sequential};
+        // Forward values and the loss stay finite, but d loss / d w0 = w1 * x = 1e400 overflows
+        let mut model = Sequential::from_layers(1, vec![
+            ModelLayer::Dense(Dense::new(vec![vec![1e-200]], vec![0.0])),
+            ModelLayer::Dense(Dense::new(vec![vec![1e200]], vec![0.0])),
+        ]).unwrap();
+        let before = model.clone();
+        let trainer = Trainer::new(Loss::MeanAbsoluteError, 0.01, 1).detect_anomaly(true);
+        let message = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| trainer.fit(&mut model, &[vec![1e200]], &[vec![0.0]])))
+            .unwrap_err();
+        assert_eq!(message.downcast_ref::<String>().unwrap(), "non-finite value in epoch 0, sample 0: gradient of parameter 0 of layer 0");
+        assert_eq!(model, before);
+    }
+
+    #[test]
+    fn test_mini_batches_average_the_sample_gradients() {
+        use neuralnet::loss_fn::Loss;
+        use neuralnet::model::ModelBuilder;
+        let rows: Vec<Vec<f64>> = (0..10).map(|i| vec![i as f64 / 5.0 - 1.0, (i % 3) as f64]).collect();
+        let targets: Vec<Vec<f64>> = rows.iter().map(|r| vec![r[0] - 0.5 * r[1]]).collect();
+        let model = ModelBuilder::new(2).seed(6).dense(3).tanh().dense(1).build::<f64>().unwrap();
+
+        // A batch size of one is plain per-sample SGD
+        let mut per_sample = model.clone();
+        Trainer::new(Loss::MeanSquaredError, 0.1, 2).fit(&mut per_sample, &rows, &targets);
+        let mut reference = model.clone();
+        for _ in 0..2 {
+            reference.train_epoch(&rows, &targets, Loss::MeanSquaredError, 0.1);
+        }
+        assert_eq!(per_sample, reference);
+
+        // Batches of 4, 4 and 2: one step per batch with the mean gradient
+        let mut batched = model.clone();
+        let history = Trainer::new(Loss::MeanSquaredError, 0.1, 1).batch_size(4).fit(&mut batched, &rows, &targets);
+        let mut reference = model.clone();
+        let mut total = 0.0;
+        for batch in [0..4, 4..8, 8..10] {
+            reference.zero_grad();
+            let n = batch.len() as f64;
+            for i in batch {
+                total += reference.accumulate_gradients(&rows[i], &targets[i], Loss::MeanSquaredError);
+            }
+            reference.sgd_step(0.1 / n);
+        }
+        for (a, b) in batched.predict(&rows).iter().zip(reference.predict(&rows).iter()) {
+            assert!((a[0] - b[0]).abs() < 1e-12);
+        }
+        assert!((history.records[0].train_loss - total / 10.0).abs() < 1e-12);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_batch_size_panics() {
+        use neuralnet::loss_fn::Loss;
+        Trainer::new(Loss::MeanSquaredError, 0.1, 1).batch_size(0);
+    }
+
+    #[test]
+    fn test_fit_weighted_scales_each_sample() {
+        use neuralnet::loss_fn::Loss;
+        use neuralnet::model::ModelBuilder;
+        let rows: Vec<Vec<f64>> = (0..8).map(|i| vec![i as f64 / 4.0 - 1.0]).collect();
+        let targets: Vec<Vec<f64>> = rows.iter().map(|r| vec![r[0] + 1.0]).collect();
+        let model = ModelBuilder::new(1).seed(2).dense(1).build::<f64>().unwrap();
+        let trainer = Trainer::new(Loss::MeanSquaredError, 0.1, 3).shuffle(5);
+
+        let (mut plain, mut ones) = (model.clone(), model.clone());
+        assert_eq!(trainer.fit_weighted(&mut ones, &rows, &targets, &[1.0; 8]), trainer.fit(&mut plain, &rows, &targets));
+        assert_eq!(ones, plain);
+
+        // A zero-weight sample is ignored, a weight of two counts as a duplicate step
+        let mut weighted = model.clone();
+        let weights = [0.0, 2.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0];
+        let history = Trainer::new(Loss::MeanSquaredError, 0.1, 1).batch_size(8).fit_weighted(&mut weighted, &rows, &targets, &weights);
+        let mut reference = model.clone();
+        reference.zero_grad();
+        let mut total = 0.0;
+        for i in 1..8 {
+            let repeats = if i == 1 { 2 } else { 1 };
+            for _ in 0..repeats {
+                total += reference.accumulate_gradients(&rows[i], &targets[i], Loss::MeanSquaredError);
+            }
+        }
+        reference.sgd_step(0.1 / 8.0);
+        for (a, b) in weighted.predict(&rows).iter().zip(reference.predict(&rows).iter()) {
+            assert!((a[0] - b[0]).abs() < 1e-12);
+        }
+        assert!((history.records[0].train_loss - total / 8.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_last_batch_policies() {
+        use neuralnet::dataset::LastBatch;
+        use neuralnet::loss_fn::Loss;
+        use neuralnet::model::ModelBuilder;
+        let rows: Vec<Vec<f64>> = (0..10).map(|i| vec![i as f64 / 5.0 - 1.0]).collect();
+        let targets: Vec<Vec<f64>> = rows.iter().map(|r| vec![0.5 - r[0]]).collect();
+        let model = ModelBuilder::new(1).seed(4).dense(2).tanh().dense(1).build::<f64>().unwrap();
+        let trainer = Trainer::new(Loss::MeanSquaredError, 0.1, 2).batch_size(4).shuffle(9);
+
+        // Gradients are averaged over the true batch size, so padding changes nothing
+        let (mut smaller, mut padded) = (model.clone(), model.clone());
+        let expected = trainer.fit(&mut smaller, &rows, &targets);
+        assert_eq!(trainer.last_batch(LastBatch::Pad).fit(&mut padded, &rows, &targets), expected);
+        assert_eq!(smaller, padded);
+
+        // Dropping the last two samples equals training on the first eight without shuffling
+        let mut dropped = model.clone();
+        let history = Trainer::new(Loss::MeanSquaredError, 0.1, 1).batch_size(4).last_batch(LastBatch::Drop).fit(&mut dropped, &rows, &targets);
+        let mut reference = model;
+        let reference_history = Trainer::new(Loss::MeanSquaredError, 0.1, 1).batch_size(4).fit(&mut reference, &rows[..8], &targets[..8]);
+        assert_eq!(dropped, reference);
+        assert_eq!(history, reference_history);
+    }
+}
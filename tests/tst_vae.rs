@@ -0,0 +1,118 @@
+#![cfg(feature = "std")]
+
+use neuralnet::vae::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neuralnet::loss_fn::{Loss, Reduction};
+    use neuralnet::model::{ModelBuilder, ModelLayer};
+    use neuralnet::random::Rng;
+
+    fn small_vae(seed: u64) -> VAE<f64> {
+        let encoder = ModelBuilder::new(4).seed(seed).dense(6).tanh().build::<f64>().unwrap();
+        let decoder = ModelBuilder::new(2).seed(seed + 1).dense(6).tanh().dense(4).sigmoid().build::<f64>().unwrap();
+        VAE::new(encoder, decoder, &mut Rng::new(seed + 2))
+    }
+
+    #[test]
+    fn test_reparameterize_and_kl() {
+        assert_eq!(reparameterize(&[1.0, -1.0], &[0.0, 2.0f64.ln() * 2.0], &[0.5, 1.0]), vec![1.5, 1.0]);
+        // The prior itself has zero divergence
+        assert_eq!(kl_divergence_standard_normal(&[0.0, 0.0], &[0.0, 0.0]), 0.0);
+        assert!((kl_divergence_standard_normal(&[1.0], &[0.0f64]) - 0.5).abs() < 1e-12);
+
+        let (mean, log_variance) = ([0.3f64, -1.2], [0.5f64, -0.4]);
+        let (grad_mean, grad_log_variance) = kl_divergence_standard_normal_derivative(&mean, &log_variance);
+        let h = 1e-6;
+        for k in 0..2 {
+            let (mut plus, mut minus) = (mean, mean);
+            plus[k] += h;
+            minus[k] -= h;
+            let numeric = (kl_divergence_standard_normal(&plus, &log_variance) - kl_divergence_standard_normal(&minus, &log_variance)) / (2.0 * h);
+            assert!((numeric - grad_mean[k]).abs() < 1e-6);
+            let (mut plus, mut minus) = (log_variance, log_variance);
+            plus[k] += h;
+            minus[k] -= h;
+            let numeric = (kl_divergence_standard_normal(&mean, &plus) - kl_divergence_standard_normal(&mean, &minus)) / (2.0 * h);
+            assert!((numeric - grad_log_variance[k]).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_vae_gradients_match_finite_differences() {
+        let mut vae = small_vae(3);
+        vae.log_variance.weights[0][1] = 0.4;
+        let inputs = [0.9, 0.1, 0.2, 0.7];
+        let loss = VaeLoss::new(Loss::BinaryCrossEntropy).with_beta(0.5);
+
+        // Replays the noise of `Rng::new(7)` so every evaluation samples the same latent vector
+        let total = |vae: &VAE<f64>| {
+            let mut rng = Rng::new(7);
+            let noise: Vec<f64> = (0..vae.latent_dim()).map(|_| rng.next_normal()).collect();
+            let (mean, log_variance) = vae.encode(&inputs);
+            let outputs = vae.decode(&reparameterize(&mean, &log_variance, &noise));
+            let reconstruction = Loss::BinaryCrossEntropy.forward_reduced(&outputs, &inputs, Reduction::Sum).scalar().unwrap();
+            reconstruction + 0.5 * kl_divergence_standard_normal(&mean, &log_variance)
+        };
+
+        let mut trained = vae.clone();
+        trained.zero_grad();
+        let step = trained.accumulate_gradients(&inputs, loss, &mut Rng::new(7));
+        assert!((step.total - total(&vae)).abs() < 1e-12);
+        assert!((step.total - (step.reconstruction + 0.5 * step.kl)).abs() < 1e-12);
+
+        let h = 1e-6;
+        let numeric = |edit: &dyn Fn(&mut VAE<f64>, f64)| {
+            let (mut plus, mut minus) = (vae.clone(), vae.clone());
+            edit(&mut plus, h);
+            edit(&mut minus, -h);
+            (total(&plus) - total(&minus)) / (2.0 * h)
+        };
+        let checks = [
+            (trained.log_variance.grads().weights[0][1], numeric(&|v, d| v.log_variance.weights[0][1] += d)),
+            (trained.mean.grads().biases[1], numeric(&|v, d| v.mean.biases[1] += d)),
+        ];
+        for (analytic, numeric) in checks {
+            assert!((analytic - numeric).abs() < 1e-6, "{} vs {}", analytic, numeric);
+        }
+
+        // An encoder weight, whose gradient passes through both heads and the sampling step
+        let ModelLayer::Dense(encoder_dense) = &trained.encoder.layers[0] else { unreachable!() };
+        let analytic = encoder_dense.grads().weights[2][3];
+        let numeric = numeric(&|v, d| {
+            if let ModelLayer::Dense(dense) = &mut v.encoder.layers[0] {
+                dense.weights[2][3] += d;
+            }
+        });
+        assert!((analytic - numeric).abs() < 1e-6, "{} vs {}", analytic, numeric);
+    }
+
+    #[test]
+    fn test_vae_training_reduces_loss_and_generates() {
+        let mut vae = small_vae(5);
+        let rows = vec![vec![1.0, 1.0, 0.0, 0.0], vec![0.0, 0.0, 1.0, 1.0], vec![1.0, 0.0, 1.0, 0.0]];
+        let loss = VaeLoss::new(Loss::BinaryCrossEntropy);
+        let mut rng = Rng::new(1);
+
+        // Averaged over 20 epochs, since every step samples new noise
+        let mut window = |vae: &mut VAE<f64>| (0..20).map(|_| vae.train_epoch(&rows, loss, 0.1, &mut rng).total).sum::<f64>() / 20.0;
+        let first = window(&mut vae);
+        for _ in 0..20 {
+            window(&mut vae);
+        }
+        let last = window(&mut vae);
+        assert!(last < first, "{} -> {}", first, last);
+
+        let reconstruction = vae.reconstruct(&rows[0]);
+        assert!(reconstruction[0] > 0.5 && reconstruction[3] < 0.5, "{:?}", reconstruction);
+        let sample = vae.sample(&mut rng);
+        assert_eq!(sample.len(), 4);
+        assert!(sample.iter().all(|&p| (0.0..=1.0).contains(&p)));
+        assert_eq!(vae.forward(&rows[1], &mut rng).len(), 4);
+
+        let encoder = ModelBuilder::new(4).dense(3).build::<f64>().unwrap();
+        let decoder = ModelBuilder::new(2).dense(5).build::<f64>().unwrap();
+        assert!(VAE::try_new(encoder, decoder, &mut rng).is_err());
+    }
+}
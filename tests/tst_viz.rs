@@ -0,0 +1,58 @@
+#![cfg(feature = "viz")]
+
+use neuralnet::viz::*;
+use neuralnet::training::{EpochRecord, History};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points() -> (Vec<Vec<f64>>, Vec<usize>) {
+        let points: Vec<Vec<f64>> = (0..20).map(|i| vec![(i % 5) as f64, (i / 5) as f64]).collect();
+        let labels = points.iter().map(|p| usize::from(p[0] > p[1])).collect();
+        (points, labels)
+    }
+
+    #[test]
+    fn test_decision_boundary_svg_and_png() {
+        let dir = tempfile::tempdir().unwrap();
+        let (points, labels) = points();
+        let classify = |p: &[f64]| usize::from(p[0] > p[1]);
+
+        let svg = dir.path().join("boundary.svg");
+        plot_decision_boundary(classify, &points, &labels, 20, &svg).unwrap();
+        let text = std::fs::read_to_string(&svg).unwrap();
+        assert!(text.starts_with("<svg"));
+        assert!(text.contains("Decision boundary"));
+        assert!(text.matches("<circle").count() >= points.len());
+
+        let png = dir.path().join("boundary.png");
+        plot_decision_boundary(classify, &points, &labels, 20, &png).unwrap();
+        assert_eq!(&std::fs::read(&png).unwrap()[..4], b"\x89PNG");
+    }
+
+    #[test]
+    fn test_history_plot() {
+        let mut history = History::new();
+        for epoch in 0..5 {
+            let metrics = [("validation_loss".to_string(), 2.0 / (epoch + 1) as f64), ("accuracy".to_string(), 0.5)];
+            history.push(EpochRecord { epoch, train_loss: 1.0 / (epoch + 1) as f64, metrics: metrics.into_iter().collect() });
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let svg = dir.path().join("loss.svg");
+        plot_history(&history, &svg).unwrap();
+        let text = std::fs::read_to_string(&svg).unwrap();
+        assert!(text.contains("train_loss") && text.contains("validation_loss"));
+        assert!(!text.contains("accuracy"));
+
+        let png = dir.path().join("loss.png");
+        plot_loss_curves(&[("train", &history.train_losses())], &png).unwrap();
+        assert!(png.exists());
+    }
+
+    #[test]
+    fn test_unsupported_extension() {
+        let err = plot_loss_curves(&[("train", &[1.0, 0.5][..])], "loss.jpg").unwrap_err();
+        assert!(err.to_string().contains("unsupported plot format"));
+    }
+}